@@ -0,0 +1,120 @@
+//! Load, export, outline and search timings on the fixtures and on a
+//! synthetic document of about 1000 pages.
+//!
+//! Run with `cargo bench -p doxx-core`; `cargo bench -p doxx-core -- large`
+//! runs only the synthetic document.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use docx_rs::{Docx, Paragraph, Run, Table, TableCell, TableRow};
+use std::io::Cursor;
+use std::path::Path;
+
+use doxx_core::document::{generate_outline, search_document, Document};
+use doxx_core::export;
+
+const FIXTURES: &[&str] = &[
+    "business-report",
+    "tables-heavy",
+    "lists-comprehensive",
+    "unicode-special",
+];
+
+fn fixture(name: &str) -> Vec<u8> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../../tests/fixtures")
+        .join(format!("{name}.docx"));
+    std::fs::read(path).unwrap()
+}
+
+/// A report of `pages` pages: each one a heading, a few paragraphs, a
+/// small table and a page break
+fn synthetic_document(pages: usize) -> Vec<u8> {
+    let sentence = "The quarterly figures were reviewed by the committee, which \
+                    noted steady growth in every region and asked for a breakdown by product. ";
+    let mut docx = Docx::new();
+    for page in 1..=pages {
+        let style = if page % 10 == 1 {
+            "Heading1"
+        } else {
+            "Heading2"
+        };
+        docx = docx.add_paragraph(
+            Paragraph::new()
+                .style(style)
+                .add_run(Run::new().add_text(format!("Section {page}"))),
+        );
+        for _ in 0..4 {
+            docx = docx
+                .add_paragraph(Paragraph::new().add_run(Run::new().add_text(sentence.repeat(3))));
+        }
+        let rows = (0..4)
+            .map(|row| {
+                TableRow::new(
+                    (0..3)
+                        .map(|column| {
+                            TableCell::new().add_paragraph(
+                                Paragraph::new()
+                                    .add_run(Run::new().add_text(format!("{}", row * 3 + column))),
+                            )
+                        })
+                        .collect(),
+                )
+            })
+            .collect();
+        docx = docx.add_table(Table::new(rows)).add_paragraph(
+            Paragraph::new().add_run(Run::new().add_break(docx_rs::BreakType::Page)),
+        );
+    }
+
+    let mut data = Cursor::new(Vec::new());
+    docx.build().pack(&mut data).unwrap();
+    data.into_inner()
+}
+
+fn fixtures(c: &mut Criterion) {
+    for name in FIXTURES {
+        let data = fixture(name);
+        c.bench_function(&format!("load/{name}"), |b| {
+            b.iter(|| Document::from_bytes(black_box(&data)).unwrap())
+        });
+        let document = Document::from_bytes(&data).unwrap();
+        c.bench_function(&format!("markdown/{name}"), |b| {
+            b.iter(|| export::to_markdown(black_box(&document)))
+        });
+    }
+}
+
+fn large(c: &mut Criterion) {
+    let data = synthetic_document(1000);
+    let document = Document::from_bytes(&data).unwrap();
+
+    let mut group = c.benchmark_group("large");
+    group.sample_size(10);
+    group.bench_function("load", |b| {
+        b.iter(|| Document::from_bytes(black_box(&data)).unwrap())
+    });
+    // docx-rs alone, the part of loading doxx doesn't control
+    group.bench_function("read_docx", |b| {
+        b.iter(|| docx_rs::read_docx(black_box(&data)).unwrap())
+    });
+    group.bench_function("markdown", |b| {
+        b.iter(|| export::to_markdown(black_box(&document)))
+    });
+    group.bench_function("text", |b| {
+        b.iter(|| export::to_text(black_box(&document), Some(80)))
+    });
+    group.bench_function("csv", |b| b.iter(|| export::to_csv(black_box(&document))));
+    group.bench_function("json", |b| {
+        b.iter(|| export::to_json(black_box(&document)).unwrap())
+    });
+    group.bench_function("outline", |b| {
+        b.iter(|| generate_outline(black_box(&document)))
+    });
+    group.bench_function("search", |b| {
+        b.iter(|| search_document(black_box(&document), "breakdown by product"))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, fixtures, large);
+criterion_main!(benches);
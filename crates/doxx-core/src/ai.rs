@@ -0,0 +1,939 @@
+//! Summaries and answers by a language model.
+//!
+//! `doxx summarize` sends a document to a model served by Ollama, or by any
+//! server with an OpenAI-style chat API (llama.cpp, LM Studio, vLLM), and
+//! prints the answer as it streams in. The settings live under `ai.` in the
+//! config. A document longer than the model reads at once is split into
+//! parts, each part is summarized, and the summaries are summarized again.
+//!
+//! `doxx ask` answers a question from the parts of the document that share
+//! the most words with it: the document is cut into chunks of consecutive
+//! elements, the chunks are scored against the question with BM25, and the
+//! best ones that fit the context are sent along with it. Each element is
+//! numbered so the answer can cite where it comes from.
+//!
+//! With `ai.privacy_mode` on, as it is by default, documents are only sent
+//! to a server on this machine. Turned off, an `https://` API elsewhere
+//! can be used too, with `ai.api_key` sent as a bearer token.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader, Read};
+use std::net::IpAddr;
+use std::ops::Range;
+use std::time::Duration;
+
+use crate::config::{AiConfig, AiProvider};
+use crate::document::{Document, DocumentElement};
+use crate::error::{DoxxError, Result};
+use crate::export::format_range_as_text;
+use crate::text_fold::{detect_language, fold};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Time to wait for a server to start answering; a large model on a CPU is
+/// slow to start
+const READ_TIMEOUT: Duration = Duration::from_secs(300);
+/// Characters in a token, roughly, for the text of most documents
+const CHARS_PER_TOKEN: usize = 4;
+/// Smallest part a document is split into, however small the context
+const MIN_PART_CHARS: usize = 1000;
+/// Most characters in a chunk scored against a question
+const CHUNK_CHARS: usize = 1500;
+/// BM25 term frequency saturation and length normalization
+const BM25_K1: f64 = 1.2;
+const BM25_B: f64 = 0.75;
+/// Words too common to say which part of a document answers a question
+const STOP_WORDS: &[&str] = &[
+    "the", "and", "for", "are", "was", "were", "what", "which", "who", "whom", "when", "where",
+    "why", "how", "does", "did", "has", "have", "had", "this", "that", "these", "those", "with",
+    "from", "about", "into", "there", "their", "they", "them", "its", "can", "could", "would",
+    "should", "will", "shall", "any", "all", "not", "but", "our", "your", "you", "his", "her",
+];
+
+const SUMMARY_PROMPT: &str = "You summarize documents. Write a concise summary of the \
+    document you are given, in the language it is written in. Keep the names, figures, \
+    dates and decisions that matter. Answer with the summary alone.";
+const PART_PROMPT: &str = "You summarize one part of a longer document. Write a concise \
+    summary of the part you are given, in the language it is written in. Keep the names, \
+    figures, dates and decisions that matter. Answer with the summary alone.";
+const ANSWER_PROMPT: &str = "You answer questions about a document from the excerpts of it \
+    you are given. Each element of an excerpt starts with its number in brackets, like [12]. \
+    Answer in the language of the question, using only what the excerpts say, and cite the \
+    elements the answer comes from by their numbers in brackets. If the excerpts don't \
+    answer the question, say so.";
+
+/// One message of a chat with the model
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Message {
+    pub role: &'static str,
+    pub content: String,
+}
+
+impl Message {
+    /// Instructions for the model
+    pub fn system(content: impl Into<String>) -> Self {
+        Self {
+            role: "system",
+            content: content.into(),
+        }
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self {
+            role: "user",
+            content: content.into(),
+        }
+    }
+}
+
+/// Where a server listens, from an `http://host:port/path` or `https://`
+/// URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoint {
+    /// Reached over `https://`
+    pub https: bool,
+    pub host: String,
+    pub port: u16,
+    /// Path the API paths are appended to, without a trailing `/`
+    pub base_path: String,
+}
+
+impl Endpoint {
+    pub fn parse(url: &str) -> Result<Self> {
+        let url = url.trim();
+        let (https, rest) = match url.split_once("://") {
+            Some((scheme, rest)) if scheme.eq_ignore_ascii_case("http") => (false, rest),
+            Some((scheme, rest)) if scheme.eq_ignore_ascii_case("https") => (true, rest),
+            Some((scheme, _)) => return Err(invalid(url, format!("unsupported scheme {scheme}"))),
+            None => (false, url),
+        };
+        let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+        let (host, port) = match authority.strip_prefix('[') {
+            // An IPv6 address, e.g. [::1]:11434
+            Some(bracketed) => {
+                let (host, after) = bracketed
+                    .split_once(']')
+                    .ok_or_else(|| invalid(url, "missing ]"))?;
+                (host, after.strip_prefix(':'))
+            }
+            None => match authority.split_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (authority, None),
+            },
+        };
+        if host.is_empty() {
+            return Err(invalid(url, "no host"));
+        }
+        let port = match port {
+            Some(port) => port
+                .parse()
+                .map_err(|_| invalid(url, format!("invalid port {port}")))?,
+            None if https => 443,
+            None => 80,
+        };
+        Ok(Self {
+            https,
+            host: host.to_string(),
+            port,
+            base_path: path.trim_end_matches('/').to_string(),
+        })
+    }
+
+    /// Whether the server is on this machine: `localhost` or a loopback
+    /// address. Other names aren't looked up.
+    pub fn is_local(&self) -> bool {
+        self.host.eq_ignore_ascii_case("localhost")
+            || self
+                .host
+                .parse::<IpAddr>()
+                .is_ok_and(|address| address.is_loopback())
+    }
+
+    /// The URL of `path` under the endpoint
+    fn url(&self, path: &str) -> String {
+        let scheme = if self.https { "https" } else { "http" };
+        let host = if self.host.contains(':') {
+            format!("[{}]", self.host)
+        } else {
+            self.host.clone()
+        };
+        format!("{scheme}://{host}:{}{}{path}", self.port, self.base_path)
+    }
+}
+
+/// `ai.endpoint` is set to something that isn't a URL
+fn invalid(url: &str, problem: impl std::fmt::Display) -> DoxxError {
+    DoxxError::Config(format!("Invalid ai.endpoint {url}: {problem}"))
+}
+
+/// A connection to the model configured under `ai.`
+#[derive(Debug, Clone)]
+pub struct AiClient {
+    provider: AiProvider,
+    url: String,
+    endpoint: Endpoint,
+    api_key: String,
+    model: String,
+    context_tokens: usize,
+    agent: ureq::Agent,
+}
+
+impl AiClient {
+    /// Fails when the endpoint is invalid, or isn't on this machine while
+    /// `ai.privacy_mode` is on
+    pub fn new(config: &AiConfig) -> Result<Self> {
+        let endpoint = Endpoint::parse(&config.endpoint)?;
+        if config.privacy_mode && !endpoint.is_local() {
+            return Err(DoxxError::Config(format!(
+                "ai.privacy_mode is on and {} is not on this machine; \
+                 to send documents there, run `doxx set ai.privacy_mode false`",
+                config.endpoint
+            )));
+        }
+        Ok(Self {
+            provider: config.provider,
+            url: config.endpoint.trim().trim_end_matches('/').to_string(),
+            endpoint,
+            api_key: config.api_key.trim().to_string(),
+            model: config.model.clone(),
+            context_tokens: config.context_tokens,
+            agent: ureq::Agent::config_builder()
+                .timeout_connect(Some(CONNECT_TIMEOUT))
+                .timeout_recv_response(Some(READ_TIMEOUT))
+                // Failed requests are read for the server's error message
+                .http_status_as_error(false)
+                .build()
+                .into(),
+        })
+    }
+
+    /// Send `messages` and return the model's answer, passing each piece of
+    /// it to `on_text` as it arrives
+    pub fn chat(&self, messages: &[Message], mut on_text: impl FnMut(&str)) -> Result<String> {
+        let path = match self.provider {
+            AiProvider::Ollama => "/api/chat",
+            AiProvider::OpenAi => "/v1/chat/completions",
+        };
+        let body = serde_json::json!({
+            "model": self.model,
+            "messages": messages,
+            "stream": true,
+        });
+        let (status, mut reply) = self.post(path, &body.to_string())?;
+        if status != 200 {
+            let mut text = String::new();
+            reply.read_to_string(&mut text)?;
+            return Err(DoxxError::Model(format!(
+                "{}{path} answered {status}: {}",
+                self.url,
+                error_message(&text)
+            )));
+        }
+
+        let mut answer = String::new();
+        for line in BufReader::new(reply).lines() {
+            let line = line.map_err(|e| {
+                DoxxError::Model(format!("Reading the answer from {}: {e}", self.url))
+            })?;
+            if let Some(piece) = stream_piece(self.provider, &line)? {
+                on_text(&piece);
+                answer.push_str(&piece);
+            }
+        }
+        Ok(answer)
+    }
+
+    /// Summary of `text`, passed to `on_text` as the model writes it. Text
+    /// too long for the model is first summarized in parts; `on_part` is
+    /// told which part (counting from 1) of how many is being read.
+    pub fn summarize(
+        &self,
+        text: &str,
+        mut on_part: impl FnMut(usize, usize),
+        on_text: impl FnMut(&str),
+    ) -> Result<String> {
+        let mut text = text.trim().to_string();
+        if text.is_empty() {
+            return Err(DoxxError::InvalidInput(
+                "There is no text to summarize".to_string(),
+            ));
+        }
+        loop {
+            let parts = split_text(&text, self.part_chars());
+            if parts.len() <= 1 {
+                break;
+            }
+            let mut summaries = Vec::new();
+            for (index, part) in parts.iter().enumerate() {
+                on_part(index + 1, parts.len());
+                let summary = self.chat(
+                    &[Message::system(PART_PROMPT), Message::user(part.as_str())],
+                    |_| {},
+                )?;
+                summaries.push(summary.trim().to_string());
+            }
+            let combined = summaries.join("\n\n");
+            // Another round would never end
+            if combined.chars().count() >= text.chars().count() {
+                return Err(DoxxError::Model(
+                    "The summaries of the parts are as long as the text; \
+                     raise ai.context_tokens if the model reads more at once"
+                        .to_string(),
+                ));
+            }
+            text = combined;
+        }
+        self.chat(
+            &[Message::system(SUMMARY_PROMPT), Message::user(text)],
+            on_text,
+        )
+    }
+
+    /// Answer `question` about `document`, passing the answer to `on_text`
+    /// as the model writes it. The chunks of the document most alike the
+    /// question are sent with it, as much as half the context holds.
+    pub fn answer_question(
+        &self,
+        document: &Document,
+        question: &str,
+        on_text: impl FnMut(&str),
+    ) -> Result<Answer> {
+        let question = question.trim();
+        if question.is_empty() {
+            return Err(DoxxError::InvalidInput(
+                "There is no question to answer".to_string(),
+            ));
+        }
+        let chunks = chunk_document(document, CHUNK_CHARS.min(self.part_chars()));
+        if chunks.is_empty() {
+            return Err(DoxxError::InvalidInput(
+                "There is no text to answer from".to_string(),
+            ));
+        }
+
+        let mut chosen = Vec::new();
+        let mut chars = 0;
+        for index in rank_chunks(&chunks, question) {
+            let length = chunks[index].text.chars().count() + 2;
+            if chars + length > self.part_chars() {
+                continue;
+            }
+            chars += length;
+            chosen.push(index);
+        }
+        // In document order, so the excerpts read as the document does
+        chosen.sort_unstable();
+        let excerpts: Vec<&str> = chosen
+            .iter()
+            .map(|index| chunks[*index].text.as_str())
+            .collect();
+        let prompt = format!(
+            "Excerpts:\n\n{}\n\nQuestion: {question}",
+            excerpts.join("\n\n")
+        );
+
+        let text = self.chat(
+            &[Message::system(ANSWER_PROMPT), Message::user(prompt)],
+            on_text,
+        )?;
+        let sent: Vec<&Range<usize>> = chosen
+            .iter()
+            .map(|index| &chunks[*index].elements)
+            .collect();
+        let citations = cited_elements(&text)
+            .into_iter()
+            .filter(|element| sent.iter().any(|range| range.contains(element)))
+            .collect();
+        Ok(Answer { text, citations })
+    }
+
+    /// Characters of text sent at once: half the context, leaving the rest
+    /// for the instructions and the answer
+    fn part_chars(&self) -> usize {
+        (self.context_tokens * CHARS_PER_TOKEN / 2).max(MIN_PART_CHARS)
+    }
+
+    /// Send a JSON `body` to `path` under the endpoint, returning the status
+    /// and the body of the response
+    fn post(&self, path: &str, body: &str) -> Result<(u16, Box<dyn Read>)> {
+        let mut request = self
+            .agent
+            .post(self.endpoint.url(path))
+            .header("Content-Type", "application/json");
+        if !self.api_key.is_empty() {
+            request = request.header("Authorization", format!("Bearer {}", self.api_key));
+        }
+        let response = request.send(body).map_err(|e| {
+            let hint = match self.provider {
+                AiProvider::Ollama => " (is `ollama serve` running?)",
+                AiProvider::OpenAi => "",
+            };
+            DoxxError::Model(format!("Couldn't connect to {}{hint}: {e}", self.url))
+        })?;
+        let status = response.status().as_u16();
+        Ok((status, Box::new(response.into_body().into_reader())))
+    }
+}
+
+/// A model's answer to a question about a document
+#[derive(Debug, Clone, PartialEq)]
+pub struct Answer {
+    pub text: String,
+    /// Elements the answer cites, as indexes into the document's elements,
+    /// in the order they are first cited
+    pub citations: Vec<usize>,
+}
+
+/// Consecutive elements of a document, scored against a question as one
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chunk {
+    pub elements: Range<usize>,
+    /// Text of the elements, each starting with its number (the index plus
+    /// one) in brackets
+    pub text: String,
+}
+
+/// Cut the text of `document` into chunks of about `max_chars` characters.
+/// Each heading starts a chunk, and an element longer than a chunk is cut
+/// short.
+pub fn chunk_document(document: &Document, max_chars: usize) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+    let mut chunk: Option<Chunk> = None;
+    let mut chunk_chars = 0;
+    for (index, element) in document.elements.iter().enumerate() {
+        let text = format_range_as_text(document, index..index + 1);
+        let text = text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        let piece: String = format!("[{}] {text}", index + 1)
+            .chars()
+            .take(max_chars)
+            .collect();
+        let chars = piece.chars().count();
+        let is_heading = matches!(element, DocumentElement::Heading { .. });
+        if let Some(open) = chunk.take_if(|_| is_heading || chunk_chars + 2 + chars > max_chars) {
+            chunks.push(open);
+        }
+        match &mut chunk {
+            Some(open) => {
+                open.text.push_str("\n\n");
+                open.text.push_str(&piece);
+                open.elements.end = index + 1;
+                chunk_chars += 2 + chars;
+            }
+            None => {
+                chunk = Some(Chunk {
+                    elements: index..index + 1,
+                    text: piece,
+                });
+                chunk_chars = chars;
+            }
+        }
+    }
+    chunks.extend(chunk);
+    chunks
+}
+
+/// Indexes of `chunks`, the ones sharing the most telling words with
+/// `question` (by BM25) first and the rest in document order
+pub fn rank_chunks(chunks: &[Chunk], question: &str) -> Vec<usize> {
+    let query: HashSet<String> = keywords(question).into_iter().collect();
+    let chunk_words: Vec<Vec<String>> = chunks
+        .iter()
+        .map(|chunk| keywords(&without_numbers(&chunk.text)))
+        .collect();
+    let average_length =
+        chunk_words.iter().map(Vec::len).sum::<usize>() as f64 / chunk_words.len().max(1) as f64;
+
+    let mut with_word: HashMap<&str, usize> = HashMap::new();
+    for words in &chunk_words {
+        let distinct: HashSet<&str> = words.iter().map(String::as_str).collect();
+        for word in distinct.into_iter().filter(|word| query.contains(*word)) {
+            *with_word.entry(word).or_default() += 1;
+        }
+    }
+
+    let total = chunks.len() as f64;
+    let scores: Vec<f64> = chunk_words
+        .iter()
+        .map(|words| {
+            let length_norm = 1.0 - BM25_B + BM25_B * words.len() as f64 / average_length.max(1.0);
+            query
+                .iter()
+                .map(|word| {
+                    let frequency = words.iter().filter(|w| *w == word).count() as f64;
+                    if frequency == 0.0 {
+                        return 0.0;
+                    }
+                    let chunks_with = with_word.get(word.as_str()).copied().unwrap_or(0) as f64;
+                    let idf = (1.0 + (total - chunks_with + 0.5) / (chunks_with + 0.5)).ln();
+                    idf * frequency * (BM25_K1 + 1.0) / (frequency + BM25_K1 * length_norm)
+                })
+                .sum()
+        })
+        .collect();
+
+    let mut order: Vec<usize> = (0..chunks.len()).collect();
+    order.sort_by(|a, b| scores[*b].total_cmp(&scores[*a]));
+    order
+}
+
+/// Chunk text without the element numbers starting its paragraphs
+fn without_numbers(text: &str) -> String {
+    text.split("\n\n")
+        .map(|piece| {
+            piece
+                .strip_prefix('[')
+                .and_then(|rest| rest.split_once("] "))
+                .filter(|(number, _)| number.chars().all(|c| c.is_ascii_digit()))
+                .map_or(piece, |(_, rest)| rest)
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Folded words of `text` that say what it is about: no short words or
+/// common ones, and plurals made singular
+fn keywords(text: &str) -> Vec<String> {
+    fold(text, detect_language(text))
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() > 2 && !STOP_WORDS.contains(word))
+        .map(|word| match word.strip_suffix('s') {
+            Some(stem) if stem.chars().count() > 3 && !stem.ends_with('s') => stem.to_string(),
+            _ => word.to_string(),
+        })
+        .collect()
+}
+
+/// Elements cited in an answer as `[12]`, `[3, 5]` or `[3][5]`, as indexes
+/// into the document's elements, in the order first cited
+pub fn cited_elements(answer: &str) -> Vec<usize> {
+    let mut cited = Vec::new();
+    for (start, _) in answer.match_indices('[') {
+        let Some(end) = answer[start..].find(']') else {
+            break;
+        };
+        let numbers: Option<Vec<usize>> = answer[start + 1..start + end]
+            .split([',', ';'])
+            .map(|number| number.trim().parse().ok().filter(|n| *n > 0))
+            .collect();
+        for number in numbers.into_iter().flatten() {
+            if !cited.contains(&(number - 1)) {
+                cited.push(number - 1);
+            }
+        }
+    }
+    cited
+}
+
+/// Text in one line of a streamed answer: a JSON object per line from
+/// Ollama, or `data: {...}` server-sent events from an OpenAI-style server
+fn stream_piece(provider: AiProvider, line: &str) -> Result<Option<String>> {
+    let line = line.trim();
+    let json = match provider {
+        AiProvider::Ollama => line,
+        AiProvider::OpenAi => match line.strip_prefix("data:") {
+            Some(data) => data.trim(),
+            None => return Ok(None),
+        },
+    };
+    if json.is_empty() || json == "[DONE]" {
+        return Ok(None);
+    }
+    let value: Value = serde_json::from_str(json)
+        .map_err(|_| DoxxError::Model(format!("Unexpected answer: {json}")))?;
+    if let Some(error) = value.get("error") {
+        return Err(DoxxError::Model(error_text(error)));
+    }
+    let content = match provider {
+        AiProvider::Ollama => value.pointer("/message/content"),
+        AiProvider::OpenAi => value.pointer("/choices/0/delta/content"),
+    };
+    Ok(content
+        .and_then(Value::as_str)
+        .filter(|content| !content.is_empty())
+        .map(str::to_string))
+}
+
+/// The message in the body of a failed response: `{"error": "..."}` from
+/// Ollama, `{"error": {"message": "..."}}` from OpenAI-style servers
+fn error_message(body: &str) -> String {
+    match serde_json::from_str::<Value>(body) {
+        Ok(value) if value.get("error").is_some() => error_text(&value["error"]),
+        _ => body.trim().to_string(),
+    }
+}
+
+fn error_text(error: &Value) -> String {
+    match error {
+        Value::String(message) => message.clone(),
+        _ => error
+            .get("message")
+            .and_then(Value::as_str)
+            .map_or_else(|| error.to_string(), str::to_string),
+    }
+}
+
+/// `text` in parts of at most `max_chars` characters, split between
+/// paragraphs where possible, then between words
+pub fn split_text(text: &str, max_chars: usize) -> Vec<String> {
+    let max_chars = max_chars.max(1);
+    let mut parts = Vec::new();
+    let mut part = String::new();
+    let mut part_chars = 0;
+    let paragraphs = text.split("\n\n").map(str::trim).filter(|p| !p.is_empty());
+    for piece in paragraphs.flat_map(|paragraph| split_paragraph(paragraph, max_chars)) {
+        let chars = piece.chars().count();
+        if part_chars > 0 && part_chars + 2 + chars > max_chars {
+            parts.push(std::mem::take(&mut part));
+            part_chars = 0;
+        }
+        if part_chars > 0 {
+            part.push_str("\n\n");
+            part_chars += 2;
+        }
+        part.push_str(&piece);
+        part_chars += chars;
+    }
+    if !part.is_empty() {
+        parts.push(part);
+    }
+    parts
+}
+
+/// `paragraph` in pieces of at most `max_chars` characters, split between
+/// words, and words longer than that cut
+fn split_paragraph(paragraph: &str, max_chars: usize) -> Vec<String> {
+    if paragraph.chars().count() <= max_chars {
+        return vec![paragraph.to_string()];
+    }
+    let mut pieces = Vec::new();
+    let mut piece = String::new();
+    let mut piece_chars = 0;
+    for word in paragraph.split_whitespace() {
+        let word: Vec<char> = word.chars().collect();
+        for fragment in word.chunks(max_chars) {
+            if piece_chars > 0 && piece_chars + 1 + fragment.len() > max_chars {
+                pieces.push(std::mem::take(&mut piece));
+                piece_chars = 0;
+            }
+            if piece_chars > 0 {
+                piece.push(' ');
+                piece_chars += 1;
+            }
+            piece.extend(fragment);
+            piece_chars += fragment.len();
+        }
+    }
+    if !piece.is_empty() {
+        pieces.push(piece);
+    }
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    fn config(endpoint: &str) -> AiConfig {
+        AiConfig {
+            endpoint: endpoint.to_string(),
+            ..AiConfig::default()
+        }
+    }
+
+    /// A server on a free local port that answers `requests` chats the way
+    /// Ollama streams them, with "Summary N." for the Nth, and returns the
+    /// head (lowercased) and body of each request it read
+    fn fake_ollama(requests: usize) -> (String, thread::JoinHandle<Vec<(String, Value)>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            let mut bodies = Vec::new();
+            for n in 1..=requests {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut length = 0;
+                let mut head = String::new();
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    let lowercase = line.to_ascii_lowercase();
+                    if let Some(value) = lowercase.strip_prefix("content-length:") {
+                        length = value.trim().parse().unwrap();
+                    }
+                    head.push_str(&lowercase);
+                    line.clear();
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                bodies.push((head, serde_json::from_slice(&body).unwrap()));
+
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/x-ndjson\r\n\
+                     Transfer-Encoding: chunked\r\n\r\n"
+                )
+                .unwrap();
+                let lines = [
+                    r#"{"message":{"role":"assistant","content":"Summary "},"done":false}"#
+                        .to_string(),
+                    format!(
+                        r#"{{"message":{{"role":"assistant","content":"{n}."}},"done":false}}"#
+                    ),
+                    r#"{"message":{"role":"assistant","content":""},"done":true}"#.to_string(),
+                ];
+                for line in lines {
+                    write!(stream, "{:x}\r\n{line}\n\r\n", line.len() + 1).unwrap();
+                }
+                write!(stream, "0\r\n\r\n").unwrap();
+            }
+            bodies
+        });
+        (url, server)
+    }
+
+    #[test]
+    fn test_endpoint() {
+        let endpoint = Endpoint::parse("http://localhost:11434/").unwrap();
+        assert_eq!(
+            endpoint,
+            Endpoint {
+                https: false,
+                host: "localhost".to_string(),
+                port: 11434,
+                base_path: String::new(),
+            }
+        );
+        assert!(endpoint.is_local());
+
+        let endpoint = Endpoint::parse("http://[::1]:8080/llm/").unwrap();
+        assert_eq!((endpoint.host.as_str(), endpoint.port), ("::1", 8080));
+        assert_eq!(endpoint.base_path, "/llm");
+        assert!(endpoint.is_local());
+
+        let endpoint = Endpoint::parse("models.example.com").unwrap();
+        assert_eq!(endpoint.port, 80);
+        assert!(!endpoint.is_local());
+        let endpoint = Endpoint::parse("https://api.example.com/v2").unwrap();
+        assert_eq!(
+            endpoint.url("/v1/chat/completions"),
+            "https://api.example.com:443/v2/v1/chat/completions"
+        );
+        assert_eq!(
+            Endpoint::parse("http://[::1]:8080")
+                .unwrap()
+                .url("/api/chat"),
+            "http://[::1]:8080/api/chat"
+        );
+        assert!(Endpoint::parse("ftp://localhost").is_err());
+        assert!(Endpoint::parse("http://localhost:port").is_err());
+
+        // Privacy mode keeps documents on this machine
+        assert!(AiClient::new(&config("http://127.0.0.1:11434")).is_ok());
+        let remote = config("http://models.example.com");
+        assert!(AiClient::new(&remote).is_err());
+        let remote = AiConfig {
+            privacy_mode: false,
+            ..remote
+        };
+        assert!(AiClient::new(&remote).is_ok());
+    }
+
+    #[test]
+    fn test_split_text() {
+        let text = "First paragraph.\n\nSecond one.\n\n\n\nThird paragraph here.";
+        assert_eq!(split_text(text, 100), [text.replace("\n\n\n\n", "\n\n")]);
+        assert_eq!(
+            split_text(text, 30),
+            ["First paragraph.\n\nSecond one.", "Third paragraph here."]
+        );
+        // Paragraphs too long are split between words, and words too long cut
+        assert_eq!(
+            split_text("one two three four", 9),
+            ["one two", "three", "four"]
+        );
+        assert_eq!(split_text("abcdefghij", 4), ["abcd", "efgh", "ij"]);
+        assert!(split_text(" \n\n ", 10).is_empty());
+    }
+
+    #[test]
+    fn test_read_stream() {
+        let events = "data: {\"choices\":[{\"delta\":{\"content\":\"Hi\"}}]}\n\n\
+            : keep-alive\n\ndata: [DONE]\n\n";
+        let pieces: Vec<_> = Cursor::new(events)
+            .lines()
+            .filter_map(|line| stream_piece(AiProvider::OpenAi, &line.unwrap()).unwrap())
+            .collect();
+        assert_eq!(pieces, ["Hi"]);
+
+        let error = stream_piece(AiProvider::Ollama, r#"{"error":"model not found"}"#);
+        assert_eq!(error.unwrap_err().to_string(), "model not found");
+        assert_eq!(
+            error_message(r#"{"error":{"message":"invalid model","type":"invalid_request"}}"#),
+            "invalid model"
+        );
+    }
+
+    #[test]
+    fn test_summarize_in_parts() {
+        let (url, server) = fake_ollama(4);
+        let client = AiClient::new(&AiConfig {
+            endpoint: url,
+            model: "tiny".to_string(),
+            context_tokens: 100,
+            ..AiConfig::default()
+        })
+        .unwrap();
+
+        // Three paragraphs, each more than half of a 1000 character part
+        let paragraph = "word ".repeat(120);
+        let text = [paragraph.as_str(); 3].join("\n\n");
+        let mut parts = Vec::new();
+        let mut streamed = Vec::new();
+        let summary = client
+            .summarize(
+                &text,
+                |part, of| parts.push((part, of)),
+                |piece| streamed.push(piece.to_string()),
+            )
+            .unwrap();
+        assert_eq!(parts, [(1, 3), (2, 3), (3, 3)]);
+        assert_eq!(summary, "Summary 4.");
+        assert_eq!(streamed, ["Summary ", "4."]);
+
+        let requests = server.join().unwrap();
+        assert!(!requests[0].0.contains("authorization:"));
+        let bodies: Vec<&Value> = requests.iter().map(|(_, body)| body).collect();
+        assert_eq!(bodies[0]["model"], "tiny");
+        assert_eq!(bodies[0]["stream"], true);
+        assert_eq!(bodies[0]["messages"][1]["content"], paragraph.trim());
+        // The last request summarizes the summaries of the parts
+        assert_eq!(
+            bodies[3]["messages"][1]["content"],
+            "Summary 1.\n\nSummary 2.\n\nSummary 3."
+        );
+    }
+
+    fn document(elements: &[(Option<u8>, &str)]) -> Document {
+        Document {
+            elements: elements
+                .iter()
+                .map(|(level, text)| match level {
+                    Some(level) => DocumentElement::Heading {
+                        level: *level,
+                        text: text.to_string(),
+                        number: None,
+                        style: None,
+                        source_level: None,
+                    },
+                    None => DocumentElement::Paragraph {
+                        text: text.to_string(),
+                        formatting: Default::default(),
+                        runs: Vec::new(),
+                        style: None,
+                    },
+                })
+                .collect(),
+            ..Document::default()
+        }
+    }
+
+    fn contract() -> Document {
+        document(&[
+            (Some(1), "Scope"),
+            (
+                None,
+                "The supplier delivers the software described in annex A.",
+            ),
+            (Some(1), "Payment"),
+            (None, "Invoices are paid within 30 days of receipt."),
+            (None, "Late payments carry interest of 2% per month."),
+            (Some(1), "Termination"),
+            (
+                None,
+                "Either party may end the agreement with 90 days notice.",
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_chunk_and_rank() {
+        let chunks = chunk_document(&contract(), 1000);
+        let ranges: Vec<_> = chunks.iter().map(|chunk| chunk.elements.clone()).collect();
+        assert_eq!(ranges, [0..2, 2..5, 5..7]);
+        assert!(chunks[1].text.starts_with("[3] Payment"));
+        assert!(chunks[1]
+            .text
+            .ends_with("[5] Late payments carry interest of 2% per month."));
+
+        assert_eq!(
+            rank_chunks(&chunks, "What are the payment terms?"),
+            [1, 0, 2]
+        );
+        assert_eq!(
+            rank_chunks(&chunks, "How do we END the agreement?"),
+            [2, 0, 1]
+        );
+        // Nothing in common: document order
+        assert_eq!(rank_chunks(&chunks, "Who signed it?"), [0, 1, 2]);
+
+        // Too small for two elements
+        let chunks = chunk_document(&contract(), 50);
+        assert_eq!(chunks[1].elements, 1..2);
+        assert_eq!(chunks[1].text.chars().count(), 50);
+    }
+
+    #[test]
+    fn test_cited_elements() {
+        assert_eq!(
+            cited_elements("Within 30 days [4], with interest [5][4]. See [2, 6]."),
+            [3, 4, 1, 5]
+        );
+        assert!(cited_elements("Arrays [i] and [0] aren't citations [").is_empty());
+    }
+
+    #[test]
+    fn test_answer_question() {
+        let (url, server) = fake_ollama(1);
+        // 1000 characters of excerpts: the payment section, then what still fits
+        let client = AiClient::new(&AiConfig {
+            endpoint: url,
+            api_key: "sk-local".to_string(),
+            context_tokens: 500,
+            ..AiConfig::default()
+        })
+        .unwrap();
+        let mut contract = contract();
+        let scope = "The software covers billing. ".repeat(30);
+        contract.elements[1] = document(&[(None, scope.as_str())]).elements.remove(0);
+        let answer = client
+            .answer_question(&contract, " When are invoices paid? ", |_| {})
+            .unwrap();
+        assert_eq!(
+            answer,
+            Answer {
+                text: "Summary 1.".to_string(),
+                citations: Vec::new(),
+            }
+        );
+
+        let requests = server.join().unwrap();
+        let (head, body) = &requests[0];
+        assert!(head.starts_with("post /api/chat http/1.1\r\n"));
+        assert!(head.contains("authorization: bearer sk-local\r\n"));
+        assert_eq!(body["messages"][0]["content"], ANSWER_PROMPT);
+        let prompt = body["messages"][1]["content"].as_str().unwrap();
+        assert!(prompt.starts_with("Excerpts:\n\n[3] Payment\n"));
+        assert!(prompt.contains("[4] Invoices are paid within 30 days of receipt."));
+        assert!(prompt.contains("\n\n[6] Termination\n"));
+        assert!(!prompt.contains("Scope"));
+        assert!(prompt.ends_with("\n\nQuestion: When are invoices paid?"));
+
+        assert!(client.answer_question(&contract, "  ", |_| {}).is_err());
+    }
+}
@@ -0,0 +1,203 @@
+//! Alt text for pictures.
+//!
+//! Word keeps the alt text an author writes for a picture on its
+//! `<wp:docPr>` (`descr`, and a short `title`), which docx-rs skips. So it
+//! is read from `word/document.xml` by top-level block, like
+//! [`crate::chart::scan_chart_references`], and matched to the pictures of
+//! each paragraph in order. Pictures without alt text take the text of the
+//! caption next to them instead.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::BTreeMap;
+
+use crate::document::DocumentElement;
+
+static DRAWING_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?s)<(/?)w:(p|tbl|sdt)\b([^>]*)>|<w:drawing>(.*?)</w:drawing>").unwrap()
+});
+static DOC_PROPERTIES_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"<wp:docPr\b([^>]*)>").unwrap());
+
+/// Alt text of one picture
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AltText {
+    /// The title Word shows above the description (older Word versions
+    /// only); short, like a tooltip
+    pub title: Option<String>,
+    pub description: Option<String>,
+}
+
+impl AltText {
+    /// What to describe the picture with: the description, or the title
+    /// when it has none
+    pub fn text(&self) -> Option<&str> {
+        self.description.as_deref().or(self.title.as_deref())
+    }
+}
+
+/// Alt text of each picture in `document_xml`, by top-level block (counted
+/// from 0 like [`crate::pages::scan_page_starts`]), in the order the block
+/// holds them. Charts and other drawings that aren't pictures are left out,
+/// as docx-rs leaves them out of a paragraph's pictures.
+pub fn scan_alt_text(document_xml: &str) -> BTreeMap<usize, Vec<AltText>> {
+    let mut pictures: BTreeMap<usize, Vec<AltText>> = BTreeMap::new();
+    let mut depth = 0usize;
+    let mut block = 0;
+
+    for captures in DRAWING_PATTERN.captures_iter(document_xml) {
+        if let Some(drawing) = captures.get(4) {
+            let drawing = drawing.as_str();
+            if depth > 0 && drawing.contains("<pic:pic") {
+                pictures.entry(block).or_default().push(alt_text(drawing));
+            }
+            continue;
+        }
+        let counted = &captures[2] != "sdt";
+        if !captures[1].is_empty() {
+            depth = depth.saturating_sub(1);
+            if depth == 0 && counted {
+                block += 1;
+            }
+        } else if captures[3].ends_with('/') {
+            if depth == 0 && counted {
+                block += 1;
+            }
+        } else {
+            depth += 1;
+        }
+    }
+    pictures
+}
+
+/// The alt text on a drawing's `<wp:docPr>`
+fn alt_text(drawing: &str) -> AltText {
+    let Some(properties) = DOC_PROPERTIES_PATTERN.captures(drawing) else {
+        return AltText::default();
+    };
+    let attribute = |name: &str| {
+        let pattern = format!(" {name}=\"");
+        let start = properties[1].find(&pattern)? + pattern.len();
+        let end = properties[1][start..].find('"')?;
+        let value = decode_entities(&properties[1][start..start + end]);
+        // Alt text is often written over several lines
+        let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+        (!value.is_empty()).then_some(value)
+    };
+    AltText {
+        title: attribute("title"),
+        description: attribute("descr"),
+    }
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&#xA;", "\n")
+        .replace("&#xa;", "\n")
+        .replace("&#10;", "\n")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Describe pictures that have no alt text (an empty description) with the
+/// caption right after them, or else right before them when that caption
+/// doesn't follow another picture, and number the rest "Image N"
+pub fn describe_from_captions(elements: &mut [DocumentElement]) {
+    let caption = |index: usize| match elements.get(index) {
+        Some(DocumentElement::Caption { text, .. }) => Some(text.trim().to_string()),
+        _ => None,
+    };
+    let is_image =
+        |index: usize| matches!(elements.get(index), Some(DocumentElement::Image { .. }));
+
+    let mut descriptions = Vec::new();
+    let mut image_number = 0;
+    for (index, element) in elements.iter().enumerate() {
+        let DocumentElement::Image { description, .. } = element else {
+            continue;
+        };
+        image_number += 1;
+        if !description.is_empty() {
+            continue;
+        }
+        let before = index
+            .checked_sub(1)
+            .filter(|&before| before == 0 || !is_image(before - 1))
+            .and_then(caption);
+        let described = caption(index + 1)
+            .or(before)
+            .unwrap_or_else(|| format!("Image {image_number}"));
+        descriptions.push((index, described));
+    }
+
+    for (index, described) in descriptions {
+        if let DocumentElement::Image { description, .. } = &mut elements[index] {
+            *description = described;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(description: &str) -> DocumentElement {
+        DocumentElement::Image {
+            description: description.to_string(),
+            width: None,
+            height: None,
+            relationship_id: None,
+            image_path: None,
+            ocr_text: None,
+        }
+    }
+
+    fn caption(text: &str) -> DocumentElement {
+        DocumentElement::Caption {
+            text: text.to_string(),
+            style: None,
+            sequence: None,
+        }
+    }
+
+    fn description(element: &DocumentElement) -> &str {
+        match element {
+            DocumentElement::Image { description, .. } => description,
+            _ => panic!("not an image"),
+        }
+    }
+
+    #[test]
+    fn test_scan_alt_text() {
+        let xml = r#"<w:body><w:p><w:r><w:drawing><wp:inline><wp:docPr id="1" name="Picture 1" descr="Sales by &amp;quarter&#xA;2024"/><a:graphic><pic:pic/></a:graphic></wp:inline></w:drawing></w:r></w:p><w:p/><w:tbl><w:tr><w:tc><w:p><w:r><w:drawing><wp:docPr id="2" name="Chart 2"/><c:chart r:id="rId5"/></w:drawing></w:r></w:p></w:tc></w:tr></w:tbl><w:p><w:r><w:drawing><wp:docPr id="3" name="Picture 3" title="Logo"/><pic:pic/></w:drawing><w:drawing><wp:docPr id="4" name="Picture 4"/><pic:pic/></w:drawing></w:r></w:p></w:body>"#;
+        let pictures = scan_alt_text(xml);
+        assert_eq!(pictures.keys().copied().collect::<Vec<_>>(), [0, 3]);
+        assert_eq!(pictures[&0][0].text(), Some("Sales by &quarter 2024"));
+        assert_eq!(pictures[&3][0].text(), Some("Logo"));
+        assert_eq!(pictures[&3][1], AltText::default());
+    }
+
+    #[test]
+    fn test_describe_from_captions() {
+        let mut elements = vec![
+            image(""),
+            caption("Figure 1: Revenue"),
+            image("Team photo"),
+            caption("Figure 2: The team"),
+            caption("Figure 3: Before"),
+            image(""),
+            image(""),
+            caption("Figure 4: After"),
+            image(""),
+        ];
+        describe_from_captions(&mut elements);
+        assert_eq!(description(&elements[0]), "Figure 1: Revenue");
+        assert_eq!(description(&elements[2]), "Team photo");
+        assert_eq!(description(&elements[5]), "Figure 3: Before");
+        assert_eq!(description(&elements[6]), "Figure 4: After");
+        // Figure 4 belongs to the picture before it
+        assert_eq!(description(&elements[8]), "Image 5");
+    }
+}
@@ -0,0 +1,194 @@
+//! Cache of parsed documents.
+//!
+//! Scripts that run doxx on the same files again and again can skip parsing
+//! with `cache.enabled`. Entries are keyed by a hash of the file's contents,
+//! the parser version and the options that change the result, so an edited
+//! file, a new doxx release or a different `--changes` mode simply misses.
+//! Anything unreadable in the cache is treated as a miss too, and failing to
+//! write an entry doesn't fail the load.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::document::{parse_package, Document, ImageOptions, ParseOptions};
+use crate::error::Result;
+use crate::pages::PageMap;
+
+/// An entry: the document and its pages, which the document's own JSON
+/// leaves out
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    document: Document,
+    pages: PageMap,
+}
+
+/// Directory of cached documents, one JSON file per entry
+#[derive(Debug, Clone)]
+pub struct DocumentCache {
+    dir: PathBuf,
+}
+
+impl DocumentCache {
+    /// Default cache location (e.g. `~/.cache/doxx/documents`)
+    pub fn default_dir() -> PathBuf {
+        dirs::cache_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("doxx")
+            .join("documents")
+    }
+
+    /// The cache at the default location
+    pub fn open_default() -> Self {
+        Self::new(Self::default_dir())
+    }
+
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Key for the document in `data`, read from `file_path`. The file name
+    /// is part of it because untitled documents are named after it.
+    pub fn key(data: &[u8], file_path: &Path, options: &ParseOptions) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(env!("CARGO_PKG_VERSION"));
+        hasher.update([0]);
+        hasher.update(file_path.file_stem().unwrap_or_default().as_encoded_bytes());
+        hasher.update([0]);
+        hasher.update(format!(
+            "{} {:?} {} {} {:?} {} {:?} {} {:?}",
+            options.auto_number_headings,
+            options.changes,
+            options.elements_per_page,
+            options.strict,
+            options.limits,
+            options.normalize_heading_levels,
+            options.overrides,
+            options.fix_encoding,
+            options.section,
+        ));
+        hasher.update([0]);
+        hasher.update(data);
+        hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.json"))
+    }
+
+    /// The cached document for `key`, if there is a readable one
+    pub fn get(&self, key: &str) -> Option<Document> {
+        let data = fs::read(self.entry_path(key)).ok()?;
+        let entry: Entry = serde_json::from_slice(&data).ok()?;
+        let mut document = entry.document;
+        document.pages = entry.pages;
+        Some(document)
+    }
+
+    /// Store `document` under `key`
+    pub fn put(&self, key: &str, document: &Document) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let entry = Entry {
+            document: document.clone(),
+            pages: document.pages.clone(),
+        };
+        // Written aside and renamed, so a concurrent reader never sees half an entry
+        let path = self.entry_path(key);
+        let partial = path.with_extension(format!("{}.tmp", std::process::id()));
+        fs::write(&partial, serde_json::to_vec(&entry)?)?;
+        fs::rename(&partial, &path)?;
+        Ok(())
+    }
+
+    /// Load the document at `file_path` from the cache, or parse it and
+    /// cache the result. Documents with images are always parsed, since
+    /// their images are extracted to temporary files that don't outlive the
+    /// process.
+    pub fn load(
+        &self,
+        file_path: &Path,
+        image_options: ImageOptions,
+        parse_options: ParseOptions,
+    ) -> Result<Document> {
+        let data = fs::read(file_path)?;
+        if image_options.enabled {
+            return parse_package(&data, Some(file_path), image_options, parse_options);
+        }
+
+        let key = Self::key(&data, file_path, &parse_options);
+        if let Some(mut document) = self.get(&key) {
+            document.metadata.file_path = file_path.to_string_lossy().to_string();
+            document.image_options = image_options;
+            return Ok(document);
+        }
+        let document = parse_package(&data, Some(file_path), image_options, parse_options)?;
+        let _ = self.put(&key, &document);
+        Ok(document)
+    }
+
+    /// Remove every entry, returning how many there were
+    pub fn clear(&self) -> Result<usize> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+        let mut removed = 0;
+        for entry in entries {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "json")
+            {
+                fs::remove_file(&path)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_caches_and_clear() {
+        let dir = std::env::temp_dir().join(format!("doxx-cache-test-{}", std::process::id()));
+        let cache = DocumentCache::new(&dir);
+        let fixture = Path::new("../../tests/fixtures/headings-hierarchy.docx");
+
+        let parsed = cache
+            .load(fixture, ImageOptions::default(), ParseOptions::default())
+            .unwrap();
+        let cached = cache
+            .load(fixture, ImageOptions::default(), ParseOptions::default())
+            .unwrap();
+        assert_eq!(
+            serde_json::to_string(&cached.elements).unwrap(),
+            serde_json::to_string(&parsed.elements).unwrap()
+        );
+        assert_eq!(cached.pages.count, parsed.pages.count);
+        assert_eq!(cached.metadata.file_path, parsed.metadata.file_path);
+
+        // Different options give a different entry
+        let data = fs::read(fixture).unwrap();
+        let options = ParseOptions {
+            auto_number_headings: false,
+            ..Default::default()
+        };
+        assert_ne!(
+            DocumentCache::key(&data, fixture, &options),
+            DocumentCache::key(&data, fixture, &ParseOptions::default())
+        );
+
+        assert_eq!(cache.clear().unwrap(), 1);
+        assert_eq!(cache.clear().unwrap(), 0);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,267 @@
+//! Charts.
+//!
+//! A chart in a .docx is a drawing pointing at a `word/charts/chartN.xml`
+//! part, which docx-rs skips. Word caches the numbers behind each series in
+//! that part, so they are read back and shown as a table in place of the
+//! chart, for the viewer and for CSV export alike.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::document::{TableCell, TableData};
+
+/// A chart's title and the data cached for its series
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Chart {
+    pub title: Option<String>,
+    /// Category (x axis) labels, shared by the series
+    pub categories: Vec<String>,
+    pub series: Vec<Series>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Series {
+    pub name: String,
+    /// One value per category; empty where the chart has no point
+    pub values: Vec<String>,
+}
+
+static BLOCK_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"<(/?)w:(p|tbl|sdt)\b([^>]*)>|<c:chart\b[^>]*?\br:id="([^"]+)""#).unwrap()
+});
+
+/// Relationship ids of the charts in `document_xml`, by top-level block
+/// (counted from 0 like [`crate::pages::scan_page_starts`])
+pub fn scan_chart_references(document_xml: &str) -> BTreeMap<usize, Vec<String>> {
+    let mut charts: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+    let mut depth = 0usize;
+    let mut block = 0;
+
+    for captures in BLOCK_PATTERN.captures_iter(document_xml) {
+        if let Some(id) = captures.get(4) {
+            if depth > 0 {
+                charts
+                    .entry(block)
+                    .or_default()
+                    .push(id.as_str().to_string());
+            }
+            continue;
+        }
+        let counted = &captures[2] != "sdt";
+        if !captures[1].is_empty() {
+            depth = depth.saturating_sub(1);
+            if depth == 0 && counted {
+                block += 1;
+            }
+        } else if captures[3].ends_with('/') {
+            if depth == 0 && counted {
+                block += 1;
+            }
+        } else {
+            depth += 1;
+        }
+    }
+    charts
+}
+
+static RELATIONSHIP_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"<Relationship\b([^>]*)>").unwrap());
+
+/// Package part of each relationship in `word/_rels/document.xml.rels`,
+/// e.g. `rId4` to `word/charts/chart1.xml`
+pub fn relationship_targets(rels_xml: &str) -> HashMap<String, String> {
+    let attribute = |attributes: &str, name: &str| {
+        let start = attributes.find(&format!("{name}=\""))? + name.len() + 2;
+        let end = attributes[start..].find('"')?;
+        Some(attributes[start..start + end].to_string())
+    };
+    RELATIONSHIP_PATTERN
+        .captures_iter(rels_xml)
+        .filter_map(|captures| {
+            let id = attribute(&captures[1], "Id")?;
+            let target = attribute(&captures[1], "Target")?;
+            let part = match target.strip_prefix('/') {
+                Some(absolute) => absolute.to_string(),
+                None => format!("word/{}", target.trim_start_matches("./")),
+            };
+            Some((id, part))
+        })
+        .collect()
+}
+
+static SERIES_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<c:ser>(.*?)</c:ser>").unwrap());
+static TITLE_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?s)<c:title>(.*?)</c:title>").unwrap());
+static TEXT_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?s)<a:t>(.*?)</a:t>|<c:v>(.*?)</c:v>").unwrap());
+static POINT_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?s)<c:pt idx="(\d+)"[^>]*>\s*<c:v>(.*?)</c:v>"#).unwrap());
+
+/// The text of `<a:t>` runs or `<c:v>` values in `xml`
+fn xml_text(xml: &str) -> String {
+    TEXT_PATTERN
+        .captures_iter(xml)
+        .filter_map(|captures| captures.get(1).or(captures.get(2)))
+        .map(|text| decode_entities(text.as_str()))
+        .collect()
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Content of the first `<c:{name}>` element in `xml`
+fn child<'a>(xml: &'a str, name: &str) -> Option<&'a str> {
+    let open = format!("<c:{name}>");
+    let close = format!("</c:{name}>");
+    let start = xml.find(&open)? + open.len();
+    let end = xml[start..].find(&close)?;
+    Some(&xml[start..start + end])
+}
+
+/// Cached points of a category or value reference, by index
+fn points(xml: &str) -> Vec<String> {
+    let mut points = Vec::new();
+    for captures in POINT_PATTERN.captures_iter(xml) {
+        let Ok(index) = captures[1].parse::<usize>() else {
+            continue;
+        };
+        // Guard against a corrupt index asking for a huge table
+        if index > 100_000 {
+            continue;
+        }
+        if points.len() <= index {
+            points.resize(index + 1, String::new());
+        }
+        points[index] = decode_entities(captures[2].trim());
+    }
+    points
+}
+
+/// Read the title and series data of a `chartN.xml` part
+pub fn parse_chart(xml: &str) -> Option<Chart> {
+    let mut chart = Chart::default();
+    // The chart title comes before the plot area; series have titles too
+    let before_plot = xml.split("<c:plotArea>").next().unwrap_or_default();
+    chart.title = TITLE_PATTERN
+        .captures(before_plot)
+        .map(|captures| xml_text(&captures[1]).trim().to_string())
+        .filter(|title| !title.is_empty());
+
+    for (number, captures) in SERIES_PATTERN.captures_iter(xml).enumerate() {
+        let series = &captures[1];
+        let name = child(series, "tx")
+            .map(xml_text)
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| format!("Series {}", number + 1));
+        // Scatter and bubble charts use x and y values instead
+        let categories = child(series, "cat")
+            .or_else(|| child(series, "xVal"))
+            .map(points)
+            .unwrap_or_default();
+        let values = child(series, "val")
+            .or_else(|| child(series, "yVal"))
+            .map(points)
+            .unwrap_or_default();
+        if categories.len() > chart.categories.len() {
+            chart.categories = categories;
+        }
+        chart.series.push(Series { name, values });
+    }
+
+    (!chart.series.is_empty()).then_some(chart)
+}
+
+impl Chart {
+    /// The chart's data as a table: a row per category, a column per series
+    pub fn to_table(&self, number: usize) -> TableData {
+        let rows = self
+            .series
+            .iter()
+            .map(|series| series.values.len())
+            .chain([self.categories.len()])
+            .max()
+            .unwrap_or(0);
+        let mut headers = vec![TableCell::new("Category".to_string())];
+        headers.extend(
+            self.series
+                .iter()
+                .map(|series| TableCell::new(series.name.clone())),
+        );
+        let rows = (0..rows)
+            .map(|row| {
+                let category = self
+                    .categories
+                    .get(row)
+                    .cloned()
+                    .unwrap_or_else(|| (row + 1).to_string());
+                let mut cells = vec![TableCell::new(category)];
+                cells.extend(self.series.iter().map(|series| {
+                    TableCell::new(series.values.get(row).cloned().unwrap_or_default())
+                }));
+                cells
+            })
+            .collect();
+
+        let mut table = TableData::new(headers, rows);
+        table.metadata.title = Some(match &self.title {
+            Some(title) => format!("Chart {number}: {title}"),
+            None => format!("Chart {number}"),
+        });
+        table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHART: &str = r#"<c:chartSpace><c:chart><c:title><c:tx><c:rich><a:p><a:r><a:t>Sales &amp; Costs</a:t></a:r></a:p></c:rich></c:tx></c:title>
+        <c:plotArea><c:barChart>
+        <c:ser><c:idx val="0"/><c:tx><c:strRef><c:strCache><c:pt idx="0"><c:v>Sales</c:v></c:pt></c:strCache></c:strRef></c:tx>
+          <c:cat><c:strRef><c:strCache><c:ptCount val="3"/><c:pt idx="0"><c:v>Q1</c:v></c:pt><c:pt idx="1"><c:v>Q2</c:v></c:pt><c:pt idx="2"><c:v>Q3</c:v></c:pt></c:strCache></c:strRef></c:cat>
+          <c:val><c:numRef><c:numCache><c:pt idx="0"><c:v>10</c:v></c:pt><c:pt idx="1"><c:v>12.5</c:v></c:pt><c:pt idx="2"><c:v>14</c:v></c:pt></c:numCache></c:numRef></c:val></c:ser>
+        <c:ser><c:idx val="1"/>
+          <c:val><c:numRef><c:numCache><c:pt idx="0"><c:v>7</c:v></c:pt><c:pt idx="2"><c:v>9</c:v></c:pt></c:numCache></c:numRef></c:val></c:ser>
+        </c:barChart></c:plotArea></c:chart></c:chartSpace>"#;
+
+    #[test]
+    fn test_parse_chart_series() {
+        let chart = parse_chart(CHART).unwrap();
+        assert_eq!(chart.title.as_deref(), Some("Sales & Costs"));
+        assert_eq!(chart.categories, ["Q1", "Q2", "Q3"]);
+        assert_eq!(chart.series[0].name, "Sales");
+        assert_eq!(chart.series[1].name, "Series 2");
+        // A missing point stays empty rather than shifting the others
+        assert_eq!(chart.series[1].values, ["7", "", "9"]);
+
+        let table = chart.to_table(1);
+        assert_eq!(
+            table.metadata.title.as_deref(),
+            Some("Chart 1: Sales & Costs")
+        );
+        let row: Vec<&str> = table.rows[1].iter().map(|c| c.content.as_str()).collect();
+        assert_eq!(row, ["Q2", "12.5", ""]);
+    }
+
+    #[test]
+    fn test_scan_and_resolve_chart_references() {
+        let xml = concat!(
+            "<w:body><w:p><w:r><w:t>Intro</w:t></w:r></w:p>",
+            "<w:p><w:r><w:drawing><c:chart xmlns:r=\"r\" r:id=\"rId7\"/></w:drawing></w:r></w:p>",
+            "</w:body>"
+        );
+        assert_eq!(
+            scan_chart_references(xml),
+            BTreeMap::from([(1, vec!["rId7".to_string()])])
+        );
+        let rels = r#"<Relationships><Relationship Id="rId7" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/chart" Target="charts/chart1.xml"/></Relationships>"#;
+        assert_eq!(relationship_targets(rels)["rId7"], "word/charts/chart1.xml");
+    }
+}
@@ -0,0 +1,85 @@
+//! When output is colored.
+//!
+//! `--color` chooses between `auto`, `always` and `never`. With `auto`, the
+//! conventions of <https://no-color.org> and <https://bixense.com/clicolors/>
+//! apply: a non-empty `NO_COLOR` turns color off, a `CLICOLOR_FORCE` other
+//! than `0` turns it on even when output isn't a terminal, and otherwise
+//! color is used on terminals only. An explicit `always` or `never` wins
+//! over both variables.
+
+use std::env;
+
+/// The `--color` setting
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum ColorChoice {
+    /// Color on terminals, following `NO_COLOR` and `CLICOLOR_FORCE`
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Whether to color output going to a terminal (`is_terminal`) or not,
+    /// reading `NO_COLOR` and `CLICOLOR_FORCE` from the environment
+    pub fn enabled(self, is_terminal: bool) -> bool {
+        let set = |name| env::var_os(name).filter(|value| !value.is_empty());
+        self.resolve(
+            is_terminal,
+            set("NO_COLOR").is_some(),
+            set("CLICOLOR_FORCE").is_some_and(|value| value != "0"),
+        )
+    }
+
+    fn resolve(self, is_terminal: bool, no_color: bool, force: bool) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto if no_color => false,
+            ColorChoice::Auto => force || is_terminal,
+        }
+    }
+}
+
+pub const BOLD: &str = "1";
+pub const RED: &str = "31";
+pub const GREEN: &str = "32";
+pub const YELLOW: &str = "33";
+pub const BLUE: &str = "34";
+pub const MAGENTA: &str = "35";
+pub const CYAN: &str = "36";
+
+/// `text` wrapped in the ANSI escape for `style` (SGR codes such as [`BOLD`]
+/// or `"1;34"`), and a reset
+pub fn paint(text: &str, style: &str) -> String {
+    format!("\x1b[{style}m{text}\x1b[0m")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_choice() {
+        use ColorChoice::*;
+        // (choice, terminal, NO_COLOR, CLICOLOR_FORCE) -> colored
+        let cases = [
+            (Auto, true, false, false, true),
+            (Auto, false, false, false, false),
+            (Auto, true, true, false, false),
+            (Auto, false, false, true, true),
+            (Auto, false, true, true, false),
+            (Always, false, true, false, true),
+            (Never, true, false, true, false),
+        ];
+        for (choice, terminal, no_color, force, colored) in cases {
+            assert_eq!(
+                choice.resolve(terminal, no_color, force),
+                colored,
+                "{choice:?} terminal={terminal} NO_COLOR={no_color} CLICOLOR_FORCE={force}"
+            );
+        }
+        assert_eq!(paint("new", GREEN), "\x1b[32mnew\x1b[0m");
+    }
+}
@@ -0,0 +1,387 @@
+use crate::error::{DoxxError, Result};
+use crate::limits::Limits;
+use crate::text_layout::WrapAlgorithm;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// User configuration loaded from `config.toml`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub outline: OutlineConfig,
+    pub export: ExportConfig,
+    pub reading: ReadingConfig,
+    pub pages: PagesConfig,
+    pub ui: UiConfig,
+    pub history: HistoryConfig,
+    pub cache: CacheConfig,
+    pub ai: AiConfig,
+    pub limits: Limits,
+    /// Saved searches, run with `--run-report <name>`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub reports: BTreeMap<String, SavedReport>,
+}
+
+/// Heading outline settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutlineConfig {
+    /// Deepest heading level listed in the outline (0 = all levels)
+    pub max_depth: u8,
+    /// Show heading numbers in the outline
+    pub show_numbers: bool,
+    /// Number headings that have no numbering of their own (1, 1.1, ...)
+    pub auto_number: bool,
+    /// Outline for documents without headings: `bold`, `pages` or a
+    /// paragraph count (empty = none)
+    pub synthesize: String,
+}
+
+impl Default for OutlineConfig {
+    fn default() -> Self {
+        Self {
+            max_depth: 0,
+            show_numbers: true,
+            auto_number: true,
+            synthesize: String::new(),
+        }
+    }
+}
+
+/// Export settings
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportConfig {
+    /// Hard-wrap text exports at this many columns (0 = no wrapping)
+    pub wrap: usize,
+    /// Break tables longer than this many rows into pages in text exports,
+    /// each starting with the header row (0 = never)
+    pub table_page_rows: usize,
+}
+
+/// Reading aids in the document view
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReadingConfig {
+    /// Start with the reading ruler (highlighted current line) shown
+    pub ruler: bool,
+    /// Dim paragraphs other than the one under the ruler
+    pub dim_unfocused: bool,
+    /// How tables wider than the view are drawn: `wrap`, `truncate` or `scroll`
+    pub table_layout: TableLayout,
+    /// How paragraphs are broken into lines: `greedy` or `optimal`
+    /// (Knuth–Plass, for evenly filled lines)
+    pub wrap: WrapAlgorithm,
+    /// Hyphenate long words at line ends
+    pub hyphenate: bool,
+    /// Lay paragraphs out justified, centered or right-aligned as in the document
+    pub align_paragraphs: bool,
+    /// Width of the text column in reading mode
+    pub width: u16,
+    /// Reading speed behind the time left shown in the status bar
+    pub words_per_minute: u32,
+}
+
+impl Default for ReadingConfig {
+    fn default() -> Self {
+        Self {
+            ruler: false,
+            dim_unfocused: true,
+            table_layout: TableLayout::default(),
+            wrap: WrapAlgorithm::default(),
+            hyphenate: false,
+            align_paragraphs: true,
+            width: 80,
+            words_per_minute: 230,
+        }
+    }
+}
+
+/// How a table wider than the document view is drawn
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TableLayout {
+    /// Shrink the columns to fit, wrapping cells over several lines
+    #[default]
+    Wrap,
+    /// Shrink the columns to fit, cutting cells short with `…`
+    Truncate,
+    /// Keep columns at full width and scroll the table sideways
+    Scroll,
+}
+
+/// Page mapping settings
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PagesConfig {
+    /// Elements per page for documents without page breaks or a saved page
+    /// count (0 = estimate from the word count)
+    pub elements_per_page: usize,
+}
+
+/// Viewer settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiConfig {
+    /// Language of the viewer's messages, e.g. `es` (empty = from the environment)
+    pub language: String,
+    /// Built-in element renderers to use, comma separated (e.g. `sparklines`)
+    pub renderers: String,
+    /// Command the `O` key opens the document with, e.g. `libreoffice
+    /// --writer`; `%` stands for the file path, which is appended otherwise
+    /// (empty = `$VISUAL`, then `$EDITOR`)
+    pub editor: String,
+    /// Most rows an image takes up in the viewer when it is drawn as a
+    /// half-block mosaic, on terminals without a graphics protocol (0 = the
+    /// height of the view)
+    pub halfblock_rows: u16,
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            language: String::new(),
+            renderers: String::new(),
+            editor: String::new(),
+            halfblock_rows: 20,
+        }
+    }
+}
+
+/// Reading history settings
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryConfig {
+    /// Record time spent per document and section in the viewer, for
+    /// `doxx history`
+    pub enabled: bool,
+}
+
+/// Parsed document cache settings
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// Keep parsed documents and reuse them while the file is unchanged
+    /// (`--no-cache` skips the cache for one run)
+    pub enabled: bool,
+}
+
+/// Language model settings, for `doxx summarize` and `doxx ask`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AiConfig {
+    /// API the server speaks: `ollama`, or `openai` for any server with an
+    /// OpenAI-style `/v1/chat/completions` (llama.cpp, LM Studio, vLLM)
+    pub provider: AiProvider,
+    /// Address of the server, `http://` or `https://`
+    pub endpoint: String,
+    /// Sent as a bearer token when set, for APIs that need one
+    pub api_key: String,
+    /// Model to ask, e.g. `llama3.2`
+    pub model: String,
+    /// Only send documents to a server on this machine
+    pub privacy_mode: bool,
+    /// Tokens the model reads at once; longer documents are summarized a
+    /// part at a time
+    pub context_tokens: usize,
+}
+
+impl Default for AiConfig {
+    fn default() -> Self {
+        Self {
+            provider: AiProvider::default(),
+            endpoint: "http://localhost:11434".to_string(),
+            api_key: String::new(),
+            model: "llama3.2".to_string(),
+            privacy_mode: true,
+            context_tokens: 4096,
+        }
+    }
+}
+
+/// API a language model server speaks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AiProvider {
+    #[default]
+    Ollama,
+    #[serde(rename = "openai")]
+    OpenAi,
+}
+
+/// A named search kept in the config, e.g. `[reports.compliance-terms]`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedReport {
+    /// Terms to look for, each counted and listed on its own
+    pub terms: Vec<String>,
+    /// Search only under headings matching this path, e.g. `3. Risks`
+    /// (empty = the whole document)
+    #[serde(rename = "in")]
+    pub section: String,
+    /// Search only in tables
+    pub tables: bool,
+    /// Ignore accents and tolerate small typos
+    pub fuzzy: bool,
+    /// Output: `markdown` or `json`
+    pub format: SavedReportFormat,
+}
+
+/// How a saved report is written out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SavedReportFormat {
+    #[default]
+    Markdown,
+    Json,
+}
+
+impl Config {
+    /// Default config location (e.g. `~/.config/doxx/config.toml`)
+    pub fn default_path() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("doxx")
+            .join("config.toml")
+    }
+
+    /// Load the config at the default location
+    pub fn load_default() -> Result<Self> {
+        Self::load(&Self::default_path())
+    }
+
+    /// Load the config at `path`, using defaults if it doesn't exist
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let data = fs::read_to_string(path)?;
+        toml::from_str(&data)
+            .map_err(|e| DoxxError::Config(format!("Invalid config file {}: {e}", path.display())))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Read a value by dotted key, e.g. `outline.max_depth`
+    pub fn get(&self, key: &str) -> Result<String> {
+        let mut value = toml::Value::try_from(self)?;
+        for part in key.split('.') {
+            value = value.get(part).cloned().ok_or_else(|| unknown_key(key))?;
+        }
+        Ok(match value {
+            toml::Value::String(s) => s,
+            other => other.to_string(),
+        })
+    }
+
+    /// Set a value by dotted key, checking it against the expected type
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let mut root = toml::Value::try_from(&*self)?;
+        let (parents, leaf) = match key.rsplit_once('.') {
+            Some((parents, leaf)) => (parents.split('.').collect::<Vec<_>>(), leaf),
+            None => (Vec::new(), key),
+        };
+
+        let mut table = root.as_table_mut().expect("config serializes to a table");
+        for part in parents {
+            table = table
+                .get_mut(part)
+                .and_then(toml::Value::as_table_mut)
+                .ok_or_else(|| unknown_key(key))?;
+        }
+        let current = table.get(leaf).ok_or_else(|| unknown_key(key))?;
+
+        let parsed = match current {
+            toml::Value::Boolean(_) => toml::Value::Boolean(
+                value
+                    .parse()
+                    .map_err(|_| DoxxError::Config(format!("{key} expects true or false")))?,
+            ),
+            toml::Value::Integer(_) => toml::Value::Integer(
+                value
+                    .parse()
+                    .map_err(|_| DoxxError::Config(format!("{key} expects a number")))?,
+            ),
+            _ => toml::Value::String(value.to_string()),
+        };
+        table.insert(leaf.to_string(), parsed);
+
+        *self = root
+            .try_into()
+            .map_err(|e| DoxxError::Config(format!("Invalid value for {key}: {value}: {e}")))?;
+        Ok(())
+    }
+}
+
+fn unknown_key(key: &str) -> DoxxError {
+    DoxxError::Config(format!("Unknown config key: {key}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_and_set_dotted_keys() {
+        let mut config = Config::default();
+        assert_eq!(config.get("outline.show_numbers").unwrap(), "true");
+
+        config.set("outline.max_depth", "2").unwrap();
+        config.set("outline.auto_number", "false").unwrap();
+        assert_eq!(config.outline.max_depth, 2);
+        assert!(!config.outline.auto_number);
+
+        assert!(config.set("outline.max_depth", "deep").is_err());
+        assert!(config.set("outline.max_depth", "300").is_err());
+        assert!(config.get("outline.missing").is_err());
+
+        config.set("reading.table_layout", "scroll").unwrap();
+        assert_eq!(config.reading.table_layout, TableLayout::Scroll);
+        assert!(config.set("reading.table_layout", "shrink").is_err());
+        config.set("reading.wrap", "optimal").unwrap();
+        assert_eq!(config.reading.wrap, WrapAlgorithm::Optimal);
+        config.set("reading.width", "72").unwrap();
+        assert_eq!(config.reading.width, 72);
+    }
+
+    #[test]
+    fn test_partial_file_uses_defaults() {
+        let config: Config = toml::from_str("[outline]\nmax_depth = 3\n").unwrap();
+        assert_eq!(config.outline.max_depth, 3);
+        assert!(config.outline.show_numbers);
+        assert!(!config.reading.ruler);
+        assert!(config.reading.dim_unfocused);
+        assert_eq!(config.reading.width, 80);
+        assert_eq!(config.reading.words_per_minute, 230);
+        assert!(!config.history.enabled);
+    }
+
+    #[test]
+    fn test_saved_reports() {
+        let config: Config = toml::from_str(
+            "[reports.compliance-terms]\nterms = [\"indemnify\", \"liability\"]\nin = \"3. Risks\"\nformat = \"json\"\n",
+        )
+        .unwrap();
+        let report = &config.reports["compliance-terms"];
+        assert_eq!(report.terms, ["indemnify", "liability"]);
+        assert_eq!(report.section, "3. Risks");
+        assert_eq!(report.format, SavedReportFormat::Json);
+        assert!(!report.tables && !report.fuzzy);
+
+        // Saved reports survive `doxx set`, and an empty list isn't written out
+        let mut updated = config.clone();
+        updated.set("outline.max_depth", "2").unwrap();
+        assert_eq!(updated.reports, config.reports);
+        assert!(!toml::to_string(&Config::default())
+            .unwrap()
+            .contains("reports"));
+    }
+}
@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::document::{load_document, ImageOptions, ParseOptions};
+use crate::error::{DoxxError, Result};
+use crate::export::{format_document, OutputOptions};
+use crate::report::collect_docx_files;
+use crate::ExportFormat;
+
+/// A document to convert, with its output path relative to the output directory
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConvertJob {
+    pub input: PathBuf,
+    pub output: PathBuf,
+}
+
+/// Result of converting one document
+#[derive(Debug)]
+pub struct ConvertOutcome {
+    pub input: PathBuf,
+    /// Path written, or why the document could not be converted
+    pub result: Result<PathBuf>,
+}
+
+/// File extension for documents exported in `format`
+pub fn output_extension(format: &ExportFormat) -> &'static str {
+    match format {
+        ExportFormat::Markdown => "md",
+        ExportFormat::Text => "txt",
+        ExportFormat::Csv => "csv",
+        ExportFormat::Json => "json",
+        ExportFormat::Ansi => "ans",
+    }
+}
+
+/// Export format for an output path, from its extension
+pub fn format_for_path(path: &Path) -> Option<ExportFormat> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    match extension.as_str() {
+        "md" | "markdown" => Some(ExportFormat::Markdown),
+        "txt" | "text" => Some(ExportFormat::Text),
+        "csv" => Some(ExportFormat::Csv),
+        "json" => Some(ExportFormat::Json),
+        _ => None,
+    }
+}
+
+/// Expand directories (recursively) and glob patterns into conversion jobs.
+/// Outputs keep each document's path below its directory, or below the part
+/// of the pattern before the first wildcard, so the folder structure is mirrored.
+pub fn collect_jobs(inputs: &[String], format: &ExportFormat) -> Result<Vec<ConvertJob>> {
+    let mut jobs: Vec<ConvertJob> = Vec::new();
+    for input in inputs {
+        let path = Path::new(input);
+        let (base, mut files) = if path.is_dir() {
+            let mut files = Vec::new();
+            collect_docx_files(path, &mut files)?;
+            (path.to_path_buf(), files)
+        } else if is_glob(input) {
+            let mut files = Vec::new();
+            for entry in glob::glob(input)? {
+                let file = entry?;
+                if file.is_file() && is_docx(&file) {
+                    files.push(file);
+                }
+            }
+            (glob_base(input), files)
+        } else if path.is_file() {
+            let parent = path.parent().unwrap_or(Path::new("")).to_path_buf();
+            (parent, vec![path.to_path_buf()])
+        } else {
+            return Err(DoxxError::NotFound(format!("File not found: {input}")));
+        };
+
+        if files.is_empty() {
+            return Err(DoxxError::NotFound(format!(
+                "No .docx files found in {input}"
+            )));
+        }
+        files.sort();
+        for file in files {
+            if jobs.iter().any(|job| job.input == file) {
+                continue;
+            }
+            let relative = file.strip_prefix(&base).unwrap_or(&file);
+            jobs.push(ConvertJob {
+                output: relative.with_extension(output_extension(format)),
+                input: file,
+            });
+        }
+    }
+
+    // Two inputs mirrored onto the same output would overwrite each other
+    let mut outputs: HashMap<&Path, &Path> = HashMap::new();
+    for job in &jobs {
+        if let Some(other) = outputs.insert(&job.output, &job.input) {
+            return Err(DoxxError::InvalidInput(format!(
+                "{} and {} would both be written to {}",
+                other.display(),
+                job.input.display(),
+                job.output.display()
+            )));
+        }
+    }
+    Ok(jobs)
+}
+
+pub(crate) fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '['])
+}
+
+pub(crate) fn is_docx(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    !name.starts_with("~$")
+        && path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("docx"))
+}
+
+/// Leading directories of a glob pattern, up to the first wildcard
+fn glob_base(pattern: &str) -> PathBuf {
+    let mut base = PathBuf::new();
+    let components: Vec<Component> = Path::new(pattern).components().collect();
+    for component in &components[..components.len().saturating_sub(1)] {
+        if is_glob(&component.as_os_str().to_string_lossy()) {
+            break;
+        }
+        base.push(component);
+    }
+    base
+}
+
+/// Convert every job into `output_dir`, running up to `parallelism`
+/// conversions at once. Outcomes come back in the order of `jobs`.
+pub async fn convert_documents(
+    jobs: Vec<ConvertJob>,
+    format: ExportFormat,
+    output_dir: &Path,
+    parse_options: ParseOptions,
+    output_options: OutputOptions,
+    parallelism: usize,
+) -> Vec<ConvertOutcome> {
+    let permits = Arc::new(Semaphore::new(parallelism.max(1)));
+    let mut tasks = JoinSet::new();
+    for (index, job) in jobs.iter().cloned().enumerate() {
+        let permits = Arc::clone(&permits);
+        let output = output_dir.join(&job.output);
+        let format = format.clone();
+        let parse_options = parse_options.clone();
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            let result =
+                convert_one(&job.input, &output, &format, parse_options, output_options).await;
+            (index, result.map(|()| output))
+        });
+    }
+
+    let mut results: Vec<Option<Result<PathBuf>>> = jobs.iter().map(|_| None).collect();
+    // A conversion that panicked leaves its slot empty and is reported below
+    while let Some(joined) = tasks.join_next().await {
+        if let Ok((index, result)) = joined {
+            results[index] = Some(result);
+        }
+    }
+
+    jobs.into_iter()
+        .zip(results)
+        .map(|(job, result)| ConvertOutcome {
+            input: job.input,
+            result: result.unwrap_or(Err(DoxxError::Aborted("conversion"))),
+        })
+        .collect()
+}
+
+async fn convert_one(
+    input: &Path,
+    output: &Path,
+    format: &ExportFormat,
+    parse_options: ParseOptions,
+    output_options: OutputOptions,
+) -> Result<()> {
+    let document = load_document(input, ImageOptions::default(), parse_options).await?;
+    let content = format_document(&document, format)?;
+    let content = output_options.apply(&content);
+    if let Some(parent) = output.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(output, content.as_bytes()).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_base_stops_at_first_wildcard() {
+        assert_eq!(glob_base("docs/**/*.docx"), PathBuf::from("docs"));
+        assert_eq!(
+            glob_base("reports/2024-*/q?.docx"),
+            PathBuf::from("reports")
+        );
+        assert_eq!(glob_base("*.docx"), PathBuf::new());
+        assert!(is_glob("docs/[ab].docx") && !is_glob("docs/a.docx"));
+    }
+
+    #[test]
+    fn test_format_for_path() {
+        assert!(matches!(
+            format_for_path(Path::new("notes.MD")),
+            Some(ExportFormat::Markdown)
+        ));
+        assert!(matches!(
+            format_for_path(Path::new("out/tables.csv")),
+            Some(ExportFormat::Csv)
+        ));
+        assert!(format_for_path(Path::new("report.docx")).is_none());
+        assert!(format_for_path(Path::new("README")).is_none());
+    }
+
+    #[test]
+    fn test_jobs_mirror_directories() {
+        let jobs = collect_jobs(
+            &["../../tests/fixtures/*.docx".to_string()],
+            &ExportFormat::Markdown,
+        )
+        .unwrap();
+        assert!(jobs.contains(&ConvertJob {
+            input: PathBuf::from("../../tests/fixtures/minimal.docx"),
+            output: PathBuf::from("minimal.md"),
+        }));
+
+        let jobs = collect_jobs(
+            &["../../tests/fixtures/minimal.docx".to_string()],
+            &ExportFormat::Json,
+        )
+        .unwrap();
+        assert_eq!(jobs[0].output, PathBuf::from("minimal.json"));
+
+        // The same file named twice is converted once
+        let twice = [
+            "../../tests".to_string(),
+            "../../tests/fixtures".to_string(),
+        ];
+        assert!(collect_jobs(&twice, &ExportFormat::Text)
+            .unwrap()
+            .iter()
+            .all(|job| job.output.starts_with("fixtures")));
+        assert!(collect_jobs(&["missing.docx".to_string()], &ExportFormat::Text).is_err());
+    }
+}
@@ -0,0 +1,1062 @@
+//! Element-level comparison of two documents (`doxx diff`).
+//!
+//! Elements are aligned with a longest-common-subsequence match on their
+//! text. Removed and added elements of the same kind that still look alike
+//! are reported as one change, and changed tables are broken down by cell.
+//!
+//! The heading trees are compared too, for `doxx diff --outline`: sections
+//! are matched by title, then by what they contain, and reported as added,
+//! removed, renamed or moved to another place in the outline.
+
+use serde::Serialize;
+use std::path::Path;
+
+use crate::color::{paint, BOLD, CYAN, GREEN, RED, YELLOW};
+use crate::document::{load_document, DocumentElement, ImageOptions, ParseOptions, TableData};
+use crate::error::Result;
+use crate::Document;
+
+/// Output format for `doxx diff` when not shown in the viewer
+#[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum DiffFormat {
+    /// Unified-style text with `-`/`+` lines and `~` for table cells
+    #[default]
+    Unified,
+    Json,
+}
+
+/// Unchanged elements shown around each change in unified output
+const CONTEXT_ELEMENTS: usize = 1;
+
+/// Largest LCS table built before falling back to a plain replace
+const MAX_LCS_CELLS: usize = 16_000_000;
+
+/// Removed and added paragraphs sharing at least this share of their words
+/// are shown as one changed paragraph
+const CHANGED_SIMILARITY: f64 = 0.5;
+
+/// One aligned step between the two documents. Element indices are
+/// 0-based positions in each document's element list.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DiffEntry {
+    Unchanged {
+        old_element: usize,
+        new_element: usize,
+        text: String,
+    },
+    Removed {
+        old_element: usize,
+        text: String,
+    },
+    Added {
+        new_element: usize,
+        text: String,
+    },
+    Changed {
+        old_element: usize,
+        new_element: usize,
+        old: String,
+        new: String,
+    },
+    TableChanged {
+        old_element: usize,
+        new_element: usize,
+        title: Option<String>,
+        cells: Vec<CellChange>,
+    },
+}
+
+/// A table cell whose content differs. Rows and columns are 1-based, with
+/// the header row counted as row 1; `row` refers to the new table unless the
+/// whole row was removed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CellChange {
+    pub row: usize,
+    pub column: usize,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct DiffSummary {
+    pub added: usize,
+    pub removed: usize,
+    pub changed: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentDiff {
+    pub old_path: String,
+    pub new_path: String,
+    pub entries: Vec<DiffEntry>,
+    /// Changes to the heading tree, in the order of the new outline
+    pub sections: Vec<SectionChange>,
+}
+
+/// What happened to a section between the two versions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SectionChangeKind {
+    Added,
+    Removed,
+    /// Its heading was reworded; it may have moved as well
+    Renamed,
+    /// Put under another heading, or in another order among its siblings
+    Moved,
+}
+
+/// A heading where it stands in one version
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SectionHeading {
+    pub title: String,
+    pub level: u8,
+    /// Titles of the headings it is nested under, outermost first
+    pub parents: Vec<String>,
+    /// 0-based position in the document's element list
+    pub element: usize,
+}
+
+impl SectionHeading {
+    /// `Policies > Remote Work`
+    pub fn path(&self) -> String {
+        let mut path = self.parents.clone();
+        path.push(self.title.clone());
+        path.join(" > ")
+    }
+}
+
+/// A section added, removed, renamed or moved. `old` is missing for added
+/// sections and `new` for removed ones.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SectionChange {
+    pub kind: SectionChangeKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old: Option<SectionHeading>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new: Option<SectionHeading>,
+}
+
+/// Whether a word is shared by both versions or only in one of them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WordChange {
+    Unchanged,
+    Removed,
+    Added,
+}
+
+impl DiffEntry {
+    pub fn is_change(&self) -> bool {
+        !matches!(self, DiffEntry::Unchanged { .. })
+    }
+
+    fn old_element(&self) -> Option<usize> {
+        match self {
+            DiffEntry::Unchanged { old_element, .. }
+            | DiffEntry::Removed { old_element, .. }
+            | DiffEntry::Changed { old_element, .. }
+            | DiffEntry::TableChanged { old_element, .. } => Some(*old_element),
+            DiffEntry::Added { .. } => None,
+        }
+    }
+
+    fn new_element(&self) -> Option<usize> {
+        match self {
+            DiffEntry::Unchanged { new_element, .. }
+            | DiffEntry::Added { new_element, .. }
+            | DiffEntry::Changed { new_element, .. }
+            | DiffEntry::TableChanged { new_element, .. } => Some(*new_element),
+            DiffEntry::Removed { .. } => None,
+        }
+    }
+}
+
+impl DocumentDiff {
+    pub fn summary(&self) -> DiffSummary {
+        let mut summary = DiffSummary::default();
+        for entry in &self.entries {
+            match entry {
+                DiffEntry::Unchanged { .. } => {}
+                DiffEntry::Removed { .. } => summary.removed += 1,
+                DiffEntry::Added { .. } => summary.added += 1,
+                DiffEntry::Changed { .. } | DiffEntry::TableChanged { .. } => summary.changed += 1,
+            }
+        }
+        summary
+    }
+}
+
+/// Load and compare two documents
+pub async fn diff_files(old: &Path, new: &Path) -> Result<DocumentDiff> {
+    // Synthetic heading numbers would shift after every inserted heading and
+    // make unchanged headings look changed
+    let options = ParseOptions {
+        auto_number_headings: false,
+        ..ParseOptions::default()
+    };
+    let old_document = load_document(old, ImageOptions::default(), options.clone())
+        .await
+        .map_err(|e| e.in_file(old))?;
+    let new_document = load_document(new, ImageOptions::default(), options)
+        .await
+        .map_err(|e| e.in_file(new))?;
+    Ok(diff_documents(&old_document, &new_document))
+}
+
+/// Align the elements of two documents and describe what changed
+pub fn diff_documents(old: &Document, new: &Document) -> DocumentDiff {
+    let old_items = comparable_elements(old);
+    let new_items = comparable_elements(new);
+
+    let mut entries = Vec::new();
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    for op in diff_sequences(&old_items, &new_items) {
+        match op {
+            Op::Equal(o, n) => {
+                flush_replacement(&mut entries, &mut removed, &mut added);
+                entries.push(DiffEntry::Unchanged {
+                    old_element: old_items[o].index,
+                    new_element: new_items[n].index,
+                    text: old_items[o].text.clone(),
+                });
+            }
+            Op::Delete(o) => removed.push(&old_items[o]),
+            Op::Insert(n) => added.push(&new_items[n]),
+        }
+    }
+    flush_replacement(&mut entries, &mut removed, &mut added);
+
+    DocumentDiff {
+        old_path: old.metadata.file_path.clone(),
+        new_path: new.metadata.file_path.clone(),
+        entries,
+        sections: diff_outlines(old, new),
+    }
+}
+
+/// A heading with the text of the elements under it, up to the next heading
+struct Section {
+    heading: SectionHeading,
+    /// Index of the section it is nested under
+    parent: Option<usize>,
+    body: String,
+}
+
+fn sections(document: &Document) -> Vec<Section> {
+    let mut sections: Vec<Section> = Vec::new();
+    // Sections the current element is nested in, innermost last
+    let mut open: Vec<usize> = Vec::new();
+    for item in comparable_elements(document) {
+        let DocumentElement::Heading { level, text, .. } = &document.elements[item.index] else {
+            if let Some(&current) = open.last() {
+                let body = &mut sections[current].body;
+                body.push_str(&item.text);
+                body.push('\n');
+            }
+            continue;
+        };
+        while open
+            .last()
+            .is_some_and(|&section| sections[section].heading.level >= *level)
+        {
+            open.pop();
+        }
+        sections.push(Section {
+            heading: SectionHeading {
+                title: text.trim().to_string(),
+                level: *level,
+                parents: open
+                    .iter()
+                    .map(|&section| sections[section].heading.title.clone())
+                    .collect(),
+                element: item.index,
+            },
+            parent: open.last().copied(),
+            body: String::new(),
+        });
+        open.push(sections.len() - 1);
+    }
+    sections
+}
+
+/// How much a removed and an added section look like one renamed section:
+/// by their titles, or by the text under them
+fn rename_similarity(old: &Section, new: &Section) -> f64 {
+    let titles = similarity(&old.heading.title, &new.heading.title);
+    if old.body.trim().is_empty() || new.body.trim().is_empty() {
+        return titles;
+    }
+    titles.max(similarity(&old.body, &new.body))
+}
+
+/// Compare the heading trees of two documents. Headings are aligned by
+/// title; a title found elsewhere in the new outline is a move, and a
+/// removed section whose title or text resembles an added one is a rename.
+/// Matched sections whose parent isn't the match of their old parent have
+/// moved too.
+pub fn diff_outlines(old: &Document, new: &Document) -> Vec<SectionChange> {
+    let old_sections = sections(old);
+    let new_sections = sections(new);
+    let old_titles: Vec<&str> = old_sections
+        .iter()
+        .map(|s| s.heading.title.as_str())
+        .collect();
+    let new_titles: Vec<&str> = new_sections
+        .iter()
+        .map(|s| s.heading.title.as_str())
+        .collect();
+
+    // Match in the new outline of each old section, and whether its title
+    // changed or it was found out of order
+    let mut matches: Vec<Option<usize>> = vec![None; old_sections.len()];
+    let mut renamed = vec![false; old_sections.len()];
+    let mut reordered = vec![false; old_sections.len()];
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    for op in diff_sequences(&old_titles, &new_titles) {
+        match op {
+            Op::Equal(o, n) => matches[o] = Some(n),
+            Op::Delete(o) => removed.push(o),
+            Op::Insert(n) => added.push(n),
+        }
+    }
+    removed.retain(|&o| {
+        let found = added.iter().position(|&n| new_titles[n] == old_titles[o]);
+        match found {
+            Some(at) => {
+                matches[o] = Some(added.remove(at));
+                reordered[o] = true;
+                false
+            }
+            None => true,
+        }
+    });
+    removed.retain(|&o| {
+        let best = added
+            .iter()
+            .enumerate()
+            .map(|(at, &n)| (at, rename_similarity(&old_sections[o], &new_sections[n])))
+            .filter(|(_, score)| *score >= CHANGED_SIMILARITY)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        match best {
+            Some((at, _)) => {
+                matches[o] = Some(added.remove(at));
+                renamed[o] = true;
+                false
+            }
+            None => true,
+        }
+    });
+
+    let heading = |section: &Section| Some(section.heading.clone());
+    // Changes sort by where they are in the new outline; removed sections
+    // go after the matches of the sections before them
+    let mut changes: Vec<((usize, bool), SectionChange)> = Vec::new();
+    for (o, section) in old_sections.iter().enumerate() {
+        let Some(n) = matches[o] else {
+            let after = matches[..o].iter().flatten().max().map_or(0, |n| n + 1);
+            changes.push((
+                (after, false),
+                SectionChange {
+                    kind: SectionChangeKind::Removed,
+                    old: heading(section),
+                    new: None,
+                },
+            ));
+            continue;
+        };
+        let parent_moved =
+            section.parent.and_then(|parent| matches[parent]) != new_sections[n].parent;
+        let kind = if renamed[o] {
+            SectionChangeKind::Renamed
+        } else if reordered[o] || parent_moved {
+            SectionChangeKind::Moved
+        } else {
+            continue;
+        };
+        changes.push((
+            (n, true),
+            SectionChange {
+                kind,
+                old: heading(section),
+                new: heading(&new_sections[n]),
+            },
+        ));
+    }
+    for n in added {
+        changes.push((
+            (n, true),
+            SectionChange {
+                kind: SectionChangeKind::Added,
+                old: None,
+                new: heading(&new_sections[n]),
+            },
+        ));
+    }
+    changes.sort_by_key(|(position, _)| *position);
+    changes.into_iter().map(|(_, change)| change).collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ElementKind {
+    Heading,
+    Paragraph,
+    List,
+    Table,
+    Image,
+}
+
+/// An element reduced to what is compared: its kind and visible text
+#[derive(Debug)]
+struct Comparable<'a> {
+    index: usize,
+    kind: ElementKind,
+    text: String,
+    table: Option<&'a TableData>,
+}
+
+impl PartialEq for Comparable<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind && self.text == other.text
+    }
+}
+
+fn comparable_elements(document: &Document) -> Vec<Comparable<'_>> {
+    document
+        .elements
+        .iter()
+        .enumerate()
+        .filter_map(|(index, element)| {
+            let (kind, text, table) = match element {
+                DocumentElement::Heading {
+                    level,
+                    text,
+                    number,
+                    ..
+                } => {
+                    let marks = "#".repeat(*level as usize);
+                    let text = match number {
+                        Some(number) => format!("{marks} {number} {text}"),
+                        None => format!("{marks} {text}"),
+                    };
+                    (ElementKind::Heading, text, None)
+                }
+                DocumentElement::Paragraph { text, .. } => {
+                    (ElementKind::Paragraph, text.trim().to_string(), None)
+                }
+                DocumentElement::List { items, .. } => {
+                    let lines: Vec<String> = items
+                        .iter()
+                        .map(|item| format!("{}- {}", "  ".repeat(item.level as usize), item.text))
+                        .collect();
+                    (ElementKind::List, lines.join("\n"), None)
+                }
+                DocumentElement::Table { table } => {
+                    let lines: Vec<String> = table_grid(table)
+                        .iter()
+                        .map(|row| row.join(" | "))
+                        .collect();
+                    (ElementKind::Table, lines.join("\n"), Some(table))
+                }
+                DocumentElement::Image { description, .. } => {
+                    (ElementKind::Image, format!("[Image: {description}]"), None)
+                }
+                // Other text blocks compare like paragraphs, marked the way
+                // Markdown would show them
+                DocumentElement::Quote { text, .. } => {
+                    (ElementKind::Paragraph, format!("> {}", text.trim()), None)
+                }
+                DocumentElement::CodeBlock { lines, .. } => {
+                    (ElementKind::Paragraph, lines.join("\n"), None)
+                }
+                DocumentElement::Caption { text, .. }
+                | DocumentElement::Title { text, .. }
+                | DocumentElement::Subtitle { text, .. }
+                | DocumentElement::Equation { text, .. } => {
+                    (ElementKind::Paragraph, text.trim().to_string(), None)
+                }
+                DocumentElement::PageBreak => return None,
+            };
+            Some(Comparable {
+                index,
+                kind,
+                text,
+                table,
+            })
+        })
+        .collect()
+}
+
+fn table_grid(table: &TableData) -> Vec<Vec<String>> {
+    let cells = |row: &[crate::document::TableCell]| -> Vec<String> {
+        row.iter()
+            .map(|cell| cell.content.trim().to_string())
+            .collect()
+    };
+    let mut grid = Vec::with_capacity(table.rows.len() + 1);
+    if !table.headers.is_empty() {
+        grid.push(cells(&table.headers));
+    }
+    grid.extend(table.rows.iter().map(|row| cells(row)));
+    grid
+}
+
+/// Turn a run of removed and added elements into entries, pairing elements
+/// of the same kind that still resemble each other as changes
+fn flush_replacement(
+    entries: &mut Vec<DiffEntry>,
+    removed: &mut Vec<&Comparable>,
+    added: &mut Vec<&Comparable>,
+) {
+    let mut next_added = 0;
+    let mut pending_removed = Vec::new();
+    for old in removed.drain(..) {
+        let partner = added[next_added..]
+            .iter()
+            .position(|new| resembles(old, new))
+            .map(|offset| next_added + offset);
+        let Some(partner) = partner else {
+            pending_removed.push(old);
+            continue;
+        };
+
+        entries.extend(pending_removed.drain(..).map(|old| DiffEntry::Removed {
+            old_element: old.index,
+            text: old.text.clone(),
+        }));
+        entries.extend(
+            added[next_added..partner]
+                .iter()
+                .map(|new| DiffEntry::Added {
+                    new_element: new.index,
+                    text: new.text.clone(),
+                }),
+        );
+        let new = added[partner];
+        entries.push(match (old.table, new.table) {
+            (Some(old_table), Some(new_table)) => DiffEntry::TableChanged {
+                old_element: old.index,
+                new_element: new.index,
+                title: new_table.metadata.title.clone(),
+                cells: diff_tables(old_table, new_table),
+            },
+            _ => DiffEntry::Changed {
+                old_element: old.index,
+                new_element: new.index,
+                old: old.text.clone(),
+                new: new.text.clone(),
+            },
+        });
+        next_added = partner + 1;
+    }
+
+    entries.extend(pending_removed.into_iter().map(|old| DiffEntry::Removed {
+        old_element: old.index,
+        text: old.text.clone(),
+    }));
+    entries.extend(
+        added
+            .drain(..)
+            .skip(next_added)
+            .map(|new| DiffEntry::Added {
+                new_element: new.index,
+                text: new.text.clone(),
+            }),
+    );
+}
+
+fn resembles(old: &Comparable, new: &Comparable) -> bool {
+    old.kind == new.kind
+        && (old.kind == ElementKind::Table
+            || similarity(&old.text, &new.text) >= CHANGED_SIMILARITY)
+}
+
+/// Share of words two texts have in common (0.0 - 1.0)
+fn similarity(old: &str, new: &str) -> f64 {
+    let old_words: Vec<&str> = old.split_whitespace().collect();
+    let new_words: Vec<&str> = new.split_whitespace().collect();
+    if old_words.is_empty() && new_words.is_empty() {
+        return 1.0;
+    }
+    let common = diff_sequences(&old_words, &new_words)
+        .iter()
+        .filter(|op| matches!(op, Op::Equal(..)))
+        .count();
+    2.0 * common as f64 / (old_words.len() + new_words.len()) as f64
+}
+
+/// Cell-by-cell differences between two versions of a table. Rows are
+/// aligned first, so an inserted row doesn't make every later row differ.
+fn diff_tables(old: &TableData, new: &TableData) -> Vec<CellChange> {
+    let old_grid = table_grid(old);
+    let new_grid = table_grid(new);
+
+    let mut changes = Vec::new();
+    let mut removed: Vec<usize> = Vec::new();
+    let mut added: Vec<usize> = Vec::new();
+    let mut flush = |removed: &mut Vec<usize>, added: &mut Vec<usize>| {
+        let pairs = removed.len().max(added.len());
+        for i in 0..pairs {
+            let old_row = removed.get(i).map(|&r| &old_grid[r]);
+            let new_row = added.get(i).map(|&r| &new_grid[r]);
+            let row = added.get(i).or(removed.get(i)).copied().unwrap_or(0) + 1;
+            let columns = old_row.map_or(0, Vec::len).max(new_row.map_or(0, Vec::len));
+            for column in 0..columns {
+                let old_cell = old_row.and_then(|cells| cells.get(column)).cloned();
+                let new_cell = new_row.and_then(|cells| cells.get(column)).cloned();
+                if old_cell != new_cell {
+                    changes.push(CellChange {
+                        row,
+                        column: column + 1,
+                        old: old_cell,
+                        new: new_cell,
+                    });
+                }
+            }
+        }
+        removed.clear();
+        added.clear();
+    };
+
+    for op in diff_sequences(&old_grid, &new_grid) {
+        match op {
+            Op::Equal(..) => flush(&mut removed, &mut added),
+            Op::Delete(o) => removed.push(o),
+            Op::Insert(n) => added.push(n),
+        }
+    }
+    flush(&mut removed, &mut added);
+    changes
+}
+
+/// Split two versions of a text into words marked as shared, removed or added
+pub fn word_diff(old: &str, new: &str) -> Vec<(WordChange, String)> {
+    let old_words: Vec<&str> = old.split_whitespace().collect();
+    let new_words: Vec<&str> = new.split_whitespace().collect();
+
+    let mut words: Vec<(WordChange, String)> = Vec::new();
+    for op in diff_sequences(&old_words, &new_words) {
+        let (change, word) = match op {
+            Op::Equal(o, _) => (WordChange::Unchanged, old_words[o]),
+            Op::Delete(o) => (WordChange::Removed, old_words[o]),
+            Op::Insert(n) => (WordChange::Added, new_words[n]),
+        };
+        // Consecutive words with the same change form one piece
+        match words.last_mut() {
+            Some((last, text)) if *last == change => {
+                text.push(' ');
+                text.push_str(word);
+            }
+            _ => words.push((change, word.to_string())),
+        }
+    }
+    words
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Edit script turning `old` into `new`, preferring deletions before
+/// insertions within a replaced stretch
+fn diff_sequences<T: PartialEq>(old: &[T], new: &[T]) -> Vec<Op> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    let mut ops: Vec<Op> = (0..prefix).map(|i| Op::Equal(i, i)).collect();
+    let (rows, cols) = (old_middle.len(), new_middle.len());
+    if rows * cols > MAX_LCS_CELLS {
+        // Too large to align precisely; treat the middle as replaced
+        ops.extend((0..rows).map(|i| Op::Delete(prefix + i)));
+        ops.extend((0..cols).map(|j| Op::Insert(prefix + j)));
+    } else {
+        // lengths[i][j]: LCS length of old_middle[i..] and new_middle[j..]
+        let width = cols + 1;
+        let mut lengths = vec![0u32; (rows + 1) * width];
+        for i in (0..rows).rev() {
+            for j in (0..cols).rev() {
+                lengths[i * width + j] = if old_middle[i] == new_middle[j] {
+                    lengths[(i + 1) * width + j + 1] + 1
+                } else {
+                    lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < rows || j < cols {
+            if i < rows && j < cols && old_middle[i] == new_middle[j] {
+                ops.push(Op::Equal(prefix + i, prefix + j));
+                i += 1;
+                j += 1;
+            } else if j == cols
+                || (i < rows && lengths[(i + 1) * width + j] >= lengths[i * width + j + 1])
+            {
+                ops.push(Op::Delete(prefix + i));
+                i += 1;
+            } else {
+                ops.push(Op::Insert(prefix + j));
+                j += 1;
+            }
+        }
+    }
+
+    let old_end = old.len() - suffix;
+    let new_end = new.len() - suffix;
+    ops.extend((0..suffix).map(|k| Op::Equal(old_end + k, new_end + k)));
+    ops
+}
+
+pub fn format_diff(diff: &DocumentDiff, format: DiffFormat) -> Result<String> {
+    Ok(match format {
+        DiffFormat::Unified => format_unified(diff),
+        DiffFormat::Json => {
+            #[derive(Serialize)]
+            struct JsonDiff<'a> {
+                old_path: &'a str,
+                new_path: &'a str,
+                summary: DiffSummary,
+                changes: Vec<&'a DiffEntry>,
+                sections: &'a [SectionChange],
+            }
+            let json = JsonDiff {
+                old_path: &diff.old_path,
+                new_path: &diff.new_path,
+                summary: diff.summary(),
+                changes: diff.entries.iter().filter(|e| e.is_change()).collect(),
+                sections: &diff.sections,
+            };
+            serde_json::to_string_pretty(&json)? + "\n"
+        }
+    })
+}
+
+/// Only the outline changes of `diff`, for `doxx diff --outline`
+pub fn format_outline_diff(diff: &DocumentDiff, format: DiffFormat) -> Result<String> {
+    Ok(match format {
+        DiffFormat::Unified => {
+            let mut output = format!("--- {}\n+++ {}\n", diff.old_path, diff.new_path);
+            for change in &diff.sections {
+                output.push_str(&describe_section_change(change));
+                output.push('\n');
+            }
+            output
+        }
+        DiffFormat::Json => {
+            #[derive(Serialize)]
+            struct JsonOutlineDiff<'a> {
+                old_path: &'a str,
+                new_path: &'a str,
+                sections: &'a [SectionChange],
+            }
+            let json = JsonOutlineDiff {
+                old_path: &diff.old_path,
+                new_path: &diff.new_path,
+                sections: &diff.sections,
+            };
+            serde_json::to_string_pretty(&json)? + "\n"
+        }
+    })
+}
+
+/// One line for a section change, marked like the unified diff: `+ Contacts`,
+/// `- Appendix`, `~ Overview -> Introduction` for a rename and
+/// `> Policies > Remote Work -> Security > Remote Work` for a move
+pub fn describe_section_change(change: &SectionChange) -> String {
+    let path = |heading: &Option<SectionHeading>| {
+        heading
+            .as_ref()
+            .map(SectionHeading::path)
+            .unwrap_or_default()
+    };
+    match change.kind {
+        SectionChangeKind::Added => format!("+ {}", path(&change.new)),
+        SectionChangeKind::Removed => format!("- {}", path(&change.old)),
+        SectionChangeKind::Renamed => {
+            format!("~ {} -> {}", path(&change.old), path(&change.new))
+        }
+        SectionChangeKind::Moved => format!("> {} -> {}", path(&change.old), path(&change.new)),
+    }
+}
+
+/// Unified-diff style text: hunks of changed elements with a little context
+fn format_unified(diff: &DocumentDiff) -> String {
+    let mut output = format!("--- {}\n+++ {}\n", diff.old_path, diff.new_path);
+
+    let entries = &diff.entries;
+    let changed: Vec<usize> = (0..entries.len())
+        .filter(|&i| entries[i].is_change())
+        .collect();
+    let mut shown_until = 0;
+    let mut index = 0;
+    while index < changed.len() {
+        // Grow the hunk while the next change is within reach of its context
+        let start = changed[index]
+            .saturating_sub(CONTEXT_ELEMENTS)
+            .max(shown_until);
+        let mut end = changed[index] + 1;
+        index += 1;
+        while index < changed.len() && changed[index] <= end + 2 * CONTEXT_ELEMENTS {
+            end = changed[index] + 1;
+            index += 1;
+        }
+        let end = (end + CONTEXT_ELEMENTS).min(entries.len());
+
+        let (old_start, new_start) = hunk_start(entries, start);
+        output.push_str(&format!("@@ -{old_start} +{new_start} @@\n"));
+        for entry in &entries[start..end] {
+            push_entry(&mut output, entry);
+        }
+        shown_until = end;
+    }
+    output
+}
+
+/// Unified output from [`format_diff`] or [`format_outline_diff`] for a
+/// color terminal: removals in red, additions in green, table changes,
+/// renames and moves in yellow and hunk headers in cyan
+pub fn colorize_unified(unified: &str) -> String {
+    let mut output = String::new();
+    for (index, line) in unified.lines().enumerate() {
+        // The `---`/`+++` file names come first; later lines start with a marker
+        let style = match line.chars().next() {
+            _ if index < 2 => Some(BOLD),
+            Some('@') => Some(CYAN),
+            Some('-') => Some(RED),
+            Some('+') => Some(GREEN),
+            Some('~' | '>') => Some(YELLOW),
+            _ => None,
+        };
+        match style {
+            Some(style) => output.push_str(&paint(line, style)),
+            None => output.push_str(line),
+        }
+        output.push('\n');
+    }
+    output
+}
+
+/// 1-based element numbers where a hunk starting at `start` begins
+fn hunk_start(entries: &[DiffEntry], start: usize) -> (usize, usize) {
+    let next_after = |element: fn(&DiffEntry) -> Option<usize>| {
+        entries[start..]
+            .iter()
+            .find_map(element)
+            .or_else(|| {
+                entries[..start]
+                    .iter()
+                    .rev()
+                    .find_map(element)
+                    .map(|e| e + 1)
+            })
+            .unwrap_or(0)
+            + 1
+    };
+    (
+        next_after(DiffEntry::old_element),
+        next_after(DiffEntry::new_element),
+    )
+}
+
+fn push_entry(output: &mut String, entry: &DiffEntry) {
+    let mut push_lines = |marker: char, text: &str| {
+        for line in text.lines() {
+            output.push_str(&format!("{marker} {line}\n"));
+        }
+    };
+    match entry {
+        DiffEntry::Unchanged { text, .. } => push_lines(' ', text),
+        DiffEntry::Removed { text, .. } => push_lines('-', text),
+        DiffEntry::Added { text, .. } => push_lines('+', text),
+        DiffEntry::Changed { old, new, .. } => {
+            push_lines('-', old);
+            push_lines('+', new);
+        }
+        DiffEntry::TableChanged { title, cells, .. } => {
+            match title {
+                Some(title) => push_lines('~', &format!("Table \"{title}\"")),
+                None => push_lines('~', "Table"),
+            }
+            for cell in cells {
+                push_lines('~', &format!("  {}", describe_cell_change(cell)));
+            }
+        }
+    }
+}
+
+/// `row 2, column 3: "$19.99" -> "$21.99"`
+pub fn describe_cell_change(cell: &CellChange) -> String {
+    let change = match (&cell.old, &cell.new) {
+        (Some(old), Some(new)) => format!("\"{old}\" -> \"{new}\""),
+        (Some(old), None) => format!("removed \"{old}\""),
+        (None, Some(new)) => format!("added \"{new}\""),
+        (None, None) => String::new(),
+    };
+    format!("row {}, column {}: {change}", cell.row, cell.column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::{DocumentMetadata, TextFormatting};
+
+    fn document(path: &str, paragraphs: &[&str]) -> Document {
+        Document {
+            title: path.to_string(),
+            metadata: DocumentMetadata {
+                file_path: path.to_string(),
+                file_size: 0,
+                word_count: 0,
+                page_count: 1,
+                created: None,
+                modified: None,
+                author: None,
+            },
+            elements: paragraphs
+                .iter()
+                .map(|text| DocumentElement::Paragraph {
+                    text: text.to_string(),
+                    formatting: TextFormatting::default(),
+                    runs: Vec::new(),
+                    style: None,
+                })
+                .collect(),
+            image_options: ImageOptions::default(),
+            warnings: Vec::new(),
+            bookmarks: Default::default(),
+            pages: Default::default(),
+            hygiene: Default::default(),
+            image_dir: None,
+        }
+    }
+
+    #[test]
+    fn test_pairs_similar_paragraphs_as_changed() {
+        let old = document(
+            "old.docx",
+            &["Terms", "Payment is due within 30 days.", "Old clause"],
+        );
+        let new = document(
+            "new.docx",
+            &[
+                "Terms",
+                "Payment is due within 45 days.",
+                "A new clause entirely",
+            ],
+        );
+        let diff = diff_documents(&old, &new);
+
+        assert_eq!(
+            diff.summary(),
+            DiffSummary {
+                added: 1,
+                removed: 1,
+                changed: 1
+            }
+        );
+        assert!(matches!(
+            &diff.entries[1],
+            DiffEntry::Changed { old, .. } if old.contains("30 days")
+        ));
+
+        let unified = format_diff(&diff, DiffFormat::Unified).unwrap();
+        assert!(unified.starts_with("--- old.docx\n+++ new.docx\n@@ -1 +1 @@\n  Terms\n"));
+        assert!(unified
+            .contains("- Payment is due within 30 days.\n+ Payment is due within 45 days.\n"));
+
+        let colored = colorize_unified(&unified);
+        assert!(colored.starts_with("\x1b[1m--- old.docx\x1b[0m\n"));
+        assert!(colored.contains("\n  Terms\n\x1b[31m- Payment is due within 30 days.\x1b[0m\n"));
+    }
+
+    /// A document of headings (`#`, `##`, ...) and paragraphs
+    fn outline_document(path: &str, lines: &[&str]) -> Document {
+        let mut document = document(path, lines);
+        for element in &mut document.elements {
+            let DocumentElement::Paragraph { text, .. } = element else {
+                continue;
+            };
+            let title = text.trim_start_matches('#');
+            let level = (text.len() - title.len()) as u8;
+            if level > 0 {
+                *element = DocumentElement::Heading {
+                    level,
+                    text: title.trim().to_string(),
+                    number: None,
+                    style: None,
+                    source_level: None,
+                };
+            }
+        }
+        document
+    }
+
+    #[test]
+    fn test_diff_outlines() {
+        let old = outline_document(
+            "old.docx",
+            &[
+                "# Overview",
+                "What this handbook covers.",
+                "# Policies",
+                "## Leave",
+                "## Remote Work",
+                "Up to three days a week.",
+                "# Security",
+                "# Appendix",
+            ],
+        );
+        let new = outline_document(
+            "new.docx",
+            &[
+                "# Introduction",
+                "What this handbook covers.",
+                "# Policies",
+                "## Leave",
+                "# Security",
+                "## Remote Work",
+                "Up to three days a week.",
+                "# Contacts",
+            ],
+        );
+        let changes = diff_outlines(&old, &new);
+        let lines: Vec<String> = changes.iter().map(describe_section_change).collect();
+        assert_eq!(
+            lines,
+            [
+                "~ Overview -> Introduction",
+                "> Policies > Remote Work -> Security > Remote Work",
+                "- Appendix",
+                "+ Contacts",
+            ]
+        );
+        assert_eq!(changes[1].new.as_ref().unwrap().element, 5);
+
+        assert!(diff_outlines(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_word_diff() {
+        assert_eq!(
+            word_diff("due within 30 days", "due within 45 days"),
+            vec![
+                (WordChange::Unchanged, "due within".to_string()),
+                (WordChange::Removed, "30".to_string()),
+                (WordChange::Added, "45".to_string()),
+                (WordChange::Unchanged, "days".to_string()),
+            ]
+        );
+    }
+}
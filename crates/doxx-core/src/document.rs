@@ -0,0 +1,4173 @@
+use crate::error::{identify_non_package, DoxxError, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::io::{Read, Seek};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::alt_text::{describe_from_captions, scan_alt_text, AltText};
+use crate::chart::{parse_chart, relationship_targets, scan_chart_references};
+use crate::events::{Event, EventSink};
+use crate::hygiene::{is_invisible, scan_package, Hygiene};
+use crate::limits::{check_package, Limits};
+use crate::math::{scan_equations, Equation};
+use crate::pages::{scan_page_starts, scan_sections, PageMap};
+
+type TableRows = Vec<Vec<TableCell>>;
+type NumberingInfo = (i32, u8);
+type HeadingNumberInfo = (String, String);
+
+/// Smallest and largest `--image-scale`
+pub const IMAGE_SCALE_RANGE: std::ops::RangeInclusive<f32> = 0.1..=2.0;
+
+/// Image rendering options
+#[derive(Debug, Clone, Default)]
+pub struct ImageOptions {
+    pub enabled: bool,
+    /// Most terminal columns an image takes (default: the terminal's width)
+    pub max_width: Option<u32>,
+    /// Most terminal rows an image takes (default: the terminal's height)
+    pub max_height: Option<u32>,
+    /// Size of images relative to their default size
+    pub scale: Option<f32>,
+}
+
+impl ImageOptions {
+    /// [`Self::scale`], or 1.0, kept within [`IMAGE_SCALE_RANGE`]
+    pub fn scale_factor(&self) -> f32 {
+        self.scale
+            .unwrap_or(1.0)
+            .clamp(*IMAGE_SCALE_RANGE.start(), *IMAGE_SCALE_RANGE.end())
+    }
+}
+
+/// Check a scale given for [`ImageOptions::scale`]
+pub fn parse_image_scale(value: &str) -> Result<f32, String> {
+    let scale: f32 = value
+        .parse()
+        .map_err(|_| format!("expected a number such as 0.5, not '{value}'"))?;
+    if !IMAGE_SCALE_RANGE.contains(&scale) {
+        return Err(format!(
+            "expected a scale from {:?} to {:?}",
+            IMAGE_SCALE_RANGE.start(),
+            IMAGE_SCALE_RANGE.end()
+        ));
+    }
+    Ok(scale)
+}
+
+/// Options that change how document content is interpreted
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// Give headings without numbering of their own synthetic numbers (1, 1.1, ...)
+    pub auto_number_headings: bool,
+    /// How tracked insertions and deletions are resolved
+    pub changes: TrackedChanges,
+    /// Elements per page when the document has no page information
+    /// (0 = estimate from the word count)
+    pub elements_per_page: usize,
+    /// Where loading progress is reported
+    pub events: EventSink,
+    /// Fail on the first content that can't be represented instead of
+    /// skipping it with a warning
+    pub strict: bool,
+    /// Size and nesting limits checked before the document is parsed
+    pub limits: Limits,
+    /// Start headings at level 1 and close skipped levels (H1 then H3
+    /// becomes H1 then H2), keeping each heading's own level as `source_level`
+    pub normalize_heading_levels: bool,
+    /// Metadata replacing the document's own (`--title`, `--author`, `--date`)
+    pub overrides: MetadataOverrides,
+    /// Decode text that was double-encoded, e.g. `â€¢` back to `•`
+    pub fix_encoding: bool,
+    /// Keep only the sections under the headings matching this path (see
+    /// [`heading_sections`]). This filters after parsing: docx-rs still reads
+    /// the whole body, then blocks outside the sections are dropped before
+    /// elements are built and pictures extracted.
+    pub section: Option<String>,
+    /// Report the first elements in an [`Event::Preview`] as soon as this
+    /// many are parsed, ahead of the rest of the body (0 = no preview)
+    pub preview_elements: usize,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            auto_number_headings: true,
+            changes: TrackedChanges::default(),
+            elements_per_page: 0,
+            events: EventSink::default(),
+            strict: false,
+            limits: Limits::default(),
+            normalize_heading_levels: false,
+            overrides: MetadataOverrides::default(),
+            fix_encoding: false,
+            section: None,
+            preview_elements: 0,
+        }
+    }
+}
+
+/// Metadata to use instead of what the document's properties say, for
+/// documents with a wrong or missing title, author or date
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetadataOverrides {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    /// Replaces both the created and modified dates
+    pub date: Option<String>,
+}
+
+impl MetadataOverrides {
+    pub fn apply(&self, document: &mut Document) {
+        if let Some(title) = &self.title {
+            document.title = title.clone();
+        }
+        if let Some(author) = &self.author {
+            document.metadata.author = Some(author.clone());
+        }
+        if let Some(date) = &self.date {
+            document.metadata.created = Some(date.clone());
+            document.metadata.modified = Some(date.clone());
+        }
+    }
+}
+
+static DATE_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^\d{4}-(0[1-9]|1[0-2])-(0[1-9]|[12]\d|3[01])(T\d{2}:\d{2}(:\d{2}(\.\d+)?)?(Z|[+-]\d{2}:\d{2})?)?$",
+    )
+    .unwrap()
+});
+
+/// Check a date given for [`MetadataOverrides::date`]: `YYYY-MM-DD`,
+/// optionally with a time as in document properties (`2024-05-01T09:30:00Z`)
+pub fn parse_date(value: &str) -> Result<String, String> {
+    if !DATE_RE.is_match(value) {
+        return Err("expected a date such as 2024-05-01 or 2024-05-01T09:30:00Z".to_string());
+    }
+    Ok(value.to_string())
+}
+
+/// How tracked changes (revisions) in the document are shown
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum TrackedChanges {
+    /// The document as if every change were accepted
+    #[default]
+    Accept,
+    /// The document as it was before the changes
+    Reject,
+    /// Both versions, with `{++inserted++}` and `{--deleted--}` CriticMarkup
+    Markup,
+}
+
+/// Kind of tracked change a run belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Revision {
+    Inserted,
+    Deleted,
+}
+
+impl Revision {
+    /// Wrap `text` in the CriticMarkup for this kind of change
+    pub fn markup(self, text: &str) -> String {
+        match self {
+            Revision::Inserted => format!("{{++{text}++}}"),
+            Revision::Deleted => format!("{{--{text}--}}"),
+        }
+    }
+}
+
+/// How [`search_document_with`] compares the query with document text
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions {
+    /// Ignore accents and tolerate small typos in each query word
+    pub fuzzy: bool,
+    /// Part of the document to look in
+    pub scope: SearchScope,
+    /// Where the finished search is reported
+    pub events: EventSink,
+}
+
+/// Part of the document a search looks in
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum SearchScope {
+    /// The whole document
+    #[default]
+    All,
+    /// The section containing this element (see [`section_range`])
+    Section(usize),
+    /// Every section under a heading matching a path such as `3. Risks` or
+    /// `Risks > Market` (see [`heading_sections`])
+    Heading(String),
+    /// Tables only
+    Tables,
+}
+
+/// Which headings the outline lists and how they are labelled
+#[derive(Debug, Clone)]
+pub struct OutlineOptions {
+    /// Deepest heading level to include (`None` = all levels)
+    pub max_depth: Option<u8>,
+    pub show_numbers: bool,
+    /// Entries to make up for a document with no headings at all
+    pub synthesize: Option<OutlineSynthesis>,
+}
+
+impl Default for OutlineOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: None,
+            show_numbers: true,
+            synthesize: None,
+        }
+    }
+}
+
+/// Where outline entries come from in a document without headings
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutlineSynthesis {
+    /// Short paragraphs that are entirely bold, used as headings
+    Bold,
+    /// The start of each page
+    Pages,
+    /// Every N paragraphs
+    Every(usize),
+}
+
+impl FromStr for OutlineSynthesis {
+    type Err = String;
+
+    /// `bold`, `pages`, or a paragraph count such as `10`
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim() {
+            "bold" => Ok(Self::Bold),
+            "pages" => Ok(Self::Pages),
+            count => match count.parse() {
+                Ok(count) if count > 0 => Ok(Self::Every(count)),
+                _ => Err(format!(
+                    "expected bold, pages or a paragraph count, found '{value}'"
+                )),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Document {
+    pub title: String,
+    pub metadata: DocumentMetadata,
+    pub elements: Vec<DocumentElement>,
+    #[serde(skip)]
+    pub image_options: ImageOptions,
+    /// Content that was skipped or only partly understood while parsing
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<ParseWarning>,
+    /// User bookmarks, named by a lowercase letter (`a`, or `й` typed on a
+    /// Russian layout), mapping to element indices, loaded from the state store
+    #[serde(skip)]
+    pub bookmarks: BTreeMap<char, usize>,
+    /// Page each element starts on
+    #[serde(skip)]
+    pub pages: PageMap,
+    /// Macros, external content, tracked changes and hidden text found in the package
+    #[serde(default, skip_serializing_if = "Hygiene::is_clean")]
+    pub hygiene: Hygiene,
+    /// Temporary directory holding the extracted images, removed once the
+    /// document and its clones are dropped
+    #[serde(skip)]
+    pub image_dir: Option<Arc<tempfile::TempDir>>,
+}
+
+impl Document {
+    /// Parse a .docx package held in memory
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        Self::from_bytes_with(data, ImageOptions::default(), ParseOptions::default())
+    }
+
+    /// Parse a .docx package held in memory, with the options of
+    /// [`load_document`]. The document has no file path, and its title falls
+    /// back to "Untitled Document" when the package doesn't set one.
+    pub fn from_bytes_with(
+        data: &[u8],
+        image_options: ImageOptions,
+        parse_options: ParseOptions,
+    ) -> Result<Self> {
+        parse_package(data, None, image_options, parse_options)
+    }
+
+    /// Parse a .docx package read from the start of `reader`
+    pub fn from_reader(mut reader: impl Read + Seek) -> Result<Self> {
+        let mut data = Vec::new();
+        reader.rewind()?;
+        reader.read_to_end(&mut data)?;
+        Self::from_bytes(&data)
+    }
+}
+
+/// Main document part inside the DOCX package
+const DOCUMENT_PART: &str = "word/document.xml";
+
+/// Where a piece of content lives in the DOCX package, e.g.
+/// `word/document.xml: paragraph 241`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceLocation {
+    pub part: String,
+    /// 1-based paragraph number among the body's paragraphs
+    pub paragraph: Option<usize>,
+    /// 1-based table number among the body's tables
+    pub table: Option<usize>,
+    /// The content sits between blocks, just after this paragraph or table
+    /// (or at the start of the body when neither is set)
+    #[serde(default)]
+    pub after: bool,
+}
+
+impl SourceLocation {
+    pub fn paragraph(number: usize) -> Self {
+        Self {
+            part: DOCUMENT_PART.to_string(),
+            paragraph: Some(number),
+            table: None,
+            after: false,
+        }
+    }
+
+    pub fn table(number: usize) -> Self {
+        Self {
+            part: DOCUMENT_PART.to_string(),
+            paragraph: None,
+            table: Some(number),
+            after: false,
+        }
+    }
+
+    /// Between blocks, just after `block` or at the start of the body
+    pub fn after(block: Option<&SourceLocation>) -> Self {
+        Self {
+            part: DOCUMENT_PART.to_string(),
+            paragraph: block.and_then(|block| block.paragraph),
+            table: block.and_then(|block| block.table),
+            after: true,
+        }
+    }
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.part)?;
+        let after = if self.after { "after " } else { "" };
+        if let Some(paragraph) = self.paragraph {
+            write!(f, ": {after}paragraph {paragraph}")?;
+        }
+        if let Some(table) = self.table {
+            write!(f, ": {after}table {table}")?;
+        }
+        if self.after && self.paragraph.is_none() && self.table.is_none() {
+            write!(f, ": start of body")?;
+        }
+        Ok(())
+    }
+}
+
+/// Non-fatal problem found while parsing, tied to where it happened
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParseWarning {
+    pub location: SourceLocation,
+    pub message: String,
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.message)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DocumentMetadata {
+    pub file_path: String,
+    pub file_size: u64,
+    pub word_count: usize,
+    pub page_count: usize,
+    pub created: Option<String>,
+    pub modified: Option<String>,
+    pub author: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DocumentElement {
+    Heading {
+        level: u8,
+        text: String,
+        number: Option<String>,
+        /// Display name of the paragraph style (e.g. "heading 2")
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        style: Option<String>,
+        /// Level in the document, when normalizing heading levels changed it
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        source_level: Option<u8>,
+    },
+    Paragraph {
+        text: String,
+        formatting: TextFormatting,
+        /// Formatting per run, with neighbouring runs that look alike merged
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        runs: Vec<TextRun>,
+        /// Display name of the paragraph style (e.g. "Quote")
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        style: Option<String>,
+    },
+    List {
+        items: Vec<ListItem>,
+        ordered: bool,
+        /// Number of the first item when it isn't 1, e.g. a list resumed
+        /// after a paragraph
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        start: Option<u32>,
+    },
+    Table {
+        table: TableData,
+    },
+    Image {
+        description: String,
+        width: Option<u32>,
+        height: Option<u32>,
+        relationship_id: Option<String>, // Link to DOCX relationship for image extraction
+        image_path: Option<std::path::PathBuf>, // Path to extracted image file
+        /// Text recognized in the image by `--ocr`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ocr_text: Option<String>,
+    },
+    PageBreak,
+    /// Block quotation (Word's Quote and Intense Quote styles)
+    Quote {
+        text: String,
+        intense: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        style: Option<String>,
+    },
+    /// Preformatted text such as source code; consecutive paragraphs in a
+    /// code style become one block, one entry per paragraph
+    CodeBlock {
+        lines: Vec<String>,
+        /// Language for syntax highlighting, from a fence or guessed
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        language: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        style: Option<String>,
+    },
+    /// Caption of a figure or table
+    Caption {
+        text: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        style: Option<String>,
+        /// What the caption's `SEQ` field numbers ("Figure", "Table")
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        sequence: Option<String>,
+    },
+    /// Document title (Word's Title style), not part of the outline
+    Title {
+        text: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        style: Option<String>,
+    },
+    Subtitle {
+        text: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        style: Option<String>,
+    },
+    /// Equation on a line of its own, as LaTeX and as a Unicode approximation
+    Equation {
+        latex: String,
+        text: String,
+    },
+}
+
+impl DocumentElement {
+    /// Words of text in the element, including list items, table cells and
+    /// code lines
+    pub fn word_count(&self) -> usize {
+        let words = |text: &str| text.split_whitespace().count();
+        match self {
+            DocumentElement::Heading { text, .. }
+            | DocumentElement::Paragraph { text, .. }
+            | DocumentElement::Quote { text, .. }
+            | DocumentElement::Caption { text, .. }
+            | DocumentElement::Title { text, .. }
+            | DocumentElement::Subtitle { text, .. }
+            | DocumentElement::Equation { text, .. } => words(text),
+            DocumentElement::List { items, .. } => items.iter().map(|item| words(&item.text)).sum(),
+            DocumentElement::Table { table } => table
+                .headers
+                .iter()
+                .chain(table.rows.iter().flatten())
+                .map(|cell| words(&cell.content))
+                .sum(),
+            DocumentElement::CodeBlock { lines, .. } => lines.iter().map(|line| words(line)).sum(),
+            DocumentElement::Image { .. } | DocumentElement::PageBreak => 0,
+        }
+    }
+}
+
+/// Fonts whose characters all have the same width, as used for code
+const MONOSPACE_FONTS: &[&str] = &[
+    "consolas",
+    "courier",
+    "courier new",
+    "lucida console",
+    "menlo",
+    "monaco",
+    "source code pro",
+    "fira code",
+    "fira mono",
+    "jetbrains mono",
+    "cascadia code",
+    "cascadia mono",
+    "dejavu sans mono",
+    "liberation mono",
+    "roboto mono",
+    "ubuntu mono",
+    "sf mono",
+];
+
+fn is_monospace_font(font: &str) -> bool {
+    MONOSPACE_FONTS.contains(&font.trim().to_lowercase().as_str())
+}
+
+/// Identifier of the first `SEQ` field in a paragraph, as in
+/// `SEQ Figure \* ARABIC`
+fn sequence_field(para: &docx_rs::Paragraph) -> Option<String> {
+    para.children.iter().find_map(|child| {
+        let docx_rs::ParagraphChild::Run(run) = child else {
+            return None;
+        };
+        run.children.iter().find_map(|child| match child {
+            docx_rs::RunChild::InstrTextString(instruction) => {
+                let mut words = instruction.split_whitespace();
+                (words.next() == Some("SEQ"))
+                    .then(|| words.next().map(str::to_string))
+                    .flatten()
+            }
+            _ => None,
+        })
+    })
+}
+
+/// Alignment set on a paragraph (`w:jc`)
+fn paragraph_alignment(para: &docx_rs::Paragraph) -> TextAlignment {
+    match para.property.alignment.as_ref().map(|jc| jc.val.as_str()) {
+        Some("center") => TextAlignment::Center,
+        Some("right" | "end") => TextAlignment::Right,
+        Some("both" | "distribute") => TextAlignment::Justify,
+        _ => TextAlignment::Left,
+    }
+}
+
+/// Latin font of a run, when set on the run itself
+fn run_font(run: &docx_rs::Run) -> Option<String> {
+    // RunFonts keeps its fields private; read the font from its serialized form
+    let fonts = serde_json::to_value(run.run_property.fonts.as_ref()?).ok()?;
+    fonts.get("ascii")?.as_str().map(str::to_string)
+}
+
+/// Paragraph styles rendered as their own kind of element
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BlockStyle {
+    Quote { intense: bool },
+    Code,
+    Caption,
+    Title,
+    Subtitle,
+}
+
+/// Recognize a style by its display name or id, ignoring case and spacing
+/// (`Intense Quote`, `IntenseQuote`, `HTML Preformatted`)
+fn block_style(style: &str) -> Option<BlockStyle> {
+    let key: String = style
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect();
+    match key.as_str() {
+        "quote" | "blockquote" | "blocktext" => Some(BlockStyle::Quote { intense: false }),
+        "intensequote" => Some(BlockStyle::Quote { intense: true }),
+        "code" | "sourcecode" | "htmlpreformatted" | "plaintext" | "macrotext" => {
+            Some(BlockStyle::Code)
+        }
+        "caption" => Some(BlockStyle::Caption),
+        "title" => Some(BlockStyle::Title),
+        "subtitle" => Some(BlockStyle::Subtitle),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TextFormatting {
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub font_size: Option<f32>,
+    pub color: Option<String>,
+    /// Alignment of the paragraph; runs leave it at the default
+    #[serde(default)]
+    pub alignment: TextAlignment,
+    /// Not visible in Word: marked hidden (`w:vanish`) or colored like its
+    /// background. Set on a paragraph when all of its text is.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hidden: bool,
+}
+
+/// A stretch of paragraph text with its own character formatting
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TextRun {
+    pub text: String,
+    pub formatting: TextFormatting,
+    /// Set for tracked changes shown with `--changes markup`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<Revision>,
+    /// LaTeX of an equation in the text; `text` holds its Unicode approximation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub math: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ListItem {
+    pub text: String,
+    pub level: u8,
+    /// Whether the item is numbered, when that differs from its list
+    /// (numbered steps nested in a bulleted list, or the reverse)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ordered: Option<bool>,
+    /// Number Word shows for the item (e.g. `2.1.` or `(a)`), used instead of
+    /// counting the items
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl ListItem {
+    pub fn is_ordered(&self, list_ordered: bool) -> bool {
+        self.ordered.unwrap_or(list_ordered)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableData {
+    pub headers: Vec<TableCell>,
+    pub rows: Vec<Vec<TableCell>>,
+    pub metadata: TableMetadata,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableCell {
+    pub content: String,
+    pub alignment: TextAlignment,
+    pub formatting: TextFormatting,
+    pub data_type: CellDataType,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableMetadata {
+    pub column_count: usize,
+    pub row_count: usize,
+    pub has_headers: bool,
+    pub column_widths: Vec<usize>,
+    pub column_alignments: Vec<TextAlignment>,
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum TextAlignment {
+    #[default]
+    Left,
+    Center,
+    Right,
+    Justify,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum CellDataType {
+    #[default]
+    Text,
+    Number,
+    Currency,
+    Percentage,
+    Date,
+    Boolean,
+    Empty,
+}
+
+impl CellDataType {
+    /// Lowercase name, as written in exports and read by `--column-types`
+    pub fn name(self) -> &'static str {
+        match self {
+            CellDataType::Text => "text",
+            CellDataType::Number => "number",
+            CellDataType::Currency => "currency",
+            CellDataType::Percentage => "percentage",
+            CellDataType::Date => "date",
+            CellDataType::Boolean => "boolean",
+            CellDataType::Empty => "empty",
+        }
+    }
+}
+
+impl std::str::FromStr for CellDataType {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "text" => Ok(CellDataType::Text),
+            "number" => Ok(CellDataType::Number),
+            "currency" => Ok(CellDataType::Currency),
+            "percentage" | "percent" => Ok(CellDataType::Percentage),
+            "date" => Ok(CellDataType::Date),
+            "boolean" | "bool" => Ok(CellDataType::Boolean),
+            other => Err(format!(
+                "unknown column type '{other}' (expected text, number, currency, percentage, date or boolean)"
+            )),
+        }
+    }
+}
+
+/// Parse a `--column-types` list such as `Price:currency,Date:date` into
+/// column references and types. A column is named by its header or by its
+/// 1-based number.
+pub fn parse_column_types(spec: &str) -> std::result::Result<Vec<(String, CellDataType)>, String> {
+    spec.split(',')
+        .filter(|entry| !entry.trim().is_empty())
+        .map(|entry| {
+            let (column, data_type) = entry
+                .rsplit_once(':')
+                .ok_or_else(|| format!("expected COLUMN:TYPE, got '{}'", entry.trim()))?;
+            let column = column.trim();
+            if column.is_empty() {
+                return Err(format!("missing column in '{}'", entry.trim()));
+            }
+            Ok((column.to_string(), data_type.parse()?))
+        })
+        .collect()
+}
+
+/// Force the type of the named columns in every table that has them,
+/// realigning the cells to match. Columns a table doesn't have are skipped.
+pub fn override_column_types(document: &mut Document, overrides: &[(String, CellDataType)]) {
+    if overrides.is_empty() {
+        return;
+    }
+    for element in &mut document.elements {
+        if let DocumentElement::Table { table } = element {
+            for (column, data_type) in overrides {
+                if let Some(index) = table.column_index(column) {
+                    table.set_column_type(index, *data_type);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    pub element_index: usize,
+    pub text: String,
+    #[allow(dead_code)]
+    pub start_pos: usize,
+    #[allow(dead_code)]
+    pub end_pos: usize,
+}
+
+pub async fn load_document(
+    file_path: &Path,
+    image_options: ImageOptions,
+    parse_options: ParseOptions,
+) -> Result<Document> {
+    let file_data = std::fs::read(file_path)?;
+    parse_package(&file_data, Some(file_path), image_options, parse_options)
+}
+
+/// Parse the package in `file_data`, read from `file_path` when it came from
+/// a file. Errors name the part at fault.
+pub(crate) fn parse_package(
+    file_data: &[u8],
+    file_path: Option<&Path>,
+    image_options: ImageOptions,
+    parse_options: ParseOptions,
+) -> Result<Document> {
+    let file_size = file_data.len() as u64;
+    let events = &parse_options.events;
+    events.emit(Event::LoadStarted {
+        path: file_path.map(Path::to_path_buf).unwrap_or_default(),
+        size: file_size,
+    });
+
+    if let Some(error) = identify_non_package(file_data) {
+        return Err(error);
+    }
+    check_package(file_data, &parse_options.limits)?;
+    let docx = docx_rs::read_docx(file_data).map_err(|e| match e {
+        docx_rs::ReaderError::ZipError(e) => DoxxError::CorruptArchive(e.to_string()),
+        e => DoxxError::parse(reader_error_part(&e), e),
+    })?;
+
+    let core_properties = read_core_properties(file_data);
+    let hygiene = scan_package(file_data);
+    let document_xml = read_package_part(file_data, DOCUMENT_PART);
+    let sections = document_xml
+        .as_deref()
+        .map(scan_sections)
+        .unwrap_or_default();
+    let mut page_starts = document_xml
+        .as_deref()
+        .map(scan_page_starts)
+        .unwrap_or_default();
+    // Section breaks other than continuous ones start a page of their own
+    page_starts.extend(
+        sections
+            .iter()
+            .filter(|section| section.starts_page())
+            .map(|section| section.first_block),
+    );
+    let equations = document_xml
+        .as_deref()
+        .map(scan_equations)
+        .unwrap_or_default();
+    let charts = document_xml
+        .as_deref()
+        .map(scan_chart_references)
+        .unwrap_or_default();
+    let alt_texts = document_xml
+        .as_deref()
+        .map(scan_alt_text)
+        .unwrap_or_default();
+    let relationships = read_package_part(file_data, "word/_rels/document.xml.rels")
+        .map(|xml| relationship_targets(&xml))
+        .unwrap_or_default();
+
+    let title = core_properties.title.clone().unwrap_or_else(|| {
+        file_path
+            .and_then(Path::file_stem)
+            .and_then(|s| s.to_str())
+            .unwrap_or("Untitled Document")
+            .to_string()
+    });
+
+    let style_names = style_names(&docx.styles);
+
+    let mut elements = Vec::new();
+    let mut word_count = 0;
+    let mut numbering_manager = DocumentNumberingManager::new();
+    let mut heading_tracker = HeadingNumberTracker::new();
+    let mut heading_levels = HeadingLevels::default();
+
+    // Analyze document structure to determine if auto-numbering should be enabled
+    let should_auto_number =
+        parse_options.auto_number_headings && analyze_heading_structure(&docx.document);
+    if should_auto_number {
+        heading_tracker.enable_auto_numbering();
+    }
+
+    // A section asked for is found from the heading styles of the parsed
+    // body, then blocks outside it are dropped before they become elements
+    // or have their pictures extracted
+    let selected_blocks = match &parse_options.section {
+        Some(path) => {
+            let blocks = section_blocks(&docx.document, path, &parse_options, should_auto_number);
+            if blocks.is_empty() {
+                return Err(DoxxError::SectionNotFound(path.clone()));
+            }
+            Some(blocks)
+        }
+        None => None,
+    };
+    let is_selected = |block: usize| {
+        selected_blocks
+            .as_ref()
+            .is_none_or(|blocks| blocks.iter().any(|range| range.contains(&block)))
+    };
+
+    // Map relationship ids (r:embed) to media file names so drawings can be
+    // resolved to their extracted files
+    let image_targets: HashMap<String, String> = docx
+        .images
+        .iter()
+        .filter_map(|(id, path, ..)| {
+            let name = Path::new(path).file_name()?.to_str()?.to_string();
+            Some((id.clone(), name))
+        })
+        .collect();
+
+    let mut chart_count = 0;
+    let mut warnings = Vec::new();
+    let mut paragraph_number = 0;
+    let mut table_number = 0;
+    // The paragraph or table last read, for content found between blocks
+    let mut last_block = None;
+    // Page of each element so far, following the page breaks Word recorded
+    let mut element_pages = Vec::new();
+    // Paragraphs numbered by Word, by element index
+    let mut word_list_items = HashMap::new();
+    let mut current_page = 1;
+    let mut block_number = 0;
+    // Inside a ``` fence written into the document
+    let mut in_fence = false;
+    // The last element is a code block the next code paragraph continues
+    let mut code_open = false;
+    let mut previewed = parse_options.preview_elements == 0;
+
+    let block_count = docx
+        .document
+        .children
+        .iter()
+        .filter(|child| {
+            matches!(
+                child,
+                docx_rs::DocumentChild::Paragraph(_) | docx_rs::DocumentChild::Table(_)
+            )
+        })
+        .count();
+
+    // Enhanced content extraction with style information
+    for child in &docx.document.children {
+        if matches!(
+            child,
+            docx_rs::DocumentChild::Paragraph(_) | docx_rs::DocumentChild::Table(_)
+        ) {
+            if block_number > 0 && page_starts.contains(&block_number) {
+                current_page += 1;
+            }
+            block_number += 1;
+        }
+
+        // Content between blocks goes with the block after it
+        let is_block = matches!(
+            child,
+            docx_rs::DocumentChild::Paragraph(_) | docx_rs::DocumentChild::Table(_)
+        );
+        if !is_selected(block_number - usize::from(is_block)) {
+            // Blocks left out still count toward the locations of warnings,
+            // and headings toward the numbers of those read
+            match child {
+                docx_rs::DocumentChild::Paragraph(para) => {
+                    paragraph_number += 1;
+                    last_block = Some(SourceLocation::paragraph(paragraph_number));
+                    if let Some(info) = style_heading(para, parse_options.changes) {
+                        let (level, _) = heading_levels
+                            .place(info.level, parse_options.normalize_heading_levels);
+                        if info.number.is_none() {
+                            heading_tracker.get_number(level);
+                        }
+                    }
+                }
+                docx_rs::DocumentChild::Table(_) => {
+                    table_number += 1;
+                    last_block = Some(SourceLocation::table(table_number));
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        match child {
+            docx_rs::DocumentChild::Paragraph(para) => {
+                paragraph_number += 1;
+                let location = SourceLocation::paragraph(paragraph_number);
+                last_block = Some(location.clone());
+                let mut text = String::new();
+                let mut formatting = TextFormatting::default();
+                let mut runs: Vec<TextRun> = Vec::new();
+                let style = para
+                    .property
+                    .style
+                    .as_ref()
+                    .map(|style| style_names.get(&style.val).unwrap_or(&style.val).clone());
+
+                // Check for heading with potential numbering first
+                let heading_info = detect_heading_with_numbering(para, parse_options.changes);
+
+                // Check for list numbering properties (Word's automatic lists)
+                let list_info = detect_list_from_paragraph_numbering(para);
+                let sequence = sequence_field(para);
+
+                // Locate images in this paragraph, keeping track of whether they
+                // appear before or after the paragraph's text
+                let mut images_before = Vec::new();
+                let mut images_after = Vec::new();
+                let mut seen_text = false;
+                // Drawings docx-rs can't read that are the charts found here
+                let mut charts_left = charts.get(&(block_number - 1)).map_or(0, Vec::len);
+                let mut paragraph_alt_texts = alt_texts
+                    .get(&(block_number - 1))
+                    .map(Vec::as_slice)
+                    .unwrap_or_default()
+                    .iter();
+                for child in &para.children {
+                    if let docx_rs::ParagraphChild::Run(run) = child {
+                        for run_child in &run.children {
+                            match run_child {
+                                docx_rs::RunChild::Text(text_elem) => {
+                                    seen_text |= !text_elem.text.trim().is_empty();
+                                }
+                                docx_rs::RunChild::Drawing(drawing) => {
+                                    match image_element_from_drawing(
+                                        drawing,
+                                        paragraph_alt_texts.next(),
+                                    ) {
+                                        Ok(image) => {
+                                            if let DocumentElement::Image {
+                                                relationship_id: Some(id),
+                                                ..
+                                            } = &image
+                                            {
+                                                if !image_targets.contains_key(id) {
+                                                    warnings.push(ParseWarning {
+                                                        location: location.clone(),
+                                                        message: format!(
+                                                            "picture relationship {id} not found in package"
+                                                        ),
+                                                    });
+                                                }
+                                            }
+                                            if seen_text {
+                                                images_after.push(image);
+                                            } else {
+                                                images_before.push(image);
+                                            }
+                                        }
+                                        Err(_) if drawing.data.is_none() && charts_left > 0 => {
+                                            charts_left -= 1;
+                                        }
+                                        Err(message) => warnings.push(ParseWarning {
+                                            location: location.clone(),
+                                            message,
+                                        }),
+                                    }
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                }
+                elements.append(&mut images_before);
+
+                for kind in skipped_paragraph_content(para) {
+                    warnings.push(ParseWarning {
+                        location: location.clone(),
+                        message: format!("unsupported {kind}, skipped"),
+                    });
+                }
+
+                // Extract text and formatting from runs
+                let mut monospace = true;
+                for (run, revision) in paragraph_runs(para, parse_options.changes) {
+                    // Extract formatting from run properties
+                    if !formatting.bold && !formatting.italic {
+                        // Only extract formatting from the first run with properties
+                        formatting = extract_run_formatting(run);
+                    }
+
+                    let mut run_text = String::new();
+                    for child in &run.children {
+                        match child {
+                            docx_rs::RunChild::Text(text_elem) => {
+                                run_text.push_str(&text_elem.text)
+                            }
+                            docx_rs::RunChild::DeleteText(deleted) => {
+                                run_text.push_str(&delete_text(deleted))
+                            }
+                            _ => {}
+                        }
+                    }
+                    if run_text.is_empty() {
+                        continue;
+                    }
+                    if !run_text.trim().is_empty() {
+                        monospace &= run_font(run).is_some_and(|font| is_monospace_font(&font));
+                    }
+                    if let Some(revision) = revision {
+                        run_text = revision.markup(&run_text);
+                    }
+                    text.push_str(&run_text);
+
+                    let run_formatting = extract_run_formatting(run);
+                    match runs.last_mut() {
+                        Some(last)
+                            if last.formatting == run_formatting && last.revision == revision =>
+                        {
+                            last.text.push_str(&run_text)
+                        }
+                        _ => runs.push(TextRun {
+                            text: run_text,
+                            formatting: run_formatting,
+                            revision,
+                            math: None,
+                        }),
+                    }
+                }
+                formatting.alignment = paragraph_alignment(para);
+                formatting.hidden =
+                    !runs.is_empty() && runs.iter().all(|run| run.formatting.hidden);
+
+                // Equations go into the text where they appear, or after it
+                // when set on lines of their own
+                let paragraph_equations = equations
+                    .get(&(block_number - 1))
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                insert_equations(&mut text, &mut runs, paragraph_equations);
+                let mut display_equations: Vec<DocumentElement> = paragraph_equations
+                    .iter()
+                    .filter(|equation| equation.display)
+                    .map(|equation| DocumentElement::Equation {
+                        latex: equation.latex.clone(),
+                        text: equation.text.clone(),
+                    })
+                    .collect();
+
+                // Code is a code paragraph style, text set entirely in a
+                // monospace font, or anything between ``` fences
+                let is_code = in_fence
+                    || matches!(
+                        style.as_deref().and_then(block_style),
+                        Some(BlockStyle::Code)
+                    )
+                    || (monospace
+                        && !text.trim().is_empty()
+                        && heading_info.is_none()
+                        && list_info.is_none());
+                // Blank lines inside a code block are part of the code
+                let continues_code = std::mem::take(&mut code_open) && is_code;
+
+                if let Some(info) = text.trim().strip_prefix("```") {
+                    if in_fence {
+                        in_fence = false;
+                    } else {
+                        let info = info.trim().to_lowercase();
+                        elements.push(DocumentElement::CodeBlock {
+                            lines: Vec::new(),
+                            language: (!info.is_empty()).then_some(info),
+                            style,
+                        });
+                        in_fence = true;
+                        code_open = true;
+                    }
+                } else if !text.trim().is_empty() || continues_code {
+                    word_count += text.split_whitespace().count();
+
+                    // Priority: list numbering > heading style > text heuristics
+                    if let Some(list_info) = list_info.filter(|_| !in_fence) {
+                        // This is an automatic Word list item - format with proper indentation
+                        let indent = "  ".repeat(list_info.level as usize);
+                        let prefix = if list_info.is_ordered {
+                            // Use the numbering manager for proper sequential numbering
+                            if let Some(num_id) = list_info.num_id {
+                                let format = get_numbering_format(num_id, list_info.level);
+                                numbering_manager.generate_number(num_id, list_info.level, format)
+                            } else {
+                                // Fallback for missing numId
+                                format!("{}. ", list_info.level + 1)
+                            }
+                        } else {
+                            "* ".to_string() // Bullets for unordered
+                        };
+                        word_list_items.insert(
+                            elements.len(),
+                            WordListItem {
+                                // numId 0 is Word's "no list"
+                                num_id: list_info.num_id.unwrap_or(0),
+                                level: list_info.level,
+                                label: list_info.is_ordered.then(|| prefix.trim_end().to_string()),
+                                text: text.trim().to_string(),
+                            },
+                        );
+                        // Mark Word-formatted list items with a special prefix to avoid reprocessing
+                        elements.push(DocumentElement::Paragraph {
+                            text: format!("__WORD_LIST__{}{}{}", indent, prefix, text.trim()),
+                            formatting,
+                            runs: Vec::new(),
+                            style,
+                        });
+                    } else if let Some(block) = if is_code {
+                        Some(BlockStyle::Code)
+                    } else if sequence.is_some() {
+                        // Numbered with a SEQ field, whatever its style
+                        Some(BlockStyle::Caption)
+                    } else {
+                        style.as_deref().and_then(block_style)
+                    } {
+                        match (block, elements.last_mut()) {
+                            (BlockStyle::Code, Some(DocumentElement::CodeBlock { lines, .. }))
+                                if continues_code =>
+                            {
+                                lines.push(text);
+                                code_open = true;
+                            }
+                            (BlockStyle::Code, _) => {
+                                elements.push(DocumentElement::CodeBlock {
+                                    lines: vec![text],
+                                    language: None,
+                                    style,
+                                });
+                                code_open = true;
+                            }
+                            (BlockStyle::Quote { intense }, _) => {
+                                elements.push(DocumentElement::Quote {
+                                    text: text.trim().to_string(),
+                                    intense,
+                                    style,
+                                })
+                            }
+                            (BlockStyle::Caption, _) => elements.push(DocumentElement::Caption {
+                                text: text.trim().to_string(),
+                                style,
+                                sequence: sequence.clone(),
+                            }),
+                            (BlockStyle::Title, _) => elements.push(DocumentElement::Title {
+                                text: text.trim().to_string(),
+                                style,
+                            }),
+                            (BlockStyle::Subtitle, _) => elements.push(DocumentElement::Subtitle {
+                                text: text.trim().to_string(),
+                                style,
+                            }),
+                        }
+                    } else {
+                        // Check for headings (with or without numbering)
+                        if let Some(heading_info) = heading_info {
+                            let heading_text = heading_info.clean_text.unwrap_or(text.clone());
+                            let (level, source_level) = heading_levels
+                                .place(heading_info.level, parse_options.normalize_heading_levels);
+
+                            let number = if heading_info.number.is_some() {
+                                heading_info.number
+                            } else {
+                                // Generate automatic numbering if enabled for this document
+                                let auto_number = heading_tracker.get_number(level);
+                                if auto_number.is_empty() {
+                                    None
+                                } else {
+                                    Some(auto_number)
+                                }
+                            };
+
+                            elements.push(DocumentElement::Heading {
+                                level,
+                                text: heading_text,
+                                number,
+                                style,
+                                source_level,
+                            });
+                        } else {
+                            // Fallback to text-based heading detection
+                            let level = detect_heading_from_text(&text, &formatting);
+                            if let Some(level) = level {
+                                let (level, source_level) = heading_levels
+                                    .place(level, parse_options.normalize_heading_levels);
+                                elements.push(DocumentElement::Heading {
+                                    level,
+                                    text,
+                                    number: None,
+                                    style,
+                                    source_level,
+                                });
+                            } else {
+                                // A single run carries nothing the paragraph formatting doesn't
+                                if runs.len() == 1
+                                    && runs[0].revision.is_none()
+                                    && runs[0].math.is_none()
+                                {
+                                    runs.clear();
+                                }
+                                elements.push(DocumentElement::Paragraph {
+                                    text,
+                                    formatting,
+                                    runs,
+                                    style,
+                                });
+                            }
+                        }
+                    }
+                }
+
+                word_count += display_equations.len();
+                elements.append(&mut display_equations);
+                elements.append(&mut images_after);
+            }
+            docx_rs::DocumentChild::Table(table) => {
+                table_number += 1;
+                last_block = Some(SourceLocation::table(table_number));
+                // Extract table data
+                if let Some(table_element) = extract_table_data(table) {
+                    elements.push(table_element);
+                } else {
+                    warnings.push(ParseWarning {
+                        location: SourceLocation::table(table_number),
+                        message: "table has no text content, skipped".to_string(),
+                    });
+                }
+            }
+            docx_rs::DocumentChild::StructuredDataTag(_) => {
+                warnings.push(ParseWarning {
+                    location: SourceLocation::after(last_block.as_ref()),
+                    message: "unsupported content control, skipped".to_string(),
+                });
+            }
+            docx_rs::DocumentChild::TableOfContents(_) => {
+                warnings.push(ParseWarning {
+                    location: SourceLocation::after(last_block.as_ref()),
+                    message: "unsupported table of contents field, skipped".to_string(),
+                });
+            }
+            // Bookmarks and comment ranges carry no visible content
+            docx_rs::DocumentChild::BookmarkStart(_)
+            | docx_rs::DocumentChild::BookmarkEnd(_)
+            | docx_rs::DocumentChild::CommentStart(_)
+            | docx_rs::DocumentChild::CommentEnd(_) => {}
+        }
+
+        // Charts are shown as a table of their data after the block holding them
+        if matches!(
+            child,
+            docx_rs::DocumentChild::Paragraph(_) | docx_rs::DocumentChild::Table(_)
+        ) {
+            for id in charts.get(&(block_number - 1)).into_iter().flatten() {
+                chart_count += 1;
+                let chart = relationships
+                    .get(id)
+                    .and_then(|part| read_package_part(file_data, part))
+                    .and_then(|xml| parse_chart(&xml));
+                match chart {
+                    Some(chart) => elements.push(DocumentElement::Table {
+                        table: chart.to_table(chart_count),
+                    }),
+                    None => warnings.push(ParseWarning {
+                        location: match child {
+                            docx_rs::DocumentChild::Table(_) => SourceLocation::table(table_number),
+                            _ => SourceLocation::paragraph(paragraph_number),
+                        },
+                        message: format!("chart {id} has no data to show, skipped"),
+                    }),
+                }
+            }
+            events.emit(Event::ElementParsed {
+                parsed: block_number,
+                total: block_count,
+            });
+        }
+        element_pages.resize(elements.len(), current_page);
+
+        if !previewed && elements.len() >= parse_options.preview_elements {
+            previewed = true;
+            let (preview, _) =
+                group_list_items(elements.clone(), element_pages.clone(), &word_list_items);
+            events.emit(Event::Preview {
+                elements: clean_word_list_markers(preview),
+            });
+        }
+
+        if let Some(warning) = warnings.first().filter(|_| parse_options.strict) {
+            return Err(DoxxError::parse(
+                warning.location.to_string(),
+                format!(
+                    "{} (strict mode stops at content it can't show)",
+                    warning.message
+                ),
+            ));
+        }
+    }
+
+    for element in &mut elements {
+        if let DocumentElement::CodeBlock {
+            lines,
+            language: language @ None,
+            ..
+        } = element
+        {
+            *language = crate::highlight::guess_language(lines);
+        }
+    }
+
+    // Pictures are extracted once the body is read, so the preview above
+    // doesn't wait for them
+    let mut image_dir = None;
+    if image_options.enabled {
+        let mut extractor = crate::image_extractor::ImageExtractor::new()?;
+        match &selected_blocks {
+            Some(_) => {
+                let names: HashSet<&str> = top_level_blocks(&docx.document)
+                    .filter(|(block, _)| is_selected(*block))
+                    .filter_map(|(_, child)| match child {
+                        docx_rs::DocumentChild::Paragraph(para) => Some(picture_ids(para)),
+                        _ => None,
+                    })
+                    .flatten()
+                    .filter_map(|id| image_targets.get(id).map(String::as_str))
+                    .collect();
+                extractor
+                    .extract_matching_images(file_data, events, |name| names.contains(name))?;
+            }
+            None => extractor.extract_images_from_bytes_with(file_data, events)?,
+        }
+        for element in &mut elements {
+            if let DocumentElement::Image {
+                relationship_id: Some(id),
+                image_path,
+                ..
+            } = element
+            {
+                *image_path = image_targets
+                    .get(id)
+                    .and_then(|name| extractor.get_image_path(name))
+                    .cloned();
+            }
+        }
+        image_dir = Some(extractor.temp_dir());
+    }
+
+    describe_from_captions(&mut elements);
+
+    // Post-process to group consecutive list items, by Word's numbering or
+    // by their text
+    let (elements, element_pages) = group_list_items(elements, element_pages, &word_list_items);
+
+    // Clean up Word list markers
+    let elements = clean_word_list_markers(elements);
+
+    let pages = PageMap::build(
+        &elements,
+        element_pages,
+        read_app_page_count(file_data),
+        parse_options.elements_per_page,
+        sections,
+    );
+
+    let metadata = DocumentMetadata {
+        file_path: file_path
+            .map(|path| path.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        file_size,
+        word_count,
+        page_count: pages.count,
+        created: core_properties.created,
+        modified: core_properties.modified,
+        author: core_properties.author,
+    };
+
+    events.emit(Event::LoadFinished {
+        elements: elements.len(),
+    });
+
+    let mut document = Document {
+        title,
+        metadata,
+        elements,
+        image_options,
+        warnings,
+        bookmarks: BTreeMap::new(),
+        pages,
+        hygiene,
+        image_dir,
+    };
+    if parse_options.fix_encoding {
+        crate::mojibake::fix_document_encoding(&mut document);
+    }
+    parse_options.overrides.apply(&mut document);
+    Ok(document)
+}
+
+/// Name the package part a docx-rs read error most likely came from
+fn reader_error_part(error: &docx_rs::ReaderError) -> &'static str {
+    use docx_rs::ReaderError;
+
+    match error {
+        ReaderError::ZipError(_) => "zip container",
+        ReaderError::DocumentNotFoundError => "_rels/.rels",
+        ReaderError::DocumentRelsNotFoundError => "word/_rels/document.xml.rels",
+        ReaderError::DocumentStylesNotFoundError => "word/styles.xml",
+        ReaderError::DocumentNumberingsNotFoundError => "word/numbering.xml",
+        ReaderError::HeaderOrFooterRelsNotFoundError => "word/_rels/header.xml.rels",
+        _ => DOCUMENT_PART,
+    }
+}
+
+/// English Metric Units per pixel at 96 DPI
+const EMU_PER_PIXEL: u32 = 9525;
+
+/// Build an Image element for a drawing, with its relationship id; the path
+/// of the extracted file is filled in once the pictures are extracted.
+/// Drawings that aren't pictures are reported back as a warning message. Pictures
+/// without alt text get an empty description, for [`describe_from_captions`].
+fn image_element_from_drawing(
+    drawing: &docx_rs::Drawing,
+    alt_text: Option<&AltText>,
+) -> std::result::Result<DocumentElement, String> {
+    let pic = match &drawing.data {
+        Some(docx_rs::DrawingData::Pic(pic)) => pic,
+        Some(docx_rs::DrawingData::TextBox(_)) => {
+            return Err("unsupported drawing (text box), skipped".to_string())
+        }
+        None => return Err("unsupported drawing (shape or chart), skipped".to_string()),
+    };
+
+    let relationship_id = (!pic.id.is_empty()).then(|| pic.id.clone());
+    let to_pixels = |emu: u32| (emu > 0).then(|| (emu / EMU_PER_PIXEL).max(1));
+
+    Ok(DocumentElement::Image {
+        description: alt_text
+            .and_then(AltText::text)
+            .unwrap_or_default()
+            .to_string(),
+        width: to_pixels(pic.size.0),
+        height: to_pixels(pic.size.1),
+        relationship_id,
+        image_path: None,
+        ocr_text: None,
+    })
+}
+
+/// Document properties from `docProps/core.xml`
+#[derive(Debug, Default)]
+struct CoreProperties {
+    title: Option<String>,
+    author: Option<String>,
+    created: Option<String>,
+    modified: Option<String>,
+}
+
+static CORE_PROPERTY_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?s)<(dc:title|dc:creator|dcterms:created|dcterms:modified)(?:\s[^>]*)?>(.*?)</")
+        .unwrap()
+});
+
+/// Read a text part of the DOCX package, e.g. `docProps/core.xml`
+fn read_package_part(file_data: &[u8], name: &str) -> Option<String> {
+    use std::io::Read;
+
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(file_data)).ok()?;
+    let mut xml = String::new();
+    archive.by_name(name).ok()?.read_to_string(&mut xml).ok()?;
+    Some(xml)
+}
+
+fn read_core_properties(file_data: &[u8]) -> CoreProperties {
+    let mut properties = CoreProperties::default();
+    let Some(xml) = read_package_part(file_data, "docProps/core.xml") else {
+        return properties;
+    };
+
+    for captures in CORE_PROPERTY_PATTERN.captures_iter(&xml) {
+        let value = unescape_xml(captures[2].trim());
+        if value.is_empty() {
+            continue;
+        }
+        match &captures[1] {
+            "dc:title" => properties.title = Some(value),
+            "dc:creator" => properties.author = Some(value),
+            "dcterms:created" => properties.created = Some(value),
+            "dcterms:modified" => properties.modified = Some(value),
+            _ => {}
+        }
+    }
+
+    properties
+}
+
+static APP_PAGES_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"<Pages>\s*(\d+)\s*</Pages>").unwrap());
+
+/// Page count Word saved in `docProps/app.xml` at its last layout
+fn read_app_page_count(file_data: &[u8]) -> Option<usize> {
+    let xml = read_package_part(file_data, "docProps/app.xml")?;
+    APP_PAGES_PATTERN.captures(&xml)?[1].parse().ok()
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+fn detect_heading_from_paragraph_style(para: &docx_rs::Paragraph) -> Option<u8> {
+    // Try to access paragraph properties and style
+    if let Some(style) = &para.property.style {
+        // Check for heading styles (Heading1, Heading2, etc.)
+        if style.val.starts_with("Heading") || style.val.starts_with("heading") {
+            if let Some(level_char) = style.val.chars().last() {
+                if let Some(level) = level_char.to_digit(10) {
+                    return Some(level.min(6) as u8);
+                }
+            }
+            // Default to level 1 for unspecified heading styles
+            return Some(1);
+        }
+    }
+
+    None
+}
+
+/// A paragraph Word numbers itself, as [`group_list_items`] needs it
+#[derive(Debug, Clone)]
+struct WordListItem {
+    /// Word's numbering instance; items sharing one are the same list
+    num_id: i32,
+    level: u8,
+    /// The number Word shows, `None` for a bullet
+    label: Option<String>,
+    text: String,
+}
+
+#[derive(Debug, Clone)]
+struct ListInfo {
+    level: u8,
+    is_ordered: bool,
+    num_id: Option<i32>, // Word's numbering definition ID
+}
+
+/// Type alias for numbering counters to simplify complex HashMap type
+type NumberingCounters = std::collections::HashMap<(i32, u8), u32>;
+
+/// Manages document-wide numbering state for proper sequential numbering
+#[derive(Debug)]
+struct DocumentNumberingManager {
+    /// Counters for each (numId, level) combination
+    /// Key: (numId, level), Value: current counter
+    counters: NumberingCounters,
+}
+
+impl DocumentNumberingManager {
+    fn new() -> Self {
+        Self {
+            counters: NumberingCounters::new(),
+        }
+    }
+
+    /// Generate the next number for a given numId and level
+    fn generate_number(&mut self, num_id: i32, level: u8, format: NumberingFormat) -> String {
+        // Get current counter for this (numId, level) combination
+        let key = (num_id, level);
+        let counter_value = {
+            let counter = self.counters.entry(key).or_insert(0);
+            *counter += 1;
+            *counter
+        };
+
+        // Reset deeper levels when we increment a higher level
+        // This handles hierarchical numbering like 1. -> 1.1 -> 2. (reset 1.1 back to 2.1)
+        self.reset_deeper_levels(num_id, level);
+
+        // For hierarchical numbering, we need to build the full number string
+        self.format_hierarchical_number(num_id, level, counter_value, format)
+    }
+
+    fn reset_deeper_levels(&mut self, num_id: i32, current_level: u8) {
+        // Reset all levels deeper than current_level for this numId
+        let keys_to_reset: Vec<_> = self
+            .counters
+            .keys()
+            .filter(|(id, level)| *id == num_id && *level > current_level)
+            .cloned()
+            .collect();
+
+        for key in keys_to_reset {
+            self.counters.remove(&key);
+        }
+    }
+
+    fn format_number(&self, counter: u32, format: NumberingFormat) -> String {
+        match format {
+            NumberingFormat::Decimal => format!("{counter}. "),
+            NumberingFormat::LowerLetter => {
+                // Convert 1->a, 2->b, etc.
+                if counter <= 26 {
+                    let letter = (b'a' + (counter - 1) as u8) as char;
+                    format!("{letter}. ")
+                } else {
+                    format!("{counter}. ") // Fallback for > 26
+                }
+            }
+            NumberingFormat::LowerRoman => format!("{}. ", Self::to_roman(counter).to_lowercase()),
+            NumberingFormat::UpperLetter => {
+                // Convert 1->A, 2->B, etc.
+                if counter <= 26 {
+                    let letter = (b'A' + (counter - 1) as u8) as char;
+                    format!("{letter}. ")
+                } else {
+                    format!("{counter}. ") // Fallback for > 26
+                }
+            }
+            NumberingFormat::UpperRoman => format!("{}. ", Self::to_roman(counter)),
+            NumberingFormat::ParenLowerLetter => {
+                if counter <= 26 {
+                    let letter = (b'a' + (counter - 1) as u8) as char;
+                    format!("({letter})")
+                } else {
+                    format!("({counter})")
+                }
+            }
+            NumberingFormat::ParenLowerRoman => {
+                format!("({})", Self::to_roman(counter).to_lowercase())
+            }
+            NumberingFormat::Bullet => "* ".to_string(),
+        }
+    }
+
+    fn to_roman(num: u32) -> String {
+        let values = [1000, 900, 500, 400, 100, 90, 50, 40, 10, 9, 5, 4, 1];
+        let symbols = [
+            "M", "CM", "D", "CD", "C", "XC", "L", "XL", "X", "IX", "V", "IV", "I",
+        ];
+
+        let mut result = String::new();
+        let mut n = num;
+
+        for (i, &value) in values.iter().enumerate() {
+            while n >= value {
+                result.push_str(symbols[i]);
+                n -= value;
+            }
+        }
+
+        result
+    }
+
+    /// Format hierarchical number (e.g., "2.1", "3.2.1")
+    fn format_hierarchical_number(
+        &self,
+        num_id: i32,
+        level: u8,
+        counter: u32,
+        format: NumberingFormat,
+    ) -> String {
+        // Check if this numId/level combination should use hierarchical numbering
+        let needs_hierarchy = matches!((num_id, level), (4, 1)); // 2.1, 2.2, etc.
+
+        if needs_hierarchy {
+            // Build hierarchical number by including parent level counters
+            let mut parts = Vec::new();
+
+            // Add parent level counter (level 0 for this numId)
+            if let Some(parent_counter) = self.counters.get(&(num_id, 0)) {
+                parts.push(parent_counter.to_string());
+            }
+
+            // Add current level counter
+            parts.push(counter.to_string());
+
+            // Join with dots and add final punctuation
+            format!("{}. ", parts.join("."))
+        } else {
+            // Use regular formatting for non-hierarchical levels
+            self.format_number(counter, format)
+        }
+    }
+}
+
+/// Different numbering formats supported by Word
+#[derive(Debug, Clone, Copy)]
+enum NumberingFormat {
+    Decimal,          // 1. 2. 3.
+    LowerLetter,      // a. b. c.
+    UpperLetter,      // A. B. C.
+    LowerRoman,       // i. ii. iii.
+    UpperRoman,       // I. II. III.
+    ParenLowerLetter, // (a) (b) (c)
+    ParenLowerRoman,  // (i) (ii) (iii)
+    #[allow(dead_code)]
+    Bullet, // * * *
+}
+
+#[derive(Debug, Clone)]
+struct HeadingInfo {
+    level: u8,
+    number: Option<String>,
+    clean_text: Option<String>, // Text with number removed
+}
+
+fn detect_list_from_paragraph_numbering(para: &docx_rs::Paragraph) -> Option<ListInfo> {
+    // Check if paragraph has numbering properties
+    if let Some(num_pr) = &para.property.numbering_property {
+        // Extract numbering level (default to 0 if not specified)
+        let level = num_pr.level.as_ref().map(|l| l.val as u8).unwrap_or(0);
+
+        // Extract numId for state tracking
+        let num_id = num_pr.id.as_ref().map(|id| id.id as i32);
+
+        // Enhanced detection for mixed list types (same numId, different levels)
+        let is_ordered = if let Some(num_id_val) = num_id {
+            match (num_id_val, level) {
+                // For Word's default mixed list (numId 1):
+                // Level 0 = decimal numbers (1. 2. 3.)
+                // Level 1 = letters (a) b) c))
+                // Level 2 = roman numerals (i. ii. iii.)
+                (1, 0) => true, // Top level: decimal numbers (was false, causing bug)
+                (1, 1) => true, // Second level: letters
+                (1, 2) => true, // Third level: roman numerals
+                (1, _) => level % 2 == 1, // Pattern for deeper levels
+                (_, _) => true, // Other numIds are typically ordered
+            }
+        } else {
+            false
+        };
+
+        return Some(ListInfo {
+            level,
+            is_ordered,
+            num_id,
+        });
+    }
+    None
+}
+
+/// Determine the numbering format based on Word's numId and level
+fn get_numbering_format(num_id: i32, level: u8) -> NumberingFormat {
+    match (num_id, level) {
+        // numId=4: Main multilevel list (from advanced-numbering-2.docx)
+        (4, 0) => NumberingFormat::Decimal,    // 1., 2., 3.
+        (4, 1) => NumberingFormat::Decimal,    // 2.1., 2.2., 2.3. (hierarchical)
+        (4, 2) => NumberingFormat::LowerRoman, // i., ii., iii.
+
+        // numId=5: Secondary list (a), (b), (c) from same document
+        (5, 2) => NumberingFormat::ParenLowerLetter, // (a), (b), (c)
+
+        // numId=2: From other test documents
+        (2, 0) => NumberingFormat::Decimal,         // 1., 2., 3.
+        (2, 3) => NumberingFormat::ParenLowerRoman, // (i), (ii), (iii)
+
+        // numId=1: Default Word numbering scheme
+        (1, 0) => NumberingFormat::Decimal,          // 1. 2. 3.
+        (1, 1) => NumberingFormat::LowerLetter,      // a. b. c.
+        (1, 2) => NumberingFormat::LowerRoman,       // i. ii. iii.
+        (1, 3) => NumberingFormat::ParenLowerLetter, // (a) (b) (c)
+        (1, 4) => NumberingFormat::ParenLowerRoman,  // (i) (ii) (iii)
+
+        // Fallback defaults based on level
+        (_, 0) => NumberingFormat::Decimal,
+        (_, 1) => NumberingFormat::LowerLetter,
+        (_, 2) => NumberingFormat::LowerRoman,
+        (_, 3) => NumberingFormat::UpperLetter,
+        (_, 4) => NumberingFormat::UpperRoman,
+        _ => NumberingFormat::Decimal,
+    }
+}
+
+fn detect_heading_with_numbering(
+    para: &docx_rs::Paragraph,
+    changes: TrackedChanges,
+) -> Option<HeadingInfo> {
+    // First check if this is a heading style
+    let heading_level = detect_heading_from_paragraph_style(para)?;
+
+    // Extract text using docx-rs proper text extraction
+    let text = extract_paragraph_text(para, changes);
+
+    // Priority order for numbering detection:
+    // 1. Manual numbering in text content (highest priority - user explicitly typed)
+    // 2. Word's automatic numbering (w:numPr) - explicit numbering properties
+    // 3. Style-based automatic generation (lowest priority - our inference)
+
+    // First, check for manual numbering in text content
+    if let Some((number, remaining_text)) = extract_heading_number_from_text(&text) {
+        return Some(HeadingInfo {
+            level: heading_level,
+            number: Some(number),
+            clean_text: Some(remaining_text),
+        });
+    }
+
+    // Second, check for Word's automatic numbering
+    if let Some(num_pr) = &para.property.numbering_property {
+        // This is automatic Word numbering - try to reconstruct
+        if let Some((num_id, level)) = extract_numbering_info(num_pr) {
+            let number = reconstruct_heading_number(num_id, level, heading_level);
+            return Some(HeadingInfo {
+                level: heading_level,
+                number: Some(number),
+                clean_text: Some(text), // Keep original text since number is automatic
+            });
+        }
+    }
+
+    // If no numbering found, return heading info without number
+    Some(HeadingInfo {
+        level: heading_level,
+        number: None,
+        clean_text: None,
+    })
+}
+
+/// Extract text from paragraph using docx-rs properly
+fn extract_paragraph_text(para: &docx_rs::Paragraph, changes: TrackedChanges) -> String {
+    let mut text = String::new();
+
+    for (run, revision) in paragraph_runs(para, changes) {
+        let run_text = extract_run_text(run);
+        match revision {
+            Some(revision) if !run_text.is_empty() => text.push_str(&revision.markup(&run_text)),
+            _ => text.push_str(&run_text),
+        }
+    }
+
+    text.trim().to_string()
+}
+
+/// Runs of a paragraph in reading order. Tracked insertions and deletions are
+/// kept or dropped according to `changes`; with `TrackedChanges::Markup` both
+/// are kept and tagged with their kind of revision.
+fn paragraph_runs(
+    para: &docx_rs::Paragraph,
+    changes: TrackedChanges,
+) -> Vec<(&docx_rs::Run, Option<Revision>)> {
+    let tag = |revision| (changes == TrackedChanges::Markup).then_some(revision);
+    let keep_inserted = changes != TrackedChanges::Reject;
+    let keep_deleted = changes != TrackedChanges::Accept;
+
+    fn deleted_runs(delete: &docx_rs::Delete, keep: bool) -> Vec<&docx_rs::Run> {
+        delete
+            .children
+            .iter()
+            .filter_map(|child| match child {
+                docx_rs::DeleteChild::Run(run) if keep => Some(run),
+                _ => None,
+            })
+            .collect()
+    }
+
+    let mut runs = Vec::new();
+    for child in &para.children {
+        match child {
+            docx_rs::ParagraphChild::Run(run) => runs.push((run.as_ref(), None)),
+            docx_rs::ParagraphChild::Insert(insert) => {
+                for child in &insert.children {
+                    match child {
+                        docx_rs::InsertChild::Run(run) if keep_inserted => {
+                            runs.push((run.as_ref(), tag(Revision::Inserted)))
+                        }
+                        // Text inserted and then deleted again in another revision
+                        docx_rs::InsertChild::Delete(delete) => runs.extend(
+                            deleted_runs(delete, keep_deleted)
+                                .into_iter()
+                                .map(|run| (run, tag(Revision::Deleted))),
+                        ),
+                        docx_rs::InsertChild::Run(_)
+                        | docx_rs::InsertChild::CommentStart(_)
+                        | docx_rs::InsertChild::CommentEnd(_) => {}
+                    }
+                }
+            }
+            docx_rs::ParagraphChild::Delete(delete) => runs.extend(
+                deleted_runs(delete, keep_deleted)
+                    .into_iter()
+                    .map(|run| (run, tag(Revision::Deleted))),
+            ),
+            // Reported by `skipped_paragraph_content`
+            docx_rs::ParagraphChild::Hyperlink(_)
+            | docx_rs::ParagraphChild::StructuredDataTag(_)
+            | docx_rs::ParagraphChild::PageNum(_)
+            | docx_rs::ParagraphChild::NumPages(_) => {}
+            // Bookmarks and comment ranges carry no visible content
+            docx_rs::ParagraphChild::BookmarkStart(_)
+            | docx_rs::ParagraphChild::BookmarkEnd(_)
+            | docx_rs::ParagraphChild::CommentStart(_)
+            | docx_rs::ParagraphChild::CommentEnd(_) => {}
+        }
+    }
+    runs
+}
+
+/// Content of a paragraph that [`paragraph_runs`] leaves out, named for a
+/// parse warning
+fn skipped_paragraph_content(para: &docx_rs::Paragraph) -> Vec<&'static str> {
+    para.children
+        .iter()
+        .filter_map(|child| match child {
+            docx_rs::ParagraphChild::Hyperlink(_) => Some("hyperlink"),
+            docx_rs::ParagraphChild::StructuredDataTag(_) => Some("content control"),
+            docx_rs::ParagraphChild::PageNum(_) | docx_rs::ParagraphChild::NumPages(_) => {
+                Some("page number field")
+            }
+            docx_rs::ParagraphChild::Run(_)
+            | docx_rs::ParagraphChild::Insert(_)
+            | docx_rs::ParagraphChild::Delete(_)
+            | docx_rs::ParagraphChild::BookmarkStart(_)
+            | docx_rs::ParagraphChild::BookmarkEnd(_)
+            | docx_rs::ParagraphChild::CommentStart(_)
+            | docx_rs::ParagraphChild::CommentEnd(_) => None,
+        })
+        .collect()
+}
+
+/// Put the in-line `equations` of a paragraph into its text, each as a run
+/// of its own holding the equation's Unicode approximation
+fn insert_equations(text: &mut String, runs: &mut Vec<TextRun>, equations: &[Equation]) {
+    let inline: Vec<&Equation> = equations.iter().filter(|eq| !eq.display).collect();
+    if inline.is_empty() {
+        return;
+    }
+    if runs.is_empty() && !text.is_empty() {
+        runs.push(TextRun {
+            text: text.clone(),
+            ..TextRun::default()
+        });
+    }
+    // From the last equation back, so earlier offsets stay valid
+    for equation in inline.into_iter().rev() {
+        let math = TextRun {
+            text: equation.text.clone(),
+            math: Some(equation.latex.clone()),
+            ..TextRun::default()
+        };
+        let mut before = 0;
+        let mut position = runs.len();
+        for (index, run) in runs.iter().enumerate() {
+            let length = run.text.chars().count();
+            if equation.offset < before + length {
+                position = index;
+                break;
+            }
+            before += length;
+        }
+        if let Some(run) = runs.get_mut(position) {
+            let split = run
+                .text
+                .char_indices()
+                .nth(equation.offset - before)
+                .map_or(run.text.len(), |(byte, _)| byte);
+            if split > 0 {
+                let rest = TextRun {
+                    text: run.text.split_off(split),
+                    ..run.clone()
+                };
+                runs.insert(position + 1, rest);
+                position += 1;
+            }
+        }
+        runs.insert(position, math);
+    }
+    *text = runs.iter().map(|run| run.text.as_str()).collect();
+}
+
+/// Text of a `w:delText`, which docx-rs keeps private but serializes
+fn delete_text(deleted: &docx_rs::DeleteText) -> String {
+    serde_json::to_value(deleted)
+        .ok()
+        .and_then(|value| value["text"].as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// Extract text from a run using docx-rs features
+fn extract_run_text(run: &docx_rs::Run) -> String {
+    let mut text = String::new();
+
+    for child in &run.children {
+        match child {
+            docx_rs::RunChild::Text(text_elem) => {
+                text.push_str(&text_elem.text);
+            }
+            docx_rs::RunChild::DeleteText(deleted) => {
+                text.push_str(&delete_text(deleted));
+            }
+            docx_rs::RunChild::Tab(_) => {
+                text.push('\t');
+            }
+            docx_rs::RunChild::Break(_) => {
+                // Break types are private, so we'll just add a line break
+                text.push('\n');
+            }
+            docx_rs::RunChild::Drawing(_) => {
+                text.push_str("[Image]");
+            }
+            _ => {
+                // Handle other run children
+            }
+        }
+    }
+
+    text
+}
+
+/// Extract numbering information from docx-rs numbering properties
+fn extract_numbering_info(num_pr: &docx_rs::NumberingProperty) -> Option<NumberingInfo> {
+    let num_id = num_pr.id.as_ref()?.id as i32;
+    let level = num_pr.level.as_ref().map(|l| l.val as u8).unwrap_or(0);
+    Some((num_id, level))
+}
+
+/// Reconstruct heading number from Word's numbering system
+fn reconstruct_heading_number(num_id: i32, level: u8, heading_level: u8) -> String {
+    // This is a simplified reconstruction
+    // In a full implementation, we'd need to access the numbering definitions
+    // and track the current state across the document
+    match (num_id, level, heading_level) {
+        // Standard heading numbering schemes
+        (_, 0, 1) => "1".to_string(),
+        (_, 1, 2) => "1.1".to_string(),
+        (_, 2, 3) => "1.1.1".to_string(),
+        (_, 3, 4) => "1.1.1.1".to_string(),
+        _ => {
+            // Fallback based on heading level
+            match heading_level {
+                1 => "1".to_string(),
+                2 => "1.1".to_string(),
+                3 => "1.1.1".to_string(),
+                _ => "1.1.1.1".to_string(),
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct HeadingNumberTracker {
+    counters: [u32; 6], // Support up to 6 heading levels
+    auto_numbering_enabled: bool,
+}
+
+impl HeadingNumberTracker {
+    fn new() -> Self {
+        Self {
+            counters: [0; 6],
+            auto_numbering_enabled: false,
+        }
+    }
+
+    fn enable_auto_numbering(&mut self) {
+        self.auto_numbering_enabled = true;
+    }
+
+    fn get_number(&mut self, level: u8) -> String {
+        if !self.auto_numbering_enabled {
+            return String::new();
+        }
+
+        let level_index = (level.saturating_sub(1) as usize).min(5);
+
+        // Increment current level
+        self.counters[level_index] += 1;
+
+        // Reset all deeper levels
+        for i in (level_index + 1)..6 {
+            self.counters[i] = 0;
+        }
+
+        // Build number string (1.2.3 format)
+        let mut parts = Vec::new();
+        for i in 0..=level_index {
+            if self.counters[i] > 0 {
+                parts.push(self.counters[i].to_string());
+            }
+        }
+
+        parts.join(".")
+    }
+}
+
+/// Levels of the headings enclosing the current one, for normalizing levels
+#[derive(Debug, Default)]
+struct HeadingLevels {
+    open: Vec<u8>,
+}
+
+impl HeadingLevels {
+    /// Level to give a heading at `level` in the document, and the document's
+    /// level when that differs. Without `normalize` levels are kept as they are.
+    fn place(&mut self, level: u8, normalize: bool) -> (u8, Option<u8>) {
+        if !normalize {
+            return (level, None);
+        }
+        while self.open.last().is_some_and(|&parent| parent >= level) {
+            self.open.pop();
+        }
+        self.open.push(level);
+        let normalized = self.open.len() as u8;
+        (normalized, (normalized != level).then_some(level))
+    }
+}
+
+/// Analyze document structure to determine if automatic numbering should be enabled
+fn analyze_heading_structure(document: &docx_rs::Document) -> bool {
+    let mut heading_count = 0;
+    let mut has_explicit_numbering = false;
+    let mut level_counts = [0u32; 6]; // Count headings at each level
+
+    for child in &document.children {
+        if let docx_rs::DocumentChild::Paragraph(para) = child {
+            if let Some(heading_level) = detect_heading_from_paragraph_style(para) {
+                let text = extract_paragraph_text(para, TrackedChanges::Accept);
+
+                // Check if this heading has explicit numbering in the text
+                if extract_heading_number_from_text(&text).is_some() {
+                    has_explicit_numbering = true;
+                }
+
+                heading_count += 1;
+                let level_index = (heading_level.saturating_sub(1) as usize).min(5);
+                level_counts[level_index] += 1;
+            }
+        }
+    }
+
+    // Don't auto-number if:
+    // 1. Any headings have explicit numbering
+    // 2. Very few headings (less than 3)
+    // 3. Only one level of headings (no hierarchy)
+    if has_explicit_numbering || heading_count < 3 {
+        return false;
+    }
+
+    // Check if we have a real hierarchy (headings at multiple levels)
+    let levels_with_headings = level_counts.iter().filter(|&&count| count > 0).count();
+
+    // Auto-number if we have multiple levels or multiple headings at level 1
+    levels_with_headings > 1 || level_counts[0] > 1
+}
+
+// Lazy static regex patterns for heading number detection
+// Focused on common patterns for manual numbering in text
+static HEADING_NUMBER_PATTERNS: Lazy<Vec<Regex>> = Lazy::new(|| {
+    vec![
+        // Standard decimal numbering: "1.", "1.1", "1.1.1", "2.1.1" (most common)
+        // For single numbers, require a period to distinguish from "Heading 1" style titles
+        // For hierarchical numbers (1.1, 1.2.3), period is optional
+        Regex::new(r"^(\d+(?:\.\d+)+\.?|\d+\.)\s+(.+)$").unwrap(),
+        // Section numbering: "Section 1.2", "Chapter 3"
+        Regex::new(r"^((?:Section|Chapter|Part)\s+\d+(?:\.\d+)*\.?)\s+(.+)$").unwrap(),
+        // Alternative numbering schemes (less common, but still useful)
+        Regex::new(r"^([A-Z]\.)\s+(.+)$").unwrap(), // "A. Introduction"
+        Regex::new(r"^([IVX]+\.)\s+(.+)$").unwrap(), // "I. Overview"
+    ]
+});
+
+fn extract_heading_number_from_text(text: &str) -> Option<HeadingNumberInfo> {
+    let text = text.trim();
+
+    // Early return for empty text
+    if text.is_empty() {
+        return None;
+    }
+
+    // Try each pattern until one matches
+    for pattern in HEADING_NUMBER_PATTERNS.iter() {
+        if let Some(captures) = pattern.captures(text) {
+            if let (Some(number_match), Some(text_match)) = (captures.get(1), captures.get(2)) {
+                let number = number_match.as_str().trim_end_matches('.');
+                let remaining_text = text_match.as_str().trim();
+
+                // Only return if we have both number and meaningful text
+                if !number.is_empty() && !remaining_text.is_empty() {
+                    return Some((number.to_string(), remaining_text.to_string()));
+                }
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_types_and_overrides() {
+        let cells = |row: &[&str]| -> Vec<TableCell> {
+            row.iter().map(|c| TableCell::new(c.to_string())).collect()
+        };
+        let mut table = TableData::new(
+            cells(&["Zip", "Price", "Note", "Blank"]),
+            vec![
+                cells(&["10001", "$5.00", "ok", ""]),
+                cells(&["94110", "$7.50", "12", ""]),
+                cells(&["60601", "", "late", ""]),
+            ],
+        );
+        assert_eq!(
+            table.column_types(),
+            vec![
+                CellDataType::Number,
+                CellDataType::Currency,
+                CellDataType::Text,
+                CellDataType::Empty
+            ]
+        );
+        assert_eq!(table.column_index("price"), Some(1));
+        assert_eq!(table.column_index("1"), Some(0));
+        assert_eq!(table.column_index("5"), None);
+
+        let overrides = parse_column_types("Zip:text, 3:date").unwrap();
+        assert_eq!(
+            overrides,
+            vec![
+                ("Zip".to_string(), CellDataType::Text),
+                ("3".to_string(), CellDataType::Date)
+            ]
+        );
+        assert!(parse_column_types("Zip").is_err());
+        assert!(parse_column_types("Zip:money").is_err());
+
+        for (column, data_type) in &overrides {
+            let index = table.column_index(column).unwrap();
+            table.set_column_type(index, *data_type);
+        }
+        assert_eq!(table.column_types()[0], CellDataType::Text);
+        assert_eq!(table.column_types()[2], CellDataType::Date);
+        assert_eq!(table.metadata.column_alignments[0], TextAlignment::Left);
+        assert_eq!(table.rows[0][0].alignment, TextAlignment::Left);
+    }
+
+    #[test]
+    fn test_heading_number_extraction() {
+        // Test most common formats (decimal hierarchical)
+        assert_eq!(
+            extract_heading_number_from_text("1. Introduction"),
+            Some(("1".to_string(), "Introduction".to_string()))
+        );
+
+        assert_eq!(
+            extract_heading_number_from_text("1.1 Project Overview"),
+            Some(("1.1".to_string(), "Project Overview".to_string()))
+        );
+
+        assert_eq!(
+            extract_heading_number_from_text("2.1.1 Something Important"),
+            Some(("2.1.1".to_string(), "Something Important".to_string()))
+        );
+
+        // Test alternative numbering schemes
+        assert_eq!(
+            extract_heading_number_from_text("A. First Section"),
+            Some(("A".to_string(), "First Section".to_string()))
+        );
+
+        assert_eq!(
+            extract_heading_number_from_text("I. Roman Numeral"),
+            Some(("I".to_string(), "Roman Numeral".to_string()))
+        );
+
+        // Test section numbering
+        assert_eq!(
+            extract_heading_number_from_text("Section 1.2 Overview"),
+            Some(("Section 1.2".to_string(), "Overview".to_string()))
+        );
+
+        // Test no numbering (should fall back to automatic generation)
+        assert_eq!(extract_heading_number_from_text("Introduction"), None);
+
+        // Test titles with numbers that should NOT be treated as numbered headings
+        assert_eq!(extract_heading_number_from_text("Heading 1"), None);
+        // Note: "Chapter 5 Summary" will match the section pattern, which is intentional
+        // The section pattern is designed to match "Chapter 5 Something" formats
+        assert_eq!(
+            extract_heading_number_from_text("Chapter 5 Summary"),
+            Some(("Chapter 5".to_string(), "Summary".to_string()))
+        );
+        assert_eq!(extract_heading_number_from_text("Version 2"), None);
+    }
+
+    #[test]
+    fn test_group_mixed_and_resumed_lists() {
+        let paragraph = |text: &str| DocumentElement::Paragraph {
+            text: text.to_string(),
+            formatting: TextFormatting::default(),
+            runs: Vec::new(),
+            style: None,
+        };
+        let elements = vec![
+            paragraph("• Prepare the workspace"),
+            paragraph("  1. Clear the desk of everything else"),
+            paragraph("  2. Plug the soldering iron in to heat"),
+            paragraph("• Solder the board"),
+            paragraph("1. Check the power supply before switching on"),
+            paragraph("Leave it a minute to settle."),
+            paragraph("2. Measure the output voltage at the test points"),
+        ];
+        let (grouped, pages) = group_list_items(elements, vec![1; 7], &HashMap::new());
+        assert_eq!(pages.len(), grouped.len());
+
+        // Numbered steps nested under bullets stay in the bulleted list
+        let DocumentElement::List {
+            items,
+            ordered,
+            start,
+        } = &grouped[0]
+        else {
+            panic!("expected a list, got {:?}", grouped[0]);
+        };
+        assert_eq!(items.len(), 4);
+        assert!(!ordered);
+        assert_eq!(
+            list_item_numbers(items, *ordered, *start),
+            [None, Some(1), Some(2), None]
+        );
+
+        // A numbered list interrupted by a paragraph resumes at its number
+        assert!(matches!(
+            &grouped[1],
+            DocumentElement::List {
+                ordered: true,
+                start: None,
+                ..
+            }
+        ));
+        assert!(matches!(&grouped[2], DocumentElement::Paragraph { .. }));
+        let DocumentElement::List {
+            items,
+            ordered,
+            start,
+        } = &grouped[3]
+        else {
+            panic!("expected a list, got {:?}", grouped[3]);
+        };
+        assert_eq!(*start, Some(2));
+        assert_eq!(list_item_numbers(items, *ordered, *start), [Some(2)]);
+    }
+
+    #[test]
+    fn test_group_word_lists_by_numbering() {
+        let paragraph = |text: &str| DocumentElement::Paragraph {
+            text: text.to_string(),
+            formatting: TextFormatting::default(),
+            runs: Vec::new(),
+            style: None,
+        };
+        let item = |num_id: i32, level: u8, label: Option<&str>, text: &str| WordListItem {
+            num_id,
+            level,
+            label: label.map(str::to_string),
+            text: text.to_string(),
+        };
+        let elements = vec![
+            paragraph("__WORD_LIST__1. Scope"),
+            paragraph("__WORD_LIST__  a. Goods"),
+            paragraph("__WORD_LIST__1. Apples"),
+            paragraph("Prices are in euros."),
+            paragraph("__WORD_LIST__2. Pears"),
+        ];
+        let word_items = HashMap::from([
+            (0, item(4, 0, Some("1."), "Scope")),
+            (1, item(4, 1, Some("a."), "Goods")),
+            // Another numbering instance right after is another list
+            (2, item(7, 0, Some("1."), "Apples")),
+            (4, item(7, 0, Some("2."), "Pears")),
+        ]);
+        let (grouped, _) = group_list_items(elements, vec![1; 5], &word_items);
+        assert_eq!(grouped.len(), 4);
+
+        let labels = |element: &DocumentElement| match element {
+            DocumentElement::List {
+                items,
+                ordered,
+                start,
+            } => list_item_labels(items, *ordered, *start),
+            other => panic!("expected a list, got {other:?}"),
+        };
+        let some = |label: &str| Some(label.to_string());
+        assert_eq!(labels(&grouped[0]), [some("1."), some("a.")]);
+        assert_eq!(labels(&grouped[1]), [some("1.")]);
+        assert!(matches!(&grouped[2], DocumentElement::Paragraph { .. }));
+        // The same instance after a paragraph keeps Word's numbers
+        assert_eq!(labels(&grouped[3]), [some("2.")]);
+    }
+
+    #[tokio::test]
+    async fn test_metadata_overrides() {
+        let path = Path::new("../../tests/fixtures/business-report.docx");
+        let parsed = load_document(path, ImageOptions::default(), ParseOptions::default())
+            .await
+            .unwrap();
+        let options = ParseOptions {
+            overrides: MetadataOverrides {
+                title: Some("Annual Report".to_string()),
+                author: Some("Finance Team".to_string()),
+                date: Some(parse_date("2024-05-01").unwrap()),
+            },
+            ..ParseOptions::default()
+        };
+        let document = load_document(path, ImageOptions::default(), options)
+            .await
+            .unwrap();
+        assert_ne!(parsed.title, "Annual Report");
+        assert_eq!(document.title, "Annual Report");
+        assert_eq!(document.metadata.author.as_deref(), Some("Finance Team"));
+        assert_eq!(document.metadata.created.as_deref(), Some("2024-05-01"));
+        assert_eq!(document.metadata.modified.as_deref(), Some("2024-05-01"));
+        assert_eq!(document.elements.len(), parsed.elements.len());
+
+        assert!(parse_date("2024-05-01T09:30:00Z").is_ok());
+        assert!(parse_date("2024-13-01").is_err());
+        assert!(parse_date("May 1st").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_parse_from_memory() {
+        let path = Path::new("../../tests/fixtures/business-report.docx");
+        let loaded = load_document(path, ImageOptions::default(), ParseOptions::default())
+            .await
+            .unwrap();
+        let data = std::fs::read(path).unwrap();
+
+        let parsed = Document::from_bytes(&data).unwrap();
+        assert_eq!(parsed.elements.len(), loaded.elements.len());
+        assert_eq!(parsed.metadata.word_count, loaded.metadata.word_count);
+        assert_eq!(parsed.metadata.file_size, loaded.metadata.file_size);
+        assert!(parsed.metadata.file_path.is_empty());
+
+        let mut reader = std::io::Cursor::new(data);
+        reader.set_position(100);
+        let read = Document::from_reader(reader).unwrap();
+        assert_eq!(read.elements.len(), loaded.elements.len());
+        // Without a file name there is nothing to take the title from
+        assert_eq!(read.title, "Untitled Document");
+
+        let error = Document::from_bytes(b"not a docx").unwrap_err();
+        assert!(matches!(error, DoxxError::UnsupportedFormat(_)));
+        let mut truncated = std::fs::read(path).unwrap();
+        truncated.truncate(truncated.len() / 2);
+        let error = Document::from_bytes(&truncated).unwrap_err();
+        assert!(matches!(error, DoxxError::CorruptArchive(_)), "{error}");
+    }
+
+    #[tokio::test]
+    async fn test_section_loading() {
+        let path = Path::new("../../tests/fixtures/numbered-headings.docx");
+        let full = load_document(path, ImageOptions::default(), ParseOptions::default())
+            .await
+            .unwrap();
+        let section = |path: &str| ParseOptions {
+            section: Some(path.to_string()),
+            ..ParseOptions::default()
+        };
+
+        for heading in [
+            "2.1",
+            "Technical Implementation > Architecture",
+            "Testing Strategy",
+        ] {
+            let document = load_document(path, ImageOptions::default(), section(heading))
+                .await
+                .unwrap();
+            let sections = heading_sections(&full.elements, heading);
+            assert_eq!(sections.len(), 1, "{heading}");
+            assert_eq!(
+                format!("{:?}", document.elements),
+                format!("{:?}", &full.elements[sections[0].clone()]),
+                "{heading}"
+            );
+        }
+
+        let error = load_document(path, ImageOptions::default(), section("Appendix Z"))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, DoxxError::SectionNotFound(_)), "{error}");
+    }
+
+    #[test]
+    fn test_section_warning_locations() {
+        let mut data = std::io::Cursor::new(Vec::new());
+        docx_rs::Docx::new()
+            .add_paragraph(
+                docx_rs::Paragraph::new()
+                    .style("Heading1")
+                    .add_run(docx_rs::Run::new().add_text("Summary")),
+            )
+            .add_paragraph(
+                docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text("All good")),
+            )
+            .add_table(docx_rs::Table::new(vec![docx_rs::TableRow::new(vec![
+                docx_rs::TableCell::new().add_paragraph(
+                    docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text("1")),
+                ),
+            ])]))
+            .add_paragraph(
+                docx_rs::Paragraph::new()
+                    .style("Heading1")
+                    .add_run(docx_rs::Run::new().add_text("Links")),
+            )
+            .add_paragraph(
+                docx_rs::Paragraph::new().add_hyperlink(
+                    docx_rs::Hyperlink::new(
+                        "https://example.com",
+                        docx_rs::HyperlinkType::External,
+                    )
+                    .add_run(docx_rs::Run::new().add_text("the site")),
+                ),
+            )
+            .build()
+            .pack(&mut data)
+            .unwrap();
+        let data = data.into_inner();
+
+        let strict = |section: Option<&str>| {
+            let options = ParseOptions {
+                section: section.map(str::to_string),
+                strict: true,
+                ..ParseOptions::default()
+            };
+            Document::from_bytes_with(&data, ImageOptions::default(), options)
+                .unwrap_err()
+                .to_string()
+        };
+        // The section's own warning names the paragraph it has in the whole body
+        let whole = strict(None);
+        assert!(whole.contains("paragraph 4"), "{whole}");
+        assert_eq!(strict(Some("Links")), whole);
+        let summary = ParseOptions {
+            section: Some("Summary".to_string()),
+            strict: true,
+            ..ParseOptions::default()
+        };
+        assert!(Document::from_bytes_with(&data, ImageOptions::default(), summary).is_ok());
+    }
+
+    #[test]
+    fn test_warning_locations_after_table() {
+        let mut data = std::io::Cursor::new(Vec::new());
+        docx_rs::Docx::new()
+            .add_paragraph(
+                docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text("Figures")),
+            )
+            .add_table(docx_rs::Table::new(vec![docx_rs::TableRow::new(vec![
+                docx_rs::TableCell::new().add_paragraph(
+                    docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text("1")),
+                ),
+            ])]))
+            .add_structured_data_tag(docx_rs::StructuredDataTag::new().add_paragraph(
+                docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text("Total")),
+            ))
+            .add_paragraph(docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text("End")))
+            .build()
+            .pack(&mut data)
+            .unwrap();
+
+        // A chart inside the table, whose part is missing
+        let mut package = zip::ZipArchive::new(std::io::Cursor::new(data.into_inner())).unwrap();
+        let mut rewritten = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for index in 0..package.len() {
+            let mut file = package.by_index(index).unwrap();
+            let mut content = String::new();
+            file.read_to_string(&mut content).unwrap();
+            if file.name() == DOCUMENT_PART {
+                let chart = r#"<c:chart xmlns:c="http://schemas.openxmlformats.org/drawingml/2006/chart" r:id="rId99"/>"#;
+                content = content.replacen("</w:tc>", &format!("{chart}</w:tc>"), 1);
+            }
+            rewritten
+                .start_file(file.name(), zip::write::SimpleFileOptions::default())
+                .unwrap();
+            std::io::Write::write_all(&mut rewritten, content.as_bytes()).unwrap();
+        }
+        let data = rewritten.finish().unwrap().into_inner();
+
+        let document = Document::from_bytes(&data).unwrap();
+        let warnings: Vec<_> = document.warnings.iter().map(|w| w.to_string()).collect();
+        assert_eq!(
+            warnings,
+            [
+                "word/document.xml: table 1: chart rId99 has no data to show, skipped",
+                "word/document.xml: after table 1: unsupported content control, skipped",
+            ]
+        );
+        assert_eq!(
+            SourceLocation::after(None).to_string(),
+            "word/document.xml: start of body"
+        );
+    }
+
+    #[test]
+    fn test_normalize_heading_levels() {
+        let normalize = |levels: &[u8]| {
+            let mut heading_levels = HeadingLevels::default();
+            levels
+                .iter()
+                .map(|&level| heading_levels.place(level, true))
+                .collect::<Vec<_>>()
+        };
+        // Starts at Heading 3, skips to 5, then comes back up past the start
+        assert_eq!(
+            normalize(&[3, 5, 4, 3, 2, 4]),
+            [
+                (1, Some(3)),
+                (2, Some(5)),
+                (2, Some(4)),
+                (1, Some(3)),
+                (1, Some(2)),
+                (2, Some(4)),
+            ]
+        );
+        assert_eq!(
+            normalize(&[1, 2, 2, 1]),
+            [(1, None), (2, None), (2, None), (1, None)]
+        );
+        assert_eq!(HeadingLevels::default().place(3, false), (3, None));
+    }
+
+    #[test]
+    fn test_synthesized_outline() {
+        let paragraph = |text: &str, bold: bool| DocumentElement::Paragraph {
+            text: text.to_string(),
+            formatting: TextFormatting {
+                bold,
+                ..TextFormatting::default()
+            },
+            runs: Vec::new(),
+            style: None,
+        };
+        let mut document = Document {
+            title: "Minutes".to_string(),
+            metadata: DocumentMetadata {
+                file_path: "minutes.docx".to_string(),
+                file_size: 0,
+                word_count: 0,
+                page_count: 1,
+                created: None,
+                modified: None,
+                author: None,
+            },
+            elements: vec![
+                paragraph("Attendees", true),
+                paragraph(
+                    "Ana, Ben and Chloé were present for the whole meeting.",
+                    false,
+                ),
+                paragraph("The budget is approved.", true),
+                paragraph("Actions", true),
+                paragraph("Ben sends the draft to the board by Friday.", false),
+            ],
+            image_options: ImageOptions::default(),
+            warnings: Vec::new(),
+            bookmarks: BTreeMap::new(),
+            hygiene: Hygiene::default(),
+            image_dir: None,
+            pages: PageMap::default(),
+        };
+        let titles = |document: &Document, synthesis: &str| {
+            let options = OutlineOptions {
+                synthesize: Some(synthesis.parse().unwrap()),
+                ..OutlineOptions::default()
+            };
+            generate_outline_with(document, &options)
+                .into_iter()
+                .map(|item| (item.title, item.element_index))
+                .collect::<Vec<_>>()
+        };
+
+        // Bold sentences are body text, not headings
+        assert_eq!(
+            titles(&document, "bold"),
+            [("Attendees".to_string(), 0), ("Actions".to_string(), 3)]
+        );
+        let every_other = titles(&document, "2");
+        assert_eq!(
+            every_other
+                .iter()
+                .map(|(_, index)| *index)
+                .collect::<Vec<_>>(),
+            [0, 2, 4]
+        );
+        assert_eq!(every_other[1].0, "The budget is approved.");
+        assert!("0".parse::<OutlineSynthesis>().is_err());
+
+        // Real headings always win
+        document.elements.insert(
+            0,
+            DocumentElement::Heading {
+                level: 1,
+                text: "Minutes".to_string(),
+                number: None,
+                style: None,
+                source_level: None,
+            },
+        );
+        assert_eq!(titles(&document, "bold"), [("Minutes".to_string(), 0)]);
+    }
+
+    #[test]
+    fn test_hidden_runs() {
+        let run = |run: docx_rs::Run| extract_run_formatting(&run.add_text("x")).hidden;
+        assert!(run(docx_rs::Run::new().vanish()));
+        assert!(run(docx_rs::Run::new().color("FFFFFF")));
+        assert!(run(docx_rs::Run::new().color("000000").highlight("black")));
+        assert!(!run(docx_rs::Run::new()
+            .color("FFFFFF")
+            .highlight("darkBlue")));
+        assert!(!run(docx_rs::Run::new().color("FF0000")));
+    }
+
+    #[test]
+    fn test_run_font() {
+        let run = docx_rs::Run::new().fonts(docx_rs::RunFonts::new().ascii("Consolas"));
+        assert_eq!(run_font(&run).as_deref(), Some("Consolas"));
+        let themed = docx_rs::Run::new().fonts(docx_rs::RunFonts::new().ascii_theme("minorHAnsi"));
+        assert_eq!(run_font(&themed), None);
+        assert_eq!(run_font(&docx_rs::Run::new()), None);
+    }
+
+    #[test]
+    fn test_skipped_paragraph_content() {
+        let para = docx_rs::Paragraph::new()
+            .add_run(docx_rs::Run::new().add_text("See "))
+            .add_hyperlink(
+                docx_rs::Hyperlink::new("https://example.com", docx_rs::HyperlinkType::External)
+                    .add_run(docx_rs::Run::new().add_text("the site")),
+            )
+            .add_bookmark_start(1, "site")
+            .add_bookmark_end(1);
+        assert_eq!(skipped_paragraph_content(&para), ["hyperlink"]);
+        assert_eq!(paragraph_runs(&para, TrackedChanges::Accept).len(), 1);
+    }
+
+    #[test]
+    fn test_scoped_search() {
+        let heading = |level: u8, number: Option<&str>, text: &str| DocumentElement::Heading {
+            level,
+            text: text.to_string(),
+            number: number.map(str::to_string),
+            style: None,
+            source_level: None,
+        };
+        let paragraph = |text: &str| DocumentElement::Paragraph {
+            text: text.to_string(),
+            formatting: TextFormatting::default(),
+            runs: Vec::new(),
+            style: None,
+        };
+        let elements = vec![
+            paragraph("Risk appetite is set yearly"),
+            heading(1, Some("1."), "Summary"),
+            paragraph("Low risk overall"),
+            heading(1, Some("3."), "Risks"),
+            heading(2, Some("3.1"), "Market"),
+            paragraph("Market risk grew"),
+            DocumentElement::Table {
+                table: TableData::new(
+                    vec![TableCell::new("Risk".to_string())],
+                    vec![vec![TableCell::new("FX".to_string())]],
+                ),
+            },
+            heading(1, Some("4."), "Outlook"),
+            heading(2, Some("4.1"), "Market"),
+        ];
+        assert_eq!(section_range(&elements, 0), 0..1);
+        assert_eq!(section_range(&elements, 5), 4..7);
+        assert_eq!(heading_sections(&elements, "3. Risks"), vec![3..7]);
+        assert_eq!(heading_sections(&elements, "risks"), vec![3..7]);
+        assert_eq!(heading_sections(&elements, "3.1"), vec![4..7]);
+        assert_eq!(heading_sections(&elements, "Market"), vec![4..7, 8..9]);
+        assert_eq!(heading_sections(&elements, "Outlook > Market"), vec![8..9]);
+        assert!(heading_sections(&elements, "Summary > Market").is_empty());
+
+        let document = Document {
+            title: "Register".to_string(),
+            metadata: DocumentMetadata {
+                file_path: "register.docx".to_string(),
+                file_size: 0,
+                word_count: 0,
+                page_count: 1,
+                created: None,
+                modified: None,
+                author: None,
+            },
+            elements,
+            image_options: ImageOptions::default(),
+            warnings: Vec::new(),
+            bookmarks: BTreeMap::new(),
+            hygiene: Hygiene::default(),
+            image_dir: None,
+            pages: PageMap::default(),
+        };
+        let found = |scope: SearchScope| {
+            let options = SearchOptions {
+                scope,
+                ..SearchOptions::default()
+            };
+            search_document_with(&document, "risk", &options)
+                .into_iter()
+                .map(|result| result.element_index)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(found(SearchScope::All), vec![0, 2, 3, 5, 6]);
+        assert_eq!(found(SearchScope::Section(2)), vec![2]);
+        assert_eq!(
+            found(SearchScope::Heading("3. Risks".to_string())),
+            vec![3, 5, 6]
+        );
+        assert_eq!(found(SearchScope::Tables), vec![6]);
+    }
+
+    #[test]
+    fn test_parse_warning_display() {
+        let warning = ParseWarning {
+            location: SourceLocation::paragraph(241),
+            message: "unsupported drawing (text box), skipped".to_string(),
+        };
+        assert_eq!(
+            warning.to_string(),
+            "word/document.xml: paragraph 241: unsupported drawing (text box), skipped"
+        );
+        assert_eq!(
+            SourceLocation::table(3).to_string(),
+            "word/document.xml: table 3"
+        );
+    }
+}
+
+/// Map style ids (`Heading1`) to their display names (`heading 1`)
+fn style_names(styles: &docx_rs::Styles) -> HashMap<String, String> {
+    styles
+        .styles
+        .iter()
+        .filter_map(|style| {
+            // `Name` keeps its value private but serializes to it
+            let name = serde_json::to_value(&style.name).ok()?;
+            Some((style.style_id.clone(), name.as_str()?.to_string()))
+        })
+        .filter(|(_, name)| !name.is_empty())
+        .collect()
+}
+
+fn extract_run_formatting(run: &docx_rs::Run) -> TextFormatting {
+    let mut formatting = TextFormatting::default();
+
+    // Access run properties directly (they're not optional in current API)
+    let props = &run.run_property;
+    formatting.bold = props.bold.is_some();
+    formatting.italic = props.italic.is_some();
+    formatting.underline = props.underline.is_some();
+
+    // Extract color information
+    if let Some(color) = &props.color {
+        // Extract color value through debug formatting as a workaround for private field access
+        let color_debug = format!("{color:?}");
+        if let Some(start) = color_debug.find("val: \"") {
+            // Safe: searching for ASCII strings in debug output
+            let search_from = start + 6; // length of "val: \""
+            if let Some(end) = color_debug[search_from..].find("\"") {
+                let color_val = &color_debug[search_from..search_from + end];
+                formatting.color = Some(color_val.to_string());
+            }
+        }
+    }
+
+    // Highlight keeps its color name private; it serializes as just that name
+    let highlight = props.highlight.as_ref().and_then(|highlight| {
+        match serde_json::to_value(highlight).ok()? {
+            serde_json::Value::String(name) => Some(name),
+            _ => None,
+        }
+    });
+    formatting.hidden = props.vanish.is_some()
+        || formatting.color.as_deref().is_some_and(|color| {
+            is_invisible(
+                color,
+                highlight.as_deref(),
+                props.shading.as_ref().map(|shading| shading.fill.as_str()),
+            )
+        });
+
+    // For now, skip font size extraction due to API complexity
+    // TODO: Add font size extraction when we understand the API better
+
+    formatting
+}
+
+fn detect_heading_from_text(text: &str, formatting: &TextFormatting) -> Option<u8> {
+    let text = text.trim();
+
+    // Be much more conservative and selective
+    if text.len() < 100 && !text.contains('\n') {
+        // Exclude common non-heading patterns first
+        if is_likely_list_item(text) || is_likely_sentence(text) {
+            return None;
+        }
+
+        // Exclude patterns that are clearly not headings
+        if text.starts_with("⏺")
+            || text.starts_with("⎿")
+            || text.starts_with("☐")
+            || text.starts_with("☒")
+        {
+            return None;
+        }
+
+        // Exclude if it contains typical sentence patterns
+        if text.contains(" the ")
+            || text.contains(" and ")
+            || text.contains(" with ")
+            || text.contains(" for ")
+        {
+            return None;
+        }
+
+        // Strong indicators of headings
+        if formatting.bold && text.len() < 60 && text.len() > 5 {
+            // Bold text that's reasonably short is likely a heading
+            if !text.ends_with('.')
+                && !text.ends_with(',')
+                && !text.ends_with(';')
+                && !text.ends_with(':')
+            {
+                return Some(determine_heading_level_from_text(text));
+            }
+        }
+
+        // Check if it's all caps (but not just a short word)
+        if text.len() > 15
+            && text.len() < 50
+            && text.chars().all(|c| {
+                c.is_uppercase() || c.is_whitespace() || c.is_numeric() || c.is_ascii_punctuation()
+            })
+        {
+            return Some(1);
+        }
+
+        // Very specific patterns that indicate headings
+        if text.starts_with("Chapter ") || text.starts_with("Section ") || text.starts_with("Part ")
+        {
+            return Some(determine_heading_level_from_text(text));
+        }
+
+        // Look for standalone phrases that could be headings (very conservative)
+        if text.len() < 40
+            && text.len() > 10
+            && !text.ends_with('.')
+            && !text.contains(',')
+            && !text.contains('(')
+            && !text.contains(':')
+        {
+            // Check if it has heading-like characteristics
+            let words = text.split_whitespace().count();
+            if (2..=5).contains(&words) {
+                // Must contain at least one meaningful word (longer than 3 chars)
+                let has_meaningful_word = text
+                    .split_whitespace()
+                    .any(|word| word.len() > 3 && word.chars().all(|c| c.is_alphabetic()));
+
+                if has_meaningful_word && text.chars().next().is_some_and(|c| c.is_uppercase()) {
+                    return Some(determine_heading_level_from_text(text));
+                }
+            }
+        }
+    }
+
+    None
+}
+
+fn determine_heading_level_from_text(text: &str) -> u8 {
+    // Simple heuristic: shorter text = higher level (lower number)
+    if text.len() < 20 {
+        1
+    } else if text.len() < 40 {
+        2
+    } else {
+        3
+    }
+}
+
+fn is_likely_list_item(text: &str) -> bool {
+    let text = text.trim();
+
+    // Skip Word-formatted list items to avoid reprocessing
+    if text.starts_with("__WORD_LIST__") {
+        return false;
+    }
+
+    // Check for numbered list patterns that are NOT headings
+    if text.starts_with(char::is_numeric) {
+        // If it starts with a number followed by "." and then has substantial content,
+        // it's likely a list item, not a heading
+        if let Some(dot_pos) = text.find('.') {
+            // Safe: '.' is ASCII, so dot_pos+1 is guaranteed to be a char boundary
+            let after_dot = &text[dot_pos + 1..].trim();
+            // If there's substantial content after the number and dot, it's likely a list item
+            if after_dot.len() > 20 {
+                return true;
+            }
+        }
+    }
+
+    // Check for bullet point patterns
+    if text.starts_with("• ") || text.starts_with("- ") || text.starts_with("* ") {
+        return true;
+    }
+
+    // Check for lettered lists
+    if text.len() > 3 && text.chars().nth(1) == Some('.') {
+        let first_char = text.chars().next().unwrap();
+        if first_char.is_ascii_lowercase() || first_char.is_ascii_uppercase() {
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Merge runs of list-like paragraphs into lists. `pages` holds the page of
+/// each element and is merged alongside: a list starts on its first item's page.
+/// Paragraphs in `word_items` (by index) are grouped by their Word numbering
+/// instance and keep Word's numbers; the rest are recognised by their text.
+fn group_list_items(
+    elements: Vec<DocumentElement>,
+    pages: Vec<usize>,
+    word_items: &HashMap<usize, WordListItem>,
+) -> (Vec<DocumentElement>, Vec<usize>) {
+    /// List items collected so far
+    struct OpenList {
+        items: Vec<ListItem>,
+        ordered: bool,
+        start: Option<u32>,
+        page: usize,
+        /// Word numbering instance, `None` for a list recognised by its text
+        num_id: Option<i32>,
+    }
+
+    fn finish(list: Option<OpenList>, result: &mut Vec<DocumentElement>, pages: &mut Vec<usize>) {
+        if let Some(list) = list {
+            result.push(DocumentElement::List {
+                items: list.items,
+                ordered: list.ordered,
+                start: list.start,
+            });
+            pages.push(list.page);
+        }
+    }
+
+    let mut result = Vec::new();
+    let mut result_pages = Vec::new();
+    let mut current: Option<OpenList> = None;
+
+    for (index, (element, page)) in elements.into_iter().zip(pages).enumerate() {
+        if let Some(item) = word_items.get(&index) {
+            // Another numbering instance is another list, even right after
+            // this one; the same one goes on, numbers included
+            if current
+                .as_ref()
+                .is_some_and(|list| list.num_id != Some(item.num_id))
+            {
+                finish(current.take(), &mut result, &mut result_pages);
+            }
+            let is_ordered = item.label.is_some();
+            let list = current.get_or_insert_with(|| OpenList {
+                items: Vec::new(),
+                ordered: is_ordered,
+                start: None,
+                page,
+                num_id: Some(item.num_id),
+            });
+            list.items.push(ListItem {
+                text: item.text.clone(),
+                level: item.level,
+                ordered: (is_ordered != list.ordered).then_some(is_ordered),
+                label: item.label.clone(),
+            });
+            continue;
+        }
+        let DocumentElement::Paragraph { text, .. } = &element else {
+            // Tables, headings and the like end the list
+            finish(current.take(), &mut result, &mut result_pages);
+            result.push(element);
+            result_pages.push(page);
+            continue;
+        };
+        if !is_likely_list_item(text) {
+            // A paragraph between items ends the list; one that resumes
+            // afterwards keeps its numbers through `start`
+            finish(current.take(), &mut result, &mut result_pages);
+            result.push(element);
+            result_pages.push(page);
+            continue;
+        }
+
+        let is_ordered = text.trim().starts_with(char::is_numeric);
+        let level = calculate_list_level(text);
+
+        // Only a change of kind at the top level starts a new list; nested
+        // items of the other kind (numbered steps under a bullet) stay in it
+        if current
+            .as_ref()
+            .is_some_and(|list| list.num_id.is_some() || (level == 0 && list.ordered != is_ordered))
+        {
+            finish(current.take(), &mut result, &mut result_pages);
+        }
+        let list = current.get_or_insert_with(|| OpenList {
+            items: Vec::new(),
+            ordered: is_ordered,
+            // A numbered list resuming after a paragraph goes on from the
+            // number it was written with
+            start: (is_ordered && level == 0)
+                .then(|| written_list_number(text))
+                .flatten()
+                .filter(|number| *number > 1),
+            page,
+            num_id: None,
+        });
+        list.items.push(ListItem {
+            text: clean_list_item_text(text),
+            level,
+            ordered: (is_ordered != list.ordered).then_some(is_ordered),
+            label: None,
+        });
+    }
+
+    // Don't forget the last list if the document ends with one
+    finish(current, &mut result, &mut result_pages);
+
+    (result, result_pages)
+}
+
+/// Number a list item was written with, e.g. 3 for `3. Third step`
+fn written_list_number(text: &str) -> Option<u32> {
+    let (number, _) = text.trim().split_once('.')?;
+    number.parse().ok()
+}
+
+/// Number shown before each item of a list: counted per nesting level and
+/// restarted under each parent, with the top level going on from `start`.
+/// Bulleted items have none.
+pub fn list_item_numbers(
+    items: &[ListItem],
+    ordered: bool,
+    start: Option<u32>,
+) -> Vec<Option<u32>> {
+    let mut counters: Vec<u32> = Vec::new();
+    items
+        .iter()
+        .map(|item| {
+            let level = item.level as usize;
+            let first_at_top = counters.is_empty();
+            counters.resize(level + 1, 0);
+            if level == 0 && first_at_top {
+                counters[0] = start.unwrap_or(1).saturating_sub(1);
+            }
+            counters[level] += 1;
+            item.is_ordered(ordered).then_some(counters[level])
+        })
+        .collect()
+}
+
+/// What is written before each item of a list: Word's own number, or the
+/// counted one from [`list_item_numbers`] followed by a dot. Bulleted items
+/// have none.
+pub fn list_item_labels(
+    items: &[ListItem],
+    ordered: bool,
+    start: Option<u32>,
+) -> Vec<Option<String>> {
+    items
+        .iter()
+        .zip(list_item_numbers(items, ordered, start))
+        .map(|(item, number)| {
+            let number = number?;
+            Some(item.label.clone().unwrap_or_else(|| format!("{number}.")))
+        })
+        .collect()
+}
+
+fn calculate_list_level(text: &str) -> u8 {
+    // Count leading whitespace to determine nesting level
+    let leading_spaces = text.len() - text.trim_start().len();
+
+    // Convert spaces to levels (every 2-4 spaces = 1 level)
+    // Use 2 spaces per level as it's common in Word documents
+    (leading_spaces / 2) as u8
+}
+
+fn clean_list_item_text(text: &str) -> String {
+    let text = text.trim();
+
+    // Remove bullet points (handle Unicode characters properly)
+    if text.starts_with("• ") {
+        return text.strip_prefix("• ").unwrap_or(text).trim().to_string();
+    }
+    if text.starts_with("- ") || text.starts_with("* ") {
+        return text
+            .strip_prefix("- ")
+            .or_else(|| text.strip_prefix("* "))
+            .unwrap_or(text)
+            .trim()
+            .to_string();
+    }
+
+    // Remove numbered list prefixes (Unicode-safe)
+    if let Some(dot_pos) = text.find('.') {
+        let prefix = &text[..dot_pos];
+        if prefix.chars().all(|c| c.is_ascii_digit()) {
+            // Safe: find() returns byte position, but we know '.' is ASCII
+            // so dot_pos+1 is guaranteed to be a valid char boundary
+            return text[dot_pos + 1..].trim().to_string();
+        }
+    }
+
+    // Remove lettered list prefixes (Unicode-safe)
+    if text.chars().count() > 2 && text.chars().nth(1) == Some('.') {
+        let first_char = text.chars().next().unwrap();
+        if first_char.is_ascii_lowercase() || first_char.is_ascii_uppercase() {
+            // Safe: skip the first character and the dot, both ASCII
+            return text.chars().skip(2).collect::<String>().trim().to_string();
+        }
+    }
+
+    text.to_string()
+}
+
+fn is_likely_sentence(text: &str) -> bool {
+    let text = text.trim();
+
+    // If it contains multiple sentences, it's probably not a heading
+    if text.matches(". ").count() > 1 {
+        return true;
+    }
+
+    // If it ends with common sentence endings and is long, it's probably a sentence
+    if text.len() > 80 && (text.ends_with('.') || text.ends_with('!') || text.ends_with('?')) {
+        return true;
+    }
+
+    // If it contains common sentence connectors, it's likely a sentence
+    if text.contains(" and ")
+        || text.contains(" but ")
+        || text.contains(" however ")
+        || text.contains(" therefore ")
+    {
+        return true;
+    }
+
+    false
+}
+
+/// Find elements containing `query`, comparing normalized, case-folded text
+/// (see [`crate::text_fold`]) so `strasse` also finds `Straße`
+#[allow(dead_code)] // Only used through the library
+pub fn search_document(document: &Document, query: &str) -> Vec<SearchResult> {
+    search_document_with(document, query, &SearchOptions::default())
+}
+
+/// Find elements matching `query`, exactly or fuzzily as `options` ask
+pub fn search_document_with(
+    document: &Document,
+    query: &str,
+    options: &SearchOptions,
+) -> Vec<SearchResult> {
+    let mut results = Vec::new();
+    let mut search = |element_index: usize, text: &str| {
+        if let Some(range) = crate::text_fold::find_matches(text, query, options.fuzzy).first() {
+            results.push(SearchResult {
+                element_index,
+                text: text.to_string(),
+                start_pos: range.start,
+                end_pos: range.end,
+            });
+        }
+    };
+
+    // Sections to look in, or `None` for all of them
+    let sections = match &options.scope {
+        SearchScope::All | SearchScope::Tables => None,
+        SearchScope::Section(element) => Some(vec![section_range(&document.elements, *element)]),
+        SearchScope::Heading(path) => Some(heading_sections(&document.elements, path)),
+    };
+    let in_scope = |index: usize, element: &DocumentElement| {
+        sections
+            .as_ref()
+            .is_none_or(|sections| sections.iter().any(|section| section.contains(&index)))
+            && (options.scope != SearchScope::Tables
+                || matches!(element, DocumentElement::Table { .. }))
+    };
+
+    for (element_index, element) in document.elements.iter().enumerate() {
+        if !in_scope(element_index, element) {
+            continue;
+        }
+        match element {
+            DocumentElement::Heading { text, .. } | DocumentElement::Paragraph { text, .. } => {
+                search(element_index, text)
+            }
+            DocumentElement::List { items, .. } => {
+                for item in items {
+                    search(element_index, &item.text);
+                }
+            }
+            DocumentElement::Table { table } => {
+                for cell in table.headers.iter().chain(table.rows.iter().flatten()) {
+                    search(element_index, &cell.content);
+                }
+            }
+            DocumentElement::Image {
+                description,
+                ocr_text,
+                ..
+            } => {
+                search(element_index, description);
+                if let Some(ocr_text) = ocr_text {
+                    search(element_index, ocr_text);
+                }
+            }
+            DocumentElement::Quote { text, .. }
+            | DocumentElement::Caption { text, .. }
+            | DocumentElement::Title { text, .. }
+            | DocumentElement::Subtitle { text, .. }
+            | DocumentElement::Equation { text, .. } => search(element_index, text),
+            DocumentElement::CodeBlock { lines, .. } => {
+                for line in lines {
+                    search(element_index, line);
+                }
+            }
+            DocumentElement::PageBreak => {}
+        }
+    }
+
+    options.events.emit(Event::SearchCompleted {
+        query: query.to_string(),
+        results: results.len(),
+    });
+    results
+}
+
+/// Elements of the section containing `element`: from its heading to the
+/// next heading at the same or a higher level. Before the first heading,
+/// the elements up to it.
+pub fn section_range(elements: &[DocumentElement], element: usize) -> std::ops::Range<usize> {
+    let top = element.min(elements.len());
+    let heading_level = |element: &DocumentElement| match element {
+        DocumentElement::Heading { level, .. } => Some(*level),
+        _ => None,
+    };
+    let start = elements[..elements.len().min(top + 1)]
+        .iter()
+        .rposition(|element| heading_level(element).is_some())
+        .unwrap_or(0);
+    let level = heading_level(&elements[start.min(elements.len().saturating_sub(1))]);
+    let end = elements
+        .iter()
+        .enumerate()
+        .skip(start + 1)
+        .find(|(_, element)| {
+            heading_level(element).is_some_and(|next| level.is_none_or(|level| next <= level))
+        })
+        .map_or(elements.len(), |(index, _)| index);
+    start.min(end)..end
+}
+
+/// Top-level blocks of `document` (paragraphs and tables), counted from 0
+/// like [`crate::pages::scan_page_starts`]
+fn top_level_blocks(
+    document: &docx_rs::Document,
+) -> impl Iterator<Item = (usize, &docx_rs::DocumentChild)> {
+    document
+        .children
+        .iter()
+        .filter(|child| {
+            matches!(
+                child,
+                docx_rs::DocumentChild::Paragraph(_) | docx_rs::DocumentChild::Table(_)
+            )
+        })
+        .enumerate()
+}
+
+/// Relationship ids of the pictures in `para`
+fn picture_ids(para: &docx_rs::Paragraph) -> impl Iterator<Item = &str> {
+    para.children
+        .iter()
+        .filter_map(|child| match child {
+            docx_rs::ParagraphChild::Run(run) => Some(&run.children),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|child| match child {
+            docx_rs::RunChild::Drawing(drawing) => match &drawing.data {
+                Some(docx_rs::DrawingData::Pic(pic)) if !pic.id.is_empty() => Some(pic.id.as_str()),
+                _ => None,
+            },
+            _ => None,
+        })
+}
+
+/// The heading `para` becomes by its style: one with text that isn't a
+/// numbered list item
+fn style_heading(para: &docx_rs::Paragraph, changes: TrackedChanges) -> Option<HeadingInfo> {
+    if para.property.numbering_property.is_some() {
+        return None;
+    }
+    detect_heading_with_numbering(para, changes)
+        .filter(|_| !extract_paragraph_text(para, changes).is_empty())
+}
+
+/// Top-level blocks of the sections under the headings matching `path` (see
+/// [`heading_sections`]), found from the heading styles alone so the rest of
+/// the body needn't be turned into elements. Headings told apart only by
+/// their formatting aren't seen.
+fn section_blocks(
+    document: &docx_rs::Document,
+    path: &str,
+    options: &ParseOptions,
+    auto_number: bool,
+) -> Vec<std::ops::Range<usize>> {
+    let mut tracker = HeadingNumberTracker::new();
+    if auto_number {
+        tracker.enable_auto_numbering();
+    }
+    let mut levels = HeadingLevels::default();
+    let mut headings = Vec::new();
+    let mut heading_blocks = Vec::new();
+    let mut block_count = 0;
+    for (block, child) in top_level_blocks(document) {
+        block_count = block + 1;
+        let docx_rs::DocumentChild::Paragraph(para) = child else {
+            continue;
+        };
+        let Some(info) = style_heading(para, options.changes) else {
+            continue;
+        };
+        let (level, source_level) = levels.place(info.level, options.normalize_heading_levels);
+        let number = info
+            .number
+            .or_else(|| Some(tracker.get_number(level)).filter(|number| !number.is_empty()));
+        headings.push(DocumentElement::Heading {
+            level,
+            text: info
+                .clean_text
+                .unwrap_or_else(|| extract_paragraph_text(para, options.changes)),
+            number,
+            style: None,
+            source_level,
+        });
+        heading_blocks.push(block);
+    }
+
+    heading_sections(&headings, path)
+        .into_iter()
+        .map(|range| {
+            heading_blocks[range.start]
+                ..heading_blocks
+                    .get(range.end)
+                    .copied()
+                    .unwrap_or(block_count)
+        })
+        .collect()
+}
+
+/// Sections under the headings matching `path`: heading texts separated by
+/// `>`, each compared without regard to case and with or without the
+/// heading's number, e.g. `3. Risks`, `Risks` or `Report > Risks`. Each
+/// part after the first must be under a heading matching the one before.
+pub fn heading_sections(elements: &[DocumentElement], path: &str) -> Vec<std::ops::Range<usize>> {
+    let parts: Vec<String> = path
+        .split('>')
+        .map(normalize_heading)
+        .filter(|part| !part.is_empty())
+        .collect();
+    let Some((last, ancestors)) = parts.split_last() else {
+        return Vec::new();
+    };
+
+    // Headings enclosing the current element, outermost first
+    let mut open: Vec<(u8, &str, Option<&str>)> = Vec::new();
+    let mut sections = Vec::new();
+    for (index, element) in elements.iter().enumerate() {
+        let DocumentElement::Heading {
+            level,
+            text,
+            number,
+            ..
+        } = element
+        else {
+            continue;
+        };
+        open.retain(|(open_level, ..)| open_level < level);
+        let matches = |part: &str, text: &str, number: Option<&str>| {
+            let text = normalize_heading(text);
+            part == text
+                || number.is_some_and(|number| {
+                    let number = normalize_heading(number);
+                    part == number || part == format!("{number} {text}")
+                })
+        };
+        if matches(last, text, number.as_deref()) {
+            // The ancestors must appear in order among the enclosing headings
+            let mut enclosing = open.iter();
+            let nested = ancestors
+                .iter()
+                .all(|part| enclosing.any(|(_, text, number)| matches(part, text, *number)));
+            if nested {
+                sections.push(section_range(elements, index));
+            }
+        }
+        open.push((*level, text, number.as_deref()));
+    }
+    sections
+}
+
+/// Heading text for comparison: lowercase, single spaces, without the dot
+/// after a number such as `3.`
+fn normalize_heading(text: &str) -> String {
+    text.split_whitespace()
+        .map(|word| {
+            let numbering = word.chars().all(|c| c.is_ascii_digit() || c == '.');
+            if numbering {
+                word.trim_end_matches('.')
+            } else {
+                word
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+#[allow(dead_code)] // Only used through the library (folder reports)
+pub fn generate_outline(document: &Document) -> Vec<OutlineItem> {
+    generate_outline_with(document, &OutlineOptions::default())
+}
+
+/// Outline limited to `options.max_depth`, with or without heading numbers
+pub fn generate_outline_with(document: &Document, options: &OutlineOptions) -> Vec<OutlineItem> {
+    let mut outline = Vec::new();
+
+    for (index, element) in document.elements.iter().enumerate() {
+        if let DocumentElement::Heading {
+            level,
+            text,
+            number,
+            ..
+        } = element
+        {
+            if options.max_depth.is_some_and(|depth| *level > depth) {
+                continue;
+            }
+            let title = if let Some(number) = number.as_ref().filter(|_| options.show_numbers) {
+                format!("{number} {text}")
+            } else {
+                text.clone()
+            };
+            outline.push(OutlineItem {
+                title,
+                level: *level,
+                element_index: index,
+            });
+        }
+    }
+
+    let has_headings = document
+        .elements
+        .iter()
+        .any(|element| matches!(element, DocumentElement::Heading { .. }));
+    match options.synthesize {
+        Some(synthesis) if !has_headings => synthesize_outline(document, synthesis),
+        _ => outline,
+    }
+}
+
+/// Outline entries standing in for headings, all at level 1
+fn synthesize_outline(document: &Document, synthesis: OutlineSynthesis) -> Vec<OutlineItem> {
+    let entry = |title: String, element_index| OutlineItem {
+        title,
+        level: 1,
+        element_index,
+    };
+    let paragraphs = document
+        .elements
+        .iter()
+        .enumerate()
+        .filter_map(|(index, element)| match element {
+            DocumentElement::Paragraph {
+                text, formatting, ..
+            } if !text.trim().is_empty() => Some((index, text, formatting)),
+            _ => None,
+        });
+
+    match synthesis {
+        OutlineSynthesis::Bold => paragraphs
+            .filter(|(_, text, formatting)| {
+                // Standalone lines, not bold passages of body text
+                formatting.bold && text.chars().count() <= 80 && !text.trim_end().ends_with('.')
+            })
+            .map(|(index, text, _)| entry(text.trim().to_string(), index))
+            .collect(),
+        OutlineSynthesis::Pages => (1..=document.pages.count)
+            .filter_map(|page| {
+                let index = document.pages.first_element(page)?;
+                let opening = document
+                    .elements
+                    .get(index)
+                    .map(element_opening)
+                    .unwrap_or_default();
+                Some(entry(format!("Page {page}: {opening}"), index))
+            })
+            .collect(),
+        OutlineSynthesis::Every(count) => paragraphs
+            .step_by(count)
+            .map(|(index, text, _)| entry(snippet(text), index))
+            .collect(),
+    }
+}
+
+/// Opening words of an element, to label an outline entry
+fn element_opening(element: &DocumentElement) -> String {
+    match element {
+        DocumentElement::Paragraph { text, .. }
+        | DocumentElement::Heading { text, .. }
+        | DocumentElement::Quote { text, .. }
+        | DocumentElement::Title { text, .. } => snippet(text),
+        DocumentElement::List { items, .. } => items
+            .first()
+            .map(|item| snippet(&item.text))
+            .unwrap_or_default(),
+        DocumentElement::Table { table } => table
+            .metadata
+            .title
+            .clone()
+            .unwrap_or_else(|| "Table".to_string()),
+        _ => String::new(),
+    }
+}
+
+/// The first words of `text`, up to about 60 characters
+fn snippet(text: &str) -> String {
+    const LENGTH: usize = 60;
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if crate::text_width::display_width(&text) <= LENGTH {
+        return text;
+    }
+    let cut = crate::text_width::truncate_to_width(&text, LENGTH);
+    let cut = cut.rsplit_once(' ').map_or(cut, |(words, _)| words);
+    format!("{cut}…")
+}
+
+fn extract_table_data(table: &docx_rs::Table) -> Option<DocumentElement> {
+    let mut header_cells = Vec::new();
+    let mut data_rows = Vec::new();
+
+    let mut is_first_row = true;
+    let mut _raw_headers = Vec::new();
+    let mut raw_rows = Vec::new();
+
+    // First pass: extract raw text content
+    for table_child in &table.rows {
+        let docx_rs::TableChild::TableRow(row) = table_child;
+        let mut row_cells = Vec::new();
+
+        for row_child in &row.cells {
+            let docx_rs::TableRowChild::TableCell(cell) = row_child;
+            let mut cell_text = String::new();
+            let mut cell_formatting = TextFormatting::default();
+
+            // Extract text and formatting from all content in the cell
+            for content in &cell.children {
+                match content {
+                    docx_rs::TableCellContent::Paragraph(para) => {
+                        for para_child in &para.children {
+                            if let docx_rs::ParagraphChild::Run(run) = para_child {
+                                // Extract formatting from the first run
+                                if !cell_formatting.bold && !cell_formatting.italic {
+                                    cell_formatting = extract_run_formatting(run);
+                                }
+
+                                for run_child in &run.children {
+                                    if let docx_rs::RunChild::Text(text_elem) = run_child {
+                                        if !cell_text.is_empty() && !cell_text.ends_with(' ') {
+                                            cell_text.push(' ');
+                                        }
+                                        cell_text.push_str(&text_elem.text);
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    _ => {
+                        // Handle nested tables or other content if needed
+                    }
+                }
+            }
+
+            let table_cell =
+                TableCell::new(cell_text.trim().to_string()).with_formatting(cell_formatting);
+            row_cells.push(table_cell);
+        }
+
+        if !row_cells.is_empty() {
+            let raw_text: Vec<String> = row_cells.iter().map(|c| c.content.clone()).collect();
+
+            if is_first_row && appears_to_be_header(&raw_text) {
+                _raw_headers = raw_text;
+                header_cells = row_cells;
+                is_first_row = false;
+            } else {
+                raw_rows.push(raw_text);
+                data_rows.push(row_cells);
+                is_first_row = false;
+            }
+        }
+    }
+
+    // If no headers were detected, use the first row as headers
+    if header_cells.is_empty() && !data_rows.is_empty() {
+        header_cells = data_rows.remove(0);
+        raw_rows.remove(0);
+    }
+
+    // Return table only if it has content
+    if !header_cells.is_empty() || !data_rows.is_empty() {
+        let table_data = TableData::new(header_cells, data_rows);
+        Some(DocumentElement::Table { table: table_data })
+    } else {
+        None
+    }
+}
+
+fn appears_to_be_header(row: &[String]) -> bool {
+    // Heuristics to detect if a row is likely a header
+    let total_chars: usize = row.iter().map(|cell| cell.len()).sum();
+    let avg_length = if !row.is_empty() {
+        total_chars / row.len()
+    } else {
+        0
+    };
+
+    // Headers tend to be shorter and more concise
+    if avg_length > 50 {
+        return false;
+    }
+
+    // Check if most cells contain typical header words or are short phrases
+    let header_indicators = row
+        .iter()
+        .filter(|cell| {
+            let cell_lower = cell.to_lowercase();
+            let word_count = cell.split_whitespace().count();
+
+            // Short phrases (1-3 words) are often headers
+            if word_count <= 3 && !cell.trim().is_empty() {
+                return true;
+            }
+
+            // Common header words
+            if cell_lower.contains("name")
+                || cell_lower.contains("date")
+                || cell_lower.contains("amount")
+                || cell_lower.contains("type")
+                || cell_lower.contains("status")
+                || cell_lower.contains("id")
+                || cell_lower.contains("description")
+                || cell_lower.contains("count")
+            {
+                return true;
+            }
+
+            false
+        })
+        .count();
+
+    // If more than half the cells look like headers, treat the row as a header
+    header_indicators > row.len() / 2
+}
+
+// Enhanced table processing functions
+impl TableData {
+    pub fn new(headers: Vec<TableCell>, rows: Vec<Vec<TableCell>>) -> Self {
+        let column_count = headers.len();
+        let row_count = rows.len();
+        let has_headers = !headers.is_empty();
+
+        // Calculate optimal column widths
+        let column_widths = calculate_column_widths(&headers, &rows);
+
+        // Determine column alignments
+        let column_alignments = determine_column_alignments(&headers, &rows);
+
+        let metadata = TableMetadata {
+            column_count,
+            row_count,
+            has_headers,
+            column_widths,
+            column_alignments,
+            title: None,
+        };
+
+        Self {
+            headers,
+            rows,
+            metadata,
+        }
+    }
+
+    pub fn _get_column_width(&self, column_index: usize) -> usize {
+        self.metadata
+            .column_widths
+            .get(column_index)
+            .copied()
+            .unwrap_or(10)
+    }
+
+    /// Index of the column `column` refers to: a 1-based number, or a
+    /// header matched without regard to case
+    pub fn column_index(&self, column: &str) -> Option<usize> {
+        let column = column.trim();
+        if let Ok(number) = column.parse::<usize>() {
+            return (1..=self.metadata.column_count)
+                .contains(&number)
+                .then(|| number - 1);
+        }
+        let column = column.to_lowercase();
+        self.headers
+            .iter()
+            .position(|header| header.content.trim().to_lowercase() == column)
+    }
+
+    /// Type of each column: the type of most of its non-empty cells when
+    /// more than 70% of them agree, otherwise text. A column without values
+    /// is empty.
+    pub fn column_types(&self) -> Vec<CellDataType> {
+        (0..self.metadata.column_count)
+            .map(|column| {
+                let mut counts: Vec<(CellDataType, usize)> = Vec::new();
+                let mut total = 0;
+                for cell in self.rows.iter().filter_map(|row| row.get(column)) {
+                    if cell.data_type == CellDataType::Empty {
+                        continue;
+                    }
+                    total += 1;
+                    match counts.iter_mut().find(|(t, _)| *t == cell.data_type) {
+                        Some((_, count)) => *count += 1,
+                        None => counts.push((cell.data_type, 1)),
+                    }
+                }
+                if total == 0 {
+                    return CellDataType::Empty;
+                }
+                // max_by_key keeps the last of equal counts; reversed, the
+                // first type seen wins a tie
+                let (data_type, count) = counts
+                    .into_iter()
+                    .rev()
+                    .max_by_key(|(_, count)| *count)
+                    .unwrap_or_default();
+                if count as f32 / total as f32 > 0.7 {
+                    data_type
+                } else {
+                    CellDataType::Text
+                }
+            })
+            .collect()
+    }
+
+    /// Give every non-empty cell in `column` the type `data_type` and the
+    /// alignment that goes with it
+    pub fn set_column_type(&mut self, column: usize, data_type: CellDataType) {
+        for cell in self.rows.iter_mut().filter_map(|row| row.get_mut(column)) {
+            if cell.data_type != CellDataType::Empty {
+                cell.data_type = data_type;
+                cell.alignment = default_alignment_for_type(data_type);
+            }
+        }
+        self.metadata.column_alignments = determine_column_alignments(&self.headers, &self.rows);
+    }
+
+    pub fn _get_column_alignment(&self, column_index: usize) -> TextAlignment {
+        self.metadata
+            .column_alignments
+            .get(column_index)
+            .copied()
+            .unwrap_or(TextAlignment::Left)
+    }
+}
+
+impl TableCell {
+    pub fn new(content: String) -> Self {
+        let data_type = detect_cell_data_type(&content);
+        let alignment = default_alignment_for_type(data_type);
+
+        Self {
+            content,
+            alignment,
+            formatting: TextFormatting::default(),
+            data_type,
+        }
+    }
+
+    pub fn _with_alignment(mut self, alignment: TextAlignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    pub fn with_formatting(mut self, formatting: TextFormatting) -> Self {
+        self.formatting = formatting;
+        self
+    }
+
+    /// Terminal columns the cell's text takes, double-width characters
+    /// counting twice
+    pub fn display_width(&self) -> usize {
+        crate::text_width::display_width(self.content.trim())
+    }
+}
+
+fn calculate_column_widths(headers: &[TableCell], rows: &TableRows) -> Vec<usize> {
+    if headers.is_empty() {
+        return Vec::new();
+    }
+
+    let mut widths = headers
+        .iter()
+        .map(|h| h.display_width())
+        .collect::<Vec<_>>();
+
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            if let Some(current_width) = widths.get_mut(i) {
+                *current_width = (*current_width).max(cell.display_width());
+            }
+        }
+    }
+
+    // Ensure minimum width of 3 characters per column
+    widths.iter_mut().for_each(|w| *w = (*w).max(3));
+
+    widths
+}
+
+fn determine_column_alignments(headers: &[TableCell], rows: &TableRows) -> Vec<TextAlignment> {
+    let column_count = headers.len();
+    let mut alignments = vec![TextAlignment::Left; column_count];
+
+    for (col_index, alignment) in alignments.iter_mut().enumerate().take(column_count) {
+        let mut numeric_count = 0;
+        let mut total_count = 0;
+
+        // Check data types in this column
+        for row in rows {
+            if let Some(cell) = row.get(col_index) {
+                total_count += 1;
+                if matches!(
+                    cell.data_type,
+                    CellDataType::Number | CellDataType::Currency | CellDataType::Percentage
+                ) {
+                    numeric_count += 1;
+                }
+            }
+        }
+
+        // If more than 70% of cells are numeric, right-align the column
+        if total_count > 0 && (numeric_count as f32 / total_count as f32) > 0.7 {
+            *alignment = TextAlignment::Right;
+        }
+    }
+
+    alignments
+}
+
+fn detect_cell_data_type(content: &str) -> CellDataType {
+    let trimmed = content.trim();
+
+    if trimmed.is_empty() {
+        return CellDataType::Empty;
+    }
+
+    // Check for currency
+    if trimmed.starts_with('$') || trimmed.starts_with('€') || trimmed.starts_with('£') {
+        return CellDataType::Currency;
+    }
+
+    // Check for percentage
+    if trimmed.ends_with('%') {
+        return CellDataType::Percentage;
+    }
+
+    // Check for boolean
+    let lower = trimmed.to_lowercase();
+    if matches!(lower.as_str(), "true" | "false" | "yes" | "no" | "y" | "n") {
+        return CellDataType::Boolean;
+    }
+
+    // Check for number (including with commas)
+    let number_candidate = trimmed.replace(',', "");
+    if number_candidate.parse::<f64>().is_ok() {
+        return CellDataType::Number;
+    }
+
+    // Check for date patterns (basic)
+    if trimmed.contains('/') || trimmed.contains('-') {
+        let parts: Vec<&str> = trimmed.split(['/', '-']).collect();
+        if parts.len() == 3 && parts.iter().all(|p| p.parse::<u32>().is_ok()) {
+            return CellDataType::Date;
+        }
+    }
+
+    CellDataType::Text
+}
+
+fn default_alignment_for_type(data_type: CellDataType) -> TextAlignment {
+    match data_type {
+        CellDataType::Number | CellDataType::Currency | CellDataType::Percentage => {
+            TextAlignment::Right
+        }
+        CellDataType::Boolean => TextAlignment::Center,
+        _ => TextAlignment::Left,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OutlineItem {
+    pub title: String,
+    pub level: u8,
+    pub element_index: usize,
+}
+
+fn clean_word_list_markers(elements: Vec<DocumentElement>) -> Vec<DocumentElement> {
+    elements
+        .into_iter()
+        .map(|element| match element {
+            DocumentElement::Paragraph {
+                text,
+                formatting,
+                runs,
+                style,
+            } => {
+                let cleaned_text = if text.starts_with("__WORD_LIST__") {
+                    text.strip_prefix("__WORD_LIST__")
+                        .unwrap_or(&text)
+                        .to_string()
+                } else {
+                    text
+                };
+                DocumentElement::Paragraph {
+                    text: cleaned_text,
+                    formatting,
+                    runs,
+                    style,
+                }
+            }
+            DocumentElement::List {
+                items,
+                ordered,
+                start,
+            } => {
+                let cleaned_items = items
+                    .into_iter()
+                    .map(|item| {
+                        let cleaned_text = if item.text.starts_with("__WORD_LIST__") {
+                            item.text
+                                .strip_prefix("__WORD_LIST__")
+                                .unwrap_or(&item.text)
+                                .to_string()
+                        } else {
+                            item.text
+                        };
+                        ListItem {
+                            text: cleaned_text,
+                            ..item
+                        }
+                    })
+                    .collect();
+                DocumentElement::List {
+                    items: cleaned_items,
+                    ordered,
+                    start,
+                }
+            }
+            other => other,
+        })
+        .collect()
+}
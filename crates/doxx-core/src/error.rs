@@ -0,0 +1,185 @@
+//! Errors from parsing and exporting documents.
+//!
+//! Programs using the library can match on [`DoxxError`] to tell a file that
+//! isn't a Word document from a damaged or password-protected one. The
+//! messages name the package part at fault but not the file, which the caller
+//! knows; the `doxx` command prefixes it. Functions that read several files
+//! say which one failed with [`DoxxError::InFile`].
+
+use std::io;
+use std::path::PathBuf;
+
+/// Why a document couldn't be read or exported
+#[derive(Debug, thiserror::Error)]
+pub enum DoxxError {
+    /// The data isn't a .docx package, e.g. a Word 97-2003 `.doc` or an RTF file
+    #[error("not a .docx document ({0})")]
+    UnsupportedFormat(String),
+    /// The zip container is damaged or truncated
+    #[error("zip container: {0}")]
+    CorruptArchive(String),
+    /// The document is password-protected, which keeps its content encrypted
+    #[error("the document is encrypted; save a copy without a password to open it")]
+    EncryptedDocument,
+    /// A part of the package couldn't be parsed or is over the configured limits
+    #[error("{part}: {detail}")]
+    ParseError { part: String, detail: String },
+    /// The document has no tables to export
+    #[error("No tables found in document")]
+    NoTables,
+    /// A table selector matched no table, or several
+    #[error("{0}")]
+    TableNotFound(String),
+    /// A section path matched no heading
+    #[error("no heading matches \"{0}\"")]
+    SectionNotFound(String),
+    /// A column selector matched no column of the table
+    #[error("{0}")]
+    ColumnNotFound(String),
+    /// The export format can't be produced by this library alone
+    #[error("{0} export is only available from the doxx command")]
+    UnsupportedExport(&'static str),
+    /// A file or folder to read doesn't exist, or holds no documents
+    #[error("{0}")]
+    NotFound(String),
+    /// An argument is out of range or malformed, e.g. a page range
+    #[error("{0}")]
+    InvalidInput(String),
+    /// A file pattern isn't valid glob syntax
+    #[error("invalid pattern: {0}")]
+    Pattern(#[from] glob::PatternError),
+    /// The config file, or a key or value set in it, is invalid
+    #[error("{0}")]
+    Config(String),
+    /// An embedded image couldn't be decoded or converted
+    #[error("image: {0}")]
+    Image(String),
+    /// The sandboxed parser process failed or was stopped
+    #[error("{0}")]
+    Sandbox(String),
+    /// The OCR program is missing or failed
+    #[error("{0}")]
+    Ocr(String),
+    /// The language model server couldn't be reached or answered an error
+    #[error("{0}")]
+    Model(String),
+    /// A background task stopped before it returned, e.g. by panicking
+    #[error("{0} aborted unexpectedly")]
+    Aborted(&'static str),
+    /// An error reading one of several files
+    #[error("{}: {source}", path.display())]
+    InFile {
+        path: PathBuf,
+        source: Box<DoxxError>,
+    },
+    #[error("JSON output: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+impl DoxxError {
+    /// `self`, saying it happened reading `path`
+    pub fn in_file(self, path: impl Into<PathBuf>) -> Self {
+        Self::InFile {
+            path: path.into(),
+            source: Box::new(self),
+        }
+    }
+
+    pub(crate) fn parse(part: impl Into<String>, detail: impl ToString) -> Self {
+        Self::ParseError {
+            part: part.into(),
+            detail: detail.to_string(),
+        }
+    }
+}
+
+impl From<glob::GlobError> for DoxxError {
+    fn from(error: glob::GlobError) -> Self {
+        Self::Io(error.into())
+    }
+}
+
+impl From<image::ImageError> for DoxxError {
+    fn from(error: image::ImageError) -> Self {
+        Self::Image(error.to_string())
+    }
+}
+
+impl From<toml::ser::Error> for DoxxError {
+    fn from(error: toml::ser::Error) -> Self {
+        Self::Config(error.to_string())
+    }
+}
+
+impl From<zip::result::ZipError> for DoxxError {
+    fn from(error: zip::result::ZipError) -> Self {
+        match error {
+            zip::result::ZipError::Io(error) => Self::Io(error),
+            error => Self::CorruptArchive(error.to_string()),
+        }
+    }
+}
+
+/// `Result` with [`DoxxError`]
+pub type Result<T, E = DoxxError> = std::result::Result<T, E>;
+
+/// Magic number of OLE compound files: Word 97-2003 documents, and .docx
+/// files encrypted with a password
+const OLE_MAGIC: &[u8] = &[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+
+/// Explain data that isn't a zip archive, or `None` if it is one
+pub(crate) fn identify_non_package(data: &[u8]) -> Option<DoxxError> {
+    if data.starts_with(b"PK") {
+        return None;
+    }
+    if data.starts_with(OLE_MAGIC) {
+        // Encrypted packages keep the encryption parameters in a stream
+        // named "EncryptionInfo" (the directory stores names as UTF-16)
+        let stream_name: Vec<u8> = "EncryptionInfo"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        if data
+            .windows(stream_name.len())
+            .any(|window| window == stream_name)
+        {
+            return Some(DoxxError::EncryptedDocument);
+        }
+        return Some(DoxxError::UnsupportedFormat(
+            "a Word 97-2003 .doc file; save it as .docx".to_string(),
+        ));
+    }
+    if data.starts_with(b"{\\rtf") {
+        return Some(DoxxError::UnsupportedFormat("an RTF file".to_string()));
+    }
+    Some(DoxxError::UnsupportedFormat(
+        "not a zip package".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identify_non_package() {
+        assert!(identify_non_package(b"PK\x03\x04").is_none());
+        assert!(matches!(
+            identify_non_package(b"{\\rtf1\\ansi"),
+            Some(DoxxError::UnsupportedFormat(_))
+        ));
+
+        let mut encrypted = OLE_MAGIC.to_vec();
+        encrypted.extend("EncryptionInfo".encode_utf16().flat_map(u16::to_le_bytes));
+        assert!(matches!(
+            identify_non_package(&encrypted),
+            Some(DoxxError::EncryptedDocument)
+        ));
+        assert!(matches!(
+            identify_non_package(OLE_MAGIC),
+            Some(DoxxError::UnsupportedFormat(message)) if message.contains(".doc")
+        ));
+    }
+}
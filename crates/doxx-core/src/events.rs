@@ -0,0 +1,197 @@
+//! Progress events.
+//!
+//! Loading and searching report what they are doing to an [`EventSink`] given
+//! in their options, so an application embedding doxx can show its own
+//! progress: a callback, or the sending half of a channel. Without a sink
+//! (the default) nothing is reported.
+
+use crate::document::DocumentElement;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Something a core operation started, finished or got through
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Event {
+    /// Reading a document began
+    LoadStarted { path: PathBuf, size: u64 },
+    /// A top-level paragraph or table of the body was parsed (counted from 1)
+    ElementParsed { parsed: usize, total: usize },
+    /// The first elements of the body, parsed ahead of the rest so they can
+    /// be shown while loading goes on (see
+    /// [`ParseOptions::preview_elements`](crate::document::ParseOptions::preview_elements))
+    Preview { elements: Vec<DocumentElement> },
+    /// An image was copied out of the package (`--images`)
+    ImageExtracted { name: String },
+    /// The document is loaded, with this many elements
+    LoadFinished { elements: usize },
+    /// A search ran, finding matches in this many places
+    SearchCompleted { query: String, results: usize },
+}
+
+/// Where events are sent
+#[derive(Clone, Default)]
+pub struct EventSink {
+    callback: Option<Arc<dyn Fn(Event) + Send + Sync>>,
+}
+
+impl EventSink {
+    /// Call `callback` with each event, on the thread doing the work
+    pub fn new(callback: impl Fn(Event) + Send + Sync + 'static) -> Self {
+        Self {
+            callback: Some(Arc::new(callback)),
+        }
+    }
+
+    /// Send each event over `sender`; events are dropped once the receiver is gone
+    pub fn channel(sender: UnboundedSender<Event>) -> Self {
+        Self::new(move |event| {
+            let _ = sender.send(event);
+        })
+    }
+
+    pub fn emit(&self, event: Event) {
+        if let Some(callback) = &self.callback {
+            callback(event);
+        }
+    }
+}
+
+impl fmt::Debug for EventSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventSink")
+            .field("connected", &self.callback.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::{
+        load_document, search_document_with, ImageOptions, ParseOptions, SearchOptions,
+    };
+    use tokio::sync::mpsc::unbounded_channel;
+
+    #[tokio::test]
+    async fn test_load_and_search_events() {
+        let (sender, mut receiver) = unbounded_channel();
+        let events = EventSink::channel(sender);
+        let options = ParseOptions {
+            events: events.clone(),
+            ..ParseOptions::default()
+        };
+        let document = load_document(
+            std::path::Path::new("../../tests/fixtures/minimal.docx"),
+            ImageOptions::default(),
+            options,
+        )
+        .await
+        .unwrap();
+        let search = SearchOptions {
+            events,
+            ..SearchOptions::default()
+        };
+        search_document_with(&document, "paragraph", &search);
+
+        let mut received = Vec::new();
+        while let Ok(event) = receiver.try_recv() {
+            received.push(event);
+        }
+        assert!(matches!(received[0], Event::LoadStarted { size, .. } if size > 0));
+        assert_eq!(
+            received[1..4],
+            [1, 2, 3].map(|parsed| Event::ElementParsed { parsed, total: 3 })
+        );
+        assert_eq!(
+            received[4],
+            Event::LoadFinished {
+                elements: document.elements.len()
+            }
+        );
+        assert_eq!(
+            received[5],
+            Event::SearchCompleted {
+                query: "paragraph".to_string(),
+                results: 2
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_preview_comes_before_the_rest() {
+        let (sender, mut receiver) = unbounded_channel();
+        let options = ParseOptions {
+            events: EventSink::channel(sender),
+            preview_elements: 2,
+            ..ParseOptions::default()
+        };
+        let document = load_document(
+            std::path::Path::new("../../tests/fixtures/minimal.docx"),
+            ImageOptions::default(),
+            options,
+        )
+        .await
+        .unwrap();
+
+        let mut received = Vec::new();
+        while let Ok(event) = receiver.try_recv() {
+            received.push(event);
+        }
+        let previews: Vec<usize> = received
+            .iter()
+            .enumerate()
+            .filter(|(_, event)| matches!(event, Event::Preview { .. }))
+            .map(|(index, _)| index)
+            .collect();
+        assert_eq!(previews.len(), 1);
+        let Event::Preview { elements } = &received[previews[0]] else {
+            unreachable!();
+        };
+        assert_eq!(elements[..], document.elements[..2]);
+        assert_eq!(
+            received[previews[0] - 1],
+            Event::ElementParsed {
+                parsed: 2,
+                total: 3
+            }
+        );
+
+        // Pictures are extracted after the preview, not before it
+        let (sender, mut receiver) = unbounded_channel();
+        let options = ParseOptions {
+            events: EventSink::channel(sender),
+            preview_elements: 1,
+            ..ParseOptions::default()
+        };
+        let images = ImageOptions {
+            enabled: true,
+            ..ImageOptions::default()
+        };
+        let document = load_document(
+            std::path::Path::new("../../tests/fixtures/images.docx"),
+            images,
+            options,
+        )
+        .await
+        .unwrap();
+        let mut received = Vec::new();
+        while let Ok(event) = receiver.try_recv() {
+            received.push(event);
+        }
+        let position = |wanted: fn(&Event) -> bool| received.iter().position(wanted).unwrap();
+        assert!(
+            position(|event| matches!(event, Event::Preview { .. }))
+                < position(|event| matches!(event, Event::ImageExtracted { .. }))
+        );
+        assert!(document.elements.iter().any(|element| matches!(
+            element,
+            DocumentElement::Image {
+                image_path: Some(_),
+                ..
+            }
+        )));
+    }
+}
@@ -0,0 +1,1314 @@
+use serde::Serialize;
+use std::borrow::Cow;
+use std::fmt::Write;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::error::{DoxxError, Result};
+use crate::text_width::{align_to_width, display_width};
+use crate::{document::*, ExportFormat};
+
+/// Export `document` in `format` to a string, as `doxx convert` writes it.
+/// Text is not wrapped and images are described rather than drawn.
+pub fn format_document(document: &Document, format: &ExportFormat) -> Result<String> {
+    Ok(match format {
+        ExportFormat::Markdown => to_markdown(document),
+        ExportFormat::Text => to_text(document, None),
+        ExportFormat::Csv => to_csv(document),
+        ExportFormat::Json => to_json(document)?,
+        ExportFormat::Ansi => return Err(DoxxError::UnsupportedExport("ANSI")),
+    })
+}
+
+/// Export the elements in `range` alone, e.g. a section or a selection
+/// saved from the viewer
+pub fn format_range(
+    document: &Document,
+    range: Range<usize>,
+    format: &ExportFormat,
+) -> Result<String> {
+    let mut part = document.clone();
+    crate::pages::select_elements(&mut part, range);
+    format_document(&part, format)
+}
+
+/// The `--export markdown` output
+pub fn to_markdown(document: &Document) -> String {
+    let mut markdown = String::new();
+
+    // Add document title
+    let _ = writeln!(markdown, "# {}\n", document.title);
+
+    // Add metadata
+    markdown.push_str("## Document Information\n\n");
+    let _ = writeln!(markdown, "- **File**: {}", document.metadata.file_path);
+    let _ = writeln!(markdown, "- **Pages**: {}", document.metadata.page_count);
+    let _ = writeln!(markdown, "- **Words**: {}", document.metadata.word_count);
+    if let Some(author) = &document.metadata.author {
+        let _ = writeln!(markdown, "- **Author**: {author}");
+    }
+    if let Some(date) = document_date(document) {
+        let _ = writeln!(markdown, "- **Date**: {date}");
+    }
+    markdown.push_str("\n---\n\n");
+
+    markdown.push_str(&format_range_as_markdown(
+        document,
+        0..document.elements.len(),
+    ));
+    markdown
+}
+
+/// Date shown for a document: when it was last modified, or else created
+fn document_date(document: &Document) -> Option<&str> {
+    let metadata = &document.metadata;
+    metadata.modified.as_deref().or(metadata.created.as_deref())
+}
+
+/// Markdown for the elements in `range` alone, without the title and
+/// document information (e.g. a selection copied in the viewer)
+pub fn format_range_as_markdown(document: &Document, range: Range<usize>) -> String {
+    let mut markdown = String::new();
+    let start = range.start;
+    for (index, element) in document.elements[range].iter().enumerate() {
+        let index = start + index;
+        // Bookmarks become HTML anchors so they can be linked to (e.g. `#bookmark-a`)
+        for (name, _) in document.bookmarks.iter().filter(|(_, i)| **i == index) {
+            let _ = writeln!(markdown, "<a id=\"bookmark-{name}\"></a>\n");
+        }
+
+        match element {
+            DocumentElement::Heading {
+                level,
+                text,
+                number,
+                ..
+            } => {
+                let prefix = "#".repeat(*level as usize + 1); // +1 because title is h1
+                let heading_text = if let Some(number) = number {
+                    format!("{number} {text}")
+                } else {
+                    text.clone()
+                };
+                let _ = writeln!(markdown, "{prefix} {heading_text}\n");
+            }
+            DocumentElement::Paragraph {
+                text,
+                formatting,
+                runs,
+                ..
+            } => {
+                // Equations in the text are written as LaTeX
+                let mut formatted_text = if runs.iter().any(|run| run.math.is_some()) {
+                    runs.iter()
+                        .map(|run| match &run.math {
+                            Some(latex) => format!("${latex}$"),
+                            None => run.text.clone(),
+                        })
+                        .collect()
+                } else {
+                    text.clone()
+                };
+
+                if formatting.bold {
+                    formatted_text = format!("**{formatted_text}**");
+                }
+                if formatting.italic {
+                    formatted_text = format!("*{formatted_text}*");
+                }
+
+                let _ = writeln!(markdown, "{formatted_text}\n");
+            }
+            DocumentElement::List {
+                items,
+                ordered,
+                start,
+            } => {
+                let labels = list_item_labels(items, *ordered, *start);
+                for (item, label) in items.iter().zip(labels) {
+                    let indent = "  ".repeat(item.level as usize);
+                    let bullet = match label {
+                        Some(label) => format!("{label} "),
+                        None => "- ".to_string(),
+                    };
+
+                    let mut item_text = item.text.clone();
+                    if false
+                    /* simplified */
+                    {
+                        item_text = format!("**{item_text}**");
+                    }
+                    if false
+                    /* simplified */
+                    {
+                        item_text = format!("*{item_text}*");
+                    }
+
+                    let _ = writeln!(markdown, "{indent}{bullet}{item_text}");
+                }
+                markdown.push('\n');
+            }
+            DocumentElement::Table { table } => {
+                // Add table title if present
+                if let Some(title) = &table.metadata.title {
+                    let _ = writeln!(markdown, "### {title}\n");
+                }
+
+                // Markdown table header
+                let header_content: Vec<String> =
+                    table.headers.iter().map(|h| h.content.clone()).collect();
+                let _ = writeln!(markdown, "| {} |", header_content.join(" | "));
+
+                // Generate alignment indicators
+                let alignment_row: Vec<String> = table
+                    .metadata
+                    .column_alignments
+                    .iter()
+                    .map(|align| match align {
+                        TextAlignment::Left => ":---".to_string(),
+                        TextAlignment::Right => "---:".to_string(),
+                        TextAlignment::Center => ":---:".to_string(),
+                        TextAlignment::Justify => ":---".to_string(),
+                    })
+                    .collect();
+                let _ = writeln!(markdown, "| {} |", alignment_row.join(" | "));
+
+                // Table rows
+                for row in &table.rows {
+                    let row_content: Vec<String> =
+                        row.iter().map(|cell| cell.content.clone()).collect();
+                    let _ = writeln!(markdown, "| {} |", row_content.join(" | "));
+                }
+                markdown.push('\n');
+            }
+            DocumentElement::Image {
+                description,
+                width,
+                height,
+                image_path,
+                ..
+            } => {
+                let alt = description.replace('[', "\\[").replace(']', "\\]");
+                let url = image_path
+                    .as_ref()
+                    .map(|p| p.to_string_lossy().to_string())
+                    .unwrap_or_else(|| description.clone());
+                let dimensions = match (width, height) {
+                    (Some(w), Some(h)) => format!(" <!-- {w}x{h} -->"),
+                    _ => String::new(),
+                };
+                let _ = writeln!(markdown, "![{alt}]({url}){dimensions}\n");
+            }
+            DocumentElement::PageBreak => {
+                markdown.push_str("\n---\n\n");
+            }
+            DocumentElement::Quote { text, intense, .. } => {
+                for line in text.lines() {
+                    if *intense && !line.trim().is_empty() {
+                        let _ = writeln!(markdown, "> **{}**", line.trim());
+                    } else {
+                        let _ = writeln!(markdown, "> {line}");
+                    }
+                }
+                markdown.push('\n');
+            }
+            DocumentElement::CodeBlock {
+                lines, language, ..
+            } => {
+                // A longer fence than any backtick run inside keeps the block closed
+                let longest = lines
+                    .iter()
+                    .flat_map(|line| line.split(|c| c != '`'))
+                    .map(str::len)
+                    .max()
+                    .unwrap_or(0);
+                let fence = "`".repeat(longest.max(2) + 1);
+                let language = language.as_deref().unwrap_or_default();
+                let _ = writeln!(
+                    markdown,
+                    "{fence}{language}\n{}\n{fence}\n",
+                    lines.join("\n")
+                );
+            }
+            DocumentElement::Caption { text, .. } | DocumentElement::Subtitle { text, .. } => {
+                let _ = writeln!(markdown, "*{}*\n", text.trim());
+            }
+            DocumentElement::Title { text, .. } => {
+                // The document title is already the top heading
+                if text.trim() != document.title.trim() {
+                    let _ = writeln!(markdown, "# {}\n", text.trim());
+                }
+            }
+            DocumentElement::Equation { latex, .. } => {
+                let _ = writeln!(markdown, "$$\n{latex}\n$$\n");
+            }
+        }
+    }
+
+    markdown
+}
+
+pub fn format_as_text(document: &Document) -> String {
+    let mut text = String::new();
+
+    // Add document title
+    let _ = writeln!(text, "{}", document.title);
+    text.push_str(&"=".repeat(display_width(&document.title)));
+    text.push_str("\n\n");
+
+    text.push_str(&format_range_as_text(document, 0..document.elements.len()));
+    text
+}
+
+/// Plain text for the elements in `range` alone, without the title
+pub fn format_range_as_text(document: &Document, range: Range<usize>) -> String {
+    let mut text = String::new();
+    for element in &document.elements[range] {
+        match element {
+            DocumentElement::Heading {
+                level,
+                text: heading_text,
+                ..
+            } => {
+                let underline = match level {
+                    1 => "=",
+                    2 => "-",
+                    _ => "~",
+                };
+                let _ = writeln!(text, "{heading_text}");
+                text.push_str(&underline.repeat(display_width(heading_text)));
+                text.push_str("\n\n");
+            }
+            DocumentElement::Paragraph {
+                text: para_text, ..
+            } => {
+                let _ = writeln!(text, "{para_text}\n");
+            }
+            DocumentElement::List {
+                items,
+                ordered,
+                start,
+            } => {
+                let labels = list_item_labels(items, *ordered, *start);
+                for (item, label) in items.iter().zip(labels) {
+                    let bullet = match label {
+                        Some(label) => format!("{label} "),
+                        None => "* ".to_string(),
+                    };
+
+                    let indent = "  ".repeat(item.level as usize);
+                    let _ = writeln!(text, "{indent}{bullet}{}", item.text);
+                }
+                text.push('\n');
+            }
+            DocumentElement::Table { table } => {
+                // Add table title if present
+                if let Some(title) = &table.metadata.title {
+                    let _ = writeln!(text, "{title}");
+                    text.push_str(&"=".repeat(display_width(title)));
+                    text.push_str("\n\n");
+                }
+
+                // Use the calculated column widths from metadata
+                let col_widths = &table.metadata.column_widths;
+
+                // Top border
+                let top_border = generate_text_table_border(col_widths, "┌", "┬", "┐", "─");
+                let _ = writeln!(text, "{top_border}");
+
+                // Header with proper alignment
+                let header_line = render_text_table_row(&table.headers, col_widths, true);
+                let _ = writeln!(text, "{header_line}");
+
+                // Header separator
+                let separator = generate_text_table_border(col_widths, "├", "┼", "┤", "─");
+                let _ = writeln!(text, "{separator}");
+
+                // Data rows
+                for row in &table.rows {
+                    let row_line = render_text_table_row(row, col_widths, false);
+                    let _ = writeln!(text, "{row_line}");
+                }
+
+                // Bottom border
+                let bottom_border = generate_text_table_border(col_widths, "└", "┴", "┘", "─");
+                let _ = writeln!(text, "{bottom_border}");
+
+                text.push('\n');
+            }
+            DocumentElement::PageBreak => {
+                text.push_str("---\n\n");
+            }
+            DocumentElement::Quote {
+                text: quote_text, ..
+            } => {
+                for line in quote_text.lines() {
+                    let _ = writeln!(text, "  │ {line}");
+                }
+                text.push('\n');
+            }
+            DocumentElement::CodeBlock { lines, .. } => {
+                for line in lines {
+                    text.push_str(format!("    {line}").trim_end());
+                    text.push('\n');
+                }
+                text.push('\n');
+            }
+            DocumentElement::Caption {
+                text: caption_text, ..
+            }
+            | DocumentElement::Subtitle {
+                text: caption_text, ..
+            } => {
+                let _ = writeln!(text, "{caption_text}\n");
+            }
+            DocumentElement::Title {
+                text: title_text, ..
+            } => {
+                if title_text.trim() != document.title.trim() {
+                    let _ = writeln!(text, "{title_text}");
+                    text.push_str(&"=".repeat(display_width(title_text)));
+                    text.push_str("\n\n");
+                }
+            }
+            DocumentElement::Equation {
+                text: equation_text,
+                ..
+            } => {
+                let _ = writeln!(text, "    {equation_text}\n");
+            }
+            DocumentElement::Image { description, .. } => {
+                let _ = writeln!(text, "[Image: {description}]\n");
+            }
+        }
+    }
+
+    text
+}
+
+/// The `--export text` output, hard-wrapping headings, paragraphs and list
+/// items at `wrap` columns. Tables are left as they are and images are
+/// described.
+pub fn to_text(document: &Document, wrap: Option<usize>) -> String {
+    to_text_with(
+        document,
+        &TextOptions {
+            wrap,
+            ..Default::default()
+        },
+    )
+}
+
+/// Layout of the `--export text` output
+#[derive(Debug, Clone, Default)]
+pub struct TextOptions {
+    /// Hard-wrap headings, paragraphs and list items at this many columns
+    pub wrap: Option<usize>,
+    /// Break tables longer than this many rows into pages, separated by a
+    /// page-break line and each starting with the header row
+    pub table_page_rows: Option<usize>,
+}
+
+/// Line endings for `--eol`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum LineEnding {
+    /// `\n`
+    Lf,
+    /// `\r\n`, as Windows tools and some repositories expect
+    Crlf,
+}
+
+/// Whitespace conventions for an export, applied to the finished output so
+/// it can be committed as is
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OutputOptions {
+    /// End every line this way; `None` keeps the line endings as exported
+    pub eol: Option<LineEnding>,
+    /// Remove spaces and tabs at the ends of lines
+    pub trim_trailing_whitespace: bool,
+}
+
+impl OutputOptions {
+    /// Whether [`OutputOptions::apply`] leaves exports as they are
+    pub fn is_default(&self) -> bool {
+        self.eol.is_none() && !self.trim_trailing_whitespace
+    }
+
+    /// `content` with its line endings and trailing whitespace normalized
+    pub fn apply<'a>(&self, content: &'a str) -> Cow<'a, str> {
+        if self.is_default() {
+            return Cow::Borrowed(content);
+        }
+        let mut output = String::with_capacity(content.len() + content.len() / 32);
+        let mut lines = content.split('\n').peekable();
+        while let Some(line) = lines.next() {
+            let last = lines.peek().is_none();
+            let had_cr = !last && line.ends_with('\r');
+            let mut line = if had_cr {
+                &line[..line.len() - 1]
+            } else {
+                line
+            };
+            if self.trim_trailing_whitespace {
+                line = line.trim_end_matches([' ', '\t']);
+            }
+            output.push_str(line);
+            if last {
+                break;
+            }
+            output.push_str(match self.eol {
+                Some(LineEnding::Crlf) => "\r\n",
+                Some(LineEnding::Lf) => "\n",
+                None if had_cr => "\r\n",
+                None => "\n",
+            });
+        }
+        Cow::Owned(output)
+    }
+}
+
+/// [`to_text`] with all of the layout options
+pub fn to_text_with(document: &Document, options: &TextOptions) -> String {
+    render_text_export(document, options, None)
+}
+
+/// [`to_text_with`], handing each extracted image to `draw_image` along
+/// with the text before it that hasn't been handed over yet, and the
+/// image's path and description. Images it reports drawn (`true`) are left
+/// out of the text; the rest are described. Returns the text after the
+/// last image.
+pub fn to_text_drawing_images(
+    document: &Document,
+    options: &TextOptions,
+    mut draw_image: impl FnMut(&str, &Path, &str) -> bool,
+) -> String {
+    render_text_export(document, options, Some(&mut draw_image))
+}
+
+/// `[Image: description]`, followed by any text `--ocr` read from the
+/// image, indented
+fn push_image_placeholder(out: &mut String, description: &str, ocr_text: Option<&str>) {
+    let _ = writeln!(out, "[Image: {description}]");
+    for line in ocr_text.into_iter().flat_map(str::lines) {
+        let _ = writeln!(out, "  {line}");
+    }
+    out.push('\n');
+}
+
+/// Append `text` after `first_indent`, wrapped with continuation lines
+/// starting at `rest_indent` when a wrap width is set
+fn push_wrapped(
+    out: &mut String,
+    text: &str,
+    wrap: Option<usize>,
+    first_indent: &str,
+    rest_indent: &str,
+) {
+    match wrap {
+        Some(width) => {
+            for line in crate::text_width::wrap(text, width, first_indent, rest_indent) {
+                out.push_str(&line);
+                out.push('\n');
+            }
+        }
+        None => {
+            let _ = writeln!(out, "{first_indent}{text}");
+        }
+    }
+}
+
+/// Width of a leading list marker such as `2.1.`, `iv.`, `(a)` or `•` plus
+/// the space after it, so wrapped lines can hang under the item text
+fn list_marker_width(text: &str) -> usize {
+    let Some((marker, _)) = text.split_once(' ') else {
+        return 0;
+    };
+    let is_marker = matches!(marker, "•" | "-" | "*" | "◦" | "▪")
+        || (marker.chars().count() <= 6
+            && (marker.ends_with('.') || marker.ends_with(')'))
+            && marker
+                .chars()
+                .all(|c| c.is_alphanumeric() || matches!(c, '.' | '(' | ')')));
+    if is_marker {
+        display_width(marker) + 1
+    } else {
+        0
+    }
+}
+
+/// Draws an image for [`to_text_drawing_images`]
+type DrawImage<'a> = &'a mut dyn FnMut(&str, &Path, &str) -> bool;
+
+/// The `--export text` output, with extracted images handed to `draw_image`
+/// when there is one
+fn render_text_export(
+    document: &Document,
+    options: &TextOptions,
+    mut draw_image: Option<DrawImage>,
+) -> String {
+    let wrap = options.wrap;
+    let mut out = String::new();
+
+    // Title
+    let _ = writeln!(out, "{}\n", document.title);
+
+    // Metadata
+    out.push_str("Document Information:\n");
+    let _ = writeln!(out, "- File: {}", document.metadata.file_path);
+    let _ = writeln!(out, "- Pages: {}", document.metadata.page_count);
+    let _ = writeln!(out, "- Words: {}", document.metadata.word_count);
+    if let Some(author) = &document.metadata.author {
+        let _ = writeln!(out, "- Author: {author}");
+    }
+    if let Some(date) = document_date(document) {
+        let _ = writeln!(out, "- Date: {date}");
+    }
+    let _ = writeln!(out, "\n{}\n", "=".repeat(50));
+
+    // Process elements in order
+    for element in &document.elements {
+        match element {
+            DocumentElement::Heading {
+                level,
+                text,
+                number,
+                ..
+            } => {
+                let prefix = format!("{} ", "#".repeat(*level as usize));
+                let heading_text = if let Some(number) = number {
+                    format!("{number} {text}")
+                } else {
+                    text.clone()
+                };
+                push_wrapped(
+                    &mut out,
+                    &heading_text,
+                    wrap,
+                    &prefix,
+                    &" ".repeat(prefix.len()),
+                );
+                out.push('\n');
+            }
+            DocumentElement::Paragraph {
+                text, formatting, ..
+            } => {
+                // Indented list-style paragraphs keep their indentation when wrapped
+                let body = text.trim_start();
+                let indent = &text[..text.len() - body.len()];
+                let mut formatted_text = body.to_string();
+
+                if formatting.bold {
+                    formatted_text = format!("**{formatted_text}**");
+                }
+                if formatting.italic {
+                    formatted_text = format!("*{formatted_text}*");
+                }
+                if formatting.underline {
+                    formatted_text = format!("_{formatted_text}_");
+                }
+
+                let rest_indent = " ".repeat(indent.len() + list_marker_width(body));
+                push_wrapped(&mut out, &formatted_text, wrap, indent, &rest_indent);
+                out.push('\n');
+            }
+            DocumentElement::List {
+                items,
+                ordered,
+                start,
+            } => {
+                let labels = list_item_labels(items, *ordered, *start);
+                for (item, label) in items.iter().zip(labels) {
+                    let marker = match label {
+                        Some(label) => format!("{label} "),
+                        None => "- ".to_string(),
+                    };
+                    let first_indent = format!("{}{marker}", "  ".repeat(item.level as usize));
+                    // Continuation lines line up with the item text, past any
+                    // nesting indentation it carries
+                    let body = item.text.trim_start();
+                    let nesting = item.text.len() - body.len();
+                    let rest_indent = " "
+                        .repeat(display_width(&first_indent) + nesting + list_marker_width(body));
+                    push_wrapped(&mut out, &item.text, wrap, &first_indent, &rest_indent);
+                }
+                out.push('\n');
+            }
+            DocumentElement::Table { table } => {
+                // Simple table rendering for text export
+                let row_line = |row: &[TableCell]| {
+                    let row_content: Vec<&str> =
+                        row.iter().map(|cell| cell.content.as_str()).collect();
+                    format!("| {} |\n", row_content.join(" | "))
+                };
+                match options
+                    .table_page_rows
+                    .filter(|&rows| rows > 0 && table.rows.len() > rows)
+                {
+                    Some(page_rows) => {
+                        for (page, rows) in table.rows.chunks(page_rows).enumerate() {
+                            if page > 0 {
+                                let _ = writeln!(out, "{}", "-".repeat(50));
+                            }
+                            if !table.headers.is_empty() {
+                                out.push_str(&row_line(&table.headers));
+                            }
+                            for row in rows {
+                                out.push_str(&row_line(row));
+                            }
+                        }
+                    }
+                    None => {
+                        for row in &table.rows {
+                            out.push_str(&row_line(row));
+                        }
+                    }
+                }
+                out.push('\n');
+            }
+            DocumentElement::Image {
+                description,
+                image_path,
+                ocr_text,
+                ..
+            } => {
+                // Drawn in the correct position, after the text before it
+                let drawn = match (image_path, draw_image.as_mut()) {
+                    (Some(path), Some(draw_image)) => {
+                        let drawn = draw_image(&out, path, description);
+                        out.clear();
+                        drawn
+                    }
+                    _ => false,
+                };
+                if drawn {
+                    out.push('\n');
+                } else {
+                    push_image_placeholder(&mut out, description, ocr_text.as_deref());
+                }
+            }
+            DocumentElement::PageBreak => {
+                let _ = writeln!(out, "{}\n", "-".repeat(50));
+            }
+            DocumentElement::Quote { text, .. } => {
+                for line in text.lines() {
+                    push_wrapped(&mut out, line, wrap, "  │ ", "  │ ");
+                }
+                out.push('\n');
+            }
+            DocumentElement::CodeBlock { lines, .. } => {
+                // Code keeps its line breaks and is never wrapped
+                for line in lines {
+                    out.push_str(format!("    {line}").trim_end());
+                    out.push('\n');
+                }
+                out.push('\n');
+            }
+            DocumentElement::Caption { text, .. } | DocumentElement::Subtitle { text, .. } => {
+                push_wrapped(&mut out, text, wrap, "", "");
+                out.push('\n');
+            }
+            DocumentElement::Title { text, .. } => {
+                if text.trim() != document.title.trim() {
+                    push_wrapped(&mut out, text, wrap, "", "");
+                    out.push('\n');
+                }
+            }
+            DocumentElement::Equation { text, .. } => {
+                let _ = writeln!(out, "    {text}\n");
+            }
+        }
+    }
+    out
+}
+
+/// The `--export csv` output: every table, each after a comment with its
+/// number and title. Empty when the document has no tables.
+pub fn to_csv(document: &Document) -> String {
+    let tables = document_tables(document);
+    let selected: Vec<_> = tables
+        .iter()
+        .map(|table| (table, Cow::Borrowed(table.table)))
+        .collect();
+    tables_to_csv(&selected, &CsvOptions::default())
+}
+
+/// Extras for CSV exports
+#[derive(Debug, Clone, Default)]
+pub struct CsvOptions {
+    /// Add a row under the header naming each column's type (`number`, `date`, ...)
+    pub types: bool,
+    /// Columns to keep, in this order, by header or 1-based number; all of
+    /// them when empty
+    pub columns: Vec<String>,
+}
+
+/// CSV of the table `selector` picks (see [`find_table`]), or of all tables
+pub fn csv_tables(
+    document: &Document,
+    selector: Option<&str>,
+    options: &CsvOptions,
+) -> Result<String> {
+    let tables = document_tables(document);
+    let selected = csv_selection(&tables, selector, &options.columns)?;
+    match selector {
+        Some(_) => Ok(table_to_csv_with(&selected[0].1, options)),
+        None => Ok(tables_to_csv(&selected, options)),
+    }
+}
+
+/// The tables `selector` picks, or all of them, each cut down to `columns`.
+/// A picked table must have every column; without a selector, tables
+/// missing one are left out, as long as some table has them all.
+fn csv_selection<'t>(
+    tables: &'t [DocumentTable<'t>],
+    selector: Option<&str>,
+    columns: &[String],
+) -> Result<Vec<(&'t DocumentTable<'t>, Cow<'t, TableData>)>> {
+    let selected: Vec<&DocumentTable> = match selector {
+        Some(selector) => vec![find_table(tables, selector)?],
+        None => tables.iter().collect(),
+    };
+    if columns.is_empty() {
+        return Ok(selected
+            .into_iter()
+            .map(|table| (table, Cow::Borrowed(table.table)))
+            .collect());
+    }
+
+    let mut projected = Vec::new();
+    for table in &selected {
+        match select_columns(table.table, columns) {
+            Ok(data) => projected.push((*table, Cow::Owned(data))),
+            Err(column) if selector.is_some() => {
+                let headers: Vec<&str> = table
+                    .table
+                    .headers
+                    .iter()
+                    .map(|header| header.content.trim())
+                    .collect();
+                return Err(DoxxError::ColumnNotFound(format!(
+                    "Table {} has no column '{column}' (its columns: {})",
+                    table.number,
+                    headers.join(", ")
+                )));
+            }
+            Err(_) => {}
+        }
+    }
+    if projected.is_empty() && !selected.is_empty() {
+        return Err(DoxxError::ColumnNotFound(format!(
+            "No table has all of the columns {}",
+            columns.join(", ")
+        )));
+    }
+    Ok(projected)
+}
+
+/// `table` with only `columns`, in that order, or the first column it
+/// doesn't have
+fn select_columns<'c>(table: &TableData, columns: &'c [String]) -> Result<TableData, &'c str> {
+    let indices = columns
+        .iter()
+        .map(|column| table.column_index(column).ok_or(column.as_str()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let pick = |row: &[TableCell]| -> Vec<TableCell> {
+        indices
+            .iter()
+            .map(|&index| {
+                row.get(index)
+                    .cloned()
+                    .unwrap_or_else(|| TableCell::new(String::new()))
+            })
+            .collect()
+    };
+    let mut selected = TableData::new(
+        pick(&table.headers),
+        table.rows.iter().map(|row| pick(row)).collect(),
+    );
+    selected.metadata.title = table.metadata.title.clone();
+    Ok(selected)
+}
+
+/// Whether `--output` names a directory, existing or marked with a
+/// trailing slash
+pub fn is_directory_output(output: &Path) -> bool {
+    output.is_dir() || output.to_string_lossy().ends_with(['/', '\\'])
+}
+
+/// Write the tables `selector` picks, or all of them, into the directory
+/// `output`, one file per table, returning the files written
+pub fn write_csv_tables(
+    document: &Document,
+    selector: Option<&str>,
+    output: &Path,
+    options: &CsvOptions,
+) -> Result<Vec<std::path::PathBuf>> {
+    let tables = document_tables(document);
+    let selected = csv_selection(&tables, selector, &options.columns)?;
+    if selected.is_empty() {
+        return Err(DoxxError::NoTables);
+    }
+
+    fs::create_dir_all(output)?;
+    selected
+        .into_iter()
+        .map(|(table, data)| {
+            let path = output.join(table_file_name(table));
+            fs::write(&path, table_to_csv_with(&data, options))?;
+            Ok(path)
+        })
+        .collect()
+}
+
+/// A table in the document, numbered from 1 in reading order
+pub struct DocumentTable<'a> {
+    pub number: usize,
+    /// The table's own title, or the nearest heading above it
+    pub title: Option<String>,
+    pub table: &'a TableData,
+}
+
+/// All tables in reading order with their inferred titles
+pub fn document_tables(document: &Document) -> Vec<DocumentTable<'_>> {
+    let mut tables = Vec::new();
+    let mut heading = None;
+    for element in &document.elements {
+        match element {
+            DocumentElement::Heading { text, .. } => heading = Some(text),
+            DocumentElement::Table { table } => tables.push(DocumentTable {
+                number: tables.len() + 1,
+                title: table.metadata.title.clone().or_else(|| heading.cloned()),
+                table,
+            }),
+            _ => {}
+        }
+    }
+    tables
+}
+
+/// Pick a table by its number, or by title (exact match first, then a unique
+/// case-insensitive substring)
+pub fn find_table<'t, 'a>(
+    tables: &'t [DocumentTable<'a>],
+    selector: &str,
+) -> Result<&'t DocumentTable<'a>> {
+    if let Ok(number) = selector.trim().parse::<usize>() {
+        return tables.iter().find(|t| t.number == number).ok_or_else(|| {
+            DoxxError::TableNotFound(format!(
+                "No table {number}: the document has {} tables",
+                tables.len()
+            ))
+        });
+    }
+
+    let needle = selector.trim().to_lowercase();
+    let title_of = |t: &DocumentTable| t.title.as_deref().unwrap_or_default().to_lowercase();
+    if let Some(table) = tables.iter().find(|t| title_of(t) == needle) {
+        return Ok(table);
+    }
+    let mut matches = tables.iter().filter(|t| title_of(t).contains(&needle));
+    match (matches.next(), matches.next()) {
+        (Some(table), None) => Ok(table),
+        (None, _) => Err(DoxxError::TableNotFound(format!(
+            "No table titled '{selector}' (see --list-tables)"
+        ))),
+        (Some(_), Some(_)) => Err(DoxxError::TableNotFound(format!(
+            "'{selector}' matches several table titles; use the table number"
+        ))),
+    }
+}
+
+/// One line per table: number, size and title
+pub fn format_table_list(tables: &[DocumentTable]) -> String {
+    let mut list = String::new();
+    for table in tables {
+        let _ = writeln!(
+            list,
+            "{}. {} ({} rows × {} columns)",
+            table.number,
+            table.title.as_deref().unwrap_or("Untitled"),
+            table.table.rows.len(),
+            table.table.metadata.column_count,
+        );
+    }
+    list
+}
+
+fn tables_to_csv(tables: &[(&DocumentTable, Cow<TableData>)], options: &CsvOptions) -> String {
+    let mut csv = String::new();
+    for (i, (table, data)) in tables.iter().enumerate() {
+        if i > 0 {
+            csv.push('\n');
+        }
+        if table.number > 1 {
+            let _ = writeln!(csv, "# Table {}", table.number);
+        }
+        if let Some(title) = &table.title {
+            let _ = writeln!(csv, "# {title}");
+        }
+        csv.push_str(&table_to_csv_with(data, options));
+    }
+    csv
+}
+
+/// Header and rows of a single table as CSV
+pub fn table_to_csv(table: &TableData) -> String {
+    table_to_csv_with(table, &CsvOptions::default())
+}
+
+/// [`table_to_csv`] with extras
+pub fn table_to_csv_with(table: &TableData, options: &CsvOptions) -> String {
+    let line = |fields: Vec<String>| fields.join(",") + "\n";
+    let cells = |row: &[TableCell]| {
+        line(
+            row.iter()
+                .map(|cell| escape_csv_field(&cell.content))
+                .collect(),
+        )
+    };
+
+    let mut csv = cells(&table.headers);
+    if options.types {
+        csv.push_str(&line(
+            table
+                .column_types()
+                .into_iter()
+                .map(|data_type| data_type.name().to_string())
+                .collect(),
+        ));
+    }
+    for row in &table.rows {
+        csv.push_str(&cells(row));
+    }
+    csv
+}
+
+/// Header and rows of a single table as tab-separated values, for pasting
+/// into spreadsheets. Tabs and line breaks inside cells become spaces.
+pub fn table_to_tsv(table: &TableData) -> String {
+    let mut tsv = String::new();
+    for row in std::iter::once(&table.headers).chain(&table.rows) {
+        let line = row
+            .iter()
+            .map(|cell| cell.content.trim().replace(['\t', '\r', '\n'], " "))
+            .collect::<Vec<_>>()
+            .join("\t");
+        tsv.push_str(&line);
+        tsv.push('\n');
+    }
+    tsv
+}
+
+/// A single table as an HTML `<table>` for rich-text clipboard targets
+pub fn table_to_html(table: &TableData) -> String {
+    let mut html = String::from("<table>\n");
+    let header = table
+        .headers
+        .iter()
+        .map(|cell| format!("<th>{}</th>", escape_html(cell.content.trim())))
+        .collect::<String>();
+    let _ = writeln!(html, "<thead><tr>{header}</tr></thead>\n<tbody>");
+    for row in &table.rows {
+        let cells = row
+            .iter()
+            .map(|cell| format!("<td>{}</td>", escape_html(cell.content.trim())))
+            .collect::<String>();
+        let _ = writeln!(html, "<tr>{cells}</tr>");
+    }
+    html.push_str("</tbody>\n</table>\n");
+    html
+}
+
+/// Output format for `--outline --format`
+#[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum OutlineFormat {
+    /// Indented plain text
+    #[default]
+    Text,
+    /// Nested bullet list
+    Markdown,
+    /// Heading tree with levels, element indices and pages
+    Json,
+    /// OPML 2.0, for outliners and mind-map tools
+    Opml,
+}
+
+/// A heading with the headings nested under it
+#[derive(Debug, Serialize)]
+pub struct OutlineNode {
+    pub title: String,
+    pub level: u8,
+    pub element_index: usize,
+    pub page: usize,
+    pub children: Vec<OutlineNode>,
+}
+
+/// Nest a flat outline: each heading takes the deeper headings after it as
+/// children, even when levels are skipped (H1 followed by H3)
+pub fn outline_tree(document: &Document, outline: &[OutlineItem]) -> Vec<OutlineNode> {
+    fn nest(
+        document: &Document,
+        outline: &[OutlineItem],
+        next: &mut usize,
+        parent_level: u8,
+    ) -> Vec<OutlineNode> {
+        let mut nodes = Vec::new();
+        while let Some(item) = outline.get(*next).filter(|item| item.level > parent_level) {
+            *next += 1;
+            nodes.push(OutlineNode {
+                title: item.title.clone(),
+                level: item.level,
+                element_index: item.element_index,
+                page: document.pages.page_of(item.element_index),
+                children: nest(document, outline, next, item.level),
+            });
+        }
+        nodes
+    }
+
+    nest(document, outline, &mut 0, 0)
+}
+
+/// The heading tree of `document` in `format`
+pub fn format_outline(
+    document: &Document,
+    outline: &[OutlineItem],
+    format: OutlineFormat,
+) -> Result<String> {
+    fn walk(
+        nodes: &[OutlineNode],
+        depth: usize,
+        line: &impl Fn(&OutlineNode, usize) -> String,
+    ) -> String {
+        nodes
+            .iter()
+            .map(|node| line(node, depth) + &walk(&node.children, depth + 1, line))
+            .collect()
+    }
+
+    let tree = outline_tree(document, outline);
+    Ok(match format {
+        OutlineFormat::Text => walk(&tree, 0, &|node, depth| {
+            format!("{}{}\n", "  ".repeat(depth), node.title)
+        }),
+        OutlineFormat::Markdown => walk(&tree, 0, &|node, depth| {
+            format!("{}- {}\n", "  ".repeat(depth), node.title)
+        }),
+        OutlineFormat::Json => {
+            #[derive(Serialize)]
+            struct JsonOutline<'a> {
+                title: &'a str,
+                headings: &'a [OutlineNode],
+            }
+            let json = JsonOutline {
+                title: &document.title,
+                headings: &tree,
+            };
+            format!("{}\n", serde_json::to_string_pretty(&json)?)
+        }
+        OutlineFormat::Opml => format_opml(&document.title, &tree),
+    })
+}
+
+fn format_opml(title: &str, tree: &[OutlineNode]) -> String {
+    fn outlines(nodes: &[OutlineNode], depth: usize, opml: &mut String) {
+        for node in nodes {
+            let indent = "  ".repeat(depth + 2);
+            let text = escape_html(&node.title);
+            if node.children.is_empty() {
+                let _ = writeln!(opml, "{indent}<outline text=\"{text}\"/>");
+            } else {
+                let _ = writeln!(opml, "{indent}<outline text=\"{text}\">");
+                outlines(&node.children, depth + 1, opml);
+                let _ = writeln!(opml, "{indent}</outline>");
+            }
+        }
+    }
+
+    let mut opml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    opml.push_str("<opml version=\"2.0\">\n");
+    let _ = writeln!(
+        opml,
+        "  <head>\n    <title>{}</title>\n  </head>\n  <body>",
+        escape_html(title)
+    );
+    outlines(tree, 0, &mut opml);
+    opml.push_str("  </body>\n</opml>\n");
+    opml
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// `table-2-quarterly-results.csv` style file name
+fn table_file_name(table: &DocumentTable) -> String {
+    let mut slug = String::new();
+    for c in table.title.as_deref().unwrap_or_default().chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug: String = slug.trim_end_matches('-').chars().take(40).collect();
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        format!("table-{}.csv", table.number)
+    } else {
+        format!("table-{}-{slug}.csv", table.number)
+    }
+}
+
+/// The `--export json` output
+pub fn to_json(document: &Document) -> Result<String> {
+    let json = crate::json_export::to_json_document(document);
+    Ok(format!("{}\n", serde_json::to_string_pretty(&json)?))
+}
+
+#[allow(dead_code)]
+pub fn extract_citations(document: &Document) -> Result<Vec<Citation>> {
+    let mut citations = Vec::new();
+
+    // Simple citation extraction - look for common citation patterns
+    for (index, element) in document.elements.iter().enumerate() {
+        let text = match element {
+            DocumentElement::Heading { text, .. } | DocumentElement::Paragraph { text, .. } => text,
+            _ => continue,
+        };
+
+        // Look for citation patterns like (Author, Year) or [1]
+        let citation_patterns = [
+            r"\([A-Z][a-z]+,\s*\d{4}\)",             // (Author, 2024)
+            r"\[[0-9]+\]",                           // [1]
+            r"\([A-Z][a-z]+\s+et\s+al\.,\s*\d{4}\)", // (Author et al., 2024)
+        ];
+
+        for pattern in &citation_patterns {
+            if let Ok(regex) = regex::Regex::new(pattern) {
+                for mat in regex.find_iter(text) {
+                    citations.push(Citation {
+                        text: mat.as_str().to_string(),
+                        element_index: index,
+                        citation_type: CitationType::InText,
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(citations)
+}
+
+#[allow(dead_code)]
+pub fn extract_bibliography(document: &Document) -> Result<Vec<Citation>> {
+    let mut bibliography = Vec::new();
+
+    // Look for bibliography or references section
+    for (index, element) in document.elements.iter().enumerate() {
+        if let DocumentElement::Heading { text, .. } = element {
+            if text.to_lowercase().contains("reference")
+                || text.to_lowercase().contains("bibliography")
+                || text.to_lowercase().contains("works cited")
+            {
+                // Process following elements as bibliography entries
+                for (bib_index, bib_element) in document.elements[index + 1..].iter().enumerate() {
+                    match bib_element {
+                        #[allow(clippy::collapsible_match)]
+                        DocumentElement::Paragraph { text, .. } => {
+                            if !text.trim().is_empty() {
+                                bibliography.push(Citation {
+                                    text: text.clone(),
+                                    element_index: index + bib_index + 1,
+                                    citation_type: CitationType::Bibliography,
+                                });
+                            }
+                        }
+                        DocumentElement::List { items, .. } => {
+                            for item in items {
+                                bibliography.push(Citation {
+                                    text: item.text.clone(),
+                                    element_index: index + bib_index + 1,
+                                    citation_type: CitationType::Bibliography,
+                                });
+                            }
+                        }
+                        DocumentElement::Heading { .. } => break, // Next section
+                        _ => {}
+                    }
+                }
+                break;
+            }
+        }
+    }
+
+    Ok(bibliography)
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub struct Citation {
+    pub text: String,
+    pub element_index: usize,
+    pub citation_type: CitationType,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub enum CitationType {
+    InText,
+    Bibliography,
+}
+
+fn escape_csv_field(field: &str) -> String {
+    if field.contains(',') || field.contains('"') || field.contains('\n') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// Helper functions for text table rendering
+fn generate_text_table_border(
+    column_widths: &[usize],
+    left: &str,
+    middle: &str,
+    right: &str,
+    fill: &str,
+) -> String {
+    let mut border = String::new();
+    border.push_str(left);
+
+    for (i, &width) in column_widths.iter().enumerate() {
+        border.push_str(&fill.repeat(width + 2)); // +2 for padding
+        if i < column_widths.len() - 1 {
+            border.push_str(middle);
+        }
+    }
+
+    border.push_str(right);
+    border
+}
+
+fn render_text_table_row(cells: &[TableCell], column_widths: &[usize], _is_header: bool) -> String {
+    let mut row = String::new();
+    row.push('│');
+
+    for (i, cell) in cells.iter().enumerate() {
+        let width = column_widths.get(i).copied().unwrap_or(10);
+        let aligned_content = align_to_width(&cell.content, cell.alignment, width);
+
+        row.push(' ');
+        row.push_str(&aligned_content);
+        row.push(' ');
+        row.push('│');
+    }
+
+    row
+}
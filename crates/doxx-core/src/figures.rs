@@ -0,0 +1,171 @@
+//! Figure and table numbering.
+//!
+//! Word numbers captions with `SEQ` fields and keeps the last computed
+//! number in the text, which goes stale when figures are moved or added
+//! without updating fields. [`number_figures`] counts captions per kind in
+//! document order, as Word does when it prints, so "see Figure 3" can be
+//! resolved to the figure that is third on paper.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::document::{Document, DocumentElement};
+
+/// A numbered caption
+#[derive(Debug, Clone, PartialEq)]
+pub struct Figure {
+    /// What is numbered: "Figure", "Table", "Listing", ...
+    pub kind: String,
+    /// Position among the captions of its kind, counting from 1
+    pub number: usize,
+    /// Caption text without its label
+    pub caption: String,
+    /// Index of the caption element
+    pub element_index: usize,
+}
+
+impl Figure {
+    /// "Figure 3"
+    pub fn label(&self) -> String {
+        format!("{} {}", self.kind, self.number)
+    }
+}
+
+/// A caption's own label: a capitalized word, a number (`3`, `2-1`, `4.2`)
+/// and a separator before the text
+static CAPTION_LABEL_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(\p{Lu}\p{L}*)\.?\s+\d+(?:[.\-–]\d+)*\s*[:.\-–—]?\s*(.*)$").unwrap()
+});
+
+/// A reference in running text: "Figure 3", "fig. 3", "Tables 2"
+static REFERENCE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)^(\p{L}+)\.?\s*(\d+)$").unwrap());
+
+/// Full name for the usual abbreviations, and singular for plurals
+fn canonical_kind(kind: &str) -> String {
+    let lower = kind.to_lowercase();
+    let lower = match lower.as_str() {
+        "fig" | "figs" => "figure",
+        "tab" | "tbl" => "table",
+        "eq" | "eqn" => "equation",
+        other => other.strip_suffix('s').unwrap_or(other),
+    };
+    let mut chars = lower.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// Number every caption of `document`. The kind comes from the caption's
+/// `SEQ` field, or else from the label it starts with; captions with
+/// neither aren't numbered.
+pub fn number_figures(document: &Document) -> Vec<Figure> {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    let mut figures = Vec::new();
+    for (element_index, element) in document.elements.iter().enumerate() {
+        let DocumentElement::Caption { text, sequence, .. } = element else {
+            continue;
+        };
+        let label = CAPTION_LABEL_RE.captures(text.trim());
+        let kind = match (sequence, &label) {
+            (Some(sequence), _) => canonical_kind(sequence),
+            (None, Some(label)) => canonical_kind(&label[1]),
+            (None, None) => continue,
+        };
+        let caption = match &label {
+            Some(label) if canonical_kind(&label[1]) == kind => label[2].to_string(),
+            _ => text.trim().to_string(),
+        };
+
+        let number = match counts.iter_mut().find(|(counted, _)| *counted == kind) {
+            Some((_, count)) => {
+                *count += 1;
+                *count
+            }
+            None => {
+                counts.push((kind.clone(), 1));
+                1
+            }
+        };
+        figures.push(Figure {
+            kind,
+            number,
+            caption,
+            element_index,
+        });
+    }
+    figures
+}
+
+/// The figure a reference such as "Figure 3", "fig. 3" or "table 2" points to
+pub fn resolve_reference<'a>(figures: &'a [Figure], reference: &str) -> Option<&'a Figure> {
+    let captures = REFERENCE_RE.captures(reference.trim())?;
+    let kind = canonical_kind(&captures[1]);
+    let number: usize = captures[2].parse().ok()?;
+    figures
+        .iter()
+        .find(|figure| figure.kind == kind && figure.number == number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::{DocumentMetadata, ImageOptions};
+
+    #[test]
+    fn test_number_figures() {
+        let caption = |text: &str, sequence: Option<&str>| DocumentElement::Caption {
+            text: text.to_string(),
+            style: None,
+            sequence: sequence.map(str::to_string),
+        };
+        let document = Document {
+            title: "Report".to_string(),
+            metadata: DocumentMetadata {
+                file_path: "report.docx".to_string(),
+                file_size: 0,
+                word_count: 0,
+                page_count: 1,
+                created: None,
+                modified: None,
+                author: None,
+            },
+            elements: vec![
+                caption("Figure 1: Revenue by quarter", Some("Figure")),
+                caption("Table 1 – Regions", None),
+                // Inserted later without updating fields
+                caption("Figure 1: Customer growth", Some("Figure")),
+                caption("Fig. 2. Churn", None),
+                caption("Source: company filings", None),
+            ],
+            image_options: ImageOptions::default(),
+            warnings: Vec::new(),
+            bookmarks: Default::default(),
+            pages: Default::default(),
+            hygiene: Default::default(),
+            image_dir: None,
+        };
+
+        let figures = number_figures(&document);
+        let labels: Vec<(String, &str)> = figures
+            .iter()
+            .map(|figure| (figure.label(), figure.caption.as_str()))
+            .collect();
+        assert_eq!(
+            labels,
+            [
+                ("Figure 1".to_string(), "Revenue by quarter"),
+                ("Table 1".to_string(), "Regions"),
+                ("Figure 2".to_string(), "Customer growth"),
+                ("Figure 3".to_string(), "Churn"),
+            ]
+        );
+
+        let found = |reference| resolve_reference(&figures, reference).map(|f| f.element_index);
+        assert_eq!(found("see"), None);
+        assert_eq!(found("figure 3"), Some(3));
+        assert_eq!(found("Fig. 2"), Some(2));
+        assert_eq!(found("Tables 1"), Some(1));
+        assert_eq!(found("Table 2"), None);
+    }
+}
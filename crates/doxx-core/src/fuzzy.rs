@@ -0,0 +1,147 @@
+/// Score bonus for each matched character
+const MATCH_SCORE: i64 = 16;
+/// Extra bonus when a match directly follows the previous one
+const CONSECUTIVE_BONUS: i64 = 24;
+/// Extra bonus when a match starts a word (after a space, punctuation or at the start)
+const WORD_START_BONUS: i64 = 20;
+/// Penalty per skipped character between two matches
+const GAP_PENALTY: i64 = 2;
+
+/// Result of fuzzy-matching a query against a candidate string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    pub score: i64,
+    /// Character (not byte) indices of the candidate that matched the query
+    pub positions: Vec<usize>,
+}
+
+/// Case-insensitive subsequence match in the style of editor command palettes.
+///
+/// Every query character must appear in order in the candidate. Consecutive
+/// runs and matches at word starts score higher, gaps score lower, so
+/// `"intro"` ranks "Introduction" above "Interim Report Overview".
+/// Whitespace in the query is ignored. Returns `None` when there is no match.
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<FuzzyMatch> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if query.is_empty() {
+        return Some(FuzzyMatch {
+            score: 0,
+            positions: Vec::new(),
+        });
+    }
+
+    let chars: Vec<char> = candidate.chars().collect();
+    let lowered: Vec<char> = chars
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+
+    // Greedily find the earliest match, then tighten it by walking backwards
+    // from its end so "abc" in "a_xabc" settles on the trailing run
+    let mut end = None;
+    let mut qi = 0;
+    for (i, c) in lowered.iter().enumerate() {
+        if *c == query[qi] {
+            qi += 1;
+            if qi == query.len() {
+                end = Some(i);
+                break;
+            }
+        }
+    }
+    let end = end?;
+
+    let mut positions = Vec::with_capacity(query.len());
+    let mut qi = query.len();
+    for i in (0..=end).rev() {
+        if lowered[i] == query[qi - 1] {
+            positions.push(i);
+            qi -= 1;
+            if qi == 0 {
+                break;
+            }
+        }
+    }
+    positions.reverse();
+
+    let mut score = 0;
+    for (n, &pos) in positions.iter().enumerate() {
+        score += MATCH_SCORE;
+        if pos == 0 || !chars[pos - 1].is_alphanumeric() {
+            score += WORD_START_BONUS;
+        }
+        if n > 0 {
+            let gap = (pos - positions[n - 1] - 1) as i64;
+            if gap == 0 {
+                score += CONSECUTIVE_BONUS;
+            } else {
+                score -= gap * GAP_PENALTY;
+            }
+        }
+    }
+    // Prefer matches that start early and shorter candidates overall
+    score -= positions[0] as i64;
+    score -= (chars.len() / 8) as i64;
+
+    Some(FuzzyMatch { score, positions })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requires_subsequence() {
+        assert!(fuzzy_match("rsk", "Risk Assessment").is_some());
+        assert!(fuzzy_match("ksr", "Risk Assessment").is_none());
+        assert_eq!(fuzzy_match("", "Anything").unwrap().score, 0);
+    }
+
+    #[test]
+    fn test_ranks_word_starts_and_runs_higher() {
+        let intro = fuzzy_match("intro", "Introduction").unwrap();
+        let scattered = fuzzy_match("intro", "Interim Report Overview").unwrap();
+        assert!(intro.score > scattered.score);
+        assert_eq!(intro.positions, vec![0, 1, 2, 3, 4]);
+
+        let acronym = fuzzy_match("ra", "Risk Assessment").unwrap();
+        assert_eq!(acronym.positions, vec![0, 5]);
+    }
+
+    #[test]
+    fn test_ranks_palette_candidates() {
+        let rank = |query: &str, candidates: &[&'static str]| {
+            let mut ranked: Vec<(i64, &str)> = candidates
+                .iter()
+                .filter_map(|c| fuzzy_match(query, c).map(|m| (m.score, *c)))
+                .collect();
+            ranked.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+            ranked.into_iter().map(|(_, c)| c).collect::<Vec<_>>()
+        };
+        // A word start beats the same letters inside a word
+        assert_eq!(
+            rank(
+                "arch",
+                &["Research Methods", "2.1 Architecture", "Data Flow"]
+            ),
+            ["2.1 Architecture", "Research Methods"]
+        );
+        // Among equal matches the earlier and then the shorter one wins
+        assert_eq!(
+            rank(
+                "test",
+                &["3.2 Integration Testing", "3 Testing Strategy", "Testing"]
+            ),
+            ["Testing", "3 Testing Strategy", "3.2 Integration Testing"]
+        );
+        // Spaces in the query don't have to line up with the candidate's
+        assert_eq!(
+            rank("unit test", &["3.1 Unit Testing"]),
+            ["3.1 Unit Testing"]
+        );
+    }
+}
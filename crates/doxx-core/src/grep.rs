@@ -0,0 +1,249 @@
+//! `doxx grep`: search many documents at once.
+//!
+//! Each document is parsed on its own task and searched the way the viewer
+//! searches, so matches inside tables, list items and captions are found
+//! too. Every match is reported with the page it starts on and the heading
+//! of the section it falls under.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::color::{paint, BOLD, GREEN, MAGENTA, RED};
+use crate::convert::{is_docx, is_glob};
+use crate::document::{
+    load_document, search_document_with, Document, DocumentElement, ImageOptions, ParseOptions,
+    SearchOptions,
+};
+use crate::error::{DoxxError, Result};
+use crate::report::collect_docx_files;
+
+/// A match in one of the searched documents
+#[derive(Debug, Clone, Serialize)]
+pub struct GrepMatch {
+    pub file: PathBuf,
+    pub page: usize,
+    /// Heading of the section the match is in, with its number
+    pub section: Option<String>,
+    /// Index of the matching element in the document
+    pub element: usize,
+    /// The paragraph, list item or table cell that matched
+    pub text: String,
+    /// Byte range of the match in `text`
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Matches in one document, or why it couldn't be searched
+#[derive(Debug)]
+pub struct GrepOutcome {
+    pub file: PathBuf,
+    pub result: Result<Vec<GrepMatch>>,
+}
+
+/// Expand folders (recursively) and glob patterns into the documents to
+/// search, each once, in the order given
+pub fn collect_files(inputs: &[String]) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    for input in inputs {
+        let path = Path::new(input);
+        let mut found = Vec::new();
+        if path.is_dir() {
+            collect_docx_files(path, &mut found)?;
+        } else if is_glob(input) {
+            for entry in glob::glob(input)? {
+                let file = entry?;
+                if file.is_file() && is_docx(&file) {
+                    found.push(file);
+                }
+            }
+        } else if path.is_file() {
+            found.push(path.to_path_buf());
+        } else {
+            return Err(DoxxError::NotFound(format!("File not found: {input}")));
+        }
+
+        found.sort();
+        for file in found {
+            if !files.contains(&file) {
+                files.push(file);
+            }
+        }
+    }
+    if files.is_empty() {
+        return Err(DoxxError::NotFound(format!(
+            "No .docx files found in {}",
+            inputs.join(", ")
+        )));
+    }
+    Ok(files)
+}
+
+/// Matches for `query` in `document`, read from `file`
+pub fn grep_document(
+    document: &Document,
+    file: &Path,
+    query: &str,
+    options: &SearchOptions,
+) -> Vec<GrepMatch> {
+    let mut sections = Vec::with_capacity(document.elements.len());
+    let mut section: Option<String> = None;
+    for element in &document.elements {
+        if let DocumentElement::Heading { text, number, .. } = element {
+            section = Some(match number {
+                Some(number) => format!("{number} {text}"),
+                None => text.clone(),
+            });
+        }
+        sections.push(section.clone());
+    }
+
+    search_document_with(document, query, options)
+        .into_iter()
+        .map(|result| GrepMatch {
+            file: file.to_path_buf(),
+            page: document.pages.page_of(result.element_index),
+            section: sections[result.element_index].clone(),
+            element: result.element_index,
+            text: result.text,
+            start: result.start_pos,
+            end: result.end_pos,
+        })
+        .collect()
+}
+
+/// Search every file for `query`, parsing up to `parallelism` documents at
+/// once. Outcomes come back in the order of `files`.
+pub async fn grep_files(
+    files: Vec<PathBuf>,
+    query: &str,
+    options: SearchOptions,
+    parse_options: ParseOptions,
+    parallelism: usize,
+) -> Vec<GrepOutcome> {
+    let query = query.to_string();
+    map_documents(files, parse_options, parallelism, move |document, file| {
+        grep_document(document, file, &query, &options)
+    })
+    .await
+    .into_iter()
+    .map(|(file, result)| GrepOutcome { file, result })
+    .collect()
+}
+
+/// Parse up to `parallelism` of `files` at once and run `inspect` on each
+/// document. Results come back in the order of `files`.
+pub async fn map_documents<T, F>(
+    files: Vec<PathBuf>,
+    parse_options: ParseOptions,
+    parallelism: usize,
+    inspect: F,
+) -> Vec<(PathBuf, Result<T>)>
+where
+    T: Send + 'static,
+    F: Fn(&Document, &Path) -> T + Clone + Send + 'static,
+{
+    let permits = Arc::new(Semaphore::new(parallelism.max(1)));
+    let mut tasks = JoinSet::new();
+    for (index, file) in files.iter().cloned().enumerate() {
+        let permits = Arc::clone(&permits);
+        let parse_options = parse_options.clone();
+        let inspect = inspect.clone();
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            let result = load_document(&file, ImageOptions::default(), parse_options)
+                .await
+                .map(|document| inspect(&document, &file));
+            (index, result)
+        });
+    }
+
+    let mut results: Vec<Option<Result<T>>> = files.iter().map(|_| None).collect();
+    // A task that panicked leaves its slot empty and is reported below
+    while let Some(joined) = tasks.join_next().await {
+        if let Ok((index, result)) = joined {
+            results[index] = Some(result);
+        }
+    }
+
+    files
+        .into_iter()
+        .zip(results)
+        .map(|(file, result)| {
+            let result = result.unwrap_or(Err(DoxxError::Aborted("search")));
+            (file, result)
+        })
+        .collect()
+}
+
+/// A match as one `file:page:section: text` line, ripgrep style, with the
+/// match highlighted when `color` is set. Line breaks in the text become
+/// spaces.
+pub fn format_match(found: &GrepMatch, color: bool) -> String {
+    let flatten = |text: &str| text.replace(['\r', '\n'], " ");
+    let text = &found.text;
+    let (before, matched, after) = match (text.get(..found.start), text.get(found.start..found.end))
+    {
+        (Some(before), Some(matched)) => (before, matched, &text[found.end..]),
+        _ => (text.as_str(), "", ""),
+    };
+
+    let file = found.file.display().to_string();
+    let page = found.page.to_string();
+    let mut line = if color {
+        format!("{}:{}:", paint(&file, MAGENTA), paint(&page, GREEN))
+    } else {
+        format!("{file}:{page}:")
+    };
+    if let Some(section) = &found.section {
+        line.push_str(&flatten(section));
+        line.push(':');
+    }
+    line.push(' ');
+    line.push_str(&flatten(before));
+    if color && !matched.is_empty() {
+        line.push_str(&paint(&flatten(matched), &format!("{BOLD};{RED}")));
+    } else {
+        line.push_str(&flatten(matched));
+    }
+    line.push_str(&flatten(after));
+    line
+}
+
+/// The matches as a pretty-printed JSON array
+pub fn matches_to_json(matches: &[GrepMatch]) -> Result<String> {
+    Ok(serde_json::to_string_pretty(matches)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_grep_reports_page_and_section() {
+        let files =
+            collect_files(&["../../tests/fixtures/business-report.docx".to_string()]).unwrap();
+        let outcomes = grep_files(
+            files,
+            "revenue",
+            SearchOptions::default(),
+            ParseOptions::default(),
+            2,
+        )
+        .await;
+        assert_eq!(outcomes.len(), 1);
+        let matches = outcomes[0].result.as_ref().unwrap();
+        assert!(!matches.is_empty());
+
+        let first = &matches[0];
+        assert!(first.section.is_some());
+        assert_eq!(first.text[first.start..first.end].to_lowercase(), "revenue");
+        let line = format_match(first, false);
+        assert!(line.starts_with("../../tests/fixtures/business-report.docx:1:"));
+        assert!(line.contains(&first.text.replace('\n', " ")));
+
+        assert!(collect_files(&["missing.docx".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,201 @@
+//! Syntax highlighting for code blocks.
+//!
+//! Code found in a document rarely says what language it is in, so a
+//! fence's info string (```` ```python ````) is used when there is one and
+//! the language is otherwise guessed from the code itself.
+
+use once_cell::sync::Lazy;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{FontStyle, Theme, ThemeSet};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+
+static SYNTAXES: Lazy<SyntaxSet> = Lazy::new(SyntaxSet::load_defaults_newlines);
+
+static THEME: Lazy<Theme> = Lazy::new(|| {
+    let mut themes = ThemeSet::load_defaults();
+    themes
+        .themes
+        .remove("base16-ocean.dark")
+        .unwrap_or_default()
+});
+
+/// A piece of a highlighted line
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub text: String,
+    pub color: (u8, u8, u8),
+    pub bold: bool,
+    pub italic: bool,
+}
+
+/// Words and symbols that give a language away, and the language they belong to
+const LANGUAGE_MARKERS: &[(&str, &[&str])] = &[
+    (
+        "rust",
+        &[
+            "fn ", "let mut ", "impl ", "pub fn", "::", "-> ", "use ", "println!",
+        ],
+    ),
+    (
+        "python",
+        &[
+            "def ", "import ", "self.", "elif ", "print(", "from ", "None", "):",
+        ],
+    ),
+    (
+        "javascript",
+        &[
+            "function ",
+            "const ",
+            "=> ",
+            "console.log",
+            "let ",
+            "require(",
+            "===",
+            "};",
+        ],
+    ),
+    ("go", &["package ", "func ", ":= ", "fmt.", "import ("]),
+    (
+        "java",
+        &["public class", "System.out", "private ", "void ", "new "],
+    ),
+    ("c", &["#include", "int main", "printf(", "->", "NULL"]),
+    (
+        "sql",
+        &[
+            "SELECT ",
+            "FROM ",
+            "WHERE ",
+            "INSERT INTO",
+            "GROUP BY",
+            "ORDER BY",
+            "JOIN ",
+        ],
+    ),
+    (
+        "bash",
+        &[
+            "$ ", "echo ", "sudo ", "cd ", "export ", "#!/bin", "apt ", " | grep",
+        ],
+    ),
+];
+
+/// Guess the language of `lines`, as a name [`highlight`] accepts. Needs a
+/// first line that names it (a shebang, `<?xml`) or at least two tell-tale
+/// words from one language, so prose in a monospace font stays plain.
+pub fn guess_language(lines: &[String]) -> Option<String> {
+    let code = lines.join("\n");
+    let trimmed = code.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    if (trimmed.starts_with('{') || trimmed.starts_with('['))
+        && serde_json::from_str::<serde_json::Value>(trimmed).is_ok()
+    {
+        return Some("json".to_string());
+    }
+    if trimmed.starts_with('<') && trimmed.ends_with('>') {
+        return Some(
+            if trimmed.starts_with("<?xml") {
+                "xml"
+            } else {
+                "html"
+            }
+            .to_string(),
+        );
+    }
+    let first_line = lines.iter().find(|line| !line.trim().is_empty())?;
+    if let Some(syntax) = SYNTAXES.find_syntax_by_first_line(first_line) {
+        let name = syntax.file_extensions.first().unwrap_or(&syntax.name);
+        return Some(name.to_lowercase());
+    }
+
+    LANGUAGE_MARKERS
+        .iter()
+        .map(|(language, markers)| {
+            let score = markers
+                .iter()
+                .filter(|marker| code.contains(*marker))
+                .count();
+            (score, *language)
+        })
+        .filter(|(score, _)| *score >= 2)
+        .max_by_key(|(score, _)| *score)
+        .map(|(_, language)| language.to_string())
+}
+
+fn find_syntax(language: &str) -> Option<&'static SyntaxReference> {
+    SYNTAXES.find_syntax_by_token(language).or_else(|| {
+        SYNTAXES
+            .syntaxes()
+            .iter()
+            .find(|syntax| syntax.name.eq_ignore_ascii_case(language))
+    })
+}
+
+/// Highlight `lines` as `language`, or `None` for a language the
+/// highlighter doesn't know
+pub fn highlight(lines: &[String], language: &str) -> Option<Vec<Vec<Token>>> {
+    let syntax = find_syntax(language)?;
+    let mut highlighter = HighlightLines::new(syntax, &THEME);
+    let mut highlighted = Vec::with_capacity(lines.len());
+    for line in lines {
+        let line = format!("{line}\n");
+        let ranges = highlighter.highlight_line(&line, &SYNTAXES).ok()?;
+        highlighted.push(
+            ranges
+                .into_iter()
+                .map(|(style, text)| Token {
+                    text: text.trim_end_matches('\n').to_string(),
+                    color: (style.foreground.r, style.foreground.g, style.foreground.b),
+                    bold: style.font_style.contains(FontStyle::BOLD),
+                    italic: style.font_style.contains(FontStyle::ITALIC),
+                })
+                .filter(|token| !token.text.is_empty())
+                .collect(),
+        );
+    }
+    Some(highlighted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(code: &str) -> Vec<String> {
+        code.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_guess_language() {
+        let rust = lines("fn main() {\n    let mut total = 0;\n    println!(\"{total}\");\n}");
+        assert_eq!(guess_language(&rust).as_deref(), Some("rust"));
+        let python = lines("def greet(name):\n    print(f\"Hello {name}\")");
+        assert_eq!(guess_language(&python).as_deref(), Some("python"));
+        assert_eq!(
+            guess_language(&lines("{\"fuzzy\": true}")).as_deref(),
+            Some("json")
+        );
+        assert_eq!(
+            guess_language(&lines("#!/usr/bin/env python3\nx = 1")).as_deref(),
+            Some("py")
+        );
+        // A monospace sentence is not code
+        assert_eq!(guess_language(&lines("Press any key to continue.")), None);
+    }
+
+    #[test]
+    fn test_highlight_keeps_text() {
+        let code = lines("let x = 1;\nlet y = \"two\";");
+        let highlighted = highlight(&code, "rust").unwrap();
+        for (tokens, line) in highlighted.iter().zip(&code) {
+            let text: String = tokens.iter().map(|token| token.text.as_str()).collect();
+            assert_eq!(&text, line);
+        }
+        // Keywords and strings get different colors
+        let colors: Vec<_> = highlighted[1].iter().map(|token| token.color).collect();
+        assert!(colors.windows(2).any(|pair| pair[0] != pair[1]));
+        assert!(highlight(&code, "no-such-language").is_none());
+    }
+}
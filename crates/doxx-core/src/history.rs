@@ -0,0 +1,414 @@
+//! Reading history: time spent in the viewer per document and per section.
+//!
+//! Recording is opt-in (`history.enabled` in the config). The viewer keeps a
+//! [`SessionClock`] while a document is open and appends the finished
+//! [`Session`] to the [`HistoryStore`], which `doxx history` prints.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::error::Result;
+use crate::store::{self, document_key};
+
+/// Longest gap between two key presses that still counts as reading. Time
+/// beyond it is taken as the reader having stepped away.
+pub const IDLE_LIMIT: Duration = Duration::from_secs(5 * 60);
+
+/// Output format for `doxx history`
+#[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum HistoryFormat {
+    #[default]
+    Text,
+    /// One row per session and section, for timesheets
+    Csv,
+    Json,
+}
+
+/// Time spent in one section of a document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SectionTime {
+    /// Heading of the section, empty before the first heading
+    pub section: String,
+    pub seconds: u64,
+}
+
+/// One viewer session on a document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    /// Canonical path of the document
+    pub file: String,
+    pub title: String,
+    /// Seconds since the Unix epoch when the document was opened
+    pub started: u64,
+    /// Time spent reading, idle gaps excluded
+    pub seconds: u64,
+    /// Reading time per section, in the order they were first visited
+    pub sections: Vec<SectionTime>,
+}
+
+/// Measures reading time while a document is open. Call [`record`] on each
+/// key press with the section that was on screen until then.
+///
+/// [`record`]: SessionClock::record
+#[derive(Debug)]
+pub struct SessionClock {
+    file: String,
+    title: String,
+    started: u64,
+    last_activity: Instant,
+    sections: Vec<(String, Duration)>,
+}
+
+impl SessionClock {
+    pub fn start(file: &Path, title: &str) -> Self {
+        Self {
+            file: document_key(file),
+            title: title.to_string(),
+            started: now(),
+            last_activity: Instant::now(),
+            sections: Vec::new(),
+        }
+    }
+
+    /// Credit the time since the last key press to `section`
+    pub fn record(&mut self, section: &str) {
+        self.record_at(section, Instant::now());
+    }
+
+    fn record_at(&mut self, section: &str, at: Instant) {
+        let elapsed = at
+            .saturating_duration_since(self.last_activity)
+            .min(IDLE_LIMIT);
+        self.last_activity = at;
+        match self.sections.iter_mut().find(|(name, _)| name == section) {
+            Some((_, total)) => *total += elapsed,
+            None => self.sections.push((section.to_string(), elapsed)),
+        }
+    }
+
+    /// The session so far, with sections under a second dropped
+    pub fn finish(&self) -> Session {
+        let sections: Vec<SectionTime> = self
+            .sections
+            .iter()
+            .map(|(section, time)| SectionTime {
+                section: section.clone(),
+                seconds: time.as_secs_f64().round() as u64,
+            })
+            .filter(|section| section.seconds > 0)
+            .collect();
+        Session {
+            file: self.file.clone(),
+            title: self.title.clone(),
+            started: self.started,
+            seconds: sections.iter().map(|section| section.seconds).sum(),
+            sections,
+        }
+    }
+}
+
+/// Reading time across all sessions on one document
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DocumentTime {
+    pub file: String,
+    pub title: String,
+    pub sessions: usize,
+    pub seconds: u64,
+    /// Start of the latest session, in seconds since the Unix epoch
+    pub last_read: u64,
+}
+
+/// JSON-backed list of finished reading sessions
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct HistoryStore {
+    sessions: Vec<Session>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl HistoryStore {
+    /// Default history location (e.g. `~/.local/state/doxx/history.json`)
+    pub fn default_path() -> PathBuf {
+        dirs::state_dir()
+            .or_else(dirs::data_local_dir)
+            .unwrap_or_else(std::env::temp_dir)
+            .join("doxx")
+            .join("history.json")
+    }
+
+    /// Open the history at the default location
+    pub fn open_default() -> Result<Self> {
+        Self::open(&Self::default_path())
+    }
+
+    /// Open the history at `path`, starting empty if it doesn't exist yet
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut store: Self = store::load(path)?;
+        store.path = path.to_path_buf();
+        Ok(store)
+    }
+
+    /// Write the history back to disk
+    pub fn save(&self) -> Result<()> {
+        store::save(&self.path, self)
+    }
+
+    /// Add a finished session; sessions with no reading time are not kept
+    pub fn record(&mut self, session: Session) {
+        if session.seconds > 0 {
+            self.sessions.push(session);
+        }
+    }
+
+    /// Sessions on `file`, or all sessions, oldest first
+    pub fn sessions(&self, file: Option<&Path>) -> Vec<&Session> {
+        let key = file.map(document_key);
+        self.sessions
+            .iter()
+            .filter(|session| key.as_ref().is_none_or(|key| session.file == *key))
+            .collect()
+    }
+
+    /// Totals per document, most recently read first
+    pub fn documents(&self) -> Vec<DocumentTime> {
+        let mut documents: Vec<DocumentTime> = Vec::new();
+        for session in &self.sessions {
+            match documents.iter_mut().find(|doc| doc.file == session.file) {
+                Some(doc) => {
+                    doc.title = session.title.clone();
+                    doc.sessions += 1;
+                    doc.seconds += session.seconds;
+                    doc.last_read = doc.last_read.max(session.started);
+                }
+                None => documents.push(DocumentTime {
+                    file: session.file.clone(),
+                    title: session.title.clone(),
+                    sessions: 1,
+                    seconds: session.seconds,
+                    last_read: session.started,
+                }),
+            }
+        }
+        documents.sort_by_key(|doc| std::cmp::Reverse(doc.last_read));
+        documents
+    }
+}
+
+/// The history in `format`: totals per document, or with `file` that document's
+/// sessions and the time spent in each section
+pub fn format_history(
+    store: &HistoryStore,
+    file: Option<&Path>,
+    format: HistoryFormat,
+) -> Result<String> {
+    let sessions = store.sessions(file);
+    Ok(match format {
+        HistoryFormat::Csv => format_history_csv(&sessions),
+        HistoryFormat::Json if file.is_some() => {
+            format!("{}\n", serde_json::to_string_pretty(&sessions)?)
+        }
+        HistoryFormat::Json => format!("{}\n", serde_json::to_string_pretty(&store.documents())?),
+        HistoryFormat::Text if sessions.is_empty() => "No reading history.\n".to_string(),
+        HistoryFormat::Text if file.is_some() => format_sessions_text(&sessions),
+        HistoryFormat::Text => format_documents_text(&store.documents()),
+    })
+}
+
+fn format_documents_text(documents: &[DocumentTime]) -> String {
+    let mut output = String::new();
+    for doc in documents {
+        let sessions = if doc.sessions == 1 {
+            "1 session".to_string()
+        } else {
+            format!("{} sessions", doc.sessions)
+        };
+        output.push_str(&format!(
+            "{}  {}  {}, last read {}\n    {}\n",
+            format_duration(doc.seconds),
+            doc.title,
+            sessions,
+            format_timestamp(doc.last_read),
+            doc.file
+        ));
+    }
+    let total: u64 = documents.iter().map(|doc| doc.seconds).sum();
+    output.push_str(&format!("\nTotal: {}\n", format_duration(total)));
+    output
+}
+
+fn format_sessions_text(sessions: &[&Session]) -> String {
+    let mut output = format!("{}\n{}\n", sessions[0].title, sessions[0].file);
+    let mut sections: Vec<(&str, u64)> = Vec::new();
+    for session in sessions {
+        output.push_str(&format!(
+            "\n{}  {}\n",
+            format_timestamp(session.started),
+            format_duration(session.seconds)
+        ));
+        for section in &session.sections {
+            output.push_str(&format!(
+                "    {:>8}  {}\n",
+                format_duration(section.seconds),
+                section_name(&section.section)
+            ));
+            match sections
+                .iter_mut()
+                .find(|(name, _)| *name == section.section)
+            {
+                Some((_, seconds)) => *seconds += section.seconds,
+                None => sections.push((&section.section, section.seconds)),
+            }
+        }
+    }
+
+    let total: u64 = sessions.iter().map(|session| session.seconds).sum();
+    output.push_str(&format!("\nTotal: {}\n", format_duration(total)));
+    sections.sort_by_key(|(_, seconds)| std::cmp::Reverse(*seconds));
+    for (section, seconds) in sections {
+        output.push_str(&format!(
+            "    {:>8}  {}\n",
+            format_duration(seconds),
+            section_name(section)
+        ));
+    }
+    output
+}
+
+fn format_history_csv(sessions: &[&Session]) -> String {
+    let mut output = String::from("started,file,title,section,seconds\n");
+    for session in sessions {
+        for section in &session.sections {
+            let fields = [
+                format_timestamp(session.started),
+                session.file.clone(),
+                session.title.clone(),
+                section.section.clone(),
+                section.seconds.to_string(),
+            ];
+            let line: Vec<String> = fields.iter().map(|f| escape_csv_field(f)).collect();
+            output.push_str(&line.join(","));
+            output.push('\n');
+        }
+    }
+    output
+}
+
+fn section_name(section: &str) -> &str {
+    if section.is_empty() {
+        "(before the first heading)"
+    } else {
+        section
+    }
+}
+
+fn escape_csv_field(field: &str) -> String {
+    if field.contains(',') || field.contains('"') || field.contains('\n') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// "1h 05m", "12m 30s" or "45s"
+pub fn format_duration(seconds: u64) -> String {
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}h {minutes:02}m")
+    } else if minutes > 0 {
+        format!("{minutes}m {seconds:02}s")
+    } else {
+        format!("{seconds}s")
+    }
+}
+
+/// UTC "YYYY-MM-DD HH:MM" for seconds since the Unix epoch
+fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86_400) as i64;
+    let time = timestamp % 86_400;
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}",
+        time / 3600,
+        time / 60 % 60
+    )
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_session_clock_and_history() {
+        let mut clock = SessionClock::start(Path::new("/reviews/contract.docx"), "Contract");
+        let start = clock.last_activity;
+        clock.record_at("", start + Duration::from_secs(20));
+        clock.record_at("1 Scope", start + Duration::from_secs(80));
+        // Away from the keyboard: only the idle limit counts
+        clock.record_at("2 Terms", start + Duration::from_secs(80) + IDLE_LIMIT * 3);
+        clock.record_at("1 Scope", start + Duration::from_secs(90) + IDLE_LIMIT * 3);
+        clock.record_at("2 Terms", start + Duration::from_secs(90) + IDLE_LIMIT * 3);
+
+        let session = clock.finish();
+        let sections: Vec<(&str, u64)> = session
+            .sections
+            .iter()
+            .map(|section| (section.section.as_str(), section.seconds))
+            .collect();
+        assert_eq!(sections, [("", 20), ("1 Scope", 70), ("2 Terms", 300)]);
+        assert_eq!(session.seconds, 390);
+
+        let path = std::env::temp_dir()
+            .join(format!("doxx-history-test-{}", std::process::id()))
+            .join("history.json");
+        let _ = fs::remove_file(&path);
+        let mut store = HistoryStore::open(&path).unwrap();
+        store.record(session.clone());
+        store.record(Session {
+            started: session.started + 3600,
+            ..session.clone()
+        });
+        store.record(Session {
+            seconds: 0,
+            sections: Vec::new(),
+            ..session
+        });
+        store.save().unwrap();
+
+        let store = HistoryStore::open(&path).unwrap();
+        let documents = store.documents();
+        assert_eq!(documents.len(), 1);
+        assert_eq!((documents[0].sessions, documents[0].seconds), (2, 780));
+
+        let text = format_history(&store, None, HistoryFormat::Text).unwrap();
+        assert!(text.contains("13m 00s  Contract  2 sessions"));
+        let csv = format_history(&store, None, HistoryFormat::Csv).unwrap();
+        assert_eq!(csv.lines().count(), 7);
+        assert!(csv.contains(",Contract,2 Terms,300"));
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00");
+        assert_eq!(format_timestamp(951_827_400), "2000-02-29 12:30");
+        assert_eq!(format_duration(3725), "1h 02m");
+    }
+}
@@ -0,0 +1,359 @@
+//! Document hygiene: what a reviewer should know before trusting a document.
+//!
+//! [`scan_package`] looks through the package for VBA macros, templates and
+//! images pulled in from outside it, other linked content, tracked changes
+//! nobody accepted or rejected, hidden text, and text colored like its
+//! background so it can't be seen on the page. None of it is shown or
+//! acted on by doxx; the findings are reported so they aren't missed when
+//! the document is later opened in Word.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::io::{Cursor, Read};
+use zip::ZipArchive;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Hygiene {
+    /// VBA project parts
+    pub macros: Vec<String>,
+    /// Templates attached from outside the package
+    pub external_templates: Vec<String>,
+    /// Images linked from outside the package, fetched when Word opens it
+    pub remote_images: Vec<String>,
+    /// Other linked content (objects, frames, subdocuments), as `kind: target`
+    pub external_content: Vec<String>,
+    /// Insertions, deletions and moves not yet accepted or rejected
+    pub tracked_changes: usize,
+    /// Runs of hidden text
+    pub hidden_text: usize,
+    /// Runs colored like their background (white on white)
+    pub invisible_text: usize,
+}
+
+static RELATIONSHIP_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"<Relationship\s[^>]*>").unwrap());
+static ATTRIBUTE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(\w+)="([^"]*)""#).unwrap());
+static REVISION_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"<w:(?:ins|del|moveFrom|moveTo)[\s>]").unwrap());
+static VANISH_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"<w:vanish(?:\s+w:val="(?:true|1|on)")?\s*/>"#).unwrap());
+/// Where shading can come from around a run: table cells and paragraphs,
+/// plus text boxes, whose shape fill isn't known
+static SHADED_RUN_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?s)<(/?)w:(tc|p|txbxContent)[\s>]|<w:shd\s[^>]*>|<w:r(?:\s[^>]*)?>\s*<w:rPr>(.*?)</w:rPr>",
+    )
+    .unwrap()
+});
+static COLOR_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"<w:color\s[^>]*w:val="(\w+)""#).unwrap());
+static SHADING_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"<w:shd\s[^>]*w:fill="(\w+)""#).unwrap());
+static HIGHLIGHT_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"<w:highlight\s[^>]*w:val="(\w+)""#).unwrap());
+
+impl Hygiene {
+    /// Nothing worth a warning was found
+    pub fn is_clean(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Runs of `xml` colored like the highlight, run, paragraph or table cell
+/// shading behind them, or like the page when there is none
+fn count_invisible_runs(xml: &str) -> usize {
+    #[derive(PartialEq)]
+    enum After {
+        CellStart,
+        ParagraphStart,
+        Content,
+    }
+    let value = |re: &Regex, text: &str| re.captures(text).map(|captures| captures[1].to_string());
+
+    let mut cell_fills: Vec<Option<String>> = Vec::new();
+    let mut paragraph_fill = None;
+    let mut in_text_box = 0usize;
+    let mut after = After::Content;
+    let mut count = 0;
+    for token in SHADED_RUN_RE.captures_iter(xml) {
+        if let Some(properties) = token.get(3) {
+            after = After::Content;
+            let properties = properties.as_str();
+            let Some(color) = value(&COLOR_RE, properties) else {
+                continue;
+            };
+            let shading = value(&SHADING_RE, properties)
+                .or_else(|| paragraph_fill.clone())
+                .or_else(|| cell_fills.iter().rev().flatten().next().cloned());
+            if in_text_box == 0
+                && is_invisible(
+                    &color,
+                    value(&HIGHLIGHT_RE, properties).as_deref(),
+                    shading.as_deref(),
+                )
+            {
+                count += 1;
+            }
+            continue;
+        }
+        let closing = token.get(1).is_some_and(|slash| !slash.is_empty());
+        match (token.get(2).map(|tag| tag.as_str()), closing) {
+            (Some("tc"), false) => {
+                cell_fills.push(None);
+                after = After::CellStart;
+            }
+            (Some("tc"), true) => {
+                cell_fills.pop();
+            }
+            (Some("p"), false) => {
+                paragraph_fill = None;
+                after = After::ParagraphStart;
+            }
+            (Some("txbxContent"), false) => in_text_box += 1,
+            (Some("txbxContent"), true) => in_text_box = in_text_box.saturating_sub(1),
+            (Some(_), _) => after = After::Content,
+            (None, _) => {
+                let fill = value(&SHADING_RE, &token[0]);
+                match after {
+                    After::CellStart => {
+                        if let Some(cell) = cell_fills.last_mut() {
+                            *cell = fill;
+                        }
+                    }
+                    After::ParagraphStart => paragraph_fill = fill,
+                    After::Content => {}
+                }
+            }
+        }
+    }
+    count
+}
+
+/// Whether text in `color` can't be told apart from its background: a
+/// `highlight` color name, else a `shading` fill, else a white page. Colors
+/// are hex (`FFFFFF`); `auto` follows the background and is always visible.
+pub fn is_invisible(color: &str, highlight: Option<&str>, shading: Option<&str>) -> bool {
+    let hex = |value: &str| {
+        let value = value.trim();
+        (value.len() == 6 && value.chars().all(|c| c.is_ascii_hexdigit()))
+            .then(|| value.to_ascii_uppercase())
+    };
+    let Some(color) = hex(color) else {
+        return false;
+    };
+    let background = highlight
+        .filter(|name| *name != "none")
+        .and_then(highlight_hex)
+        .or_else(|| shading.and_then(hex))
+        .unwrap_or_else(|| "FFFFFF".to_string());
+    color == background
+}
+
+/// Hex value of a `w:highlight` color name
+fn highlight_hex(name: &str) -> Option<String> {
+    let hex = match name {
+        "black" => "000000",
+        "blue" => "0000FF",
+        "cyan" => "00FFFF",
+        "green" => "00FF00",
+        "magenta" => "FF00FF",
+        "red" => "FF0000",
+        "yellow" => "FFFF00",
+        "white" => "FFFFFF",
+        "darkBlue" => "000080",
+        "darkCyan" => "008080",
+        "darkGreen" => "008000",
+        "darkMagenta" => "800080",
+        "darkRed" => "800000",
+        "darkYellow" => "808000",
+        "darkGray" => "808080",
+        "lightGray" => "C0C0C0",
+        _ => return None,
+    };
+    Some(hex.to_string())
+}
+
+/// Parts with the document's text, where tracked changes and hidden text count
+fn is_content_part(name: &str) -> bool {
+    let Some(file) = name.strip_prefix("word/") else {
+        return false;
+    };
+    !file.contains('/')
+        && file.ends_with(".xml")
+        && ["document", "header", "footer", "footnotes", "endnotes"]
+            .iter()
+            .any(|prefix| file.starts_with(prefix))
+}
+
+/// Look through the package in `file_data`. Data that isn't a zip archive
+/// has nothing to find.
+pub fn scan_package(file_data: &[u8]) -> Hygiene {
+    let mut hygiene = Hygiene::default();
+    let Ok(mut archive) = ZipArchive::new(Cursor::new(file_data)) else {
+        return hygiene;
+    };
+
+    for i in 0..archive.len() {
+        let Ok(mut entry) = archive.by_index(i) else {
+            continue;
+        };
+        let name = entry.name().to_string();
+        if name.to_lowercase().ends_with("vbaproject.bin") {
+            hygiene.macros.push(name);
+            continue;
+        }
+        let is_rels = name.ends_with(".rels");
+        if !is_rels && !is_content_part(&name) {
+            continue;
+        }
+        let mut xml = String::new();
+        if entry.read_to_string(&mut xml).is_err() {
+            continue;
+        }
+
+        if is_rels {
+            for relationship in RELATIONSHIP_RE.find_iter(&xml) {
+                add_relationship(&mut hygiene, relationship.as_str());
+            }
+        } else {
+            hygiene.tracked_changes += REVISION_RE.find_iter(&xml).count();
+            hygiene.hidden_text += VANISH_RE.find_iter(&xml).count();
+            hygiene.invisible_text += count_invisible_runs(&xml);
+        }
+    }
+    hygiene
+}
+
+/// Record a `<Relationship>` pointing outside the package; hyperlinks are
+/// only followed when clicked, so they don't count
+fn add_relationship(hygiene: &mut Hygiene, relationship: &str) {
+    let attribute = |name: &str| {
+        ATTRIBUTE_RE
+            .captures_iter(relationship)
+            .find(|captures| &captures[1] == name)
+            .map(|captures| captures[2].replace("&amp;", "&"))
+    };
+    if attribute("TargetMode").as_deref() != Some("External") {
+        return;
+    }
+    let target = attribute("Target").unwrap_or_default();
+    let kind = attribute("Type").unwrap_or_default();
+    let kind = kind.rsplit('/').next().unwrap_or_default();
+    match kind {
+        "hyperlink" => {}
+        "attachedTemplate" => hygiene.external_templates.push(target),
+        "image" => hygiene.remote_images.push(target),
+        _ => hygiene.external_content.push(format!("{kind}: {target}")),
+    }
+}
+
+/// The `--hygiene` report for the document at `path`
+pub fn format_report(hygiene: &Hygiene, path: &str) -> String {
+    let mut report = format!("Document hygiene: {path}\n\n");
+    if hygiene.is_clean() {
+        report
+            .push_str("✓ No macros, external content, unresolved tracked changes or hidden text\n");
+        return report;
+    }
+
+    let mut list = |label: &str, items: &[String]| {
+        if !items.is_empty() {
+            report.push_str(&format!("⚠ {label} ({}):\n", items.len()));
+            for item in items {
+                report.push_str(&format!("    {item}\n"));
+            }
+        }
+    };
+    list("VBA macros", &hygiene.macros);
+    list("External templates", &hygiene.external_templates);
+    list("Remote images", &hygiene.remote_images);
+    list("External content", &hygiene.external_content);
+    if hygiene.tracked_changes > 0 {
+        report.push_str(&format!(
+            "⚠ Unresolved tracked changes: {}\n",
+            hygiene.tracked_changes
+        ));
+    }
+    if hygiene.hidden_text > 0 {
+        report.push_str(&format!("⚠ Hidden text runs: {}\n", hygiene.hidden_text));
+    }
+    if hygiene.invisible_text > 0 {
+        report.push_str(&format!(
+            "⚠ Text runs colored like their background: {}\n",
+            hygiene.invisible_text
+        ));
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    fn package(parts: &[(&str, &str)]) -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in parts {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_scan_package() {
+        let fixture = std::fs::read("../../tests/fixtures/business-report.docx").unwrap();
+        assert!(scan_package(&fixture).is_clean());
+
+        const TYPES: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+        let settings_rels = format!(
+            r#"<Relationships><Relationship Id="rId1" Type="{TYPES}/attachedTemplate" Target="https://example.com/t.dotm" TargetMode="External"/></Relationships>"#
+        );
+        let document_rels = format!(
+            r#"<Relationships>
+<Relationship Id="rId2" Type="{TYPES}/image" Target="media/image1.png"/>
+<Relationship Id="rId3" Type="{TYPES}/image" Target="http://tracker.example/p.gif?a=1&amp;b=2" TargetMode="External"/>
+<Relationship Id="rId4" Type="{TYPES}/hyperlink" Target="https://example.com" TargetMode="External"/>
+<Relationship Id="rId5" TargetMode="External" Type="{TYPES}/oleObject" Target="file:///C:/data.xlsx"/>
+</Relationships>"#
+        );
+        let document = r#"<w:body><w:p><w:ins w:id="1"><w:r><w:t>new</w:t></w:r></w:ins>
+<w:del w:id="2"><w:r><w:delText>old</w:delText></w:r></w:del>
+<w:r><w:rPr><w:vanish/></w:rPr><w:t>secret</w:t></w:r>
+<w:r><w:rPr><w:vanish w:val="false"/></w:rPr><w:t>shown</w:t></w:r>
+<w:r><w:rPr><w:color w:val="ffffff"/></w:rPr><w:t>white on white</w:t></w:r>
+<w:r><w:rPr><w:color w:val="FFFFFF"/><w:shd w:val="clear" w:fill="1F3864"/></w:rPr><w:t>white on navy</w:t></w:r>
+<w:r><w:rPr><w:color w:val="000000"/><w:highlight w:val="black"/></w:rPr><w:t>redacted?</w:t></w:r></w:p>
+<w:tbl><w:tr><w:tc><w:tcPr><w:shd w:val="clear" w:fill="4472C4"/></w:tcPr><w:p><w:r><w:rPr><w:color w:val="FFFFFF"/></w:rPr><w:t>header</w:t></w:r></w:p></w:tc></w:tr></w:tbl>
+<w:p><w:r><w:drawing><w:txbxContent><w:p><w:r><w:rPr><w:color w:val="FFFFFF"/></w:rPr><w:t>on a shape</w:t></w:r></w:p></w:txbxContent></w:drawing></w:r></w:p></w:body>"#;
+        let hygiene = scan_package(&package(&[
+            ("word/document.xml", document),
+            ("word/_rels/document.xml.rels", &document_rels),
+            ("word/_rels/settings.xml.rels", &settings_rels),
+            ("word/vbaProject.bin", "VBA"),
+            // Style definitions aren't text
+            ("word/styles.xml", "<w:vanish/>"),
+        ]));
+
+        assert_eq!(hygiene.macros, ["word/vbaProject.bin"]);
+        assert_eq!(hygiene.external_templates, ["https://example.com/t.dotm"]);
+        assert_eq!(
+            hygiene.remote_images,
+            ["http://tracker.example/p.gif?a=1&b=2"]
+        );
+        assert_eq!(
+            hygiene.external_content,
+            ["oleObject: file:///C:/data.xlsx"]
+        );
+        assert_eq!(hygiene.tracked_changes, 2);
+        assert_eq!(hygiene.hidden_text, 1);
+        assert_eq!(hygiene.invisible_text, 2);
+        assert!(!is_invisible("auto", None, None));
+
+        let report = format_report(&hygiene, "suspicious.docm");
+        assert!(report.contains("⚠ VBA macros (1):\n    word/vbaProject.bin\n"));
+        assert!(report.contains("⚠ Unresolved tracked changes: 2\n"));
+        assert!(report.contains("⚠ Text runs colored like their background: 2\n"));
+    }
+}
@@ -0,0 +1,362 @@
+//! PNG copies of images terminals can't show as they are.
+//!
+//! Word keeps pasted screenshots and drawings from older Office versions as
+//! Windows metafiles (EMF, WMF), diagrams as SVG and scans as TIFF. SVG is
+//! rasterized with resvg and TIFF re-encoded. Metafiles are drawing
+//! commands; doxx doesn't play them back, but most of the ones in documents
+//! are a single bitmap wrapped in a few records, and that bitmap is what
+//! gets extracted. Metafiles drawn with lines and text alone have none.
+
+use once_cell::sync::Lazy;
+use std::io::Cursor;
+use std::sync::Arc;
+
+use crate::error::{DoxxError, Result};
+use crate::image_extractor::ImageFormat;
+
+/// Longest side, in pixels, SVG drawings are rasterized at
+const SVG_MAX_SIDE: f32 = 2048.0;
+
+/// Fonts for the text in SVG drawings, loaded on first use
+static FONTS: Lazy<Arc<resvg::usvg::fontdb::Database>> = Lazy::new(|| {
+    let mut fonts = resvg::usvg::fontdb::Database::new();
+    fonts.load_system_fonts();
+    Arc::new(fonts)
+});
+
+/// `data`, an image in `format`, as a PNG, or `None` when the format
+/// doesn't need converting
+pub fn to_png(format: ImageFormat, data: &[u8]) -> Result<Option<Vec<u8>>> {
+    let png = match format {
+        ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::Gif | ImageFormat::Bmp => {
+            return Ok(None)
+        }
+        ImageFormat::Tiff => encode_png(&image::load_from_memory(data)?)?,
+        ImageFormat::Svg => rasterize_svg(data)?,
+        ImageFormat::Emf => bitmap_to_png(emf_bitmap(data).ok_or_else(|| no_bitmap("EMF"))?)?,
+        ImageFormat::Wmf => bitmap_to_png(wmf_bitmap(data).ok_or_else(|| no_bitmap("WMF"))?)?,
+    };
+    Ok(Some(png))
+}
+
+fn no_bitmap(format: &str) -> DoxxError {
+    DoxxError::Image(format!("no bitmap in {format} drawing"))
+}
+
+fn encode_png(image: &image::DynamicImage) -> Result<Vec<u8>> {
+    let mut png = Vec::new();
+    image.write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)?;
+    Ok(png)
+}
+
+fn rasterize_svg(data: &[u8]) -> Result<Vec<u8>> {
+    let options = resvg::usvg::Options {
+        fontdb: Arc::clone(&FONTS),
+        ..resvg::usvg::Options::default()
+    };
+    let tree = resvg::usvg::Tree::from_data(data, &options)
+        .map_err(|e| DoxxError::Image(e.to_string()))?;
+    let size = tree.size();
+    // Small drawings are scaled up to stay sharp when zoomed, big ones down
+    let scale = (SVG_MAX_SIDE / size.width().max(size.height())).min(4.0);
+    let width = (size.width() * scale).ceil() as u32;
+    let height = (size.height() * scale).ceil() as u32;
+    let mut pixmap = resvg::tiny_skia::Pixmap::new(width, height)
+        .ok_or_else(|| DoxxError::Image("SVG drawing has no size".to_string()))?;
+    resvg::render(
+        &tree,
+        resvg::tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+    pixmap
+        .encode_png()
+        .map_err(|e| DoxxError::Image(e.to_string()))
+}
+
+/// A device-independent bitmap found in a metafile: its BITMAPINFO header
+/// (with the color table) and its pixels
+struct Bitmap<'a> {
+    info: &'a [u8],
+    bits: &'a [u8],
+}
+
+impl Bitmap<'_> {
+    fn width(&self) -> u64 {
+        match read_u32(self.info, 0) {
+            Some(12) => read_u16(self.info, 4).unwrap_or(0) as u64,
+            _ => read_u32(self.info, 4).unwrap_or(0) as u64,
+        }
+    }
+
+    fn height(&self) -> u64 {
+        match read_u32(self.info, 0) {
+            Some(12) => read_u16(self.info, 6).unwrap_or(0) as u64,
+            // Negative heights are top-down bitmaps
+            _ => (read_u32(self.info, 8).unwrap_or(0) as i32).unsigned_abs() as u64,
+        }
+    }
+
+    fn area(&self) -> u64 {
+        self.width() * self.height()
+    }
+}
+
+/// BITMAPINFOHEADER compression values for embedded JPEG and PNG images
+const BI_JPEG: u32 = 4;
+const BI_PNG: u32 = 5;
+
+fn bitmap_to_png(bitmap: Bitmap) -> Result<Vec<u8>> {
+    let header_size = read_u32(bitmap.info, 0)
+        .ok_or_else(|| DoxxError::Image("empty bitmap header".to_string()))?;
+    if header_size >= 40 {
+        if let Some(BI_JPEG | BI_PNG) = read_u32(bitmap.info, 16) {
+            return encode_png(&image::load_from_memory(bitmap.bits)?);
+        }
+    }
+
+    // A BMP file is the same header and pixels behind a BITMAPFILEHEADER
+    let offset = 14 + bitmap.info.len();
+    let mut bmp = Vec::with_capacity(offset + bitmap.bits.len());
+    bmp.extend_from_slice(b"BM");
+    bmp.extend_from_slice(&((offset + bitmap.bits.len()) as u32).to_le_bytes());
+    bmp.extend_from_slice(&[0; 4]);
+    bmp.extend_from_slice(&(offset as u32).to_le_bytes());
+    bmp.extend_from_slice(bitmap.info);
+    bmp.extend_from_slice(bitmap.bits);
+    encode_png(&image::load_from_memory_with_format(
+        &bmp,
+        image::ImageFormat::Bmp,
+    )?)
+}
+
+/// The largest bitmap an EMF file draws
+fn emf_bitmap(data: &[u8]) -> Option<Bitmap<'_>> {
+    const EMR_HEADER: u32 = 1;
+    const EMR_EOF: u32 = 14;
+    const EMR_BITBLT: u32 = 76;
+    const EMR_STRETCHBLT: u32 = 77;
+    const EMR_SETDIBITSTODEVICE: u32 = 80;
+    const EMR_STRETCHDIBITS: u32 = 81;
+
+    if read_u32(data, 0) != Some(EMR_HEADER) {
+        return None;
+    }
+    let mut largest: Option<Bitmap> = None;
+    let mut position = 0;
+    while let (Some(kind), Some(size)) = (read_u32(data, position), read_u32(data, position + 4)) {
+        let size = size as usize;
+        let Some(record) = data.get(position..position + size).filter(|_| size >= 8) else {
+            break;
+        };
+        // Where each record keeps the offsets and sizes of its bitmap
+        let fields = match kind {
+            EMR_EOF => break,
+            EMR_BITBLT | EMR_STRETCHBLT => Some(84),
+            EMR_SETDIBITSTODEVICE | EMR_STRETCHDIBITS => Some(48),
+            _ => None,
+        };
+        let bitmap = fields.and_then(|at| {
+            let slice = |offset: usize, length: usize| {
+                let offset = read_u32(record, offset)? as usize;
+                let length = read_u32(record, length)? as usize;
+                record.get(offset..offset.checked_add(length)?)
+            };
+            Some(Bitmap {
+                info: slice(at, at + 4).filter(|info| !info.is_empty())?,
+                bits: slice(at + 8, at + 12).filter(|bits| !bits.is_empty())?,
+            })
+        });
+        if let Some(bitmap) = bitmap {
+            if largest
+                .as_ref()
+                .is_none_or(|largest| bitmap.area() > largest.area())
+            {
+                largest = Some(bitmap);
+            }
+        }
+        position += size;
+    }
+    largest
+}
+
+/// The largest bitmap a WMF file draws
+fn wmf_bitmap(data: &[u8]) -> Option<Bitmap<'_>> {
+    const PLACEABLE_KEY: u32 = 0x9AC6_CDD7;
+    const META_EOF: u16 = 0x0000;
+    const META_DIBBITBLT: u16 = 0x0940;
+    const META_DIBSTRETCHBLT: u16 = 0x0B41;
+    const META_STRETCHDIB: u16 = 0x0F43;
+
+    // An optional 22-byte header with the picture's bounds comes first
+    let start = if read_u32(data, 0) == Some(PLACEABLE_KEY) {
+        22
+    } else {
+        0
+    };
+    // The header's size is counted in 16-bit words, like every record's
+    let header_words = read_u16(data, start + 2)? as usize;
+    let mut largest: Option<Bitmap> = None;
+    let mut position = start + header_words * 2;
+    while let (Some(words), Some(function)) =
+        (read_u32(data, position), read_u16(data, position + 4))
+    {
+        let size = (words as usize).checked_mul(2)?;
+        let Some(record) = data.get(position..position + size).filter(|_| size >= 6) else {
+            break;
+        };
+        // Where the packed bitmap starts, after the record's parameters
+        let dib_at = match function {
+            META_EOF => break,
+            META_DIBBITBLT => Some(22),
+            META_DIBSTRETCHBLT => Some(26),
+            META_STRETCHDIB => Some(28),
+            _ => None,
+        };
+        if let Some(bitmap) = dib_at
+            .and_then(|at| record.get(at..))
+            .and_then(split_packed_dib)
+        {
+            if largest
+                .as_ref()
+                .is_none_or(|largest| bitmap.area() > largest.area())
+            {
+                largest = Some(bitmap);
+            }
+        }
+        position += size;
+    }
+    largest
+}
+
+/// Split a packed DIB (header, color table, pixels) into header and pixels
+fn split_packed_dib(dib: &[u8]) -> Option<Bitmap<'_>> {
+    const BI_BITFIELDS: u32 = 3;
+
+    let header_size = read_u32(dib, 0)? as usize;
+    let (bit_count, colors, entry_size) = match header_size {
+        12 => (read_u16(dib, 10)?, 0, 3),
+        40.. => {
+            let used = read_u32(dib, 32)? as usize;
+            let masks = if header_size == 40 && read_u32(dib, 16)? == BI_BITFIELDS {
+                3
+            } else {
+                0
+            };
+            (read_u16(dib, 14)?, used.max(masks), 4)
+        }
+        // Not a bitmap header, e.g. a blit without a bitmap
+        _ => return None,
+    };
+    let colors = match colors {
+        0 if bit_count <= 8 => 1 << bit_count,
+        colors => colors,
+    };
+    let info_size = header_size + colors * entry_size;
+    let bits = dib.get(info_size..).filter(|bits| !bits.is_empty())?;
+    Some(Bitmap {
+        info: &dib[..info_size],
+        bits,
+    })
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        data.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 2x1 24-bit bitmap, as BITMAPINFOHEADER and pixel rows
+    fn dib() -> (Vec<u8>, Vec<u8>) {
+        let mut info = Vec::new();
+        for value in [40u32, 2, 1] {
+            info.extend_from_slice(&value.to_le_bytes());
+        }
+        info.extend_from_slice(&1u16.to_le_bytes());
+        info.extend_from_slice(&24u16.to_le_bytes());
+        info.extend_from_slice(&[0; 24]);
+        // Blue then red, padded to four bytes
+        let bits = vec![255, 0, 0, 0, 0, 255, 0, 0];
+        (info, bits)
+    }
+
+    fn record(kind: u32, body: &[u8]) -> Vec<u8> {
+        let mut record = kind.to_le_bytes().to_vec();
+        record.extend_from_slice(&((body.len() + 8) as u32).to_le_bytes());
+        record.extend_from_slice(body);
+        record
+    }
+
+    fn assert_blue_red(png: &[u8]) {
+        let image = image::load_from_memory(png).unwrap().to_rgb8();
+        assert_eq!(image.dimensions(), (2, 1));
+        assert_eq!(image.get_pixel(0, 0).0, [0, 0, 255]);
+        assert_eq!(image.get_pixel(1, 0).0, [255, 0, 0]);
+    }
+
+    #[test]
+    fn test_emf_bitmap() {
+        let (info, bits) = dib();
+        // EMR_STRETCHDIBITS: 72 bytes of fields, then the header and pixels
+        let mut body = vec![0u8; 64];
+        let info_at = 72u32;
+        let bits_at = info_at + info.len() as u32;
+        for (at, value) in [
+            (40, info_at),
+            (44, info.len() as u32),
+            (48, bits_at),
+            (52, bits.len() as u32),
+        ] {
+            body[at..at + 4].copy_from_slice(&value.to_le_bytes());
+        }
+        body.extend_from_slice(&info);
+        body.extend_from_slice(&bits);
+
+        let mut emf = record(1, &[0; 80]);
+        emf.extend(record(81, &body));
+        emf.extend(record(14, &[0; 12]));
+        assert_blue_red(&to_png(ImageFormat::Emf, &emf).unwrap().unwrap());
+
+        // Lines and text only
+        let mut vector = record(1, &[0; 80]);
+        vector.extend(record(14, &[0; 12]));
+        assert!(to_png(ImageFormat::Emf, &vector).is_err());
+    }
+
+    #[test]
+    fn test_wmf_bitmap() {
+        let (info, bits) = dib();
+        let mut wmf = vec![1, 0, 9, 0, 0, 3];
+        wmf.resize(18, 0);
+        // META_STRETCHDIB: size in words, function, 22 bytes of parameters
+        let size = (6 + 22 + info.len() + bits.len()) / 2;
+        wmf.extend_from_slice(&(size as u32).to_le_bytes());
+        wmf.extend_from_slice(&0x0F43u16.to_le_bytes());
+        wmf.extend_from_slice(&[0; 22]);
+        wmf.extend_from_slice(&info);
+        wmf.extend_from_slice(&bits);
+        wmf.extend_from_slice(&[3, 0, 0, 0, 0, 0]);
+        assert_blue_red(&to_png(ImageFormat::Wmf, &wmf).unwrap().unwrap());
+    }
+
+    #[test]
+    fn test_svg_and_unchanged_formats() {
+        let svg = br#"<svg xmlns="http://www.w3.org/2000/svg" width="20" height="10">
+            <rect width="20" height="10" fill="red"/></svg>"#;
+        let png = to_png(ImageFormat::Svg, svg).unwrap().unwrap();
+        let image = image::load_from_memory(&png).unwrap();
+        assert_eq!(image.width(), image.height() * 2);
+
+        assert!(to_png(ImageFormat::Png, b"").unwrap().is_none());
+        assert!(to_png(ImageFormat::Svg, b"<not svg").is_err());
+    }
+}
@@ -0,0 +1,627 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tempfile::TempDir;
+use zip::ZipArchive;
+
+use crate::error::Result;
+use crate::events::{Event, EventSink};
+use crate::image_convert;
+
+// Type aliases to simplify complex return types
+type ImageList<'a> = Vec<(&'a String, &'a PathBuf)>;
+type ExtractedImages = Vec<(String, PathBuf)>;
+
+/// Manages extraction of images from DOCX files
+#[derive(Debug)]
+pub struct ImageExtractor {
+    /// Directory of this extractor's own, removed once it and every handle
+    /// from [`ImageExtractor::temp_dir`] are dropped
+    temp_dir: Arc<TempDir>,
+    extracted_images: HashMap<String, PathBuf>, // relationship_id -> temp_file_path
+    /// Relationship ids pointing at each media file, by file name
+    relationships: HashMap<String, Vec<String>>,
+    /// Media file names in the order the document body first shows them
+    document_order: Vec<String>,
+}
+
+/// How [`ImageExtractor::extract_to`] writes images
+#[derive(Debug, Clone, Default)]
+pub struct ExtractOptions {
+    /// Also write `manifest.json`, describing each image written
+    pub manifest: bool,
+    /// Replace files already in the directory instead of numbering the new
+    /// ones (`image1-2.png`)
+    pub overwrite: bool,
+}
+
+/// What [`ImageExtractor::extract_to`] wrote, in document order
+#[derive(Debug, Clone, Serialize)]
+pub struct ImageManifest {
+    pub images: Vec<ManifestEntry>,
+}
+
+/// One image written by [`ImageExtractor::extract_to`]
+#[derive(Debug, Clone, Serialize)]
+pub struct ManifestEntry {
+    /// Where the image first appears in the document body, counting images
+    /// from 1; `None` for media the body doesn't show (e.g. header logos)
+    pub ordinal: Option<usize>,
+    /// Name of the media file in the package, e.g. `image3.emf`
+    pub name: String,
+    /// Name of the file written, e.g. `image3.png`, or `image3-2.png` when
+    /// the directory already had one
+    pub file: String,
+    /// Relationships in the document that refer to the image, e.g. `rId8`
+    pub relationship_ids: Vec<String>,
+    /// Size in pixels, when the image could be decoded
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// Hex SHA-256 of the file written
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Gif,
+    Bmp,
+    Tiff,
+    /// Windows enhanced metafile
+    Emf,
+    /// Windows metafile
+    Wmf,
+    Svg,
+}
+
+impl ImageFormat {
+    pub fn from_filename(filename: &str) -> Option<Self> {
+        let extension = Path::new(filename).extension()?.to_str()?.to_lowercase();
+
+        match extension.as_str() {
+            "png" => Some(Self::Png),
+            "jpg" | "jpeg" => Some(Self::Jpeg),
+            "gif" => Some(Self::Gif),
+            "bmp" => Some(Self::Bmp),
+            "tiff" | "tif" => Some(Self::Tiff),
+            "emf" => Some(Self::Emf),
+            "wmf" => Some(Self::Wmf),
+            "svg" => Some(Self::Svg),
+            _ => None,
+        }
+    }
+
+    pub fn to_extension(&self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Jpeg => "jpg",
+            Self::Gif => "gif",
+            Self::Bmp => "bmp",
+            Self::Tiff => "tiff",
+            Self::Emf => "emf",
+            Self::Wmf => "wmf",
+            Self::Svg => "svg",
+        }
+    }
+
+    /// Whether images in this format are converted to PNG on extraction, for
+    /// terminals and viewers that can't show them as they are
+    pub fn needs_conversion(&self) -> bool {
+        matches!(self, Self::Tiff | Self::Emf | Self::Wmf | Self::Svg)
+    }
+}
+
+impl ImageExtractor {
+    /// Create a new image extractor with a temporary directory of its own
+    pub fn new() -> Result<Self> {
+        let temp_dir = tempfile::Builder::new().prefix("doxx-images-").tempdir()?;
+
+        Ok(Self {
+            temp_dir: Arc::new(temp_dir),
+            extracted_images: HashMap::new(),
+            relationships: HashMap::new(),
+            document_order: Vec::new(),
+        })
+    }
+
+    /// Extract all images from a DOCX file
+    pub fn extract_images_from_docx(&mut self, docx_path: &Path) -> Result<()> {
+        self.extract_images_from_docx_with(docx_path, &EventSink::default())
+    }
+
+    /// Extract all images from a DOCX file, reporting each one to `events`
+    pub fn extract_images_from_docx_with(
+        &mut self,
+        docx_path: &Path,
+        events: &EventSink,
+    ) -> Result<()> {
+        self.extract_images_from_bytes_with(&fs::read(docx_path)?, events)
+    }
+
+    /// Extract all images from a DOCX package held in memory, reporting each
+    /// one to `events`
+    pub fn extract_images_from_bytes_with(
+        &mut self,
+        docx_data: &[u8],
+        events: &EventSink,
+    ) -> Result<()> {
+        self.extract_matching_images(docx_data, events, |_| true)
+    }
+
+    /// Extract the images of a DOCX package held in memory whose media file
+    /// name (e.g. `image1.png`) `wanted` accepts, reporting each one to
+    /// `events`
+    pub fn extract_matching_images(
+        &mut self,
+        docx_data: &[u8],
+        events: &EventSink,
+        wanted: impl Fn(&str) -> bool,
+    ) -> Result<()> {
+        let mut archive = ZipArchive::new(Cursor::new(docx_data))?;
+        let names: HashSet<String> = archive.file_names().map(str::to_string).collect();
+
+        // Look for images in the word/media/ folder
+        for i in 0..archive.len() {
+            let mut file = archive.by_index(i)?;
+            let outpath = file.name().to_string(); // Clone the name to avoid borrow issues
+
+            // Check if this is an image file in the media folder
+            let format = ImageFormat::from_filename(&outpath);
+            if let (true, Some(format)) = (outpath.starts_with("word/media/"), format) {
+                let filename = Path::new(&outpath)
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("unknown");
+                if !wanted(filename) {
+                    continue;
+                }
+
+                // Read the image data
+                let mut buffer = Vec::new();
+                file.read_to_end(&mut buffer)?;
+
+                // Formats terminals can't show are stored as PNG, next to
+                // any PNG of the same name; the original is kept when the
+                // conversion fails
+                let converted = format
+                    .needs_conversion()
+                    .then(|| image_convert::to_png(format, &buffer).ok().flatten())
+                    .flatten();
+                let stored_name = match &converted {
+                    Some(_) => png_name(filename, &names),
+                    None => filename.to_string(),
+                };
+                if let Some(png) = converted {
+                    buffer = png;
+                }
+
+                // Create a unique temp file path
+                let temp_file_path = self.temp_dir.path().join(stored_name);
+
+                // Write to temp file
+                let mut temp_file = File::create(&temp_file_path)?;
+                temp_file.write_all(&buffer)?;
+
+                // Store the mapping (we'll enhance this with proper relationship parsing later)
+                let rel_id = filename.to_string(); // Simplified for now
+                self.extracted_images.insert(rel_id, temp_file_path);
+                events.emit(Event::ImageExtracted {
+                    name: filename.to_string(),
+                });
+            }
+        }
+
+        let mut read_part = |name: &str| {
+            let mut xml = String::new();
+            archive.by_name(name).ok()?.read_to_string(&mut xml).ok()?;
+            Some(xml)
+        };
+        let rels = read_part("word/_rels/document.xml.rels").unwrap_or_default();
+        let body = read_part("word/document.xml").unwrap_or_default();
+        self.read_document_order(&rels, &body);
+        Ok(())
+    }
+
+    /// Work out which relationships point at each media file and the order
+    /// the body shows them in, from `word/_rels/document.xml.rels` and
+    /// `word/document.xml`
+    fn read_document_order(&mut self, rels: &str, body: &str) {
+        let mut targets = HashMap::new();
+        for relationship in RELATIONSHIP_PATTERN.find_iter(rels) {
+            let attributes: HashMap<&str, &str> = ATTRIBUTE_PATTERN
+                .captures_iter(relationship.as_str())
+                .map(|captures| {
+                    let (_, [name, value]) = captures.extract();
+                    (name, value)
+                })
+                .collect();
+            let (Some(id), Some(target)) = (attributes.get("Id"), attributes.get("Target")) else {
+                continue;
+            };
+            let Some(name) = Path::new(target).file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            if self.extracted_images.contains_key(name) {
+                targets.insert(id.to_string(), name.to_string());
+            }
+        }
+
+        self.relationships.clear();
+        self.document_order.clear();
+        // Pictures refer to their image with r:embed, VML shapes with r:id
+        for reference in REFERENCE_PATTERN.captures_iter(body) {
+            let id = &reference[1];
+            let Some(name) = targets.get(id) else {
+                continue;
+            };
+            let ids = self.relationships.entry(name.clone()).or_default();
+            if !ids.iter().any(|known| known == id) {
+                ids.push(id.to_string());
+            }
+            if !self.document_order.contains(name) {
+                self.document_order.push(name.clone());
+            }
+        }
+        // Media the body doesn't show still gets its relationship ids
+        for (id, name) in targets {
+            let ids = self.relationships.entry(name).or_default();
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+    }
+
+    /// Copy the extracted images from this extractor's own directory into
+    /// `dir` in document order (then by name for images the body doesn't
+    /// show), returning what was written. `dir` may hold other files, even
+    /// another document's images: names already taken get a number unless
+    /// `options.overwrite` is set, and the manifest lists only this document's.
+    pub fn extract_to(&self, dir: &Path, options: &ExtractOptions) -> Result<ImageManifest> {
+        fs::create_dir_all(dir)?;
+
+        let mut names: Vec<&String> = self.extracted_images.keys().collect();
+        names.sort_by_key(|name| {
+            let ordinal = self.document_order.iter().position(|shown| shown == *name);
+            (ordinal.is_none(), ordinal, name.to_string())
+        });
+
+        let mut taken = HashSet::new();
+        let mut images = Vec::with_capacity(names.len());
+        for name in names {
+            let stored = &self.extracted_images[name];
+            // Converted images are named for their format, e.g. image1.png
+            // for image1.emf
+            let stored_name = stored
+                .file_name()
+                .and_then(|file| file.to_str())
+                .unwrap_or(name);
+            let file = available_name(dir, stored_name, &mut taken, options.overwrite);
+            let data = fs::read(stored)?;
+            fs::write(dir.join(&file), &data)?;
+
+            let dimensions = image::ImageReader::new(Cursor::new(&data))
+                .with_guessed_format()
+                .ok()
+                .and_then(|reader| reader.into_dimensions().ok());
+            let mut relationship_ids = self.relationships.get(name).cloned().unwrap_or_default();
+            relationship_ids.sort_by_key(|id| (id.len(), id.clone()));
+            images.push(ManifestEntry {
+                ordinal: self
+                    .document_order
+                    .iter()
+                    .position(|shown| shown == name)
+                    .map(|index| index + 1),
+                name: name.clone(),
+                file,
+                relationship_ids,
+                width: dimensions.map(|(width, _)| width),
+                height: dimensions.map(|(_, height)| height),
+                sha256: Sha256::digest(&data)
+                    .iter()
+                    .map(|b| format!("{b:02x}"))
+                    .collect(),
+            });
+        }
+
+        let manifest = ImageManifest { images };
+        if options.manifest {
+            let file = available_name(dir, MANIFEST_NAME, &mut taken, options.overwrite);
+            fs::write(
+                dir.join(file),
+                serde_json::to_string_pretty(&manifest)? + "\n",
+            )?;
+        }
+        Ok(manifest)
+    }
+
+    /// Handle on the directory the images are extracted to, keeping their
+    /// paths valid after the extractor is dropped
+    pub fn temp_dir(&self) -> Arc<TempDir> {
+        Arc::clone(&self.temp_dir)
+    }
+
+    /// Get image data by relationship ID
+    pub fn get_image_data(&self, rel_id: &str) -> Result<Vec<u8>> {
+        if let Some(path) = self.extracted_images.get(rel_id) {
+            Ok(fs::read(path)?)
+        } else {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("Image not found: {rel_id}"),
+            )
+            .into())
+        }
+    }
+
+    /// Get the path to an extracted image
+    pub fn get_image_path(&self, rel_id: &str) -> Option<&PathBuf> {
+        self.extracted_images.get(rel_id)
+    }
+
+    /// List all extracted images
+    pub fn list_images(&self) -> ImageList<'_> {
+        self.extracted_images.iter().collect()
+    }
+
+    /// Get all extracted images as a vector of (rel_id, path) pairs
+    pub fn get_extracted_images(&self) -> ExtractedImages {
+        self.extracted_images
+            .iter()
+            .map(|(rel_id, path)| (rel_id.clone(), path.clone()))
+            .collect()
+    }
+
+    /// Get all extracted images sorted by filename for consistent ordering
+    pub fn get_extracted_images_sorted(&self) -> ExtractedImages {
+        let mut images: ExtractedImages = self
+            .extracted_images
+            .iter()
+            .map(|(rel_id, path)| (rel_id.clone(), path.clone()))
+            .collect();
+
+        // Sort by filename to ensure consistent ordering
+        images.sort_by(|a, b| a.0.cmp(&b.0));
+        images
+    }
+
+    /// Clean up temporary files now, before the directory is dropped
+    pub fn cleanup(&self) -> Result<()> {
+        if self.temp_dir.path().exists() {
+            fs::remove_dir_all(self.temp_dir.path())?;
+        }
+        Ok(())
+    }
+}
+
+/// Name of the manifest [`ImageExtractor::extract_to`] writes
+pub const MANIFEST_NAME: &str = "manifest.json";
+
+static RELATIONSHIP_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"<Relationship\s[^>]*>").unwrap());
+static ATTRIBUTE_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(\w+)="([^"]*)""#).unwrap());
+static REFERENCE_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"\br:(?:embed|id)="([^"]+)""#).unwrap());
+
+/// `name`, or `name` numbered (`image1-2.png`, `image1-3.png`, ...) when a
+/// file of that name is in `dir` or was already written this time
+pub(crate) fn available_name(
+    dir: &Path,
+    name: &str,
+    taken: &mut HashSet<String>,
+    overwrite: bool,
+) -> String {
+    let path = Path::new(name);
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or(name);
+    let extension = path.extension().and_then(|e| e.to_str());
+    let mut candidate = name.to_string();
+    let mut number = 1;
+    while taken.contains(&candidate) || (!overwrite && dir.join(&candidate).exists()) {
+        number += 1;
+        candidate = match extension {
+            Some(extension) => format!("{stem}-{number}.{extension}"),
+            None => format!("{stem}-{number}"),
+        };
+    }
+    taken.insert(candidate.clone());
+    candidate
+}
+
+/// Name for the PNG made from the media file `filename` in the extractor's
+/// own directory: `image1.png` for `image1.emf`, or `image1.emf.png` when the
+/// package has an `image1.png`
+fn png_name(filename: &str, package_names: &HashSet<String>) -> String {
+    let stem = Path::new(filename)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(filename);
+    if package_names.contains(&format!("word/media/{stem}.png")) {
+        format!("{filename}.png")
+    } else {
+        format!("{stem}.png")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_format_detection() {
+        assert!(matches!(
+            ImageFormat::from_filename("image.png"),
+            Some(ImageFormat::Png)
+        ));
+        assert!(matches!(
+            ImageFormat::from_filename("photo.jpg"),
+            Some(ImageFormat::Jpeg)
+        ));
+        assert!(matches!(
+            ImageFormat::from_filename("photo.jpeg"),
+            Some(ImageFormat::Jpeg)
+        ));
+        assert!(matches!(
+            ImageFormat::from_filename("animation.gif"),
+            Some(ImageFormat::Gif)
+        ));
+        assert!(matches!(
+            ImageFormat::from_filename("bitmap.bmp"),
+            Some(ImageFormat::Bmp)
+        ));
+        assert!(matches!(
+            ImageFormat::from_filename("scan.tiff"),
+            Some(ImageFormat::Tiff)
+        ));
+        assert_eq!(
+            ImageFormat::from_filename("drawing.EMF"),
+            Some(ImageFormat::Emf)
+        );
+        assert!(ImageFormat::Wmf.needs_conversion() && !ImageFormat::Png.needs_conversion());
+        assert!(ImageFormat::from_filename("document.txt").is_none());
+
+        let names = HashSet::from(["word/media/image2.png".to_string()]);
+        assert_eq!(png_name("image1.emf", &names), "image1.png");
+        assert_eq!(png_name("image2.svg", &names), "image2.svg.png");
+    }
+
+    #[test]
+    fn test_image_extractor_creation() {
+        let extractor = ImageExtractor::new().unwrap();
+        assert!(extractor.temp_dir.path().exists());
+        assert!(extractor.extracted_images.is_empty());
+
+        // Each extractor has its own directory, removed with the last handle
+        let other = ImageExtractor::new().unwrap();
+        assert_ne!(other.temp_dir.path(), extractor.temp_dir.path());
+        let dir = extractor.temp_dir();
+        let path = dir.path().to_path_buf();
+        drop(extractor);
+        assert!(path.exists());
+        drop(dir);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_converted_images_stay_with_their_extractor() {
+        let path = Path::new("../../tests/fixtures/images.docx");
+        let mut first = ImageExtractor::new().unwrap();
+        let mut second = ImageExtractor::new().unwrap();
+        first.extract_images_from_docx(path).unwrap();
+        second.extract_images_from_docx(path).unwrap();
+
+        // The PNG made from the SVG is written where only its extractor reads it
+        let png =
+            |extractor: &ImageExtractor| extractor.get_image_path("image4.svg").unwrap().clone();
+        assert_eq!(png(&first), first.temp_dir.path().join("image4.png"));
+        assert_eq!(png(&second), second.temp_dir.path().join("image4.png"));
+        let converted = fs::read(png(&first)).unwrap();
+        drop(second);
+        assert_eq!(fs::read(png(&first)).unwrap(), converted);
+    }
+
+    #[test]
+    fn test_extract_to() {
+        let mut extractor = ImageExtractor::new().unwrap();
+        extractor
+            .extract_images_from_docx(Path::new("../../tests/fixtures/images.docx"))
+            .unwrap();
+        let dir = std::env::temp_dir().join(format!("doxx-extract-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let options = ExtractOptions {
+            manifest: true,
+            ..ExtractOptions::default()
+        };
+
+        let first = extractor.extract_to(&dir, &options).unwrap();
+        assert_eq!(first.images.len(), 4);
+        let image1 = first.images.iter().find(|image| image.name == "image1.jpg");
+        let image1 = image1.unwrap();
+        assert_eq!(image1.file, "image1.jpg");
+        assert_eq!(image1.relationship_ids, ["rId7"]);
+        assert!(image1.width.is_some() && image1.sha256.len() == 64);
+        // The SVG is written as the PNG made from it
+        assert!(first.images.iter().any(|image| image.file == "image4.png"));
+        // Images in the body come first, numbered in order
+        let ordinals: Vec<usize> = first
+            .images
+            .iter()
+            .map_while(|image| image.ordinal)
+            .collect();
+        assert_eq!(ordinals, (1..=ordinals.len()).collect::<Vec<_>>());
+        assert!(dir.join(MANIFEST_NAME).exists());
+
+        // Nothing is clobbered the second time
+        let second = extractor.extract_to(&dir, &options).unwrap();
+        assert_eq!(second.images[0].file, numbered(&first.images[0].file));
+        assert_eq!(second.images[0].sha256, first.images[0].sha256);
+        assert!(dir.join("manifest-2.json").exists());
+
+        let overwrite = ExtractOptions {
+            overwrite: true,
+            ..ExtractOptions::default()
+        };
+        let third = extractor.extract_to(&dir, &overwrite).unwrap();
+        assert_eq!(third.images[0].file, first.images[0].file);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_extract_documents_sharing_image_names() {
+        // Both packages have a different word/media/image1.png
+        let fixtures = ["retro-gaming-guide.docx", "unicode_panic_test.docx"];
+        let extractors: Vec<ImageExtractor> = fixtures
+            .iter()
+            .map(|fixture| {
+                let mut extractor = ImageExtractor::new().unwrap();
+                extractor
+                    .extract_images_from_docx(&Path::new("../../tests/fixtures").join(fixture))
+                    .unwrap();
+                extractor
+            })
+            .collect();
+        let dir =
+            std::env::temp_dir().join(format!("doxx-shared-names-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let options = ExtractOptions {
+            manifest: true,
+            ..ExtractOptions::default()
+        };
+
+        for (fixture, extractor) in fixtures.iter().zip(&extractors) {
+            let data = fs::read(Path::new("../../tests/fixtures").join(fixture)).unwrap();
+            let mut archive = ZipArchive::new(Cursor::new(data)).unwrap();
+            let mut original = Vec::new();
+            archive
+                .by_name("word/media/image1.png")
+                .unwrap()
+                .read_to_end(&mut original)
+                .unwrap();
+            let sha256: String = Sha256::digest(&original)
+                .iter()
+                .map(|b| format!("{b:02x}"))
+                .collect();
+
+            let manifest = extractor.extract_to(&dir, &options).unwrap();
+            let image = manifest
+                .images
+                .iter()
+                .find(|image| image.name == "image1.png")
+                .unwrap();
+            assert_eq!(image.sha256, sha256, "{fixture}");
+            assert_eq!(fs::read(dir.join(&image.file)).unwrap(), original);
+        }
+        assert!(dir.join("image1-2.png").exists() && dir.join("manifest-2.json").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    fn numbered(file: &str) -> String {
+        let (stem, extension) = file.rsplit_once('.').unwrap();
+        format!("{stem}-2.{extension}")
+    }
+}
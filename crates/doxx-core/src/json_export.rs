@@ -0,0 +1,404 @@
+//! Versioned JSON export format.
+//!
+//! `--export json` writes a [`JsonDocument`] rather than the internal
+//! [`Document`] so the output stays stable as the parser evolves. Breaking
+//! changes bump [`SCHEMA_VERSION`]; `--json-schema` prints the matching schema.
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+use crate::document::{
+    list_item_numbers, Document, DocumentElement, Revision, TableData, TextFormatting,
+};
+
+/// Version of the format written by `--export json`
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// JSON Schema (draft 2020-12) for [`JsonDocument`]
+pub const JSON_SCHEMA: &str = include_str!("../schema/document-v2.schema.json");
+
+#[derive(Debug, Serialize)]
+pub struct JsonDocument {
+    pub schema_version: u32,
+    pub title: String,
+    pub metadata: JsonMetadata,
+    pub outline: Vec<JsonOutlineEntry>,
+    pub elements: Vec<JsonElement>,
+    /// Files referenced by elements (currently images)
+    pub assets: Vec<JsonAsset>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonMetadata {
+    pub file_path: String,
+    pub file_size: u64,
+    pub word_count: usize,
+    pub page_count: usize,
+    pub author: Option<String>,
+    pub created: Option<String>,
+    pub modified: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonOutlineEntry {
+    pub level: u8,
+    pub number: Option<String>,
+    pub title: String,
+    /// Index into `elements`
+    pub element: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JsonElement {
+    Heading {
+        level: u8,
+        number: Option<String>,
+        text: String,
+        style: Option<String>,
+        /// Level in the document, when normalizing heading levels changed it
+        #[serde(skip_serializing_if = "Option::is_none")]
+        source_level: Option<u8>,
+    },
+    Paragraph {
+        text: String,
+        style: Option<String>,
+        /// Consecutive pieces of `text` with their formatting; concatenated they equal `text`
+        spans: Vec<JsonSpan>,
+    },
+    List {
+        ordered: bool,
+        items: Vec<JsonListItem>,
+    },
+    Table {
+        title: Option<String>,
+        headers: Vec<String>,
+        /// Detected (or overridden) type of each column, e.g. `number` or `date`
+        column_types: Vec<&'static str>,
+        rows: Vec<Vec<String>>,
+    },
+    Image {
+        description: String,
+        width: Option<u32>,
+        height: Option<u32>,
+        /// Index into `assets`
+        asset: usize,
+        /// Text recognized in the image by `--ocr`
+        #[serde(skip_serializing_if = "Option::is_none")]
+        ocr_text: Option<String>,
+    },
+    PageBreak,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonSpan {
+    pub text: String,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub font_size: Option<f32>,
+    pub color: Option<String>,
+    /// Tracked change this span belongs to (`--changes markup` only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revision: Option<Revision>,
+    /// LaTeX of an equation; `text` is its Unicode approximation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latex: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonListItem {
+    pub text: String,
+    pub level: u8,
+    /// Number shown before the item; bulleted items have none
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number: Option<u32>,
+    /// Number as Word writes it (e.g. `2.1.` or `(a)`), for items in a Word list
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonAsset {
+    pub kind: String,
+    pub relationship_id: Option<String>,
+    /// Extracted file, when images were extracted (`--images`)
+    pub path: Option<String>,
+    /// Hex SHA-256 of the extracted file
+    pub sha256: Option<String>,
+    pub size: Option<u64>,
+}
+
+/// Convert a parsed document to the versioned export format
+pub fn to_json_document(document: &Document) -> JsonDocument {
+    let metadata = &document.metadata;
+    let mut outline = Vec::new();
+    let mut assets = Vec::new();
+
+    let elements = document
+        .elements
+        .iter()
+        .enumerate()
+        .map(|(index, element)| match element {
+            DocumentElement::Heading {
+                level,
+                text,
+                number,
+                style,
+                source_level,
+            } => {
+                outline.push(JsonOutlineEntry {
+                    level: *level,
+                    number: number.clone(),
+                    title: text.clone(),
+                    element: index,
+                });
+                JsonElement::Heading {
+                    level: *level,
+                    number: number.clone(),
+                    text: text.clone(),
+                    style: style.clone(),
+                    source_level: *source_level,
+                }
+            }
+            DocumentElement::Paragraph {
+                text,
+                formatting,
+                runs,
+                style,
+            } => JsonElement::Paragraph {
+                text: text.clone(),
+                style: style.clone(),
+                spans: if runs.is_empty() {
+                    vec![json_span(text, formatting, None)]
+                } else {
+                    runs.iter()
+                        .map(|run| JsonSpan {
+                            latex: run.math.clone(),
+                            ..json_span(&run.text, &run.formatting, run.revision)
+                        })
+                        .collect()
+                },
+            },
+            DocumentElement::List {
+                items,
+                ordered,
+                start,
+            } => JsonElement::List {
+                ordered: *ordered,
+                items: items
+                    .iter()
+                    .zip(list_item_numbers(items, *ordered, *start))
+                    .map(|(item, number)| JsonListItem {
+                        text: item.text.clone(),
+                        level: item.level,
+                        number,
+                        label: item.label.clone().filter(|_| number.is_some()),
+                    })
+                    .collect(),
+            },
+            DocumentElement::Table { table } => json_table(table),
+            DocumentElement::Image {
+                description,
+                width,
+                height,
+                relationship_id,
+                image_path,
+                ocr_text,
+            } => {
+                assets.push(json_asset(relationship_id, image_path.as_deref()));
+                JsonElement::Image {
+                    description: description.clone(),
+                    width: *width,
+                    height: *height,
+                    asset: assets.len() - 1,
+                    ocr_text: ocr_text.clone(),
+                }
+            }
+            DocumentElement::PageBreak => JsonElement::PageBreak,
+            DocumentElement::Quote {
+                text,
+                intense,
+                style,
+            } => {
+                let default = if *intense { "Intense Quote" } else { "Quote" };
+                styled_paragraph(text.clone(), style, default)
+            }
+            DocumentElement::CodeBlock { lines, style, .. } => {
+                styled_paragraph(lines.join("\n"), style, "HTML Preformatted")
+            }
+            DocumentElement::Caption { text, style, .. } => {
+                styled_paragraph(text.clone(), style, "Caption")
+            }
+            DocumentElement::Title { text, style } => {
+                styled_paragraph(text.clone(), style, "Title")
+            }
+            DocumentElement::Subtitle { text, style } => {
+                styled_paragraph(text.clone(), style, "Subtitle")
+            }
+            DocumentElement::Equation { latex, text } => JsonElement::Paragraph {
+                text: text.clone(),
+                style: Some("Equation".to_string()),
+                spans: vec![JsonSpan {
+                    latex: Some(latex.clone()),
+                    ..json_span(text, &TextFormatting::default(), None)
+                }],
+            },
+        })
+        .collect();
+
+    JsonDocument {
+        schema_version: SCHEMA_VERSION,
+        title: document.title.clone(),
+        metadata: JsonMetadata {
+            file_path: metadata.file_path.clone(),
+            file_size: metadata.file_size,
+            word_count: metadata.word_count,
+            page_count: metadata.page_count,
+            author: metadata.author.clone(),
+            created: metadata.created.clone(),
+            modified: metadata.modified.clone(),
+        },
+        outline,
+        elements,
+        assets,
+        warnings: document.warnings.iter().map(ToString::to_string).collect(),
+    }
+}
+
+/// Quotes, code, captions and titles are exported as paragraphs; their style
+/// name tells them apart
+fn styled_paragraph(text: String, style: &Option<String>, default_style: &str) -> JsonElement {
+    JsonElement::Paragraph {
+        spans: vec![json_span(&text, &TextFormatting::default(), None)],
+        text,
+        style: Some(style.clone().unwrap_or_else(|| default_style.to_string())),
+    }
+}
+
+fn json_span(text: &str, formatting: &TextFormatting, revision: Option<Revision>) -> JsonSpan {
+    JsonSpan {
+        text: text.to_string(),
+        bold: formatting.bold,
+        italic: formatting.italic,
+        underline: formatting.underline,
+        font_size: formatting.font_size,
+        color: formatting.color.clone(),
+        revision,
+        latex: None,
+    }
+}
+
+fn json_table(table: &TableData) -> JsonElement {
+    let cells = |row: &[crate::document::TableCell]| -> Vec<String> {
+        row.iter().map(|cell| cell.content.clone()).collect()
+    };
+    JsonElement::Table {
+        title: table.metadata.title.clone(),
+        headers: cells(&table.headers),
+        column_types: table
+            .column_types()
+            .into_iter()
+            .map(|data_type| data_type.name())
+            .collect(),
+        rows: table.rows.iter().map(|row| cells(row)).collect(),
+    }
+}
+
+fn json_asset(relationship_id: &Option<String>, path: Option<&Path>) -> JsonAsset {
+    let data = path.and_then(|path| std::fs::read(path).ok());
+    JsonAsset {
+        kind: "image".to_string(),
+        relationship_id: relationship_id.clone(),
+        path: path.map(|path| path.display().to_string()),
+        sha256: data.as_ref().map(|data| {
+            Sha256::digest(data)
+                .iter()
+                .map(|b| format!("{b:02x}"))
+                .collect()
+        }),
+        size: data.map(|data| data.len() as u64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::{DocumentMetadata, ImageOptions, TextRun};
+
+    #[test]
+    fn test_spans_and_outline() {
+        let bold = TextFormatting {
+            bold: true,
+            ..Default::default()
+        };
+        let document = Document {
+            title: "Spec".to_string(),
+            metadata: DocumentMetadata {
+                file_path: "spec.docx".to_string(),
+                file_size: 10,
+                word_count: 4,
+                page_count: 1,
+                created: None,
+                modified: None,
+                author: None,
+            },
+            elements: vec![
+                DocumentElement::Heading {
+                    level: 1,
+                    text: "Scope".to_string(),
+                    number: Some("1.".to_string()),
+                    style: Some("heading 1".to_string()),
+                    source_level: None,
+                },
+                DocumentElement::Paragraph {
+                    text: "Must not fail".to_string(),
+                    formatting: TextFormatting::default(),
+                    runs: vec![
+                        TextRun {
+                            text: "Must ".to_string(),
+                            formatting: TextFormatting::default(),
+                            ..Default::default()
+                        },
+                        TextRun {
+                            text: "not".to_string(),
+                            formatting: bold,
+                            ..Default::default()
+                        },
+                        TextRun {
+                            text: " fail".to_string(),
+                            formatting: TextFormatting::default(),
+                            ..Default::default()
+                        },
+                    ],
+                    style: None,
+                },
+            ],
+            image_options: ImageOptions::default(),
+            warnings: Vec::new(),
+            bookmarks: Default::default(),
+            pages: Default::default(),
+            hygiene: Default::default(),
+            image_dir: None,
+        };
+
+        let json = serde_json::to_value(to_json_document(&document)).unwrap();
+        assert_eq!(json["schema_version"], 2);
+        assert_eq!(json["outline"][0]["element"], 0);
+        assert_eq!(json["elements"][0]["type"], "heading");
+        assert_eq!(json["elements"][0]["style"], "heading 1");
+        assert_eq!(json["elements"][1]["spans"][1]["text"], "not");
+        assert_eq!(json["elements"][1]["spans"][1]["bold"], true);
+    }
+
+    #[test]
+    fn test_schema_is_valid_json() {
+        let schema: serde_json::Value = serde_json::from_str(JSON_SCHEMA).unwrap();
+        assert_eq!(
+            schema["properties"]["schema_version"]["const"],
+            SCHEMA_VERSION
+        );
+    }
+}
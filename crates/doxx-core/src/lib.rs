@@ -0,0 +1,70 @@
+//! doxx-core: parsing and export of .docx files
+//!
+//! This library reads Microsoft Word documents into a [`Document`] and exports
+//! them as Markdown, text, CSV or JSON, without the interactive viewer. That
+//! lives in `doxx-tui` and the `doxx` command in the `doxx` package.
+//!
+//! Documents are read from a file with [`document::load_document`], or from
+//! memory with [`Document::from_bytes`] and [`Document::from_reader`].
+//! [`export::format_document`] returns an export as a string; nothing in the
+//! parser or the `to_*` exporters writes to stdout. Both fail with a
+//! [`DoxxError`] that can be matched on, e.g. to tell an encrypted document
+//! from a damaged one.
+
+pub mod ai;
+pub mod alt_text;
+pub mod cache;
+pub mod chart;
+pub mod color;
+pub mod config;
+pub mod convert;
+pub mod diff;
+pub mod document;
+pub mod error;
+pub mod events;
+pub mod export;
+pub mod figures;
+pub mod fuzzy;
+pub mod grep;
+pub mod highlight;
+pub mod history;
+pub mod hygiene;
+pub mod image_convert;
+pub mod image_extractor;
+pub mod json_export;
+pub mod limits;
+pub mod math;
+pub mod mojibake;
+pub mod object_extractor;
+pub mod ocr;
+pub mod pages;
+pub mod report;
+pub mod rpc;
+pub mod sandbox;
+pub mod saved_report;
+pub mod state;
+pub mod store;
+pub mod tags;
+pub mod text_fold;
+pub mod text_layout;
+pub mod text_width;
+pub mod typography;
+
+/// Export format options
+#[derive(Clone)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum ExportFormat {
+    Markdown,
+    Text,
+    Csv,
+    Json,
+    /// The document as the viewer draws it, styled with ANSI escapes. Only
+    /// `doxx-tui` can render it; see `App::render_ansi`.
+    Ansi,
+}
+
+// Re-export commonly used types
+pub use document::{Document, DocumentElement};
+pub use error::DoxxError;
+pub use image_extractor::ImageExtractor;
+pub use object_extractor::ObjectExtractor;
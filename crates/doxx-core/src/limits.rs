@@ -0,0 +1,203 @@
+//! Resource limits for untrusted documents.
+//!
+//! A .docx is a zip archive, so a small file can expand to gigabytes of XML,
+//! or nest elements deep enough to exhaust the parser. [`check_package`]
+//! walks the archive before anything is parsed and stops at the first limit
+//! exceeded, naming the part and the setting that caught it.
+
+use quick_xml::events::Event;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Cursor, Read};
+use zip::ZipArchive;
+
+use crate::error::{DoxxError, Result};
+
+const MEGABYTE: u64 = 1024 * 1024;
+
+/// Largest document doxx will parse (`[limits]` in the config)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Limits {
+    /// Total size of all parts once decompressed, in megabytes
+    pub max_decompressed_mb: u64,
+    /// XML elements across all parts
+    pub max_elements: usize,
+    /// Deepest nesting of XML elements in any part
+    pub max_depth: usize,
+    /// Address space of a `--sandbox` parser process, in megabytes (0 = no limit)
+    pub sandbox_memory_mb: u64,
+    /// CPU time of a `--sandbox` parser process, in seconds (0 = no limit)
+    pub sandbox_cpu_seconds: u64,
+}
+
+impl Limits {
+    /// [`max_decompressed_mb`](Self::max_decompressed_mb) in bytes
+    pub fn max_decompressed_bytes(&self) -> u64 {
+        self.max_decompressed_mb.saturating_mul(MEGABYTE)
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_decompressed_mb: 512,
+            max_elements: 5_000_000,
+            max_depth: 256,
+            sandbox_memory_mb: 4096,
+            sandbox_cpu_seconds: 60,
+        }
+    }
+}
+
+/// Check the package in `file_data` against `limits`, decompressing each part
+/// at most up to what is left of the size budget. Data that isn't a zip
+/// archive passes, leaving the parser to report it.
+pub fn check_package(file_data: &[u8], limits: &Limits) -> Result<()> {
+    let Ok(mut archive) = ZipArchive::new(Cursor::new(file_data)) else {
+        return Ok(());
+    };
+    let mut budget = limits.max_decompressed_bytes();
+    let mut elements = 0;
+
+    for i in 0..archive.len() {
+        let entry = archive
+            .by_index(i)
+            .map_err(|e| DoxxError::CorruptArchive(format!("entry {i}: {e}")))?;
+        let name = entry.name().to_string();
+        let is_xml = name.ends_with(".xml") || name.ends_with(".rels");
+
+        // Read one byte past the budget, so going over it is seen without
+        // inflating the rest
+        let mut limited = entry.take(budget.saturating_add(1));
+        let size = if is_xml {
+            let mut xml = Vec::new();
+            limited.read_to_end(&mut xml)?;
+            if xml.len() as u64 <= budget {
+                let (count, depth) = measure_xml(&xml);
+                elements += count;
+                if depth > limits.max_depth {
+                    return Err(DoxxError::parse(
+                        name,
+                        format!(
+                            "elements nested {depth} deep, over the limit of {} (limits.max_depth)",
+                            limits.max_depth
+                        ),
+                    ));
+                }
+                if elements > limits.max_elements {
+                    return Err(DoxxError::parse(
+                        name,
+                        format!(
+                            "more than {} XML elements in the document (limits.max_elements)",
+                            limits.max_elements
+                        ),
+                    ));
+                }
+            }
+            xml.len() as u64
+        } else {
+            io::copy(&mut limited, &mut io::sink())?
+        };
+
+        if size > budget {
+            return Err(DoxxError::parse(
+                name,
+                format!(
+                    "document expands to more than {} MB (limits.max_decompressed_mb)",
+                    limits.max_decompressed_mb
+                ),
+            ));
+        }
+        budget -= size;
+    }
+    Ok(())
+}
+
+/// Number of elements in `xml` and how deep they nest. Comments, CDATA and
+/// attribute values are read as such, so markup written inside them isn't
+/// counted.
+fn measure_xml(xml: &[u8]) -> (usize, usize) {
+    let mut reader = quick_xml::Reader::from_reader(xml);
+    // Mismatched tags are the parser's to report; only the shape counts here
+    reader.config_mut().check_end_names = false;
+    let mut count = 0;
+    let mut depth = 0usize;
+    let mut deepest = 0;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(_)) => {
+                count += 1;
+                depth += 1;
+                deepest = deepest.max(depth);
+            }
+            Ok(Event::Empty(_)) => {
+                count += 1;
+                deepest = deepest.max(depth + 1);
+            }
+            Ok(Event::End(_)) => depth = depth.saturating_sub(1),
+            // XML too broken to read on is left for the parser to reject
+            Ok(Event::Eof) | Err(_) => break,
+            Ok(_) => {}
+        }
+    }
+    (count, deepest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    fn package(document_xml: &str) -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file("word/document.xml", SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(document_xml.as_bytes()).unwrap();
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_package_limits() {
+        assert_eq!(
+            measure_xml(br#"<?xml version="1.0"?><a><b x="1"/><c><d></d></c></a>"#),
+            (4, 3)
+        );
+        // Markup inside comments, CDATA and attribute values isn't counted
+        assert_eq!(
+            measure_xml(
+                br#"<!DOCTYPE a><a><!-- <b><c> --><d x="1 > 0" y='<e>'/><![CDATA[<f><g>]]></a>"#
+            ),
+            (2, 2)
+        );
+
+        let fixture = std::fs::read("../../tests/fixtures/business-report.docx").unwrap();
+        assert!(check_package(&fixture, &Limits::default()).is_ok());
+
+        // A deeply nested body
+        let deep = format!("{}{}", "<w:p>".repeat(300), "</w:p>".repeat(300));
+        let error = check_package(&package(&deep), &Limits::default()).unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("word/document.xml: elements nested 300 deep"));
+
+        // Many elements
+        let many = Limits {
+            max_elements: 100,
+            ..Limits::default()
+        };
+        let error = check_package(&package(&"<w:p/>".repeat(101)), &many).unwrap_err();
+        assert!(error.to_string().contains("limits.max_elements"));
+
+        // 2 MB of zeros compresses to a few kilobytes
+        let small = Limits {
+            max_decompressed_mb: 1,
+            ..Limits::default()
+        };
+        let bomb = package(&"0".repeat(2 * MEGABYTE as usize));
+        assert!(bomb.len() < 64 * 1024);
+        let error = check_package(&bomb, &small).unwrap_err();
+        assert!(error.to_string().contains("limits.max_decompressed_mb"));
+    }
+}
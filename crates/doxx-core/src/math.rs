@@ -0,0 +1,991 @@
+//! Equations (Office Math, OMML).
+//!
+//! docx-rs drops `m:oMath`, so equations are read from `word/document.xml`
+//! directly. Each is turned into LaTeX for Markdown export and into a
+//! Unicode approximation (`x² + √(y+1)`, `∑ᵢ₌₁ⁿ aᵢ`) for the terminal.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::BTreeMap;
+
+/// An equation found in a paragraph
+#[derive(Debug, Clone, PartialEq)]
+pub struct Equation {
+    pub latex: String,
+    /// Unicode approximation for plain text
+    pub text: String,
+    /// On a line of its own (`m:oMathPara`) rather than inside text
+    pub display: bool,
+    /// Characters of paragraph text before the equation
+    pub offset: usize,
+}
+
+/// Equation structure, as far as it matters for rendering
+#[derive(Debug, Clone, PartialEq)]
+enum Math {
+    Text(String),
+    Row(Vec<Math>),
+    Fraction(Box<Math>, Box<Math>),
+    Scripts {
+        base: Box<Math>,
+        sub: Option<Box<Math>>,
+        sup: Option<Box<Math>>,
+    },
+    Root {
+        degree: Option<Box<Math>>,
+        body: Box<Math>,
+    },
+    Delimited {
+        open: String,
+        close: String,
+        separator: String,
+        items: Vec<Math>,
+    },
+    /// Sums, products and integrals
+    Nary {
+        operator: String,
+        sub: Option<Box<Math>>,
+        sup: Option<Box<Math>>,
+        body: Box<Math>,
+    },
+    Function {
+        name: Box<Math>,
+        body: Box<Math>,
+    },
+    Accent {
+        mark: char,
+        body: Box<Math>,
+    },
+    Bar {
+        top: bool,
+        body: Box<Math>,
+    },
+    /// A limit written under (`lim` with `n→∞`) or over its base
+    Limit {
+        base: Box<Math>,
+        limit: Box<Math>,
+        under: bool,
+    },
+    Matrix(Vec<Vec<Math>>),
+    /// Equation array: one equation per line
+    Lines(Vec<Math>),
+}
+
+// --- Reading OMML --------------------------------------------------------
+
+#[derive(Debug, Default)]
+struct Element {
+    name: String,
+    attributes: String,
+    children: Vec<Node>,
+}
+
+#[derive(Debug)]
+enum Node {
+    Element(Element),
+    Text(String),
+}
+
+impl Element {
+    fn child(&self, name: &str) -> Option<&Element> {
+        self.elements().find(|element| element.name == name)
+    }
+
+    fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|node| match node {
+            Node::Element(element) => Some(element),
+            Node::Text(_) => None,
+        })
+    }
+
+    /// `m:val` of the property `name` inside the properties element `properties`
+    fn property(&self, properties: &str, name: &str) -> Option<String> {
+        let value = self.child(properties)?.child(name)?;
+        Some(attribute(&value.attributes, "m:val").unwrap_or_default())
+    }
+
+    fn text(&self) -> String {
+        let mut text = String::new();
+        for node in &self.children {
+            match node {
+                Node::Text(t) => text.push_str(t),
+                Node::Element(element) => text.push_str(&element.text()),
+            }
+        }
+        text
+    }
+}
+
+static TAG_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"<(/?)([A-Za-z_][\w:.-]*)([^>]*?)(/?)>|([^<]+)").unwrap());
+
+/// Parse an XML fragment into a tree, leniently: unmatched tags are closed
+/// where their parent closes
+fn parse_xml(xml: &str) -> Element {
+    let mut stack = vec![Element::default()];
+    for captures in TAG_PATTERN.captures_iter(xml) {
+        if let Some(text) = captures.get(5) {
+            if let Some(top) = stack.last_mut() {
+                top.children
+                    .push(Node::Text(decode_entities(text.as_str())));
+            }
+            continue;
+        }
+        let name = &captures[2];
+        if !captures[1].is_empty() {
+            // Close up to and including the matching element
+            if let Some(open) = stack.iter().rposition(|element| element.name == name) {
+                while stack.len() > open.max(1) {
+                    let element = stack.pop().expect("stack is not empty");
+                    if let Some(parent) = stack.last_mut() {
+                        parent.children.push(Node::Element(element));
+                    }
+                }
+            }
+            continue;
+        }
+        let element = Element {
+            name: name.to_string(),
+            attributes: captures[3].to_string(),
+            children: Vec::new(),
+        };
+        if captures[4].is_empty() {
+            stack.push(element);
+        } else if let Some(parent) = stack.last_mut() {
+            parent.children.push(Node::Element(element));
+        }
+    }
+    while stack.len() > 1 {
+        let element = stack.pop().expect("stack is not empty");
+        if let Some(parent) = stack.last_mut() {
+            parent.children.push(Node::Element(element));
+        }
+    }
+    stack.pop().unwrap_or_default()
+}
+
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+    let start = attributes.find(&format!("{name}=\""))? + name.len() + 2;
+    let end = attributes[start..].find('"')?;
+    Some(decode_entities(&attributes[start..start + end]))
+}
+
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Convert the children of an OMML element
+fn convert_row(element: &Element) -> Math {
+    let mut items: Vec<Math> = element.elements().filter_map(convert).collect();
+    if items.len() == 1 {
+        items.pop().expect("one item")
+    } else {
+        Math::Row(items)
+    }
+}
+
+/// The argument `name` (`m:e`, `m:num`, ...) of `element`, empty if missing
+fn argument(element: &Element, name: &str) -> Box<Math> {
+    Box::new(
+        element
+            .child(name)
+            .map(convert_row)
+            .unwrap_or(Math::Row(Vec::new())),
+    )
+}
+
+fn optional_argument(element: &Element, name: &str, hide: &str) -> Option<Box<Math>> {
+    let hidden = element
+        .child(&format!("{}Pr", element.name))
+        .and_then(|properties| properties.child(hide))
+        .is_some_and(|flag| {
+            !matches!(
+                attribute(&flag.attributes, "m:val").as_deref(),
+                Some("0" | "off" | "false")
+            )
+        });
+    let argument = argument(element, name);
+    (!hidden && !is_empty(&argument)).then_some(argument)
+}
+
+fn is_empty(math: &Math) -> bool {
+    match math {
+        Math::Text(text) => text.is_empty(),
+        Math::Row(items) => items.iter().all(is_empty),
+        _ => false,
+    }
+}
+
+fn convert(element: &Element) -> Option<Math> {
+    let name = element.name.strip_prefix("m:")?;
+    let properties = format!("{}Pr", element.name);
+    Some(match name {
+        "r" => Math::Text(
+            element
+                .elements()
+                .filter(|child| child.name == "m:t")
+                .map(Element::text)
+                .collect(),
+        ),
+        "f" => Math::Fraction(argument(element, "m:num"), argument(element, "m:den")),
+        "sSup" => Math::Scripts {
+            base: argument(element, "m:e"),
+            sub: None,
+            sup: Some(argument(element, "m:sup")),
+        },
+        "sSub" => Math::Scripts {
+            base: argument(element, "m:e"),
+            sub: Some(argument(element, "m:sub")),
+            sup: None,
+        },
+        "sSubSup" | "sPre" => Math::Scripts {
+            base: argument(element, "m:e"),
+            sub: Some(argument(element, "m:sub")),
+            sup: Some(argument(element, "m:sup")),
+        },
+        "rad" => Math::Root {
+            degree: optional_argument(element, "m:deg", "m:degHide"),
+            body: argument(element, "m:e"),
+        },
+        "d" => Math::Delimited {
+            open: element
+                .property(&properties, "m:begChr")
+                .unwrap_or_else(|| "(".to_string()),
+            close: element
+                .property(&properties, "m:endChr")
+                .unwrap_or_else(|| ")".to_string()),
+            separator: element
+                .property(&properties, "m:sepChr")
+                .unwrap_or_else(|| "|".to_string()),
+            items: element
+                .elements()
+                .filter(|child| child.name == "m:e")
+                .map(convert_row)
+                .collect(),
+        },
+        "nary" => Math::Nary {
+            operator: element
+                .property(&properties, "m:chr")
+                .unwrap_or_else(|| "∫".to_string()),
+            sub: optional_argument(element, "m:sub", "m:subHide"),
+            sup: optional_argument(element, "m:sup", "m:supHide"),
+            body: argument(element, "m:e"),
+        },
+        "func" => Math::Function {
+            name: argument(element, "m:fName"),
+            body: argument(element, "m:e"),
+        },
+        "acc" => Math::Accent {
+            mark: element
+                .property(&properties, "m:chr")
+                .and_then(|mark| mark.chars().next())
+                .unwrap_or('\u{302}'),
+            body: argument(element, "m:e"),
+        },
+        "bar" => Math::Bar {
+            top: element.property(&properties, "m:pos").as_deref() == Some("top"),
+            body: argument(element, "m:e"),
+        },
+        "limLow" | "limUpp" => Math::Limit {
+            base: argument(element, "m:e"),
+            limit: argument(element, "m:lim"),
+            under: name == "limLow",
+        },
+        "m" => Math::Matrix(
+            element
+                .elements()
+                .filter(|row| row.name == "m:mr")
+                .map(|row| {
+                    row.elements()
+                        .filter(|cell| cell.name == "m:e")
+                        .map(convert_row)
+                        .collect()
+                })
+                .collect(),
+        ),
+        "eqArr" => Math::Lines(
+            element
+                .elements()
+                .filter(|child| child.name == "m:e")
+                .map(convert_row)
+                .collect(),
+        ),
+        // Containers that only group or decorate their content
+        "box" | "borderBox" | "groupChr" | "phant" => *argument(element, "m:e"),
+        "oMath" | "e" => convert_row(element),
+        _ => return None,
+    })
+}
+
+// --- LaTeX ---------------------------------------------------------------
+
+/// LaTeX commands for symbols
+const LATEX_SYMBOLS: &[(char, &str)] = &[
+    ('α', "\\alpha"),
+    ('β', "\\beta"),
+    ('γ', "\\gamma"),
+    ('δ', "\\delta"),
+    ('ε', "\\epsilon"),
+    ('ζ', "\\zeta"),
+    ('η', "\\eta"),
+    ('θ', "\\theta"),
+    ('ι', "\\iota"),
+    ('κ', "\\kappa"),
+    ('λ', "\\lambda"),
+    ('μ', "\\mu"),
+    ('ν', "\\nu"),
+    ('ξ', "\\xi"),
+    ('π', "\\pi"),
+    ('ρ', "\\rho"),
+    ('σ', "\\sigma"),
+    ('τ', "\\tau"),
+    ('υ', "\\upsilon"),
+    ('φ', "\\phi"),
+    ('χ', "\\chi"),
+    ('ψ', "\\psi"),
+    ('ω', "\\omega"),
+    ('Γ', "\\Gamma"),
+    ('Δ', "\\Delta"),
+    ('Θ', "\\Theta"),
+    ('Λ', "\\Lambda"),
+    ('Ξ', "\\Xi"),
+    ('Π', "\\Pi"),
+    ('Σ', "\\Sigma"),
+    ('Φ', "\\Phi"),
+    ('Ψ', "\\Psi"),
+    ('Ω', "\\Omega"),
+    ('∞', "\\infty"),
+    ('±', "\\pm"),
+    ('∓', "\\mp"),
+    ('×', "\\times"),
+    ('÷', "\\div"),
+    ('·', "\\cdot"),
+    ('⋅', "\\cdot"),
+    ('≤', "\\leq"),
+    ('≥', "\\geq"),
+    ('≠', "\\neq"),
+    ('≈', "\\approx"),
+    ('≡', "\\equiv"),
+    ('∝', "\\propto"),
+    ('→', "\\to"),
+    ('←', "\\leftarrow"),
+    ('⇒', "\\Rightarrow"),
+    ('⇔', "\\Leftrightarrow"),
+    ('∈', "\\in"),
+    ('∉', "\\notin"),
+    ('⊂', "\\subset"),
+    ('⊆', "\\subseteq"),
+    ('∪', "\\cup"),
+    ('∩', "\\cap"),
+    ('∅', "\\emptyset"),
+    ('∀', "\\forall"),
+    ('∃', "\\exists"),
+    ('∂', "\\partial"),
+    ('∇', "\\nabla"),
+    ('…', "\\ldots"),
+    ('⋯', "\\cdots"),
+    ('−', "-"),
+];
+
+/// N-ary operators and their LaTeX commands
+const LATEX_OPERATORS: &[(&str, &str)] = &[
+    ("∑", "\\sum"),
+    ("∏", "\\prod"),
+    ("∐", "\\coprod"),
+    ("∫", "\\int"),
+    ("∬", "\\iint"),
+    ("∭", "\\iiint"),
+    ("∮", "\\oint"),
+    ("⋃", "\\bigcup"),
+    ("⋂", "\\bigcap"),
+];
+
+/// Function names LaTeX has commands for
+const LATEX_FUNCTIONS: &[&str] = &[
+    "sin", "cos", "tan", "cot", "sec", "csc", "arcsin", "arccos", "arctan", "sinh", "cosh", "tanh",
+    "log", "ln", "lg", "exp", "lim", "max", "min", "sup", "inf", "det", "gcd", "deg", "dim", "ker",
+    "arg", "Pr",
+];
+
+fn latex_text(text: &str) -> String {
+    let mut latex = String::new();
+    for c in text.chars() {
+        if let Some((_, command)) = LATEX_SYMBOLS.iter().find(|(symbol, _)| *symbol == c) {
+            if command.starts_with('\\') {
+                // Spaced so a following letter doesn't run into the command
+                latex.push_str(&format!(" {command} "));
+            } else {
+                latex.push_str(command);
+            }
+        } else {
+            match c {
+                '{' | '}' | '%' | '#' | '&' | '$' | '_' => {
+                    latex.push('\\');
+                    latex.push(c);
+                }
+                '\\' => latex.push_str("\\backslash "),
+                '^' => latex.push_str("\\hat{}"),
+                _ => latex.push(c),
+            }
+        }
+    }
+    latex
+}
+
+fn latex_delimiter(delimiter: &str, left: bool) -> String {
+    let command = match delimiter {
+        "" => ".",
+        "{" => "\\{",
+        "}" => "\\}",
+        "⟨" | "〈" => "\\langle",
+        "⟩" | "〉" => "\\rangle",
+        "⌈" => "\\lceil",
+        "⌉" => "\\rceil",
+        "⌊" => "\\lfloor",
+        "⌋" => "\\rfloor",
+        "‖" => "\\|",
+        other => other,
+    };
+    format!("{}{command}", if left { "\\left" } else { "\\right" })
+}
+
+fn latex_group(math: &Math) -> String {
+    format!("{{{}}}", to_latex(math))
+}
+
+fn latex_accent(mark: char) -> &'static str {
+    match mark {
+        '\u{303}' | '~' => "\\tilde",
+        '\u{307}' => "\\dot",
+        '\u{308}' => "\\ddot",
+        '\u{20D7}' | '→' => "\\vec",
+        '\u{305}' | '\u{304}' | '¯' => "\\bar",
+        '\u{30C}' => "\\check",
+        '\u{301}' => "\\acute",
+        '\u{300}' => "\\grave",
+        '\u{306}' => "\\breve",
+        _ => "\\hat",
+    }
+}
+
+/// LaTeX for `math`, with runs of spaces collapsed
+fn latex(math: &Math) -> String {
+    to_latex(math)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn to_latex(math: &Math) -> String {
+    match math {
+        Math::Text(text) => latex_text(text),
+        Math::Row(items) => items.iter().map(to_latex).collect(),
+        Math::Fraction(numerator, denominator) => {
+            format!(
+                "\\frac{}{}",
+                latex_group(numerator),
+                latex_group(denominator)
+            )
+        }
+        Math::Scripts { base, sub, sup } => {
+            let mut latex = match base.as_ref() {
+                Math::Text(text) if text.chars().count() == 1 => to_latex(base),
+                _ => latex_group(base),
+            };
+            if let Some(sub) = sub {
+                latex.push('_');
+                latex.push_str(&latex_group(sub));
+            }
+            if let Some(sup) = sup {
+                latex.push('^');
+                latex.push_str(&latex_group(sup));
+            }
+            latex
+        }
+        Math::Root { degree, body } => match degree {
+            Some(degree) => format!("\\sqrt[{}]{}", to_latex(degree), latex_group(body)),
+            None => format!("\\sqrt{}", latex_group(body)),
+        },
+        Math::Delimited {
+            open,
+            close,
+            separator,
+            items,
+        } => {
+            let separator = match separator.as_str() {
+                "|" => " \\mid ".to_string(),
+                other => latex_text(other),
+            };
+            format!(
+                "{} {} {}",
+                latex_delimiter(open, true),
+                items
+                    .iter()
+                    .map(to_latex)
+                    .collect::<Vec<_>>()
+                    .join(&separator),
+                latex_delimiter(close, false)
+            )
+        }
+        Math::Nary {
+            operator,
+            sub,
+            sup,
+            body,
+        } => {
+            let mut latex = LATEX_OPERATORS
+                .iter()
+                .find(|(symbol, _)| symbol == operator)
+                .map(|(_, command)| command.to_string())
+                .unwrap_or_else(|| latex_text(operator));
+            if let Some(sub) = sub {
+                latex.push('_');
+                latex.push_str(&latex_group(sub));
+            }
+            if let Some(sup) = sup {
+                latex.push('^');
+                latex.push_str(&latex_group(sup));
+            }
+            format!("{latex} {}", to_latex(body))
+        }
+        Math::Function { name, body } => {
+            let name = match name.as_ref() {
+                Math::Text(text) if LATEX_FUNCTIONS.contains(&text.as_str()) => {
+                    format!("\\{text}")
+                }
+                Math::Text(text) => format!("\\operatorname{{{text}}}"),
+                other => to_latex(other),
+            };
+            format!("{name} {}", to_latex(body))
+        }
+        Math::Accent { mark, body } => format!("{}{}", latex_accent(*mark), latex_group(body)),
+        Math::Bar { top, body } => format!(
+            "{}{}",
+            if *top { "\\overline" } else { "\\underline" },
+            latex_group(body)
+        ),
+        Math::Limit { base, limit, under } => match base.as_ref() {
+            Math::Text(text) if *under && LATEX_FUNCTIONS.contains(&text.as_str()) => {
+                format!("\\{text}_{}", latex_group(limit))
+            }
+            _ => format!(
+                "{}{}{}",
+                if *under { "\\underset" } else { "\\overset" },
+                latex_group(limit),
+                latex_group(base)
+            ),
+        },
+        Math::Matrix(rows) => format!(
+            "\\begin{{matrix}} {} \\end{{matrix}}",
+            rows.iter()
+                .map(|row| row.iter().map(to_latex).collect::<Vec<_>>().join(" & "))
+                .collect::<Vec<_>>()
+                .join(" \\\\ ")
+        ),
+        Math::Lines(lines) => format!(
+            "\\begin{{aligned}} {} \\end{{aligned}}",
+            lines
+                .iter()
+                .map(to_latex)
+                .collect::<Vec<_>>()
+                .join(" \\\\ ")
+        ),
+    }
+}
+
+// --- Unicode -------------------------------------------------------------
+
+const SUPERSCRIPTS: &[(char, char)] = &[
+    ('0', '⁰'),
+    ('1', '¹'),
+    ('2', '²'),
+    ('3', '³'),
+    ('4', '⁴'),
+    ('5', '⁵'),
+    ('6', '⁶'),
+    ('7', '⁷'),
+    ('8', '⁸'),
+    ('9', '⁹'),
+    ('+', '⁺'),
+    ('-', '⁻'),
+    ('−', '⁻'),
+    ('=', '⁼'),
+    ('(', '⁽'),
+    (')', '⁾'),
+    ('a', 'ᵃ'),
+    ('b', 'ᵇ'),
+    ('c', 'ᶜ'),
+    ('d', 'ᵈ'),
+    ('e', 'ᵉ'),
+    ('f', 'ᶠ'),
+    ('g', 'ᵍ'),
+    ('h', 'ʰ'),
+    ('i', 'ⁱ'),
+    ('j', 'ʲ'),
+    ('k', 'ᵏ'),
+    ('l', 'ˡ'),
+    ('m', 'ᵐ'),
+    ('n', 'ⁿ'),
+    ('o', 'ᵒ'),
+    ('p', 'ᵖ'),
+    ('r', 'ʳ'),
+    ('s', 'ˢ'),
+    ('t', 'ᵗ'),
+    ('u', 'ᵘ'),
+    ('v', 'ᵛ'),
+    ('w', 'ʷ'),
+    ('x', 'ˣ'),
+    ('y', 'ʸ'),
+    ('z', 'ᶻ'),
+];
+
+const SUBSCRIPTS: &[(char, char)] = &[
+    ('0', '₀'),
+    ('1', '₁'),
+    ('2', '₂'),
+    ('3', '₃'),
+    ('4', '₄'),
+    ('5', '₅'),
+    ('6', '₆'),
+    ('7', '₇'),
+    ('8', '₈'),
+    ('9', '₉'),
+    ('+', '₊'),
+    ('-', '₋'),
+    ('−', '₋'),
+    ('=', '₌'),
+    ('(', '₍'),
+    (')', '₎'),
+    ('a', 'ₐ'),
+    ('e', 'ₑ'),
+    ('h', 'ₕ'),
+    ('i', 'ᵢ'),
+    ('j', 'ⱼ'),
+    ('k', 'ₖ'),
+    ('l', 'ₗ'),
+    ('m', 'ₘ'),
+    ('n', 'ₙ'),
+    ('o', 'ₒ'),
+    ('p', 'ₚ'),
+    ('r', 'ᵣ'),
+    ('s', 'ₛ'),
+    ('t', 'ₜ'),
+    ('u', 'ᵤ'),
+    ('v', 'ᵥ'),
+    ('x', 'ₓ'),
+];
+
+/// `text` in superscript or subscript letters, if there are all of them
+fn script_chars(text: &str, table: &[(char, char)]) -> Option<String> {
+    text.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| table.iter().find(|(plain, _)| *plain == c).map(|(_, s)| *s))
+        .collect()
+}
+
+/// `math` as a superscript or subscript: raised or lowered characters when
+/// Unicode has them all, otherwise `^x` / `_(x+1)`
+fn unicode_script(math: &Math, table: &[(char, char)], marker: char) -> String {
+    let text = to_unicode(math);
+    match script_chars(&text, table) {
+        Some(script) if !script.is_empty() => script,
+        _ => format!("{marker}{}", parenthesize(&text)),
+    }
+}
+
+/// Wrap `text` in parentheses unless it is a single symbol or number
+fn parenthesize(text: &str) -> String {
+    let simple = text.chars().count() <= 1
+        || text.chars().all(|c| c.is_alphanumeric() || c == '.')
+        || (text.starts_with('(') && text.ends_with(')'));
+    if simple {
+        text.to_string()
+    } else {
+        format!("({text})")
+    }
+}
+
+fn to_unicode(math: &Math) -> String {
+    match math {
+        Math::Text(text) => text.clone(),
+        Math::Row(items) => items.iter().map(to_unicode).collect(),
+        Math::Fraction(numerator, denominator) => format!(
+            "{}/{}",
+            parenthesize(&to_unicode(numerator)),
+            parenthesize(&to_unicode(denominator))
+        ),
+        Math::Scripts { base, sub, sup } => {
+            let mut text = to_unicode(base);
+            if let Some(sub) = sub {
+                text.push_str(&unicode_script(sub, SUBSCRIPTS, '_'));
+            }
+            if let Some(sup) = sup {
+                text.push_str(&unicode_script(sup, SUPERSCRIPTS, '^'));
+            }
+            text
+        }
+        Math::Root { degree, body } => {
+            let sign = match degree.as_deref().map(to_unicode).as_deref() {
+                None => "√".to_string(),
+                Some("3") => "∛".to_string(),
+                Some("4") => "∜".to_string(),
+                Some(degree) => match script_chars(degree, SUPERSCRIPTS) {
+                    Some(degree) => format!("{degree}√"),
+                    None => format!("({degree})√"),
+                },
+            };
+            format!("{sign}{}", parenthesize(&to_unicode(body)))
+        }
+        Math::Delimited {
+            open,
+            close,
+            separator,
+            items,
+        } => format!(
+            "{open}{}{close}",
+            items
+                .iter()
+                .map(to_unicode)
+                .collect::<Vec<_>>()
+                .join(separator)
+        ),
+        Math::Nary {
+            operator,
+            sub,
+            sup,
+            body,
+        } => {
+            let mut text = operator.clone();
+            if let Some(sub) = sub {
+                text.push_str(&unicode_script(sub, SUBSCRIPTS, '_'));
+            }
+            if let Some(sup) = sup {
+                text.push_str(&unicode_script(sup, SUPERSCRIPTS, '^'));
+            }
+            format!("{text} {}", to_unicode(body))
+        }
+        Math::Function { name, body } => {
+            let body = to_unicode(body);
+            if body.starts_with(['(', '[', '{']) {
+                format!("{}{body}", to_unicode(name))
+            } else {
+                format!("{} {body}", to_unicode(name))
+            }
+        }
+        Math::Accent { mark, body } => {
+            let body = to_unicode(body);
+            let combining = match mark {
+                '^' => '\u{302}',
+                '~' => '\u{303}',
+                '¯' => '\u{304}',
+                '→' => '\u{20D7}',
+                mark => *mark,
+            };
+            if body.chars().count() == 1 && is_combining(combining) {
+                format!("{body}{combining}")
+            } else {
+                format!("{body}{mark}")
+            }
+        }
+        Math::Bar { top, body } => {
+            let line = if *top { '\u{305}' } else { '\u{332}' };
+            to_unicode(body).chars().flat_map(|c| [c, line]).collect()
+        }
+        Math::Limit { base, limit, under } => {
+            let table = if *under { SUBSCRIPTS } else { SUPERSCRIPTS };
+            format!(
+                "{}{}",
+                to_unicode(base),
+                unicode_script(limit, table, if *under { '_' } else { '^' })
+            )
+        }
+        Math::Matrix(rows) => format!(
+            "[{}]",
+            rows.iter()
+                .map(|row| row.iter().map(to_unicode).collect::<Vec<_>>().join(" "))
+                .collect::<Vec<_>>()
+                .join("; ")
+        ),
+        Math::Lines(lines) => lines.iter().map(to_unicode).collect::<Vec<_>>().join("; "),
+    }
+}
+
+fn is_combining(c: char) -> bool {
+    matches!(c, '\u{300}'..='\u{36F}' | '\u{20D0}'..='\u{20FF}')
+}
+
+// --- Finding equations -----------------------------------------------------
+
+static BLOCK_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"<w:t(?:\s[^>]*)?>([^<]*)</w:t>|<(/?)w:(p|tbl|sdt)\b([^>]*)>|<m:oMath(Para)?\b[^>]*>",
+    )
+    .unwrap()
+});
+
+/// Equations in the body paragraphs of `document_xml`, by top-level block
+/// (counted from 0 like [`crate::pages::scan_page_starts`]). Equations in
+/// tables and content controls are not included.
+pub fn scan_equations(document_xml: &str) -> BTreeMap<usize, Vec<Equation>> {
+    let mut equations: BTreeMap<usize, Vec<Equation>> = BTreeMap::new();
+    let mut depth = 0usize;
+    let mut block = 0;
+    let mut in_paragraph = false;
+    let mut offset = 0;
+    let mut position = 0;
+
+    while let Some(captures) = BLOCK_PATTERN.captures_at(document_xml, position) {
+        let whole = captures.get(0).expect("match");
+        position = whole.end();
+        if let Some(text) = captures.get(1) {
+            if in_paragraph && depth == 1 {
+                offset += decode_entities(text.as_str()).chars().count();
+            }
+            continue;
+        }
+        if let Some(tag) = captures.get(3) {
+            let closing = !captures[2].is_empty();
+            let self_closing = captures[4].ends_with('/');
+            let counted = tag.as_str() != "sdt";
+            if closing {
+                depth = depth.saturating_sub(1);
+                if depth == 0 && counted {
+                    block += 1;
+                    in_paragraph = false;
+                }
+            } else if self_closing {
+                if depth == 0 && counted {
+                    block += 1;
+                }
+            } else {
+                if depth == 0 {
+                    in_paragraph = tag.as_str() == "p";
+                    offset = 0;
+                }
+                depth += 1;
+            }
+            continue;
+        }
+
+        // An equation: read up to its end and convert it
+        let display = captures.get(5).is_some();
+        let close = if display {
+            "</m:oMathPara>"
+        } else {
+            "</m:oMath>"
+        };
+        let end = document_xml[position..]
+            .find(close)
+            .map_or(document_xml.len(), |end| position + end + close.len());
+        let tree = parse_xml(&document_xml[whole.start()..end]);
+        position = end;
+        if !(in_paragraph && depth == 1) {
+            continue;
+        }
+        let Some(root) = tree.elements().next() else {
+            continue;
+        };
+        // An equation paragraph may hold several equations, one per line
+        let parts: Vec<&Element> = if display {
+            root.elements()
+                .filter(|child| child.name == "m:oMath")
+                .collect()
+        } else {
+            vec![root]
+        };
+        for part in parts {
+            let math = convert_row(part);
+            equations.entry(block).or_default().push(Equation {
+                latex: latex(&math),
+                text: to_unicode(&math),
+                display,
+                offset,
+            });
+        }
+    }
+    equations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn equation(omml: &str) -> (String, String) {
+        let tree = parse_xml(omml);
+        let math = convert_row(tree.elements().next().unwrap());
+        (latex(&math), to_unicode(&math))
+    }
+
+    fn run(text: &str) -> String {
+        format!("<m:r><m:t>{text}</m:t></m:r>")
+    }
+
+    #[test]
+    fn test_fractions_scripts_and_roots() {
+        let omml = format!(
+            "<m:oMath><m:f><m:num>{}</m:num><m:den>{}</m:den></m:f>{}\
+             <m:sSup><m:e>{}</m:e><m:sup>{}</m:sup></m:sSup>{}\
+             <m:rad><m:radPr><m:degHide m:val=\"1\"/></m:radPr><m:deg/><m:e>{}</m:e></m:rad></m:oMath>",
+            run("a+b"),
+            run("2"),
+            run("="),
+            run("x"),
+            run("2"),
+            run("+"),
+            run("y+1"),
+        );
+        let (latex, text) = equation(&omml);
+        assert_eq!(latex, "\\frac{a+b}{2}=x^{2}+\\sqrt{y+1}");
+        assert_eq!(text, "(a+b)/2=x²+√(y+1)");
+    }
+
+    #[test]
+    fn test_sums_functions_and_delimiters() {
+        let omml = format!(
+            "<m:oMath><m:nary><m:naryPr><m:chr m:val=\"∑\"/></m:naryPr>\
+             <m:sub>{}</m:sub><m:sup>{}</m:sup><m:e><m:sSub><m:e>{}</m:e><m:sub>{}</m:sub></m:sSub></m:e></m:nary>\
+             {}<m:func><m:fName>{}</m:fName><m:e><m:d><m:e>{}</m:e></m:d></m:e></m:func></m:oMath>",
+            run("i=1"),
+            run("n"),
+            run("a"),
+            run("i"),
+            run("≤"),
+            run("sin"),
+            run("θ"),
+        );
+        let (latex, text) = equation(&omml);
+        assert_eq!(
+            latex,
+            "\\sum_{i=1}^{n} a_{i} \\leq \\sin \\left( \\theta \\right)"
+        );
+        assert_eq!(text, "∑ᵢ₌₁ⁿ aᵢ≤sin(θ)");
+    }
+
+    #[test]
+    fn test_scan_finds_inline_and_display_equations() {
+        let xml = format!(
+            "<w:body><w:p><w:r><w:t>Area &amp; size: </w:t></w:r><m:oMath>{}</m:oMath>\
+             <w:r><w:t> units</w:t></w:r></w:p>\
+             <w:tbl><w:tr><w:tc><w:p><m:oMath>{}</m:oMath></w:p></w:tc></w:tr></w:tbl>\
+             <w:p><m:oMathPara><m:oMath>{}</m:oMath></m:oMathPara></w:p></w:body>",
+            run("πr²"),
+            run("skipped"),
+            run("E=mc²"),
+        );
+        let equations = scan_equations(&xml);
+        assert_eq!(equations.len(), 2);
+        let inline = &equations[&0][0];
+        assert_eq!((inline.offset, inline.display), (13, false));
+        assert_eq!(inline.latex, "\\pi r²");
+        let display = &equations[&2][0];
+        assert!(display.display);
+        assert_eq!(display.text, "E=mc²");
+    }
+}
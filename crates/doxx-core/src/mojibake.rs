@@ -0,0 +1,201 @@
+//! Repair of double-encoded text ("mojibake").
+//!
+//! Text that was UTF-8, read back as Windows-1252 and saved again turns
+//! each accented letter or typographic mark into two or three Latin-1
+//! characters: `é` into `Ã©`, `•` into `â€¢`. `--fix-encoding` finds those
+//! sequences, puts the original bytes back together and decodes them as
+//! UTF-8 again, as many times as the text was mangled.
+//!
+//! Only sequences that decode to characters found in real text are
+//! replaced, so a German `ß“` or a Portuguese `Ã` on its own is left
+//! alone.
+
+use std::borrow::Cow;
+
+use crate::document::{Document, DocumentElement};
+
+/// Times a text is repaired, for text that went through the round trip
+/// more than once
+const MAX_LAYERS: usize = 3;
+
+/// Characters Windows-1252 puts at bytes 0x80–0x9F, in order. The five
+/// bytes it leaves undefined decode to the C1 control at the same place.
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8D}', 'Ž', '\u{8F}',
+    '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9D}', 'ž', 'Ÿ',
+];
+
+/// The byte `c` was decoded from by Windows-1252 (or Latin-1), if it was
+/// one of the upper half
+fn windows_1252_byte(c: char) -> Option<u8> {
+    match c as u32 {
+        0x80..=0xFF => Some(c as u8),
+        _ => WINDOWS_1252_HIGH
+            .iter()
+            .position(|high| *high == c)
+            .map(|index| 0x80 + index as u8),
+    }
+}
+
+/// Whether `c` is a character documents are written in, as opposed to
+/// what a chance byte sequence tends to decode to
+fn is_plausible(c: char) -> bool {
+    matches!(c as u32,
+        // Latin-1, Latin Extended, IPA, modifiers, combining marks, Greek,
+        // Cyrillic, Armenian, Hebrew and Arabic
+        0xA0..=0x6FF
+        // Punctuation, currency, letterlike symbols, arrows, math, boxes,
+        // shapes and dingbats
+        | 0x2000..=0x27BF
+        // CJK, kana, Hangul and full-width forms
+        | 0x3000..=0x9FFF
+        | 0xAC00..=0xD7AF
+        | 0xFF00..=0xFFEF
+        // Emoji
+        | 0x1F000..=0x1FAFF)
+}
+
+/// `text` with its double-encoded sequences decoded, once
+fn repair_once(text: &str) -> Cow<'_, str> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut repaired = String::new();
+    let mut copied = 0;
+    let mut index = 0;
+    while index < chars.len() {
+        let (start, lead) = chars[index];
+        let length = match windows_1252_byte(lead) {
+            Some(0xC2..=0xDF) => 2,
+            Some(0xE0..=0xEF) => 3,
+            Some(0xF0..=0xF4) => 4,
+            _ => 0,
+        };
+        let bytes: Option<Vec<u8>> = (length > 0)
+            .then(|| chars.get(index..index + length))
+            .flatten()
+            .and_then(|sequence| {
+                sequence
+                    .iter()
+                    .map(|(_, c)| windows_1252_byte(*c))
+                    .collect()
+            });
+        let decoded = bytes
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .filter(|decoded| decoded.chars().all(is_plausible));
+        match decoded {
+            Some(decoded) => {
+                repaired.push_str(&text[copied..start]);
+                repaired.push_str(&decoded);
+                index += length;
+                copied = chars.get(index).map_or(text.len(), |(at, _)| *at);
+            }
+            None => index += 1,
+        }
+    }
+    if repaired.is_empty() {
+        // Nothing decoded; a repair always adds at least one character
+        return Cow::Borrowed(text);
+    }
+    repaired.push_str(&text[copied..]);
+    Cow::Owned(repaired)
+}
+
+/// `text` with double-encoded characters decoded, e.g. `â€¢ cafÃ©` becomes
+/// `• café`
+pub fn fix_mojibake(text: &str) -> Cow<'_, str> {
+    let mut text = Cow::Borrowed(text);
+    for _ in 0..MAX_LAYERS {
+        match repair_once(&text) {
+            Cow::Owned(repaired) => text = Cow::Owned(repaired),
+            Cow::Borrowed(_) => break,
+        }
+    }
+    text
+}
+
+/// Repair the double-encoded text throughout `document`, returning how many
+/// pieces of text changed
+pub fn fix_document_encoding(document: &mut Document) -> usize {
+    let mut repaired = 0;
+    let mut fix = |text: &mut String| {
+        if let Cow::Owned(fixed) = fix_mojibake(text) {
+            *text = fixed;
+            repaired += 1;
+        }
+    };
+
+    fix(&mut document.title);
+    if let Some(author) = &mut document.metadata.author {
+        fix(author);
+    }
+    for element in &mut document.elements {
+        match element {
+            DocumentElement::Heading { text, .. }
+            | DocumentElement::Quote { text, .. }
+            | DocumentElement::Caption { text, .. }
+            | DocumentElement::Title { text, .. }
+            | DocumentElement::Subtitle { text, .. } => fix(text),
+            DocumentElement::Paragraph { text, runs, .. } => {
+                fix(text);
+                for run in runs.iter_mut() {
+                    fix(&mut run.text);
+                }
+            }
+            DocumentElement::List { items, .. } => {
+                for item in items {
+                    fix(&mut item.text);
+                }
+            }
+            DocumentElement::Table { table } => {
+                for cell in table
+                    .headers
+                    .iter_mut()
+                    .chain(table.rows.iter_mut().flatten())
+                {
+                    fix(&mut cell.content);
+                }
+                if let Some(title) = &mut table.metadata.title {
+                    fix(title);
+                }
+            }
+            DocumentElement::Image {
+                description,
+                ocr_text,
+                ..
+            } => {
+                fix(description);
+                if let Some(ocr_text) = ocr_text {
+                    fix(ocr_text);
+                }
+            }
+            DocumentElement::CodeBlock { lines, .. } => {
+                for line in lines {
+                    fix(line);
+                }
+            }
+            // Equations are kept as written
+            DocumentElement::Equation { .. } | DocumentElement::PageBreak => {}
+        }
+    }
+    repaired
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fix_mojibake() {
+        assert_eq!(fix_mojibake("â€¢ First item"), "• First item");
+        assert_eq!(fix_mojibake("cafÃ© â€“ â€œbienâ€\u{9D}"), "café – “bien”");
+        assert_eq!(fix_mojibake("Ð\u{9F}Ñ€Ð¸Ð²ÐµÑ‚"), "Привет");
+        assert_eq!(fix_mojibake("ðŸ˜€"), "😀");
+        assert_eq!(fix_mojibake("100Â\u{A0}km"), "100\u{A0}km");
+        // Encoded twice
+        assert_eq!(fix_mojibake("ÃƒÂ©tÃƒÂ©"), "été");
+
+        // Text that only looks suspicious is left as it is
+        for text in ["Straße“ sagte er", "São Paulo", "Ã la carte", "plain ASCII"] {
+            assert!(matches!(fix_mojibake(text), Cow::Borrowed(_)), "{text}");
+        }
+    }
+}
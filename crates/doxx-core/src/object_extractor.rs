@@ -0,0 +1,219 @@
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+
+use crate::error::{DoxxError, Result};
+use crate::image_extractor::available_name;
+use crate::limits::Limits;
+
+/// A file embedded in a DOCX: a spreadsheet, a PDF or another OLE object
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddedObject {
+    /// File name of the part, e.g. `Microsoft_Excel_Worksheet.xlsx`
+    pub name: String,
+    /// Path of the part in the package, e.g. `word/embeddings/oleObject1.bin`
+    pub part: String,
+    /// What kind of file it is, from its extension
+    pub kind: &'static str,
+    /// Uncompressed size in bytes
+    pub size: u64,
+}
+
+/// Lists and extracts the files embedded in a DOCX file (`word/embeddings/`)
+#[derive(Debug)]
+pub struct ObjectExtractor {
+    docx_path: PathBuf,
+    objects: Vec<EmbeddedObject>,
+}
+
+impl ObjectExtractor {
+    /// Read the list of embedded files in `docx_path`
+    pub fn new(docx_path: &Path) -> Result<Self> {
+        let file = File::open(docx_path)?;
+        let mut archive = ZipArchive::new(file)?;
+
+        let mut objects = Vec::new();
+        for i in 0..archive.len() {
+            let file = archive.by_index(i)?;
+            let part = file.name().to_string();
+            let Some(name) = part.strip_prefix("word/embeddings/") else {
+                continue;
+            };
+            if name.is_empty() || file.is_dir() {
+                continue;
+            }
+            objects.push(EmbeddedObject {
+                name: name.to_string(),
+                kind: object_kind(name),
+                size: file.size(),
+                part,
+            });
+        }
+        objects.sort_by(|a, b| a.part.cmp(&b.part));
+
+        Ok(Self {
+            docx_path: docx_path.to_path_buf(),
+            objects,
+        })
+    }
+
+    /// All embedded files, ordered by part name
+    pub fn list_objects(&self) -> &[EmbeddedObject] {
+        &self.objects
+    }
+
+    /// Copy every embedded file into `dir`, returning the paths written.
+    /// Files already in `dir` are kept and the new one numbered
+    /// (`data-2.xlsx`); all of them together may expand to at most
+    /// `limits.max_decompressed_mb`.
+    pub fn extract_to(&self, dir: &Path, limits: &Limits) -> Result<Vec<PathBuf>> {
+        let mut archive = ZipArchive::new(File::open(&self.docx_path)?)?;
+        fs::create_dir_all(dir)?;
+
+        let mut budget = limits.max_decompressed_bytes();
+        let mut taken = HashSet::new();
+        let mut written = Vec::new();
+        for object in &self.objects {
+            // Keep only the file name, so a crafted part name can't escape `dir`
+            let Some(file_name) = Path::new(&object.name)
+                .file_name()
+                .and_then(|name| name.to_str())
+            else {
+                continue;
+            };
+            let target = dir.join(available_name(dir, file_name, &mut taken, false));
+            // Copy one byte past the budget, so going over it is seen without
+            // inflating the rest
+            let mut entry = archive
+                .by_name(&object.part)?
+                .take(budget.saturating_add(1));
+            let mut output =
+                File::create(&target).map_err(|e| DoxxError::Io(e).in_file(&target))?;
+            let size = io::copy(&mut entry, &mut output)?;
+            if size > budget {
+                drop(output);
+                let _ = fs::remove_file(&target);
+                return Err(DoxxError::parse(
+                    object.part.clone(),
+                    format!(
+                        "embedded files expand to more than {} MB (limits.max_decompressed_mb)",
+                        limits.max_decompressed_mb
+                    ),
+                ));
+            }
+            budget -= size;
+            written.push(target);
+        }
+        Ok(written)
+    }
+}
+
+/// Friendly name for the kind of an embedded file
+fn object_kind(name: &str) -> &'static str {
+    let extension = Path::new(name)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+
+    match extension.as_str() {
+        "xlsx" | "xlsm" | "xls" => "Excel workbook",
+        "docx" | "docm" | "doc" => "Word document",
+        "pptx" | "pptm" | "ppt" => "PowerPoint presentation",
+        "vsdx" | "vsd" => "Visio drawing",
+        "pdf" => "PDF document",
+        "bin" => "OLE object",
+        "emf" | "wmf" => "Metafile preview",
+        _ => "File",
+    }
+}
+
+/// Size in bytes, KB or MB, e.g. `12.3 KB`
+fn format_size(size: u64) -> String {
+    const KB: f64 = 1024.0;
+    let size = size as f64;
+    if size < KB {
+        format!("{size} B")
+    } else if size < KB * KB {
+        format!("{:.1} KB", size / KB)
+    } else {
+        format!("{:.1} MB", size / (KB * KB))
+    }
+}
+
+/// One line per embedded file: number, name, kind and size
+pub fn format_object_list(objects: &[EmbeddedObject]) -> String {
+    let mut list = String::new();
+    for (index, object) in objects.iter().enumerate() {
+        list.push_str(&format!(
+            "{}. {} ({}, {})\n",
+            index + 1,
+            object.name,
+            object.kind,
+            format_size(object.size),
+        ));
+    }
+    list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_object_kind_and_listing() {
+        assert_eq!(
+            object_kind("Microsoft_Excel_Worksheet.xlsx"),
+            "Excel workbook"
+        );
+        assert_eq!(object_kind("oleObject1.BIN"), "OLE object");
+        assert_eq!(object_kind("notes"), "File");
+
+        let objects = [
+            EmbeddedObject {
+                name: "report.pdf".to_string(),
+                part: "word/embeddings/report.pdf".to_string(),
+                kind: "PDF document",
+                size: 200,
+            },
+            EmbeddedObject {
+                name: "data.xlsx".to_string(),
+                part: "word/embeddings/data.xlsx".to_string(),
+                kind: "Excel workbook",
+                size: 3 * 1024 * 1024 / 2,
+            },
+        ];
+        assert_eq!(
+            format_object_list(&objects),
+            "1. report.pdf (PDF document, 200 B)\n2. data.xlsx (Excel workbook, 1.5 MB)\n"
+        );
+    }
+
+    #[test]
+    fn test_extract_to() {
+        let extractor =
+            ObjectExtractor::new(Path::new("../../tests/fixtures/attachments.docx")).unwrap();
+        let dir = std::env::temp_dir().join(format!("doxx-objects-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let first = extractor.extract_to(&dir, &Limits::default()).unwrap();
+        assert!(!first.is_empty());
+        assert_eq!(first.len(), extractor.list_objects().len());
+        // Nothing is clobbered the second time
+        let second = extractor.extract_to(&dir, &Limits::default()).unwrap();
+        for (first, second) in first.iter().zip(&second) {
+            assert_ne!(first, second);
+            assert_eq!(fs::read(first).unwrap(), fs::read(second).unwrap());
+        }
+
+        let tight = Limits {
+            max_decompressed_mb: 0,
+            ..Limits::default()
+        };
+        let error = extractor.extract_to(&dir, &tight).unwrap_err();
+        assert!(error.to_string().contains("limits.max_decompressed_mb"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
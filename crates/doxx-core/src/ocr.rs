@@ -0,0 +1,292 @@
+//! Text recognition in images, with tesseract.
+//!
+//! `--ocr` runs the `tesseract` command over each extracted image and keeps
+//! what it reads on the image element, so screenshots of text can be found
+//! by search and show up in text exports. Nothing is linked in: without
+//! tesseract on the `PATH`, `--ocr` fails with a message saying so.
+//!
+//! `--scanned` goes further for documents that are nothing but page scans:
+//! each page image is replaced by the paragraphs tesseract finds on it,
+//! with lines set in larger type than the body taken as headings.
+
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::document::{Document, DocumentElement, TextFormatting};
+use crate::error::{DoxxError, Result};
+
+/// How images are read
+#[derive(Debug, Clone)]
+pub struct OcrOptions {
+    /// The tesseract executable
+    pub program: PathBuf,
+    /// Tesseract language codes, e.g. `eng` or `eng+deu` (default: tesseract's own)
+    pub language: Option<String>,
+}
+
+impl Default for OcrOptions {
+    fn default() -> Self {
+        Self {
+            program: PathBuf::from("tesseract"),
+            language: None,
+        }
+    }
+}
+
+/// Text tesseract reads in the image at `path`, with blank lines and
+/// surrounding whitespace trimmed
+pub fn recognize(path: &Path, options: &OcrOptions) -> Result<String> {
+    let text = run_tesseract(path, options, None)?;
+    Ok(text
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// Tesseract's output for the image at `path`, in the output format named
+/// by `config` (e.g. `tsv`) or as plain text
+fn run_tesseract(path: &Path, options: &OcrOptions, config: Option<&str>) -> Result<String> {
+    let mut command = Command::new(&options.program);
+    command.arg(path).arg("stdout");
+    if let Some(language) = &options.language {
+        command.arg("-l").arg(language);
+    }
+    command.args(config);
+    let output = command.output().map_err(|e| running_failed(options, e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(DoxxError::Ocr(
+            stderr
+                .lines()
+                .rfind(|line| !line.trim().is_empty())
+                .unwrap_or("tesseract failed")
+                .trim()
+                .to_string(),
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Read every extracted image in `document`, storing the text on its
+/// element. Images tesseract can't read are left without text and
+/// returned as warnings.
+pub fn recognize_images(document: &mut Document, options: &OcrOptions) -> Vec<String> {
+    let mut warnings = Vec::new();
+    for element in &mut document.elements {
+        let DocumentElement::Image {
+            description,
+            image_path: Some(path),
+            ocr_text,
+            ..
+        } = element
+        else {
+            continue;
+        };
+        match recognize(path, options) {
+            Ok(text) => *ocr_text = Some(text).filter(|text| !text.is_empty()),
+            Err(e) => warnings.push(format!("{description}: text recognition failed: {e:#}")),
+        }
+    }
+    warnings
+}
+
+/// Replace every extracted image in `document` with the text tesseract
+/// reads on it, laid out as headings and paragraphs. Images with no text
+/// stay as they are; images tesseract can't read are returned as warnings.
+pub fn read_scanned_pages(document: &mut Document, options: &OcrOptions) -> Vec<String> {
+    let mut warnings = Vec::new();
+    // Back to front, so replacing an image leaves the indices before it alone
+    for index in (0..document.elements.len()).rev() {
+        let DocumentElement::Image {
+            description,
+            image_path: Some(path),
+            ..
+        } = &document.elements[index]
+        else {
+            continue;
+        };
+        let elements = match run_tesseract(path, options, Some("tsv")) {
+            Ok(tsv) => page_elements(&tsv),
+            Err(e) => {
+                warnings.push(format!("{description}: text recognition failed: {e:#}"));
+                continue;
+            }
+        };
+        if elements.is_empty() {
+            continue;
+        }
+        document.metadata.word_count += elements.iter().map(|e| e.word_count()).sum::<usize>();
+        document.pages.expand(index, elements.len());
+        document.elements.splice(index..=index, elements);
+    }
+    warnings.reverse();
+    warnings
+}
+
+/// A paragraph tesseract found, from its `tsv` output
+struct OcrParagraph<'a> {
+    lines: Vec<String>,
+    /// Tesseract's number for the last line, to tell when a new one starts
+    line_number: &'a str,
+    /// Height of the tallest word, in pixels
+    height: u32,
+}
+
+/// Headings and paragraphs from tesseract's `tsv` output for one page.
+/// Paragraphs are tesseract's own; a short one-line paragraph in type at
+/// least a third taller than the page's typical word is a heading.
+fn page_elements(tsv: &str) -> Vec<DocumentElement> {
+    let mut paragraphs: Vec<OcrParagraph> = Vec::new();
+    let mut current = None;
+    let mut heights = Vec::new();
+    // Columns: level page block par line word left top width height conf text
+    for row in tsv.lines().skip(1) {
+        let fields: Vec<&str> = row.split('\t').collect();
+        let [level, _, block, par, line, _, _, _, _, height, _, text] = fields[..] else {
+            continue;
+        };
+        let text = text.trim();
+        // Level 5 rows are words; the others outline the blocks around them
+        if level != "5" || text.is_empty() {
+            continue;
+        }
+        let height: u32 = height.parse().unwrap_or(0);
+        heights.push(height);
+
+        if current != Some((block, par)) {
+            current = Some((block, par));
+            paragraphs.push(OcrParagraph {
+                lines: Vec::new(),
+                line_number: line,
+                height: 0,
+            });
+        }
+        let paragraph = paragraphs.last_mut().expect("a paragraph was started");
+        paragraph.height = paragraph.height.max(height);
+        match paragraph.lines.last_mut() {
+            Some(last) if paragraph.line_number == line => {
+                last.push(' ');
+                last.push_str(text);
+            }
+            _ => {
+                paragraph.lines.push(text.to_string());
+                paragraph.line_number = line;
+            }
+        }
+    }
+
+    heights.sort_unstable();
+    let body_height = heights.get(heights.len() / 2).copied().unwrap_or(0).max(1);
+    paragraphs
+        .into_iter()
+        .map(|paragraph| {
+            let text = join_lines(&paragraph.lines);
+            let short = paragraph.lines.len() == 1 && text.split_whitespace().count() <= 12;
+            let scale = paragraph.height as f32 / body_height as f32;
+            if short && scale >= 4.0 / 3.0 {
+                DocumentElement::Heading {
+                    level: if scale >= 1.8 { 1 } else { 2 },
+                    text,
+                    number: None,
+                    style: None,
+                    source_level: None,
+                }
+            } else {
+                DocumentElement::Paragraph {
+                    text,
+                    formatting: TextFormatting::default(),
+                    runs: Vec::new(),
+                    style: None,
+                }
+            }
+        })
+        .collect()
+}
+
+/// The lines of a scanned paragraph as one line of text, with words
+/// hyphenated across line ends joined up again
+fn join_lines(lines: &[String]) -> String {
+    let mut text = String::new();
+    for line in lines {
+        let continues_word = text.ends_with('-')
+            && text[..text.len() - 1].ends_with(char::is_alphabetic)
+            && line.starts_with(char::is_lowercase);
+        if continues_word {
+            text.pop();
+        } else if !text.is_empty() {
+            text.push(' ');
+        }
+        text.push_str(line);
+    }
+    text
+}
+
+/// Fail with a helpful message when tesseract can't be run, before any
+/// document is parsed for it
+pub fn check_available(options: &OcrOptions) -> Result<()> {
+    match Command::new(&options.program).arg("--version").output() {
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == ErrorKind::NotFound => Err(DoxxError::Ocr(format!(
+            "{} not found; install tesseract to use --ocr",
+            options.program.display()
+        ))),
+        Err(e) => Err(running_failed(options, e)),
+    }
+}
+
+fn running_failed(options: &OcrOptions, error: std::io::Error) -> DoxxError {
+    DoxxError::Ocr(format!("running {}: {error}", options.program.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_tesseract() {
+        let options = OcrOptions {
+            program: PathBuf::from("doxx-no-such-tesseract"),
+            language: None,
+        };
+        let error = check_available(&options).unwrap_err().to_string();
+        assert!(error.contains("install tesseract"), "{error}");
+        assert!(recognize(Path::new("image.png"), &options).is_err());
+    }
+
+    #[test]
+    fn test_scanned_page_layout() {
+        let rows = [
+            "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext",
+            "1\t1\t0\t0\t0\t0\t0\t0\t800\t1000\t-1\t",
+            "5\t1\t1\t1\t1\t1\t10\t10\t100\t40\t96\tAnnual",
+            "5\t1\t1\t1\t1\t2\t120\t10\t100\t40\t96\tReport",
+            "5\t1\t2\t1\t1\t1\t10\t60\t50\t20\t91\tSales",
+            "5\t1\t2\t1\t1\t2\t70\t60\t50\t20\t93\tgrew",
+            "5\t1\t2\t1\t1\t3\t130\t60\t50\t20\t90\tev-",
+            "5\t1\t2\t1\t2\t1\t10\t85\t50\t20\t92\terywhere",
+            "5\t1\t2\t1\t2\t2\t70\t85\t50\t20\t92\tin",
+            "5\t1\t2\t1\t3\t1\t10\t110\t50\t20\t92\tNorth-East.",
+            "5\t1\t2\t2\t1\t1\t10\t150\t50\t20\t95\tCosts",
+            "5\t1\t2\t2\t1\t2\t70\t150\t50\t20\t95\tfell.",
+        ];
+        let elements = page_elements(&rows.join("\n"));
+        assert_eq!(elements.len(), 3);
+        assert!(matches!(
+            &elements[0],
+            DocumentElement::Heading { level: 1, text, .. } if text == "Annual Report"
+        ));
+        assert!(matches!(
+            &elements[1],
+            DocumentElement::Paragraph { text, .. } if text == "Sales grew everywhere in North-East."
+        ));
+        assert!(matches!(
+            &elements[2],
+            DocumentElement::Paragraph { text, .. } if text == "Costs fell."
+        ));
+        assert!(page_elements("").is_empty());
+    }
+}
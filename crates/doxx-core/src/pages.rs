@@ -0,0 +1,626 @@
+//! Page mapping.
+//!
+//! A .docx has no fixed pages, but Word records where its last layout broke
+//! them (`w:lastRenderedPageBreak`) and how many there were (`docProps/app.xml`),
+//! and section breaks (`w:sectPr`) start new pages of a given size. The map
+//! uses the best of those that is available to place every element on a page,
+//! falling back to an estimate from the page size and margins.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::ops::{Range, RangeInclusive};
+
+use crate::document::{Document, DocumentElement};
+use crate::error::{DoxxError, Result};
+
+/// Words on a typical page (Letter, 1 inch margins), for documents that
+/// carry no page information
+const WORDS_PER_PAGE: usize = 250;
+
+/// Twips (1/20 point) per inch, the unit of page sizes and margins
+const TWIPS_PER_INCH: u32 = 1440;
+
+/// Where page boundaries come from, best first
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum PageSource {
+    /// Page breaks from Word's last layout and hard page breaks
+    Rendered,
+    /// Word's page count, spread over the elements by word count
+    WordCount,
+    /// Guessed from the word count, or a configured number of elements per page
+    #[default]
+    Estimated,
+}
+
+/// How a section begins (`w:type` of its `w:sectPr`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum SectionBreak {
+    #[default]
+    NextPage,
+    Continuous,
+    NextColumn,
+    EvenPage,
+    OddPage,
+}
+
+impl SectionBreak {
+    fn from_val(val: &str) -> Self {
+        match val {
+            "continuous" => Self::Continuous,
+            "nextColumn" => Self::NextColumn,
+            "evenPage" => Self::EvenPage,
+            "oddPage" => Self::OddPage,
+            _ => Self::NextPage,
+        }
+    }
+}
+
+/// Page margins in twips
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Margins {
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+    pub left: u32,
+}
+
+/// Page setup of one section of the document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Section {
+    /// First top-level block of the section (counted from 0)
+    pub first_block: usize,
+    pub break_type: SectionBreak,
+    /// Page width and height in twips
+    pub page_width: u32,
+    pub page_height: u32,
+    pub landscape: bool,
+    pub margins: Margins,
+}
+
+impl Default for Section {
+    /// Word's defaults: US Letter, portrait, 1 inch margins
+    fn default() -> Self {
+        Self {
+            first_block: 0,
+            break_type: SectionBreak::default(),
+            page_width: 12240,
+            page_height: 15840,
+            landscape: false,
+            margins: Margins {
+                top: TWIPS_PER_INCH,
+                right: TWIPS_PER_INCH,
+                bottom: TWIPS_PER_INCH,
+                left: TWIPS_PER_INCH,
+            },
+        }
+    }
+}
+
+impl Section {
+    /// The section starts on a new page rather than continuing the last one
+    pub fn starts_page(&self) -> bool {
+        !matches!(
+            self.break_type,
+            SectionBreak::Continuous | SectionBreak::NextColumn
+        )
+    }
+
+    /// Words that fit on a page of this section, scaled from a typical
+    /// Letter page by the area inside the margins
+    pub fn words_per_page(&self) -> usize {
+        let area = |width: u32, height: u32| u64::from(width) * u64::from(height);
+        let text_area = area(
+            self.page_width
+                .saturating_sub(self.margins.left + self.margins.right),
+            self.page_height
+                .saturating_sub(self.margins.top + self.margins.bottom),
+        );
+        let letter = area(TWIPS_PER_INCH * 13 / 2, TWIPS_PER_INCH * 9);
+        ((WORDS_PER_PAGE as u64 * text_area / letter) as usize).max(WORDS_PER_PAGE / 5)
+    }
+}
+
+/// The page each element starts on
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PageMap {
+    /// 1-based page of each element
+    pages: Vec<usize>,
+    pub count: usize,
+    pub source: PageSource,
+    /// Page setup of each section, in document order
+    pub sections: Vec<Section>,
+}
+
+impl PageMap {
+    /// Place `elements` on pages. `element_pages` holds the page of each
+    /// element according to the document's own page breaks; `word_pages` is
+    /// the page count Word saved; a non-zero `elements_per_page` replaces the
+    /// word count estimate when neither is available. The estimate fits the
+    /// page size of the document's main (last) section.
+    pub fn build(
+        elements: &[DocumentElement],
+        element_pages: Vec<usize>,
+        word_pages: Option<usize>,
+        elements_per_page: usize,
+        sections: Vec<Section>,
+    ) -> Self {
+        let mut map = Self::place(
+            elements,
+            element_pages,
+            word_pages,
+            elements_per_page,
+            &sections,
+        );
+        map.sections = sections;
+        map
+    }
+
+    fn place(
+        elements: &[DocumentElement],
+        element_pages: Vec<usize>,
+        word_pages: Option<usize>,
+        elements_per_page: usize,
+        sections: &[Section],
+    ) -> Self {
+        let rendered = element_pages.last().copied().unwrap_or(1);
+        if rendered > 1 && element_pages.len() == elements.len() {
+            return Self {
+                count: rendered.max(word_pages.unwrap_or(0)),
+                pages: element_pages,
+                source: PageSource::Rendered,
+                ..Self::default()
+            };
+        }
+
+        if let Some(count) = word_pages.filter(|count| *count > 0) {
+            return Self::spread_by_words(elements, count, PageSource::WordCount);
+        }
+        if elements_per_page > 0 {
+            let pages: Vec<usize> = (0..elements.len())
+                .map(|index| index / elements_per_page + 1)
+                .collect();
+            return Self {
+                count: pages.last().copied().unwrap_or(1),
+                pages,
+                source: PageSource::Estimated,
+                ..Self::default()
+            };
+        }
+
+        let words: usize = elements.iter().map(element_word_count).sum();
+        let words_per_page = sections
+            .last()
+            .map_or(WORDS_PER_PAGE, Section::words_per_page);
+        Self::spread_by_words(
+            elements,
+            words.div_ceil(words_per_page).max(1),
+            PageSource::Estimated,
+        )
+    }
+
+    /// Split `count` pages over the elements in proportion to their words
+    fn spread_by_words(elements: &[DocumentElement], count: usize, source: PageSource) -> Self {
+        let total: usize = elements.iter().map(element_word_count).sum();
+        let mut before = 0;
+        let pages = elements
+            .iter()
+            .map(|element| {
+                let page = (before * count)
+                    .checked_div(total)
+                    .map_or(1, |page| (page + 1).min(count));
+                before += element_word_count(element);
+                page
+            })
+            .collect();
+        Self {
+            pages,
+            count,
+            source,
+            ..Self::default()
+        }
+    }
+
+    /// Page that `element` starts on
+    pub fn page_of(&self, element: usize) -> usize {
+        self.pages
+            .get(element)
+            .or(self.pages.last())
+            .copied()
+            .unwrap_or(1)
+    }
+
+    /// First element on `page` or, when an element from an earlier page runs
+    /// across it, the next one to start
+    pub fn first_element(&self, page: usize) -> Option<usize> {
+        if page == 0 || page > self.count {
+            return None;
+        }
+        self.pages
+            .iter()
+            .position(|p| *p >= page)
+            .or(self.pages.len().checked_sub(1))
+    }
+
+    /// Elements starting on any of `pages`
+    pub fn elements_on(&self, pages: &RangeInclusive<usize>) -> Range<usize> {
+        let start = self
+            .pages
+            .iter()
+            .position(|p| p >= pages.start())
+            .unwrap_or(self.pages.len());
+        let end = self
+            .pages
+            .iter()
+            .position(|p| p > pages.end())
+            .unwrap_or(self.pages.len());
+        start..end.max(start)
+    }
+
+    /// Keep the pages of `range` of the elements, as when they are cut out
+    pub fn retain(&mut self, range: Range<usize>) {
+        self.pages = self.pages[range].to_vec();
+    }
+
+    /// Put the `count` elements replacing the one at `index` on its page
+    pub fn expand(&mut self, index: usize, count: usize) {
+        if let Some(&page) = self.pages.get(index) {
+            self.pages
+                .splice(index..=index, std::iter::repeat_n(page, count));
+        }
+    }
+}
+
+fn element_word_count(element: &DocumentElement) -> usize {
+    match element {
+        DocumentElement::Heading { text, .. } | DocumentElement::Paragraph { text, .. } => {
+            text.split_whitespace().count()
+        }
+        DocumentElement::List { items, .. } => items
+            .iter()
+            .map(|item| item.text.split_whitespace().count())
+            .sum(),
+        DocumentElement::Table { table } => table
+            .headers
+            .iter()
+            .chain(table.rows.iter().flatten())
+            .map(|cell| cell.content.split_whitespace().count())
+            .sum(),
+        DocumentElement::Quote { text, .. }
+        | DocumentElement::Caption { text, .. }
+        | DocumentElement::Title { text, .. }
+        | DocumentElement::Subtitle { text, .. }
+        | DocumentElement::Equation { text, .. } => text.split_whitespace().count(),
+        DocumentElement::CodeBlock { lines, .. } => lines
+            .iter()
+            .map(|line| line.split_whitespace().count())
+            .sum(),
+        DocumentElement::Image { .. } | DocumentElement::PageBreak => 0,
+    }
+}
+
+static PAGE_MARKUP_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"<(/?)w:(p|tbl|sdt|t|br|lastRenderedPageBreak|pageBreakBefore)\b([^>]*)>").unwrap()
+});
+
+/// Top-level blocks (body paragraphs and tables, counted from 0 in document
+/// order) that start a new page in `document_xml`. A break before any text
+/// of its paragraph starts the page there; one after text starts it at the
+/// next block.
+pub fn scan_page_starts(document_xml: &str) -> BTreeSet<usize> {
+    let mut starts = BTreeSet::new();
+    let mut depth = 0usize;
+    let mut block = 0;
+    let mut in_block = false;
+    let mut seen_text = false;
+
+    for captures in PAGE_MARKUP_PATTERN.captures_iter(document_xml) {
+        let closing = !captures[1].is_empty();
+        let attributes = &captures[3];
+        let self_closing = attributes.ends_with('/');
+        match &captures[2] {
+            "p" | "tbl" | "sdt" => {
+                let counted = &captures[2] != "sdt";
+                if closing {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 && in_block {
+                        block += 1;
+                        in_block = false;
+                    }
+                } else if self_closing {
+                    if depth == 0 && counted {
+                        block += 1;
+                    }
+                } else {
+                    if depth == 0 && counted {
+                        in_block = true;
+                        seen_text = false;
+                    }
+                    depth += 1;
+                }
+            }
+            "t" if !closing => seen_text = true,
+            "br" if !attributes.contains(r#"w:type="page""#) => {}
+            "br" | "lastRenderedPageBreak" if in_block && !closing => {
+                starts.insert(if seen_text { block + 1 } else { block });
+            }
+            "pageBreakBefore" if in_block && !closing => {
+                let off = ["\"0\"", "\"false\"", "\"off\""]
+                    .iter()
+                    .any(|value| attributes.contains(value));
+                if !off {
+                    starts.insert(block);
+                }
+            }
+            _ => {}
+        }
+    }
+    starts
+}
+
+static SECTION_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"<(/?)w:(p|tbl|sdt)\b([^>]*)>|<w:sectPr\b([^>]*)>").unwrap());
+static SECTION_TYPE_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"<w:type\b[^>]*\bw:val="(\w+)""#).unwrap());
+static PAGE_SIZE_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"<w:pgSz\b([^>]*)>").unwrap());
+static PAGE_MARGIN_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"<w:pgMar\b([^>]*)>").unwrap());
+
+/// Value of the twips attribute `name` (e.g. `w:w="12240"`) in `attributes`
+fn twips(attributes: &str, name: &str) -> Option<u32> {
+    let start = attributes.find(&format!(" {name}=\""))? + name.len() + 3;
+    let end = attributes[start..].find('"')?;
+    // Measurements may be written with a fraction, or negative for margins
+    attributes[start..start + end]
+        .parse::<f64>()
+        .ok()
+        .map(|value| value.max(0.0) as u32)
+}
+
+/// Page setup from the content of a `w:sectPr` element
+fn parse_section(content: &str, first_block: usize) -> Section {
+    let mut section = Section {
+        first_block,
+        ..Section::default()
+    };
+    if let Some(captures) = SECTION_TYPE_PATTERN.captures(content) {
+        section.break_type = SectionBreak::from_val(&captures[1]);
+    }
+    if let Some(captures) = PAGE_SIZE_PATTERN.captures(content) {
+        let attributes = &captures[1];
+        section.page_width = twips(attributes, "w:w").unwrap_or(section.page_width);
+        section.page_height = twips(attributes, "w:h").unwrap_or(section.page_height);
+        section.landscape = attributes.contains(r#"w:orient="landscape""#)
+            || section.page_width > section.page_height;
+    }
+    if let Some(captures) = PAGE_MARGIN_PATTERN.captures(content) {
+        let attributes = &captures[1];
+        let margins = &mut section.margins;
+        margins.top = twips(attributes, "w:top").unwrap_or(margins.top);
+        margins.right = twips(attributes, "w:right").unwrap_or(margins.right);
+        margins.bottom = twips(attributes, "w:bottom").unwrap_or(margins.bottom);
+        margins.left = twips(attributes, "w:left").unwrap_or(margins.left);
+    }
+    section
+}
+
+/// Sections of `document_xml` in order. Each `w:sectPr` in a paragraph ends a
+/// section with that paragraph; the one at the end of the body describes the
+/// last section.
+pub fn scan_sections(document_xml: &str) -> Vec<Section> {
+    let mut sections = Vec::new();
+    let mut depth = 0usize;
+    let mut block = 0;
+    let mut first_block = 0;
+    // End of the last `w:sectPr` read, to skip the old settings kept inside
+    // a tracked change (`w:sectPrChange`)
+    let mut read_up_to = 0;
+
+    for captures in SECTION_PATTERN.captures_iter(document_xml) {
+        let whole = captures.get(0).expect("match");
+        if let Some(attributes) = captures.get(4) {
+            if whole.start() < read_up_to || depth > 1 {
+                continue;
+            }
+            let content = if attributes.as_str().ends_with('/') {
+                ""
+            } else {
+                let rest = &document_xml[whole.end()..];
+                let end = rest.find("</w:sectPr>").unwrap_or(rest.len());
+                read_up_to = whole.end() + end;
+                &rest[..end]
+            };
+            sections.push(parse_section(content, first_block));
+            // In a paragraph the section ends with it; at the body's end it's the last
+            first_block = block + 1;
+            continue;
+        }
+        let counted = &captures[2] != "sdt";
+        if !captures[1].is_empty() {
+            depth = depth.saturating_sub(1);
+            if depth == 0 && counted {
+                block += 1;
+            }
+        } else if captures[3].ends_with('/') {
+            if depth == 0 && counted {
+                block += 1;
+            }
+        } else {
+            depth += 1;
+        }
+    }
+    sections
+}
+
+/// Cut `document` down to the elements starting on `pages`, e.g. to export
+/// only part of it. Bookmarks outside the selection are dropped.
+pub fn select_pages(document: &mut Document, pages: &RangeInclusive<usize>) -> Result<()> {
+    let count = document.pages.count;
+    if *pages.start() > count {
+        return Err(DoxxError::InvalidInput(format!(
+            "Page {} is past the end of the document ({count} pages)",
+            pages.start()
+        )));
+    }
+    select_elements(document, document.pages.elements_on(pages));
+    Ok(())
+}
+
+/// Keep only the elements in `range`, with the pages and bookmarks that
+/// fall inside it
+pub fn select_elements(document: &mut Document, range: Range<usize>) {
+    document.elements = document.elements.drain(range.clone()).collect();
+    document.pages.retain(range.clone());
+    document.bookmarks = std::mem::take(&mut document.bookmarks)
+        .into_iter()
+        .filter(|(_, index)| range.contains(index))
+        .map(|(name, index)| (name, index - range.start))
+        .collect();
+}
+
+/// Parse a page selection such as `3`, `2-5` or `4-` (to the end)
+pub fn parse_page_range(text: &str) -> Result<RangeInclusive<usize>> {
+    let parse = |part: &str| -> Result<usize> {
+        let page: usize = part.trim().parse().map_err(|_| {
+            DoxxError::InvalidInput(format!(
+                "Invalid page range: {text} (expected e.g. 3, 2-5 or 4-)"
+            ))
+        })?;
+        if page == 0 {
+            return Err(DoxxError::InvalidInput(format!(
+                "Pages are numbered from 1: {text}"
+            )));
+        }
+        Ok(page)
+    };
+    let range = match text.split_once('-') {
+        Some((start, "")) => parse(start)?..=usize::MAX,
+        Some((start, end)) => parse(start)?..=parse(end)?,
+        None => {
+            let page = parse(text)?;
+            page..=page
+        }
+    };
+    if range.start() > range.end() {
+        return Err(DoxxError::InvalidInput(format!(
+            "Invalid page range: {text} (start is after end)"
+        )));
+    }
+    Ok(range)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::TextFormatting;
+
+    fn paragraph(words: usize) -> DocumentElement {
+        DocumentElement::Paragraph {
+            text: vec!["word"; words].join(" "),
+            formatting: TextFormatting::default(),
+            runs: Vec::new(),
+            style: None,
+        }
+    }
+
+    #[test]
+    fn test_scan_rendered_and_hard_breaks() {
+        let xml = concat!(
+            "<w:body>",
+            "<w:p><w:r><w:t>One</w:t></w:r></w:p>",
+            // Word broke the page before this paragraph's text
+            "<w:p><w:r><w:lastRenderedPageBreak/><w:t>Two</w:t></w:r></w:p>",
+            "<w:tbl><w:tr><w:tc><w:p><w:r><w:t>Cell</w:t></w:r></w:p></w:tc></w:tr></w:tbl>",
+            // A hard break after text moves the next block to a new page
+            "<w:p><w:r><w:t>Three</w:t><w:br w:type=\"page\"/></w:r></w:p>",
+            "<w:p/>",
+            "<w:p><w:pPr><w:pageBreakBefore/></w:pPr><w:r><w:t>Six</w:t></w:r></w:p>",
+            "<w:p><w:r><w:br/><w:t>Line break only</w:t></w:r></w:p>",
+            "</w:body>",
+        );
+        assert_eq!(scan_page_starts(xml), BTreeSet::from([1, 4, 5]));
+    }
+
+    #[test]
+    fn test_page_map_sources() {
+        let elements: Vec<DocumentElement> = (0..4).map(|_| paragraph(100)).collect();
+
+        let rendered = PageMap::build(&elements, vec![1, 1, 2, 3], Some(3), 0, Vec::new());
+        assert_eq!(rendered.source, PageSource::Rendered);
+        assert_eq!(rendered.first_element(2), Some(2));
+        assert_eq!(rendered.page_of(3), 3);
+        assert_eq!(rendered.elements_on(&(2..=3)), 2..4);
+        assert_eq!(rendered.first_element(4), None);
+
+        let counted = PageMap::build(&elements, vec![1; 4], Some(2), 0, Vec::new());
+        assert_eq!(counted.source, PageSource::WordCount);
+        assert_eq!(
+            (0..4).map(|e| counted.page_of(e)).collect::<Vec<_>>(),
+            [1, 1, 2, 2]
+        );
+
+        // 400 words is two estimated pages, unless elements per page is set
+        assert_eq!(
+            PageMap::build(&elements, vec![1; 4], None, 0, Vec::new()).count,
+            2
+        );
+        let fixed = PageMap::build(&elements, vec![1; 4], None, 3, Vec::new());
+        assert_eq!((fixed.count, fixed.page_of(3)), (2, 2));
+
+        // A landscape A5 page holds fewer words than a Letter page
+        let small = Section {
+            page_width: 11906,
+            page_height: 8391,
+            landscape: true,
+            ..Section::default()
+        };
+        assert_eq!(Section::default().words_per_page(), WORDS_PER_PAGE);
+        let estimated = PageMap::build(&elements, vec![1; 4], None, 0, vec![small]);
+        assert_eq!(estimated.count, 4);
+    }
+
+    #[test]
+    fn test_scan_sections() {
+        let xml = concat!(
+            "<w:body>",
+            "<w:p><w:r><w:t>Portrait</w:t></w:r></w:p>",
+            "<w:p><w:pPr><w:sectPr><w:pgSz w:w=\"11906\" w:h=\"16838\"/>",
+            "<w:pgMar w:top=\"1440\" w:right=\"1800\" w:bottom=\"1440\" w:left=\"1800\"/>",
+            "</w:sectPr></w:pPr></w:p>",
+            "<w:tbl><w:tr><w:tc><w:p><w:r><w:t>Wide table</w:t></w:r></w:p></w:tc></w:tr></w:tbl>",
+            "<w:p><w:pPr><w:sectPr><w:type w:val=\"continuous\"/>",
+            "<w:pgSz w:w=\"16838\" w:h=\"11906\" w:orient=\"landscape\"/>",
+            "<w:sectPrChange><w:sectPr><w:pgSz w:w=\"1\" w:h=\"1\"/></w:sectPr></w:sectPrChange>",
+            "</w:sectPr></w:pPr></w:p>",
+            "<w:p><w:r><w:t>Back</w:t></w:r></w:p>",
+            "<w:sectPr><w:type w:val=\"oddPage\"/></w:sectPr>",
+            "</w:body>",
+        );
+        let sections = scan_sections(xml);
+        assert_eq!(sections.len(), 3);
+        assert_eq!(sections[0].first_block, 0);
+        assert_eq!(sections[0].page_width, 11906);
+        assert_eq!(sections[0].margins.left, 1800);
+        assert_eq!(
+            (sections[1].first_block, sections[1].break_type),
+            (2, SectionBreak::Continuous)
+        );
+        assert!(sections[1].landscape && !sections[1].starts_page());
+        assert_eq!(sections[1].page_width, 16838);
+        assert_eq!(
+            (sections[2].first_block, sections[2].break_type),
+            (4, SectionBreak::OddPage)
+        );
+        assert!(sections[2].starts_page());
+    }
+
+    #[test]
+    fn test_parse_page_range() {
+        assert_eq!(parse_page_range("3").unwrap(), 3..=3);
+        assert_eq!(parse_page_range("2-5").unwrap(), 2..=5);
+        assert_eq!(parse_page_range("4-").unwrap(), 4..=usize::MAX);
+        assert!(parse_page_range("0").is_err());
+        assert!(parse_page_range("5-2").is_err());
+        assert!(parse_page_range("two").is_err());
+    }
+}
@@ -0,0 +1,265 @@
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::document::{generate_outline, load_document, Document, ImageOptions, ParseOptions};
+use crate::error::{DoxxError, Result};
+
+/// Output format for folder reports
+#[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum ReportFormat {
+    #[default]
+    Markdown,
+    Text,
+    Csv,
+    Json,
+}
+
+/// Number of top-level headings listed per document
+const TOP_HEADING_COUNT: usize = 3;
+
+/// One row of a folder report
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportEntry {
+    pub path: String,
+    pub title: String,
+    pub author: Option<String>,
+    pub modified: Option<String>,
+    pub word_count: usize,
+    pub top_headings: Vec<String>,
+}
+
+/// A document that could not be parsed
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportFailure {
+    pub path: String,
+    pub error: String,
+}
+
+/// Inventory of all documents found under a folder
+#[derive(Debug, Default, Serialize)]
+pub struct FolderReport {
+    pub root: String,
+    pub documents: Vec<ReportEntry>,
+    pub failures: Vec<ReportFailure>,
+}
+
+impl ReportEntry {
+    pub fn from_document(path: &Path, document: &Document) -> Self {
+        let outline = generate_outline(document);
+        let top_level = outline.iter().map(|item| item.level).min().unwrap_or(1);
+        let top_headings = outline
+            .iter()
+            .filter(|item| item.level == top_level)
+            .take(TOP_HEADING_COUNT)
+            .map(|item| item.title.clone())
+            .collect();
+
+        Self {
+            path: path.to_string_lossy().to_string(),
+            title: document.title.clone(),
+            author: document.metadata.author.clone(),
+            modified: document
+                .metadata
+                .modified
+                .as_ref()
+                .map(|m| m.split('T').next().unwrap_or(m).to_string()),
+            word_count: document.metadata.word_count,
+            top_headings,
+        }
+    }
+}
+
+/// Parse every .docx under `dir` (recursively) and collect a report
+pub async fn build_folder_report(dir: &Path) -> Result<FolderReport> {
+    if !dir.is_dir() {
+        return Err(DoxxError::NotFound(format!(
+            "Not a directory: {}",
+            dir.display()
+        )));
+    }
+
+    let mut files = Vec::new();
+    collect_docx_files(dir, &mut files)?;
+    files.sort();
+
+    let mut report = FolderReport {
+        root: dir.to_string_lossy().to_string(),
+        ..Default::default()
+    };
+
+    for file in files {
+        let relative = file.strip_prefix(dir).unwrap_or(&file).to_path_buf();
+        match load_document(&file, ImageOptions::default(), ParseOptions::default()).await {
+            Ok(document) => report
+                .documents
+                .push(ReportEntry::from_document(&relative, &document)),
+            Err(e) => report.failures.push(ReportFailure {
+                path: relative.to_string_lossy().to_string(),
+                error: e.to_string(),
+            }),
+        }
+    }
+
+    Ok(report)
+}
+
+/// Recursively gather .docx files, skipping hidden entries and Word lock files (`~$*.docx`)
+pub fn collect_docx_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+        if name.starts_with('.') || name.starts_with("~$") {
+            continue;
+        }
+
+        if path.is_dir() {
+            collect_docx_files(&path, files)?;
+        } else if path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("docx"))
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+pub fn format_report(report: &FolderReport, format: ReportFormat) -> Result<String> {
+    Ok(match format {
+        ReportFormat::Markdown => format_report_markdown(report),
+        ReportFormat::Text => format_report_text(report),
+        ReportFormat::Csv => format_report_csv(report),
+        ReportFormat::Json => serde_json::to_string_pretty(report)? + "\n",
+    })
+}
+
+fn format_report_markdown(report: &FolderReport) -> String {
+    let mut output = format!("# Document Report: {}\n\n", report.root);
+    output.push_str(&format!(
+        "{} documents, {} words total\n\n",
+        report.documents.len(),
+        report.documents.iter().map(|d| d.word_count).sum::<usize>()
+    ));
+
+    output.push_str("| File | Title | Author | Modified | Words | Top Headings |\n");
+    output.push_str("| :--- | :--- | :--- | :--- | ---: | :--- |\n");
+    for entry in &report.documents {
+        output.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {} |\n",
+            escape_markdown_cell(&entry.path),
+            escape_markdown_cell(&entry.title),
+            escape_markdown_cell(entry.author.as_deref().unwrap_or("-")),
+            entry.modified.as_deref().unwrap_or("-"),
+            entry.word_count,
+            escape_markdown_cell(&entry.top_headings.join("; ")),
+        ));
+    }
+
+    if !report.failures.is_empty() {
+        output.push_str("\n## Failed to parse\n\n");
+        for failure in &report.failures {
+            output.push_str(&format!("- `{}`: {}\n", failure.path, failure.error));
+        }
+    }
+
+    output
+}
+
+fn format_report_text(report: &FolderReport) -> String {
+    let mut output = format!("Document Report: {}\n", report.root);
+    output.push_str(&"=".repeat(50));
+    output.push_str("\n\n");
+
+    for entry in &report.documents {
+        output.push_str(&format!("{}\n", entry.path));
+        output.push_str(&format!("  Title:    {}\n", entry.title));
+        output.push_str(&format!(
+            "  Author:   {}\n",
+            entry.author.as_deref().unwrap_or("-")
+        ));
+        output.push_str(&format!(
+            "  Modified: {}\n",
+            entry.modified.as_deref().unwrap_or("-")
+        ));
+        output.push_str(&format!("  Words:    {}\n", entry.word_count));
+        if !entry.top_headings.is_empty() {
+            output.push_str(&format!("  Headings: {}\n", entry.top_headings.join("; ")));
+        }
+        output.push('\n');
+    }
+
+    for failure in &report.failures {
+        output.push_str(&format!("{}\n  Error: {}\n\n", failure.path, failure.error));
+    }
+
+    output
+}
+
+fn format_report_csv(report: &FolderReport) -> String {
+    let mut output = String::from("file,title,author,modified,words,top_headings\n");
+    for entry in &report.documents {
+        let fields = [
+            entry.path.clone(),
+            entry.title.clone(),
+            entry.author.clone().unwrap_or_default(),
+            entry.modified.clone().unwrap_or_default(),
+            entry.word_count.to_string(),
+            entry.top_headings.join("; "),
+        ];
+        let line: Vec<String> = fields.iter().map(|f| escape_csv_field(f)).collect();
+        output.push_str(&line.join(","));
+        output.push('\n');
+    }
+    output
+}
+
+pub(crate) fn escape_markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+fn escape_csv_field(field: &str) -> String {
+    if field.contains(',') || field.contains('"') || field.contains('\n') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_report() -> FolderReport {
+        FolderReport {
+            root: "docs".to_string(),
+            documents: vec![ReportEntry {
+                path: "a|b.docx".to_string(),
+                title: "Quarterly, Report".to_string(),
+                author: Some("Jane".to_string()),
+                modified: Some("2024-01-31".to_string()),
+                word_count: 120,
+                top_headings: vec!["Intro".to_string(), "Results".to_string()],
+            }],
+            failures: vec![],
+        }
+    }
+
+    #[test]
+    fn test_markdown_report_escapes_pipes() {
+        let output = format_report(&sample_report(), ReportFormat::Markdown).unwrap();
+        assert!(output.contains("| a\\|b.docx | Quarterly, Report | Jane | 2024-01-31 | 120 |"));
+        assert!(output.contains("Intro; Results"));
+    }
+
+    #[test]
+    fn test_csv_report_quotes_commas() {
+        let output = format_report(&sample_report(), ReportFormat::Csv).unwrap();
+        assert!(output.contains("\"Quarterly, Report\""));
+    }
+}
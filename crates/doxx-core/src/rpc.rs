@@ -0,0 +1,390 @@
+//! Headless JSON-RPC 2.0 server for editor integrations (`doxx --rpc`).
+//!
+//! An editor extension starts one `doxx --rpc` process and keeps it running,
+//! so previews don't pay for a new process and a fresh parse per request.
+//! Messages are read from stdin and answered on stdout, either one JSON
+//! object per line or framed with `Content-Length` headers as in LSP; each
+//! reply uses the framing of its request.
+//!
+//! Methods (all take the document `path` it was loaded with):
+//!
+//! - `load {path}`: parse, or parse again after the file changed; returns the
+//!   title, metadata, outline, assets, warnings and `element_count`
+//! - `get_elements {path, start?, end?}`: elements `start..end` in the
+//!   `--export json` form, each with its `index` and `page`
+//! - `search {path, query, fuzzy?}`: matches with their element and page
+//! - `export {path, format, start?, end?}`: the document or a range as
+//!   `markdown`, `text`, `csv` or `json`, returned as `content`
+//! - `close {path}`: forget the document
+
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+use crate::document::{
+    parse_package, search_document_with, Document, ImageOptions, ParseOptions, SearchOptions,
+};
+use crate::error::Result;
+use crate::export::{format_document, format_range};
+use crate::json_export::to_json_document;
+use crate::ExportFormat;
+
+/// Error codes defined by JSON-RPC 2.0
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// A document that couldn't be read or exported
+const DOCUMENT_ERROR: i64 = -32000;
+/// A method called with a document that hasn't been loaded
+const NOT_LOADED: i64 = -32001;
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl ToString) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+        }
+    }
+}
+
+struct Loaded {
+    document: Document,
+    /// Elements in the `--export json` form
+    elements: Vec<Value>,
+}
+
+#[derive(Deserialize)]
+struct PathParams {
+    path: String,
+}
+
+#[derive(Deserialize)]
+struct RangeParams {
+    path: String,
+    start: Option<usize>,
+    end: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct SearchParams {
+    path: String,
+    query: String,
+    #[serde(default)]
+    fuzzy: bool,
+}
+
+#[derive(Deserialize)]
+struct ExportParams {
+    path: String,
+    format: String,
+    start: Option<usize>,
+    end: Option<usize>,
+}
+
+/// Documents loaded by the client, and how new ones are parsed
+pub struct RpcServer {
+    parse_options: ParseOptions,
+    documents: HashMap<String, Loaded>,
+}
+
+impl RpcServer {
+    pub fn new(parse_options: ParseOptions) -> Self {
+        Self {
+            parse_options,
+            documents: HashMap::new(),
+        }
+    }
+
+    /// Answer one message, or return `None` for a notification (a request
+    /// without an `id`)
+    pub fn handle(&mut self, message: &str) -> Option<String> {
+        let request: Value = match serde_json::from_str(message) {
+            Ok(request) => request,
+            Err(e) => return Some(error_response(Value::Null, RpcError::new(PARSE_ERROR, e))),
+        };
+        let Some(request) = request.as_object() else {
+            return Some(error_response(
+                Value::Null,
+                RpcError::new(INVALID_REQUEST, "expected a request object"),
+            ));
+        };
+        let id = request.get("id").cloned();
+        let result = match request.get("method").and_then(Value::as_str) {
+            Some(method) => {
+                let params = request.get("params").cloned().unwrap_or(Value::Null);
+                self.call(method, params)
+            }
+            None => Err(RpcError::new(INVALID_REQUEST, "missing method")),
+        };
+
+        let id = id?;
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }).to_string(),
+            Err(error) => error_response(id, error),
+        })
+    }
+
+    fn call(&mut self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "load" => self.load(parse_params(params)?),
+            "get_elements" => self.get_elements(parse_params(params)?),
+            "search" => self.search(parse_params(params)?),
+            "export" => self.export(parse_params(params)?),
+            "close" => {
+                let PathParams { path } = parse_params(params)?;
+                Ok(json!({ "closed": self.documents.remove(&path).is_some() }))
+            }
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("unknown method {method}"),
+            )),
+        }
+    }
+
+    fn loaded(&self, path: &str) -> Result<&Loaded, RpcError> {
+        self.documents.get(path).ok_or_else(|| {
+            RpcError::new(NOT_LOADED, format!("{path} isn't loaded; call load first"))
+        })
+    }
+
+    fn load(&mut self, PathParams { path }: PathParams) -> Result<Value, RpcError> {
+        let data = std::fs::read(&path).map_err(|e| RpcError::new(DOCUMENT_ERROR, e))?;
+        let document = parse_package(
+            &data,
+            Some(Path::new(&path)),
+            ImageOptions::default(),
+            self.parse_options.clone(),
+        )
+        .map_err(|e| RpcError::new(DOCUMENT_ERROR, e))?;
+
+        let mut summary = serde_json::to_value(to_json_document(&document))
+            .map_err(|e| RpcError::new(DOCUMENT_ERROR, e))?;
+        let elements = match summary
+            .as_object_mut()
+            .and_then(|summary| summary.remove("elements"))
+        {
+            Some(Value::Array(elements)) => elements,
+            _ => Vec::new(),
+        };
+        summary["element_count"] = elements.len().into();
+        self.documents.insert(path, Loaded { document, elements });
+        Ok(summary)
+    }
+
+    fn get_elements(&self, params: RangeParams) -> Result<Value, RpcError> {
+        let loaded = self.loaded(&params.path)?;
+        let total = loaded.elements.len();
+        let end = params.end.unwrap_or(total).min(total);
+        let start = params.start.unwrap_or(0).min(end);
+        let elements: Vec<Value> = loaded.elements[start..end]
+            .iter()
+            .zip(start..)
+            .map(|(element, index)| {
+                let mut entry = Map::new();
+                entry.insert("index".to_string(), index.into());
+                entry.insert(
+                    "page".to_string(),
+                    loaded.document.pages.page_of(index).into(),
+                );
+                if let Value::Object(fields) = element {
+                    entry.extend(fields.clone());
+                }
+                Value::Object(entry)
+            })
+            .collect();
+        Ok(json!({ "start": start, "end": end, "total": total, "elements": elements }))
+    }
+
+    fn search(&self, params: SearchParams) -> Result<Value, RpcError> {
+        let loaded = self.loaded(&params.path)?;
+        let options = SearchOptions {
+            fuzzy: params.fuzzy,
+            ..Default::default()
+        };
+        let matches: Vec<Value> = search_document_with(&loaded.document, &params.query, &options)
+            .into_iter()
+            .map(|result| {
+                json!({
+                    "element": result.element_index,
+                    "page": loaded.document.pages.page_of(result.element_index),
+                    "text": result.text,
+                    "start": result.start_pos,
+                    "end": result.end_pos,
+                })
+            })
+            .collect();
+        Ok(Value::Array(matches))
+    }
+
+    fn export(&self, params: ExportParams) -> Result<Value, RpcError> {
+        let loaded = self.loaded(&params.path)?;
+        let format = match params.format.as_str() {
+            "markdown" | "md" => ExportFormat::Markdown,
+            "text" | "txt" => ExportFormat::Text,
+            "csv" => ExportFormat::Csv,
+            "json" => ExportFormat::Json,
+            other => {
+                return Err(RpcError::new(
+                    INVALID_PARAMS,
+                    format!("unknown format {other} (markdown, text, csv or json)"),
+                ))
+            }
+        };
+        let content = if params.start.is_none() && params.end.is_none() {
+            format_document(&loaded.document, &format)
+        } else {
+            let total = loaded.document.elements.len();
+            let end = params.end.unwrap_or(total).min(total);
+            let start = params.start.unwrap_or(0).min(end);
+            format_range(&loaded.document, start..end, &format)
+        }
+        .map_err(|e| RpcError::new(DOCUMENT_ERROR, e))?;
+        Ok(json!({ "content": content }))
+    }
+}
+
+fn parse_params<T: for<'de> Deserialize<'de>>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e))
+}
+
+fn error_response(id: Value, error: RpcError) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": error.code, "message": error.message },
+    })
+    .to_string()
+}
+
+/// Serve requests from `input` until it ends, answering on `output`
+pub fn serve(mut server: RpcServer, mut input: impl BufRead, mut output: impl Write) -> Result<()> {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        let header = line.trim();
+        if header.is_empty() {
+            continue;
+        }
+
+        let length = header
+            .split_once(':')
+            .filter(|(name, _)| name.trim().eq_ignore_ascii_case("content-length"))
+            .and_then(|(_, value)| value.trim().parse::<usize>().ok());
+        let reply = match length {
+            Some(length) => {
+                // Skip any other headers up to the blank line before the body
+                loop {
+                    line.clear();
+                    if input.read_line(&mut line)? == 0 || line.trim().is_empty() {
+                        break;
+                    }
+                }
+                let mut body = vec![0; length];
+                input.read_exact(&mut body)?;
+                server
+                    .handle(&String::from_utf8_lossy(&body))
+                    .map(|reply| format!("Content-Length: {}\r\n\r\n{reply}", reply.len()))
+            }
+            None => server.handle(header).map(|reply| format!("{reply}\n")),
+        };
+        if let Some(reply) = reply {
+            output.write_all(reply.as_bytes())?;
+            output.flush()?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = "../../tests/fixtures/headings-hierarchy.docx";
+
+    fn call(server: &mut RpcServer, method: &str, params: Value) -> Value {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        serde_json::from_str(&server.handle(&request.to_string()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_methods() {
+        let mut server = RpcServer::new(ParseOptions::default());
+        let response = call(
+            &mut server,
+            "search",
+            json!({ "path": FIXTURE, "query": "x" }),
+        );
+        assert_eq!(response["error"]["code"], NOT_LOADED);
+
+        let response = call(&mut server, "load", json!({ "path": FIXTURE }));
+        let count = response["result"]["element_count"].as_u64().unwrap();
+        assert!(count > 2);
+        assert!(response["result"]["outline"].as_array().is_some());
+
+        let response = call(
+            &mut server,
+            "get_elements",
+            json!({ "path": FIXTURE, "start": 1, "end": 3 }),
+        );
+        let elements = response["result"]["elements"].as_array().unwrap();
+        assert_eq!(elements.len(), 2);
+        assert_eq!(elements[0]["index"], 1);
+        assert_eq!(elements[0]["type"], "heading");
+
+        let response = call(
+            &mut server,
+            "search",
+            json!({ "path": FIXTURE, "query": "Prerequisites" }),
+        );
+        assert!(!response["result"].as_array().unwrap().is_empty());
+
+        let response = call(
+            &mut server,
+            "export",
+            json!({ "path": FIXTURE, "format": "markdown", "start": 1, "end": 2 }),
+        );
+        assert!(response["result"]["content"]
+            .as_str()
+            .unwrap()
+            .contains("Introduction"));
+
+        let response = call(&mut server, "nope", Value::Null);
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+        // Notifications get no reply
+        assert!(server
+            .handle(r#"{"jsonrpc":"2.0","method":"close","params":{"path":"x"}}"#)
+            .is_none());
+    }
+
+    #[test]
+    fn test_serve_framing() {
+        let body = r#"{"jsonrpc":"2.0","id":7,"method":"close","params":{"path":"a"}}"#;
+        let input = format!(
+            "{body}\nContent-Length: {}\r\nContent-Type: application/json\r\n\r\n{body}",
+            body.len()
+        );
+        let mut output = Vec::new();
+        serve(
+            RpcServer::new(ParseOptions::default()),
+            input.as_bytes(),
+            &mut output,
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let reply = r#"{"id":7,"jsonrpc":"2.0","result":{"closed":false}}"#;
+        assert_eq!(
+            output,
+            format!("{reply}\nContent-Length: {}\r\n\r\n{reply}", reply.len())
+        );
+    }
+}
@@ -0,0 +1,120 @@
+//! Parsing untrusted documents in a separate process.
+//!
+//! The parent starts a worker (normally its own executable) that loads the
+//! document and writes it to stdout with [`write_parsed`]; [`load_in_worker`]
+//! reads it back. A parser crash, runaway allocation or infinite loop then
+//! ends the worker, not the session. On Unix the worker also runs under
+//! memory, CPU time and file size limits, and on Linux it can't gain
+//! privileges (`PR_SET_NO_NEW_PRIVS`). There is no syscall filtering
+//! (seccomp, landlock): the worker can still read and write any file the
+//! user can, so the limits contain resource exhaustion, not an exploit.
+
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+
+use crate::document::Document;
+use crate::error::{DoxxError, Result};
+use crate::limits::Limits;
+use crate::pages::PageMap;
+
+/// What the worker sends back: the document and its pages, which the
+/// document's own JSON leaves out
+#[derive(Serialize, Deserialize)]
+struct Parsed {
+    document: Document,
+    pages: PageMap,
+}
+
+/// Write `document` for [`load_in_worker`] to read, from the worker process.
+/// Its extracted images are left behind for the parent, which removes them.
+pub fn write_parsed(mut document: Document, output: impl Write) -> Result<()> {
+    if let Some(dir) = document.image_dir.take().and_then(Arc::into_inner) {
+        let _ = dir.keep();
+    }
+    let pages = std::mem::take(&mut document.pages);
+    serde_json::to_writer(output, &Parsed { document, pages })?;
+    Ok(())
+}
+
+/// Run `worker` under the sandbox limits in `limits` and read the document
+/// it writes. The worker's error message is returned if it fails.
+pub fn load_in_worker(mut worker: Command, limits: &Limits) -> Result<Document> {
+    // The worker's temporary files, its extracted images among them, go in
+    // a directory the parent owns and removes with the document
+    let temp_dir = tempfile::Builder::new().prefix("doxx-worker-").tempdir()?;
+    worker
+        .env("TMPDIR", temp_dir.path())
+        .env("TMP", temp_dir.path())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    restrict(&mut worker, limits);
+
+    let output = worker
+        .spawn()
+        .map_err(|e| DoxxError::Sandbox(format!("Failed to start the parser process: {e}")))?
+        .wait_with_output()?;
+    let status = output.status;
+
+    if !status.success() {
+        // The worker's own error, e.g. `Error: file.docx: zip container: ...`
+        let errors = String::from_utf8_lossy(&output.stderr);
+        let message = errors.lines().next().unwrap_or_default();
+        if let Some(message) = message.strip_prefix("Error: ") {
+            return Err(DoxxError::Sandbox(message.to_string()));
+        }
+        return Err(DoxxError::Sandbox(format!(
+            "Parser process stopped ({status}); the document may be malformed or hostile"
+        )));
+    }
+
+    let parsed: Parsed = serde_json::from_slice(&output.stdout)
+        .map_err(|e| DoxxError::Sandbox(format!("Invalid output from the parser process: {e}")))?;
+    let mut document = parsed.document;
+    document.pages = parsed.pages;
+    document.image_dir = Some(Arc::new(temp_dir));
+    Ok(document)
+}
+
+#[cfg(unix)]
+fn restrict(worker: &mut Command, limits: &Limits) {
+    use std::os::unix::process::CommandExt;
+
+    const MEGABYTE: u64 = 1024 * 1024;
+    let memory = limits.sandbox_memory_mb.saturating_mul(MEGABYTE);
+    let cpu = limits.sandbox_cpu_seconds;
+    // Extracted images are the only files the worker writes
+    let file_size = limits.max_decompressed_mb.saturating_mul(MEGABYTE);
+
+    // SAFETY: only async-signal-safe calls (setrlimit, prctl) run between
+    // fork and exec
+    unsafe {
+        worker.pre_exec(move || {
+            let set = |resource, value: u64| {
+                let limit = libc::rlimit {
+                    rlim_cur: value as libc::rlim_t,
+                    rlim_max: value as libc::rlim_t,
+                };
+                if value > 0 && libc::setrlimit(resource, &limit) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            };
+            set(libc::RLIMIT_AS, memory)?;
+            set(libc::RLIMIT_CPU, cpu)?;
+            set(libc::RLIMIT_FSIZE, file_size)?;
+            #[cfg(target_os = "linux")]
+            if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(unix))]
+fn restrict(_worker: &mut Command, _limits: &Limits) {
+    // Only the process boundary protects the session here
+}
@@ -0,0 +1,270 @@
+//! Saved searches: named term lists kept in the config as
+//! `[reports.<name>]` and run with `--run-report <name>`.
+//!
+//! Each term is searched for separately, with the definition's scope, in
+//! one document or every document under a folder, so the same questions
+//! get a report of the same shape every time they are asked.
+
+use serde::Serialize;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use crate::config::{SavedReport, SavedReportFormat};
+use crate::document::{Document, ParseOptions, SearchOptions, SearchScope};
+use crate::error::{DoxxError, Result};
+use crate::grep::{grep_document, map_documents, GrepMatch};
+use crate::report::{escape_markdown_cell, ReportFailure};
+use crate::text_width::{display_width, skip_width, truncate_to_width};
+
+/// Columns of context kept on each side of a match in Markdown reports
+const SNIPPET_CONTEXT: usize = 60;
+
+/// A saved search run over a set of documents
+#[derive(Debug, Serialize)]
+pub struct SearchReport {
+    pub name: String,
+    pub terms: Vec<String>,
+    /// What the search was limited to, e.g. `under "3. Risks"`
+    pub scope: Option<String>,
+    pub documents: Vec<DocumentHits>,
+    pub failures: Vec<ReportFailure>,
+}
+
+/// Matches for each term in one document
+#[derive(Debug, Serialize)]
+pub struct DocumentHits {
+    pub file: PathBuf,
+    pub terms: Vec<TermHits>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TermHits {
+    pub term: String,
+    pub count: usize,
+    pub matches: Vec<GrepMatch>,
+}
+
+/// How `definition` searches each document
+pub fn search_options(definition: &SavedReport) -> SearchOptions {
+    let scope = if !definition.section.is_empty() {
+        SearchScope::Heading(definition.section.clone())
+    } else if definition.tables {
+        SearchScope::Tables
+    } else {
+        SearchScope::All
+    };
+    SearchOptions {
+        fuzzy: definition.fuzzy,
+        scope,
+        ..SearchOptions::default()
+    }
+}
+
+/// Run the saved search `name` over `files`, parsing up to `parallelism`
+/// documents at once
+pub async fn run_report(
+    name: &str,
+    definition: &SavedReport,
+    files: Vec<PathBuf>,
+    parse_options: ParseOptions,
+    parallelism: usize,
+) -> Result<SearchReport> {
+    if definition.terms.is_empty() {
+        return Err(DoxxError::InvalidInput(format!(
+            "Report {name} has no terms; add some under [reports.{name}]"
+        )));
+    }
+
+    let options = search_options(definition);
+    let terms = definition.terms.clone();
+    let outcomes = map_documents(files, parse_options, parallelism, move |document, file| {
+        document_hits(document, file, &terms, &options)
+    })
+    .await;
+
+    let mut documents = Vec::new();
+    let mut failures = Vec::new();
+    for (file, result) in outcomes {
+        match result {
+            Ok(hits) => documents.push(hits),
+            Err(e) => failures.push(ReportFailure {
+                path: file.to_string_lossy().to_string(),
+                error: format!("{e:#}"),
+            }),
+        }
+    }
+
+    let scope = if !definition.section.is_empty() {
+        Some(format!("under \"{}\"", definition.section))
+    } else {
+        definition.tables.then(|| "tables only".to_string())
+    };
+    Ok(SearchReport {
+        name: name.to_string(),
+        terms: definition.terms.clone(),
+        scope,
+        documents,
+        failures,
+    })
+}
+
+fn document_hits(
+    document: &Document,
+    file: &Path,
+    terms: &[String],
+    options: &SearchOptions,
+) -> DocumentHits {
+    DocumentHits {
+        file: file.to_path_buf(),
+        terms: terms
+            .iter()
+            .map(|term| {
+                let matches = grep_document(document, file, term, options);
+                TermHits {
+                    term: term.clone(),
+                    count: matches.len(),
+                    matches,
+                }
+            })
+            .collect(),
+    }
+}
+
+pub fn format_search_report(report: &SearchReport, format: SavedReportFormat) -> Result<String> {
+    Ok(match format {
+        SavedReportFormat::Markdown => format_markdown(report),
+        SavedReportFormat::Json => serde_json::to_string_pretty(report)? + "\n",
+    })
+}
+
+fn format_markdown(report: &SearchReport) -> String {
+    let mut output = format!("# Report: {}\n\n", report.name);
+    let _ = write!(
+        output,
+        "Searched {} document{} for {}",
+        report.documents.len(),
+        if report.documents.len() == 1 { "" } else { "s" },
+        report.terms.join(", ")
+    );
+    if let Some(scope) = &report.scope {
+        let _ = write!(output, " ({scope})");
+    }
+    output.push_str("\n\n");
+
+    // Counts: one row per document, one column per term
+    let _ = writeln!(
+        output,
+        "| Document | {} |",
+        report
+            .terms
+            .iter()
+            .map(|term| escape_markdown_cell(term))
+            .collect::<Vec<_>>()
+            .join(" | ")
+    );
+    let _ = writeln!(output, "| :--- |{}", " ---: |".repeat(report.terms.len()));
+    for document in &report.documents {
+        let counts: Vec<String> = document
+            .terms
+            .iter()
+            .map(|hits| hits.count.to_string())
+            .collect();
+        let _ = writeln!(
+            output,
+            "| {} | {} |",
+            escape_markdown_cell(&document.file.display().to_string()),
+            counts.join(" | ")
+        );
+    }
+    let totals: Vec<String> = (0..report.terms.len())
+        .map(|term| {
+            let total: usize = report.documents.iter().map(|d| d.terms[term].count).sum();
+            total.to_string()
+        })
+        .collect();
+    let _ = writeln!(output, "| **Total** | {} |", totals.join(" | "));
+
+    for document in &report.documents {
+        if document.terms.iter().all(|hits| hits.count == 0) {
+            continue;
+        }
+        let _ = write!(output, "\n## {}\n", document.file.display());
+        for hits in document.terms.iter().filter(|hits| hits.count > 0) {
+            let _ = write!(output, "\n### {} ({})\n\n", hits.term, hits.count);
+            for found in &hits.matches {
+                let _ = write!(output, "- Page {}", found.page);
+                if let Some(section) = &found.section {
+                    let _ = write!(output, ", {}", section.replace('\n', " "));
+                }
+                let _ = writeln!(output, ": {}", snippet(found));
+            }
+        }
+    }
+
+    if !report.failures.is_empty() {
+        output.push_str("\n## Failed to parse\n\n");
+        for failure in &report.failures {
+            let _ = writeln!(output, "- `{}`: {}", failure.path, failure.error);
+        }
+    }
+    output
+}
+
+/// The match in bold with some of the text around it, on one line
+fn snippet(found: &GrepMatch) -> String {
+    let text = &found.text;
+    let (Some(before), Some(matched)) = (text.get(..found.start), text.get(found.start..found.end))
+    else {
+        return text.replace('\n', " ");
+    };
+    let after = &text[found.end..];
+
+    let before = match display_width(before).checked_sub(SNIPPET_CONTEXT) {
+        Some(skip) if skip > 0 => format!("…{}", skip_width(before, skip)),
+        _ => before.to_string(),
+    };
+    let after = if display_width(after) > SNIPPET_CONTEXT {
+        format!("{}…", truncate_to_width(after, SNIPPET_CONTEXT))
+    } else {
+        after.to_string()
+    };
+    format!("{before}**{matched}**{after}").replace(['\r', '\n'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_saved_report() {
+        let definition = SavedReport {
+            terms: vec!["revenue".to_string(), "growth".to_string()],
+            section: "Revenue Analysis".to_string(),
+            ..SavedReport::default()
+        };
+        let files = vec![PathBuf::from("../../tests/fixtures/business-report.docx")];
+        let report = run_report("finance", &definition, files, ParseOptions::default(), 1)
+            .await
+            .unwrap();
+        assert!(report.failures.is_empty());
+        let hits = &report.documents[0].terms;
+        assert!(hits[0].count > 0 && hits[1].count > 0);
+        assert!(hits
+            .iter()
+            .flat_map(|hits| &hits.matches)
+            .all(|found| found.section.as_deref() == Some("Revenue Analysis")));
+
+        let markdown = format_search_report(&report, SavedReportFormat::Markdown).unwrap();
+        assert!(markdown.starts_with("# Report: finance\n"));
+        assert!(markdown.contains("(under \"Revenue Analysis\")"));
+        assert!(markdown.contains("| Document | revenue | growth |"));
+        assert!(markdown.contains("**revenue**") || markdown.contains("**Revenue**"));
+
+        let empty = SavedReport::default();
+        assert!(
+            run_report("empty", &empty, Vec::new(), ParseOptions::default(), 1)
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,178 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::Result;
+use crate::store;
+
+/// Oldest positions are dropped once the store grows past this many documents
+const MAX_POSITIONS: usize = 500;
+
+/// Where the reader left off in a document
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReadingPosition {
+    /// Element at the top of the document view
+    pub scroll_offset: usize,
+    /// Visual rows of that element scrolled out of view
+    #[serde(default)]
+    pub scroll_line_offset: usize,
+    /// View mode name (`document`, `outline` or `search`)
+    pub view: String,
+    #[serde(default)]
+    pub search_query: String,
+    /// Seconds since the Unix epoch when the position was saved
+    #[serde(default)]
+    pub updated: u64,
+}
+
+/// JSON-backed store of reading positions keyed by document content hash, so
+/// renamed or moved copies of a file resume at the same place
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PositionStore {
+    positions: BTreeMap<String, ReadingPosition>,
+    /// Bookmarks named by a lowercase letter per document, mapping to element
+    /// indices
+    #[serde(default)]
+    bookmarks: BTreeMap<String, BTreeMap<char, usize>>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl PositionStore {
+    /// Default store location (e.g. `~/.local/state/doxx/positions.json`)
+    pub fn default_path() -> PathBuf {
+        dirs::state_dir()
+            .or_else(dirs::data_local_dir)
+            .unwrap_or_else(std::env::temp_dir)
+            .join("doxx")
+            .join("positions.json")
+    }
+
+    /// Open the store at the default location
+    pub fn open_default() -> Result<Self> {
+        Self::open(&Self::default_path())
+    }
+
+    /// Open the store at `path`, starting empty if it doesn't exist yet
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut store: Self = store::load(path)?;
+        store.path = path.to_path_buf();
+        Ok(store)
+    }
+
+    /// Write the store back to disk
+    pub fn save(&self) -> Result<()> {
+        store::save(&self.path, self)
+    }
+
+    /// Last saved position for a document hash
+    pub fn get(&self, key: &str) -> Option<&ReadingPosition> {
+        self.positions.get(key)
+    }
+
+    /// Record a position, stamping it with the current time
+    pub fn set(&mut self, key: &str, mut position: ReadingPosition) {
+        position.updated = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        self.positions.insert(key.to_string(), position);
+
+        while self.positions.len() > MAX_POSITIONS {
+            let oldest = self
+                .positions
+                .iter()
+                .min_by_key(|(_, p)| p.updated)
+                .map(|(key, _)| key.clone());
+            match oldest {
+                Some(key) => self.positions.remove(&key),
+                None => break,
+            };
+        }
+    }
+}
+
+impl PositionStore {
+    /// Bookmarks saved for a document hash
+    pub fn bookmarks(&self, key: &str) -> BTreeMap<char, usize> {
+        self.bookmarks.get(key).cloned().unwrap_or_default()
+    }
+
+    /// Replace the bookmarks for a document hash
+    pub fn set_bookmarks(&mut self, key: &str, bookmarks: BTreeMap<char, usize>) {
+        if bookmarks.is_empty() {
+            self.bookmarks.remove(key);
+        } else {
+            self.bookmarks.insert(key.to_string(), bookmarks);
+        }
+    }
+}
+
+/// Load the saved bookmarks for a file, empty if there are none or the store
+/// can't be read
+pub fn load_bookmarks(path: &Path) -> BTreeMap<char, usize> {
+    file_digest(path)
+        .and_then(|key| Ok(PositionStore::open_default()?.bookmarks(&key)))
+        .unwrap_or_default()
+}
+
+/// Hex SHA-256 of a file's contents
+pub fn file_digest(path: &Path) -> Result<String> {
+    let digest = Sha256::digest(fs::read(path)?);
+    Ok(digest.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_and_eviction() {
+        let path = std::env::temp_dir()
+            .join(format!("doxx-positions-test-{}", std::process::id()))
+            .join("positions.json");
+        let _ = fs::remove_file(&path);
+
+        let mut store = PositionStore::open(&path).unwrap();
+        for i in 0..MAX_POSITIONS + 3 {
+            store.set(
+                &format!("doc{i}"),
+                ReadingPosition {
+                    scroll_offset: i,
+                    view: "document".to_string(),
+                    ..Default::default()
+                },
+            );
+        }
+        store.save().unwrap();
+
+        let reopened = PositionStore::open(&path).unwrap();
+        assert_eq!(reopened.positions.len(), MAX_POSITIONS);
+        let last = format!("doc{}", MAX_POSITIONS + 2);
+        assert_eq!(
+            reopened.get(&last).unwrap().scroll_offset,
+            MAX_POSITIONS + 2
+        );
+    }
+
+    #[test]
+    fn test_bookmarks_round_trip() {
+        let path = std::env::temp_dir()
+            .join(format!("doxx-bookmarks-test-{}", std::process::id()))
+            .join("bookmarks.json");
+        let _ = fs::remove_file(&path);
+
+        let mut store = PositionStore::open(&path).unwrap();
+        store.set_bookmarks("doc", BTreeMap::from([('a', 3), ('z', 42)]));
+        store.save().unwrap();
+
+        let mut reopened = PositionStore::open(&path).unwrap();
+        assert_eq!(reopened.bookmarks("doc").get(&'z'), Some(&42));
+
+        reopened.set_bookmarks("doc", BTreeMap::new());
+        assert!(reopened.bookmarks("doc").is_empty());
+    }
+}
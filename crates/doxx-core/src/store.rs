@@ -0,0 +1,34 @@
+//! Small JSON files doxx keeps between runs: tags, reading positions and
+//! reading history.
+
+use serde::{de::DeserializeOwned, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::error::Result;
+
+/// Read the store at `path`, starting empty if it doesn't exist yet
+pub fn load<T: DeserializeOwned + Default>(path: &Path) -> Result<T> {
+    if !path.exists() {
+        return Ok(T::default());
+    }
+    let data = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&data)?)
+}
+
+/// Write `store` to `path`, creating its directory if needed
+pub fn save<T: Serialize>(path: &Path, store: &T) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(store)?)?;
+    Ok(())
+}
+
+/// Documents are keyed by their canonical path so relative invocations agree
+pub fn document_key(file: &Path) -> String {
+    fs::canonicalize(file)
+        .unwrap_or_else(|_| file.to_path_buf())
+        .to_string_lossy()
+        .to_string()
+}
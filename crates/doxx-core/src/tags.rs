@@ -0,0 +1,161 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use crate::error::Result;
+use crate::store::{self, document_key};
+
+/// Tags and notes attached to a single document
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TaggedDocument {
+    pub tags: BTreeSet<String>,
+    pub note: Option<String>,
+}
+
+/// Small JSON-backed database mapping document paths to tags and notes
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TagDatabase {
+    documents: BTreeMap<String, TaggedDocument>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl TagDatabase {
+    /// Default database location (e.g. `~/.local/share/doxx/tags.json`)
+    pub fn default_path() -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("doxx")
+            .join("tags.json")
+    }
+
+    /// Open the database at the default location
+    pub fn open_default() -> Result<Self> {
+        Self::open(&Self::default_path())
+    }
+
+    /// Open the database at `path`, starting empty if it doesn't exist yet
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut db: Self = store::load(path)?;
+        db.path = path.to_path_buf();
+        Ok(db)
+    }
+
+    /// Write the database back to disk
+    pub fn save(&self) -> Result<()> {
+        store::save(&self.path, self)
+    }
+
+    /// Add tags to a document
+    pub fn add_tags(&mut self, file: &Path, tags: &[String]) {
+        let entry = self.documents.entry(document_key(file)).or_default();
+        for tag in tags {
+            let tag = normalize_tag(tag);
+            if !tag.is_empty() {
+                entry.tags.insert(tag);
+            }
+        }
+    }
+
+    /// Remove tags from a document, dropping the entry once it carries nothing
+    pub fn remove_tags(&mut self, file: &Path, tags: &[String]) {
+        let key = document_key(file);
+        if let Some(entry) = self.documents.get_mut(&key) {
+            for tag in tags {
+                entry.tags.remove(&normalize_tag(tag));
+            }
+            if entry.tags.is_empty() && entry.note.is_none() {
+                self.documents.remove(&key);
+            }
+        }
+    }
+
+    /// Set or clear the note attached to a document
+    pub fn set_note(&mut self, file: &Path, note: Option<String>) {
+        let key = document_key(file);
+        let entry = self.documents.entry(key.clone()).or_default();
+        entry.note = note.filter(|n| !n.trim().is_empty());
+        if entry.tags.is_empty() && entry.note.is_none() {
+            self.documents.remove(&key);
+        }
+    }
+
+    /// Look up the tags and note for a document
+    pub fn get(&self, file: &Path) -> Option<&TaggedDocument> {
+        self.documents.get(&document_key(file))
+    }
+
+    /// List documents carrying all of the given tags (all documents if `tags` is empty)
+    pub fn filter(&self, tags: &[String]) -> Vec<(&str, &TaggedDocument)> {
+        let wanted: Vec<String> = tags.iter().map(|t| normalize_tag(t)).collect();
+        self.documents
+            .iter()
+            .filter(|(_, doc)| wanted.iter().all(|t| doc.tags.contains(t)))
+            .map(|(path, doc)| (path.as_str(), doc))
+            .collect()
+    }
+
+    /// All known tags with the number of documents using each
+    pub fn tag_counts(&self) -> BTreeMap<&str, usize> {
+        let mut counts = BTreeMap::new();
+        for doc in self.documents.values() {
+            for tag in &doc.tags {
+                *counts.entry(tag.as_str()).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+}
+
+fn normalize_tag(tag: &str) -> String {
+    tag.trim().trim_start_matches('#').to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn temp_db(name: &str) -> TagDatabase {
+        let path = std::env::temp_dir()
+            .join(format!("doxx-tags-test-{}", std::process::id()))
+            .join(format!("{name}.json"));
+        let _ = fs::remove_file(&path);
+        TagDatabase::open(&path).unwrap()
+    }
+
+    #[test]
+    fn test_add_filter_and_remove_tags() {
+        let mut db = temp_db("add_filter_remove");
+        let contract = Path::new("contract.docx");
+        let memo = Path::new("memo.docx");
+
+        db.add_tags(contract, &["Legal".to_string(), "#urgent".to_string()]);
+        db.add_tags(memo, &["legal".to_string()]);
+
+        assert_eq!(db.filter(&["legal".to_string()]).len(), 2);
+        assert_eq!(
+            db.filter(&["legal".to_string(), "urgent".to_string()])
+                .len(),
+            1
+        );
+        assert_eq!(db.tag_counts().get("legal"), Some(&2));
+
+        db.remove_tags(memo, &["legal".to_string()]);
+        assert!(db.get(memo).is_none());
+    }
+
+    #[test]
+    fn test_round_trip_with_note() {
+        let mut db = temp_db("round_trip");
+        let file = Path::new("report.docx");
+        db.add_tags(file, &["finance".to_string()]);
+        db.set_note(file, Some("Check Q3 numbers".to_string()));
+        db.save().unwrap();
+
+        let reopened = TagDatabase::open(&db.path).unwrap();
+        let entry = reopened.get(file).unwrap();
+        assert!(entry.tags.contains("finance"));
+        assert_eq!(entry.note.as_deref(), Some("Check Q3 numbers"));
+    }
+}
@@ -0,0 +1,236 @@
+//! Text folding for search.
+//!
+//! Text and query are compared after Unicode compatibility normalization
+//! (NFKC) and full case folding, so `strasse` finds `Straße`, `file` finds
+//! `ﬁle`, and composed and decomposed accents match each other. Folding
+//! follows the language of each paragraph, guessed from its letters, which
+//! matters for Turkish and Azerbaijani dotted and dotless i.
+//!
+//! Fuzzy search goes further: accents are dropped altogether and each query
+//! word may be a few typos away from the word it matches.
+
+use std::ops::Range;
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Case folding rules that differ by language
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FoldLanguage {
+    Default,
+    /// Turkish and Azerbaijani: `I` folds to `ı` and `İ` to `i`
+    Turkic,
+}
+
+/// Guess the folding rules for a paragraph from letters only its language uses
+pub fn detect_language(text: &str) -> FoldLanguage {
+    if text
+        .chars()
+        .any(|c| matches!(c, 'ı' | 'İ' | 'ğ' | 'Ğ' | 'ş' | 'Ş' | 'ə' | 'Ə'))
+    {
+        FoldLanguage::Turkic
+    } else {
+        FoldLanguage::Default
+    }
+}
+
+/// Normalize and case-fold `text` for comparison
+pub fn fold(text: &str, language: FoldLanguage) -> String {
+    let mut folded = String::with_capacity(text.len());
+    for c in text.nfkc() {
+        fold_char(c, language, &mut folded);
+    }
+    folded.nfc().collect()
+}
+
+fn fold_char(c: char, language: FoldLanguage, out: &mut String) {
+    match c {
+        'I' if language == FoldLanguage::Turkic => out.push('ı'),
+        'İ' if language == FoldLanguage::Turkic => out.push('i'),
+        'ß' | 'ẞ' => out.push_str("ss"),
+        'ς' => out.push('σ'),
+        'ſ' => out.push('s'),
+        _ => out.extend(c.to_lowercase()),
+    }
+}
+
+/// Byte ranges of `text` matching `query` after folding, without overlaps.
+/// A match that starts or ends inside a character that folds to several
+/// (`ß` → `ss`) covers the whole character.
+pub fn find_folded(text: &str, query: &str) -> Vec<Range<usize>> {
+    let language = detect_language(text);
+    let query = fold(query, language);
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    // ASCII folds byte for byte, so offsets in the folded text are offsets
+    // in `text`; most documents take this path
+    if text.is_ascii() {
+        let folded = text.to_ascii_lowercase();
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        for (offset, _) in folded.match_indices(&query) {
+            if ranges.last().is_none_or(|last| offset >= last.end) {
+                ranges.push(offset..offset + query.len());
+            }
+        }
+        return ranges;
+    }
+
+    // Fold grapheme by grapheme, remembering where each folded piece came from
+    let mut folded = String::with_capacity(text.len());
+    let mut pieces: Vec<(usize, Range<usize>)> = Vec::new();
+    for (start, grapheme) in text.grapheme_indices(true) {
+        pieces.push((folded.len(), start..start + grapheme.len()));
+        if language == FoldLanguage::Default && grapheme.is_ascii() {
+            folded.extend(grapheme.chars().map(|c| c.to_ascii_lowercase()));
+        } else {
+            folded.push_str(&fold(grapheme, language));
+        }
+    }
+    let source_of = |folded_offset: usize| {
+        let piece = pieces
+            .partition_point(|(start, _)| *start <= folded_offset)
+            .saturating_sub(1);
+        pieces[piece].1.clone()
+    };
+
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for (offset, _) in folded.match_indices(&query) {
+        let start = source_of(offset).start;
+        let end = source_of(offset + query.len() - 1).end;
+        if ranges.last().is_some_and(|last| start < last.end) {
+            continue;
+        }
+        ranges.push(start..end);
+    }
+    ranges
+}
+
+/// Fold `text` and drop combining marks, so `Café` and `cafe` compare equal
+pub fn strip_diacritics(text: &str, language: FoldLanguage) -> String {
+    fold(text, language)
+        .nfd()
+        .filter(|c| !unicode_normalization::char::is_combining_mark(*c))
+        .collect()
+}
+
+/// Byte ranges of `text` matching `query`, exactly after folding or, with
+/// `fuzzy`, word by word ignoring accents and allowing small typos
+pub fn find_matches(text: &str, query: &str, fuzzy: bool) -> Vec<Range<usize>> {
+    if fuzzy {
+        find_fuzzy(text, query)
+    } else {
+        find_folded(text, query)
+    }
+}
+
+/// Edits tolerated between a query word and a document word: none for short
+/// words, where a typo is as likely to be a different word, more for long ones
+fn typo_tolerance(word: &str) -> usize {
+    match word.chars().count() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+/// Runs of consecutive words in `text` matching the words of `query` in order.
+/// A word matches when it contains the query word or is within
+/// [`typo_tolerance`] edits of it.
+pub fn find_fuzzy(text: &str, query: &str) -> Vec<Range<usize>> {
+    let language = detect_language(text);
+    let query_words: Vec<String> = query
+        .unicode_words()
+        .map(|word| strip_diacritics(word, language))
+        .collect();
+    if query_words.is_empty() {
+        return Vec::new();
+    }
+
+    let words: Vec<(Range<usize>, String)> = text
+        .split_word_bound_indices()
+        .filter(|(_, word)| word.chars().any(char::is_alphanumeric))
+        .map(|(start, word)| (start..start + word.len(), strip_diacritics(word, language)))
+        .collect();
+
+    let mut ranges = Vec::new();
+    let mut index = 0;
+    while index + query_words.len() <= words.len() {
+        let window = &words[index..index + query_words.len()];
+        let matched = window.iter().zip(&query_words).all(|((_, word), wanted)| {
+            word.contains(wanted.as_str()) || edit_distance(word, wanted) <= typo_tolerance(wanted)
+        });
+        if matched {
+            ranges.push(window[0].0.start..window[window.len() - 1].0.end);
+            index += query_words.len();
+        } else {
+            index += 1;
+        }
+    }
+    ranges
+}
+
+/// Levenshtein distance between two strings, counted in characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_folds_sharp_s_ligatures_and_accents() {
+        assert_eq!(find_folded("Die Straße ist lang", "strasse"), vec![4..11]);
+        assert_eq!(find_folded("STRASSE", "straße"), vec![0..7]);
+        assert_eq!(find_folded("ﬁle.txt", "file"), vec![0..5]);
+        // Precomposed é in the text, e + combining acute in the query, and back
+        assert_eq!(find_folded("Café au lait", "cafe\u{301}"), vec![0..5]);
+        assert_eq!(find_folded("Cafe\u{301} au lait", "CAFÉ"), vec![0..6]);
+        assert!(find_folded("Cafe au lait", "café").is_empty());
+    }
+
+    #[test]
+    fn test_turkic_dotted_and_dotless_i() {
+        assert_eq!(detect_language("Diyarbakır'da"), FoldLanguage::Turkic);
+        // In Turkish text, I is the capital of ı and İ the capital of i
+        assert_eq!(
+            find_folded("Kırşehir ISPARTA İzmir", "ısparta"),
+            vec![11..18]
+        );
+        assert_eq!(find_folded("Kırşehir ISPARTA İzmir", "izmir").len(), 1);
+        assert_eq!(detect_language("Istanbul"), FoldLanguage::Default);
+        assert_eq!(find_folded("Istanbul", "istanbul"), vec![0..8]);
+    }
+
+    #[test]
+    fn test_matches_do_not_overlap() {
+        assert_eq!(find_folded("aaaa", "aa"), vec![0..2, 2..4]);
+        assert!(find_folded("anything", "").is_empty());
+    }
+
+    #[test]
+    fn test_fuzzy_ignores_accents_and_typos() {
+        let text = "Send the invoice to the billing address of the Café";
+        assert_eq!(find_fuzzy(text, "adress"), vec![32..39]);
+        assert_eq!(find_fuzzy(text, "billing adress"), vec![24..39]);
+        assert_eq!(find_fuzzy(text, "cafe"), vec![47..52]);
+        // Short words must match exactly, long ones tolerate two edits
+        assert!(find_fuzzy(text, "tha").is_empty());
+        assert_eq!(find_fuzzy("Accommodation", "acomodation"), vec![0..13]);
+        assert!(find_fuzzy(text, "shipping").is_empty());
+        assert!(find_matches(text, "adress", false).is_empty());
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+}
@@ -0,0 +1,435 @@
+//! Paragraph layout for the terminal.
+//!
+//! [`layout_paragraph`] breaks a paragraph into lines of a given width and
+//! aligns them as the document does: justified paragraphs are spread to both
+//! margins, centered and right-aligned ones padded on the left.
+//!
+//! Lines are broken greedily, each filled in turn, or optimally: in the
+//! manner of Knuth and Plass, all breaks are chosen together so that the
+//! slack left on each line but the last is as even as possible, which keeps
+//! justified text from opening wide gaps. With hyphenation, long words may
+//! also break between syllables. No dictionaries ship with doxx, so the
+//! syllables come from a vowel and consonant heuristic that suits English
+//! and most Latin-script languages.
+
+use serde::{Deserialize, Serialize};
+
+use crate::document::TextAlignment;
+use crate::text_width::{display_width, truncate_to_width};
+
+/// How a paragraph is broken into lines
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WrapAlgorithm {
+    /// Fill each line before starting the next
+    #[default]
+    Greedy,
+    /// Choose the breaks together for even lines (Knuth–Plass)
+    Optimal,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LayoutOptions {
+    pub algorithm: WrapAlgorithm,
+    /// Break long words between syllables
+    pub hyphenate: bool,
+    /// Follow the paragraph's alignment; otherwise every line is left-aligned
+    pub align: bool,
+}
+
+/// Cost of ending a line with a hyphen, in squared columns of slack
+const HYPHEN_PENALTY: u64 = 50;
+/// Shortest word hyphenation is tried on, and the fewest letters left on
+/// either side of a break
+const MIN_HYPHENATED: usize = 6;
+const MIN_BEFORE_HYPHEN: usize = 2;
+const MIN_AFTER_HYPHEN: usize = 3;
+
+/// What follows a piece of text, and what a line break there leaves
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Join {
+    /// A space between words, dropped at a break
+    Space,
+    /// A syllable boundary: nothing, or a hyphen at a break
+    Hyphen,
+    /// Nothing either way (after a `-` in the text, or inside a word too
+    /// wide for any line)
+    None,
+}
+
+#[derive(Debug)]
+struct Piece<'a> {
+    text: &'a str,
+    width: usize,
+    join: Join,
+}
+
+/// Lines of `text` at most `width` columns wide, the first starting after
+/// `indent` spaces. Runs of whitespace collapse to one space.
+pub fn layout_paragraph(
+    text: &str,
+    width: usize,
+    indent: usize,
+    alignment: TextAlignment,
+    options: &LayoutOptions,
+) -> Vec<String> {
+    let width = width.max(1);
+    let indent = indent.min(width - 1);
+    let pieces = pieces(text, width, options.hyphenate);
+    if pieces.is_empty() {
+        return vec![String::new()];
+    }
+
+    let room = |start: usize| if start == 0 { width - indent } else { width };
+    let breaks = match options.algorithm {
+        WrapAlgorithm::Greedy => greedy_breaks(&pieces, room),
+        WrapAlgorithm::Optimal => optimal_breaks(&pieces, room),
+    };
+    let alignment = if options.align {
+        alignment
+    } else {
+        TextAlignment::Left
+    };
+
+    let mut lines = Vec::with_capacity(breaks.len());
+    let mut start = 0;
+    for (number, &end) in breaks.iter().enumerate() {
+        let last = end == pieces.len();
+        let mut line = if number == 0 {
+            " ".repeat(indent)
+        } else {
+            String::new()
+        };
+        line.push_str(&aligned(&pieces[start..end], room(start), alignment, last));
+        lines.push(line);
+        start = end;
+    }
+    lines
+}
+
+/// The words of `text`, split at syllables when hyphenating, after hyphens,
+/// and wherever a word is wider than `width`
+fn pieces(text: &str, width: usize, hyphenate: bool) -> Vec<Piece<'_>> {
+    let mut pieces = Vec::new();
+    for word in text.split_whitespace() {
+        let mut cuts: Vec<(usize, Join)> = word
+            .match_indices('-')
+            .map(|(offset, _)| (offset + 1, Join::None))
+            .filter(|&(offset, _)| offset > 1 && offset < word.len())
+            .collect();
+        if hyphenate {
+            cuts.extend(
+                syllable_breaks(word)
+                    .into_iter()
+                    .map(|at| (at, Join::Hyphen)),
+            );
+            cuts.sort_by_key(|&(offset, _)| offset);
+        }
+
+        let mut start = 0;
+        for (end, join) in cuts.into_iter().chain([(word.len(), Join::Space)]) {
+            let mut rest = &word[start..end];
+            // Split what no line can hold
+            while display_width(rest) > width {
+                let part = truncate_to_width(rest, width);
+                let part = if part.is_empty() {
+                    // A double-width character in a one-column view
+                    &rest[..rest.chars().next().map_or(rest.len(), char::len_utf8)]
+                } else {
+                    part
+                };
+                pieces.push(Piece {
+                    text: part,
+                    width: display_width(part),
+                    join: Join::None,
+                });
+                rest = &rest[part.len()..];
+            }
+            if !rest.is_empty() {
+                pieces.push(Piece {
+                    text: rest,
+                    width: display_width(rest),
+                    join,
+                });
+            }
+            start = end;
+        }
+    }
+    pieces
+}
+
+/// Columns taken by `pieces` set on one line, with a hyphen if it ends at a
+/// syllable break
+fn line_width(pieces: &[Piece], last: bool) -> usize {
+    let words: usize = pieces.iter().map(|piece| piece.width).sum();
+    let spaces = pieces[..pieces.len() - 1]
+        .iter()
+        .filter(|piece| piece.join == Join::Space)
+        .count();
+    let hyphen = !last && pieces[pieces.len() - 1].join == Join::Hyphen;
+    words + spaces + usize::from(hyphen)
+}
+
+/// End of each line (an index into `pieces`), filling every line as far as it goes
+fn greedy_breaks(pieces: &[Piece], room: impl Fn(usize) -> usize) -> Vec<usize> {
+    let mut breaks = Vec::new();
+    let mut start = 0;
+    while start < pieces.len() {
+        let mut end = start + 1;
+        while end < pieces.len()
+            && line_width(&pieces[start..=end], end + 1 == pieces.len()) <= room(start)
+        {
+            end += 1;
+        }
+        breaks.push(end);
+        start = end;
+    }
+    breaks
+}
+
+/// End of each line, chosen to minimise the sum over all lines but the last
+/// of their slack squared, plus a penalty for each hyphen
+fn optimal_breaks(pieces: &[Piece], room: impl Fn(usize) -> usize) -> Vec<usize> {
+    let count = pieces.len();
+    // Least cost of setting the first `end` pieces, and where its last line starts
+    let mut best: Vec<(u64, usize)> = vec![(u64::MAX, 0); count + 1];
+    best[0] = (0, 0);
+
+    for end in 1..=count {
+        let last = end == count;
+        for start in (0..end).rev() {
+            let used = line_width(&pieces[start..end], last);
+            let room = room(start);
+            // A single piece always gets a line, even if it overflows it
+            if used > room && start + 1 < end {
+                break;
+            }
+            if best[start].0 == u64::MAX {
+                continue;
+            }
+            let slack = room.saturating_sub(used) as u64;
+            let mut cost = if last { 0 } else { slack * slack };
+            if !last && pieces[end - 1].join == Join::Hyphen {
+                cost += HYPHEN_PENALTY;
+            }
+            let total = best[start].0.saturating_add(cost);
+            if total < best[end].0 {
+                best[end] = (total, start);
+            }
+        }
+    }
+
+    let mut breaks = Vec::new();
+    let mut end = count;
+    while end > 0 {
+        breaks.push(end);
+        end = best[end].1;
+    }
+    breaks.reverse();
+    breaks
+}
+
+/// `pieces` set as one line in `room` columns
+fn aligned(pieces: &[Piece], room: usize, alignment: TextAlignment, last: bool) -> String {
+    let used = line_width(pieces, last);
+    let slack = room.saturating_sub(used);
+    let gaps = pieces[..pieces.len() - 1]
+        .iter()
+        .filter(|piece| piece.join == Join::Space)
+        .count();
+    // Justified lines spread the slack over their spaces, earlier ones first
+    let (extra, wider) = match alignment {
+        TextAlignment::Justify if !last && gaps > 0 => (slack / gaps, slack % gaps),
+        _ => (0, 0),
+    };
+
+    let mut line = match alignment {
+        TextAlignment::Right => " ".repeat(slack),
+        TextAlignment::Center => " ".repeat(slack / 2),
+        TextAlignment::Left | TextAlignment::Justify => String::new(),
+    };
+    let mut gap = 0;
+    for (i, piece) in pieces.iter().enumerate() {
+        line.push_str(piece.text);
+        if i + 1 == pieces.len() {
+            if !last && piece.join == Join::Hyphen {
+                line.push('-');
+            }
+        } else if piece.join == Join::Space {
+            line.push_str(&" ".repeat(1 + extra + usize::from(gap < wider)));
+            gap += 1;
+        }
+    }
+    line
+}
+
+fn is_vowel(c: char) -> bool {
+    "aeiouyàáâãäåæèéêëìíîïòóôõöøœùúûüý".contains(c.to_lowercase().next().unwrap_or(c))
+}
+
+/// Byte offsets in `word` where it may be hyphenated: before a consonant
+/// between two vowels (`ba-con`), or between two consonants between vowels
+/// (`bas-ket`), keeping pairs such as `th` and `ck` together (`ge-ther`). Punctuation
+/// around the word is left out; words with digits or other symbols inside
+/// aren't hyphenated.
+fn syllable_breaks(word: &str) -> Vec<usize> {
+    let core_start = word.find(char::is_alphabetic).unwrap_or(word.len());
+    let core_end = word.rfind(char::is_alphabetic).map_or(core_start, |at| {
+        at + word[at..].chars().next().map_or(0, char::len_utf8)
+    });
+    let core = &word[core_start..core_end];
+    let letters: Vec<(usize, char)> = core.char_indices().collect();
+    if letters.len() < MIN_HYPHENATED || !letters.iter().all(|(_, c)| c.is_alphabetic()) {
+        return Vec::new();
+    }
+
+    const DIGRAPHS: [&str; 8] = ["ch", "ck", "gh", "ph", "qu", "sh", "th", "wh"];
+    let consonant = |i: usize| !is_vowel(letters[i].1);
+    let vowel = |i: usize| is_vowel(letters[i].1);
+    let digraph = |i: usize| {
+        let pair: String = [letters[i].1, letters[i + 1].1]
+            .iter()
+            .flat_map(|c| c.to_lowercase())
+            .collect();
+        DIGRAPHS.contains(&pair.as_str())
+    };
+    let mut breaks = Vec::new();
+    let candidates = letters
+        .iter()
+        .enumerate()
+        .take(letters.len() + 1 - MIN_AFTER_HYPHEN)
+        .skip(MIN_BEFORE_HYPHEN);
+    for (i, &(offset, _)) in candidates {
+        // A pair such as `th` counts as one consonant
+        let before_single =
+            vowel(i - 1) && consonant(i) && (vowel(i + 1) || (digraph(i) && vowel(i + 2)));
+        let between_pair =
+            vowel(i - 2) && consonant(i - 1) && consonant(i) && vowel(i + 1) && !digraph(i - 1);
+        if before_single || between_pair {
+            breaks.push(core_start + offset);
+        }
+    }
+    breaks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(algorithm: WrapAlgorithm, hyphenate: bool) -> LayoutOptions {
+        LayoutOptions {
+            algorithm,
+            hyphenate,
+            align: true,
+        }
+    }
+
+    #[test]
+    fn test_syllable_breaks() {
+        let hyphenated = |word: &str| {
+            let mut text = word.to_string();
+            for at in syllable_breaks(word).into_iter().rev() {
+                text.insert(at, '-');
+            }
+            text
+        };
+        assert_eq!(hyphenated("hyphenation"), "hy-phe-na-tion");
+        assert_eq!(hyphenated("basketball,"), "bas-ket-ball,");
+        assert_eq!(hyphenated("(together)"), "(to-ge-ther)");
+        assert_eq!(hyphenated("short"), "short");
+        assert_eq!(hyphenated("v1.2.3-beta"), "v1.2.3-beta");
+    }
+
+    #[test]
+    fn test_alignment() {
+        let text = "the quick brown fox jumps over the lazy dog";
+        let greedy = options(WrapAlgorithm::Greedy, false);
+        assert_eq!(
+            layout_paragraph(text, 16, 0, TextAlignment::Justify, &greedy),
+            vec!["the  quick brown", "fox  jumps  over", "the lazy dog"]
+        );
+        assert_eq!(
+            layout_paragraph(text, 16, 0, TextAlignment::Right, &greedy),
+            vec![" the quick brown", "  fox jumps over", "    the lazy dog"]
+        );
+        assert_eq!(
+            layout_paragraph("centered", 12, 0, TextAlignment::Center, &greedy),
+            vec!["  centered"]
+        );
+        // The indent takes room from the first line only
+        assert_eq!(
+            layout_paragraph(text, 16, 2, TextAlignment::Left, &greedy),
+            vec!["  the quick", "brown fox jumps", "over the lazy", "dog"]
+        );
+        // Not following the document, everything is left-aligned
+        let plain = LayoutOptions {
+            align: false,
+            ..greedy
+        };
+        assert_eq!(
+            layout_paragraph(text, 16, 0, TextAlignment::Justify, &plain)[0],
+            "the quick brown"
+        );
+        // Double-width text is measured in columns
+        assert_eq!(
+            layout_paragraph("日本語 テキスト", 9, 0, TextAlignment::Right, &greedy),
+            vec!["   日本語", " テキスト"]
+        );
+    }
+
+    #[test]
+    fn test_optimal_breaks_even_out_lines() {
+        let text = "aaa bb cc ddddd";
+        // Greedy fills the first line and leaves the second short
+        assert_eq!(
+            layout_paragraph(
+                text,
+                6,
+                0,
+                TextAlignment::Left,
+                &options(WrapAlgorithm::Greedy, false)
+            ),
+            vec!["aaa bb", "cc", "ddddd"]
+        );
+        assert_eq!(
+            layout_paragraph(
+                text,
+                6,
+                0,
+                TextAlignment::Left,
+                &options(WrapAlgorithm::Optimal, false)
+            ),
+            vec!["aaa", "bb cc", "ddddd"]
+        );
+    }
+
+    #[test]
+    fn test_hyphenation_and_long_words() {
+        let hyphenating = options(WrapAlgorithm::Greedy, true);
+        assert_eq!(
+            layout_paragraph(
+                "a hyphenation test",
+                10,
+                0,
+                TextAlignment::Left,
+                &hyphenating
+            ),
+            vec!["a hyphena-", "tion test"]
+        );
+        // Existing hyphens are break points too, and words wider than a line
+        // are split
+        let plain = options(WrapAlgorithm::Optimal, false);
+        assert_eq!(
+            layout_paragraph("well-known", 6, 0, TextAlignment::Left, &plain),
+            vec!["well-", "known"]
+        );
+        assert_eq!(
+            layout_paragraph("abcdefghij", 4, 0, TextAlignment::Justify, &plain),
+            vec!["abcd", "efgh", "ij"]
+        );
+        assert_eq!(
+            layout_paragraph("   ", 10, 0, TextAlignment::Left, &plain),
+            vec![""]
+        );
+    }
+}
@@ -0,0 +1,167 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::document::TextAlignment;
+
+/// Marker appended to text cut by [`ellipsize`]
+pub const ELLIPSIS: &str = "…";
+
+/// Terminal columns a string occupies (CJK and most emoji take two)
+pub fn display_width(text: &str) -> usize {
+    text.width()
+}
+
+/// Longest prefix of `text` that fits in `max_width` columns, never splitting
+/// a grapheme cluster or a double-width character
+pub fn truncate_to_width(text: &str, max_width: usize) -> &str {
+    let mut width = 0;
+    for (offset, grapheme) in text.grapheme_indices(true) {
+        width += grapheme.width();
+        if width > max_width {
+            return &text[..offset];
+        }
+    }
+    text
+}
+
+/// What is left of `text` after its first `columns` columns, for scrolling
+/// it sideways. A double-width character cut in half leaves a space, so the
+/// rest stays in its column.
+pub fn skip_width(text: &str, columns: usize) -> String {
+    let mut width = 0;
+    for (offset, grapheme) in text.grapheme_indices(true) {
+        if width >= columns {
+            let padding = " ".repeat(width - columns);
+            return padding + &text[offset..];
+        }
+        width += grapheme.width();
+    }
+    String::new()
+}
+
+/// Fit `text` into `max_width` columns, ending it with `…` when it has to be cut
+pub fn ellipsize(text: &str, max_width: usize) -> String {
+    if display_width(text) <= max_width {
+        return text.to_string();
+    }
+    if max_width == 0 {
+        return String::new();
+    }
+    let kept = truncate_to_width(text, max_width - ELLIPSIS.width());
+    format!("{}{ELLIPSIS}", kept.trim_end())
+}
+
+/// Greedy word wrap to `width` columns. The first line starts with
+/// `first_indent` and the rest with `rest_indent`; runs of whitespace collapse
+/// to one space and words too wide for a line are split between graphemes.
+pub fn wrap(text: &str, width: usize, first_indent: &str, rest_indent: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = first_indent.to_string();
+    let mut line_width = display_width(first_indent);
+    let mut line_empty = true;
+
+    for word in text.split_whitespace() {
+        let word_width = display_width(word);
+        if !line_empty && line_width + 1 + word_width > width {
+            lines.push(std::mem::replace(&mut line, rest_indent.to_string()));
+            line_width = display_width(rest_indent);
+            line_empty = true;
+        }
+
+        let mut word = word;
+        while line_empty && line_width + display_width(word) > width {
+            let piece = truncate_to_width(word, width.saturating_sub(line_width));
+            if piece.is_empty() {
+                // Not even one grapheme fits after the indent; let it overflow
+                break;
+            }
+            line.push_str(piece);
+            lines.push(std::mem::replace(&mut line, rest_indent.to_string()));
+            line_width = display_width(rest_indent);
+            word = &word[piece.len()..];
+        }
+        if word.is_empty() {
+            continue;
+        }
+
+        if !line_empty {
+            line.push(' ');
+            line_width += 1;
+        }
+        line.push_str(word);
+        line_width += display_width(word);
+        line_empty = false;
+    }
+
+    if !line_empty || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// `text`, trimmed, padded with spaces to `width` columns as table cells are
+/// aligned. Justified text is left-aligned; text already wider is left as is.
+pub fn align_to_width(text: &str, alignment: TextAlignment, width: usize) -> String {
+    let text = text.trim();
+    let padding = width.saturating_sub(display_width(text));
+    let left = match alignment {
+        TextAlignment::Left | TextAlignment::Justify => 0,
+        TextAlignment::Right => padding,
+        TextAlignment::Center => padding / 2,
+    };
+    format!("{}{text}{}", " ".repeat(left), " ".repeat(padding - left))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncates_by_display_columns() {
+        assert_eq!(truncate_to_width("hello", 3), "hel");
+        // Each CJK character is two columns wide and is never split
+        assert_eq!(truncate_to_width("日本語テキスト", 5), "日本");
+        assert_eq!(truncate_to_width("a👍b", 2), "a");
+        // Combining marks stay with their base character
+        assert_eq!(truncate_to_width("e\u{301}e\u{301}", 1), "e\u{301}");
+
+        assert_eq!(skip_width("hello", 2), "llo");
+        assert_eq!(skip_width("日本語", 2), "本語");
+        assert_eq!(skip_width("日本語", 3), " 語");
+        assert_eq!(skip_width("abc", 5), "");
+    }
+
+    #[test]
+    fn test_ellipsize_fits_width() {
+        assert_eq!(ellipsize("short", 10), "short");
+        assert_eq!(ellipsize("Quarterly report", 8), "Quarter…");
+        assert_eq!(ellipsize("日本語テキスト", 6), "日本…");
+        assert!(display_width(&ellipsize("日本語テキスト", 6)) <= 6);
+        assert_eq!(ellipsize("anything", 0), "");
+    }
+
+    #[test]
+    fn test_align_to_width() {
+        assert_eq!(align_to_width(" 42 ", TextAlignment::Right, 5), "   42");
+        assert_eq!(align_to_width("ab", TextAlignment::Center, 5), " ab  ");
+        // Double-width characters count as two columns
+        assert_eq!(align_to_width("東京", TextAlignment::Left, 6), "東京  ");
+        assert_eq!(align_to_width("👍", TextAlignment::Center, 4), " 👍 ");
+        assert_eq!(align_to_width("日本語", TextAlignment::Right, 4), "日本語");
+    }
+
+    #[test]
+    fn test_wrap_with_indents() {
+        assert_eq!(
+            wrap("the quick brown fox jumps", 12, "- ", "  "),
+            vec!["- the quick", "  brown fox", "  jumps"]
+        );
+        // Double-width text wraps by columns and long words are split
+        assert_eq!(
+            wrap("日本語 テキスト", 8, "", ""),
+            vec!["日本語", "テキスト"]
+        );
+        assert_eq!(wrap("abcdefghij", 4, "", ""), vec!["abcd", "efgh", "ij"]);
+        assert_eq!(wrap("", 10, "> ", ""), vec!["> "]);
+    }
+}
@@ -0,0 +1,126 @@
+use crate::document::{Document, DocumentElement};
+
+/// Direction of punctuation conversion applied before export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypographyMode {
+    /// Curly quotes, dashes and ellipses become plain ASCII
+    Ascii,
+    /// Straight quotes, `--` and `...` become typographic punctuation
+    Smart,
+}
+
+/// Convert punctuation in a piece of text
+pub fn normalize_typography(text: &str, mode: TypographyMode) -> String {
+    match mode {
+        TypographyMode::Ascii => to_ascii(text),
+        TypographyMode::Smart => to_smart(text),
+    }
+}
+
+fn to_ascii(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' | '\u{2032}' => output.push('\''),
+            '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' | '\u{2033}' => output.push('"'),
+            '\u{2014}' | '\u{2015}' => output.push_str("--"),
+            '\u{2013}' | '\u{2012}' | '\u{2010}' | '\u{2011}' => output.push('-'),
+            '\u{2026}' => output.push_str("..."),
+            '\u{00A0}' | '\u{2007}' | '\u{202F}' => output.push(' '),
+            _ => output.push(c),
+        }
+    }
+    output
+}
+
+fn to_smart(text: &str) -> String {
+    let text = text.replace("...", "\u{2026}").replace("--", "\u{2014}");
+    let mut output = String::with_capacity(text.len());
+    let mut previous: Option<char> = None;
+    for c in text.chars() {
+        // A quote opens when it follows whitespace, an opening bracket or another opener
+        let opening = previous.is_none_or(|p| {
+            p.is_whitespace() || matches!(p, '(' | '[' | '{' | '\u{2014}' | '\u{201C}' | '\u{2018}')
+        });
+        match c {
+            '"' if opening => output.push('\u{201C}'),
+            '"' => output.push('\u{201D}'),
+            '\'' if opening => output.push('\u{2018}'),
+            '\'' => output.push('\u{2019}'),
+            _ => output.push(c),
+        }
+        previous = Some(c);
+    }
+    output
+}
+
+/// Convert punctuation throughout a document's title and text content
+pub fn apply_typography(document: &mut Document, mode: TypographyMode) {
+    let convert = |text: &mut String| *text = normalize_typography(text, mode);
+
+    convert(&mut document.title);
+    for element in &mut document.elements {
+        match element {
+            DocumentElement::Heading { text, .. } => convert(text),
+            DocumentElement::Paragraph { text, runs, .. } => {
+                convert(text);
+                for run in runs.iter_mut().filter(|run| run.math.is_none()) {
+                    convert(&mut run.text);
+                }
+            }
+            DocumentElement::List { items, .. } => {
+                for item in items {
+                    convert(&mut item.text);
+                }
+            }
+            DocumentElement::Table { table } => {
+                for cell in table
+                    .headers
+                    .iter_mut()
+                    .chain(table.rows.iter_mut().flatten())
+                {
+                    convert(&mut cell.content);
+                }
+                if let Some(title) = &mut table.metadata.title {
+                    convert(title);
+                }
+            }
+            DocumentElement::Image { description, .. } => convert(description),
+            DocumentElement::Quote { text, .. }
+            | DocumentElement::Caption { text, .. }
+            | DocumentElement::Title { text, .. }
+            | DocumentElement::Subtitle { text, .. } => convert(text),
+            // Code and equations keep their quotes and dashes as written
+            DocumentElement::CodeBlock { .. } | DocumentElement::Equation { .. } => {}
+            DocumentElement::PageBreak => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_smart_punctuation_to_ascii() {
+        assert_eq!(
+            normalize_typography(
+                "\u{201C}It\u{2019}s done\u{201D} \u{2014} mostly\u{2026}",
+                TypographyMode::Ascii
+            ),
+            "\"It's done\" -- mostly..."
+        );
+        assert_eq!(
+            normalize_typography("pages 3\u{2013}5", TypographyMode::Ascii),
+            "pages 3-5"
+        );
+    }
+
+    #[test]
+    fn test_ascii_to_smart_punctuation() {
+        assert_eq!(
+            normalize_typography("\"It's done\" -- ('mostly')...", TypographyMode::Smart),
+            "\u{201C}It\u{2019}s done\u{201D} \u{2014} (\u{2018}mostly\u{2019})\u{2026}"
+        );
+    }
+}
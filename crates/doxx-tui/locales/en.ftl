@@ -0,0 +1,250 @@
+# doxx viewer messages (English)
+#
+# This catalog is the reference for translations: copy it to <language>.ftl,
+# translate the text to the right of each `=`, and keep the message ids and
+# { $variables } as they are. Messages a translation leaves out are shown in
+# English.
+
+## Status messages
+
+status-page-past-end = Page { $page } is past the end of the document ({ $pages } pages)
+status-parse-warnings =
+    { $count ->
+        [one] ⚠ 1 parse warning (run with --warnings for details)
+       *[other] ⚠ { $count } parse warnings (run with --warnings for details)
+    }
+status-image-failed = Failed to load { $path }: { $error }
+status-oldest-jump = Already at the oldest jump
+status-newest-jump = Already at the newest jump
+status-no-next-heading = No next heading
+status-no-previous-heading = No previous heading
+status-heading-jump-cancelled = Heading jump cancelled: { $key }{ $key } for the same level, { $key }h for any level
+status-ruler-on = Reading ruler on (j/k move the line)
+status-ruler-off = Reading ruler off
+status-reading-mode-on = Reading mode ({ $width } columns, +/- to adjust, R to leave)
+status-reading-mode-off = Reading mode off
+status-reading-width = Reading width: { $width } columns
+status-no-hidden-text = No hidden text in this document
+status-hidden-text-on =
+    { $count ->
+        [one] Highlighting 1 run of hidden text
+       *[other] Highlighting { $count } runs of hidden text
+    }
+status-hidden-text-off = Hidden text shown like the rest
+status-copied = Copied to clipboard!
+status-copied-table = Copied table to clipboard (TSV)!
+status-copied-table-csv = Copied table to clipboard (CSV)!
+status-no-table = No table here to copy (scroll one to the top, or use the ruler)
+status-no-table-open = No table here to open (scroll one to the top, or use the ruler)
+status-no-image = No image here to preview (scroll one into view)
+status-image-not-loaded = Images aren't loaded; start doxx with --images to preview them
+status-last-column = The last column shown can't be hidden
+status-tables-wrap = Tables fit the view, with long cells wrapped
+status-tables-truncate = Tables fit the view, with long cells cut short
+status-tables-scroll = Tables at full width (< and > scroll them)
+status-tables-fitted = Tables fit the view; press w for full width
+status-copied-selection-text =
+    { $count ->
+        [one] Copied 1 element to clipboard as text!
+       *[other] Copied { $count } elements to clipboard as text!
+    }
+status-copied-selection-markdown =
+    { $count ->
+        [one] Copied 1 element to clipboard as Markdown!
+       *[other] Copied { $count } elements to clipboard as Markdown!
+    }
+status-exported =
+    { $count ->
+        [one] Exported 1 element to { $path }
+       *[other] Exported { $count } elements to { $path }
+    }
+status-export-failed = Export failed: { $error }
+status-export-unknown-format = Name the file .md, .txt, .csv or .json to pick the export format
+status-export-no-tables = No tables to export as CSV
+status-copy-failed = Failed to copy to clipboard.
+status-no-clipboard = Clipboard not available.
+status-clipboard-empty = The clipboard has no text to search for
+status-fuzzy-on = Fuzzy search on (accents ignored, small typos allowed)
+status-fuzzy-off = Fuzzy search off
+status-scope = Searching only { $scope } (Ctrl-T to change)
+status-scope-all = Searching the whole document
+status-resumed = Resumed last reading position (--fresh to start over)
+status-no-bookmarks = No bookmarks set (m + letter to add one)
+status-mark-prompt = Mark: press a lowercase letter (Esc to cancel)
+status-bookmark-set = Bookmark '{ $name }' set
+status-bookmark-unset = Bookmark '{ $name }' is not set
+status-go-to-invalid = Go to a page (42), a percentage (50%), a heading number (3.2, or 3. for a top-level one) or a figure (Figure 3)
+status-figure-not-found = No { $figure } in this document
+status-no-figures = No numbered figures or tables in this document
+status-heading-not-found = No heading numbered { $number }
+status-command-finished = Command finished ({ $status })
+status-no-file = This document wasn't opened from a file
+status-no-editor = Set ui.editor, $VISUAL or $EDITOR to edit the document
+status-editor-failed = Failed to start the editor: { $error }
+status-editor-unchanged = Document not saved yet; it reloads when the file changes
+status-reloaded = Document reloaded
+status-reload-failed = Reload failed: { $error }
+status-reload-unavailable = The file changed; reopen it to see the changes
+status-loading = Loading…
+status-loading-progress = Loading… { $parsed } of { $total } paragraphs and tables
+status-ask-failed = Couldn't answer: { $error }
+
+## Document hygiene banner
+
+hygiene-banner = ⚠ This document contains { $findings } (doxx --hygiene for details, Esc to dismiss)
+hygiene-macros = macros
+hygiene-external-templates =
+    { $count ->
+        [one] an external template
+       *[other] { $count } external templates
+    }
+hygiene-remote-images =
+    { $count ->
+        [one] a remote image
+       *[other] { $count } remote images
+    }
+hygiene-external-content =
+    { $count ->
+        [one] a linked object
+       *[other] { $count } linked objects
+    }
+hygiene-tracked-changes =
+    { $count ->
+        [one] 1 unresolved tracked change
+       *[other] { $count } unresolved tracked changes
+    }
+hygiene-hidden-text =
+    { $count ->
+        [one] 1 run of hidden text
+       *[other] { $count } runs of hidden text
+    }
+hygiene-invisible-text =
+    { $count ->
+        [one] 1 run colored like its background
+       *[other] { $count } runs colored like their background
+    }
+
+## Copied content
+
+copy-no-results = No search results to copy.
+copy-results-heading = Search results for '{ $query }':
+copy-outline-heading = Document Outline:
+copy-unavailable = Content not available for copying in this view.
+
+## Views and labels
+
+view-document = 📄 Document
+view-outline = 📋 Outline
+view-search = 🔍 Search
+view-jump = 🧭 Jump
+view-table = 📊 Table
+view-image = 🖼 Image
+view-help = ❓ Help
+title-document = 📄 doxx - { $title }
+title-outline = 📋 Document Outline
+title-search = 🔍 Search
+title-search-fuzzy = 🔍 Search (fuzzy)
+scope-tables = tables
+scope-before-first-heading = before the first heading
+title-results = Results ({ $current }/{ $total })
+title-help = Help
+title-bookmarks = 🔖 Bookmarks • press a letter
+title-answer = 💬 { $question }
+answer-waiting = Asking { $model }…
+answer-sources = Sources:
+answer-help = Esc to close
+answer-help-sources = Enter or 1-9 to go to a source • Esc to close
+title-table = 📊 { $title } • columns { $first }–{ $last } of { $columns }
+title-image = 🖼 { $description } • { $width }×{ $height } px • { $zoom }%
+table-untitled = Table
+table-sum = Sum
+table-average = Average
+palette-scope-headings = headings
+palette-scope-all = headings + text
+palette-scope-figures = figures and tables
+palette-title = 🧭 Jump to ({ $scope }) • Tab to toggle
+palette-matches = { $shown } of { $total } matches
+image-available = [Image available - use --export text to view]
+image-not-extracted = [Image not extracted]
+preview-table =
+    { $rows ->
+        [one] Table (1 row)
+       *[other] Table ({ $rows } rows)
+    }
+preview-page-break = Page break
+file-unknown = Unknown
+
+## Status bar and key hints
+
+status-bar = { $view } • 📄 { $file } • { $section }Page { $page } of { $pages } • { $percent }% • { $minutes ->
+        [0] done
+       *[other] { $minutes } min left
+    } • { $words } words • { $element }/{ $elements }
+status-section = § { $title }
+status-search-matches = • 🔍 { $current }/{ $total } matches
+status-search-none = • 🔍 No matches
+prompt-export-document = Export to:
+prompt-export-section = Export section to:
+prompt-export-selection = Export selection to:
+key-hints = [↕] Scroll [o] Outline [s] Search [c] Copy [h] Help [q] Quit
+table-hint = [←→] Column [↕] Scroll [s] Sort [x] Hide column [X] Show all [Esc] Back
+image-hint = [+/-] Zoom [0] Fit [←→↕] Pan [Esc] Back
+selection-hint =
+    { $count ->
+        [one] Selecting 1 element • [j/k] Extend [y] Copy text [m] Copy Markdown [e] Export [Esc] Cancel
+       *[other] Selecting { $count } elements • [j/k] Extend [y] Copy text [m] Copy Markdown [e] Export [Esc] Cancel
+    }
+
+## Help
+
+help-title = 🆘 doxx - Help
+help-section-navigation = 📖 Document Navigation:
+help-scroll-up = Scroll up
+help-scroll-down = Scroll down
+help-page-up = Page up
+help-page-down = Page down
+help-heading-same-level = Previous / next heading, same level
+help-heading-any-level = Previous / next heading, any level
+help-start = Go to start
+help-end = Go to end
+help-go-to = Go to page (3), percentage, heading number (3.2 or 3.) or figure
+help-figures = List figures and tables by number
+help-jump-back = Back to previous position (jump list)
+help-jump-forward = Forward in jump list
+help-ruler = Toggle reading ruler (j/k move it)
+help-reading-mode = Toggle reading mode (centered text, no borders)
+help-reading-width = Widen / narrow the text in reading mode
+help-reveal-hidden = Highlight hidden and white-on-white text
+help-section-search = 🔍 Search:
+help-open-search = Open search
+help-search-document = Search in document (highlights matches)
+help-next-result = Next result
+help-previous-result = Previous result
+help-fuzzy = Toggle fuzzy search (accents, typos)
+help-search-scope = Search the current section / tables only / everything
+help-paste-search = Search for the text on the clipboard (in search)
+help-section-other = 📋 Other Features:
+help-outline = Show outline
+help-outline-pane = Toggle outline pane (Tab switches focus)
+help-copy = Copy content to clipboard (table at top: TSV)
+help-copy-table = Copy the current table as TSV / CSV
+help-select = Select elements to copy or export (j/k extend, y text, m Markdown, e export)
+help-export = Export the document / current section to a file (.md, .txt, .csv, .json)
+help-table-mode = Open the table in table mode (←→ columns, s sort, x/X hide/show)
+help-image-preview = Preview the image full screen (+/- zoom, arrows pan)
+help-table-layout = Tables: wrap cells, cut them short, or full width
+help-table-shift = Scroll full-width tables sideways
+help-palette = Jump to heading (fuzzy)
+help-set-bookmark = Set bookmark
+help-jump-bookmark = Jump to bookmark (' lists them)
+help-ask = Ask a question about the document (ai.)
+help-shell = Run shell command (% = file path)
+help-editor = Open the file in an editor and reload it when saved
+help-suspend = Suspend to shell
+help-toggle-help = Toggle help
+help-quit = Quit
+help-section-copy = 📄 Copy Functionality:
+help-copy-document = Document:  Copies full document as text
+help-copy-outline = Outline:   Copies document structure
+help-copy-search = Search:    Copies search results (use F2)
+help-close = Press any key to close help...
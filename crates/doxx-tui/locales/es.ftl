@@ -0,0 +1,245 @@
+# Mensajes del visor doxx (español)
+
+## Mensajes de estado
+
+status-page-past-end = La página { $page } está más allá del final del documento ({ $pages } páginas)
+status-parse-warnings =
+    { $count ->
+        [one] ⚠ 1 aviso de análisis (ejecute con --warnings para ver los detalles)
+       *[other] ⚠ { $count } avisos de análisis (ejecute con --warnings para ver los detalles)
+    }
+status-image-failed = No se pudo cargar { $path }: { $error }
+status-oldest-jump = Ya está en el salto más antiguo
+status-newest-jump = Ya está en el salto más reciente
+status-no-next-heading = No hay un título siguiente
+status-no-previous-heading = No hay un título anterior
+status-heading-jump-cancelled = Salto de título cancelado: { $key }{ $key } para el mismo nivel, { $key }h para cualquier nivel
+status-ruler-on = Regla de lectura activada (j/k mueven la línea)
+status-ruler-off = Regla de lectura desactivada
+status-reading-mode-on = Modo lectura ({ $width } columnas, +/- para ajustar, R para salir)
+status-reading-mode-off = Modo lectura desactivado
+status-reading-width = Ancho de lectura: { $width } columnas
+status-no-hidden-text = Este documento no tiene texto oculto
+status-hidden-text-on =
+    { $count ->
+        [one] Resaltando 1 fragmento de texto oculto
+       *[other] Resaltando { $count } fragmentos de texto oculto
+    }
+status-hidden-text-off = Texto oculto mostrado como el resto
+status-copied = ¡Copiado al portapapeles!
+status-copied-table = ¡Tabla copiada al portapapeles (TSV)!
+status-copied-table-csv = ¡Tabla copiada al portapapeles (CSV)!
+status-no-table = No hay ninguna tabla aquí para copiar (desplácela arriba o use la regla)
+status-no-table-open = No hay ninguna tabla aquí para abrir (desplácela arriba o use la regla)
+status-no-image = No hay ninguna imagen aquí para ver (desplácela a la vista)
+status-image-not-loaded = Las imágenes no están cargadas; inicie doxx con --images para verlas
+status-last-column = No se puede ocultar la última columna visible
+status-tables-wrap = Las tablas se ajustan a la vista, con las celdas largas en varias líneas
+status-tables-truncate = Las tablas se ajustan a la vista, con las celdas largas recortadas
+status-tables-scroll = Tablas a ancho completo (< y > las desplazan)
+status-tables-fitted = Las tablas se ajustan a la vista; pulse w para verlas a ancho completo
+status-copied-selection-text =
+    { $count ->
+        [one] ¡1 elemento copiado al portapapeles como texto!
+       *[other] ¡{ $count } elementos copiados al portapapeles como texto!
+    }
+status-copied-selection-markdown =
+    { $count ->
+        [one] ¡1 elemento copiado al portapapeles como Markdown!
+       *[other] ¡{ $count } elementos copiados al portapapeles como Markdown!
+    }
+status-exported =
+    { $count ->
+        [one] 1 elemento exportado a { $path }
+       *[other] { $count } elementos exportados a { $path }
+    }
+status-export-failed = Error al exportar: { $error }
+status-export-unknown-format = Use la extensión .md, .txt, .csv o .json para elegir el formato
+status-export-no-tables = No hay tablas que exportar como CSV
+status-copy-failed = No se pudo copiar al portapapeles.
+status-no-clipboard = El portapapeles no está disponible.
+status-clipboard-empty = El portapapeles no tiene texto que buscar
+status-fuzzy-on = Búsqueda aproximada activada (sin acentos, admite pequeñas erratas)
+status-fuzzy-off = Búsqueda aproximada desactivada
+status-scope = Buscando solo en { $scope } (Ctrl-T para cambiar)
+status-scope-all = Buscando en todo el documento
+status-resumed = Se reanudó la última posición de lectura (--fresh para empezar de nuevo)
+status-no-bookmarks = No hay marcadores (m + letra para añadir uno)
+status-mark-prompt = Marcador: pulse una letra minúscula (Esc para cancelar)
+status-bookmark-set = Marcador '{ $name }' establecido
+status-bookmark-unset = El marcador '{ $name }' no está establecido
+status-go-to-invalid = Ir a una página (42), un porcentaje (50%), un número de título (3.2, o 3. para uno de primer nivel) o una figura (Figura 3)
+status-figure-not-found = No hay { $figure } en este documento
+status-no-figures = Este documento no tiene figuras ni tablas numeradas
+status-heading-not-found = No hay ningún título con el número { $number }
+status-command-finished = Comando terminado ({ $status })
+status-no-file = Este documento no se abrió desde un archivo
+status-no-editor = Configura ui.editor, $VISUAL o $EDITOR para editar el documento
+status-editor-failed = No se pudo iniciar el editor: { $error }
+status-editor-unchanged = Documento aún sin guardar; se recargará cuando cambie el archivo
+status-reloaded = Documento recargado
+status-reload-failed = Error al recargar: { $error }
+status-reload-unavailable = El archivo cambió; vuelve a abrirlo para ver los cambios
+status-loading = Cargando…
+status-loading-progress = Cargando… { $parsed } de { $total } párrafos y tablas
+status-ask-failed = No se pudo responder: { $error }
+
+## Aviso de higiene del documento
+
+hygiene-banner = ⚠ Este documento contiene { $findings } (doxx --hygiene para ver los detalles, Esc para cerrar)
+hygiene-macros = macros
+hygiene-external-templates =
+    { $count ->
+        [one] una plantilla externa
+       *[other] { $count } plantillas externas
+    }
+hygiene-remote-images =
+    { $count ->
+        [one] una imagen remota
+       *[other] { $count } imágenes remotas
+    }
+hygiene-external-content =
+    { $count ->
+        [one] un objeto vinculado
+       *[other] { $count } objetos vinculados
+    }
+hygiene-tracked-changes =
+    { $count ->
+        [one] 1 cambio controlado sin resolver
+       *[other] { $count } cambios controlados sin resolver
+    }
+hygiene-hidden-text =
+    { $count ->
+        [one] 1 fragmento de texto oculto
+       *[other] { $count } fragmentos de texto oculto
+    }
+hygiene-invisible-text =
+    { $count ->
+        [one] 1 fragmento del color de su fondo
+       *[other] { $count } fragmentos del color de su fondo
+    }
+
+## Contenido copiado
+
+copy-no-results = No hay resultados de búsqueda que copiar.
+copy-results-heading = Resultados de búsqueda de '{ $query }':
+copy-outline-heading = Esquema del documento:
+copy-unavailable = No se puede copiar contenido en esta vista.
+
+## Vistas y etiquetas
+
+view-document = 📄 Documento
+view-outline = 📋 Esquema
+view-search = 🔍 Búsqueda
+view-jump = 🧭 Ir a
+view-table = 📊 Tabla
+view-image = 🖼 Imagen
+view-help = ❓ Ayuda
+title-document = 📄 doxx - { $title }
+title-outline = 📋 Esquema del documento
+title-search = 🔍 Búsqueda
+title-search-fuzzy = 🔍 Búsqueda (aproximada)
+scope-tables = tablas
+scope-before-first-heading = antes del primer encabezado
+title-results = Resultados ({ $current }/{ $total })
+title-help = Ayuda
+title-bookmarks = 🔖 Marcadores • pulse una letra
+title-answer = 💬 { $question }
+answer-waiting = Preguntando a { $model }…
+answer-sources = Fuentes:
+answer-help = Esc para cerrar
+answer-help-sources = Enter o 1-9 para ir a una fuente • Esc para cerrar
+title-table = 📊 { $title } • columnas { $first }–{ $last } de { $columns }
+title-image = 🖼 { $description } • { $width }×{ $height } px • { $zoom } %
+table-untitled = Tabla
+table-sum = Suma
+table-average = Media
+palette-scope-headings = títulos
+palette-scope-all = títulos + texto
+palette-scope-figures = figuras y tablas
+palette-title = 🧭 Ir a ({ $scope }) • Tab para cambiar
+palette-matches = { $shown } de { $total } coincidencias
+image-available = [Imagen disponible - use --export text para verla]
+image-not-extracted = [Imagen no extraída]
+preview-table =
+    { $rows ->
+        [one] Tabla (1 fila)
+       *[other] Tabla ({ $rows } filas)
+    }
+preview-page-break = Salto de página
+file-unknown = Desconocido
+
+## Barra de estado y teclas
+
+status-bar = { $view } • 📄 { $file } • { $section }Página { $page } de { $pages } • { $percent } % • { $minutes ->
+        [0] terminado
+       *[other] quedan { $minutes } min
+    } • { $words } palabras • { $element }/{ $elements }
+status-section = § { $title }
+status-search-matches = • 🔍 { $current }/{ $total } coincidencias
+status-search-none = • 🔍 Sin coincidencias
+prompt-export-document = Exportar a:
+prompt-export-section = Exportar la sección a:
+prompt-export-selection = Exportar la selección a:
+key-hints = [↕] Desplazar [o] Esquema [s] Buscar [c] Copiar [h] Ayuda [q] Salir
+table-hint = [←→] Columna [↕] Desplazar [s] Ordenar [x] Ocultar columna [X] Mostrar todas [Esc] Volver
+image-hint = [+/-] Zoom [0] Ajustar [←→↕] Desplazar [Esc] Volver
+selection-hint =
+    { $count ->
+        [one] 1 elemento seleccionado • [j/k] Ampliar [y] Copiar texto [m] Copiar Markdown [e] Exportar [Esc] Cancelar
+       *[other] { $count } elementos seleccionados • [j/k] Ampliar [y] Copiar texto [m] Copiar Markdown [e] Exportar [Esc] Cancelar
+    }
+
+## Ayuda
+
+help-title = 🆘 doxx - Ayuda
+help-section-navigation = 📖 Navegación:
+help-scroll-up = Subir
+help-scroll-down = Bajar
+help-page-up = Página anterior
+help-page-down = Página siguiente
+help-heading-same-level = Título anterior / siguiente, mismo nivel
+help-heading-any-level = Título anterior / siguiente, cualquier nivel
+help-start = Ir al principio
+help-end = Ir al final
+help-go-to = Ir a una página (3), un porcentaje, un número de título (3.2 o 3.) o una figura
+help-figures = Listar figuras y tablas por número
+help-jump-back = Volver a la posición anterior (lista de saltos)
+help-jump-forward = Avanzar en la lista de saltos
+help-ruler = Activar la regla de lectura (j/k la mueven)
+help-reading-mode = Activar el modo lectura (texto centrado, sin bordes)
+help-reading-width = Ensanchar / estrechar el texto en modo lectura
+help-reveal-hidden = Resaltar el texto oculto y el blanco sobre blanco
+help-section-search = 🔍 Búsqueda:
+help-open-search = Abrir la búsqueda
+help-search-document = Buscar en el documento (resalta coincidencias)
+help-next-result = Resultado siguiente
+help-previous-result = Resultado anterior
+help-fuzzy = Búsqueda aproximada (acentos, erratas)
+help-search-scope = Buscar en la sección actual / solo tablas / todo
+help-paste-search = Buscar el texto del portapapeles (en la búsqueda)
+help-section-other = 📋 Otras funciones:
+help-outline = Mostrar el esquema
+help-outline-pane = Panel de esquema (Tab cambia el foco)
+help-copy = Copiar al portapapeles (tabla arriba: TSV)
+help-copy-table = Copiar la tabla actual como TSV / CSV
+help-select = Seleccionar elementos para copiar o exportar (j/k amplían, y texto, m Markdown, e exportar)
+help-export = Exportar el documento / la sección actual a un archivo (.md, .txt, .csv, .json)
+help-table-mode = Abrir la tabla en modo tabla (←→ columnas, s ordenar, x/X ocultar/mostrar)
+help-image-preview = Ver la imagen a pantalla completa (+/- zoom, flechas desplazan)
+help-table-layout = Tablas: celdas en varias líneas, recortadas o a ancho completo
+help-table-shift = Desplazar lateralmente las tablas a ancho completo
+help-palette = Ir a un título (aproximado)
+help-set-bookmark = Poner un marcador
+help-jump-bookmark = Ir a un marcador (' los lista)
+help-ask = Hacer una pregunta sobre el documento (ai.)
+help-shell = Ejecutar un comando (% = ruta del archivo)
+help-editor = Abrir el archivo en un editor y recargarlo al guardar
+help-suspend = Suspender al shell
+help-toggle-help = Mostrar u ocultar la ayuda
+help-quit = Salir
+help-section-copy = 📄 Copiar:
+help-copy-document = Documento: copia todo el documento como texto
+help-copy-outline = Esquema:   copia la estructura del documento
+help-copy-search = Búsqueda:  copia los resultados (use F2)
+help-close = Pulse cualquier tecla para cerrar la ayuda...
@@ -0,0 +1,117 @@
+//! Rendered text as ANSI escape sequences.
+//!
+//! `--export ansi` draws the document into a ratatui [`Buffer`] the way the
+//! viewer does and writes the buffer out row by row, with SGR escapes where
+//! the style changes, so `less -R` or `cat` shows it later without doxx.
+
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Modifier, Style};
+use unicode_width::UnicodeWidthStr;
+
+/// The rows of `buffer` as lines of text, styled with ANSI escapes when
+/// `styled` is set. Trailing blank cells are dropped from each row.
+pub fn buffer_to_ansi(buffer: &Buffer, styled: bool) -> String {
+    let area = buffer.area;
+    let mut out = String::new();
+    for y in area.top()..area.bottom() {
+        let row: Vec<_> = (area.left()..area.right())
+            .filter_map(|x| buffer.cell((x, y)))
+            .collect();
+        let end = row
+            .iter()
+            .rposition(|cell| !is_blank(cell.symbol(), cell.style()))
+            .map_or(0, |last| last + 1);
+
+        let mut current = Style::default();
+        let mut skip = 0;
+        for cell in &row[..end] {
+            // The cells after a wide character are covered by it
+            if skip > 0 {
+                skip -= 1;
+                continue;
+            }
+            let style = cell.style();
+            if styled && !same_style(style, current) {
+                if !same_style(current, Style::default()) {
+                    out.push_str("\x1b[0m");
+                }
+                let codes = sgr_codes(style);
+                if !codes.is_empty() {
+                    out.push_str(&format!("\x1b[{codes}m"));
+                }
+                current = style;
+            }
+            out.push_str(cell.symbol());
+            skip = cell.symbol().width().saturating_sub(1);
+        }
+        if styled && !same_style(current, Style::default()) {
+            out.push_str("\x1b[0m");
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// A space that shows nothing: no background, underline or reversal
+fn is_blank(symbol: &str, style: Style) -> bool {
+    symbol.trim().is_empty()
+        && matches!(style.bg, None | Some(Color::Reset))
+        && !style
+            .add_modifier
+            .intersects(Modifier::REVERSED | Modifier::UNDERLINED | Modifier::CROSSED_OUT)
+}
+
+fn same_style(a: Style, b: Style) -> bool {
+    let color = |color: Option<Color>| color.filter(|color| *color != Color::Reset);
+    color(a.fg) == color(b.fg) && color(a.bg) == color(b.bg) && a.add_modifier == b.add_modifier
+}
+
+/// SGR parameters for `style`, e.g. `1;33`
+fn sgr_codes(style: Style) -> String {
+    let mut codes: Vec<String> = [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::SLOW_BLINK, "5"),
+        (Modifier::RAPID_BLINK, "6"),
+        (Modifier::REVERSED, "7"),
+        (Modifier::HIDDEN, "8"),
+        (Modifier::CROSSED_OUT, "9"),
+    ]
+    .into_iter()
+    .filter(|(modifier, _)| style.add_modifier.contains(*modifier))
+    .map(|(_, code)| code.to_string())
+    .collect();
+    codes.extend(style.fg.and_then(|color| color_code(color, false)));
+    codes.extend(style.bg.and_then(|color| color_code(color, true)));
+    codes.join(";")
+}
+
+fn color_code(color: Color, background: bool) -> Option<String> {
+    let base = |code: u8| Some((code + if background { 10 } else { 0 }).to_string());
+    match color {
+        Color::Reset => None,
+        Color::Black => base(30),
+        Color::Red => base(31),
+        Color::Green => base(32),
+        Color::Yellow => base(33),
+        Color::Blue => base(34),
+        Color::Magenta => base(35),
+        Color::Cyan => base(36),
+        Color::Gray => base(37),
+        Color::DarkGray => base(90),
+        Color::LightRed => base(91),
+        Color::LightGreen => base(92),
+        Color::LightYellow => base(93),
+        Color::LightBlue => base(94),
+        Color::LightMagenta => base(95),
+        Color::LightCyan => base(96),
+        Color::White => base(97),
+        Color::Rgb(r, g, b) => Some(format!(
+            "{};2;{r};{g};{b}",
+            if background { 48 } else { 38 }
+        )),
+        Color::Indexed(index) => Some(format!("{};5;{index}", if background { 48 } else { 38 })),
+    }
+}
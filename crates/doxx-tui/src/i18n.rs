@@ -0,0 +1,208 @@
+//! Localized viewer text.
+//!
+//! Help text, status messages and labels are looked up by id in Fluent
+//! catalogs (`locales/<language>.ftl`) built into doxx. A catalog in
+//! `~/.config/doxx/locales/` is read on top of them, so a team can add a
+//! language or reword messages without rebuilding. Messages a catalog leaves
+//! out are shown in English.
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+use once_cell::sync::OnceCell;
+use std::path::{Path, PathBuf};
+use unic_langid::LanguageIdentifier;
+
+/// Catalogs built into doxx, by language
+const CATALOGS: &[(&str, &str)] = &[
+    ("en", include_str!("../locales/en.ftl")),
+    ("es", include_str!("../locales/es.ftl")),
+];
+
+static LOCALIZER: OnceCell<Localizer> = OnceCell::new();
+
+/// Messages in one language, falling back to English
+pub struct Localizer {
+    /// Preferred language first, English last
+    bundles: Vec<FluentBundle<FluentResource>>,
+    /// Problems with the language or the user's catalogs
+    pub warnings: Vec<String>,
+}
+
+impl Localizer {
+    /// Messages for `language` (e.g. `es` or `pt-BR`), with any user catalogs
+    /// named after it (`pt.ftl`, `pt-BR.ftl`) found in `user_dir`
+    pub fn new(language: &str, user_dir: Option<&Path>) -> Self {
+        let mut warnings = Vec::new();
+        let english: LanguageIdentifier = "en".parse().expect("valid language tag");
+        let requested: LanguageIdentifier = language.parse().unwrap_or_else(|_| {
+            warnings.push(format!("Unknown language {language:?}, using English"));
+            english.clone()
+        });
+
+        let mut bundles = Vec::new();
+        for language in [requested, english] {
+            if bundles.iter().any(|bundle: &FluentBundle<FluentResource>| {
+                bundle.locales[0].language == language.language
+            }) {
+                continue;
+            }
+            let primary = language.language.as_str().to_string();
+            let mut bundle = FluentBundle::new_concurrent(vec![language.clone()]);
+            // Terminals show Unicode isolation marks around arguments as junk
+            bundle.set_use_isolating(false);
+            let mut has_messages = false;
+
+            if let Some((_, source)) = CATALOGS.iter().find(|(tag, _)| *tag == primary) {
+                let resource = FluentResource::try_new(source.to_string())
+                    .unwrap_or_else(|(resource, _)| resource);
+                bundle.add_resource_overriding(resource);
+                has_messages = true;
+            }
+
+            let mut names = vec![primary];
+            names.push(language.to_string());
+            names.dedup();
+            for name in names {
+                let Some(path) = user_dir.map(|dir| dir.join(format!("{name}.ftl"))) else {
+                    continue;
+                };
+                let Ok(source) = std::fs::read_to_string(&path) else {
+                    continue;
+                };
+                let resource =
+                    FluentResource::try_new(source).unwrap_or_else(|(resource, errors)| {
+                        warnings.push(format!(
+                            "{}: {} invalid entries skipped",
+                            path.display(),
+                            errors.len()
+                        ));
+                        resource
+                    });
+                bundle.add_resource_overriding(resource);
+                has_messages = true;
+            }
+
+            if has_messages {
+                bundles.push(bundle);
+            } else {
+                warnings.push(format!(
+                    "No messages for language {language}, using English"
+                ));
+            }
+        }
+
+        Self { bundles, warnings }
+    }
+
+    /// The message `id` with `args` filled in, or the id itself if no
+    /// catalog has it
+    pub fn message(&self, id: &str, args: Option<&FluentArgs>) -> String {
+        for bundle in &self.bundles {
+            if let Some(pattern) = bundle.get_message(id).and_then(|message| message.value()) {
+                let mut errors = Vec::new();
+                return bundle
+                    .format_pattern(pattern, args, &mut errors)
+                    .into_owned();
+            }
+        }
+        id.to_string()
+    }
+}
+
+/// Use `localizer` for [`tr`] and [`tr_args`]. Only the first call has any
+/// effect; without one, messages are in English.
+pub fn install(localizer: Localizer) {
+    let _ = LOCALIZER.set(localizer);
+}
+
+fn localizer() -> &'static Localizer {
+    LOCALIZER.get_or_init(|| Localizer::new("en", None))
+}
+
+/// Message `id` in the viewer's language
+pub fn tr(id: &str) -> String {
+    localizer().message(id, None)
+}
+
+/// Message `id` with its `{ $variables }` filled in
+pub fn tr_args(id: &str, args: &[(&str, FluentValue)]) -> String {
+    let mut fluent_args = FluentArgs::new();
+    for (name, value) in args {
+        fluent_args.set(*name, value.clone());
+    }
+    localizer().message(id, Some(&fluent_args))
+}
+
+/// Language set in the environment (`LC_ALL`, `LC_MESSAGES` or `LANG`), as a
+/// tag such as `es-ES`; `None` for the C locale
+pub fn environment_language() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .map(|value| {
+            let tag = value.split(['.', '@']).next().unwrap_or_default();
+            tag.replace('_', "-")
+        })
+        .filter(|tag| !tag.is_empty() && tag != "C" && tag != "POSIX")
+}
+
+/// Where user catalogs are looked for (e.g. `~/.config/doxx/locales`)
+pub fn user_catalog_dir() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("doxx")
+        .join("locales")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalogs_parse() {
+        for (language, source) in CATALOGS {
+            if let Err((_, errors)) = FluentResource::try_new(source.to_string()) {
+                panic!("{language}.ftl: {errors:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_translation_with_fallback_and_plurals() {
+        let spanish = Localizer::new("es-MX", None);
+        assert!(spanish.warnings.is_empty());
+        assert_eq!(spanish.message("title-help", None), "Ayuda");
+        assert_eq!(spanish.message("no-such-message", None), "no-such-message");
+
+        let mut args = FluentArgs::new();
+        args.set("count", 1);
+        assert_eq!(
+            Localizer::new("en", None).message("status-parse-warnings", Some(&args)),
+            "⚠ 1 parse warning (run with --warnings for details)"
+        );
+        args.set("count", 3);
+        assert!(spanish
+            .message("status-parse-warnings", Some(&args))
+            .starts_with("⚠ 3 avisos"));
+
+        // An unknown language falls back to English
+        let klingon = Localizer::new("tlh", None);
+        assert_eq!(klingon.message("title-help", None), "Help");
+        assert_eq!(klingon.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_user_catalog_overrides() {
+        let dir = std::env::temp_dir().join(format!("doxx-i18n-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("de.ftl"), "title-help = Hilfe\n").unwrap();
+        std::fs::write(dir.join("en.ftl"), "help-quit = Exit\n").unwrap();
+
+        let german = Localizer::new("de-AT", Some(&dir));
+        assert_eq!(german.message("title-help", None), "Hilfe");
+        assert_eq!(german.message("help-quit", None), "Exit");
+        assert_eq!(german.message("help-scroll-up", None), "Scroll up");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
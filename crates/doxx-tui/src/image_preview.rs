@@ -0,0 +1,225 @@
+//! Image preview: one picture on the whole screen.
+//!
+//! `i` on an image opens it at the largest size the terminal holds. `+` and
+//! `-` zoom in and out around the middle of the view and the arrow keys pan
+//! it. Graphics protocols don't scale a picture up past its own size, so the
+//! part in view is cropped and resized to the pixels of the screen before it
+//! is handed to the terminal, and again only when the view changes.
+
+use image::{imageops::FilterType, DynamicImage};
+use ratatui::{buffer::Buffer, layout::Rect, widgets::StatefulWidget};
+use ratatui_image::{picker::Picker, protocol::StatefulProtocol, Resize, StatefulImage};
+
+/// Closest zoom, as a multiple of the whole picture fitted to the screen
+pub const MAX_ZOOM: f64 = 16.0;
+/// How much one `+` or `-` zooms
+pub const ZOOM_STEP: f64 = 1.5;
+/// How far one arrow key pans, as a share of the view
+pub const PAN_STEP: f64 = 0.25;
+
+/// Part of a picture, in its pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// A picture open in the preview, and how it is zoomed
+pub struct ImagePreview {
+    /// Index of the image element in the document
+    pub element: usize,
+    pub description: String,
+    image: DynamicImage,
+    /// 1.0 shows the whole picture
+    pub zoom: f64,
+    /// Middle of the view, as a share of the picture's width and height
+    center: (f64, f64),
+    rendered: Option<Rendered>,
+}
+
+/// The view as last sent to the terminal
+struct Rendered {
+    area: Rect,
+    region: Region,
+    /// Where in `area` it is drawn
+    target: Rect,
+    protocol: Box<dyn StatefulProtocol>,
+}
+
+impl ImagePreview {
+    pub fn new(element: usize, description: String, image: DynamicImage) -> Self {
+        Self {
+            element,
+            description,
+            image,
+            zoom: 1.0,
+            center: (0.5, 0.5),
+            rendered: None,
+        }
+    }
+
+    /// Width and height of the picture in pixels
+    pub fn dimensions(&self) -> (u32, u32) {
+        (self.image.width(), self.image.height())
+    }
+
+    pub fn zoom_in(&mut self) {
+        self.zoom = (self.zoom * ZOOM_STEP).min(MAX_ZOOM);
+    }
+
+    /// Zoom out, no further than the whole picture
+    pub fn zoom_out(&mut self) {
+        self.zoom = (self.zoom / ZOOM_STEP).max(1.0);
+    }
+
+    /// Back to the whole picture
+    pub fn reset(&mut self) {
+        self.zoom = 1.0;
+        self.center = (0.5, 0.5);
+    }
+
+    /// Move the view by `dx` and `dy` steps (negative is left and up)
+    pub fn pan(&mut self, dx: i32, dy: i32) {
+        let step = PAN_STEP / self.zoom;
+        self.center.0 = (self.center.0 + dx as f64 * step).clamp(0.0, 1.0);
+        self.center.1 = (self.center.1 + dy as f64 * step).clamp(0.0, 1.0);
+    }
+
+    /// Part of the picture in view on a screen `screen` pixels in size: at
+    /// zoom 1 the whole picture, closer in as much of it as fills the screen,
+    /// kept inside the picture
+    pub fn visible_region(&self, screen: (u32, u32)) -> Region {
+        let (width, height) = (self.image.width() as f64, self.image.height() as f64);
+        let fit = (screen.0 as f64 / width).min(screen.1 as f64 / height);
+        let scale = fit * self.zoom;
+        let view_width = (screen.0 as f64 / scale).clamp(1.0, width);
+        let view_height = (screen.1 as f64 / scale).clamp(1.0, height);
+        let x = (self.center.0 * width - view_width / 2.0).clamp(0.0, width - view_width);
+        let y = (self.center.1 * height - view_height / 2.0).clamp(0.0, height - view_height);
+        Region {
+            x: x.round() as u32,
+            y: y.round() as u32,
+            width: (view_width.round() as u32).max(1),
+            height: (view_height.round() as u32).max(1),
+        }
+    }
+
+    /// Draw the view in the middle of `area`
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer, picker: &mut Picker) {
+        if area.is_empty() {
+            return;
+        }
+        let (font_width, font_height) = picker.font_size;
+        let screen = (
+            area.width as u32 * font_width.max(1) as u32,
+            area.height as u32 * font_height.max(1) as u32,
+        );
+        let region = self.visible_region(screen);
+        // Panning stops at the edge: keep the middle where the view ended up
+        // so panning back moves it at once
+        let (width, height) = self.dimensions();
+        self.center = (
+            (region.x as f64 + region.width as f64 / 2.0) / width as f64,
+            (region.y as f64 + region.height as f64 / 2.0) / height as f64,
+        );
+
+        let stale = !matches!(&self.rendered, Some(rendered) if rendered.area == area && rendered.region == region);
+        if stale {
+            let crop = self
+                .image
+                .crop_imm(region.x, region.y, region.width, region.height)
+                .resize(screen.0, screen.1, FilterType::Triangle);
+            let columns = crop.width().div_ceil(font_width.max(1) as u32) as u16;
+            let rows = crop.height().div_ceil(font_height.max(1) as u32) as u16;
+            let target = Rect {
+                x: area.x + area.width.saturating_sub(columns) / 2,
+                y: area.y + area.height.saturating_sub(rows) / 2,
+                width: columns.min(area.width),
+                height: rows.min(area.height),
+            };
+            self.rendered = Some(Rendered {
+                area,
+                region,
+                target,
+                protocol: picker.new_resize_protocol(crop),
+            });
+        }
+
+        if let Some(rendered) = &mut self.rendered {
+            StatefulWidget::render(
+                StatefulImage::new(None).resize(Resize::Fit(None)),
+                rendered.target,
+                buf,
+                &mut rendered.protocol,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preview(width: u32, height: u32) -> ImagePreview {
+        ImagePreview::new(
+            0,
+            "Chart".to_string(),
+            DynamicImage::new_rgb8(width, height),
+        )
+    }
+
+    #[test]
+    fn test_visible_region_zoom() {
+        let mut preview = preview(400, 200);
+        let whole = Region {
+            x: 0,
+            y: 0,
+            width: 400,
+            height: 200,
+        };
+        assert_eq!(preview.visible_region((800, 800)), whole);
+
+        // A screen as wide as the picture fills it once zoomed in
+        preview.zoom = 2.0;
+        assert_eq!(
+            preview.visible_region((800, 400)),
+            Region {
+                x: 100,
+                y: 50,
+                width: 200,
+                height: 100,
+            }
+        );
+
+        for _ in 0..20 {
+            preview.zoom_in();
+        }
+        assert_eq!(preview.zoom, MAX_ZOOM);
+        preview.reset();
+        preview.zoom_out();
+        assert_eq!(preview.zoom, 1.0);
+        assert_eq!(preview.visible_region((800, 800)), whole);
+    }
+
+    #[test]
+    fn test_pan_stays_inside() {
+        let mut preview = preview(400, 200);
+        preview.zoom = 2.0;
+        preview.pan(1, 0);
+        assert_eq!(preview.visible_region((800, 400)).x, 150);
+
+        // Past the edge the view stops at it
+        for _ in 0..10 {
+            preview.pan(-1, -1);
+        }
+        let region = preview.visible_region((800, 400));
+        assert_eq!((region.x, region.y), (0, 0));
+
+        // Without zoom there is nowhere to pan to
+        preview.zoom = 1.0;
+        preview.pan(1, 1);
+        assert_eq!(preview.visible_region((800, 400)).x, 0);
+    }
+}
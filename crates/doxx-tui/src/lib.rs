@@ -0,0 +1,25 @@
+//! doxx-tui: the doxx terminal viewer
+//!
+//! A ratatui application for browsing a [`doxx_core::Document`]: outline,
+//! search, bookmarks, inline images and the diff viewer. Other applications can
+//! open it with [`run_viewer`], embed it in their own layout as a
+//! [`DoxxViewerWidget`], and adjust how elements are drawn through a
+//! [`render::RendererRegistry`].
+
+pub mod ansi;
+pub mod i18n;
+pub mod image_preview;
+pub mod render;
+pub mod table_view;
+pub mod terminal_image;
+pub mod terminal_probe;
+pub mod ui;
+pub mod widget;
+
+pub use render::{ElementKind, ElementRenderer, RenderContext, RendererRegistry};
+pub use terminal_image::{TerminalImageRenderer, TerminalImageSupport};
+pub use ui::{
+    run_diff_viewer, run_viewer, run_viewer_while_loading, App, DocumentParser, DocumentSource,
+    OcrPass, Preparation, ViewerOptions,
+};
+pub use widget::DoxxViewerWidget;
@@ -0,0 +1,337 @@
+//! Custom element renderers.
+//!
+//! The viewer draws each element itself, but a [`RendererRegistry`] can take
+//! over particular kinds of element: an embedder registers its own
+//! [`ElementRenderer`], and a few optional built-ins can be switched on by name
+//! (`ui.renderers` in the config). A renderer that declines an element (returns
+//! `None`) leaves it to the next one registered for its kind, and finally to
+//! the viewer's own rendering.
+
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use std::collections::HashMap;
+
+use doxx_core::document::{CellDataType, DocumentElement, TableData};
+
+/// Kinds of element a renderer can be registered for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ElementKind {
+    Heading,
+    Paragraph,
+    List,
+    Table,
+    Image,
+    PageBreak,
+    Quote,
+    CodeBlock,
+    Caption,
+    Title,
+    Subtitle,
+    Equation,
+}
+
+impl ElementKind {
+    pub fn of(element: &DocumentElement) -> Self {
+        match element {
+            DocumentElement::Heading { .. } => Self::Heading,
+            DocumentElement::Paragraph { .. } => Self::Paragraph,
+            DocumentElement::List { .. } => Self::List,
+            DocumentElement::Table { .. } => Self::Table,
+            DocumentElement::Image { .. } => Self::Image,
+            DocumentElement::PageBreak => Self::PageBreak,
+            DocumentElement::Quote { .. } => Self::Quote,
+            DocumentElement::CodeBlock { .. } => Self::CodeBlock,
+            DocumentElement::Caption { .. } => Self::Caption,
+            DocumentElement::Title { .. } => Self::Title,
+            DocumentElement::Subtitle { .. } => Self::Subtitle,
+            DocumentElement::Equation { .. } => Self::Equation,
+        }
+    }
+}
+
+/// What a renderer knows about where its lines will be shown
+#[derive(Debug, Clone, Copy)]
+pub struct RenderContext {
+    /// Columns available; longer lines are wrapped
+    pub width: u16,
+    /// Document colors are shown (`--color`)
+    pub color: bool,
+}
+
+/// Draws elements of the kinds it is registered for
+pub trait ElementRenderer: Send + Sync {
+    /// Lines for `element`, or `None` to leave it to the next renderer
+    fn render(
+        &self,
+        element: &DocumentElement,
+        context: &RenderContext,
+    ) -> Option<Vec<Line<'static>>>;
+}
+
+impl<F> ElementRenderer for F
+where
+    F: Fn(&DocumentElement, &RenderContext) -> Option<Vec<Line<'static>>> + Send + Sync,
+{
+    fn render(
+        &self,
+        element: &DocumentElement,
+        context: &RenderContext,
+    ) -> Option<Vec<Line<'static>>> {
+        self(element, context)
+    }
+}
+
+/// Built-in renderers that can be switched on by name, and what they draw
+pub const BUILTIN_RENDERERS: &[(&str, &str)] = &[
+    ("sparklines", "numeric table columns as sparklines"),
+    ("latex-equations", "equations as their LaTeX source"),
+];
+
+/// Renderers by element kind, most recently registered first
+#[derive(Default)]
+pub struct RendererRegistry {
+    renderers: HashMap<ElementKind, Vec<Box<dyn ElementRenderer>>>,
+}
+
+impl RendererRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry with the built-ins named in `names` (comma separated, e.g.
+    /// `sparklines,latex-equations`), and a warning for each unknown name
+    pub fn with_builtins(names: &str) -> (Self, Vec<String>) {
+        let mut registry = Self::new();
+        let mut warnings = Vec::new();
+        for name in names.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            match name {
+                "sparklines" => registry.register(ElementKind::Table, sparkline_table),
+                "latex-equations" => registry.register(ElementKind::Equation, latex_equation),
+                _ => warnings.push(format!(
+                    "Unknown renderer {name:?} (available: {})",
+                    BUILTIN_RENDERERS
+                        .iter()
+                        .map(|(name, _)| *name)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )),
+            }
+        }
+        (registry, warnings)
+    }
+
+    /// Use `renderer` for elements of `kind`, ahead of any registered before
+    pub fn register(&mut self, kind: ElementKind, renderer: impl ElementRenderer + 'static) {
+        self.renderers
+            .entry(kind)
+            .or_default()
+            .insert(0, Box::new(renderer));
+    }
+
+    /// Lines from the first renderer that takes `element`, if any
+    pub fn render(
+        &self,
+        element: &DocumentElement,
+        context: &RenderContext,
+    ) -> Option<Vec<Line<'static>>> {
+        self.renderers
+            .get(&ElementKind::of(element))?
+            .iter()
+            .find_map(|renderer| renderer.render(element, context))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.renderers.values().all(Vec::is_empty)
+    }
+}
+
+const SPARK_BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Value of a number, currency or percentage cell (`$1,200.50` is 1200.5)
+pub(crate) fn cell_value(content: &str) -> Option<f64> {
+    content
+        .trim()
+        .trim_start_matches(['$', '€', '£'])
+        .trim_end_matches('%')
+        .replace(',', "")
+        .parse()
+        .ok()
+}
+
+/// One bar per value, scaled between the smallest and largest
+fn sparkline(values: &[f64]) -> String {
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    values
+        .iter()
+        .map(|value| {
+            let level = if max > min {
+                ((value - min) / (max - min) * 7.0).round() as usize
+            } else {
+                3
+            };
+            SPARK_BARS[level.min(7)]
+        })
+        .collect()
+}
+
+/// Numeric columns of `table`: header and the values of its rows
+fn numeric_columns(table: &TableData) -> Vec<(String, Vec<f64>)> {
+    (0..table.headers.len())
+        .filter_map(|column| {
+            let cells: Vec<_> = table
+                .rows
+                .iter()
+                .filter_map(|row| row.get(column))
+                .filter(|cell| cell.data_type != CellDataType::Empty)
+                .collect();
+            let numeric = cells.iter().all(|cell| {
+                matches!(
+                    cell.data_type,
+                    CellDataType::Number | CellDataType::Currency | CellDataType::Percentage
+                )
+            });
+            if !numeric || cells.len() < 2 {
+                return None;
+            }
+            let values = cells.iter().filter_map(|cell| cell_value(&cell.content));
+            Some((table.headers[column].content.clone(), values.collect()))
+        })
+        .collect()
+}
+
+/// Tables with numeric columns as one sparkline per column, with its range
+fn sparkline_table(
+    element: &DocumentElement,
+    context: &RenderContext,
+) -> Option<Vec<Line<'static>>> {
+    let DocumentElement::Table { table } = element else {
+        return None;
+    };
+    let columns = numeric_columns(table);
+    if columns.is_empty() {
+        return None;
+    }
+
+    let mut lines = Vec::new();
+    if let Some(title) = &table.metadata.title {
+        lines.push(Line::from(Span::styled(
+            title.clone(),
+            Style::default().add_modifier(Modifier::BOLD),
+        )));
+    }
+    let label_width = columns
+        .iter()
+        .map(|(header, _)| doxx_core::text_width::display_width(header))
+        .max()
+        .unwrap_or(0);
+    for (header, values) in columns {
+        let padding = label_width - doxx_core::text_width::display_width(&header);
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let range = format!("  {min} – {max}");
+        // Values past the width are cut off rather than wrapped
+        let room = (context.width as usize).saturating_sub(label_width + 4 + range.len());
+        let mut bars = sparkline(&values);
+        if values.len() > room && room > 1 {
+            bars = format!(
+                "{}…",
+                doxx_core::text_width::truncate_to_width(&bars, room - 1)
+            );
+        }
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("  {header}{}  ", " ".repeat(padding)),
+                Style::default().fg(Color::Cyan),
+            ),
+            Span::styled(bars, Style::default().fg(Color::Green)),
+            Span::styled(range, Style::default().fg(Color::DarkGray)),
+        ]));
+    }
+    lines.push(Line::from(""));
+    Some(lines)
+}
+
+/// Equations as LaTeX rather than a Unicode approximation
+fn latex_equation(
+    element: &DocumentElement,
+    _context: &RenderContext,
+) -> Option<Vec<Line<'static>>> {
+    let DocumentElement::Equation { latex, .. } = element else {
+        return None;
+    };
+    Some(vec![
+        Line::from(Span::styled(
+            format!("    $$ {latex} $$"),
+            Style::default().fg(Color::Cyan),
+        )),
+        Line::from(""),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use doxx_core::document::TextFormatting;
+
+    fn paragraph(text: &str) -> DocumentElement {
+        DocumentElement::Paragraph {
+            text: text.to_string(),
+            formatting: TextFormatting::default(),
+            runs: Vec::new(),
+            style: None,
+        }
+    }
+
+    fn line_text(line: &Line) -> String {
+        line.spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect()
+    }
+
+    #[test]
+    fn test_registry_order_and_fallback() {
+        let context = RenderContext {
+            width: 80,
+            color: false,
+        };
+        let mut registry = RendererRegistry::new();
+        assert!(registry.is_empty());
+        registry.register(
+            ElementKind::Paragraph,
+            |_: &DocumentElement, _: &RenderContext| Some(vec![Line::from("first")]),
+        );
+        // A later renderer goes first, and can pass on elements it doesn't want
+        registry.register(
+            ElementKind::Paragraph,
+            |element: &DocumentElement, _: &RenderContext| match element {
+                DocumentElement::Paragraph { text, .. } if text.starts_with('!') => {
+                    Some(vec![Line::from(text.to_uppercase())])
+                }
+                _ => None,
+            },
+        );
+
+        let render = |element| {
+            registry
+                .render(&element, &context)
+                .map(|lines| line_text(&lines[0]))
+        };
+        assert_eq!(render(paragraph("!loud")).as_deref(), Some("!LOUD"));
+        assert_eq!(render(paragraph("quiet")).as_deref(), Some("first"));
+        assert_eq!(render(DocumentElement::PageBreak), None);
+    }
+
+    #[test]
+    fn test_builtins_by_name() {
+        let (registry, warnings) = RendererRegistry::with_builtins("sparklines, nope");
+        assert!(!registry.is_empty());
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("\"nope\""));
+
+        assert_eq!(sparkline(&[1.0, 5.0, 3.0]), "▁█▅");
+        assert_eq!(cell_value("$1,200.50"), Some(1200.5));
+        assert_eq!(cell_value("12%"), Some(12.0));
+    }
+}
@@ -0,0 +1,433 @@
+//! Table mode, and fitting tables to the document view.
+//!
+//! Enter on a table opens it in a view of its own: the cursor moves between
+//! columns and the view scrolls sideways to keep it in sight, columns can be
+//! hidden, rows sorted by the cursor's column, and numeric columns are summed
+//! and averaged in a footer. The table itself is never changed; a
+//! [`TableView`] only holds which rows and columns are shown, and in what order.
+//!
+//! In the document view, [`fit_column_widths`] shrinks the columns of a table
+//! too wide for the terminal.
+
+use std::cmp::Ordering;
+
+use doxx_core::document::{CellDataType, TableCell, TableData};
+
+use crate::render::cell_value;
+
+/// Rows ordered by one column
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TableSort {
+    pub column: usize,
+    pub descending: bool,
+}
+
+/// Sum and average of a column whose cells are all numbers
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnSummary {
+    pub sum: f64,
+    pub average: f64,
+    /// Currency symbol the column's cells start with, if they share one
+    pub prefix: String,
+    /// `%` for a column of percentages
+    pub suffix: &'static str,
+}
+
+impl ColumnSummary {
+    /// `value` written like the column's cells: whole numbers as they are,
+    /// others to two decimals, with digit grouping and the column's unit
+    pub fn format(&self, value: f64) -> String {
+        let digits = if value.fract() == 0.0 && value.abs() < 1e15 {
+            format!("{:.0}", value.abs())
+        } else {
+            format!("{:.2}", value.abs())
+        };
+        let (whole, fraction) = digits.split_at(digits.find('.').unwrap_or(digits.len()));
+        let mut grouped = String::new();
+        for (index, digit) in whole.chars().enumerate() {
+            if index > 0 && (whole.len() - index) % 3 == 0 {
+                grouped.push(',');
+            }
+            grouped.push(digit);
+        }
+        let sign = if value < 0.0 { "-" } else { "" };
+        format!("{sign}{}{grouped}{fraction}{}", self.prefix, self.suffix)
+    }
+}
+
+/// How a table open in table mode is shown
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableView {
+    /// Index of the table in the document
+    pub element: usize,
+    /// Column the cursor is on, where hiding and sorting apply
+    pub column: usize,
+    /// Leftmost column shown, when the table is wider than the view
+    pub first_column: usize,
+    /// Topmost row shown, counted in display order
+    pub first_row: usize,
+    pub hidden: Vec<bool>,
+    pub sort: Option<TableSort>,
+}
+
+impl TableView {
+    pub fn new(element: usize, table: &TableData) -> Self {
+        Self {
+            element,
+            column: 0,
+            first_column: 0,
+            first_row: 0,
+            hidden: vec![false; column_count(table)],
+            sort: None,
+        }
+    }
+
+    /// Columns not hidden, left to right
+    pub fn visible_columns(&self) -> Vec<usize> {
+        (0..self.hidden.len())
+            .filter(|&column| !self.hidden[column])
+            .collect()
+    }
+
+    /// Move the cursor to the next shown column to the right or left
+    pub fn move_column(&mut self, right: bool) {
+        let visible = self.visible_columns();
+        let next = if right {
+            visible.iter().find(|&&column| column > self.column)
+        } else {
+            visible.iter().rev().find(|&&column| column < self.column)
+        };
+        if let Some(&next) = next {
+            self.column = next;
+        }
+    }
+
+    /// Hide the cursor's column, moving the cursor to a neighbour. The last
+    /// shown column stays, so there is always something to look at.
+    pub fn hide_column(&mut self) -> bool {
+        if self.visible_columns().len() <= 1 {
+            return false;
+        }
+        self.hidden[self.column] = true;
+        let visible = self.visible_columns();
+        self.column = visible
+            .iter()
+            .copied()
+            .find(|&column| column > self.column)
+            .or_else(|| visible.last().copied())
+            .unwrap_or(0);
+        if self.sort.is_some_and(|sort| self.hidden[sort.column]) {
+            self.sort = None;
+        }
+        true
+    }
+
+    pub fn show_all_columns(&mut self) {
+        self.hidden.fill(false);
+    }
+
+    /// Sort by the cursor's column: ascending, then descending, then back to
+    /// the document's order
+    pub fn cycle_sort(&mut self) {
+        self.sort = match self.sort {
+            Some(TableSort {
+                column,
+                descending: false,
+            }) if column == self.column => Some(TableSort {
+                column,
+                descending: true,
+            }),
+            Some(TableSort { column, .. }) if column == self.column => None,
+            _ => Some(TableSort {
+                column: self.column,
+                descending: false,
+            }),
+        };
+        self.first_row = 0;
+    }
+
+    /// Indexes of the table's rows in the order they are shown
+    pub fn row_order(&self, table: &TableData) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..table.rows.len()).collect();
+        if let Some(sort) = self.sort {
+            // Stable, so equal cells keep the document's order
+            order.sort_by(|&a, &b| {
+                let (a, b) = (
+                    table.rows[a].get(sort.column),
+                    table.rows[b].get(sort.column),
+                );
+                match (is_blank(a), is_blank(b)) {
+                    (false, false) if sort.descending => compare_cells(a, b).reverse(),
+                    (false, false) => compare_cells(a, b),
+                    // Empty cells last, whichever the direction
+                    (a, b) => a.cmp(&b),
+                }
+            });
+        }
+        order
+    }
+
+    /// Scroll sideways so the cursor's column is shown, given the display
+    /// width of each column and the columns of room
+    pub fn scroll_to_cursor(&mut self, widths: &[usize], room: usize) {
+        let visible = self.visible_columns();
+        let Some(cursor) = visible.iter().position(|&column| column == self.column) else {
+            return;
+        };
+        let mut first = visible
+            .iter()
+            .position(|&column| column >= self.first_column)
+            .unwrap_or(0)
+            .min(cursor);
+        // Each column takes its width, a space either side and a border
+        let span = |from: usize, to: usize| -> usize {
+            visible[from..=to]
+                .iter()
+                .map(|&column| widths.get(column).copied().unwrap_or(0) + 3)
+                .sum::<usize>()
+                + 1
+        };
+        while first < cursor && span(first, cursor) > room {
+            first += 1;
+        }
+        // Bring columns back in from the left when the rest leave room, e.g.
+        // after hiding some
+        let last = visible.len() - 1;
+        while first > 0 && span(first - 1, last) <= room {
+            first -= 1;
+        }
+        self.first_column = visible[first];
+    }
+}
+
+/// Narrowest a column is shrunk to: a character and an ellipsis, with room
+/// to spare
+const MIN_COLUMN_WIDTH: usize = 4;
+
+/// Column widths that fit `room` terminal columns, borders and padding
+/// included. Columns are shrunk in proportion to their width, so wide ones
+/// give up the most; none goes below a few characters, even if the table then
+/// still overflows.
+pub fn fit_column_widths(widths: &[usize], room: usize) -> Vec<usize> {
+    // A space either side of each cell, and a border between and around them
+    let budget = room.saturating_sub(3 * widths.len() + 1);
+    let total: usize = widths.iter().sum();
+    if total <= budget {
+        return widths.to_vec();
+    }
+
+    let mut fitted: Vec<usize> = widths
+        .iter()
+        .map(|&width| (width * budget / total).max(width.min(MIN_COLUMN_WIDTH)))
+        .collect();
+    // Rounding down leaves a few columns over; they go to the columns that
+    // were cut the most
+    let mut used: usize = fitted.iter().sum();
+    while used < budget {
+        let Some(column) = (0..widths.len())
+            .filter(|&column| fitted[column] < widths[column])
+            .max_by_key(|&column| widths[column] - fitted[column])
+        else {
+            break;
+        };
+        fitted[column] += 1;
+        used += 1;
+    }
+    fitted
+}
+
+/// Columns in `table`, counting rows longer than the header
+pub fn column_count(table: &TableData) -> usize {
+    table
+        .rows
+        .iter()
+        .map(Vec::len)
+        .chain([table.headers.len()])
+        .max()
+        .unwrap_or(0)
+}
+
+fn is_numeric(cell: &TableCell) -> bool {
+    matches!(
+        cell.data_type,
+        CellDataType::Number | CellDataType::Currency | CellDataType::Percentage
+    )
+}
+
+fn is_blank(cell: Option<&TableCell>) -> bool {
+    cell.is_none_or(|cell| cell.data_type == CellDataType::Empty)
+}
+
+/// Order of two cells: numbers by value and other text ignoring case
+fn compare_cells(a: Option<&TableCell>, b: Option<&TableCell>) -> Ordering {
+    let value = |cell: Option<&TableCell>| {
+        cell.filter(|cell| is_numeric(cell))
+            .and_then(|cell| cell_value(&cell.content))
+    };
+    match (value(a), value(b)) {
+        (Some(x), Some(y)) => x.total_cmp(&y),
+        // Numbers before text in a mixed column
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => {
+            let text = |cell: Option<&TableCell>| cell.map(|cell| cell.content.to_lowercase());
+            text(a).cmp(&text(b))
+        }
+    }
+}
+
+/// Sum and average of `column` if every non-empty cell in it is a number,
+/// currency or percentage
+pub fn column_summary(table: &TableData, column: usize) -> Option<ColumnSummary> {
+    let cells: Vec<_> = table
+        .rows
+        .iter()
+        .filter_map(|row| row.get(column))
+        .filter(|cell| cell.data_type != CellDataType::Empty)
+        .collect();
+    if cells.is_empty() || !cells.iter().all(|cell| is_numeric(cell)) {
+        return None;
+    }
+    let values: Vec<f64> = cells
+        .iter()
+        .filter_map(|cell| cell_value(&cell.content))
+        .collect();
+    let sum: f64 = values.iter().sum();
+    let symbol = |cell: &&TableCell| {
+        cell.content
+            .trim()
+            .chars()
+            .next()
+            .filter(|c| "$€£".contains(*c))
+    };
+    let prefix = symbol(&cells[0])
+        .filter(|first| cells.iter().all(|cell| symbol(cell) == Some(*first)))
+        .map(String::from)
+        .unwrap_or_default();
+    let percentages = cells
+        .iter()
+        .all(|cell| cell.data_type == CellDataType::Percentage);
+    Some(ColumnSummary {
+        sum,
+        average: sum / values.len().max(1) as f64,
+        prefix,
+        suffix: if percentages { "%" } else { "" },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use doxx_core::document::{TableMetadata, TextAlignment, TextFormatting};
+
+    fn cell(content: &str, data_type: CellDataType) -> TableCell {
+        TableCell {
+            content: content.to_string(),
+            alignment: TextAlignment::Left,
+            formatting: TextFormatting::default(),
+            data_type,
+        }
+    }
+
+    fn table() -> TableData {
+        use CellDataType::*;
+        let row = |name, amount: &str, kind| {
+            vec![
+                cell(name, Text),
+                cell(amount, if amount.is_empty() { Empty } else { kind }),
+            ]
+        };
+        TableData {
+            headers: vec![cell("Item", Text), cell("Cost", Text)],
+            rows: vec![
+                row("pears", "$1,200.50", Currency),
+                row("Apples", "$99", Currency),
+                row("figs", "", Currency),
+                row("dates", "$300", Currency),
+            ],
+            metadata: TableMetadata {
+                column_count: 2,
+                row_count: 4,
+                has_headers: true,
+                column_widths: vec![6, 9],
+                column_alignments: vec![TextAlignment::Left, TextAlignment::Right],
+                title: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_sorting_and_summaries() {
+        let table = table();
+        let mut view = TableView::new(0, &table);
+        assert_eq!(view.row_order(&table), [0, 1, 2, 3]);
+
+        // Text ignores case
+        view.cycle_sort();
+        assert_eq!(view.row_order(&table), [1, 3, 2, 0]);
+
+        // Amounts by value, not as text, with the empty cell last either way
+        view.move_column(true);
+        view.cycle_sort();
+        assert_eq!(view.row_order(&table), [1, 3, 0, 2]);
+        view.cycle_sort();
+        assert_eq!(view.row_order(&table), [0, 3, 1, 2]);
+        view.cycle_sort();
+        assert_eq!(view.sort, None);
+
+        assert_eq!(column_summary(&table, 0), None);
+        let summary = column_summary(&table, 1).unwrap();
+        assert_eq!(summary.sum, 1599.5);
+        assert_eq!(summary.format(summary.sum), "$1,599.50");
+        assert_eq!(summary.format(summary.average), "$533.17");
+        assert_eq!(summary.format(-1_000_000.0), "-$1,000,000");
+    }
+
+    #[test]
+    fn test_fit_column_widths() {
+        // Fits already
+        assert_eq!(fit_column_widths(&[5, 10], 40), [5, 10]);
+
+        // 60 columns of text into 41: 31 after borders and padding, shared
+        // in proportion
+        let fitted = fit_column_widths(&[10, 40, 10], 41);
+        assert_eq!(fitted.iter().sum::<usize>(), 31);
+        assert_eq!(fitted, [5, 21, 5]);
+
+        // Narrow columns keep a minimum even when that overflows
+        assert_eq!(fit_column_widths(&[2, 30, 30], 12), [2, 4, 4]);
+    }
+
+    #[test]
+    fn test_hiding_and_scrolling_columns() {
+        let mut wide = table();
+        for row in wide.rows.iter_mut().chain([&mut wide.headers]) {
+            row.extend([cell("x", CellDataType::Text), cell("y", CellDataType::Text)]);
+        }
+        let mut view = TableView::new(0, &wide);
+
+        view.move_column(true);
+        view.cycle_sort();
+        assert!(view.hide_column());
+        assert_eq!((view.column, view.sort), (2, None));
+        view.move_column(false);
+        assert_eq!(view.column, 0);
+
+        // Four columns of width 10 take 13 each; room for two
+        view.move_column(true);
+        view.move_column(true);
+        view.scroll_to_cursor(&[10; 4], 30);
+        assert_eq!((view.column, view.first_column), (3, 2));
+        view.move_column(false);
+        view.move_column(false);
+        view.scroll_to_cursor(&[10; 4], 30);
+        assert_eq!(view.first_column, 0);
+
+        view.hide_column();
+        view.hide_column();
+        assert!(!view.hide_column());
+        assert_eq!(view.visible_columns(), [3]);
+        view.show_all_columns();
+        assert_eq!(view.visible_columns().len(), 4);
+    }
+}
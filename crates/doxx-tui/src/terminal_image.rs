@@ -0,0 +1,475 @@
+use anyhow::Result;
+use base64::Engine;
+use std::io::Write;
+use std::path::Path;
+
+use crate::terminal_probe::{self, passthrough, Multiplexer};
+use doxx_core::document::{Document, ImageOptions};
+use doxx_core::export::{self, TextOptions};
+
+/// Base64 bytes in each chunk of a Kitty graphics transfer
+const KITTY_CHUNK: usize = 4096;
+
+/// Most columns and rows an image takes unless `--image-width` or
+/// `--image-height` ask for more
+const DEFAULT_MAX_COLUMNS: u32 = 80;
+const DEFAULT_MAX_ROWS: u32 = 24;
+
+/// Terminal image display capabilities
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TerminalImageSupport {
+    Kitty,      // Kitty graphics protocol
+    ITerm2,     // iTerm2 graphics protocol
+    Sixel,      // Sixel graphics
+    HalfBlocks, // Unicode half-block fallback
+    None,       // Text description only
+}
+
+/// Handles display of images in the terminal using various protocols
+#[derive(Debug)]
+pub struct TerminalImageRenderer {
+    support: TerminalImageSupport,
+    max_width: u32,
+    max_height: u32,
+}
+
+impl TerminalImageRenderer {
+    /// Create a new terminal image renderer with auto-detected capabilities
+    pub fn new() -> Self {
+        Self::with_options(None, None, None)
+    }
+
+    /// Create a new terminal image renderer with custom size limits
+    pub fn with_size_limits(max_width: Option<u32>, max_height: Option<u32>) -> Self {
+        Self::with_options(max_width, max_height, None)
+    }
+
+    /// Create a new terminal image renderer with custom size limits and scaling
+    pub fn with_options(
+        max_width: Option<u32>,
+        max_height: Option<u32>,
+        scale: Option<f32>,
+    ) -> Self {
+        let options = ImageOptions {
+            enabled: true,
+            max_width,
+            max_height,
+            scale,
+        };
+        let (max_width, max_height) = image_limits(Self::get_terminal_size(), &options);
+
+        Self {
+            support: Self::detect_capabilities(),
+            max_width,
+            max_height,
+        }
+    }
+
+    /// Create a renderer with specific capabilities (for testing)
+    pub fn with_support(support: TerminalImageSupport) -> Self {
+        let (max_width, max_height) =
+            image_limits(Self::get_terminal_size(), &ImageOptions::default());
+
+        Self {
+            support,
+            max_width,
+            max_height,
+        }
+    }
+
+    /// Detect terminal image display capabilities: from the environment
+    /// first, then by asking the terminal when that doesn't name a graphics
+    /// protocol or doxx runs inside tmux or screen
+    pub fn detect_capabilities() -> TerminalImageSupport {
+        let support = Self::detect_from_env();
+        if support != TerminalImageSupport::HalfBlocks && Multiplexer::detect().is_none() {
+            return support;
+        }
+        match terminal_probe::probe() {
+            Some(probed) if probed.kitty => TerminalImageSupport::Kitty,
+            Some(probed) if probed.iterm2 => TerminalImageSupport::ITerm2,
+            _ => support,
+        }
+    }
+
+    fn detect_from_env() -> TerminalImageSupport {
+        // Check for WezTerm FIRST - it supports Kitty protocol
+        if let Ok(term_program) = std::env::var("TERM_PROGRAM") {
+            if term_program == "WezTerm" {
+                return TerminalImageSupport::Kitty;
+            }
+        }
+
+        // Check for iTerm2 (this function exists)
+        if viuer::is_iterm_supported() {
+            return TerminalImageSupport::ITerm2;
+        }
+
+        // Sixel support disabled for now to avoid linking issues
+        // Will re-enable after fixing dependencies
+
+        // Check terminal type for Kitty support
+        if let Ok(term) = std::env::var("TERM") {
+            match term.as_str() {
+                "xterm-kitty" => TerminalImageSupport::Kitty,
+                "wezterm" => TerminalImageSupport::Kitty,
+                _ => TerminalImageSupport::HalfBlocks,
+            }
+        } else {
+            TerminalImageSupport::HalfBlocks
+        }
+    }
+
+    /// Get the current support level
+    pub fn support(&self) -> TerminalImageSupport {
+        self.support
+    }
+
+    /// Check if we can display images inline
+    pub fn can_display_images(&self) -> bool {
+        !matches!(self.support, TerminalImageSupport::None)
+    }
+
+    /// Render an image from a file path
+    pub fn render_image_from_path(&self, image_path: &Path, description: &str) -> Result<()> {
+        match self.support {
+            TerminalImageSupport::None => {
+                println!("📷 Image: {description}");
+                Ok(())
+            }
+            _ => {
+                match self.display(image_path) {
+                    Ok(_) => {
+                        // Print description after the image
+                        if !description.is_empty() {
+                            println!("📷 {description}");
+                        }
+                        Ok(())
+                    }
+                    Err(e) => {
+                        // Fallback to text description if image display fails
+                        println!("📷 Image: {description} (display failed: {e})");
+                        Ok(())
+                    }
+                }
+            }
+        }
+    }
+
+    /// Render an image from raw bytes
+    pub fn render_image_from_bytes(&self, image_data: &[u8], description: &str) -> Result<()> {
+        match self.support {
+            TerminalImageSupport::None => {
+                println!("📷 Image: {description}");
+                Ok(())
+            }
+            _ => {
+                // Create a temporary file for viuer (it needs a file path)
+                let temp_path = std::env::temp_dir().join("doxx_temp_image.png");
+                std::fs::write(&temp_path, image_data)?;
+
+                let displayed = self.display(&temp_path);
+                // Clean up temp file
+                let _ = std::fs::remove_file(&temp_path);
+                match displayed {
+                    Ok(_) => {
+                        if !description.is_empty() {
+                            println!("📷 {description}");
+                        }
+                        Ok(())
+                    }
+                    Err(e) => {
+                        println!("📷 Image: {description} (display failed: {e})");
+                        Ok(())
+                    }
+                }
+            }
+        }
+    }
+
+    /// Show the image at `path` with the detected protocol
+    fn display(&self, path: &Path) -> Result<()> {
+        let multiplexer = Multiplexer::detect();
+        // viuer only speaks a protocol it detected itself, and never through
+        // a multiplexer; the terminal's answers count for more than that
+        let viuer_knows = match self.support {
+            TerminalImageSupport::Kitty => viuer::get_kitty_support() != viuer::KittySupport::None,
+            TerminalImageSupport::ITerm2 => viuer::is_iterm_supported(),
+            _ => true,
+        };
+        if multiplexer.is_some() || !viuer_knows {
+            if let Some(sequence) = self.escape_sequence(path, multiplexer)? {
+                let mut stdout = std::io::stdout().lock();
+                stdout.write_all(sequence.as_bytes())?;
+                writeln!(stdout)?;
+                stdout.flush()?;
+                return Ok(());
+            }
+        }
+
+        // Use viuer to display the image with appropriate protocol
+        let mut conf = viuer::Config {
+            transparent: true,
+            absolute_offset: false,
+            width: Some(self.max_width),
+            height: Some(self.max_height),
+            ..Default::default()
+        };
+
+        // Set protocol based on terminal capability
+        match self.support {
+            TerminalImageSupport::Kitty => {
+                conf.use_kitty = true;
+                conf.use_iterm = false;
+            }
+            TerminalImageSupport::ITerm2 => {
+                conf.use_kitty = false;
+                conf.use_iterm = true;
+            }
+            _ => {}
+        }
+
+        viuer::print_from_file(path, &conf)?;
+        Ok(())
+    }
+
+    /// The escape codes that draw the image at `path` with the Kitty or
+    /// iTerm2 protocol, wrapped for `multiplexer`. `None` for other kinds
+    /// of support, which go through viuer.
+    fn escape_sequence(
+        &self,
+        path: &Path,
+        multiplexer: Option<Multiplexer>,
+    ) -> Result<Option<String>> {
+        let data = std::fs::read(path)?;
+        let image = image::load_from_memory(&data)?;
+        let (columns, rows) = fit_cells(
+            image.width(),
+            image.height(),
+            self.max_width,
+            self.max_height,
+        );
+        let engine = base64::engine::general_purpose::STANDARD;
+
+        Ok(match self.support {
+            TerminalImageSupport::Kitty => {
+                // f=100 is PNG, so anything else is converted first
+                let png = if image::guess_format(&data).ok() == Some(image::ImageFormat::Png) {
+                    data
+                } else {
+                    let mut png = Vec::new();
+                    image.write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)?;
+                    png
+                };
+                let encoded = engine.encode(png);
+                let chunks: Vec<&[u8]> = encoded.as_bytes().chunks(KITTY_CHUNK).collect();
+                let mut sequence = String::new();
+                for (index, chunk) in chunks.iter().enumerate() {
+                    let more = u8::from(index + 1 < chunks.len());
+                    let control = if index == 0 {
+                        format!("f=100,a=T,c={columns},r={rows},m={more}")
+                    } else {
+                        format!("m={more}")
+                    };
+                    // Base64 is ASCII
+                    let payload = std::str::from_utf8(chunk).unwrap_or_default();
+                    sequence.push_str(&passthrough(
+                        &format!("\x1b_G{control};{payload}\x1b\\"),
+                        multiplexer,
+                    ));
+                }
+                Some(sequence)
+            }
+            TerminalImageSupport::ITerm2 => Some(passthrough(
+                &format!(
+                    "\x1b]1337;File=inline=1;size={};width={columns};height={rows};preserveAspectRatio=1:{}\x07",
+                    data.len(),
+                    engine.encode(&data)
+                ),
+                multiplexer,
+            )),
+            _ => None,
+        })
+    }
+
+    /// Get terminal size for image scaling
+    fn get_terminal_size() -> (u32, u32) {
+        // Try to get terminal size from crossterm
+        if let Ok((width, height)) = crossterm::terminal::size() {
+            (width as u32, height as u32)
+        } else {
+            // Fallback to reasonable defaults
+            (80, 24)
+        }
+    }
+
+    /// Print capabilities information for debugging
+    pub fn print_capabilities(&self) {
+        println!("=== Terminal Image Debug Information ===");
+        println!("Detected support: {:?}", self.support);
+        println!("Max dimensions: {}x{}", self.max_width, self.max_height);
+        println!("Can display images: {}", self.can_display_images());
+
+        // Environment variables
+        if let Ok(term) = std::env::var("TERM") {
+            println!("TERM: {term}");
+        } else {
+            println!("TERM: not set");
+        }
+
+        if let Ok(term_program) = std::env::var("TERM_PROGRAM") {
+            println!("TERM_PROGRAM: {term_program}");
+        } else {
+            println!("TERM_PROGRAM: not set");
+        }
+
+        // Viuer capabilities
+        println!(
+            "viuer::is_iterm_supported(): {}",
+            viuer::is_iterm_supported()
+        );
+
+        // What the terminal itself answered
+        println!("Multiplexer: {:?}", Multiplexer::detect());
+        match terminal_probe::probe() {
+            Some(probed) => println!(
+                "Terminal answers: kitty={} iterm2={} sixel={}",
+                probed.kitty, probed.iterm2, probed.sixel
+            ),
+            None => println!("Terminal answers: not asked (stdout is not a terminal)"),
+        }
+
+        // Additional debug info
+        if let Ok(colorterm) = std::env::var("COLORTERM") {
+            println!("COLORTERM: {colorterm}");
+        }
+
+        println!("========================================");
+    }
+
+    /// Debug method to test image rendering
+    pub fn debug_render(&self) {
+        println!(
+            "DEBUG: Attempting to render test image with support: {:?}",
+            self.support
+        );
+    }
+}
+
+/// Print the `--export text` output to the terminal, drawing extracted
+/// images where they appear
+pub fn print_text_with_images(document: &Document, options: &TextOptions) {
+    let renderer = TerminalImageRenderer::with_options(
+        document.image_options.max_width,
+        document.image_options.max_height,
+        document.image_options.scale,
+    );
+    let rest = export::to_text_drawing_images(document, options, |before, path, description| {
+        print!("{before}");
+        renderer.render_image_from_path(path, description).is_ok()
+    });
+    print!("{rest}");
+}
+
+/// Most columns and rows an image takes on a terminal `terminal` cells in
+/// size: `max_width` and `max_height` from `options`, or else the terminal's
+/// size up to 80 by 24, times its scale, and never wider than the terminal
+fn image_limits(terminal: (u32, u32), options: &ImageOptions) -> (u32, u32) {
+    let (terminal_width, terminal_height) = (terminal.0.max(1), terminal.1.max(1));
+    let width = options
+        .max_width
+        .unwrap_or(terminal_width.min(DEFAULT_MAX_COLUMNS));
+    let height = options
+        .max_height
+        .unwrap_or(terminal_height.min(DEFAULT_MAX_ROWS));
+    let scale = options.scale_factor();
+    let scaled = |cells: u32| ((cells as f32 * scale).round() as u32).max(1);
+    (scaled(width).min(terminal_width), scaled(height))
+}
+
+/// The columns and rows an image `width` by `height` pixels takes up when
+/// fitted into `max_columns` by `max_rows` cells, keeping its shape. Cells
+/// are taken to be twice as tall as they are wide.
+fn fit_cells(width: u32, height: u32, max_columns: u32, max_rows: u32) -> (u32, u32) {
+    let (width, height) = (width.max(1) as f64, height.max(1) as f64 / 2.0);
+    let scale = (max_columns.max(1) as f64 / width).min(max_rows.max(1) as f64 / height);
+    let columns = (width * scale).round().max(1.0) as u32;
+    let rows = (height * scale).round().max(1.0) as u32;
+    (columns.min(max_columns.max(1)), rows.min(max_rows.max(1)))
+}
+
+impl Default for TerminalImageRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capability_detection() {
+        // This will vary by environment, but should not panic
+        let support = TerminalImageRenderer::detect_capabilities();
+        println!("Detected support: {support:?}");
+    }
+
+    #[test]
+    fn test_renderer_creation() {
+        let renderer = TerminalImageRenderer::new();
+        assert!(renderer.max_width > 0);
+        assert!(renderer.max_height > 0);
+    }
+
+    #[test]
+    fn test_image_limits() {
+        let options = |max_width, max_height, scale| ImageOptions {
+            enabled: true,
+            max_width,
+            max_height,
+            scale,
+        };
+        // The terminal's size, up to 80 by 24
+        assert_eq!(
+            image_limits((200, 60), &options(None, None, None)),
+            (80, 24)
+        );
+        assert_eq!(image_limits((40, 12), &options(None, None, None)), (40, 12));
+        // Asked for: larger than the default, but no wider than the terminal
+        assert_eq!(
+            image_limits((200, 60), &options(Some(160), Some(50), None)),
+            (160, 50)
+        );
+        assert_eq!(
+            image_limits((100, 30), &options(Some(160), None, None)),
+            (100, 24)
+        );
+        // Scaled, within 0.1 to 2.0
+        assert_eq!(
+            image_limits((200, 60), &options(None, None, Some(0.5))),
+            (40, 12)
+        );
+        assert_eq!(
+            image_limits((200, 60), &options(None, None, Some(9.0))),
+            (160, 48)
+        );
+    }
+
+    #[test]
+    fn test_fit_cells() {
+        // A wide image fills the width, a tall one the height
+        assert_eq!(fit_cells(800, 400, 80, 24), (80, 20));
+        assert_eq!(fit_cells(100, 1000, 80, 24), (5, 24));
+        assert_eq!(fit_cells(0, 0, 0, 0), (1, 1));
+    }
+
+    #[test]
+    fn test_can_display_images() {
+        let renderer = TerminalImageRenderer::with_support(TerminalImageSupport::Kitty);
+        assert!(renderer.can_display_images());
+
+        let renderer = TerminalImageRenderer::with_support(TerminalImageSupport::None);
+        assert!(!renderer.can_display_images());
+    }
+}
@@ -0,0 +1,276 @@
+//! Asking the terminal what images it can show.
+//!
+//! `TERM` and friends only name a handful of terminals, and inside tmux or
+//! screen they name the multiplexer instead. So when they don't settle it,
+//! doxx sends the terminal a Kitty graphics query and an iTerm2 query and
+//! reads back what it answers, with a device attributes (DA1) request
+//! behind them: every terminal answers that one, so its reply marks the end
+//! and a terminal that ignores the first two costs no more than a round
+//! trip. Inside tmux or screen all three queries are wrapped to pass
+//! through the multiplexer to the terminal outside it, DA1 included: the
+//! multiplexer would answer a bare DA1 itself, straight away, ending the
+//! wait before the terminal's replies came back. When the multiplexer
+//! doesn't pass them on, nothing answers and the probe waits out its
+//! timeout.
+
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// How long to wait for the terminal to answer
+pub const PROBE_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Image id used for the Kitty query; it is never displayed
+const KITTY_QUERY_ID: u32 = 31;
+
+/// Longest sequence screen passes through in one piece
+const SCREEN_CHUNK: usize = 760;
+
+/// A terminal multiplexer doxx is running inside
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Multiplexer {
+    Tmux,
+    Screen,
+}
+
+impl Multiplexer {
+    /// The multiplexer this process runs in, from its environment
+    pub fn detect() -> Option<Self> {
+        let term = std::env::var("TERM").unwrap_or_default();
+        if std::env::var_os("TMUX").is_some() || term.starts_with("tmux") {
+            Some(Self::Tmux)
+        } else if std::env::var_os("STY").is_some() || term.starts_with("screen") {
+            Some(Self::Screen)
+        } else {
+            None
+        }
+    }
+
+    /// `sequence` wrapped so the multiplexer hands it to the terminal
+    /// outside it unchanged
+    pub fn wrap(self, sequence: &str) -> String {
+        match self {
+            // One DCS, with the escapes inside doubled
+            Self::Tmux => format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b")),
+            // screen drops long DCS strings, so send it a piece at a time
+            Self::Screen => {
+                let mut wrapped = String::new();
+                let mut rest = sequence;
+                while !rest.is_empty() {
+                    let mut end = rest.len().min(SCREEN_CHUNK);
+                    while !rest.is_char_boundary(end) {
+                        end -= 1;
+                    }
+                    wrapped.push_str("\x1bP");
+                    wrapped.push_str(&rest[..end]);
+                    wrapped.push_str("\x1b\\");
+                    rest = &rest[end..];
+                }
+                wrapped
+            }
+        }
+    }
+}
+
+/// `sequence` wrapped for `multiplexer`, or as it is outside one
+pub fn passthrough(sequence: &str, multiplexer: Option<Multiplexer>) -> String {
+    match multiplexer {
+        Some(multiplexer) => multiplexer.wrap(sequence),
+        None => sequence.to_string(),
+    }
+}
+
+/// What the terminal said it can do
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TerminalCapabilities {
+    /// Answered the Kitty graphics query
+    pub kitty: bool,
+    /// Answered iTerm2's `ReportCellSize`
+    pub iterm2: bool,
+    /// Listed sixel graphics in its device attributes
+    pub sixel: bool,
+    /// The multiplexer the queries were passed through
+    pub multiplexer: Option<Multiplexer>,
+}
+
+/// The queries to send: Kitty, iTerm2 and then DA1 for the terminal to
+/// answer last, each passed through `multiplexer`
+pub fn probe_query(multiplexer: Option<Multiplexer>) -> String {
+    let kitty = format!("\x1b_Gi={KITTY_QUERY_ID},s=1,v=1,a=q,t=d,f=24;AAAA\x1b\\");
+    let iterm2 = "\x1b]1337;ReportCellSize\x07";
+    format!(
+        "{}{}{}",
+        passthrough(&kitty, multiplexer),
+        passthrough(iterm2, multiplexer),
+        passthrough("\x1b[c", multiplexer)
+    )
+}
+
+/// The attributes in the first DA1 reply (`ESC [ ? 62 ; 4 c`) in `reply`,
+/// or `None` before it has arrived in full
+pub fn device_attributes(reply: &[u8]) -> Option<Vec<u32>> {
+    let start = reply.windows(3).position(|window| window == b"\x1b[?")? + 3;
+    let length = reply[start..]
+        .iter()
+        .position(|byte| !byte.is_ascii_digit() && *byte != b';')?;
+    if reply[start + length] != b'c' {
+        return None;
+    }
+    let attributes = std::str::from_utf8(&reply[start..start + length]).ok()?;
+    Some(
+        attributes
+            .split(';')
+            .filter_map(|attribute| attribute.parse().ok())
+            .collect(),
+    )
+}
+
+/// What the replies to [`probe_query`] say the terminal supports
+pub fn parse_replies(reply: &[u8], multiplexer: Option<Multiplexer>) -> TerminalCapabilities {
+    let contains = |needle: &[u8]| reply.windows(needle.len()).any(|window| window == needle);
+    TerminalCapabilities {
+        kitty: contains(format!("_Gi={KITTY_QUERY_ID};OK").as_bytes()),
+        iterm2: contains(b"]1337;ReportCellSize="),
+        // Attribute 4 is sixel graphics
+        sixel: device_attributes(reply).is_some_and(|attributes| attributes.contains(&4)),
+        multiplexer,
+    }
+}
+
+/// Ask the terminal what it supports, once per process. `None` when
+/// stdout isn't a terminal or it can't be asked.
+pub fn probe() -> Option<TerminalCapabilities> {
+    static PROBED: OnceLock<Option<TerminalCapabilities>> = OnceLock::new();
+    *PROBED.get_or_init(|| probe_with_timeout(PROBE_TIMEOUT))
+}
+
+/// Ask the terminal what it supports, waiting up to `timeout` for answers
+pub fn probe_with_timeout(timeout: Duration) -> Option<TerminalCapabilities> {
+    use std::io::IsTerminal;
+
+    if !std::io::stdout().is_terminal() {
+        return None;
+    }
+    let multiplexer = Multiplexer::detect();
+    let reply = query_terminal(probe_query(multiplexer).as_bytes(), timeout)?;
+    Some(parse_replies(&reply, multiplexer))
+}
+
+/// Write `query` to the controlling terminal and read what comes back
+/// until the DA1 reply or `timeout`, with the terminal in raw mode so the
+/// replies aren't echoed or held back for a newline
+#[cfg(unix)]
+fn query_terminal(query: &[u8], timeout: Duration) -> Option<Vec<u8>> {
+    use std::io::{Read, Write};
+    use std::os::fd::AsRawFd;
+    use std::time::Instant;
+
+    let mut tty = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .ok()?;
+    let fd = tty.as_raw_fd();
+
+    // SAFETY: `fd` is open for as long as `tty` is, and tcgetattr only
+    // writes to the termios it is given
+    let original = unsafe {
+        let mut termios = std::mem::zeroed::<libc::termios>();
+        if libc::tcgetattr(fd, &mut termios) != 0 {
+            return None;
+        }
+        termios
+    };
+    let mut raw = original;
+    // SAFETY: as above; cfmakeraw only changes the struct it is given
+    unsafe {
+        libc::cfmakeraw(&mut raw);
+        if libc::tcsetattr(fd, libc::TCSANOW, &raw) != 0 {
+            return None;
+        }
+    }
+
+    let mut reply = Vec::new();
+    let sent = tty.write_all(query).and_then(|_| tty.flush()).is_ok();
+    let deadline = Instant::now() + timeout;
+    let mut chunk = [0u8; 256];
+    while sent && device_attributes(&reply).is_none() {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            break;
+        }
+        let mut ready = libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // SAFETY: one valid pollfd, for an open descriptor
+        let waiting = unsafe { libc::poll(&mut ready, 1, left.as_millis().max(1) as libc::c_int) };
+        if waiting <= 0 {
+            break;
+        }
+        match tty.read(&mut chunk) {
+            Ok(0) | Err(_) => break,
+            Ok(read) => reply.extend_from_slice(&chunk[..read]),
+        }
+    }
+
+    // SAFETY: restores the settings read above on the same descriptor
+    unsafe {
+        libc::tcsetattr(fd, libc::TCSANOW, &original);
+    }
+    sent.then_some(reply)
+}
+
+#[cfg(not(unix))]
+fn query_terminal(_query: &[u8], _timeout: Duration) -> Option<Vec<u8>> {
+    // Windows consoles are left to the environment checks
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_passthrough_wrapping() {
+        assert_eq!(passthrough("\x1b[c", None), "\x1b[c");
+        assert_eq!(
+            Multiplexer::Tmux.wrap("\x1b_Ga=q\x1b\\"),
+            "\x1bPtmux;\x1b\x1b_Ga=q\x1b\x1b\\\x1b\\"
+        );
+
+        let long = "x".repeat(SCREEN_CHUNK + 10);
+        let wrapped = Multiplexer::Screen.wrap(&long);
+        assert_eq!(wrapped.matches("\x1bP").count(), 2);
+        assert!(wrapped.starts_with("\x1bPxxx") && wrapped.ends_with("xxx\x1b\\"));
+
+        // DA1 goes last and wrapped too, so the terminal rather than the
+        // multiplexer answers it, after the other two
+        let query = probe_query(Some(Multiplexer::Tmux));
+        assert!(query.starts_with("\x1bPtmux;\x1b\x1b_Gi=31,"));
+        assert!(query.ends_with("\x07\x1b\\\x1bPtmux;\x1b\x1b[c\x1b\\"));
+        assert!(probe_query(None).ends_with("\x07\x1b[c"));
+    }
+
+    #[test]
+    fn test_parse_replies() {
+        let kitty = b"\x1b_Gi=31;OK\x1b\\\x1b[?62;22c";
+        assert_eq!(
+            parse_replies(kitty, None),
+            TerminalCapabilities {
+                kitty: true,
+                ..TerminalCapabilities::default()
+            }
+        );
+
+        let iterm2 = b"\x1b]1337;ReportCellSize=17.0;8.0;2.0\x07\x1b[?62;4;22c";
+        let capabilities = parse_replies(iterm2, Some(Multiplexer::Tmux));
+        assert!(capabilities.iterm2 && capabilities.sixel && !capabilities.kitty);
+        assert_eq!(capabilities.multiplexer, Some(Multiplexer::Tmux));
+
+        assert_eq!(device_attributes(b"\x1b[?1;2c"), Some(vec![1, 2]));
+        assert_eq!(device_attributes(b"\x1b[?64;4"), None);
+        assert_eq!(device_attributes(b"\x1b_Gi=31;OK"), None);
+        assert_eq!(parse_replies(b"", None), TerminalCapabilities::default());
+    }
+}
@@ -0,0 +1,5748 @@
+use anyhow::Result;
+use arboard::Clipboard;
+use crossterm::{
+    cursor,
+    event::{
+        self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, KeyboardEnhancementFlags,
+        MouseEventKind, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
+    execute,
+    terminal::{
+        disable_raw_mode, enable_raw_mode, supports_keyboard_enhancement, EnterAlternateScreen,
+        LeaveAlternateScreen,
+    },
+    tty::IsTty,
+};
+use ratatui::{
+    backend::{Backend, CrosstermBackend},
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Position, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{
+        Block, Borders, Clear, List, ListItem, ListState, Paragraph, Scrollbar,
+        ScrollbarOrientation, ScrollbarState, StatefulWidget, Widget, Wrap,
+    },
+    Frame, Terminal,
+};
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use unicode_width::UnicodeWidthStr;
+
+use crate::i18n::{tr, tr_args};
+use crate::image_preview::ImagePreview;
+use crate::render::{RenderContext, RendererRegistry};
+use crate::table_view::{column_count, column_summary, fit_column_widths, TableView};
+use crate::terminal_image::TerminalImageRenderer;
+use crate::terminal_probe::{self, Multiplexer};
+use crate::widget::DoxxViewerWidget;
+use doxx_core::ai::{AiClient, Answer};
+use doxx_core::color;
+use doxx_core::config::{AiConfig, Config, TableLayout};
+use doxx_core::convert::format_for_path;
+use doxx_core::diff::{describe_cell_change, word_diff, DiffEntry, DocumentDiff, WordChange};
+use doxx_core::document::*;
+use doxx_core::events::{self, EventSink};
+use doxx_core::figures::{number_figures, resolve_reference};
+use doxx_core::fuzzy::{fuzzy_match, FuzzyMatch};
+use doxx_core::highlight;
+use doxx_core::history::{HistoryStore, SessionClock};
+use doxx_core::hygiene::Hygiene;
+use doxx_core::limits::Limits;
+use doxx_core::ocr::{self, OcrOptions};
+use doxx_core::pages::PageSource;
+use doxx_core::sandbox;
+use doxx_core::state::{file_digest, PositionStore, ReadingPosition};
+use doxx_core::text_fold::find_matches;
+use doxx_core::text_layout::{layout_paragraph, LayoutOptions};
+use doxx_core::text_width::{
+    align_to_width, display_width, ellipsize, skip_width, truncate_to_width,
+};
+use ratatui_image::{
+    picker::{Picker, ProtocolType},
+    protocol::StatefulProtocol,
+    Resize, StatefulImage,
+};
+
+/// Decoded image ready for inline rendering, keyed by element index in `ImageProtocols`
+pub struct InlineImage {
+    pub protocol: Box<dyn StatefulProtocol>,
+    pub width_px: u32,
+    pub height_px: u32,
+}
+
+type ImageProtocols = HashMap<usize, InlineImage>;
+
+/// Upper bound on palette rows so huge documents stay responsive while typing
+const PALETTE_MAX_RESULTS: usize = 200;
+/// Oldest entries fall off the jump list past this length
+const JUMP_LIST_MAX: usize = 100;
+
+/// A jump target offered by the heading palette
+pub struct PaletteEntry {
+    pub label: String,
+    pub element_index: usize,
+    /// Heading level, or `None` for a body paragraph
+    pub level: Option<u8>,
+}
+
+/// Rendered height of every document element at the current width, so the
+/// document view can scroll by visual line instead of by element
+#[derive(Default)]
+pub struct DocumentLayout {
+    width: u16,
+    font_size: (u16, u16),
+    max_image_rows: u16,
+    image_count: usize,
+    /// First visual row of each element, followed by the total row count
+    starts: Vec<usize>,
+}
+
+impl DocumentLayout {
+    fn is_stale(
+        &self,
+        width: u16,
+        font_size: (u16, u16),
+        max_image_rows: u16,
+        images: usize,
+    ) -> bool {
+        self.starts.is_empty()
+            || self.width != width
+            || self.font_size != font_size
+            || self.max_image_rows != max_image_rows
+            || self.image_count != images
+    }
+
+    pub fn total_rows(&self) -> usize {
+        self.starts.last().copied().unwrap_or(0)
+    }
+
+    /// Number of visual rows taken by the element at `index`
+    pub fn element_rows(&self, index: usize) -> usize {
+        match (self.starts.get(index), self.starts.get(index + 1)) {
+            (Some(start), Some(end)) => end - start,
+            _ => 0,
+        }
+    }
+
+    /// Visual row of `offset` rows into the element at `index`
+    pub fn row_of(&self, index: usize, offset: usize) -> usize {
+        self.starts.get(index).copied().unwrap_or(0) + offset
+    }
+
+    /// Element containing visual row `row`, and how far into it that row is
+    pub fn position_of(&self, row: usize) -> (usize, usize) {
+        let elements = self.starts.len().saturating_sub(1);
+        if elements == 0 {
+            return (0, 0);
+        }
+        let index = self.starts[..elements]
+            .partition_point(|&start| start <= row)
+            .saturating_sub(1);
+        (index, row - self.starts[index])
+    }
+}
+
+/// Set while the kitty keyboard protocol flags are pushed, so every path that
+/// restores the terminal (including the panic hook) knows to pop them
+static KEYBOARD_ENHANCED: AtomicBool = AtomicBool::new(false);
+
+/// How often the UI wakes up without input to process background work
+const TICK_RATE: Duration = Duration::from_millis(250);
+
+/// How long a status bar message stays visible
+const STATUS_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Narrowest reading mode column, and how much `+`/`-` change its width
+const MIN_READING_WIDTH: u16 = 20;
+const READING_WIDTH_STEP: u16 = 4;
+
+/// Messages delivered to the UI loop from background tasks
+pub enum AppMessage {
+    /// Show a transient message in the status bar
+    Status(String),
+    /// An image finished decoding and is ready for inline rendering
+    ImageLoaded(usize, InlineImage),
+    /// Top-level paragraphs and tables parsed so far while loading, out of all of them
+    LoadProgress(usize, usize),
+    /// The first elements of a document that is still loading
+    Preview(Vec<DocumentElement>),
+    /// The document finished loading in the background
+    Loaded(Box<Result<Document>>),
+    /// Another piece of the answer to question number `.0`
+    AnswerText(usize, String),
+    /// The model finished answering question number `.0`
+    Answered(usize, Result<Answer>),
+    /// Leave the viewer (e.g. on SIGINT)
+    Quit,
+}
+
+/// How the viewer starts: view, position, search and display toggles
+#[derive(Debug, Clone, Default)]
+pub struct ViewerOptions {
+    /// Show document colors
+    pub color: bool,
+    /// Draw without any color, highlighting in reverse video instead
+    /// (`--color=never`, `NO_COLOR`)
+    pub monochrome: bool,
+    /// Open the interactive viewer even when stdout isn't a terminal
+    pub force_ui: bool,
+    /// Start at the top instead of the last reading position
+    pub fresh: bool,
+    /// Show images inline
+    pub images: bool,
+    /// Start with the outline
+    pub outline: bool,
+    pub outline_options: OutlineOptions,
+    /// Page to open at
+    pub page: Option<usize>,
+    /// Search to run on opening
+    pub search: Option<String>,
+    pub search_fuzzy: bool,
+    /// Part of the document the search looks in
+    pub search_scope: SearchScope,
+    /// Parse warnings are already shown elsewhere (e.g. printed to stderr)
+    pub warnings: bool,
+    /// How tracked changes were parsed; with `Markup` the `{++ ++}` and
+    /// `{-- --}` marks in the text are drawn as insertions and deletions
+    pub changes: TrackedChanges,
+    /// How to parse the file again after it is edited with `O` (`None` = the
+    /// document isn't reloaded)
+    pub source: Option<DocumentSource>,
+}
+
+/// How the viewer reloads a document that changed on disk: how it is
+/// parsed, and what is done to it before it is shown
+#[derive(Debug, Clone)]
+pub struct DocumentSource {
+    pub parser: DocumentParser,
+    pub preparation: Preparation,
+}
+
+/// Where a document is parsed
+#[derive(Debug, Clone)]
+pub enum DocumentParser {
+    /// Parse the file in this process
+    File(ParseOptions),
+    /// Parse it in a sandboxed worker process (`--sandbox`) started as
+    /// `program args...`
+    Worker {
+        program: PathBuf,
+        args: Vec<OsString>,
+        limits: Limits,
+    },
+}
+
+/// What is done to a document once it is parsed, on opening and on every
+/// reload
+#[derive(Debug, Clone, Default)]
+pub struct Preparation {
+    /// Column types given with `--column-types`
+    pub column_types: Vec<(String, CellDataType)>,
+    /// Text recognition in images (`--ocr`, `--scanned`)
+    pub ocr: Option<OcrPass>,
+}
+
+/// Reading the text in a document's images with tesseract
+#[derive(Debug, Clone)]
+pub struct OcrPass {
+    pub options: OcrOptions,
+    /// Replace page scans with the text read from them (`--scanned`)
+    pub scanned: bool,
+    /// Keep showing the images once they are read (`--images`)
+    pub show_images: bool,
+}
+
+impl Preparation {
+    /// `options`, with images extracted for OCR even when they aren't shown
+    pub fn image_options(&self, options: &ImageOptions) -> ImageOptions {
+        ImageOptions {
+            enabled: options.enabled || self.ocr.is_some(),
+            ..options.clone()
+        }
+    }
+
+    /// Read the text in the images of `document`, returning warnings about
+    /// those that couldn't be read. A `--sandbox` worker does this itself.
+    pub fn recognize(&self, document: &mut Document) -> Vec<String> {
+        match &self.ocr {
+            Some(pass) if pass.scanned => ocr::read_scanned_pages(document, &pass.options),
+            Some(pass) => ocr::recognize_images(document, &pass.options),
+            None => Vec::new(),
+        }
+    }
+
+    /// Everything after OCR: drop images that were only extracted to be
+    /// read, and apply the column types
+    pub fn finish(&self, document: &mut Document) {
+        if self.ocr.as_ref().is_some_and(|pass| !pass.show_images) {
+            document.image_options.enabled = false;
+            for element in &mut document.elements {
+                if let DocumentElement::Image { image_path, .. } = element {
+                    *image_path = None;
+                }
+            }
+        }
+        override_column_types(document, &self.column_types);
+    }
+}
+
+/// A document still being parsed while the viewer shows what there is of it
+/// (see [`run_viewer_while_loading`])
+pub struct Loading {
+    /// Top-level paragraphs and tables parsed so far, out of `total`
+    pub parsed: usize,
+    pub total: usize,
+    /// How the viewer starts once the whole document is in
+    options: ViewerOptions,
+    /// Record reading time once loaded (`history.enabled`)
+    history: bool,
+}
+
+/// Answer to a question asked with `?`, shown in a popup as it is written
+#[derive(Debug, Clone, Default)]
+pub struct AnswerPopup {
+    /// Which question this is, counting those asked
+    id: usize,
+    pub question: String,
+    pub text: String,
+    /// Elements the answer cites, once it is complete
+    pub citations: Vec<usize>,
+    pub done: bool,
+    /// Lines of the popup scrolled out of view
+    pub scroll: u16,
+}
+
+pub struct App {
+    pub document: Document,
+    /// Set until the document has finished loading in the background
+    pub loading: Option<Loading>,
+    /// Why loading in the background failed
+    load_error: Option<anyhow::Error>,
+    pub current_view: ViewMode,
+    /// Element at the top of the document view
+    pub scroll_offset: usize,
+    /// Visual rows of the top element scrolled out of view
+    pub scroll_line_offset: usize,
+    pub layout: DocumentLayout,
+    pub viewport_height: u16,
+    pub search_query: String,
+    pub search_results: Vec<SearchResult>,
+    /// Match ignoring accents and tolerating small typos
+    pub search_fuzzy: bool,
+    /// Part of the document searched (Ctrl-T cycles through them)
+    pub search_scope: SearchScope,
+    pub current_search_index: usize,
+    pub outline_state: ListState,
+    /// Headings listed in the outline, built once per document
+    outline_items: Vec<OutlineItem>,
+    /// First outline entry on screen; the mouse wheel moves it without
+    /// moving the selection
+    pub outline_scroll: usize,
+    /// Entries the outline showed when last drawn
+    outline_height: usize,
+    pub show_help: bool,
+    pub clipboard: Option<Clipboard>,
+    pub status_message: Option<String>,
+    pub color_enabled: bool,
+    /// Draw without any color (see [`ViewerOptions::monochrome`])
+    pub monochrome: bool,
+    pub image_picker: Option<Picker>,
+    pub image_protocols: ImageProtocols,
+    /// Most rows a half-block image takes up (0 = the view height)
+    pub halfblock_rows: u16,
+    pub status_expires_at: Option<Instant>,
+    pub should_quit: bool,
+    pub prompt: Option<Prompt>,
+    pub pending_bookmark: Option<BookmarkAction>,
+    pub pending_heading: Option<HeadingDirection>,
+    /// Visual selection being extended with j/k, if one is active
+    pub selection: Option<Selection>,
+    /// Table open in table mode (Enter on a table)
+    pub table_view: Option<TableView>,
+    /// Picture open in the image preview (`i` on an image)
+    pub image_preview: Option<ImagePreview>,
+    /// Show the banner about macros, external content and the like (Esc hides it)
+    pub hygiene_banner: bool,
+    /// How tables wider than the document view are drawn
+    pub table_layout: TableLayout,
+    /// Columns full-width tables are scrolled sideways by
+    pub table_shift: usize,
+    /// How paragraphs are broken into lines and aligned
+    pub paragraph_layout: LayoutOptions,
+    /// Positions (element, line offset) left by jumps, for Ctrl-O/Ctrl-I
+    pub jump_list: Vec<(usize, usize)>,
+    /// Current place in `jump_list`; equal to its length when not moving through it
+    pub jump_index: usize,
+    pub outline_options: OutlineOptions,
+    pub split_view: bool,
+    pub split_focus: SplitFocus,
+    /// Highlight one row of the document view as the reading line
+    pub reading_ruler: bool,
+    /// Reading mode: the document alone, in a centered column
+    pub reading_mode: bool,
+    /// Width of the reading mode column
+    pub reading_width: u16,
+    /// Highlight text Word doesn't show: hidden runs and text colored like
+    /// its background
+    pub reveal_hidden: bool,
+    /// The text carries `--changes markup` marks to draw as revisions
+    revision_markup: bool,
+    /// Words from each element to the end of the document, for the reading
+    /// time left
+    words_after: Vec<usize>,
+    words_per_minute: u32,
+    /// Reading time per section, when `history.enabled` is set
+    pub reading_session: Option<SessionClock>,
+    /// Ruler row, counted from the top of the view
+    pub ruler_offset: usize,
+    /// Dim elements other than the one under the ruler
+    pub dim_unfocused: bool,
+    pub palette_query: String,
+    pub palette_entries: Vec<PaletteEntry>,
+    pub palette_matches: Vec<(usize, FuzzyMatch)>,
+    pub palette_state: ListState,
+    pub palette_include_paragraphs: bool,
+    /// The palette lists numbered figures and tables instead of headings
+    pub palette_figures: bool,
+    /// Renderers that take over drawing some elements
+    pub renderers: RendererRegistry,
+    /// Answer to the last question asked with `?`, while its popup is open
+    pub answer: Option<AnswerPopup>,
+    /// Questions asked so far
+    questions_asked: usize,
+    /// Language model settings (`ai.`)
+    ai: AiConfig,
+    /// Where the terminal cursor goes after drawing (in the search or a prompt)
+    pub cursor_position: Option<Position>,
+    /// Command that opens the document for editing (`ui.editor`, else
+    /// `$VISUAL` or `$EDITOR`)
+    editor: String,
+    source: Option<DocumentSource>,
+    /// Modification time of the file when it was opened for editing; while
+    /// set, the document is reloaded whenever the file is saved
+    watched_modified: Option<SystemTime>,
+    pub message_tx: UnboundedSender<AppMessage>,
+    pub message_rx: UnboundedReceiver<AppMessage>,
+}
+
+#[derive(Debug, Clone)]
+pub enum ViewMode {
+    Document,
+    Outline,
+    Search,
+    Palette,
+    /// One table, with its columns scrolled, hidden and sorted
+    Table,
+    /// One picture on the whole screen, zoomed and panned
+    Image,
+    #[allow(dead_code)]
+    Help,
+}
+
+/// What a line of prompt input will be used for once submitted
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PromptKind {
+    /// `!` - run a shell command, substituting `%` with the document path
+    ShellCommand,
+    /// `/` - incremental search that highlights matches in the document
+    Search,
+    /// `g` - go to a page (`42`), a percentage (`50%`) or a heading number (`3.2`)
+    GoTo,
+    /// `e`/`E` - save the document, section or selection to a file, in the
+    /// format its extension names
+    Export(ExportScope),
+    /// `?` - ask the language model under `ai.` about the document
+    Ask,
+}
+
+impl PromptKind {
+    fn label(&self) -> String {
+        match self {
+            PromptKind::ShellCommand => "!".to_string(),
+            PromptKind::Search => "/".to_string(),
+            PromptKind::GoTo => "g ".to_string(),
+            PromptKind::Ask => "? ".to_string(),
+            PromptKind::Export(scope) => format!(
+                "{} ",
+                tr(match scope {
+                    ExportScope::Document => "prompt-export-document",
+                    ExportScope::Section => "prompt-export-section",
+                    ExportScope::Selection { .. } => "prompt-export-selection",
+                })
+            ),
+        }
+    }
+}
+
+/// What the export prompt saves
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportScope {
+    Document,
+    /// The section at the top of the view, down to the next heading at the
+    /// same or a higher level
+    Section,
+    /// Elements selected with `V`
+    Selection {
+        start: usize,
+        end: usize,
+    },
+}
+
+/// Which pane receives keys in the split outline/document layout
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SplitFocus {
+    Outline,
+    Document,
+}
+
+/// Direction of a `[`/`]` heading motion, waiting for its second key
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HeadingDirection {
+    Previous,
+    Next,
+}
+
+/// Second key expected after `m` (set) or `'` (jump) for bookmarks
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BookmarkAction {
+    Set,
+    Jump,
+}
+
+/// Elements marked with `V` for copying, from where the selection started
+/// to the element it has been extended to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Selection {
+    pub anchor: usize,
+    pub cursor: usize,
+}
+
+impl Selection {
+    pub fn range(&self) -> Range<usize> {
+        self.anchor.min(self.cursor)..self.anchor.max(self.cursor) + 1
+    }
+}
+
+/// Single-line text input shown in place of the key hints
+#[derive(Debug, Clone)]
+pub struct Prompt {
+    pub kind: PromptKind,
+    pub input: String,
+}
+
+impl App {
+    pub fn new(document: Document, options: &ViewerOptions, config: &Config) -> Self {
+        let (message_tx, message_rx) = unbounded_channel();
+        let outline_items = generate_outline_with(&document, &options.outline_options);
+        let mut app = Self {
+            document,
+            loading: None,
+            load_error: None,
+            current_view: ViewMode::Document,
+            scroll_offset: 0,
+            scroll_line_offset: 0,
+            layout: DocumentLayout::default(),
+            viewport_height: 0,
+            search_query: String::new(),
+            search_results: Vec::new(),
+            search_fuzzy: false,
+            search_scope: SearchScope::All,
+            current_search_index: 0,
+            outline_state: ListState::default(),
+            outline_items,
+            outline_scroll: 0,
+            outline_height: 0,
+            show_help: false,
+            clipboard: Clipboard::new().ok(),
+            status_message: None,
+            color_enabled: options.color,
+            monochrome: options.monochrome,
+            image_picker: None,
+            image_protocols: HashMap::new(),
+            status_expires_at: None,
+            should_quit: false,
+            prompt: None,
+            pending_bookmark: None,
+            pending_heading: None,
+            selection: None,
+            table_view: None,
+            image_preview: None,
+            hygiene_banner: false,
+            table_layout: config.reading.table_layout,
+            table_shift: 0,
+            paragraph_layout: LayoutOptions {
+                algorithm: config.reading.wrap,
+                hyphenate: config.reading.hyphenate,
+                align: config.reading.align_paragraphs,
+            },
+            jump_list: Vec::new(),
+            jump_index: 0,
+            split_view: false,
+            split_focus: SplitFocus::Document,
+            reading_ruler: config.reading.ruler,
+            reading_mode: false,
+            reading_width: config.reading.width.max(MIN_READING_WIDTH),
+            reveal_hidden: false,
+            revision_markup: options.changes == TrackedChanges::Markup,
+            words_after: Vec::new(),
+            words_per_minute: config.reading.words_per_minute.max(1),
+            reading_session: None,
+            ruler_offset: 0,
+            dim_unfocused: config.reading.dim_unfocused,
+            outline_options: options.outline_options.clone(),
+            palette_query: String::new(),
+            palette_entries: Vec::new(),
+            palette_matches: Vec::new(),
+            palette_state: ListState::default(),
+            palette_include_paragraphs: false,
+            palette_figures: false,
+            renderers: RendererRegistry::default(),
+            answer: None,
+            questions_asked: 0,
+            ai: config.ai.clone(),
+            cursor_position: None,
+            editor: config.ui.editor.clone(),
+            halfblock_rows: config.ui.halfblock_rows,
+            source: options.source.clone(),
+            watched_modified: None,
+            message_tx,
+            message_rx,
+        };
+        app.apply_options(options);
+        app
+    }
+
+    /// Viewer for the document at `path` that is still loading, showing its
+    /// elements as they come in (see [`run_viewer_while_loading`])
+    pub fn loading(path: &Path, options: &ViewerOptions, config: &Config) -> Self {
+        let placeholder = Document {
+            title: path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            metadata: DocumentMetadata {
+                file_path: path.to_string_lossy().into_owned(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        // Positions, searches and images wait for the whole document
+        let display = ViewerOptions {
+            color: options.color,
+            monochrome: options.monochrome,
+            outline_options: options.outline_options.clone(),
+            changes: options.changes,
+            ..Default::default()
+        };
+        let mut app = Self::new(placeholder, &display, config);
+        app.loading = Some(Loading {
+            parsed: 0,
+            total: 0,
+            options: options.clone(),
+            history: config.history.enabled,
+        });
+        app
+    }
+
+    /// Start the way the command line asked: view, position, search, images
+    fn apply_options(&mut self, options: &ViewerOptions) {
+        if options.outline {
+            self.current_view = ViewMode::Outline;
+        }
+
+        self.search_fuzzy = options.search_fuzzy;
+        self.search_scope = options.search_scope.clone();
+        if let Some(search) = &options.search {
+            self.search_query = search.clone();
+            self.refresh_search();
+            self.current_view = ViewMode::Search;
+        }
+
+        if let Some(page) = options.page {
+            match self.document.pages.first_element(page) {
+                Some(element) => self.jump_to_element(element),
+                None => self.set_status_message(tr_args(
+                    "status-page-past-end",
+                    &[
+                        ("page", page.into()),
+                        ("pages", self.document.pages.count.into()),
+                    ],
+                )),
+            }
+        }
+
+        self.hygiene_banner = !self.document.hygiene.is_clean();
+
+        self.count_words();
+
+        if !self.document.warnings.is_empty() && !options.warnings {
+            self.set_status_message(tr_args(
+                "status-parse-warnings",
+                &[("count", self.document.warnings.len().into())],
+            ));
+        }
+
+        // Initialize image support if images are enabled
+        if options.images {
+            self.init_image_support();
+        }
+    }
+
+    /// Viewer with the default options and configuration, e.g. for a
+    /// [`DoxxViewerWidget`] in another application
+    pub fn embedded(document: Document) -> Self {
+        Self::new(document, &ViewerOptions::default(), &Config::default())
+    }
+
+    fn count_words(&mut self) {
+        let mut words = 0;
+        self.words_after = std::iter::once(0)
+            .chain(self.document.elements.iter().rev().map(|element| {
+                words += element.word_count();
+                words
+            }))
+            .collect();
+        self.words_after.reverse();
+    }
+
+    fn init_image_support(&mut self) {
+        // Query the terminal for its font size; graphics protocols need accurate
+        // cell dimensions, so without them fall back to half-block rendering
+        #[cfg(unix)]
+        let termios_picker = Picker::from_termios().ok();
+        #[cfg(not(unix))]
+        let termios_picker: Option<Picker> = None;
+
+        let picker = match termios_picker {
+            Some(mut picker) => {
+                picker.guess_protocol();
+                apply_terminal_probe(&mut picker);
+                picker
+            }
+            None => {
+                let mut picker = Picker::new((8, 16));
+                picker.protocol_type = ProtocolType::Halfblocks;
+                picker
+            }
+        };
+
+        self.load_inline_images(picker);
+    }
+
+    fn load_inline_images(&mut self, picker: Picker) {
+        // Decode images in the background so the document shows up immediately;
+        // each one is handed to the UI loop as soon as it is ready
+        let images: Vec<(usize, std::path::PathBuf)> = self
+            .document
+            .elements
+            .iter()
+            .enumerate()
+            .filter_map(|(index, element)| match element {
+                DocumentElement::Image {
+                    image_path: Some(path),
+                    ..
+                } => Some((index, path.clone())),
+                _ => None,
+            })
+            .collect();
+
+        let tx = self.message_tx.clone();
+        let mut decode_picker = picker;
+        tokio::task::spawn_blocking(move || {
+            for (index, path) in images {
+                let decoded = image::ImageReader::open(&path)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|reader| Ok(reader.decode()?));
+                let message = match decoded {
+                    Ok(dyn_img) => {
+                        let (width_px, height_px) = (dyn_img.width(), dyn_img.height());
+                        let protocol = decode_picker.new_resize_protocol(dyn_img);
+                        AppMessage::ImageLoaded(
+                            index,
+                            InlineImage {
+                                protocol,
+                                width_px,
+                                height_px,
+                            },
+                        )
+                    }
+                    Err(e) => AppMessage::Status(tr_args(
+                        "status-image-failed",
+                        &[
+                            ("path", path.display().to_string().into()),
+                            ("error", e.to_string().into()),
+                        ],
+                    )),
+                };
+                if tx.send(message).is_err() {
+                    break;
+                }
+            }
+        });
+
+        self.image_picker = Some(picker);
+    }
+
+    /// React to a terminal resize: if the cell size in pixels changed (e.g. the
+    /// font was zoomed), rebuild the image protocols so they fit the new grid
+    pub fn handle_resize(&mut self) {
+        let Some(picker) = self.image_picker else {
+            return;
+        };
+
+        #[cfg(unix)]
+        let font_size = Picker::from_termios().map(|p| p.font_size).ok();
+        #[cfg(not(unix))]
+        let font_size: Option<(u16, u16)> = None;
+
+        if let Some(font_size) = font_size.filter(|size| *size != picker.font_size) {
+            let mut picker = picker;
+            picker.font_size = font_size;
+            self.image_protocols.clear();
+            self.load_inline_images(picker);
+        }
+    }
+
+    pub fn next_search_result(&mut self) {
+        if !self.search_results.is_empty() {
+            self.current_search_index = (self.current_search_index + 1) % self.search_results.len();
+            if let Some(result) = self.search_results.get(self.current_search_index) {
+                self.jump_with_history(result.element_index);
+            }
+        }
+    }
+
+    pub fn prev_search_result(&mut self) {
+        if !self.search_results.is_empty() {
+            self.current_search_index = if self.current_search_index == 0 {
+                self.search_results.len() - 1
+            } else {
+                self.current_search_index - 1
+            };
+            if let Some(result) = self.search_results.get(self.current_search_index) {
+                self.jump_with_history(result.element_index);
+            }
+        }
+    }
+
+    /// Outline entries after applying the configured depth and numbering
+    pub fn outline(&self) -> &[OutlineItem] {
+        &self.outline_items
+    }
+
+    /// Select an outline entry, scrolling the outline to show it
+    pub fn select_outline(&mut self, index: usize) {
+        let index = index.min(self.outline_items.len().saturating_sub(1));
+        self.outline_state.select(Some(index));
+        let height = self.outline_height.max(1);
+        if index < self.outline_scroll {
+            self.outline_scroll = index;
+        } else if index >= self.outline_scroll + height {
+            self.outline_scroll = index + 1 - height;
+        }
+    }
+
+    /// Move the outline selection by `delta` entries
+    pub fn move_outline_selection(&mut self, delta: isize) {
+        let selected = self.outline_state.selected().unwrap_or(0);
+        self.select_outline(selected.saturating_add_signed(delta));
+    }
+
+    /// Scroll the outline by `delta` entries, leaving the selection where it is
+    pub fn scroll_outline(&mut self, delta: isize) {
+        let last = self
+            .outline_items
+            .len()
+            .saturating_sub(self.outline_height.max(1));
+        self.outline_scroll = self.outline_scroll.saturating_add_signed(delta).min(last);
+    }
+
+    /// Show or hide the outline pane next to the document
+    pub fn toggle_split(&mut self) {
+        self.split_view = !self.split_view;
+        self.split_focus = SplitFocus::Document;
+    }
+
+    /// Highlight the outline entry for the section containing the top of the
+    /// document view (scroll-spy)
+    pub fn sync_outline_to_scroll(&mut self) {
+        let current = self
+            .outline()
+            .iter()
+            .rposition(|item| item.element_index <= self.scroll_offset);
+        self.select_outline(current.unwrap_or(0));
+    }
+
+    /// Move the outline pane selection and preview that section in the document
+    pub fn split_outline_move(&mut self, down: bool) {
+        self.move_outline_selection(if down { 1 } else { -1 });
+        let selected = self.outline_state.selected().unwrap_or(0);
+        if let Some(element) = self.outline().get(selected).map(|item| item.element_index) {
+            self.jump_to_element(element);
+        }
+    }
+
+    /// Move focus to the outline pane of the split view. Browsing the pane
+    /// moves the document, so where reading left off is remembered.
+    pub fn focus_outline_pane(&mut self) {
+        self.record_jump();
+        self.split_focus = SplitFocus::Outline;
+    }
+
+    /// Remember the current position before a jump, dropping any forward history
+    pub fn record_jump(&mut self) {
+        let position = (self.scroll_offset, self.scroll_line_offset);
+        self.jump_list.truncate(self.jump_index);
+        if self.jump_list.last() != Some(&position) {
+            self.jump_list.push(position);
+        }
+        if self.jump_list.len() > JUMP_LIST_MAX {
+            self.jump_list.remove(0);
+        }
+        self.jump_index = self.jump_list.len();
+    }
+
+    /// Jump to an element, recording the position left behind
+    pub fn jump_with_history(&mut self, index: usize) {
+        self.record_jump();
+        self.jump_to_element(index);
+    }
+
+    /// Go back to the position before the last jump (Ctrl-O)
+    pub fn jump_back(&mut self) {
+        // Leaving the newest position: keep it so Ctrl-I can come back
+        if self.jump_index == self.jump_list.len() {
+            let position = (self.scroll_offset, self.scroll_line_offset);
+            if self.jump_list.last() != Some(&position) {
+                self.jump_list.push(position);
+            }
+            self.jump_index = self.jump_list.len() - 1;
+        }
+        if self.jump_index == 0 {
+            self.set_status_message(tr("status-oldest-jump"));
+            return;
+        }
+        self.jump_index -= 1;
+        self.go_to_jump();
+    }
+
+    /// Redo a jump undone with Ctrl-O (Ctrl-I)
+    pub fn jump_forward(&mut self) {
+        if self.jump_index + 1 >= self.jump_list.len() {
+            self.set_status_message(tr("status-newest-jump"));
+            return;
+        }
+        self.jump_index += 1;
+        self.go_to_jump();
+    }
+
+    fn go_to_jump(&mut self) {
+        let (element, line) = self.jump_list[self.jump_index];
+        self.jump_to_element(element);
+        self.scroll_line_offset = line;
+        self.current_view = ViewMode::Document;
+    }
+
+    /// Jump to the previous or next heading in the outline, optionally only
+    /// one at the same level as the current section (`[[`/`]]` vs `[h`/`]h`)
+    pub fn jump_to_heading(&mut self, direction: HeadingDirection, same_level: bool) {
+        let outline = self.outline();
+        let position = self.scroll_offset;
+        let level = outline
+            .iter()
+            .rfind(|item| item.element_index <= position)
+            .map(|item| item.level);
+        let wanted = |item: &&OutlineItem| !same_level || level.is_none_or(|l| item.level == l);
+
+        let target = match direction {
+            HeadingDirection::Next => outline
+                .iter()
+                .filter(wanted)
+                .find(|item| item.element_index > position),
+            // A heading scrolled partly out of view counts as behind us
+            HeadingDirection::Previous => outline.iter().filter(wanted).rfind(|item| {
+                item.element_index < position
+                    || (item.element_index == position && self.scroll_line_offset > 0)
+            }),
+        };
+        match (target, direction) {
+            (Some(item), _) => self.jump_with_history(item.element_index),
+            (None, HeadingDirection::Next) => self.set_status_message(tr("status-no-next-heading")),
+            (None, HeadingDirection::Previous) => {
+                self.set_status_message(tr("status-no-previous-heading"))
+            }
+        }
+    }
+
+    /// Finish a `[`/`]` heading jump with the key pressed after it: `h` for a
+    /// heading at any level, the same bracket again for one at the same
+    /// level. Any other key cancels it, saying so unless it was Esc.
+    pub fn finish_heading_jump(&mut self, code: KeyCode) {
+        let Some(direction) = self.pending_heading.take() else {
+            return;
+        };
+        match (code, direction) {
+            (KeyCode::Char('h'), _) => self.jump_to_heading(direction, false),
+            (KeyCode::Char('['), HeadingDirection::Previous)
+            | (KeyCode::Char(']'), HeadingDirection::Next) => self.jump_to_heading(direction, true),
+            (KeyCode::Esc, _) => {}
+            _ => {
+                let key = match direction {
+                    HeadingDirection::Previous => "[",
+                    HeadingDirection::Next => "]",
+                };
+                self.set_status_message(tr_args(
+                    "status-heading-jump-cancelled",
+                    &[("key", key.into())],
+                ));
+            }
+        }
+    }
+
+    /// Go to a page (`42`), a point through the document (`50%`), the
+    /// heading with a given number (`3.2`) or a figure (`Figure 3`,
+    /// `table 2`), as typed at the `g` prompt. A bare number is always a
+    /// page; a top-level heading is written with its dot (`3.`).
+    pub fn go_to(&mut self, target: &str) {
+        let target = target.trim();
+        if target.starts_with(char::is_alphabetic) {
+            match resolve_reference(&number_figures(&self.document), target) {
+                Some(figure) => self.jump_with_history(figure.element_index),
+                None => self.set_status_message(tr_args(
+                    "status-figure-not-found",
+                    &[("figure", target.to_string().into())],
+                )),
+            }
+        } else if let Some(percent) = target.strip_suffix('%') {
+            match percent.trim().parse::<f64>() {
+                Ok(percent) if (0.0..=100.0).contains(&percent) => {
+                    self.record_jump();
+                    let row = self.layout.total_rows() as f64 * percent / 100.0;
+                    self.scroll_to_row(row as usize);
+                }
+                _ => self.set_status_message(tr("status-go-to-invalid")),
+            }
+        } else if let Ok(page) = target.parse::<usize>() {
+            match self.document.pages.first_element(page) {
+                Some(element) => self.jump_with_history(element),
+                None => self.set_status_message(tr_args(
+                    "status-page-past-end",
+                    &[
+                        ("page", page.into()),
+                        ("pages", self.document.pages.count.into()),
+                    ],
+                )),
+            }
+        } else if target.contains('.') {
+            let wanted = target.trim_end_matches('.');
+            let heading = self.document.elements.iter().position(|element| {
+                matches!(
+                    element,
+                    DocumentElement::Heading { number: Some(number), .. }
+                        if number.trim().trim_end_matches('.') == wanted
+                )
+            });
+            match heading {
+                Some(index) => self.jump_with_history(index),
+                None => self.set_status_message(tr_args(
+                    "status-heading-not-found",
+                    &[("number", wanted.to_string().into())],
+                )),
+            }
+        } else {
+            self.set_status_message(tr("status-go-to-invalid"));
+        }
+    }
+
+    /// Put the start of an element at the top of the document view
+    pub fn jump_to_element(&mut self, index: usize) {
+        self.scroll_offset = index.min(self.document.elements.len().saturating_sub(1));
+        self.scroll_line_offset = 0;
+    }
+
+    /// Visual row currently at the top of the document view
+    pub fn top_row(&self) -> usize {
+        self.layout
+            .row_of(self.scroll_offset, self.scroll_line_offset)
+    }
+
+    /// Scroll so visual row `row` is at the top, stopping once the last row
+    /// reaches the bottom of the view
+    pub fn scroll_to_row(&mut self, row: usize) {
+        if self.layout.starts.is_empty() {
+            // Not laid out yet, so fall back to one row per element
+            self.jump_to_element(row);
+            return;
+        }
+        let max_row = self
+            .layout
+            .total_rows()
+            .saturating_sub(self.viewport_height as usize);
+        let (index, offset) = self.layout.position_of(row.min(max_row));
+        self.scroll_offset = index;
+        self.scroll_line_offset = offset;
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.scroll_to_row(self.top_row().saturating_sub(1));
+    }
+
+    pub fn scroll_down(&mut self) {
+        // Don't pull content back up if a jump left us past the last full page
+        let top = self.top_row();
+        if top + (self.viewport_height as usize) < self.layout.total_rows() {
+            self.scroll_to_row(top + 1);
+        }
+    }
+
+    pub fn toggle_ruler(&mut self) {
+        self.reading_ruler = !self.reading_ruler;
+        self.set_status_message(tr(if self.reading_ruler {
+            "status-ruler-on"
+        } else {
+            "status-ruler-off"
+        }));
+    }
+
+    /// Show the document alone in a centered column, or with the usual borders and status bar
+    pub fn toggle_reading_mode(&mut self) {
+        self.reading_mode = !self.reading_mode;
+        self.set_status_message(if self.reading_mode {
+            tr_args(
+                "status-reading-mode-on",
+                &[("width", self.reading_width.into())],
+            )
+        } else {
+            tr("status-reading-mode-off")
+        });
+    }
+
+    /// How far the view is through the document: the percentage read down to
+    /// the bottom of the view, the heading of the section at the top, and the
+    /// minutes it takes to read the words below the view
+    pub fn progress(&self) -> (usize, Option<String>, usize) {
+        let total = self.layout.total_rows();
+        let bottom = self.top_row() + self.viewport_height as usize;
+        let (percent, below) = if total == 0 {
+            let elements = self.document.elements.len().max(1);
+            (
+                (self.scroll_offset + 1) * 100 / elements,
+                self.scroll_offset + 1,
+            )
+        } else if bottom >= total {
+            (100, self.document.elements.len())
+        } else {
+            (bottom * 100 / total, self.layout.position_of(bottom).0 + 1)
+        };
+
+        let words_left = self.words_after.get(below).copied().unwrap_or(0);
+        let minutes_left = words_left.div_ceil(self.words_per_minute as usize);
+        (percent, self.current_section(), minutes_left)
+    }
+
+    /// Heading of the section at the top of the document view
+    pub fn current_section(&self) -> Option<String> {
+        self.document.elements[..self.document.elements.len().min(self.scroll_offset + 1)]
+            .iter()
+            .rev()
+            .find_map(|element| match element {
+                DocumentElement::Heading {
+                    text,
+                    number: Some(number),
+                    ..
+                } if self.outline_options.show_numbers => Some(format!("{number} {text}")),
+                DocumentElement::Heading { text, .. } => Some(text.clone()),
+                _ => None,
+            })
+    }
+
+    /// Elements of the section at the top of the view: from its heading to
+    /// the next heading at the same or a higher level. Before the first
+    /// heading, the elements up to it.
+    pub fn section_range(&self) -> Range<usize> {
+        section_range(&self.document.elements, self.scroll_offset)
+    }
+
+    /// Credit the time since the last key press to the section on screen
+    pub fn record_reading_time(&mut self) {
+        let section = self.current_section().unwrap_or_default();
+        if let Some(session) = &mut self.reading_session {
+            session.record(&section);
+        }
+    }
+
+    /// Add the finished session to the reading history
+    pub fn save_history(&mut self) -> Result<()> {
+        self.record_reading_time();
+        let Some(session) = &self.reading_session else {
+            return Ok(());
+        };
+        let mut store = HistoryStore::open_default()?;
+        store.record(session.finish());
+        Ok(store.save()?)
+    }
+
+    /// Highlight hidden and invisible text, or show it like the rest
+    pub fn toggle_reveal_hidden(&mut self) {
+        let count = self
+            .document
+            .elements
+            .iter()
+            .filter_map(|element| match element {
+                DocumentElement::Paragraph { runs, .. } => Some(runs),
+                _ => None,
+            })
+            .flatten()
+            .filter(|run| run.formatting.hidden)
+            .count();
+        if count == 0 {
+            self.set_status_message(tr("status-no-hidden-text"));
+            return;
+        }
+        self.reveal_hidden = !self.reveal_hidden;
+        // Highlighted paragraphs wrap as runs rather than laid out lines
+        self.layout = DocumentLayout::default();
+        self.set_status_message(if self.reveal_hidden {
+            tr_args("status-hidden-text-on", &[("count", count.into())])
+        } else {
+            tr("status-hidden-text-off")
+        });
+    }
+
+    /// Widen or narrow the reading mode column
+    pub fn adjust_reading_width(&mut self, wider: bool) {
+        self.reading_width = if wider {
+            self.reading_width.saturating_add(READING_WIDTH_STEP)
+        } else {
+            self.reading_width
+                .saturating_sub(READING_WIDTH_STEP)
+                .max(MIN_READING_WIDTH)
+        };
+        self.set_status_message(tr_args(
+            "status-reading-width",
+            &[("width", self.reading_width.into())],
+        ));
+    }
+
+    /// Visual row under the reading ruler
+    pub fn ruler_row(&self) -> usize {
+        let max_offset = (self.viewport_height as usize).saturating_sub(1);
+        self.top_row() + self.ruler_offset.min(max_offset)
+    }
+
+    /// Move the ruler down a line. Past the middle of the view the text
+    /// scrolls under it instead, until the end of the document is in view.
+    pub fn ruler_down(&mut self) {
+        let top = self.top_row();
+        if self.ruler_offset >= self.viewport_height as usize / 2 {
+            self.scroll_down();
+        }
+        if self.top_row() == top && self.ruler_row() + 1 < self.layout.total_rows() {
+            let max_offset = (self.viewport_height as usize).saturating_sub(1);
+            self.ruler_offset = (self.ruler_offset + 1).min(max_offset);
+        }
+    }
+
+    /// Move the ruler up a line, scrolling once it is back at the middle
+    pub fn ruler_up(&mut self) {
+        let top = self.top_row();
+        if self.ruler_offset <= self.viewport_height as usize / 2 {
+            self.scroll_up();
+        }
+        if self.top_row() == top {
+            self.ruler_offset = self.ruler_offset.saturating_sub(1);
+        }
+    }
+
+    /// Rows moved by PageUp/PageDown, keeping a little overlap for context
+    pub fn page_size(&self) -> usize {
+        (self.viewport_height as usize).saturating_sub(2).max(1)
+    }
+
+    pub fn page_up(&mut self, page_size: usize) {
+        self.scroll_to_row(self.top_row().saturating_sub(page_size));
+    }
+
+    pub fn page_down(&mut self, page_size: usize) {
+        let top = self.top_row();
+        if top + (self.viewport_height as usize) < self.layout.total_rows() {
+            self.scroll_to_row(top + page_size);
+        }
+    }
+
+    pub fn scroll_to_end(&mut self) {
+        self.scroll_to_row(usize::MAX);
+    }
+
+    /// Most columns an inline image takes in a view `width` columns wide
+    fn image_columns(&self, width: u16) -> u16 {
+        self.document
+            .image_options
+            .max_width
+            .map_or(width, |columns| columns.min(width as u32) as u16)
+            .max(1)
+    }
+
+    /// Recompute element heights when the width, font or loaded images change
+    fn ensure_layout(&mut self, width: u16, font_size: (u16, u16), max_image_rows: u16) {
+        if !self
+            .layout
+            .is_stale(width, font_size, max_image_rows, self.image_protocols.len())
+        {
+            return;
+        }
+
+        let mut starts = Vec::with_capacity(self.document.elements.len() + 1);
+        let mut row = 0;
+        for (index, element) in self.document.elements.iter().enumerate() {
+            starts.push(row);
+            let image = self.image_protocols.get(&index);
+            if let Some(image) = image {
+                let columns = self.image_columns(width);
+                let scale = self.document.image_options.scale_factor();
+                row +=
+                    inline_image_size(image, font_size, columns, max_image_rows, scale).1 as usize;
+            }
+            let mut text = Text::default();
+            append_element_lines(
+                &mut text,
+                element,
+                self.color_enabled,
+                image.is_some(),
+                &self.renderers,
+                width,
+                self.fit(),
+            );
+            if !text.lines.is_empty() {
+                row += Paragraph::new(text)
+                    .wrap(Wrap { trim: false })
+                    .line_count(width);
+            }
+        }
+        starts.push(row);
+
+        self.layout = DocumentLayout {
+            width,
+            font_size,
+            max_image_rows,
+            image_count: self.image_protocols.len(),
+            starts,
+        };
+        // Rewrapping can shorten the top element
+        self.scroll_line_offset = self.scroll_line_offset.min(
+            self.layout
+                .element_rows(self.scroll_offset)
+                .saturating_sub(1),
+        );
+    }
+
+    /// The whole document drawn `width` columns wide as the viewer draws
+    /// it, as text with ANSI styles (plain when color is off), for
+    /// `--export ansi`. Images are shown as their placeholders.
+    pub fn render_ansi(&self, width: u16) -> String {
+        let title = Line::from(Span::styled(
+            self.document.title.clone(),
+            Style::default().add_modifier(Modifier::BOLD),
+        ));
+        let mut out = String::new();
+        // One element at a time, so no buffer has to be taller than a u16
+        for (index, element) in self.document.elements.iter().enumerate() {
+            let mut text = Text::default();
+            if index == 0 {
+                text.lines.push(title.clone());
+                text.lines.push(Line::default());
+            }
+            append_element_lines(
+                &mut text,
+                element,
+                self.color_enabled,
+                false,
+                &self.renderers,
+                width,
+                self.fit(),
+            );
+            let paragraph = Paragraph::new(text).wrap(Wrap { trim: false });
+            let height = paragraph.line_count(width).min(u16::MAX as usize) as u16;
+            let area = Rect::new(0, 0, width, height);
+            let mut buffer = Buffer::empty(area);
+            Widget::render(paragraph, area, &mut buffer);
+            out.push_str(&crate::ansi::buffer_to_ansi(&buffer, self.color_enabled));
+        }
+        out
+    }
+
+    pub fn copy_content(&mut self) {
+        if let Some(clipboard) = &mut self.clipboard {
+            // A table at the top of the document view is copied on its own
+            if let (ViewMode::Document, Some(DocumentElement::Table { table })) = (
+                &self.current_view,
+                self.document.elements.get(self.scroll_offset),
+            ) {
+                // Spreadsheets paste the TSV text into cells; rich editors
+                // pick up the HTML table instead
+                let tsv = doxx_core::export::table_to_tsv(table);
+                let copied = clipboard
+                    .set_html(doxx_core::export::table_to_html(table), Some(tsv.clone()))
+                    .or_else(|_| clipboard.set_text(tsv));
+                self.set_status_message(tr(match copied {
+                    Ok(_) => "status-copied-table",
+                    Err(_) => "status-copy-failed",
+                }));
+                return;
+            }
+
+            let content = match self.current_view {
+                ViewMode::Document => {
+                    // Copy the full document as text
+                    doxx_core::export::format_as_text(&self.document)
+                }
+                ViewMode::Search => {
+                    // Copy search results
+                    if self.search_results.is_empty() {
+                        tr("copy-no-results")
+                    } else {
+                        let mut content = tr_args(
+                            "copy-results-heading",
+                            &[("query", self.search_query.as_str().into())],
+                        );
+                        content.push_str("\n\n");
+                        for (i, result) in self.search_results.iter().enumerate() {
+                            content.push_str(&format!("{}. {}\n", i + 1, result.text.trim()));
+                        }
+                        content
+                    }
+                }
+                ViewMode::Outline => {
+                    // Copy document outline
+                    let mut content = tr("copy-outline-heading");
+                    content.push_str("\n\n");
+                    for item in &self.outline_items {
+                        let indent = "  ".repeat((item.level as usize).saturating_sub(1));
+                        content.push_str(&format!("{}{}\n", indent, item.title));
+                    }
+                    content
+                }
+                _ => tr("copy-unavailable"),
+            };
+
+            match clipboard.set_text(content) {
+                Ok(_) => {
+                    self.set_status_message(tr("status-copied"));
+                }
+                Err(_) => {
+                    self.set_status_message(tr("status-copy-failed"));
+                }
+            }
+        } else {
+            self.set_status_message(tr("status-no-clipboard"));
+        }
+    }
+
+    /// The element being read: the one under the reading ruler, or else the
+    /// one at the top of the view
+    pub fn focused_element(&self) -> usize {
+        if self.reading_ruler {
+            self.layout.position_of(self.ruler_row()).0
+        } else {
+            self.scroll_offset
+        }
+    }
+
+    /// Copy the table being read as CSV or TSV, for pasting into a spreadsheet
+    pub fn copy_table(&mut self, csv: bool) {
+        let Some(DocumentElement::Table { table }) =
+            self.document.elements.get(self.focused_element())
+        else {
+            self.set_status_message(tr("status-no-table"));
+            return;
+        };
+        let content = if csv {
+            doxx_core::export::table_to_csv(table)
+        } else {
+            doxx_core::export::table_to_tsv(table)
+        };
+        let Some(clipboard) = &mut self.clipboard else {
+            self.set_status_message(tr("status-no-clipboard"));
+            return;
+        };
+        let copied = clipboard.set_text(content);
+        self.set_status_message(tr(match copied {
+            Ok(_) if csv => "status-copied-table-csv",
+            Ok(_) => "status-copied-table",
+            Err(_) => "status-copy-failed",
+        }));
+    }
+
+    fn fit(&self) -> Fit {
+        Fit {
+            tables: self.table_layout,
+            table_shift: self.table_shift,
+            paragraphs: self.paragraph_layout,
+            reveal_hidden: self.reveal_hidden,
+            revision_markup: self.revision_markup,
+        }
+    }
+
+    /// Switch tables between wrapped, truncated and full-width scrolled
+    pub fn cycle_table_layout(&mut self) {
+        let (layout, message) = match self.table_layout {
+            TableLayout::Wrap => (TableLayout::Truncate, "status-tables-truncate"),
+            TableLayout::Truncate => (TableLayout::Scroll, "status-tables-scroll"),
+            TableLayout::Scroll => (TableLayout::Wrap, "status-tables-wrap"),
+        };
+        self.table_layout = layout;
+        self.table_shift = 0;
+        // Wrapped cells change how many rows each table takes
+        self.layout = DocumentLayout::default();
+        self.set_status_message(tr(message));
+    }
+
+    /// Scroll full-width tables sideways, up to the right edge of the widest
+    pub fn shift_tables(&mut self, right: bool) {
+        const STEP: usize = 8;
+        if self.table_layout != TableLayout::Scroll {
+            self.set_status_message(tr("status-tables-fitted"));
+            return;
+        }
+        let widest = self
+            .document
+            .elements
+            .iter()
+            .filter_map(|element| match element {
+                DocumentElement::Table { table } => Some(
+                    table.metadata.column_widths.iter().sum::<usize>()
+                        + 3 * table.metadata.column_widths.len()
+                        + 1,
+                ),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0);
+        let limit = widest.saturating_sub(self.layout.width as usize);
+        self.table_shift = if right {
+            (self.table_shift + STEP).min(limit)
+        } else {
+            self.table_shift.saturating_sub(STEP)
+        };
+    }
+
+    /// Open the table being read in table mode
+    pub fn open_table(&mut self) {
+        let element = self.focused_element();
+        let Some(DocumentElement::Table { table }) = self.document.elements.get(element) else {
+            self.set_status_message(tr("status-no-table-open"));
+            return;
+        };
+        self.table_view = Some(TableView::new(element, table));
+        self.current_view = ViewMode::Table;
+    }
+
+    /// Open the picture at the top of the view (or under the ruler), or else
+    /// the next one on screen, in the image preview
+    pub fn open_image_preview(&mut self) {
+        let first = self.focused_element();
+        let bottom = self.top_row() + (self.viewport_height as usize).saturating_sub(1);
+        let last = self.layout.position_of(bottom).0.max(first);
+        let image = (first..=last).find_map(|index| match self.document.elements.get(index) {
+            Some(DocumentElement::Image {
+                description,
+                image_path,
+                ..
+            }) => Some((index, description.clone(), image_path.clone())),
+            _ => None,
+        });
+        let Some((element, description, path)) = image else {
+            self.set_status_message(tr("status-no-image"));
+            return;
+        };
+        // Pictures are only extracted, and the terminal only asked how to
+        // draw them, with --images
+        let Some(path) = path.filter(|_| self.image_picker.is_some()) else {
+            self.set_status_message(tr("status-image-not-loaded"));
+            return;
+        };
+        let decoded = image::ImageReader::open(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|reader| Ok(reader.decode()?));
+        match decoded {
+            Ok(image) => {
+                self.image_preview = Some(ImagePreview::new(element, description, image));
+                self.current_view = ViewMode::Image;
+            }
+            Err(e) => self.set_status_message(tr_args(
+                "status-image-failed",
+                &[
+                    ("path", path.display().to_string().into()),
+                    ("error", e.to_string().into()),
+                ],
+            )),
+        }
+    }
+
+    /// Scroll the rows of the open table by `rows`
+    pub fn scroll_table(&mut self, down: bool, rows: usize) {
+        let Some(view) = self.table_view.as_mut() else {
+            return;
+        };
+        let Some(DocumentElement::Table { table }) = self.document.elements.get(view.element)
+        else {
+            return;
+        };
+        // Drawing stops it short of the end once the last row is in view
+        view.first_row = if down {
+            (view.first_row + rows).min(table.rows.len().saturating_sub(1))
+        } else {
+            view.first_row.saturating_sub(rows)
+        };
+    }
+
+    /// Start a visual selection at the element at the top of the view (or
+    /// under the reading ruler)
+    pub fn start_selection(&mut self) {
+        let element = self.focused_element();
+        self.selection = Some(Selection {
+            anchor: element,
+            cursor: element,
+        });
+    }
+
+    /// Extend the selection by one element, scrolling to keep it in view
+    pub fn extend_selection(&mut self, down: bool) {
+        let Some(selection) = self.selection.as_mut() else {
+            return;
+        };
+        let last = self.document.elements.len().saturating_sub(1);
+        selection.cursor = if down {
+            (selection.cursor + 1).min(last)
+        } else {
+            selection.cursor.saturating_sub(1)
+        };
+        let cursor = selection.cursor;
+
+        let top = self.top_row();
+        let start = self.layout.row_of(cursor, 0);
+        let end = self.layout.row_of(cursor + 1, 0);
+        let height = self.viewport_height as usize;
+        if start < top {
+            self.scroll_to_row(start);
+        } else if end > top + height {
+            // The whole element if it fits, else its start
+            self.scroll_to_row(end.saturating_sub(height).min(start));
+        }
+    }
+
+    /// Open the export prompt, filled in with a Markdown file named after
+    /// the document
+    pub fn open_export_prompt(&mut self, scope: ExportScope) {
+        let stem = Path::new(&self.document.metadata.file_path)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .filter(|stem| !stem.is_empty())
+            .unwrap_or("document");
+        let input = match scope {
+            ExportScope::Document => format!("{stem}.md"),
+            ExportScope::Section => format!("{stem}-section.md"),
+            ExportScope::Selection { .. } => format!("{stem}-selection.md"),
+        };
+        self.prompt = Some(Prompt {
+            kind: PromptKind::Export(scope),
+            input,
+        });
+    }
+
+    /// End the selection and ask where to export it
+    pub fn export_selection(&mut self) {
+        if let Some(selection) = self.selection.take() {
+            let range = selection.range();
+            self.open_export_prompt(ExportScope::Selection {
+                start: range.start,
+                end: range.end,
+            });
+        }
+    }
+
+    /// Write what `scope` covers to `path` (`~` is the home directory), in
+    /// the format its extension names
+    pub fn export_to(&mut self, scope: ExportScope, path: &str) {
+        let path = match path.trim().strip_prefix("~/").zip(dirs::home_dir()) {
+            Some((rest, home)) => home.join(rest),
+            None => Path::new(path.trim()).to_path_buf(),
+        };
+        let Some(format) = format_for_path(&path) else {
+            self.set_status_message(tr("status-export-unknown-format"));
+            return;
+        };
+        let range = match scope {
+            ExportScope::Document => 0..self.document.elements.len(),
+            ExportScope::Section => self.section_range(),
+            ExportScope::Selection { start, end } => start..end,
+        };
+        let count = range.len();
+
+        let message = match doxx_core::export::format_range(&self.document, range, &format) {
+            Ok(content) if content.is_empty() => tr("status-export-no-tables"),
+            Ok(content) => match std::fs::write(&path, content) {
+                Ok(()) => tr_args(
+                    "status-exported",
+                    &[
+                        ("count", count.into()),
+                        ("path", path.display().to_string().into()),
+                    ],
+                ),
+                Err(err) => tr_args("status-export-failed", &[("error", err.to_string().into())]),
+            },
+            Err(err) => tr_args("status-export-failed", &[("error", err.to_string().into())]),
+        };
+        self.set_status_message(message);
+    }
+
+    /// Copy the selected elements as plain text or Markdown and end the selection
+    pub fn copy_selection(&mut self, markdown: bool) {
+        let Some(selection) = self.selection.take() else {
+            return;
+        };
+        let range = selection.range();
+        let count = range.len();
+        let content = if markdown {
+            doxx_core::export::format_range_as_markdown(&self.document, range)
+        } else {
+            doxx_core::export::format_range_as_text(&self.document, range)
+        };
+        let Some(clipboard) = &mut self.clipboard else {
+            self.set_status_message(tr("status-no-clipboard"));
+            return;
+        };
+        match clipboard.set_text(content.trim_end().to_string()) {
+            Ok(_) => self.set_status_message(tr_args(
+                if markdown {
+                    "status-copied-selection-markdown"
+                } else {
+                    "status-copied-selection-text"
+                },
+                &[("count", count.into())],
+            )),
+            Err(_) => self.set_status_message(tr("status-copy-failed")),
+        }
+    }
+
+    /// Re-run the search for the current query and jump back to the first hit
+    pub fn refresh_search(&mut self) {
+        let options = SearchOptions {
+            fuzzy: self.search_fuzzy,
+            scope: self.search_scope.clone(),
+            ..SearchOptions::default()
+        };
+        self.search_results = search_document_with(&self.document, &self.search_query, &options);
+        self.current_search_index = 0;
+    }
+
+    /// Switch between exact and fuzzy matching, re-running the current search
+    pub fn toggle_search_fuzzy(&mut self) {
+        self.search_fuzzy = !self.search_fuzzy;
+        self.refresh_search();
+        self.set_status_message(tr(if self.search_fuzzy {
+            "status-fuzzy-on"
+        } else {
+            "status-fuzzy-off"
+        }));
+    }
+
+    /// Narrow the search to the section on screen, then to tables, then
+    /// back to the whole document, re-running the current search
+    pub fn cycle_search_scope(&mut self) {
+        self.search_scope = match self.search_scope {
+            SearchScope::All => SearchScope::Section(self.scroll_offset),
+            SearchScope::Section(_) => SearchScope::Tables,
+            SearchScope::Heading(_) | SearchScope::Tables => SearchScope::All,
+        };
+        self.refresh_search();
+        let message = match self.search_scope_label() {
+            Some(label) => tr_args("status-scope", &[("scope", label.into())]),
+            None => tr("status-scope-all"),
+        };
+        self.set_status_message(message);
+    }
+
+    /// What the search is limited to, e.g. `tables`, or `None` for the whole
+    /// document
+    pub fn search_scope_label(&self) -> Option<String> {
+        match &self.search_scope {
+            SearchScope::All => None,
+            SearchScope::Section(element) => {
+                let start = section_range(&self.document.elements, *element).start;
+                Some(match self.document.elements.get(start) {
+                    Some(DocumentElement::Heading { text, .. }) => text.clone(),
+                    _ => tr("scope-before-first-heading"),
+                })
+            }
+            SearchScope::Heading(path) => Some(path.clone()),
+            SearchScope::Tables => Some(tr("scope-tables")),
+        }
+    }
+
+    /// Resume where this document was left last time, unless the command line
+    /// already asked for a specific view or position
+    pub fn restore_position(&mut self, options: &ViewerOptions) {
+        if options.fresh || options.outline || options.search.is_some() || options.page.is_some() {
+            return;
+        }
+        let Ok(key) = file_digest(Path::new(&self.document.metadata.file_path)) else {
+            return;
+        };
+        let Ok(store) = PositionStore::open_default() else {
+            return;
+        };
+        let Some(position) = store.get(&key) else {
+            return;
+        };
+
+        self.jump_to_element(position.scroll_offset);
+        self.scroll_line_offset = position.scroll_line_offset;
+        if !position.search_query.is_empty() {
+            self.search_query = position.search_query.clone();
+            self.refresh_search();
+        }
+        self.current_view = match position.view.as_str() {
+            "outline" => ViewMode::Outline,
+            "search" => ViewMode::Search,
+            _ => ViewMode::Document,
+        };
+        self.set_status_message(tr("status-resumed"));
+    }
+
+    /// Remember the current position for the next time this document is opened
+    pub fn save_position(&self) -> Result<()> {
+        let key = file_digest(Path::new(&self.document.metadata.file_path))?;
+        let mut store = PositionStore::open_default()?;
+        self.store_position(&mut store, &key);
+        Ok(store.save()?)
+    }
+
+    /// Put the current position and the bookmarks in `store`, under the
+    /// document hash `key`
+    fn store_position(&self, store: &mut PositionStore, key: &str) {
+        let view = match self.current_view {
+            ViewMode::Outline => "outline",
+            ViewMode::Search => "search",
+            _ => "document",
+        };
+        store.set_bookmarks(key, self.document.bookmarks.clone());
+        store.set(
+            key,
+            ReadingPosition {
+                scroll_offset: self.scroll_offset,
+                scroll_line_offset: self.scroll_line_offset,
+                view: view.to_string(),
+                search_query: self.search_query.clone(),
+                updated: 0,
+            },
+        );
+    }
+
+    /// Wait for the bookmark letter after `m` or `'`
+    pub fn start_bookmark(&mut self, action: BookmarkAction) {
+        if action == BookmarkAction::Jump && self.document.bookmarks.is_empty() {
+            self.set_status_message(tr("status-no-bookmarks"));
+            return;
+        }
+        if action == BookmarkAction::Set {
+            self.set_status_message(tr("status-mark-prompt"));
+        }
+        self.pending_bookmark = Some(action);
+    }
+
+    /// Apply the pending bookmark action to `name`
+    pub fn finish_bookmark(&mut self, name: char) {
+        let Some(action) = self.pending_bookmark.take() else {
+            return;
+        };
+        self.clear_status_message();
+        if !name.is_lowercase() {
+            return;
+        }
+
+        match action {
+            BookmarkAction::Set => {
+                self.document.bookmarks.insert(name, self.scroll_offset);
+                self.set_status_message(tr_args(
+                    "status-bookmark-set",
+                    &[("name", name.to_string().into())],
+                ));
+            }
+            BookmarkAction::Jump => match self.document.bookmarks.get(&name) {
+                Some(&index) => {
+                    self.jump_with_history(index);
+                    self.current_view = ViewMode::Document;
+                }
+                None => self.set_status_message(tr_args(
+                    "status-bookmark-unset",
+                    &[("name", name.to_string().into())],
+                )),
+            },
+        }
+    }
+
+    /// Open the jump palette over the document with an empty query
+    pub fn open_palette(&mut self) {
+        self.palette_query.clear();
+        self.palette_figures = false;
+        self.rebuild_palette_entries();
+        self.current_view = ViewMode::Palette;
+    }
+
+    /// Open the palette on the document's figures and tables, by their
+    /// printed numbers
+    pub fn open_figures(&mut self) {
+        self.palette_query.clear();
+        self.palette_figures = true;
+        self.rebuild_palette_entries();
+        if self.palette_entries.is_empty() {
+            self.set_status_message(tr("status-no-figures"));
+            return;
+        }
+        self.current_view = ViewMode::Palette;
+    }
+
+    /// Switch between headings only and headings plus body paragraphs
+    pub fn toggle_palette_paragraphs(&mut self) {
+        if self.palette_figures {
+            self.palette_figures = false;
+        } else {
+            self.palette_include_paragraphs = !self.palette_include_paragraphs;
+        }
+        self.rebuild_palette_entries();
+    }
+
+    fn rebuild_palette_entries(&mut self) {
+        if self.palette_figures {
+            self.palette_entries = number_figures(&self.document)
+                .into_iter()
+                .map(|figure| PaletteEntry {
+                    label: format!("{}: {}", figure.label(), figure.caption),
+                    element_index: figure.element_index,
+                    level: Some(1),
+                })
+                .collect();
+            self.refresh_palette();
+            return;
+        }
+        self.palette_entries = self
+            .document
+            .elements
+            .iter()
+            .enumerate()
+            .filter_map(|(index, element)| match element {
+                DocumentElement::Heading {
+                    level,
+                    text,
+                    number,
+                    ..
+                } => Some(PaletteEntry {
+                    label: match number {
+                        Some(number) => format!("{number} {text}"),
+                        None => text.clone(),
+                    },
+                    element_index: index,
+                    level: Some(*level),
+                }),
+                DocumentElement::Paragraph { text, .. }
+                    if self.palette_include_paragraphs && !text.trim().is_empty() =>
+                {
+                    Some(PaletteEntry {
+                        label: text.trim().to_string(),
+                        element_index: index,
+                        level: None,
+                    })
+                }
+                _ => None,
+            })
+            .collect();
+        self.refresh_palette();
+    }
+
+    /// Re-rank palette entries for the current query, best match first
+    pub fn refresh_palette(&mut self) {
+        let mut matches: Vec<(usize, FuzzyMatch)> = self
+            .palette_entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, entry)| fuzzy_match(&self.palette_query, &entry.label).map(|m| (i, m)))
+            .collect();
+        // Stable sort keeps document order among equal scores
+        matches.sort_by_key(|(_, m)| std::cmp::Reverse(m.score));
+        matches.truncate(PALETTE_MAX_RESULTS);
+        self.palette_matches = matches;
+        self.palette_state
+            .select((!self.palette_matches.is_empty()).then_some(0));
+    }
+
+    pub fn palette_select_next(&mut self) {
+        if let Some(selected) = self.palette_state.selected() {
+            if selected + 1 < self.palette_matches.len() {
+                self.palette_state.select(Some(selected + 1));
+            }
+        }
+    }
+
+    pub fn palette_select_prev(&mut self) {
+        if let Some(selected) = self.palette_state.selected() {
+            self.palette_state.select(Some(selected.saturating_sub(1)));
+        }
+    }
+
+    /// Scroll to the selected palette entry and close the palette
+    pub fn palette_jump(&mut self) {
+        let target = self
+            .palette_state
+            .selected()
+            .and_then(|selected| self.palette_matches.get(selected))
+            .map(|(entry, _)| self.palette_entries[*entry].element_index);
+        if let Some(element_index) = target {
+            self.jump_with_history(element_index);
+        }
+        self.current_view = ViewMode::Document;
+    }
+
+    /// Search for the text on the clipboard: it goes into the search view's
+    /// query, or the prompt when one is open
+    pub fn paste_clipboard(&mut self) {
+        let Some(clipboard) = &mut self.clipboard else {
+            self.set_status_message(tr("status-no-clipboard"));
+            return;
+        };
+        let query = clipboard
+            .get_text()
+            .map(|text| clipboard_query(&text))
+            .unwrap_or_default();
+        if query.is_empty() {
+            self.set_status_message(tr("status-clipboard-empty"));
+        } else {
+            self.handle_paste(&query);
+        }
+    }
+
+    /// Insert a whole pasted string into whichever text field is active
+    pub fn handle_paste(&mut self, pasted: &str) {
+        if let Some(prompt) = self.prompt.as_mut() {
+            push_text_input(&mut prompt.input, pasted);
+            self.sync_search_prompt();
+        } else if matches!(self.current_view, ViewMode::Search) {
+            push_text_input(&mut self.search_query, pasted);
+            self.refresh_search();
+        } else if matches!(self.current_view, ViewMode::Palette) {
+            push_text_input(&mut self.palette_query, pasted);
+            self.refresh_palette();
+        }
+    }
+
+    pub fn open_prompt(&mut self, kind: PromptKind) {
+        if kind == PromptKind::Search {
+            // Incremental search moves the view as it is typed
+            self.record_jump();
+        }
+        self.prompt = Some(Prompt {
+            kind,
+            input: String::new(),
+        });
+    }
+
+    /// Follow the `/` prompt as it is typed: re-run the search and move to the
+    /// first match at or after the current position
+    pub fn sync_search_prompt(&mut self) {
+        let Some(Prompt {
+            kind: PromptKind::Search,
+            input,
+        }) = &self.prompt
+        else {
+            return;
+        };
+        if *input == self.search_query {
+            return;
+        }
+        let query = input.clone();
+        self.search(&query);
+    }
+
+    /// Search for `query` and move to its first match at or after the
+    /// current position
+    pub fn search(&mut self, query: &str) {
+        self.search_query = query.to_string();
+        self.refresh_search();
+
+        let position = self.scroll_offset;
+        if let Some(index) = self
+            .search_results
+            .iter()
+            .position(|r| r.element_index >= position)
+            .or((!self.search_results.is_empty()).then_some(0))
+        {
+            self.current_search_index = index;
+            self.jump_to_element(self.search_results[index].element_index);
+        }
+    }
+
+    /// Drop the current search and its highlights
+    pub fn clear_search(&mut self) {
+        self.search_query.clear();
+        self.search_results.clear();
+        self.current_search_index = 0;
+    }
+
+    pub fn set_status_message(&mut self, message: impl Into<String>) {
+        self.status_message = Some(message.into());
+        self.status_expires_at = Some(Instant::now() + STATUS_TIMEOUT);
+    }
+
+    pub fn clear_status_message(&mut self) {
+        self.status_message = None;
+        self.status_expires_at = None;
+    }
+
+    pub fn handle_message(&mut self, message: AppMessage) {
+        match message {
+            AppMessage::Quit => self.should_quit = true,
+            AppMessage::Status(text) => self.set_status_message(text),
+            AppMessage::ImageLoaded(index, image) => {
+                self.image_protocols.insert(index, image);
+            }
+            AppMessage::LoadProgress(parsed, total) => {
+                if let Some(loading) = &mut self.loading {
+                    loading.parsed = parsed;
+                    loading.total = total;
+                }
+            }
+            AppMessage::Preview(elements) => {
+                if self.loading.is_some() {
+                    let mut document = std::mem::take(&mut self.document);
+                    document.elements = elements;
+                    self.replace_document(document);
+                }
+            }
+            AppMessage::AnswerText(id, piece) => {
+                if let Some(answer) = self.answer.as_mut().filter(|answer| answer.id == id) {
+                    answer.text.push_str(&piece);
+                }
+            }
+            AppMessage::Answered(id, answered) => {
+                if self.answer.as_ref().is_none_or(|answer| answer.id != id) {
+                    return;
+                }
+                match answered {
+                    Ok(answered) => {
+                        let answer = self.answer.as_mut().expect("checked above");
+                        answer.text = answered.text.trim().to_string();
+                        answer.citations = answered.citations;
+                        answer.done = true;
+                    }
+                    Err(e) => {
+                        self.answer = None;
+                        self.set_status_message(tr_args(
+                            "status-ask-failed",
+                            &[("error", format!("{e:#}").into())],
+                        ));
+                    }
+                }
+            }
+            AppMessage::Loaded(loaded) => match *loaded {
+                Ok(document) => self.finish_loading(document),
+                Err(e) => {
+                    self.load_error = Some(e);
+                    self.should_quit = true;
+                }
+            },
+        }
+    }
+
+    /// Ask the model under `ai.` about the document in the background,
+    /// showing the answer in a popup as it is written
+    pub fn ask(&mut self, question: &str) {
+        let client = match AiClient::new(&self.ai) {
+            Ok(client) => client,
+            Err(e) => {
+                self.set_status_message(tr_args(
+                    "status-ask-failed",
+                    &[("error", format!("{e:#}").into())],
+                ));
+                return;
+            }
+        };
+        self.questions_asked += 1;
+        let id = self.questions_asked;
+        let question = question.trim().to_string();
+        self.answer = Some(AnswerPopup {
+            id,
+            question: question.clone(),
+            ..Default::default()
+        });
+
+        let document = self.document.clone();
+        let tx = self.message_tx.clone();
+        tokio::task::spawn_blocking(move || {
+            let answered = client.answer_question(&document, &question, |piece| {
+                let _ = tx.send(AppMessage::AnswerText(id, piece.to_string()));
+            });
+            let _ = tx.send(AppMessage::Answered(
+                id,
+                answered.map_err(anyhow::Error::from),
+            ));
+        });
+    }
+
+    /// Close the answer popup at the `nth` element it cites (counting from 0)
+    pub fn go_to_citation(&mut self, nth: usize) {
+        let Some(&element) = self
+            .answer
+            .as_ref()
+            .and_then(|answer| answer.citations.get(nth))
+        else {
+            return;
+        };
+        self.answer = None;
+        if element < self.document.elements.len() {
+            self.jump_with_history(element);
+        }
+    }
+
+    /// Show the whole document in place of what was shown while it loaded,
+    /// then apply the options the viewer was opened with
+    fn finish_loading(&mut self, mut document: Document) {
+        let Some(loading) = self.loading.take() else {
+            return;
+        };
+        // Moving around the first elements already says where to read
+        let moved = self.scroll_offset > 0 || self.scroll_line_offset > 0;
+        document.bookmarks.append(&mut self.document.bookmarks);
+        self.replace_document(document);
+        self.source = loading.options.source.clone();
+        self.apply_options(&loading.options);
+        if !moved {
+            self.restore_position(&loading.options);
+        }
+        if loading.history && !self.document.metadata.file_path.is_empty() {
+            self.reading_session = Some(SessionClock::start(
+                Path::new(&self.document.metadata.file_path),
+                &self.document.title,
+            ));
+        }
+    }
+
+    /// Periodic housekeeping, called every `TICK_RATE`
+    pub fn on_tick(&mut self) {
+        if self
+            .status_expires_at
+            .is_some_and(|expires_at| Instant::now() >= expires_at)
+        {
+            self.clear_status_message();
+        }
+    }
+
+    fn file_modified(&self) -> Option<SystemTime> {
+        std::fs::metadata(&self.document.metadata.file_path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+
+    /// Whether the file opened with `O` has been saved since
+    pub fn source_changed(&self) -> bool {
+        self.watched_modified
+            .is_some_and(|before| self.file_modified().is_some_and(|now| now != before))
+    }
+
+    /// Parse the file again and show it in place of the current document,
+    /// near the same reading position
+    pub async fn reload_document(&mut self) {
+        self.watched_modified = self.file_modified();
+        let Some(source) = self.source.clone() else {
+            self.set_status_message(tr("status-reload-unavailable"));
+            return;
+        };
+        let path = PathBuf::from(&self.document.metadata.file_path);
+        let image_options = self.document.image_options.clone();
+        let preparation = &source.preparation;
+        let loaded = match source.parser {
+            DocumentParser::File(parse_options) => load_document(
+                &path,
+                preparation.image_options(&image_options),
+                parse_options,
+            )
+            .await
+            .map(|mut document| {
+                preparation.recognize(&mut document);
+                document
+            }),
+            DocumentParser::Worker {
+                program,
+                args,
+                limits,
+            } => {
+                let mut worker = std::process::Command::new(program);
+                worker.args(args);
+                sandbox::load_in_worker(worker, &limits)
+            }
+        };
+        match loaded {
+            Ok(mut document) => {
+                preparation.finish(&mut document);
+                document.image_options = image_options;
+                document.bookmarks = std::mem::take(&mut self.document.bookmarks);
+                self.replace_document(document);
+                self.set_status_message(tr("status-reloaded"));
+            }
+            Err(e) => self.set_status_message(tr_args(
+                "status-reload-failed",
+                &[("error", format!("{e:#}").into())],
+            )),
+        }
+    }
+
+    /// Show `document` instead of the current one, dropping state that
+    /// points at elements of the old one
+    fn replace_document(&mut self, document: Document) {
+        self.document = document;
+        self.outline_items = generate_outline_with(&self.document, &self.outline_options);
+        if let Some(selected) = self.outline_state.selected() {
+            self.select_outline(selected);
+        }
+        self.layout = DocumentLayout::default();
+        self.selection = None;
+        self.jump_list.clear();
+        self.jump_index = 0;
+        self.table_view = None;
+        self.image_preview = None;
+        if matches!(self.current_view, ViewMode::Table | ViewMode::Image) {
+            self.current_view = ViewMode::Document;
+        }
+        self.scroll_offset = self
+            .scroll_offset
+            .min(self.document.elements.len().saturating_sub(1));
+        self.scroll_line_offset = 0;
+        self.hygiene_banner &= !self.document.hygiene.is_clean();
+        self.count_words();
+        if !self.search_query.is_empty() {
+            self.refresh_search();
+        }
+        self.image_protocols.clear();
+        if let Some(picker) = self.image_picker {
+            self.load_inline_images(picker);
+        }
+    }
+}
+
+async fn run_non_interactive(
+    document: Document,
+    options: &ViewerOptions,
+    config: &Config,
+) -> Result<()> {
+    let app = App::new(document, options, config);
+    // Section titles and headings stand out when color is on
+    let styled = |text: &str, style: &str| {
+        if options.color {
+            color::paint(text, style)
+        } else {
+            text.to_string()
+        }
+    };
+    let bold = |text: &str| styled(text, color::BOLD);
+
+    match app.current_view {
+        ViewMode::Outline => {
+            // Show outline
+            let outline = app.outline();
+            println!("{}", bold("Document Outline:"));
+            println!("================");
+            for item in outline {
+                let indent = "  ".repeat((item.level.saturating_sub(1)) as usize);
+                println!("{}{}", indent, item.title);
+            }
+        }
+        ViewMode::Search => {
+            // Show search results
+            println!(
+                "{}",
+                bold(&format!("Search Results for '{}':", app.search_query))
+            );
+            println!("==============================");
+            for (i, result) in app.search_results.iter().enumerate() {
+                println!("{}. {}", i + 1, result.text.trim());
+                println!();
+            }
+            if app.search_results.is_empty() {
+                println!("No results found.");
+            }
+        }
+        _ => {
+            // Default: show basic document info and content preview
+            println!("{}", bold(&format!("Document: {}", app.document.title)));
+            println!("Pages: {}", app.document.metadata.page_count);
+            println!("Words: {}", app.document.metadata.word_count);
+            println!();
+            println!("{}", bold("Content Preview:"));
+            println!("================");
+
+            // Show first few elements with proper formatting
+            let preview_count = std::cmp::min(app.document.elements.len(), 20);
+            for element in &app.document.elements[0..preview_count] {
+                match element {
+                    DocumentElement::Heading {
+                        level,
+                        text,
+                        number,
+                        ..
+                    } => {
+                        let prefix = match level {
+                            1 => "# ",
+                            2 => "## ",
+                            _ => "### ",
+                        };
+                        let heading_text = if let Some(number) = number {
+                            format!("{number} {text}")
+                        } else {
+                            text.clone()
+                        };
+                        println!("{}", styled(&format!("{prefix}{heading_text}"), "1;34"));
+                        println!();
+                    }
+                    DocumentElement::Paragraph { text, .. } => {
+                        println!("{text}");
+                        println!();
+                    }
+                    DocumentElement::List {
+                        items,
+                        ordered,
+                        start,
+                    } => {
+                        let labels = list_item_labels(items, *ordered, *start);
+                        for (item, label) in items.iter().zip(labels) {
+                            let bullet = match label {
+                                Some(label) => format!("{label} "),
+                                None => "• ".to_string(),
+                            };
+                            let indent = "  ".repeat(item.level as usize);
+                            println!("{}{}{}", indent, bullet, item.text);
+                        }
+                        println!();
+                    }
+                    DocumentElement::Table { .. } => {
+                        println!("[Table content - use --export csv to view]");
+                        println!();
+                    }
+                    DocumentElement::Image {
+                        description,
+                        image_path,
+                        ..
+                    } => {
+                        if let Some(path) = image_path {
+                            // Try to display the image inline using terminal protocols
+                            match TerminalImageRenderer::with_options(
+                                app.document.image_options.max_width,
+                                app.document.image_options.max_height,
+                                app.document.image_options.scale,
+                            )
+                            .render_image_from_path(path, description)
+                            {
+                                Ok(_) => {
+                                    // Image displayed successfully
+                                    println!();
+                                }
+                                Err(_) => {
+                                    // Fallback to text description
+                                    println!("📷 [Image: {description}]");
+                                    println!();
+                                }
+                            }
+                        } else {
+                            println!("📷 [Image: {description}]");
+                            println!();
+                        }
+                    }
+                    DocumentElement::PageBreak => {
+                        println!("---");
+                        println!();
+                    }
+                    DocumentElement::Quote { text, .. } => {
+                        for line in text.lines() {
+                            println!("  │ {line}");
+                        }
+                        println!();
+                    }
+                    DocumentElement::CodeBlock { lines, .. } => {
+                        for line in lines {
+                            println!("    {line}");
+                        }
+                        println!();
+                    }
+                    DocumentElement::Caption { text, .. }
+                    | DocumentElement::Title { text, .. }
+                    | DocumentElement::Subtitle { text, .. } => {
+                        println!("{text}");
+                        println!();
+                    }
+                    DocumentElement::Equation { text, .. } => {
+                        println!("    {text}");
+                        println!();
+                    }
+                }
+            }
+
+            if app.document.elements.len() > preview_count {
+                println!(
+                    "... and {} more elements",
+                    app.document.elements.len() - preview_count
+                );
+                println!();
+            }
+
+            println!(
+                "Use --export to save full content, or run in an interactive terminal for full UI."
+            );
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn run_viewer(
+    document: Document,
+    options: &ViewerOptions,
+    config: &Config,
+    renderers: RendererRegistry,
+) -> Result<()> {
+    // Check if we're in an interactive terminal or forced to use UI
+    if !options.force_ui && !IsTty::is_tty(&io::stdout()) {
+        // Fallback for non-interactive environments
+        return run_non_interactive(document, options, config).await;
+    }
+
+    let mut terminal = enter_terminal()?;
+
+    // Create app
+    let mut app = App::new(document, options, config);
+    app.renderers = renderers;
+    app.restore_position(options);
+    if config.history.enabled && !app.document.metadata.file_path.is_empty() {
+        app.reading_session = Some(SessionClock::start(
+            Path::new(&app.document.metadata.file_path),
+            &app.document.title,
+        ));
+    }
+
+    run_interactive(&mut terminal, app).await
+}
+
+/// Open the viewer for the document at `path` right away and parse it in the
+/// background with `load`, which is given `parse_options` to parse with.
+/// The first screenful is shown as soon as it is parsed, with a loading
+/// indicator in the status bar until the rest is in; the options that
+/// depend on the whole document (position, search, page, images) apply then.
+pub async fn run_viewer_while_loading<F, Fut>(
+    path: &Path,
+    parse_options: ParseOptions,
+    load: F,
+    options: &ViewerOptions,
+    config: &Config,
+    renderers: RendererRegistry,
+) -> Result<()>
+where
+    F: FnOnce(ParseOptions) -> Fut + Send + 'static,
+    Fut: std::future::Future<Output = Result<Document>>,
+{
+    if !options.force_ui && !IsTty::is_tty(&io::stdout()) {
+        let document = load(parse_options).await?;
+        return run_non_interactive(document, options, config).await;
+    }
+
+    let mut terminal = enter_terminal()?;
+
+    let mut app = App::loading(path, options, config);
+    app.renderers = renderers;
+
+    let tx = app.message_tx.clone();
+    let forward = parse_options.events.clone();
+    let parse_options = ParseOptions {
+        events: EventSink::new(move |event| {
+            forward.emit(event.clone());
+            let message = match event {
+                events::Event::ElementParsed { parsed, total } => {
+                    AppMessage::LoadProgress(parsed, total)
+                }
+                events::Event::Preview { elements } => AppMessage::Preview(elements),
+                _ => return,
+            };
+            let _ = tx.send(message);
+        }),
+        // Every element takes at least a row, so this many fill the screen
+        preview_elements: terminal.size()?.height as usize,
+        ..parse_options
+    };
+    let tx = app.message_tx.clone();
+    let runtime = tokio::runtime::Handle::current();
+    tokio::task::spawn_blocking(move || {
+        let loaded = runtime.block_on(load(parse_options));
+        let _ = tx.send(AppMessage::Loaded(Box::new(loaded)));
+    });
+
+    run_interactive(&mut terminal, app).await
+}
+
+/// Switch to the alternate screen in raw mode, making sure a panic can't
+/// leave the terminal that way
+fn enter_terminal() -> Result<Terminal<CrosstermBackend<io::Stdout>>> {
+    install_panic_hook();
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(
+        stdout,
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )?;
+    // Terminals without the kitty keyboard protocol keep the legacy encoding
+    if matches!(supports_keyboard_enhancement(), Ok(true)) {
+        KEYBOARD_ENHANCED.store(true, Ordering::SeqCst);
+        push_keyboard_enhancement()?;
+    }
+    let backend = CrosstermBackend::new(stdout);
+    Ok(Terminal::new(backend)?)
+}
+
+/// Run the viewer until it is closed, then restore the terminal and
+/// remember where reading stopped
+async fn run_interactive(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    mut app: App,
+) -> Result<()> {
+    // Raw mode delivers Ctrl-C as a key press, but a SIGINT sent from outside
+    // (e.g. `kill -INT`) still needs to shut down cleanly
+    let quit_tx = app.message_tx.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            let _ = quit_tx.send(AppMessage::Quit);
+        }
+    });
+
+    // Run the app
+    let res = run_app(terminal, &mut app).await;
+
+    // Restore terminal
+    restore_terminal()?;
+    terminal.show_cursor()?;
+
+    if let Err(err) = res {
+        println!("{err:?}");
+    }
+    if let Some(err) = app.load_error.take() {
+        return Err(err);
+    }
+    // Closed before the document was in: nothing worth remembering
+    if app.loading.is_some() {
+        return Ok(());
+    }
+
+    if let Err(err) = app.save_position() {
+        eprintln!("Could not save reading position: {err}");
+    }
+    if let Err(err) = app.save_history() {
+        eprintln!("Could not save reading history: {err}");
+    }
+
+    Ok(())
+}
+
+/// Correct `picker`'s guess with what the terminal answers when asked
+/// directly. ratatui-image only notices tmux from `TERM`, never asks about
+/// iTerm2, and can't pass Kitty images or anything at all through screen.
+fn apply_terminal_probe(picker: &mut Picker) {
+    let multiplexer = Multiplexer::detect();
+    let guessed_graphics = picker.protocol_type != ProtocolType::Halfblocks;
+    if multiplexer.is_none() && guessed_graphics {
+        return;
+    }
+    if multiplexer == Some(Multiplexer::Screen) {
+        picker.protocol_type = ProtocolType::Halfblocks;
+        return;
+    }
+    let Some(probed) = terminal_probe::probe() else {
+        return;
+    };
+
+    let in_tmux = multiplexer == Some(Multiplexer::Tmux);
+    picker.is_tmux |= in_tmux;
+    picker.protocol_type = if probed.kitty && !in_tmux {
+        ProtocolType::Kitty
+    } else if probed.iterm2 {
+        ProtocolType::Iterm2
+    } else if probed.sixel {
+        ProtocolType::Sixel
+    } else if in_tmux && picker.protocol_type == ProtocolType::Kitty {
+        ProtocolType::Halfblocks
+    } else {
+        picker.protocol_type
+    };
+}
+
+/// Whether a key press should be inserted into a text field. Characters
+/// committed by an IME arrive as plain or shifted `Char` events, while
+/// Ctrl/Alt chords are commands rather than text
+fn is_text_input(key: &KeyEvent) -> bool {
+    !key.modifiers
+        .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+}
+
+/// Append text to a single-line input, folding newlines and tabs into spaces
+/// and dropping other control characters
+/// Text copied from an email or a ticket as a one-line search: `>` quote
+/// markers, line breaks and quotes around the whole text are dropped
+pub fn clipboard_query(text: &str) -> String {
+    let words: Vec<&str> = text
+        .lines()
+        .map(|line| line.trim_start().trim_start_matches(['>', ' ']))
+        .flat_map(str::split_whitespace)
+        .collect();
+    let query = words.join(" ");
+    for (open, close) in [('"', '"'), ('\u{201C}', '\u{201D}'), ('\'', '\'')] {
+        if let Some(inner) = query
+            .strip_prefix(open)
+            .and_then(|rest| rest.strip_suffix(close))
+        {
+            return inner.trim().to_string();
+        }
+    }
+    query
+}
+
+/// The clipboard's text as a search query, for `--search-clipboard`
+pub fn read_clipboard_query() -> Result<String> {
+    let text = Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .map_err(|e| anyhow::anyhow!("Could not read the clipboard: {e}"))?;
+    let query = clipboard_query(&text);
+    if query.is_empty() {
+        anyhow::bail!("The clipboard has no text to search for");
+    }
+    Ok(query)
+}
+
+fn push_text_input(buffer: &mut String, text: &str) {
+    let mut last_was_space = buffer.ends_with(' ');
+    for c in text.trim_end_matches(['\r', '\n']).chars() {
+        if matches!(c, '\r' | '\n' | '\t') {
+            if !last_was_space {
+                buffer.push(' ');
+                last_was_space = true;
+            }
+        } else if !c.is_control() {
+            buffer.push(c);
+            last_was_space = c == ' ';
+        }
+    }
+}
+
+fn handle_prompt_key<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    key: KeyEvent,
+) -> Result<()> {
+    let Some(prompt) = app.prompt.as_mut() else {
+        return Ok(());
+    };
+
+    match key.code {
+        KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.toggle_search_fuzzy()
+        }
+        KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.cycle_search_scope()
+        }
+        KeyCode::Char('v') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.paste_clipboard()
+        }
+        KeyCode::Esc => {
+            // Cancelling a `/` search also removes its highlights
+            if prompt.kind == PromptKind::Search {
+                app.clear_search();
+            }
+            app.prompt = None;
+        }
+        // Backspace on an empty prompt closes it
+        KeyCode::Backspace if prompt.input.pop().is_none() => app.prompt = None,
+        KeyCode::Char(c) if is_text_input(&key) => prompt.input.push(c),
+        KeyCode::Enter => {
+            let prompt = app.prompt.take().expect("prompt is open");
+            match prompt.kind {
+                PromptKind::ShellCommand => {
+                    if !prompt.input.trim().is_empty() {
+                        run_shell_command(terminal, app, &prompt.input)?;
+                    }
+                }
+                // The search already follows the input; Enter just keeps it
+                PromptKind::Search => {}
+                PromptKind::GoTo => {
+                    if !prompt.input.trim().is_empty() {
+                        app.go_to(&prompt.input);
+                    }
+                }
+                PromptKind::Export(scope) => {
+                    if !prompt.input.trim().is_empty() {
+                        app.export_to(scope, &prompt.input);
+                    }
+                }
+                PromptKind::Ask => {
+                    if !prompt.input.trim().is_empty() {
+                        app.ask(&prompt.input);
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+    app.sync_search_prompt();
+    Ok(())
+}
+
+fn handle_answer_key(app: &mut App, key: KeyEvent) {
+    let Some(answer) = app.answer.as_mut() else {
+        return;
+    };
+    match command_code(key.code) {
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('?') => app.answer = None,
+        KeyCode::Up | KeyCode::Char('k') => answer.scroll = answer.scroll.saturating_sub(1),
+        KeyCode::Down | KeyCode::Char('j') => answer.scroll = answer.scroll.saturating_add(1),
+        KeyCode::Enter => app.go_to_citation(0),
+        KeyCode::Char(digit @ '1'..='9') => app.go_to_citation(digit as usize - '1' as usize),
+        _ => {}
+    }
+}
+
+fn handle_table_key(app: &mut App, key: KeyEvent) {
+    if app.status_message.is_some() {
+        app.clear_status_message();
+    }
+    let page = (app.viewport_height as usize).saturating_sub(8).max(1);
+    match command_code(key.code) {
+        KeyCode::Char('q') | KeyCode::Esc => {
+            app.table_view = None;
+            app.current_view = ViewMode::Document;
+        }
+        KeyCode::F(1) => app.show_help = !app.show_help,
+        KeyCode::Up | KeyCode::Char('k') => app.scroll_table(false, 1),
+        KeyCode::Down | KeyCode::Char('j') => app.scroll_table(true, 1),
+        KeyCode::PageUp => app.scroll_table(false, page),
+        KeyCode::PageDown => app.scroll_table(true, page),
+        KeyCode::Home => app.scroll_table(false, usize::MAX),
+        KeyCode::End => app.scroll_table(true, usize::MAX),
+        code => {
+            let Some(view) = app.table_view.as_mut() else {
+                return;
+            };
+            match code {
+                KeyCode::Left | KeyCode::Char('h') => view.move_column(false),
+                KeyCode::Right | KeyCode::Char('l') => view.move_column(true),
+                KeyCode::Char('s') => view.cycle_sort(),
+                KeyCode::Char('X') => view.show_all_columns(),
+                KeyCode::Char('x') if !view.hide_column() => {
+                    app.set_status_message(tr("status-last-column"))
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn handle_image_key(app: &mut App, key: KeyEvent) {
+    if app.status_message.is_some() {
+        app.clear_status_message();
+    }
+    match command_code(key.code) {
+        KeyCode::Char('q') | KeyCode::Esc => {
+            app.image_preview = None;
+            app.current_view = ViewMode::Document;
+        }
+        KeyCode::F(1) => app.show_help = !app.show_help,
+        code => {
+            let Some(preview) = app.image_preview.as_mut() else {
+                return;
+            };
+            match code {
+                KeyCode::Char('+') | KeyCode::Char('=') => preview.zoom_in(),
+                KeyCode::Char('-') => preview.zoom_out(),
+                KeyCode::Char('0') => preview.reset(),
+                KeyCode::Left | KeyCode::Char('h') => preview.pan(-1, 0),
+                KeyCode::Right | KeyCode::Char('l') => preview.pan(1, 0),
+                KeyCode::Up | KeyCode::Char('k') => preview.pan(0, -1),
+                KeyCode::Down | KeyCode::Char('j') => preview.pan(0, 1),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Open the document with `ui.editor`, `$VISUAL` or `$EDITOR` outside the
+/// TUI, and reload it if it was saved by the time the editor exits. An
+/// application that returns at once (e.g. an office suite already running)
+/// is caught by the check on every tick instead.
+async fn open_in_editor<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()> {
+    if app.document.metadata.file_path.is_empty() {
+        app.set_status_message(tr("status-no-file"));
+        return Ok(());
+    }
+    let Some(editor) = Some(app.editor.trim().to_string())
+        .filter(|editor| !editor.is_empty())
+        .or_else(|| {
+            std::env::var("VISUAL")
+                .ok()
+                .filter(|editor| !editor.is_empty())
+        })
+        .or_else(|| {
+            std::env::var("EDITOR")
+                .ok()
+                .filter(|editor| !editor.is_empty())
+        })
+    else {
+        app.set_status_message(tr("status-no-editor"));
+        return Ok(());
+    };
+    let path = shell_quote(&app.document.metadata.file_path);
+    let command = if editor.contains('%') {
+        editor.replace('%', &path)
+    } else {
+        format!("{editor} {path}")
+    };
+
+    app.watched_modified = app.file_modified();
+    restore_terminal()?;
+    let status = shell(&command).status();
+    resume_terminal(terminal)?;
+
+    match status {
+        Err(e) => app.set_status_message(tr_args(
+            "status-editor-failed",
+            &[("error", e.to_string().into())],
+        )),
+        Ok(_) if app.source_changed() => app.reload_document().await,
+        Ok(status) if !status.success() => app.set_status_message(tr_args(
+            "status-command-finished",
+            &[("status", status.to_string().into())],
+        )),
+        Ok(_) => app.set_status_message(tr("status-editor-unchanged")),
+    }
+    Ok(())
+}
+
+/// `command` run by the platform shell
+fn shell(command: &str) -> std::process::Command {
+    #[cfg(unix)]
+    let (program, flag) = ("sh", "-c");
+    #[cfg(not(unix))]
+    let (program, flag) = ("cmd", "/C");
+    let mut shell = std::process::Command::new(program);
+    shell.arg(flag).arg(command);
+    shell
+}
+
+/// Run a shell command outside the TUI with `%` replaced by the document path,
+/// then wait for Enter before returning to the viewer
+fn run_shell_command<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    command: &str,
+) -> Result<()> {
+    let command = command.replace('%', &shell_quote(&app.document.metadata.file_path));
+
+    restore_terminal()?;
+    println!("$ {command}");
+
+    let status = shell(&command).status();
+
+    match &status {
+        Ok(status) if !status.success() => println!("\n[command exited with {status}]"),
+        Err(e) => println!("\n[failed to run command: {e}]"),
+        _ => {}
+    }
+    println!("Press Enter to return to doxx...");
+    let mut line = String::new();
+    let _ = io::stdin().read_line(&mut line);
+
+    resume_terminal(terminal)?;
+    if let Ok(status) = status {
+        app.set_status_message(tr_args(
+            "status-command-finished",
+            &[("status", status.to_string().into())],
+        ));
+    }
+    Ok(())
+}
+
+fn shell_quote(text: &str) -> String {
+    if cfg!(unix) {
+        format!("'{}'", text.replace('\'', "'\\''"))
+    } else {
+        format!("\"{text}\"")
+    }
+}
+
+/// Hand the terminal back to the shell on Ctrl-Z and pick up again on `fg`
+fn suspend_process<B: Backend>(terminal: &mut Terminal<B>) -> Result<()> {
+    #[cfg(unix)]
+    {
+        restore_terminal()?;
+        // SAFETY: raising a signal on our own process has no memory-safety implications;
+        // execution continues here once the shell resumes us with SIGCONT
+        unsafe {
+            libc::raise(libc::SIGTSTP);
+        }
+        resume_terminal(terminal)?;
+    }
+    #[cfg(not(unix))]
+    let _ = terminal;
+    Ok(())
+}
+
+/// Re-enter raw mode and the alternate screen after running outside the TUI
+fn resume_terminal<B: Backend>(terminal: &mut Terminal<B>) -> Result<()> {
+    enable_raw_mode()?;
+    execute!(
+        io::stdout(),
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )?;
+    if KEYBOARD_ENHANCED.load(Ordering::SeqCst) {
+        push_keyboard_enhancement()?;
+    }
+    terminal.clear()?;
+    Ok(())
+}
+
+/// Ask for unambiguous escape codes, press/repeat/release events and the
+/// layout's shifted characters (kitty keyboard protocol)
+fn push_keyboard_enhancement() -> Result<()> {
+    execute!(
+        io::stdout(),
+        PushKeyboardEnhancementFlags(
+            KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+                | KeyboardEnhancementFlags::REPORT_EVENT_TYPES
+                | KeyboardEnhancementFlags::REPORT_ALTERNATE_KEYS
+        )
+    )?;
+    Ok(())
+}
+
+/// Show a document comparison full screen: unchanged elements dimmed,
+/// removals in red, additions in green and changed words marked inline.
+/// `monochrome` draws it without color, leaving the `-`/`+`/`~` markers.
+pub fn run_diff_viewer(diff: &DocumentDiff, monochrome: bool) -> Result<()> {
+    let (lines, change_lines) = diff_lines(diff);
+    let summary = diff.summary();
+    let title = format!(
+        "doxx diff - {} → {}  (+{} -{} ~{})",
+        diff.old_path, diff.new_path, summary.added, summary.removed, summary.changed
+    );
+
+    install_panic_hook();
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+
+    let res = run_diff_app(&mut terminal, &title, lines, &change_lines, monochrome);
+
+    restore_terminal()?;
+    terminal.show_cursor()?;
+    res
+}
+
+fn run_diff_app<B: Backend>(
+    terminal: &mut Terminal<B>,
+    title: &str,
+    lines: Vec<Line<'static>>,
+    change_lines: &[usize],
+    monochrome: bool,
+) -> Result<()> {
+    let mut scroll = 0;
+    // First visual row of each line at the current width, then the total
+    let mut line_rows: Vec<usize> = Vec::new();
+    let mut viewport = 0;
+
+    loop {
+        terminal.draw(|f| {
+            let area = f.area();
+            let block = Block::default()
+                .title(title.to_string())
+                .title_bottom(" [↕] Scroll  [n/N] Next/previous change  [q] Quit ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Blue));
+            let inner = block.inner(area);
+            viewport = inner.height as usize;
+
+            line_rows.clear();
+            let mut row = 0;
+            for line in &lines {
+                line_rows.push(row);
+                row += Paragraph::new(line.clone())
+                    .wrap(Wrap { trim: false })
+                    .line_count(inner.width)
+                    .max(1);
+            }
+            line_rows.push(row);
+            scroll = scroll.min(row.saturating_sub(viewport));
+
+            let paragraph = Paragraph::new(lines.clone())
+                .block(block)
+                .wrap(Wrap { trim: false })
+                .scroll((scroll as u16, 0));
+            f.render_widget(paragraph, area);
+            if monochrome {
+                strip_colors(f.buffer_mut(), area);
+            }
+        })?;
+
+        let change_rows = change_lines.iter().map(|&line| line_rows[line]);
+        if let Event::Key(key) = event::read()? {
+            if !is_key_down(&key) {
+                continue;
+            }
+            match command_code(key.code) {
+                KeyCode::Char('q') | KeyCode::Esc => break,
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+                KeyCode::Up | KeyCode::Char('k') => scroll = scroll.saturating_sub(1),
+                KeyCode::Down | KeyCode::Char('j') => scroll += 1,
+                KeyCode::PageUp => scroll = scroll.saturating_sub(viewport.max(1)),
+                KeyCode::PageDown | KeyCode::Char(' ') => scroll += viewport.max(1),
+                KeyCode::Home => scroll = 0,
+                KeyCode::End => scroll = usize::MAX,
+                KeyCode::Char('n') => {
+                    if let Some(row) = change_rows.clone().find(|&row| row > scroll) {
+                        scroll = row;
+                    }
+                }
+                KeyCode::Char('N') | KeyCode::Char('p') => {
+                    if let Some(row) = change_rows.clone().rev().find(|&row| row < scroll) {
+                        scroll = row;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Styled lines for a diff, with the index of the first line of each change
+fn diff_lines(diff: &DocumentDiff) -> (Vec<Line<'static>>, Vec<usize>) {
+    let unchanged = Style::default().fg(Color::DarkGray);
+    let removed = Style::default().fg(Color::Red);
+    let added = Style::default().fg(Color::Green);
+    let changed = Style::default().fg(Color::Yellow);
+    let marked = |marker: &'static str, text: &str, style: Style| -> Vec<Line<'static>> {
+        text.lines()
+            .map(|line| Line::styled(format!("{marker} {line}"), style))
+            .collect()
+    };
+
+    let mut lines = Vec::new();
+    let mut change_lines = Vec::new();
+    for entry in &diff.entries {
+        if entry.is_change() {
+            change_lines.push(lines.len());
+        }
+        match entry {
+            DiffEntry::Unchanged { text, .. } => lines.extend(marked(" ", text, unchanged)),
+            DiffEntry::Removed { text, .. } => lines.extend(marked("-", text, removed)),
+            DiffEntry::Added { text, .. } => lines.extend(marked("+", text, added)),
+            DiffEntry::Changed { old, new, .. } => {
+                let mut spans = vec![Span::styled("~ ", changed)];
+                for (index, (change, words)) in word_diff(old, new).into_iter().enumerate() {
+                    if index > 0 {
+                        spans.push(Span::raw(" "));
+                    }
+                    spans.push(match change {
+                        WordChange::Unchanged => Span::raw(words),
+                        WordChange::Removed => {
+                            Span::styled(words, removed.add_modifier(Modifier::CROSSED_OUT))
+                        }
+                        WordChange::Added => {
+                            Span::styled(words, added.add_modifier(Modifier::UNDERLINED))
+                        }
+                    });
+                }
+                lines.push(Line::from(spans));
+            }
+            DiffEntry::TableChanged { title, cells, .. } => {
+                let heading = match title {
+                    Some(title) => format!("~ Table \"{title}\""),
+                    None => "~ Table".to_string(),
+                };
+                lines.push(Line::styled(heading, changed.add_modifier(Modifier::BOLD)));
+                for cell in cells {
+                    let style = match (&cell.old, &cell.new) {
+                        (Some(_), None) => removed,
+                        (None, Some(_)) => added,
+                        _ => changed,
+                    };
+                    lines.push(Line::styled(
+                        format!("~   {}", describe_cell_change(cell)),
+                        style,
+                    ));
+                }
+            }
+        }
+        lines.push(Line::default());
+    }
+    (lines, change_lines)
+}
+
+/// Presses and auto-repeats trigger bindings; releases (only reported by the
+/// kitty protocol) are ignored
+fn is_key_down(key: &KeyEvent) -> bool {
+    key.kind != KeyEventKind::Release
+}
+
+/// Map a key to the binding it should trigger. With a Russian, Ukrainian or
+/// Belarusian (ЙЦУКЕН) layout active, letters are translated back to the
+/// QWERTY key in the same position so `j`/`k`/`q`, `[`/`]` etc. keep working
+/// without switching layouts. Belarusian `і` sits on `b`, but is read as the
+/// Ukrainian one on `s`. Text fields use the raw key instead.
+///
+/// The kitty protocol's `REPORT_ALTERNATE_KEYS` also sends the key's position
+/// on a US layout, which would cover every layout, but crossterm (0.27 up to
+/// 0.29) drops that field and `KeyEventState` doesn't carry it. This table is
+/// the fallback until it does; other layouts need switching to Latin for
+/// commands.
+fn command_code(code: KeyCode) -> KeyCode {
+    const CYRILLIC: &str =
+        "йцукенгшщзхъфывапролджэячсмитьбюёЙЦУКЕНГШЩЗХЪФЫВАПРОЛДЖЭЯЧСМИТЬБЮЁіїєўІЇЄЎ";
+    const QWERTY: &str =
+        "qwertyuiop[]asdfghjkl;'zxcvbnm,.`QWERTYUIOP{}ASDFGHJKL:\"ZXCVBNM<>~s]'oS}\"O";
+
+    match code {
+        KeyCode::Char(c) => CYRILLIC
+            .chars()
+            .zip(QWERTY.chars())
+            .find(|(cyrillic, _)| *cyrillic == c)
+            .map(|(_, latin)| KeyCode::Char(latin))
+            .unwrap_or(code),
+        _ => code,
+    }
+}
+
+/// Leave raw mode, the alternate screen and mouse capture
+fn restore_terminal() -> Result<()> {
+    if KEYBOARD_ENHANCED.load(Ordering::SeqCst) {
+        execute!(io::stdout(), PopKeyboardEnhancementFlags)?;
+    }
+    disable_raw_mode()?;
+    execute!(
+        io::stdout(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        DisableBracketedPaste,
+        cursor::Show
+    )?;
+    Ok(())
+}
+
+/// Restore the terminal before the default panic output is printed
+fn install_panic_hook() {
+    let original_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |panic_info| {
+        let _ = restore_terminal();
+        original_hook(panic_info);
+    }));
+}
+
+async fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()> {
+    let mut last_tick = Instant::now();
+    loop {
+        terminal.draw(|f| ui(f, app))?;
+
+        let timeout = TICK_RATE.saturating_sub(last_tick.elapsed());
+        if event::poll(timeout)? {
+            let event = event::read()?;
+            app.record_reading_time();
+            match event {
+                Event::Key(key)
+                    if is_key_down(&key)
+                        && command_code(key.code) == KeyCode::Char('c')
+                        && key.modifiers.contains(KeyModifiers::CONTROL) =>
+                {
+                    break
+                }
+                Event::Key(key)
+                    if is_key_down(&key)
+                        && command_code(key.code) == KeyCode::Char('z')
+                        && key.modifiers.contains(KeyModifiers::CONTROL) =>
+                {
+                    suspend_process(terminal)?;
+                }
+                Event::Key(key)
+                    if is_key_down(&key)
+                        && command_code(key.code) == KeyCode::Char('p')
+                        && key.modifiers.contains(KeyModifiers::CONTROL)
+                        && app.prompt.is_none() =>
+                {
+                    app.open_palette();
+                }
+                Event::Key(key) if is_key_down(&key) && app.prompt.is_some() => {
+                    handle_prompt_key(terminal, app, key)?;
+                }
+                Event::Key(key) if is_key_down(&key) && app.pending_heading.is_some() => {
+                    app.finish_heading_jump(command_code(key.code));
+                }
+                Event::Key(key) if is_key_down(&key) && app.answer.is_some() => {
+                    handle_answer_key(app, key);
+                }
+                Event::Key(key) if is_key_down(&key) && app.selection.is_some() => {
+                    match command_code(key.code) {
+                        KeyCode::Down | KeyCode::Char('j') => app.extend_selection(true),
+                        KeyCode::Up | KeyCode::Char('k') => app.extend_selection(false),
+                        KeyCode::Char('y') => app.copy_selection(false),
+                        KeyCode::Char('m') => app.copy_selection(true),
+                        KeyCode::Char('e') => app.export_selection(),
+                        KeyCode::Esc | KeyCode::Char('V') | KeyCode::Char('q') => {
+                            app.selection = None
+                        }
+                        _ => {}
+                    }
+                }
+                Event::Key(key)
+                    if is_key_down(&key) && matches!(app.current_view, ViewMode::Table) =>
+                {
+                    handle_table_key(app, key);
+                }
+                Event::Key(key)
+                    if is_key_down(&key) && matches!(app.current_view, ViewMode::Image) =>
+                {
+                    handle_image_key(app, key);
+                }
+                // The letter names the bookmark as typed, in any layout
+                Event::Key(key) if is_key_down(&key) && app.pending_bookmark.is_some() => {
+                    match key.code {
+                        KeyCode::Char(name) => app.finish_bookmark(name),
+                        _ => {
+                            app.pending_bookmark = None;
+                            app.clear_status_message();
+                        }
+                    }
+                }
+                #[allow(clippy::collapsible_match)]
+                Event::Key(key) => {
+                    if is_key_down(&key) {
+                        // Clear status message on any key press (except the copy key)
+                        if app.status_message.is_some()
+                            && command_code(key.code) != KeyCode::Char('c')
+                            && key.code != KeyCode::F(2)
+                        {
+                            app.clear_status_message();
+                        }
+                        match app.current_view {
+                            ViewMode::Document
+                                if app.split_view && app.split_focus == SplitFocus::Outline =>
+                            {
+                                match command_code(key.code) {
+                                    KeyCode::Char('q') => break,
+                                    KeyCode::Char('v') => app.toggle_split(),
+                                    KeyCode::Tab | KeyCode::Esc => {
+                                        app.split_focus = SplitFocus::Document
+                                    }
+                                    KeyCode::Char('h') | KeyCode::F(1) => {
+                                        app.show_help = !app.show_help
+                                    }
+                                    KeyCode::Up | KeyCode::Char('k') => {
+                                        app.split_outline_move(false)
+                                    }
+                                    KeyCode::Down | KeyCode::Char('j') => {
+                                        app.split_outline_move(true)
+                                    }
+                                    KeyCode::Enter => app.split_focus = SplitFocus::Document,
+                                    _ => {}
+                                }
+                            }
+                            ViewMode::Document => match command_code(key.code) {
+                                // Checked before the plain letter bindings; a terminal
+                                // without key disambiguation sends Ctrl-I as Tab
+                                KeyCode::Char('o')
+                                    if key.modifiers.contains(KeyModifiers::CONTROL) =>
+                                {
+                                    app.jump_back()
+                                }
+                                KeyCode::Char('i')
+                                    if key.modifiers.contains(KeyModifiers::CONTROL) =>
+                                {
+                                    app.jump_forward()
+                                }
+                                KeyCode::Char('f')
+                                    if key.modifiers.contains(KeyModifiers::CONTROL) =>
+                                {
+                                    app.toggle_search_fuzzy()
+                                }
+                                KeyCode::Char('t')
+                                    if key.modifiers.contains(KeyModifiers::CONTROL) =>
+                                {
+                                    app.cycle_search_scope()
+                                }
+                                KeyCode::Char('q') => break,
+                                KeyCode::Esc if app.hygiene_banner => app.hygiene_banner = false,
+                                KeyCode::Char('o') => app.current_view = ViewMode::Outline,
+                                KeyCode::Char('s') => app.current_view = ViewMode::Search,
+                                KeyCode::Char('h') | KeyCode::F(1) => {
+                                    app.show_help = !app.show_help
+                                }
+                                KeyCode::Char('c') => app.copy_content(),
+                                KeyCode::Char('t') => app.copy_table(false),
+                                KeyCode::Char('C') => app.copy_table(true),
+                                KeyCode::Char('!') => app.open_prompt(PromptKind::ShellCommand),
+                                KeyCode::Char('/') => app.open_prompt(PromptKind::Search),
+                                KeyCode::Char('g') => app.open_prompt(PromptKind::GoTo),
+                                KeyCode::Char('?') => app.open_prompt(PromptKind::Ask),
+                                KeyCode::Char('e') => app.open_export_prompt(ExportScope::Document),
+                                KeyCode::Char('E') => app.open_export_prompt(ExportScope::Section),
+                                KeyCode::Char('O') => open_in_editor(terminal, app).await?,
+                                KeyCode::Char('v') => app.toggle_split(),
+                                KeyCode::Char('V') => app.start_selection(),
+                                KeyCode::Enter => app.open_table(),
+                                KeyCode::Char('i') => app.open_image_preview(),
+                                KeyCode::Char('w') => app.cycle_table_layout(),
+                                KeyCode::Char('<') => app.shift_tables(false),
+                                KeyCode::Char('>') => app.shift_tables(true),
+                                KeyCode::Tab if app.split_view => app.focus_outline_pane(),
+                                KeyCode::Tab => app.jump_forward(),
+                                KeyCode::Char('[') => {
+                                    app.pending_heading = Some(HeadingDirection::Previous)
+                                }
+                                KeyCode::Char(']') => {
+                                    app.pending_heading = Some(HeadingDirection::Next)
+                                }
+                                KeyCode::Char('m') => app.start_bookmark(BookmarkAction::Set),
+                                KeyCode::Char('\'') => app.start_bookmark(BookmarkAction::Jump),
+                                KeyCode::Char('r') => app.toggle_ruler(),
+                                KeyCode::Char('R') => app.toggle_reading_mode(),
+                                KeyCode::Char('f') => app.open_figures(),
+                                KeyCode::Char('H') => app.toggle_reveal_hidden(),
+                                KeyCode::Char('+') | KeyCode::Char('=') if app.reading_mode => {
+                                    app.adjust_reading_width(true)
+                                }
+                                KeyCode::Char('-') if app.reading_mode => {
+                                    app.adjust_reading_width(false)
+                                }
+                                KeyCode::Up | KeyCode::Char('k') if app.reading_ruler => {
+                                    app.ruler_up()
+                                }
+                                KeyCode::Down | KeyCode::Char('j') if app.reading_ruler => {
+                                    app.ruler_down()
+                                }
+                                KeyCode::Up | KeyCode::Char('k') => app.scroll_up(),
+                                KeyCode::Down | KeyCode::Char('j') => app.scroll_down(),
+                                KeyCode::PageUp => app.page_up(app.page_size()),
+                                KeyCode::PageDown => app.page_down(app.page_size()),
+                                KeyCode::Home => app.jump_with_history(0),
+                                KeyCode::End => {
+                                    app.record_jump();
+                                    app.scroll_to_end()
+                                }
+                                KeyCode::Char('n') if !app.search_results.is_empty() => {
+                                    app.next_search_result()
+                                }
+                                KeyCode::Char('p') | KeyCode::Char('N')
+                                    if !app.search_results.is_empty() =>
+                                {
+                                    app.prev_search_result()
+                                }
+                                _ => {}
+                            },
+                            ViewMode::Outline => match command_code(key.code) {
+                                KeyCode::Char('q') | KeyCode::Esc => {
+                                    app.current_view = ViewMode::Document
+                                }
+                                KeyCode::Char('c') => app.copy_content(),
+                                KeyCode::Up | KeyCode::Char('k') => app.move_outline_selection(-1),
+                                KeyCode::Down | KeyCode::Char('j') => app.move_outline_selection(1),
+                                KeyCode::PageUp => app
+                                    .move_outline_selection(-(app.outline_height.max(1) as isize)),
+                                KeyCode::PageDown => {
+                                    app.move_outline_selection(app.outline_height.max(1) as isize)
+                                }
+                                KeyCode::Home => app.select_outline(0),
+                                KeyCode::End => app.select_outline(usize::MAX),
+                                KeyCode::Enter => {
+                                    let element =
+                                        app.outline_state.selected().and_then(|selected| {
+                                            app.outline()
+                                                .get(selected)
+                                                .map(|item| item.element_index)
+                                        });
+                                    if let Some(element) = element {
+                                        app.jump_with_history(element);
+                                        app.current_view = ViewMode::Document;
+                                    }
+                                }
+                                _ => {}
+                            },
+                            ViewMode::Search => match key.code {
+                                // `q` only leaves an empty search so it can still be typed
+                                KeyCode::Char('q') if app.search_query.is_empty() => {
+                                    app.current_view = ViewMode::Document
+                                }
+                                KeyCode::Esc => app.current_view = ViewMode::Document,
+                                KeyCode::F(2) => app.copy_content(), // Use F2 for copy in search mode to avoid conflicts
+                                KeyCode::Char('f')
+                                    if key.modifiers.contains(KeyModifiers::CONTROL) =>
+                                {
+                                    app.toggle_search_fuzzy()
+                                }
+                                KeyCode::Char('t')
+                                    if key.modifiers.contains(KeyModifiers::CONTROL) =>
+                                {
+                                    app.cycle_search_scope()
+                                }
+                                KeyCode::Char('v')
+                                    if key.modifiers.contains(KeyModifiers::CONTROL) =>
+                                {
+                                    app.paste_clipboard()
+                                }
+                                KeyCode::Char(c) if is_text_input(&key) => {
+                                    app.search_query.push(c);
+                                    app.refresh_search();
+                                }
+                                KeyCode::Backspace => {
+                                    app.search_query.pop();
+                                    app.refresh_search();
+                                }
+                                KeyCode::Enter | KeyCode::Down => app.next_search_result(),
+                                KeyCode::Up => app.prev_search_result(),
+                                _ => {}
+                            },
+                            ViewMode::Palette => match key.code {
+                                KeyCode::Esc => app.current_view = ViewMode::Document,
+                                KeyCode::Enter => app.palette_jump(),
+                                KeyCode::Down => app.palette_select_next(),
+                                KeyCode::Up => app.palette_select_prev(),
+                                KeyCode::Tab => app.toggle_palette_paragraphs(),
+                                KeyCode::Char(c) if is_text_input(&key) => {
+                                    app.palette_query.push(c);
+                                    app.refresh_palette();
+                                }
+                                KeyCode::Backspace => {
+                                    app.palette_query.pop();
+                                    app.refresh_palette();
+                                }
+                                _ => {}
+                            },
+                            // Handled by `handle_table_key` and `handle_image_key`
+                            ViewMode::Table | ViewMode::Image => {}
+                            ViewMode::Help => match command_code(key.code) {
+                                KeyCode::Char('q')
+                                | KeyCode::Esc
+                                | KeyCode::Char('h')
+                                | KeyCode::F(1) => {
+                                    app.show_help = false;
+                                    app.current_view = ViewMode::Document;
+                                }
+                                _ => {}
+                            },
+                        }
+                    }
+                }
+                Event::Paste(pasted) => app.handle_paste(&pasted),
+                Event::Resize(_, _) => {
+                    // Graphics protocols can leave stale pixels behind, so force
+                    // a full redraw at the new size
+                    terminal.autoresize()?;
+                    terminal.clear()?;
+                    app.handle_resize();
+                }
+                Event::Mouse(mouse) => {
+                    match mouse.kind {
+                        MouseEventKind::ScrollUp => {
+                            match app.current_view {
+                                ViewMode::Document => {
+                                    // Scroll up 3 lines for smooth mouse wheel experience
+                                    for _ in 0..3 {
+                                        app.scroll_up();
+                                    }
+                                }
+                                ViewMode::Outline => app.scroll_outline(-3),
+                                ViewMode::Search => app.prev_search_result(),
+                                ViewMode::Palette => app.palette_select_prev(),
+                                ViewMode::Table => app.scroll_table(false, 3),
+                                ViewMode::Image => {
+                                    if let Some(preview) = app.image_preview.as_mut() {
+                                        preview.zoom_in()
+                                    }
+                                }
+                                _ => {}
+                            }
+                        }
+                        MouseEventKind::ScrollDown => {
+                            match app.current_view {
+                                ViewMode::Document => {
+                                    // Scroll down 3 lines for smooth mouse wheel experience
+                                    for _ in 0..3 {
+                                        app.scroll_down();
+                                    }
+                                }
+                                ViewMode::Outline => app.scroll_outline(3),
+                                ViewMode::Search => app.next_search_result(),
+                                ViewMode::Palette => app.palette_select_next(),
+                                ViewMode::Table => app.scroll_table(true, 3),
+                                ViewMode::Image => {
+                                    if let Some(preview) = app.image_preview.as_mut() {
+                                        preview.zoom_out()
+                                    }
+                                }
+                                _ => {}
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // Apply results from background tasks
+        while let Ok(message) = app.message_rx.try_recv() {
+            app.handle_message(message);
+        }
+        if app.should_quit {
+            break;
+        }
+
+        if last_tick.elapsed() >= TICK_RATE {
+            app.on_tick();
+            if app.source_changed() {
+                app.reload_document().await;
+            }
+            last_tick = Instant::now();
+        }
+    }
+
+    Ok(())
+}
+
+fn ui(f: &mut Frame, app: &mut App) {
+    f.render_stateful_widget(DoxxViewerWidget::new(), f.area(), app);
+    if let Some(position) = app.cursor_position {
+        f.set_cursor_position(position);
+    }
+}
+
+/// Draw the viewer into `area`, with the status bar along the bottom if `status_bar`
+pub(crate) fn draw(area: Rect, buf: &mut Buffer, app: &mut App, status_bar: bool) {
+    app.cursor_position = None;
+    // Reading mode leaves the document alone on screen
+    let status_bar = status_bar
+        && !(app.reading_mode && matches!(app.current_view, ViewMode::Document))
+        && !matches!(app.current_view, ViewMode::Image);
+    let (content, status) = if status_bar {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(3)].as_ref())
+            .split(area);
+        (chunks[0], Some(chunks[1]))
+    } else {
+        (area, None)
+    };
+
+    // Main content area
+    match app.current_view {
+        ViewMode::Document => render_document_area(buf, content, app),
+        ViewMode::Outline => render_outline(buf, content, app),
+        ViewMode::Search => render_search(buf, content, app),
+        ViewMode::Palette => {
+            render_document_area(buf, content, app);
+            render_palette(buf, content, app);
+        }
+        ViewMode::Table => render_table_view(buf, content, app),
+        ViewMode::Image => render_image_preview(buf, content, app),
+        ViewMode::Help => render_help(buf, content),
+    }
+
+    if app.pending_bookmark == Some(BookmarkAction::Jump) {
+        render_bookmarks(buf, content, app);
+    }
+    if let Some(answer) = &app.answer {
+        render_answer(buf, content, answer, &app.document, &app.ai.model);
+    }
+
+    // Status bar
+    if let Some(status) = status {
+        render_status_bar(buf, status, app);
+    }
+
+    // Help overlay
+    if app.show_help {
+        render_help_overlay(buf, area);
+    }
+
+    if app.monochrome {
+        strip_colors(buf, area);
+    }
+}
+
+/// Drop the colors in `area`, turning backgrounds (the status bar, search and
+/// selection highlights) into reverse video so they stay visible
+fn strip_colors(buf: &mut Buffer, area: Rect) {
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            let cell = &mut buf[(x, y)];
+            if cell.bg != Color::Reset {
+                cell.modifier.insert(Modifier::REVERSED);
+            }
+            cell.fg = Color::Reset;
+            cell.bg = Color::Reset;
+        }
+    }
+}
+
+/// Document view, with the outline pane beside it when the split layout is on
+fn render_document_area(buf: &mut Buffer, area: Rect, app: &mut App) {
+    let area = if app.hygiene_banner {
+        render_hygiene_banner(buf, area, &app.document.hygiene)
+    } else {
+        area
+    };
+    if !app.split_view || app.reading_mode {
+        render_document(buf, area, app);
+        return;
+    }
+
+    let panes = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
+        .split(area);
+    if app.split_focus == SplitFocus::Document {
+        app.sync_outline_to_scroll();
+    }
+    render_outline(buf, panes[0], app);
+    render_document(buf, panes[1], app);
+}
+
+/// Banner along the top of `area` listing what the hygiene scan found,
+/// returning the rest of the area
+fn render_hygiene_banner(buf: &mut Buffer, area: Rect, hygiene: &Hygiene) -> Rect {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0)])
+        .split(area);
+
+    let mut findings = Vec::new();
+    if !hygiene.macros.is_empty() {
+        findings.push(tr("hygiene-macros"));
+    }
+    for (id, count) in [
+        (
+            "hygiene-external-templates",
+            hygiene.external_templates.len(),
+        ),
+        ("hygiene-remote-images", hygiene.remote_images.len()),
+        ("hygiene-external-content", hygiene.external_content.len()),
+        ("hygiene-tracked-changes", hygiene.tracked_changes),
+        ("hygiene-hidden-text", hygiene.hidden_text),
+        ("hygiene-invisible-text", hygiene.invisible_text),
+    ] {
+        if count > 0 {
+            findings.push(tr_args(id, &[("count", count.into())]));
+        }
+    }
+    let banner = tr_args(
+        "hygiene-banner",
+        &[("findings", findings.join(", ").into())],
+    );
+    Widget::render(
+        Paragraph::new(banner).style(
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ),
+        chunks[0],
+        buf,
+    );
+    chunks[1]
+}
+
+fn render_document(buf: &mut Buffer, area: Rect, app: &mut App) {
+    let title = tr_args(
+        "title-document",
+        &[("title", app.document.title.as_str().into())],
+    );
+    let border_color = if app.split_view && app.split_focus == SplitFocus::Outline {
+        Color::DarkGray
+    } else {
+        Color::Blue
+    };
+    let inner = if app.reading_mode {
+        render_reading_margins(buf, area, app)
+    } else {
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(border_color));
+        let inner = block.inner(area);
+        Widget::render(block, area, buf);
+        inner
+    };
+
+    let font_size = app
+        .image_picker
+        .as_ref()
+        .map(|picker| picker.font_size)
+        .unwrap_or((8, 16));
+    // Half-block mosaics are coarse, so they are kept short unless
+    // --image-height asks otherwise
+    let halfblocks = app
+        .image_picker
+        .is_some_and(|picker| picker.protocol_type == ProtocolType::Halfblocks);
+    let max_image_rows = app
+        .document
+        .image_options
+        .max_height
+        .map(|rows| rows as u16)
+        .unwrap_or_else(|| {
+            let view_rows = inner.height.saturating_sub(2);
+            match app.halfblock_rows {
+                rows if halfblocks && rows > 0 => rows.min(view_rows),
+                _ => view_rows,
+            }
+        })
+        .max(1);
+
+    let max_image_cols = app.image_columns(inner.width);
+    let scale = app.document.image_options.scale_factor();
+
+    app.viewport_height = inner.height;
+    app.ensure_layout(inner.width, font_size, max_image_rows);
+    let ruler_row = app.reading_ruler.then(|| app.ruler_row());
+    let focused_element = ruler_row
+        .filter(|_| app.dim_unfocused)
+        .map(|row| app.layout.position_of(row).0);
+
+    // Lay out elements top to bottom: runs of text are rendered as wrapped
+    // paragraphs, and inline images get a reserved block of rows in between.
+    // The top element may be partly scrolled out of view by `skip` rows.
+    let bottom = inner.y + inner.height;
+    let mut y = inner.y;
+    let mut pending = Text::default();
+    let mut skip = app.scroll_line_offset as u16;
+    let mut text_skip = 0;
+
+    for actual_index in app.scroll_offset..app.document.elements.len() {
+        if y >= bottom {
+            break;
+        }
+        let element = &app.document.elements[actual_index];
+
+        if let Some(image) = app.image_protocols.get_mut(&actual_index) {
+            y = render_text_segment(buf, inner, y, std::mem::take(&mut pending), text_skip);
+            if y >= bottom {
+                break;
+            }
+
+            // A partly scrolled image is fitted into the rows still visible
+            let (cols, rows) =
+                inline_image_size(image, font_size, max_image_cols, max_image_rows, scale);
+            let visible_rows = rows.saturating_sub(skip);
+            skip = skip.saturating_sub(rows);
+            if visible_rows > 0 {
+                let image_area = Rect {
+                    x: inner.x,
+                    y,
+                    width: cols,
+                    height: visible_rows.min(bottom - y),
+                };
+                StatefulWidget::render(
+                    StatefulImage::new(None).resize(Resize::Fit(None)),
+                    image_area,
+                    buf,
+                    &mut image.protocol,
+                );
+                y += image_area.height;
+            }
+        }
+        text_skip += std::mem::take(&mut skip);
+
+        let first_line = pending.lines.len();
+        append_element_lines(
+            &mut pending,
+            element,
+            app.color_enabled,
+            app.image_protocols.contains_key(&actual_index),
+            &app.renderers,
+            inner.width,
+            app.fit(),
+        );
+        // Only elements in the search scope have matches to highlight
+        let has_results = app
+            .search_results
+            .binary_search_by_key(&actual_index, |result| result.element_index)
+            .is_ok();
+        if !app.search_query.is_empty() && has_results {
+            // Matches in the element of the selected result stand out
+            let is_current = app
+                .search_results
+                .get(app.current_search_index)
+                .is_some_and(|r| r.element_index == actual_index);
+            let match_style = if is_current {
+                Style::default().bg(Color::LightRed).fg(Color::Black)
+            } else {
+                Style::default().bg(Color::Yellow).fg(Color::Black)
+            };
+            highlight_matches(
+                &mut pending.lines[first_line..],
+                &app.search_query,
+                app.search_fuzzy,
+                match_style,
+            );
+        }
+        if app
+            .selection
+            .is_some_and(|selection| selection.range().contains(&actual_index))
+        {
+            for line in &mut pending.lines[first_line..] {
+                for span in &mut line.spans {
+                    span.style = span.style.add_modifier(Modifier::REVERSED);
+                }
+            }
+        }
+        if focused_element.is_some_and(|focused| focused != actual_index) {
+            for line in &mut pending.lines[first_line..] {
+                for span in &mut line.spans {
+                    span.style = span.style.add_modifier(Modifier::DIM);
+                }
+            }
+        }
+    }
+    render_text_segment(buf, inner, y, pending, text_skip);
+
+    if let Some(row) = ruler_row {
+        let ruler = Rect {
+            x: inner.x,
+            y: inner.y + (row - app.top_row()) as u16,
+            width: inner.width,
+            height: 1,
+        };
+        buf.set_style(ruler, Style::default().bg(Color::DarkGray));
+    }
+    if app.reading_mode {
+        return;
+    }
+
+    // Render scrollbar
+    let scrollbar = Scrollbar::default()
+        .orientation(ScrollbarOrientation::VerticalRight)
+        .begin_symbol(Some("↑"))
+        .end_symbol(Some("↓"));
+
+    let mut scrollbar_state = ScrollbarState::default()
+        .content_length(app.layout.total_rows())
+        .viewport_content_length(inner.height as usize)
+        .position(app.top_row());
+
+    StatefulWidget::render(
+        scrollbar,
+        area.inner(Margin {
+            vertical: 1,
+            horizontal: 0,
+        }),
+        buf,
+        &mut scrollbar_state,
+    );
+}
+
+/// The reading mode column centered in `area`, with a blank row above and
+/// below; status messages, which have no status bar to go to, show in the
+/// bottom one
+fn render_reading_margins(buf: &mut Buffer, area: Rect, app: &App) -> Rect {
+    let width = app.reading_width.min(area.width.saturating_sub(2));
+    let column = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + 1.min(area.height),
+        width,
+        height: area.height.saturating_sub(2),
+    };
+    if let Some(message) = &app.status_message {
+        let row = Rect {
+            y: area.bottom().saturating_sub(1),
+            height: 1.min(area.height),
+            ..column
+        };
+        Widget::render(
+            Paragraph::new(message.as_str())
+                .style(Style::default().fg(Color::DarkGray))
+                .alignment(Alignment::Center),
+            row,
+            buf,
+        );
+    }
+    column
+}
+
+/// Render a run of text starting at row `y` with its first `skip` wrapped rows
+/// scrolled out of view, returning the row after it
+fn render_text_segment(buf: &mut Buffer, inner: Rect, y: u16, text: Text, skip: u16) -> u16 {
+    let bottom = inner.y + inner.height;
+    if text.lines.is_empty() || y >= bottom {
+        return y;
+    }
+
+    let paragraph = Paragraph::new(text)
+        .wrap(Wrap { trim: false }) // Don't trim whitespace to preserve list indentation
+        .scroll((skip, 0));
+    let height = (paragraph.line_count(inner.width) as u16)
+        .saturating_sub(skip)
+        .min(bottom - y);
+    Widget::render(
+        paragraph,
+        Rect {
+            x: inner.x,
+            y,
+            width: inner.width,
+            height,
+        },
+        buf,
+    );
+    y + height
+}
+
+/// Cell size for an inline image: its size at the terminal's font size
+/// times `scale`, shrunk to fit `max_cols`, keeping the aspect ratio and
+/// capping the height
+fn inline_image_size(
+    image: &InlineImage,
+    font_size: (u16, u16),
+    max_cols: u16,
+    max_rows: u16,
+    scale: f32,
+) -> (u16, u16) {
+    let (font_w, font_h) = (font_size.0.max(1) as u32, font_size.1.max(1) as u32);
+    let scaled_width = (image.width_px as f32 * scale).round() as u32;
+    let natural_cols = scaled_width.div_ceil(font_w).max(1);
+    let cols = natural_cols.min(max_cols as u32).max(1);
+    let rows = (cols * font_w * image.height_px)
+        .div_ceil(image.width_px.max(1) * font_h)
+        .max(1);
+
+    (cols as u16, (rows.min(max_rows as u32)) as u16)
+}
+
+/// Turn the CriticMarkup left by `--changes markup` into styled spans:
+/// insertions underlined in green, deletions struck through in red
+fn revision_spans(text: String, style: Style) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut rest = text.as_str();
+    while let Some(start) = [rest.find("{++"), rest.find("{--")]
+        .into_iter()
+        .flatten()
+        .min()
+    {
+        let (close, change_style) = if rest[start..].starts_with("{++") {
+            (
+                "++}",
+                style.fg(Color::Green).add_modifier(Modifier::UNDERLINED),
+            )
+        } else {
+            (
+                "--}",
+                style.fg(Color::Red).add_modifier(Modifier::CROSSED_OUT),
+            )
+        };
+        let body = start + 3;
+        let Some(length) = rest[body..].find(close) else {
+            break;
+        };
+        if start > 0 {
+            spans.push(Span::styled(rest[..start].to_string(), style));
+        }
+        spans.push(Span::styled(
+            rest[body..body + length].to_string(),
+            change_style,
+        ));
+        rest = &rest[body + length + close.len()..];
+    }
+    if !rest.is_empty() || spans.is_empty() {
+        spans.push(Span::styled(rest.to_string(), style));
+    }
+    spans
+}
+
+/// How elements are fitted to the width of the document view
+#[derive(Debug, Clone, Copy)]
+struct Fit {
+    tables: TableLayout,
+    /// Columns scrolled sideways, for [`TableLayout::Scroll`]
+    table_shift: usize,
+    paragraphs: LayoutOptions,
+    /// Paragraphs with hidden text show it highlighted, run by run
+    reveal_hidden: bool,
+    /// Draw `--changes markup` marks as revisions (see [`revision_spans`])
+    revision_markup: bool,
+}
+
+impl Fit {
+    /// `text` as one span, or split at its revision marks when the document
+    /// was parsed with `--changes markup`
+    fn change_spans(&self, text: String, style: Style) -> Vec<Span<'static>> {
+        if self.revision_markup {
+            revision_spans(text, style)
+        } else {
+            vec![Span::styled(text, style)]
+        }
+    }
+}
+
+fn append_element_lines<'a>(
+    text: &mut Text<'a>,
+    element: &'a DocumentElement,
+    color_enabled: bool,
+    has_inline_image: bool,
+    renderers: &RendererRegistry,
+    width: u16,
+    fit: Fit,
+) {
+    let context = RenderContext {
+        width,
+        color: color_enabled,
+    };
+    if let Some(lines) = renderers.render(element, &context) {
+        text.lines.extend(lines);
+        return;
+    }
+
+    match element {
+        DocumentElement::Heading {
+            level,
+            text: heading_text,
+            number,
+            ..
+        } => {
+            let style = match level {
+                1 => Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+                2 => Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+                _ => Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            };
+
+            let prefix = match level {
+                1 => "■ ".to_string(),
+                2 => "  ▶ ".to_string(),
+                3 => "    ◦ ".to_string(),
+                _ => "      • ".to_string(),
+            };
+
+            let display_text = if let Some(number) = number {
+                format!("{number} {heading_text}")
+            } else {
+                heading_text.clone()
+            };
+
+            let mut spans = vec![Span::styled(prefix, style)];
+            spans.extend(fit.change_spans(display_text, style));
+            text.lines.push(Line::from(spans));
+            text.lines.push(Line::from(""));
+        }
+        DocumentElement::Paragraph {
+            text: para_text,
+            formatting,
+            runs,
+            ..
+        } => {
+            let mut style = Style::default();
+            if formatting.bold {
+                style = style.add_modifier(Modifier::BOLD);
+            }
+            if formatting.italic {
+                style = style.add_modifier(Modifier::ITALIC);
+            }
+            if formatting.underline {
+                style = style.add_modifier(Modifier::UNDERLINED);
+            }
+
+            // Apply text color from document formatting (only if color is enabled)
+            if color_enabled {
+                if let Some(color_hex) = &formatting.color {
+                    if let Some(color) = hex_to_color(color_hex) {
+                        style = style.fg(color);
+                    }
+                }
+            }
+
+            // Skip empty paragraphs
+            if para_text.trim().is_empty() {
+                return;
+            }
+            // Long paragraphs get some indentation
+            let indent = if para_text.len() > 100 { 2 } else { 0 };
+
+            if fit.reveal_hidden && runs.iter().any(|run| run.formatting.hidden) {
+                let hidden = style.bg(Color::Magenta).fg(Color::Black);
+                let mut spans = vec![Span::raw(" ".repeat(indent))];
+                spans.extend(runs.iter().map(|run| {
+                    Span::styled(
+                        run.text.as_str(),
+                        if run.formatting.hidden { hidden } else { style },
+                    )
+                }));
+                text.lines.push(Line::from(spans));
+            } else if (fit.revision_markup || runs.iter().any(|run| run.revision.is_some()))
+                && (para_text.contains("{++") || para_text.contains("{--"))
+            {
+                // Tracked changes keep their marked spans, wrapped as they come
+                let display_text = format!("{}{para_text}", " ".repeat(indent));
+                text.lines
+                    .push(Line::from(revision_spans(display_text, style)));
+            } else {
+                let lines = layout_paragraph(
+                    para_text,
+                    width as usize,
+                    indent,
+                    formatting.alignment,
+                    &fit.paragraphs,
+                );
+                text.lines
+                    .extend(lines.into_iter().map(|line| Line::styled(line, style)));
+            }
+            text.lines.push(Line::from(""));
+        }
+        DocumentElement::List {
+            items,
+            ordered,
+            start,
+        } => {
+            let labels = list_item_labels(items, *ordered, *start);
+            for (item, label) in items.iter().zip(labels) {
+                let bullet = match label {
+                    Some(label) => format!("{label} "),
+                    None => "• ".to_string(),
+                };
+
+                let indent = "  ".repeat(item.level as usize);
+
+                // Combine indent and bullet to ensure proper spacing
+                let prefixed_bullet = format!("{indent}{bullet}");
+                let mut spans = vec![Span::styled(
+                    prefixed_bullet,
+                    Style::default().fg(Color::Blue),
+                )];
+                spans.extend(fit.change_spans(item.text.clone(), Style::default()));
+                text.lines.push(Line::from(spans));
+            }
+            text.lines.push(Line::from(""));
+        }
+        DocumentElement::Table { table } => {
+            render_table_enhanced(table, text, width, fit);
+        }
+        DocumentElement::Image {
+            description,
+            width,
+            height,
+            image_path,
+            ocr_text,
+            ..
+        } => {
+            let dimensions = match (width, height) {
+                (Some(w), Some(h)) => format!(" ({w}x{h})"),
+                _ => String::new(),
+            };
+
+            let status = if has_inline_image {
+                String::new()
+            } else if image_path.is_some() {
+                format!(" {}", tr("image-available"))
+            } else {
+                format!(" {}", tr("image-not-extracted"))
+            };
+
+            let line = Line::from(vec![
+                Span::styled("🖼️  ", Style::default().fg(Color::Magenta)),
+                Span::styled(description, Style::default().fg(Color::Gray)),
+                Span::styled(dimensions, Style::default().fg(Color::DarkGray)),
+                Span::styled(status, Style::default().fg(Color::Green)),
+            ]);
+            text.lines.push(line);
+            // Text read from the image stands in for it when it isn't drawn
+            if let Some(ocr_text) = ocr_text.as_ref().filter(|_| !has_inline_image) {
+                for ocr_line in ocr_text.lines() {
+                    text.lines.push(Line::from(Span::styled(
+                        format!("    {ocr_line}"),
+                        Style::default()
+                            .fg(Color::Gray)
+                            .add_modifier(Modifier::ITALIC),
+                    )));
+                }
+            }
+            text.lines.push(Line::from(""));
+        }
+        DocumentElement::PageBreak => {
+            text.lines.push(Line::from(Span::styled(
+                "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━",
+                Style::default().fg(Color::DarkGray),
+            )));
+            text.lines.push(Line::from(""));
+        }
+        DocumentElement::Quote {
+            text: quote_text,
+            intense,
+            ..
+        } => {
+            let (bar, mut style) = if *intense {
+                ("┃ ", Style::default().add_modifier(Modifier::BOLD))
+            } else {
+                ("│ ", Style::default())
+            };
+            style = style.add_modifier(Modifier::ITALIC);
+            for line in quote_text.lines() {
+                let mut spans = vec![Span::styled(
+                    format!("  {bar}"),
+                    Style::default().fg(Color::Blue),
+                )];
+                spans.extend(fit.change_spans(line.to_string(), style));
+                text.lines.push(Line::from(spans));
+            }
+            text.lines.push(Line::from(""));
+        }
+        DocumentElement::CodeBlock {
+            lines, language, ..
+        } => {
+            // Code keeps its line breaks and indentation behind a gutter
+            let gutter = || Span::styled("  ▏ ", Style::default().fg(Color::DarkGray));
+            let highlighted = language
+                .as_deref()
+                .and_then(|language| highlight::highlight(lines, language));
+            if let Some(highlighted) = highlighted {
+                for tokens in highlighted {
+                    let mut spans = vec![gutter()];
+                    spans.extend(tokens.into_iter().map(|token| {
+                        let (r, g, b) = token.color;
+                        let mut style = Style::default().fg(Color::Rgb(r, g, b));
+                        if token.bold {
+                            style = style.add_modifier(Modifier::BOLD);
+                        }
+                        if token.italic {
+                            style = style.add_modifier(Modifier::ITALIC);
+                        }
+                        Span::styled(token.text, style)
+                    }));
+                    text.lines.push(Line::from(spans));
+                }
+            } else {
+                for line in lines {
+                    text.lines.push(Line::from(vec![
+                        gutter(),
+                        Span::styled(line.as_str(), Style::default().fg(Color::LightGreen)),
+                    ]));
+                }
+            }
+            text.lines.push(Line::from(""));
+        }
+        DocumentElement::Caption {
+            text: caption_text, ..
+        } => {
+            let style = Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::ITALIC);
+            text.lines
+                .push(Line::from(fit.change_spans(caption_text.clone(), style)));
+            text.lines.push(Line::from(""));
+        }
+        DocumentElement::Title {
+            text: title_text, ..
+        } => {
+            let style = Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+            text.lines
+                .push(Line::from(fit.change_spans(title_text.clone(), style)));
+            text.lines.push(Line::from(""));
+        }
+        DocumentElement::Subtitle {
+            text: subtitle_text,
+            ..
+        } => {
+            let style = Style::default()
+                .fg(Color::Gray)
+                .add_modifier(Modifier::ITALIC);
+            text.lines
+                .push(Line::from(fit.change_spans(subtitle_text.clone(), style)));
+            text.lines.push(Line::from(""));
+        }
+        DocumentElement::Equation {
+            text: equation_text,
+            ..
+        } => {
+            text.lines.push(Line::from(Span::styled(
+                format!("    {equation_text}"),
+                Style::default().fg(Color::Cyan),
+            )));
+            text.lines.push(Line::from(""));
+        }
+    }
+}
+
+/// Patch `style` onto every occurrence of `query` (normalized and case-folded),
+/// spans as needed. Matches spanning two spans are not highlighted.
+fn highlight_matches(lines: &mut [Line<'_>], query: &str, fuzzy: bool, style: Style) {
+    for line in lines {
+        let spans = std::mem::take(&mut line.spans);
+        for span in spans {
+            let ranges = find_matches(&span.content, query, fuzzy);
+            if ranges.is_empty() {
+                line.spans.push(span);
+                continue;
+            }
+            let mut last = 0;
+            for range in ranges {
+                if range.start > last {
+                    line.spans.push(Span::styled(
+                        span.content[last..range.start].to_string(),
+                        span.style,
+                    ));
+                }
+                line.spans.push(Span::styled(
+                    span.content[range.clone()].to_string(),
+                    span.style.patch(style),
+                ));
+                last = range.end;
+            }
+            if last < span.content.len() {
+                line.spans
+                    .push(Span::styled(span.content[last..].to_string(), span.style));
+            }
+        }
+    }
+}
+
+fn render_outline(buf: &mut Buffer, area: Rect, app: &mut App) {
+    let outline = app.outline();
+    let items: Vec<ListItem> = outline
+        .iter()
+        .map(|item| {
+            let indent = "  ".repeat((item.level.saturating_sub(1)) as usize);
+            // Borders and the highlight symbol take four columns
+            let budget = (area.width as usize).saturating_sub(4 + indent.len());
+            ListItem::new(format!("{indent}{}", ellipsize(&item.title, budget)))
+        })
+        .collect();
+
+    let border_color = if app.split_view && app.split_focus == SplitFocus::Document {
+        Color::DarkGray
+    } else {
+        Color::Green
+    };
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(tr("title-outline"))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(border_color)),
+        )
+        .style(Style::default().fg(Color::White))
+        .highlight_style(Style::default().bg(Color::Blue).fg(Color::White))
+        .highlight_symbol("➤ ");
+
+    // Drawn from our own scroll position; the selection is only passed on
+    // while it is in view, or the list would scroll back to it
+    app.outline_height = area.height.saturating_sub(2) as usize;
+    app.outline_scroll = app.outline_scroll.min(
+        app.outline_items
+            .len()
+            .saturating_sub(app.outline_height.max(1)),
+    );
+    let visible = app.outline_scroll..app.outline_scroll + app.outline_height;
+    let mut state = ListState::default()
+        .with_offset(app.outline_scroll)
+        .with_selected(
+            app.outline_state
+                .selected()
+                .filter(|selected| visible.contains(selected)),
+        );
+    StatefulWidget::render(list, area, buf, &mut state);
+}
+
+fn render_search(buf: &mut Buffer, area: Rect, app: &mut App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+        .split(area);
+
+    // Search input
+    let input = Paragraph::new(app.search_query.as_str())
+        .style(Style::default().fg(Color::Yellow))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title({
+                    let title = tr(if app.search_fuzzy {
+                        "title-search-fuzzy"
+                    } else {
+                        "title-search"
+                    });
+                    match app.search_scope_label() {
+                        Some(scope) => format!("{title} • {scope}"),
+                        None => title,
+                    }
+                })
+                .border_style(Style::default().fg(Color::Yellow)),
+        );
+    Widget::render(input, chunks[0], buf);
+
+    // Keep the terminal cursor at the end of the query so IME candidate
+    // windows open next to the text being composed
+    let input_width = chunks[0].width.saturating_sub(2);
+    let cursor_offset = (app.search_query.width() as u16).min(input_width.saturating_sub(1));
+    app.cursor_position = Some(Position::from((
+        chunks[0].x + 1 + cursor_offset,
+        chunks[0].y + 1,
+    )));
+
+    // Search results
+    let results: Vec<ListItem> = app
+        .search_results
+        .iter()
+        .enumerate()
+        .map(|(i, result)| {
+            let prefix = "📄"; // Simplified for now
+
+            let style = if i == app.current_search_index {
+                Style::default().bg(Color::Blue).fg(Color::White)
+            } else {
+                Style::default()
+            };
+
+            // Cut long results to the list width, keeping the prefix and index visible
+            let suffix = format!(" [{}]", i + 1);
+            let budget = (chunks[1].width as usize)
+                .saturating_sub(2 + display_width(prefix) + 1 + suffix.len());
+            let display_text = ellipsize(&result.text, budget);
+
+            ListItem::new(format!("{prefix} {display_text}{suffix}")).style(style)
+        })
+        .collect();
+
+    let results_list = List::new(results).block(
+        Block::default()
+            .title(tr_args(
+                "title-results",
+                &[
+                    (
+                        "current",
+                        if app.search_results.is_empty() {
+                            0
+                        } else {
+                            app.current_search_index + 1
+                        }
+                        .into(),
+                    ),
+                    ("total", app.search_results.len().into()),
+                ],
+            ))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow)),
+    );
+
+    Widget::render(results_list, chunks[1], buf);
+}
+
+/// Help lines: a section heading, or keys and what they do (both message ids
+/// are looked up; an empty key indents the message)
+const HELP_LINES: &[(&str, &str)] = &[
+    ("#", "help-section-navigation"),
+    ("↑/k", "help-scroll-up"),
+    ("↓/j", "help-scroll-down"),
+    ("Page Up", "help-page-up"),
+    ("Page Down", "help-page-down"),
+    ("[[ / ]]", "help-heading-same-level"),
+    ("[h / ]h", "help-heading-any-level"),
+    ("Home", "help-start"),
+    ("End", "help-end"),
+    ("g", "help-go-to"),
+    ("Ctrl-O", "help-jump-back"),
+    ("Ctrl-I/Tab", "help-jump-forward"),
+    ("r", "help-ruler"),
+    ("R", "help-reading-mode"),
+    ("+ / -", "help-reading-width"),
+    ("H", "help-reveal-hidden"),
+    ("#", "help-section-search"),
+    ("s", "help-open-search"),
+    ("/", "help-search-document"),
+    ("n", "help-next-result"),
+    ("p/N", "help-previous-result"),
+    ("Ctrl-F", "help-fuzzy"),
+    ("Ctrl-T", "help-search-scope"),
+    ("Ctrl-V", "help-paste-search"),
+    ("#", "help-section-other"),
+    ("o", "help-outline"),
+    ("v", "help-outline-pane"),
+    ("c", "help-copy"),
+    ("t / C", "help-copy-table"),
+    ("V", "help-select"),
+    ("e / E", "help-export"),
+    ("Enter", "help-table-mode"),
+    ("i", "help-image-preview"),
+    ("w", "help-table-layout"),
+    ("< / >", "help-table-shift"),
+    ("Ctrl-P", "help-palette"),
+    ("f", "help-figures"),
+    ("m + letter", "help-set-bookmark"),
+    ("' + letter", "help-jump-bookmark"),
+    ("?", "help-ask"),
+    ("!", "help-shell"),
+    ("O", "help-editor"),
+    ("Ctrl-Z", "help-suspend"),
+    ("h/F1", "help-toggle-help"),
+    ("q", "help-quit"),
+    ("#", "help-section-copy"),
+    ("", "help-copy-document"),
+    ("", "help-copy-outline"),
+    ("", "help-copy-search"),
+];
+
+fn render_help(buf: &mut Buffer, area: Rect) {
+    let mut help_text = vec![tr("help-title")];
+    for (keys, message) in HELP_LINES {
+        match *keys {
+            "#" => {
+                help_text.push(String::new());
+                help_text.push(tr(message));
+            }
+            "" => help_text.push(format!("  {}", tr(message))),
+            keys => help_text.push(format!("  {keys:<10} {}", tr(message))),
+        }
+    }
+    help_text.push(String::new());
+    help_text.push(tr("help-close"));
+
+    let help = Paragraph::new(help_text.join("\n"))
+        .block(
+            Block::default()
+                .title(tr("title-help"))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow)),
+        )
+        .wrap(Wrap { trim: true });
+
+    Widget::render(help, area, buf);
+}
+
+fn render_palette(buf: &mut Buffer, area: Rect, app: &mut App) {
+    let popup = centered_rect(70, 70, area);
+    Widget::render(Clear, popup, buf);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+        .split(popup);
+
+    let scope = tr(if app.palette_figures {
+        "palette-scope-figures"
+    } else if app.palette_include_paragraphs {
+        "palette-scope-all"
+    } else {
+        "palette-scope-headings"
+    });
+    let input = Paragraph::new(app.palette_query.as_str())
+        .style(Style::default().fg(Color::Yellow))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(tr_args("palette-title", &[("scope", scope.into())]))
+                .border_style(Style::default().fg(Color::Yellow)),
+        );
+    Widget::render(input, chunks[0], buf);
+
+    let input_width = chunks[0].width.saturating_sub(2);
+    let cursor_offset = (app.palette_query.width() as u16).min(input_width.saturating_sub(1));
+    app.cursor_position = Some(Position::from((
+        chunks[0].x + 1 + cursor_offset,
+        chunks[0].y + 1,
+    )));
+
+    let highlight = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+    let items: Vec<ListItem> = app
+        .palette_matches
+        .iter()
+        .map(|(entry, fuzzy)| {
+            let entry = &app.palette_entries[*entry];
+            let indent = match entry.level {
+                Some(level) => "  ".repeat(level.saturating_sub(1) as usize),
+                None => "    ¶ ".to_string(),
+            };
+            let mut spans = vec![Span::raw(indent)];
+            let mut matched = fuzzy.positions.iter().peekable();
+            for (i, c) in entry.label.chars().enumerate() {
+                if matched.next_if_eq(&&i).is_some() {
+                    spans.push(Span::styled(c.to_string(), highlight));
+                } else {
+                    spans.push(Span::raw(c.to_string()));
+                }
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let title = tr_args(
+        "palette-matches",
+        &[
+            ("shown", app.palette_matches.len().into()),
+            ("total", app.palette_entries.len().into()),
+        ],
+    );
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(Color::Yellow)),
+        )
+        .highlight_style(Style::default().bg(Color::Blue).fg(Color::White))
+        .highlight_symbol("➤ ");
+    StatefulWidget::render(list, chunks[1], buf, &mut app.palette_state);
+}
+
+/// Popup listing bookmarks while waiting for the letter after `'`
+fn render_bookmarks(buf: &mut Buffer, area: Rect, app: &App) {
+    let items: Vec<ListItem> = app
+        .document
+        .bookmarks
+        .iter()
+        .map(|(name, &index)| {
+            let preview = app
+                .document
+                .elements
+                .get(index)
+                .map(element_preview)
+                .unwrap_or_default();
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!(" {name} "),
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(preview),
+            ]))
+        })
+        .collect();
+
+    let height = (items.len() as u16 + 2).min(area.height);
+    let width = area.width.min(60);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    Widget::render(Clear, popup, buf);
+    Widget::render(
+        List::new(items).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(tr("title-bookmarks"))
+                .border_style(Style::default().fg(Color::Yellow)),
+        ),
+        popup,
+        buf,
+    );
+}
+
+/// The answer to a question asked with `?`, with the elements it cites
+fn render_answer(
+    buf: &mut Buffer,
+    area: Rect,
+    answer: &AnswerPopup,
+    document: &Document,
+    model: &str,
+) {
+    let popup = centered_rect(70, 60, area);
+    Widget::render(Clear, popup, buf);
+
+    let mut lines: Vec<Line> = if answer.text.is_empty() {
+        vec![Line::styled(
+            tr_args("answer-waiting", &[("model", model.into())]),
+            Style::default().fg(Color::DarkGray),
+        )]
+    } else {
+        answer.text.lines().map(Line::raw).collect()
+    };
+    if !answer.citations.is_empty() {
+        lines.push(Line::raw(""));
+        lines.push(Line::styled(
+            tr("answer-sources"),
+            Style::default().add_modifier(Modifier::BOLD),
+        ));
+        for (nth, &element) in answer.citations.iter().enumerate() {
+            let preview = document
+                .elements
+                .get(element)
+                .map(element_preview)
+                .unwrap_or_default();
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!(" {} ", nth + 1),
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    format!("[{}] ", element + 1),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::raw(preview),
+            ]));
+        }
+    }
+    if answer.done {
+        lines.push(Line::raw(""));
+        lines.push(Line::styled(
+            tr(if answer.citations.is_empty() {
+                "answer-help"
+            } else {
+                "answer-help-sources"
+            }),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+
+    let text = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(tr_args(
+                    "title-answer",
+                    &[("question", answer.question.as_str().into())],
+                ))
+                .border_style(Style::default().fg(Color::Yellow)),
+        )
+        .wrap(Wrap { trim: false })
+        .scroll((answer.scroll, 0));
+    Widget::render(text, popup, buf);
+}
+
+/// Short one-line description of an element for lists and popups
+fn element_preview(element: &DocumentElement) -> String {
+    let text = match element {
+        DocumentElement::Heading { text, number, .. } => match number {
+            Some(number) => format!("{number} {text}"),
+            None => text.clone(),
+        },
+        DocumentElement::Paragraph { text, .. }
+        | DocumentElement::Quote { text, .. }
+        | DocumentElement::Caption { text, .. }
+        | DocumentElement::Title { text, .. }
+        | DocumentElement::Subtitle { text, .. }
+        | DocumentElement::Equation { text, .. } => text.clone(),
+        DocumentElement::CodeBlock { lines, .. } => lines.first().cloned().unwrap_or_default(),
+        DocumentElement::List { items, .. } => items
+            .first()
+            .map(|item| item.text.clone())
+            .unwrap_or_default(),
+        DocumentElement::Table { table } => tr_args(
+            "preview-table",
+            &[("rows", table.metadata.row_count.into())],
+        ),
+        DocumentElement::Image { description, .. } => description.clone(),
+        DocumentElement::PageBreak => tr("preview-page-break"),
+    };
+    ellipsize(text.trim(), 50)
+}
+
+fn render_help_overlay(buf: &mut Buffer, area: Rect) {
+    let area = centered_rect(60, 70, area);
+    Widget::render(Clear, area, buf);
+    render_help(buf, area);
+}
+
+fn render_status_bar(buf: &mut Buffer, area: Rect, app: &mut App) {
+    let metadata = &app.document.metadata;
+    let view_indicator = tr(match app.current_view {
+        ViewMode::Document => "view-document",
+        ViewMode::Outline => "view-outline",
+        ViewMode::Search => "view-search",
+        ViewMode::Palette => "view-jump",
+        ViewMode::Table => "view-table",
+        ViewMode::Image => "view-image",
+        ViewMode::Help => "view-help",
+    });
+
+    let search_info = if !app.search_results.is_empty() {
+        let matches = tr_args(
+            "status-search-matches",
+            &[
+                ("current", (app.current_search_index + 1).into()),
+                ("total", app.search_results.len().into()),
+            ],
+        );
+        format!(" {matches}")
+    } else if !app.search_query.is_empty() {
+        format!(" {}", tr("status-search-none"))
+    } else {
+        String::new()
+    };
+
+    let status_text = if let Some(status_msg) = &app.status_message {
+        // Show status message (like copy confirmation) with higher priority
+        status_msg.clone()
+    } else if let Some(loading) = &app.loading {
+        if loading.total == 0 {
+            tr("status-loading")
+        } else {
+            tr_args(
+                "status-loading-progress",
+                &[
+                    ("parsed", loading.parsed.into()),
+                    ("total", loading.total.into()),
+                ],
+            )
+        }
+    } else {
+        // Estimated pages are marked as approximate
+        let approximate = if app.document.pages.source == PageSource::Estimated {
+            "~"
+        } else {
+            ""
+        };
+        let file = metadata
+            .file_path
+            .split('/')
+            .next_back()
+            .map(str::to_string)
+            .unwrap_or_else(|| tr("file-unknown"));
+        let page = format!(
+            "{approximate}{}",
+            app.document.pages.page_of(app.scroll_offset)
+        );
+        let (percent, section, minutes_left) = app.progress();
+        let section = section
+            .map(|title| {
+                format!(
+                    "{} • ",
+                    tr_args("status-section", &[("title", title.into())])
+                )
+            })
+            .unwrap_or_default();
+        let status = tr_args(
+            "status-bar",
+            &[
+                ("view", view_indicator.into()),
+                ("file", file.into()),
+                ("section", section.into()),
+                ("page", page.into()),
+                ("pages", app.document.pages.count.into()),
+                ("percent", percent.into()),
+                ("minutes", minutes_left.into()),
+                ("words", metadata.word_count.into()),
+                ("element", (app.scroll_offset + 1).into()),
+                ("elements", app.document.elements.len().into()),
+            ],
+        );
+        format!("{status}{search_info}")
+    };
+
+    let status_style = if app.status_message.is_some() {
+        // Highlight status messages
+        Style::default()
+            .fg(Color::Green)
+            .bg(Color::DarkGray)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::White).bg(Color::DarkGray)
+    };
+
+    let status = Paragraph::new(ellipsize(&status_text, area.width as usize))
+        .style(status_style)
+        .block(Block::default());
+
+    Widget::render(status, area, buf);
+
+    let help_area = Rect {
+        x: area.x,
+        y: area.y + 1,
+        width: area.width,
+        height: 1,
+    };
+
+    // An open prompt takes over the key hint line
+    if let Some(prompt) = &app.prompt {
+        let prompt_text = format!("{}{}", prompt.kind.label(), prompt.input);
+        let cursor_x = help_area.x + (prompt_text.width() as u16).min(area.width);
+        Widget::render(
+            Paragraph::new(prompt_text).style(Style::default().fg(Color::Yellow)),
+            help_area,
+            buf,
+        );
+        app.cursor_position = Some(Position::from((cursor_x, help_area.y)));
+        return;
+    }
+
+    if let Some(selection) = &app.selection {
+        let hint = tr_args(
+            "selection-hint",
+            &[("count", selection.range().len().into())],
+        );
+        Widget::render(
+            Paragraph::new(ellipsize(&hint, area.width as usize))
+                .style(Style::default().fg(Color::Yellow)),
+            help_area,
+            buf,
+        );
+        return;
+    }
+
+    // Navigation help
+    let help_text = tr(match app.current_view {
+        ViewMode::Table => "table-hint",
+        ViewMode::Image => "image-hint",
+        _ => "key-hints",
+    });
+
+    let help = Paragraph::new(help_text)
+        .style(Style::default().fg(Color::Gray))
+        .block(Block::default());
+
+    Widget::render(help, help_area, buf);
+}
+
+/// The picture open in the image preview, as large as the screen allows
+fn render_image_preview(buf: &mut Buffer, area: Rect, app: &mut App) {
+    let (Some(preview), Some(mut picker)) = (app.image_preview.as_mut(), app.image_picker) else {
+        return;
+    };
+    let (width, height) = preview.dimensions();
+    let title = tr_args(
+        "title-image",
+        &[
+            ("description", preview.description.clone().into()),
+            ("width", width.into()),
+            ("height", height.into()),
+            ("zoom", ((preview.zoom * 100.0).round() as u32).into()),
+        ],
+    );
+    let block = Block::default()
+        .title(title)
+        .title_bottom(tr("image-hint"))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Magenta));
+    let inner = block.inner(area);
+    Widget::render(block, area, buf);
+    preview.render(inner, buf, &mut picker);
+}
+
+/// The table open in table mode, filling `area`: the columns that fit from
+/// the view's first one, the rows from its first row, and a footer with the
+/// sum and average of numeric columns
+fn render_table_view(buf: &mut Buffer, area: Rect, app: &mut App) {
+    let Some(view) = app.table_view.as_mut() else {
+        return;
+    };
+    let Some(DocumentElement::Table { table }) = app.document.elements.get(view.element) else {
+        return;
+    };
+
+    let columns = column_count(table);
+    let summaries: Vec<_> = (0..columns)
+        .map(|column| column_summary(table, column))
+        .collect();
+    let content = |row: &[TableCell], column: usize| -> String {
+        row.get(column)
+            .map(|cell| cell.content.trim().to_string())
+            .unwrap_or_default()
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Magenta));
+    let inner = block.inner(area);
+
+    // Widest cell of each column, with room for the sort arrow in the
+    // header, but never wider than the view
+    let widths: Vec<usize> = (0..columns)
+        .map(|column| {
+            let cells = table
+                .rows
+                .iter()
+                .map(|row| display_width(&content(row, column)));
+            let footer = summaries[column].as_ref().map_or(0, |summary| {
+                display_width(&summary.format(summary.sum))
+                    .max(display_width(&summary.format(summary.average)))
+            });
+            cells
+                .chain([display_width(&content(&table.headers, column)) + 2, footer])
+                .max()
+                .unwrap_or(1)
+                .clamp(1, (inner.width as usize).saturating_sub(4).max(1))
+        })
+        .collect();
+
+    view.scroll_to_cursor(&widths, inner.width as usize);
+    let mut shown = Vec::new();
+    let mut used = 1;
+    for column in view.visible_columns() {
+        if column < view.first_column {
+            continue;
+        }
+        if !shown.is_empty() && used + widths[column] + 3 > inner.width as usize {
+            break;
+        }
+        used += widths[column] + 3;
+        shown.push(column);
+    }
+    let shown_widths: Vec<usize> = shown.iter().map(|&column| widths[column]).collect();
+
+    let title = table
+        .metadata
+        .title
+        .clone()
+        .unwrap_or_else(|| tr("table-untitled"));
+    let block = block.title(tr_args(
+        "title-table",
+        &[
+            ("title", title.into()),
+            ("first", (shown.first().unwrap_or(&0) + 1).into()),
+            ("last", (shown.last().unwrap_or(&0) + 1).into()),
+            ("columns", columns.into()),
+        ],
+    ));
+    Widget::render(block, area, buf);
+
+    let has_footer = shown.iter().any(|&column| summaries[column].is_some());
+    let frame_rows = 4 + if has_footer { 3 } else { 0 };
+    let body_rows = (inner.height as usize).saturating_sub(frame_rows);
+    let order = view.row_order(table);
+    view.first_row = view.first_row.min(order.len().saturating_sub(body_rows));
+
+    let border_style = Style::default().fg(Color::Gray);
+    let cursor_style = Style::default().fg(Color::Yellow);
+    // A row of cells, each given its text and style
+    let row_line = |cells: Vec<(String, Style)>| {
+        let mut spans = vec![Span::styled("│", border_style)];
+        for (text, style) in cells {
+            spans.push(Span::styled(format!(" {text} "), style));
+            spans.push(Span::styled("│", border_style));
+        }
+        Line::from(spans)
+    };
+    let fit = |text: &str, alignment: TextAlignment, width: usize| {
+        align_to_width(&ellipsize(text, width), alignment, width)
+    };
+
+    let mut lines = vec![Line::from(Span::styled(
+        generate_table_border(&shown_widths, BorderType::Top),
+        border_style,
+    ))];
+    lines.push(row_line(
+        shown
+            .iter()
+            .map(|&column| {
+                let arrow = match view.sort {
+                    Some(sort) if sort.column == column && sort.descending => " ▼",
+                    Some(sort) if sort.column == column => " ▲",
+                    _ => "",
+                };
+                let header = format!("{}{arrow}", content(&table.headers, column));
+                let mut style = Style::default().add_modifier(Modifier::BOLD);
+                if column == view.column {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                (fit(&header, TextAlignment::Left, widths[column]), style)
+            })
+            .collect(),
+    ));
+    lines.push(Line::from(Span::styled(
+        generate_table_border(&shown_widths, BorderType::Separator),
+        border_style,
+    )));
+
+    for &row in order.iter().skip(view.first_row).take(body_rows) {
+        let cells = &table.rows[row];
+        lines.push(row_line(
+            shown
+                .iter()
+                .map(|&column| {
+                    let alignment = cells
+                        .get(column)
+                        .map_or(TextAlignment::Left, |cell| cell.alignment);
+                    let style = if column == view.column {
+                        cursor_style
+                    } else {
+                        Style::default()
+                    };
+                    (
+                        fit(&content(cells, column), alignment, widths[column]),
+                        style,
+                    )
+                })
+                .collect(),
+        ));
+    }
+
+    if has_footer {
+        lines.push(Line::from(Span::styled(
+            generate_table_border(&shown_widths, BorderType::Separator),
+            border_style,
+        )));
+        for (label, average) in [(tr("table-sum"), false), (tr("table-average"), true)] {
+            lines.push(row_line(
+                shown
+                    .iter()
+                    .enumerate()
+                    .map(|(position, &column)| {
+                        let width = widths[column];
+                        let text = match &summaries[column] {
+                            Some(summary) => fit(
+                                &summary.format(if average {
+                                    summary.average
+                                } else {
+                                    summary.sum
+                                }),
+                                TextAlignment::Right,
+                                width,
+                            ),
+                            // The label goes in the first column shown, when it
+                            // has no total of its own
+                            None if position == 0 => fit(&label, TextAlignment::Left, width),
+                            None => " ".repeat(width),
+                        };
+                        (text, Style::default().fg(Color::Cyan))
+                    })
+                    .collect(),
+            ));
+        }
+    }
+    lines.push(Line::from(Span::styled(
+        generate_table_border(&shown_widths, BorderType::Bottom),
+        border_style,
+    )));
+
+    Widget::render(Paragraph::new(lines), inner, buf);
+}
+
+fn render_table_enhanced(table: &TableData, text: &mut Text, width: u16, fit: Fit) {
+    let metadata = &table.metadata;
+
+    // Add table title if present
+    if let Some(title) = &metadata.title {
+        text.lines.push(Line::from(Span::styled(
+            format!("📊 {title}"),
+            Style::default()
+                .fg(Color::Blue)
+                .add_modifier(Modifier::BOLD),
+        )));
+        text.lines.push(Line::from(""));
+    }
+
+    // Generate table with proper alignment and borders
+    if !table.headers.is_empty() {
+        let column_widths = match fit.tables {
+            TableLayout::Scroll => metadata.column_widths.clone(),
+            TableLayout::Wrap | TableLayout::Truncate => {
+                fit_column_widths(&metadata.column_widths, width as usize)
+            }
+        };
+        let wrap_cells = fit.tables == TableLayout::Wrap;
+        // Full-width tables are cut to the view where it has been scrolled to
+        let visible = |line: String| match fit.tables {
+            TableLayout::Scroll => {
+                let shifted = skip_width(&line, fit.table_shift);
+                truncate_to_width(&shifted, width as usize).to_string()
+            }
+            TableLayout::Wrap | TableLayout::Truncate => line,
+        };
+
+        // Top border
+        let top_border = generate_table_border(&column_widths, BorderType::Top);
+        text.lines.push(Line::from(Span::styled(
+            visible(top_border),
+            Style::default().fg(Color::Gray),
+        )));
+
+        // Header row
+        for header_line in render_table_row(&table.headers, &column_widths, true, wrap_cells) {
+            text.lines.push(Line::from(Span::styled(
+                visible(header_line),
+                Style::default().add_modifier(Modifier::BOLD),
+            )));
+        }
+
+        // Header separator
+        let separator = generate_table_border(&column_widths, BorderType::Separator);
+        text.lines.push(Line::from(Span::styled(
+            visible(separator),
+            Style::default().fg(Color::Gray),
+        )));
+
+        // Data rows
+        for row in &table.rows {
+            for row_line in render_table_row(row, &column_widths, false, wrap_cells) {
+                text.lines.push(Line::from(Span::raw(visible(row_line))));
+            }
+        }
+
+        // Bottom border
+        let bottom_border = generate_table_border(&column_widths, BorderType::Bottom);
+        text.lines.push(Line::from(Span::styled(
+            visible(bottom_border),
+            Style::default().fg(Color::Gray),
+        )));
+    }
+
+    text.lines.push(Line::from(""));
+}
+
+#[derive(Clone, Copy)]
+enum BorderType {
+    Top,
+    Separator,
+    Bottom,
+}
+
+fn generate_table_border(column_widths: &[usize], border_type: BorderType) -> String {
+    let (left, middle, right, fill) = match border_type {
+        BorderType::Top => ("┌", "┬", "┐", "─"),
+        BorderType::Separator => ("├", "┼", "┤", "─"),
+        BorderType::Bottom => ("└", "┴", "┘", "─"),
+    };
+
+    let mut border = String::new();
+    border.push_str(left);
+
+    for (i, &width) in column_widths.iter().enumerate() {
+        border.push_str(&fill.repeat(width + 2)); // +2 for padding
+        if i < column_widths.len() - 1 {
+            border.push_str(middle);
+        }
+    }
+
+    border.push_str(right);
+    border
+}
+
+/// Lines of a table row. Cells too wide for their column are wrapped onto
+/// more lines when `wrap`, and otherwise cut short with `…`.
+fn render_table_row(
+    cells: &[TableCell],
+    column_widths: &[usize],
+    is_header: bool,
+    wrap: bool,
+) -> Vec<String> {
+    let column_width = |i: usize| column_widths.get(i).copied().unwrap_or(10);
+    let cell_lines: Vec<Vec<String>> = cells
+        .iter()
+        .enumerate()
+        .map(|(i, cell)| {
+            let content = cell.content.trim();
+            let width = column_width(i);
+            if wrap && display_width(content) > width {
+                doxx_core::text_width::wrap(content, width, "", "")
+            } else {
+                vec![ellipsize(content, width)]
+            }
+        })
+        .collect();
+    let height = cell_lines.iter().map(Vec::len).max().unwrap_or(1);
+
+    (0..height)
+        .map(|line| {
+            let mut row = String::new();
+            row.push('│');
+
+            for (i, cell) in cells.iter().enumerate() {
+                let content = cell_lines[i].get(line).map_or("", String::as_str);
+                let aligned_content = align_to_width(content, cell.alignment, column_width(i));
+                let formatted_content = if is_header {
+                    aligned_content
+                } else {
+                    apply_cell_formatting(&aligned_content, &cell.formatting)
+                };
+
+                row.push(' ');
+                row.push_str(&formatted_content);
+                row.push(' ');
+                row.push('│');
+            }
+
+            row
+        })
+        .collect()
+}
+
+fn apply_cell_formatting(content: &str, _formatting: &TextFormatting) -> String {
+    // For terminal output, we'll keep formatting simple
+    // Advanced formatting could use ANSI codes here
+    content.to_string()
+}
+
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
+
+/// Convert hex color code to ratatui Color
+fn hex_to_color(hex: &str) -> Option<Color> {
+    // Remove # if present and ensure we have 6 characters
+    let hex = hex.trim_start_matches('#');
+    if hex.len() != 6 {
+        return None;
+    }
+
+    // Parse RGB components
+    let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
+    let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
+    let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
+
+    Some(Color::Rgb(r, g, b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Viewer on a fixture, laid out 80 columns wide with a 10-row view
+    fn fixture_app(name: &str) -> App {
+        let data = std::fs::read(Path::new("../../tests/fixtures").join(name)).unwrap();
+        app_on(Document::from_bytes(&data).unwrap())
+    }
+
+    /// Viewer on `document`, laid out 80 columns wide with a 10-row view
+    fn app_on(document: Document) -> App {
+        let mut app = App::embedded(document);
+        app.viewport_height = 10;
+        app.ensure_layout(80, (8, 16), 20);
+        app
+    }
+
+    fn heading_index(app: &App, wanted: &str) -> usize {
+        app.document
+            .elements
+            .iter()
+            .position(|element| {
+                matches!(element, DocumentElement::Heading { number: Some(number), .. } if number == wanted)
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_go_to() {
+        let data = std::fs::read("../../tests/fixtures/numbered-headings.docx").unwrap();
+        let mut document = Document::from_bytes(&data).unwrap();
+        for text in ["Figure 1: Layers", "Table 1: Budget", "Figure 2: Data flow"] {
+            document.elements.push(DocumentElement::Caption {
+                text: text.to_string(),
+                style: None,
+                sequence: None,
+            });
+        }
+        let captions = document.elements.len() - 3;
+        let mut app = app_on(document);
+
+        // A heading number, with or without its trailing dot
+        app.go_to("3.1");
+        assert_eq!(app.scroll_offset, heading_index(&app, "3.1"));
+        app.go_to("3.");
+        assert_eq!(app.scroll_offset, heading_index(&app, "3"));
+        app.go_to("2.1.");
+        assert_eq!(app.scroll_offset, heading_index(&app, "2.1"));
+
+        // A bare number is a page, never a top-level heading
+        app.go_to("1");
+        assert_eq!(
+            app.scroll_offset,
+            app.document.pages.first_element(1).unwrap()
+        );
+
+        app.go_to("50%");
+        assert_eq!(app.top_row(), app.layout.total_rows() / 2);
+        app.go_to("0%");
+        assert_eq!((app.scroll_offset, app.scroll_line_offset), (0, 0));
+
+        app.go_to("Figure 2");
+        assert_eq!(app.scroll_offset, captions + 2);
+        app.go_to("table 1");
+        assert_eq!(app.scroll_offset, captions + 1);
+        assert!(app.status_message.is_none());
+
+        // Nothing moves for what can't be found or read
+        for target in ["42", "Figure 3", "9.9", "150%", "half%", "-3", ""] {
+            app.go_to("3.");
+            app.clear_status_message();
+            app.go_to(target);
+            assert_eq!(app.scroll_offset, heading_index(&app, "3"), "{target}");
+            assert!(app.status_message.is_some(), "{target}");
+        }
+    }
+
+    #[test]
+    fn test_command_code() {
+        let command = |c: char| command_code(KeyCode::Char(c));
+        // Russian
+        assert_eq!(command('о'), KeyCode::Char('j'));
+        assert_eq!(command('й'), KeyCode::Char('q'));
+        assert_eq!(command('Р'), KeyCode::Char('H'));
+        assert_eq!(command('х'), KeyCode::Char('['));
+        assert_eq!(command('ъ'), KeyCode::Char(']'));
+        // Ukrainian and Belarusian letters Russian doesn't have
+        assert_eq!(command('і'), KeyCode::Char('s'));
+        assert_eq!(command('ї'), KeyCode::Char(']'));
+        assert_eq!(command('є'), KeyCode::Char('\''));
+        assert_eq!(command('ў'), KeyCode::Char('o'));
+        assert_eq!(command('Ў'), KeyCode::Char('O'));
+        // Everything else is left alone
+        assert_eq!(command('j'), KeyCode::Char('j'));
+        assert_eq!(command('ß'), KeyCode::Char('ß'));
+        assert_eq!(command_code(KeyCode::Esc), KeyCode::Esc);
+    }
+
+    #[test]
+    fn test_is_key_down() {
+        let key = |kind| KeyEvent::new_with_kind(KeyCode::Char('j'), KeyModifiers::NONE, kind);
+        assert!(is_key_down(&key(KeyEventKind::Press)));
+        assert!(is_key_down(&key(KeyEventKind::Repeat)));
+        assert!(!is_key_down(&key(KeyEventKind::Release)));
+    }
+
+    #[test]
+    fn test_jump_list() {
+        let mut app = fixture_app("numbered-headings.docx");
+        app.jump_with_history(5);
+        app.jump_with_history(10);
+
+        app.jump_back();
+        assert_eq!(app.scroll_offset, 5);
+        app.jump_back();
+        assert_eq!(app.scroll_offset, 0);
+        // Ctrl-O at the oldest jump stays put
+        app.jump_back();
+        assert_eq!(app.scroll_offset, 0);
+        assert!(app.status_message.take().is_some());
+
+        app.jump_forward();
+        assert_eq!(app.scroll_offset, 5);
+        app.jump_forward();
+        assert_eq!(app.scroll_offset, 10);
+        // Ctrl-I (Tab) at the newest jump stays put
+        app.jump_forward();
+        assert_eq!(app.scroll_offset, 10);
+        assert!(app.status_message.take().is_some());
+
+        // A new jump from the middle drops the forward history
+        app.jump_back();
+        app.jump_with_history(2);
+        app.jump_forward();
+        assert_eq!(app.scroll_offset, 2);
+        assert!(app.status_message.take().is_some());
+        app.jump_back();
+        assert_eq!(app.scroll_offset, 5);
+        app.jump_back();
+        assert_eq!(app.scroll_offset, 0);
+        assert_eq!(app.jump_list, [(0, 0), (5, 0), (2, 0)]);
+    }
+
+    #[test]
+    fn test_outline_pane_focus_records_jump() {
+        let mut app = fixture_app("numbered-headings.docx");
+        app.toggle_split();
+        app.jump_to_element(7);
+        app.focus_outline_pane();
+        assert_eq!(app.split_focus, SplitFocus::Outline);
+
+        // Browsing the outline moves the document; Ctrl-O comes back
+        app.split_outline_move(true);
+        app.split_outline_move(true);
+        assert_ne!(app.scroll_offset, 7);
+        app.split_focus = SplitFocus::Document;
+        app.jump_back();
+        assert_eq!(app.scroll_offset, 7);
+    }
+
+    #[test]
+    fn test_jump_to_heading() {
+        let mut app = fixture_app("numbered-headings.docx");
+        let first = heading_index(&app, "1");
+
+        // At the first heading there is nothing before it at any level
+        app.jump_to_element(first);
+        for same_level in [false, true] {
+            app.jump_to_heading(HeadingDirection::Previous, same_level);
+            assert_eq!(app.scroll_offset, first);
+            assert!(app.status_message.take().is_some());
+        }
+        app.jump_to_heading(HeadingDirection::Next, false);
+        assert_eq!(app.scroll_offset, heading_index(&app, "1.1"));
+        app.jump_to_element(first);
+        app.jump_to_heading(HeadingDirection::Next, true);
+        assert_eq!(app.scroll_offset, heading_index(&app, "2"));
+
+        // At the last heading there is nothing after it at any level
+        let last = heading_index(&app, "3.2");
+        app.jump_to_element(last);
+        for same_level in [false, true] {
+            app.jump_to_heading(HeadingDirection::Next, same_level);
+            assert_eq!(app.scroll_offset, last);
+            assert!(app.status_message.take().is_some());
+        }
+        app.jump_to_heading(HeadingDirection::Previous, false);
+        assert_eq!(app.scroll_offset, heading_index(&app, "3.1"));
+        // The same level skips the top-level heading between them
+        app.jump_to_heading(HeadingDirection::Previous, true);
+        assert_eq!(app.scroll_offset, heading_index(&app, "2.2"));
+    }
+
+    #[test]
+    fn test_finish_heading_jump() {
+        let mut app = fixture_app("numbered-headings.docx");
+        let first = heading_index(&app, "1");
+        app.jump_to_element(first);
+
+        app.pending_heading = Some(HeadingDirection::Next);
+        app.finish_heading_jump(KeyCode::Char(']'));
+        assert_eq!(app.scroll_offset, heading_index(&app, "2"));
+
+        // Any other key cancels the jump and says so, Esc quietly
+        app.pending_heading = Some(HeadingDirection::Previous);
+        app.finish_heading_jump(KeyCode::Char('x'));
+        assert_eq!(app.scroll_offset, heading_index(&app, "2"));
+        assert!(app.pending_heading.is_none());
+        assert!(app.status_message.take().unwrap().contains("[h"));
+        app.pending_heading = Some(HeadingDirection::Next);
+        app.finish_heading_jump(KeyCode::Esc);
+        assert!(app.pending_heading.is_none() && app.status_message.is_none());
+    }
+
+    #[test]
+    fn test_bookmarks() {
+        let mut app = fixture_app("numbered-headings.docx");
+        let set = |app: &mut App, element: usize, name: char| {
+            app.jump_to_element(element);
+            app.start_bookmark(BookmarkAction::Set);
+            app.finish_bookmark(name);
+        };
+        set(&mut app, 6, 'a');
+        // Named by the letter typed, not the QWERTY key under it
+        set(&mut app, 9, 'й');
+        set(&mut app, 3, 'A');
+        assert_eq!(
+            app.document.bookmarks,
+            std::collections::BTreeMap::from([('a', 6), ('й', 9)])
+        );
+
+        // Saved with the position and read back when the document is opened again
+        let path = std::env::temp_dir()
+            .join(format!("doxx-ui-bookmarks-test-{}", std::process::id()))
+            .join("positions.json");
+        let _ = std::fs::remove_file(&path);
+        let mut store = PositionStore::open(&path).unwrap();
+        app.store_position(&mut store, "doc");
+        store.save().unwrap();
+
+        let mut reopened = fixture_app("numbered-headings.docx");
+        reopened.document.bookmarks = PositionStore::open(&path).unwrap().bookmarks("doc");
+        for (name, element) in [('й', 9), ('a', 6)] {
+            reopened.start_bookmark(BookmarkAction::Jump);
+            reopened.finish_bookmark(name);
+            assert_eq!(reopened.scroll_offset, element);
+        }
+        reopened.start_bookmark(BookmarkAction::Jump);
+        reopened.finish_bookmark('q');
+        assert_eq!(reopened.scroll_offset, 6);
+        assert!(reopened.status_message.is_some());
+        // Ctrl-O goes back from a bookmark jump
+        reopened.jump_back();
+        assert_eq!(reopened.scroll_offset, 9);
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_palette() {
+        let mut app = fixture_app("numbered-headings.docx");
+        let labels = |app: &App| -> Vec<String> {
+            app.palette_matches
+                .iter()
+                .map(|(entry, _)| app.palette_entries[*entry].label.clone())
+                .collect()
+        };
+
+        // Headings only at first, in document order for an empty query
+        app.open_palette();
+        assert!(matches!(app.current_view, ViewMode::Palette));
+        assert!(app
+            .palette_entries
+            .iter()
+            .all(|entry| entry.level.is_some()));
+        assert_eq!(
+            labels(&app)[..2],
+            ["1 Introduction", "1.1 Project Overview"]
+        );
+
+        app.palette_query = "test".to_string();
+        app.refresh_palette();
+        assert_eq!(
+            labels(&app),
+            [
+                "3 Testing Strategy",
+                "3.1 Unit Testing",
+                "3.2 Integration Testing"
+            ]
+        );
+        app.palette_select_next();
+        app.palette_jump();
+        assert!(matches!(app.current_view, ViewMode::Document));
+        assert_eq!(app.scroll_offset, heading_index(&app, "3.1"));
+        app.jump_back();
+        assert_eq!(app.scroll_offset, 0);
+
+        // Tab adds the body paragraphs, and takes them away again
+        let (paragraph, text) = app
+            .document
+            .elements
+            .iter()
+            .enumerate()
+            .find_map(|(index, element)| match element {
+                DocumentElement::Paragraph { text, .. } if text.split_whitespace().count() > 3 => {
+                    Some((index, text.trim().to_string()))
+                }
+                _ => None,
+            })
+            .unwrap();
+        app.open_palette();
+        let headings = app.palette_entries.len();
+        app.palette_query = text.clone();
+        app.refresh_palette();
+        assert!(!labels(&app).contains(&text));
+
+        app.toggle_palette_paragraphs();
+        assert!(app.palette_entries.len() > headings);
+        assert_eq!(labels(&app)[0], text);
+        app.palette_jump();
+        assert_eq!(app.scroll_offset, paragraph);
+
+        app.open_palette();
+        app.toggle_palette_paragraphs();
+        assert_eq!(app.palette_entries.len(), headings);
+    }
+}
@@ -0,0 +1,402 @@
+//! The viewer as a ratatui widget.
+//!
+//! [`DoxxViewerWidget`] draws an [`App`] into any area of another application's
+//! frame, e.g. a preview pane in a file manager. The host keeps the `App` as
+//! the widget's state and drives it through its methods (`scroll_down`,
+//! `page_down`, `search`, `jump_to_element`, ...) from its own key handling.
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::widgets::StatefulWidget;
+
+use crate::ui::{draw, App};
+
+/// Stateful widget drawing a document, its outline or its search results,
+/// depending on the view of the [`App`] it is rendered with
+#[derive(Debug, Clone, Copy)]
+pub struct DoxxViewerWidget {
+    status_bar: bool,
+}
+
+impl Default for DoxxViewerWidget {
+    fn default() -> Self {
+        Self { status_bar: true }
+    }
+}
+
+impl DoxxViewerWidget {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Show the status bar (document info and key hints) along the bottom
+    pub fn status_bar(mut self, status_bar: bool) -> Self {
+        self.status_bar = status_bar;
+        self
+    }
+}
+
+impl StatefulWidget for DoxxViewerWidget {
+    type State = App;
+
+    fn render(self, area: Rect, buf: &mut Buffer, app: &mut App) {
+        draw(area, buf, app, self.status_bar);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::InlineImage;
+    use doxx_core::document::{
+        Document, DocumentElement, DocumentMetadata, ImageOptions, TableCell, TableData,
+        TextFormatting,
+    };
+    use ratatui::style::{Color, Modifier};
+    use ratatui_image::picker::{Picker, ProtocolType};
+
+    fn document(paragraphs: &[&str]) -> Document {
+        Document {
+            title: "Notes".to_string(),
+            metadata: DocumentMetadata {
+                file_path: "notes.docx".to_string(),
+                file_size: 0,
+                word_count: 0,
+                page_count: 1,
+                created: None,
+                modified: None,
+                author: None,
+            },
+            elements: paragraphs
+                .iter()
+                .map(|text| DocumentElement::Paragraph {
+                    text: text.to_string(),
+                    formatting: TextFormatting::default(),
+                    runs: Vec::new(),
+                    style: None,
+                })
+                .collect(),
+            image_options: ImageOptions::default(),
+            warnings: Vec::new(),
+            bookmarks: Default::default(),
+            pages: Default::default(),
+            hygiene: Default::default(),
+            image_dir: None,
+        }
+    }
+
+    fn rows(buf: &Buffer) -> Vec<String> {
+        (0..buf.area.height)
+            .map(|y| {
+                (0..buf.area.width)
+                    .map(|x| buf[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_widget_renders_into_area() {
+        let paragraphs: Vec<String> = (1..=20).map(|n| format!("Paragraph {n}")).collect();
+        let paragraphs: Vec<&str> = paragraphs.iter().map(String::as_str).collect();
+        let mut app = App::embedded(document(&paragraphs));
+
+        // Drawn offset inside a larger buffer, as a pane of another app
+        let area = Rect::new(10, 2, 30, 8);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 50, 12));
+        DoxxViewerWidget::new()
+            .status_bar(false)
+            .render(area, &mut buf, &mut app);
+        let screen = rows(&buf);
+        assert!(screen[2].starts_with(&" ".repeat(10)) && screen[2].contains("Notes"));
+        assert!(screen[3].trim_start().starts_with("│Paragraph 1 "));
+        assert!(screen.iter().all(|row| !row.contains("Paragraph 7")));
+        assert!(screen[0].trim().is_empty() && screen[1].trim().is_empty());
+
+        app.search("Paragraph 12");
+        let mut buf = Buffer::empty(Rect::new(0, 0, 50, 12));
+        DoxxViewerWidget::new().render(area, &mut buf, &mut app);
+        assert!(rows(&buf).iter().any(|row| row.contains("Paragraph 12")));
+    }
+
+    #[test]
+    fn test_selection_is_highlighted() {
+        let mut app = App::embedded(document(&["One", "Two", "Three", "Four"]));
+        let area = Rect::new(0, 0, 30, 14);
+        let mut buf = Buffer::empty(area);
+        DoxxViewerWidget::new().render(area, &mut buf, &mut app);
+
+        app.start_selection();
+        app.extend_selection(true);
+        app.extend_selection(true);
+        app.extend_selection(false);
+        assert_eq!(app.selection.map(|selection| selection.range()), Some(0..2));
+
+        let mut buf = Buffer::empty(area);
+        DoxxViewerWidget::new().render(area, &mut buf, &mut app);
+        let reversed = |y: u16| buf[(1, y)].modifier.contains(Modifier::REVERSED);
+        let screen = rows(&buf);
+        let row = |text: &str| screen.iter().position(|row| row.contains(text)).unwrap() as u16;
+        assert!(reversed(row("One")) && reversed(row("Two")));
+        assert!(!reversed(row("Three")));
+        assert!(screen
+            .iter()
+            .any(|row| row.contains("Selecting 2 elements")));
+    }
+
+    #[test]
+    fn test_status_bar_progress() {
+        let paragraphs: Vec<String> = (1..=40)
+            .map(|n| format!("Paragraph {n} of words"))
+            .collect();
+        let paragraphs: Vec<&str> = paragraphs.iter().map(String::as_str).collect();
+        let mut app = App::embedded(document(&paragraphs));
+        let area = Rect::new(0, 0, 120, 14);
+        let mut buf = Buffer::empty(area);
+        DoxxViewerWidget::new().render(area, &mut buf, &mut app);
+        // 9 rows of 80 show the first 5 paragraphs; 140 words are left
+        assert_eq!(app.progress(), (11, None, 1));
+
+        app.scroll_to_end();
+        DoxxViewerWidget::new().render(area, &mut buf, &mut app);
+        assert!(rows(&buf).iter().any(|row| row.contains("• 100% • done •")));
+    }
+
+    #[test]
+    fn test_section_range() {
+        let mut document = document(&["Intro", "A", "a1", "a2", "A.1", "a3", "B", "b1"]);
+        for (index, level, text) in [(1, 1, "A"), (4, 2, "A.1"), (6, 1, "B")] {
+            document.elements[index] = DocumentElement::Heading {
+                level,
+                text: text.to_string(),
+                number: None,
+                style: None,
+                source_level: None,
+            };
+        }
+        let mut app = App::embedded(document);
+        assert_eq!(app.section_range(), 0..1);
+        app.scroll_offset = 2;
+        assert_eq!(app.section_range(), 1..6);
+        app.scroll_offset = 5;
+        assert_eq!(app.section_range(), 4..6);
+        app.scroll_offset = 7;
+        assert_eq!(app.section_range(), 6..8);
+    }
+
+    #[test]
+    fn test_outline_scrolls_apart_from_selection() {
+        let mut document = document(&[]);
+        document.elements = (1..=30)
+            .map(|n| DocumentElement::Heading {
+                level: 1,
+                text: format!("Chapter {n}"),
+                number: None,
+                style: None,
+                source_level: None,
+            })
+            .collect();
+        let mut app = App::embedded(document);
+        app.current_view = crate::ui::ViewMode::Outline;
+        let area = Rect::new(0, 0, 30, 12);
+        let mut buf = Buffer::empty(area);
+        DoxxViewerWidget::new().render(area, &mut buf, &mut app);
+
+        // The wheel scrolls the list but keeps the selection
+        app.select_outline(0);
+        app.scroll_outline(10);
+        DoxxViewerWidget::new().render(area, &mut buf, &mut app);
+        assert_eq!(app.outline_state.selected(), Some(0));
+        assert!(rows(&buf)[1].contains("Chapter 11"));
+
+        // Moving the selection brings it back into view
+        app.move_outline_selection(1);
+        DoxxViewerWidget::new().render(area, &mut buf, &mut app);
+        assert_eq!(app.outline_scroll, 1);
+        assert!(rows(&buf)[1].contains("Chapter 2"));
+    }
+
+    #[test]
+    fn test_monochrome_uses_reverse_video() {
+        let mut app = App::embedded(document(&["One", "Two"]));
+        app.monochrome = true;
+        let area = Rect::new(0, 0, 30, 10);
+        let mut buf = Buffer::empty(area);
+        DoxxViewerWidget::new().render(area, &mut buf, &mut app);
+
+        let cells = || area.positions().map(|position| &buf[position]);
+        assert!(cells().all(|cell| cell.fg == Color::Reset && cell.bg == Color::Reset));
+        // The status bar keeps standing out
+        assert!(cells().any(|cell| cell.modifier.contains(Modifier::REVERSED)));
+    }
+
+    #[test]
+    fn test_render_ansi() {
+        let mut doc = document(&["Intro"]);
+        let cells = |row: &[&str]| row.iter().map(|c| TableCell::new(c.to_string())).collect();
+        doc.elements.push(DocumentElement::Table {
+            table: TableData::new(cells(&["Item", "Cost"]), vec![cells(&["Tea", "$3"])]),
+        });
+        let mut app = App::embedded(doc);
+
+        let plain = app.render_ansi(40);
+        assert!(!plain.contains('\x1b'));
+        assert!(plain.starts_with("Notes\n\nIntro\n"));
+        assert!(plain.contains("│ Tea  │   $3 │"));
+        assert!(plain.lines().all(|line| line == line.trim_end()));
+
+        app.color_enabled = true;
+        let styled = app.render_ansi(40);
+        assert!(styled.starts_with("\x1b[1mNotes\x1b[0m\n"));
+        assert!(styled.contains("│ Tea  │   $3 │"));
+    }
+
+    #[test]
+    fn test_halfblock_images_are_kept_short() {
+        let mut doc = document(&["Before", "After"]);
+        doc.elements.insert(
+            1,
+            DocumentElement::Image {
+                description: "Chart".to_string(),
+                width: None,
+                height: None,
+                relationship_id: None,
+                image_path: None,
+                ocr_text: None,
+            },
+        );
+        let mut app = App::embedded(doc);
+        let mut picker = Picker::new((8, 16));
+        picker.protocol_type = ProtocolType::Halfblocks;
+        app.image_picker = Some(picker);
+        // A tall image that would fill the whole view at its natural size
+        let image = image::DynamicImage::new_rgb8(160, 1600);
+        app.image_protocols.insert(
+            1,
+            InlineImage {
+                protocol: picker.new_resize_protocol(image),
+                width_px: 160,
+                height_px: 1600,
+            },
+        );
+
+        let image_rows = |app: &mut App| {
+            let area = Rect::new(0, 0, 60, 50);
+            let mut buf = Buffer::empty(area);
+            DoxxViewerWidget::new().render(area, &mut buf, app);
+            let screen = rows(&buf);
+            let before = screen
+                .iter()
+                .position(|row| row.contains("Before"))
+                .unwrap();
+            let after = screen.iter().position(|row| row.contains("After"))?;
+            Some(after - before)
+        };
+        // Besides the image: a blank line, the caption line and another blank
+        assert_eq!(image_rows(&mut app), Some(1 + 20 + 3));
+        app.halfblock_rows = 5;
+        assert_eq!(image_rows(&mut app), Some(1 + 5 + 3));
+        // Up to the height of the view, pushing the next paragraph off screen
+        app.halfblock_rows = 0;
+        assert_eq!(image_rows(&mut app), None);
+    }
+
+    #[test]
+    fn test_clipboard_query() {
+        use crate::ui::clipboard_query;
+
+        assert_eq!(
+            clipboard_query("> the quarterly\n> revenue figures\r\n"),
+            "the quarterly revenue figures"
+        );
+        assert_eq!(clipboard_query("\u{201C}net margin\u{201D}"), "net margin");
+        assert_eq!(clipboard_query("  \n\t "), "");
+
+        let mut app = App::embedded(document(&["Revenue grew", "Costs fell"]));
+        app.current_view = crate::ui::ViewMode::Search;
+        app.handle_paste(&clipboard_query("\"costs\"\n"));
+        assert_eq!(app.search_query, "costs");
+        assert_eq!(app.search_results.len(), 1);
+    }
+
+    #[test]
+    fn test_loading_shows_preview_first() {
+        use crate::ui::{AppMessage, ViewerOptions};
+        use doxx_core::config::Config;
+
+        let options = ViewerOptions {
+            search: Some("Paragraph 3".to_string()),
+            ..Default::default()
+        };
+        let mut app = App::loading(
+            std::path::Path::new("notes.docx"),
+            &options,
+            &Config::default(),
+        );
+        let area = Rect::new(0, 0, 60, 10);
+        let mut buf = Buffer::empty(area);
+        DoxxViewerWidget::new().render(area, &mut buf, &mut app);
+        assert!(rows(&buf).iter().any(|row| row.contains("Loading…")));
+
+        let whole = document(&["Paragraph 1", "Paragraph 2", "Paragraph 3"]);
+        app.handle_message(AppMessage::Preview(whole.elements[..2].to_vec()));
+        app.handle_message(AppMessage::LoadProgress(2, 3));
+        let mut buf = Buffer::empty(area);
+        DoxxViewerWidget::new().render(area, &mut buf, &mut app);
+        let screen = rows(&buf);
+        assert!(screen.iter().any(|row| row.contains("Paragraph 2")));
+        assert!(screen
+            .iter()
+            .any(|row| row.contains("Loading… 2 of 3 paragraphs and tables")));
+        // The search waits for the whole document
+        assert!(app.search_query.is_empty());
+
+        app.handle_message(AppMessage::Loaded(Box::new(Ok(whole))));
+        assert!(app.loading.is_none());
+        assert_eq!(app.document.elements.len(), 3);
+        assert_eq!(app.search_results.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_answer_popup() {
+        use crate::ui::AppMessage;
+        use doxx_core::ai::Answer;
+
+        let paragraphs: Vec<String> = (1..=30).map(|n| format!("Paragraph {n}")).collect();
+        let paragraphs: Vec<&str> = paragraphs.iter().map(String::as_str).collect();
+        let mut app = App::embedded(document(&paragraphs));
+        app.ask("Which paragraph comes last?");
+        let area = Rect::new(0, 0, 80, 24);
+        let mut buf = Buffer::empty(area);
+        DoxxViewerWidget::new().render(area, &mut buf, &mut app);
+        let screen = rows(&buf);
+        assert!(screen
+            .iter()
+            .any(|row| row.contains("Which paragraph comes last?")));
+        assert!(screen.iter().any(|row| row.contains("Asking llama3.2…")));
+
+        app.handle_message(AppMessage::AnswerText(1, "Paragraph 30 [30]".to_string()));
+        // Pieces of a question asked before are dropped
+        app.handle_message(AppMessage::AnswerText(0, " or not".to_string()));
+        app.handle_message(AppMessage::Answered(
+            1,
+            Ok(Answer {
+                text: "Paragraph 30 [30], after 29 [29].".to_string(),
+                citations: vec![29, 28],
+            }),
+        ));
+        let mut buf = Buffer::empty(area);
+        DoxxViewerWidget::new().render(area, &mut buf, &mut app);
+        let screen = rows(&buf);
+        assert!(screen
+            .iter()
+            .any(|row| row.contains("Paragraph 30 [30], after 29 [29].")));
+        assert!(screen
+            .iter()
+            .any(|row| row.contains(" 2 [29] Paragraph 29")));
+
+        app.go_to_citation(1);
+        assert!(app.answer.is_none());
+        assert_eq!(app.scroll_offset, 28);
+    }
+}
@@ -35,6 +35,12 @@
 
         # Read package info from local Cargo.toml (avoids duplication)
         cargoToml = builtins.fromTOML (builtins.readFile ./Cargo.toml);
+        # Fields inherited with `.workspace = true` are read from [workspace.package]
+        workspacePackage = cargoToml.workspace.package or {};
+        packageField = name:
+          if builtins.isAttrs (cargoToml.package.${name} or null)
+          then workspacePackage.${name} or null
+          else cargoToml.package.${name} or null;
         pname = cargoToml.package.name or "doxx";
         version = cargoToml.package.version or "0.0.0";
         description =
@@ -79,8 +85,8 @@
           else mapped;
 
         licenseAttr =
-          if cargoToml.package ? license
-          then parseLicenseString cargoToml.package.license
+          if packageField "license" != null
+          then parseLicenseString (packageField "license")
           else null;
 
         # Build local checkout by default
@@ -110,7 +116,10 @@
           meta =
             (with lib; {
               inherit description;
-              homepage = cargoToml.package.homepage or "https://github.com/bgreenwell/doxx";
+              homepage =
+                if packageField "homepage" != null
+                then packageField "homepage"
+                else "https://github.com/bgreenwell/doxx";
               platforms = platforms.linux ++ platforms.darwin;
               mainProgram = pname;
             })
@@ -71,7 +71,8 @@ esac
 log "Bumping $bump_type version: $current_version -> $new_version"
 
 # Update version in Cargo.toml
-sed -i.bak "s/^version = \"$current_version\"/version = \"$new_version\"/" "$PROJECT_DIR/Cargo.toml"
+# (the workspace version and the versions of the path dependencies on doxx-core and doxx-tui)
+sed -i.bak "s/version = \"$current_version\"/version = \"$new_version\"/" "$PROJECT_DIR/Cargo.toml"
 rm "$PROJECT_DIR/Cargo.toml.bak"
 
 # Run tests to make sure everything still works
@@ -17,7 +17,14 @@ fn main() -> Result<(), DynError> {
     generate_business_report_doc()?;
     generate_unicode_special_doc()?;
     generate_export_test_doc()?;
+    generate_tracked_changes_doc()?;
+    generate_contract_revision_docs()?;
+    generate_handbook_revision_docs()?;
     generate_retro_gaming_guide()?;
+    generate_paragraph_styles_doc()?;
+    generate_equations_doc()?;
+    generate_charts_doc()?;
+    generate_attachments_doc()?;
 
     println!("All test documents generated successfully!");
     Ok(())
@@ -512,6 +519,582 @@ fn generate_export_test_doc() -> Result<(), DynError> {
     Ok(())
 }
 
+fn generate_tracked_changes_doc() -> Result<(), DynError> {
+    let doc = Docx::new()
+        .add_paragraph(
+            Paragraph::new().add_run(Run::new().add_text("Tracked Changes Test").bold().size(24)),
+        )
+        .add_paragraph(
+            Paragraph::new()
+                .add_run(Run::new().add_text("Payment is due within "))
+                .add_delete(
+                    Delete::new()
+                        .author("Legal")
+                        .date("2024-01-10T09:00:00Z")
+                        .add_run(Run::new().add_delete_text("30")),
+                )
+                .add_insert(
+                    Insert::new(Run::new().add_text("45"))
+                        .author("Legal")
+                        .date("2024-01-10T09:00:00Z"),
+                )
+                .add_run(Run::new().add_text(" days of the invoice date.")),
+        )
+        .add_paragraph(
+            Paragraph::new().add_insert(
+                Insert::new(Run::new().add_text("This paragraph was added in review."))
+                    .author("Editor")
+                    .date("2024-01-11T14:30:00Z"),
+            ),
+        )
+        .add_paragraph(
+            Paragraph::new()
+                .add_run(Run::new().add_text("Late fees apply"))
+                .add_delete(
+                    Delete::new()
+                        .author("Editor")
+                        .date("2024-01-11T14:30:00Z")
+                        .add_run(Run::new().add_delete_text(" at the discretion of the vendor")),
+                )
+                .add_run(Run::new().add_text(".")),
+        );
+
+    let path = "tests/fixtures/tracked-changes.docx";
+    let file = File::create(path)?;
+    doc.build().pack(file)?;
+    println!("Generated: {path}");
+    Ok(())
+}
+
+/// Quotes, code, captions and titles written with Word's paragraph styles,
+/// plus code in a ``` fence and in a monospace font
+fn generate_paragraph_styles_doc() -> Result<(), DynError> {
+    let styled = |style: &str, text: &str| {
+        Paragraph::new()
+            .style(style)
+            .add_run(Run::new().add_text(text))
+    };
+    let plain = |text: &str| Paragraph::new().add_run(Run::new().add_text(text));
+    let doc =
+        Docx::new()
+            .add_paragraph(styled("Title", "Release Notes"))
+            .add_paragraph(styled("Subtitle", "Version 2.0 highlights"))
+            .add_paragraph(styled("Heading1", "Overview"))
+            .add_paragraph(Paragraph::new().add_run(
+                Run::new().add_text("This release focuses on speed. As one early user put it:"),
+            ))
+            .add_paragraph(styled(
+                "Quote",
+                "It opens documents faster than my word processor.",
+            ))
+            .add_paragraph(styled(
+                "IntenseQuote",
+                "Back up your configuration before upgrading.",
+            ))
+            .add_paragraph(styled("Heading1", "Configuration"))
+            .add_paragraph(
+                Paragraph::new().add_run(Run::new().add_text("Settings live in a TOML file:")),
+            )
+            .add_paragraph(styled("HTMLPreformatted", "[search]"))
+            .add_paragraph(styled("HTMLPreformatted", "fuzzy = true"))
+            .add_paragraph(styled("HTMLPreformatted", ""))
+            .add_paragraph(styled("HTMLPreformatted", "[pages]"))
+            .add_paragraph(styled("HTMLPreformatted", "elements_per_page = 40"))
+            .add_paragraph(styled("Caption", "Listing 1: A minimal configuration file"))
+            .add_paragraph(styled("Heading1", "Scripting"))
+            .add_paragraph(plain("```python"))
+            .add_paragraph(plain("def open_all(paths):"))
+            .add_paragraph(plain("    return [doxx.open(path) for path in paths]"))
+            .add_paragraph(plain("```"))
+            .add_paragraph(Paragraph::new().add_run(
+                Run::new().add_text("Or from Rust, with the code set in a monospace font:"),
+            ));
+    let doc = [
+        "fn main() {",
+        "    let mut pages = 0;",
+        "    println!(\"{pages} pages\");",
+        "}",
+    ]
+    .iter()
+    .fold(doc, |doc, line| {
+        doc.add_paragraph(
+            Paragraph::new().add_run(
+                Run::new()
+                    .add_text(*line)
+                    .fonts(RunFonts::new().ascii("Consolas")),
+            ),
+        )
+    });
+
+    let path = "tests/fixtures/paragraph-styles.docx";
+    let file = File::create(path)?;
+    doc.build().pack(file)?;
+    println!("Generated: {path}");
+    Ok(())
+}
+
+/// Equations in Office Math (OMML), in line and on lines of their own.
+/// docx-rs can't write OMML, so placeholder runs are swapped for it after
+/// the document is built.
+fn generate_equations_doc() -> Result<(), DynError> {
+    let r = |text: &str| format!("<m:r><m:t>{text}</m:t></m:r>");
+    let sup = |base: &str, sup: &str| {
+        format!(
+            "<m:sSup><m:e>{}</m:e><m:sup>{}</m:sup></m:sSup>",
+            r(base),
+            r(sup)
+        )
+    };
+    let area = format!("<m:oMath>{}{}</m:oMath>", r("A=π"), sup("r", "2"));
+    let quadratic = format!(
+        "<m:oMathPara><m:oMath>{}<m:f><m:num>{}<m:rad><m:radPr><m:degHide m:val=\"1\"/></m:radPr>\
+         <m:deg/><m:e>{}{}</m:e></m:rad></m:num><m:den>{}</m:den></m:f></m:oMath></m:oMathPara>",
+        r("x="),
+        r("−b±"),
+        sup("b", "2"),
+        r("−4ac"),
+        r("2a"),
+    );
+    let sum = format!(
+        "<m:oMathPara><m:oMath><m:nary><m:naryPr><m:chr m:val=\"∑\"/></m:naryPr>\
+         <m:sub>{}</m:sub><m:sup>{}</m:sup><m:e>{}</m:e></m:nary>{}\
+         <m:f><m:num>{}</m:num><m:den>{}</m:den></m:f></m:oMath></m:oMathPara>",
+        r("i=1"),
+        r("n"),
+        r("i"),
+        r("="),
+        r("n(n+1)"),
+        r("2"),
+    );
+    let plain = |text: &str| Paragraph::new().add_run(Run::new().add_text(text));
+    let doc = Docx::new()
+        .add_paragraph(
+            Paragraph::new()
+                .style("Heading1")
+                .add_run(Run::new().add_text("Equations")),
+        )
+        .add_paragraph(
+            Paragraph::new()
+                .add_run(Run::new().add_text("The area of a circle is "))
+                .add_run(Run::new().add_text("{{MATH1}}"))
+                .add_run(Run::new().add_text(" for a radius r.")),
+        )
+        .add_paragraph(plain("The roots of a quadratic are"))
+        .add_paragraph(plain("{{MATH2}}"))
+        .add_paragraph(plain("and the first n integers add up to"))
+        .add_paragraph(plain("{{MATH3}}"));
+
+    let path = "tests/fixtures/equations.docx";
+    pack_with_replacements(
+        doc,
+        path,
+        &[
+            ("{{MATH1}}", area),
+            ("{{MATH2}}", quadratic),
+            ("{{MATH3}}", sum),
+        ],
+        &[] as &[(&str, String)],
+    )?;
+    println!("Generated: {path}");
+    Ok(())
+}
+
+/// A bar chart and a line chart, each a drawing that refers to a chart part
+/// holding the series data
+fn generate_charts_doc() -> Result<(), DynError> {
+    let points = |values: &[&str]| -> String {
+        values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| format!("<c:pt idx=\"{index}\"><c:v>{value}</c:v></c:pt>"))
+            .collect()
+    };
+    let series = |index: usize, name: &str, categories: &[&str], values: &[&str]| {
+        format!(
+            "<c:ser><c:idx val=\"{index}\"/><c:order val=\"{index}\"/>\
+             <c:tx><c:strRef><c:f>Sheet1!$B$1</c:f><c:strCache><c:ptCount val=\"1\"/>{}</c:strCache></c:strRef></c:tx>\
+             <c:cat><c:strRef><c:f>Sheet1!$A$2</c:f><c:strCache><c:ptCount val=\"{}\"/>{}</c:strCache></c:strRef></c:cat>\
+             <c:val><c:numRef><c:f>Sheet1!$B$2</c:f><c:numCache><c:formatCode>General</c:formatCode><c:ptCount val=\"{}\"/>{}</c:numCache></c:numRef></c:val></c:ser>",
+            points(&[name]),
+            categories.len(),
+            points(categories),
+            values.len(),
+            points(values),
+        )
+    };
+    let chart_part = |title: Option<&str>, kind: &str, series: String| {
+        let title = title.map_or(String::from("<c:autoTitleDeleted val=\"1\"/>"), |title| {
+            format!("<c:title><c:tx><c:rich><a:bodyPr/><a:p><a:r><a:t>{title}</a:t></a:r></a:p></c:rich></c:tx><c:overlay val=\"0\"/></c:title>")
+        });
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\
+             <c:chartSpace xmlns:c=\"http://schemas.openxmlformats.org/drawingml/2006/chart\" \
+             xmlns:a=\"http://schemas.openxmlformats.org/drawingml/2006/main\" \
+             xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\">\
+             <c:chart>{title}<c:plotArea><c:layout/><c:{kind}>{series}<c:axId val=\"1\"/><c:axId val=\"2\"/></c:{kind}></c:plotArea>\
+             <c:plotVisOnly val=\"1\"/></c:chart></c:chartSpace>"
+        )
+    };
+    let drawing = |id: &str, name: &str| {
+        format!(
+            "<w:r><w:drawing><wp:inline distT=\"0\" distB=\"0\" distL=\"0\" distR=\"0\">\
+             <wp:extent cx=\"5486400\" cy=\"3200400\"/><wp:docPr id=\"{}\" name=\"{name}\"/>\
+             <a:graphic><a:graphicData uri=\"http://schemas.openxmlformats.org/drawingml/2006/chart\">\
+             <c:chart xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\" r:id=\"{id}\"/>\
+             </a:graphicData></a:graphic></wp:inline></w:drawing></w:r>",
+            &id[3..],
+        )
+    };
+
+    let quarters = ["Q1", "Q2", "Q3", "Q4"];
+    let revenue = chart_part(
+        Some("Revenue by Quarter"),
+        "barChart",
+        series(0, "2023", &quarters, &["1.2", "1.4", "1.5", "1.9"])
+            + &series(1, "2024", &quarters, &["1.6", "1.8", "2.0", "2.1"]),
+    );
+    let users = chart_part(
+        None,
+        "lineChart",
+        series(
+            0,
+            "Active users",
+            &["Jan", "Feb", "Mar"],
+            &["1200", "1850", "2400"],
+        ),
+    );
+    let rels = "<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+        <Relationship Id=\"rId901\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/chart\" Target=\"charts/chart1.xml\"/>\
+        <Relationship Id=\"rId902\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/chart\" Target=\"charts/chart2.xml\"/>\
+        </Relationships>";
+
+    let plain = |text: &str| Paragraph::new().add_run(Run::new().add_text(text));
+    let doc = Docx::new()
+        .add_paragraph(
+            Paragraph::new()
+                .style("Heading1")
+                .add_run(Run::new().add_text("Quarterly Results")),
+        )
+        .add_paragraph(plain("Revenue grew in every quarter (in millions):"))
+        .add_paragraph(plain("{{CHART1}}"))
+        .add_paragraph(plain("Active users since launch:"))
+        .add_paragraph(plain("{{CHART2}}"));
+
+    let path = "tests/fixtures/charts.docx";
+    pack_with_replacements(
+        doc,
+        path,
+        &[
+            ("{{CHART1}}", drawing("rId901", "Chart 1")),
+            ("{{CHART2}}", drawing("rId902", "Chart 2")),
+        ],
+        &[
+            ("word/charts/chart1.xml", revenue),
+            ("word/charts/chart2.xml", users),
+            ("word/_rels/document.xml.rels", rels.to_string()),
+        ],
+    )?;
+    println!("Generated: {path}");
+    Ok(())
+}
+
+/// A memo with a spreadsheet and a PDF embedded in its package
+fn generate_attachments_doc() -> Result<(), DynError> {
+    // A one-sheet workbook, itself a zip package
+    let mut workbook = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    for (name, xml) in [
+        (
+            "[Content_Types].xml",
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+             <Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">\
+             <Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>\
+             <Default Extension=\"xml\" ContentType=\"application/xml\"/>\
+             <Override PartName=\"/xl/workbook.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml\"/>\
+             <Override PartName=\"/xl/worksheets/sheet1.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml\"/>\
+             </Types>",
+        ),
+        (
+            "_rels/.rels",
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+             <Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+             <Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument\" Target=\"xl/workbook.xml\"/>\
+             </Relationships>",
+        ),
+        (
+            "xl/workbook.xml",
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+             <workbook xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" \
+             xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\">\
+             <sheets><sheet name=\"Budget\" sheetId=\"1\" r:id=\"rId1\"/></sheets></workbook>",
+        ),
+        (
+            "xl/_rels/workbook.xml.rels",
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+             <Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+             <Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet\" Target=\"worksheets/sheet1.xml\"/>\
+             </Relationships>",
+        ),
+        (
+            "xl/worksheets/sheet1.xml",
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+             <worksheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\"><sheetData>\
+             <row r=\"1\"><c r=\"A1\" t=\"inlineStr\"><is><t>Item</t></is></c><c r=\"B1\" t=\"inlineStr\"><is><t>Cost</t></is></c></row>\
+             <row r=\"2\"><c r=\"A2\" t=\"inlineStr\"><is><t>Travel</t></is></c><c r=\"B2\"><v>1200</v></c></row>\
+             </sheetData></worksheet>",
+        ),
+    ] {
+        workbook.start_file(name, zip::write::SimpleFileOptions::default())?;
+        std::io::Write::write_all(&mut workbook, xml.as_bytes())?;
+    }
+    let workbook = workbook.finish()?.into_inner();
+
+    let pdf = b"%PDF-1.4\n1 0 obj << /Type /Catalog /Pages 2 0 R >> endobj\n\
+        2 0 obj << /Type /Pages /Kids [3 0 R] /Count 1 >> endobj\n\
+        3 0 obj << /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] >> endobj\n\
+        trailer << /Root 1 0 R >>\n%%EOF\n"
+        .to_vec();
+    let rels = "<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+        <Relationship Id=\"rId911\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/package\" Target=\"embeddings/Microsoft_Excel_Worksheet.xlsx\"/>\
+        <Relationship Id=\"rId912\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/oleObject\" Target=\"embeddings/Travel_Policy.pdf\"/>\
+        </Relationships>";
+
+    let doc =
+        Docx::new()
+            .add_paragraph(Paragraph::new().add_run(Run::new().add_text("Travel Budget").bold()))
+            .add_paragraph(Paragraph::new().add_run(Run::new().add_text(
+                "The budget spreadsheet and the travel policy are attached to this memo.",
+            )));
+
+    let path = "tests/fixtures/attachments.docx";
+    pack_with_replacements(
+        doc,
+        path,
+        &[],
+        &[
+            ("word/embeddings/Microsoft_Excel_Worksheet.xlsx", workbook),
+            ("word/embeddings/Travel_Policy.pdf", pdf),
+            ("word/_rels/document.xml.rels", rels.as_bytes().to_vec()),
+        ],
+    )?;
+    println!("Generated: {path}");
+    Ok(())
+}
+
+/// Write `doc` to `path` with each placeholder run swapped for raw XML that
+/// docx-rs can't produce, and `extra_parts` added to the package
+fn pack_with_replacements<T: AsRef<[u8]>>(
+    doc: Docx,
+    path: &str,
+    replacements: &[(&str, String)],
+    extra_parts: &[(&str, T)],
+) -> Result<(), DynError> {
+    let mut packed = std::io::Cursor::new(Vec::new());
+    doc.build().pack(&mut packed)?;
+
+    let mut source = zip::ZipArchive::new(packed)?;
+    let mut target = zip::ZipWriter::new(File::create(path)?);
+    for index in 0..source.len() {
+        let mut entry = source.by_index(index)?;
+        let mut data = String::new();
+        std::io::Read::read_to_string(&mut entry, &mut data)?;
+        if entry.name() == "word/document.xml" {
+            data = data.replacen(
+                "<w:document ",
+                "<w:document xmlns:m=\"http://schemas.openxmlformats.org/officeDocument/2006/math\" \
+                 xmlns:a=\"http://schemas.openxmlformats.org/drawingml/2006/main\" \
+                 xmlns:c=\"http://schemas.openxmlformats.org/drawingml/2006/chart\" ",
+                1,
+            );
+            for (placeholder, xml) in replacements {
+                let at = data.find(placeholder).ok_or("placeholder missing")?;
+                let start = data[..at].rfind("<w:r>").ok_or("run start missing")?;
+                let end = at + data[at..].find("</w:r>").ok_or("run end missing")? + 6;
+                data.replace_range(start..end, xml);
+            }
+        }
+        if entry.name() == "[Content_Types].xml" {
+            let overrides: String = extra_parts
+                .iter()
+                .filter(|(name, _)| !name.ends_with(".rels"))
+                .map(|(name, _)| {
+                    format!(
+                        "<Override PartName=\"/{name}\" ContentType=\"{}\"/>",
+                        content_type(name)
+                    )
+                })
+                .collect();
+            data = data.replace("</Types>", &format!("{overrides}</Types>"));
+        }
+        let added = extra_parts.iter().find(|(name, _)| *name == entry.name());
+        if let Some((_, xml)) = added.filter(|(name, _)| name.ends_with(".rels")) {
+            // Relationships are merged into the existing list
+            let xml = String::from_utf8_lossy(xml.as_ref());
+            let body = xml
+                .split_once("<Relationships")
+                .and_then(|(_, rest)| rest.split_once('>'))
+                .map_or("", |(_, rest)| rest.trim_end_matches("</Relationships>"));
+            data = data.replace("</Relationships>", &format!("{body}</Relationships>"));
+        }
+        target.start_file(entry.name(), zip::write::SimpleFileOptions::default())?;
+        std::io::Write::write_all(&mut target, data.as_bytes())?;
+    }
+    for (name, data) in extra_parts {
+        if source.by_name(name).is_err() {
+            target.start_file(*name, zip::write::SimpleFileOptions::default())?;
+            std::io::Write::write_all(&mut target, data.as_ref())?;
+        }
+    }
+    target.finish()?;
+    Ok(())
+}
+
+fn content_type(part: &str) -> &'static str {
+    if part.contains("/charts/") {
+        "application/vnd.openxmlformats-officedocument.drawingml.chart+xml"
+    } else if part.ends_with(".xlsx") {
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+    } else if part.ends_with(".pdf") {
+        "application/pdf"
+    } else {
+        "application/xml"
+    }
+}
+
+/// Two versions of a contract for `doxx diff`
+fn generate_contract_revision_docs() -> Result<(), DynError> {
+    let price_table = |rows: &[[&str; 3]]| {
+        Table::new(
+            rows.iter()
+                .map(|row| {
+                    TableRow::new(
+                        row.iter()
+                            .map(|cell| {
+                                TableCell::new().add_paragraph(
+                                    Paragraph::new().add_run(Run::new().add_text(*cell)),
+                                )
+                            })
+                            .collect(),
+                    )
+                })
+                .collect(),
+        )
+    };
+    let paragraph = |text: &str| Paragraph::new().add_run(Run::new().add_text(text));
+    let heading = |text: &str| Paragraph::new().add_run(Run::new().add_text(text).bold().size(28));
+
+    let v1 = Docx::new()
+        .add_paragraph(heading("Service Agreement"))
+        .add_paragraph(paragraph(
+            "This agreement is made between Acme Corp and the Client.",
+        ))
+        .add_paragraph(heading("Payment Terms"))
+        .add_paragraph(paragraph(
+            "Payment is due within 30 days of the invoice date.",
+        ))
+        .add_paragraph(paragraph(
+            "Late payments incur a fee at the discretion of the vendor.",
+        ))
+        .add_table(price_table(&[
+            ["Service", "Price", "Term"],
+            ["Support", "$100", "Monthly"],
+            ["Hosting", "$250", "Monthly"],
+        ]))
+        .add_paragraph(heading("Termination"))
+        .add_paragraph(paragraph(
+            "Either party may terminate with 60 days written notice.",
+        ));
+
+    let v2 = Docx::new()
+        .add_paragraph(heading("Service Agreement"))
+        .add_paragraph(paragraph(
+            "This agreement is made between Acme Corp and the Client.",
+        ))
+        .add_paragraph(heading("Payment Terms"))
+        .add_paragraph(paragraph(
+            "Payment is due within 45 days of the invoice date.",
+        ))
+        .add_table(price_table(&[
+            ["Service", "Price", "Term"],
+            ["Support", "$120", "Monthly"],
+            ["Hosting", "$250", "Monthly"],
+            ["Backups", "$40", "Monthly"],
+        ]))
+        .add_paragraph(heading("Termination"))
+        .add_paragraph(paragraph(
+            "Either party may terminate with 60 days written notice.",
+        ))
+        .add_paragraph(paragraph(
+            "Confidentiality obligations survive termination of this agreement.",
+        ));
+
+    for (path, doc) in [
+        ("tests/fixtures/contract-v1.docx", v1),
+        ("tests/fixtures/contract-v2.docx", v2),
+    ] {
+        let file = File::create(path)?;
+        doc.build().pack(file)?;
+        println!("Generated: {path}");
+    }
+    Ok(())
+}
+
+/// Two versions of a handbook with a reorganized outline, for `doxx diff --outline`
+fn generate_handbook_revision_docs() -> Result<(), DynError> {
+    let heading = |level: usize, text: &str| {
+        Paragraph::new()
+            .add_run(Run::new().add_text(text))
+            .style(&format!("Heading{level}"))
+    };
+    let paragraph = |text: &str| Paragraph::new().add_run(Run::new().add_text(text));
+    let overview = "This handbook describes how we work and what we expect of each other.";
+    let leave = "Employees accrue two days of paid leave per month of service.";
+    let expenses = "Expenses are reimbursed within 30 days when submitted with receipts.";
+    let remote = "Remote work is allowed up to three days a week with manager approval.";
+    let passwords = "Passwords must be at least 14 characters and never reused.";
+
+    let v1 = Docx::new()
+        .add_paragraph(heading(1, "Overview"))
+        .add_paragraph(paragraph(overview))
+        .add_paragraph(heading(1, "Policies"))
+        .add_paragraph(heading(2, "Leave"))
+        .add_paragraph(paragraph(leave))
+        .add_paragraph(heading(2, "Expenses"))
+        .add_paragraph(paragraph(expenses))
+        .add_paragraph(heading(2, "Remote Work"))
+        .add_paragraph(paragraph(remote))
+        .add_paragraph(heading(1, "Security"))
+        .add_paragraph(heading(2, "Passwords"))
+        .add_paragraph(paragraph(passwords))
+        .add_paragraph(heading(1, "Appendix"))
+        .add_paragraph(paragraph("Forms are available from the office manager."));
+
+    let v2 = Docx::new()
+        .add_paragraph(heading(1, "Introduction"))
+        .add_paragraph(paragraph(overview))
+        .add_paragraph(heading(1, "Policies"))
+        .add_paragraph(heading(2, "Leave"))
+        .add_paragraph(paragraph(leave))
+        .add_paragraph(heading(2, "Travel and Expenses"))
+        .add_paragraph(paragraph(expenses))
+        .add_paragraph(heading(1, "Security"))
+        .add_paragraph(heading(2, "Passwords"))
+        .add_paragraph(paragraph(passwords))
+        .add_paragraph(heading(2, "Remote Work"))
+        .add_paragraph(paragraph(remote))
+        .add_paragraph(heading(1, "Contacts"))
+        .add_paragraph(paragraph("Questions go to people@example.com."));
+
+    for (path, doc) in [
+        ("tests/fixtures/handbook-v1.docx", v1),
+        ("tests/fixtures/handbook-v2.docx", v2),
+    ] {
+        let file = File::create(path)?;
+        doc.build().pack(file)?;
+        println!("Generated: {path}");
+    }
+    Ok(())
+}
+
 fn generate_retro_gaming_guide() -> Result<(), DynError> {
     let doc = Docx::new()
         .add_paragraph(Paragraph::new().add_run(Run::new().add_text("🚀 RETRO GAMING GUIDE 🎮").bold().size(28)))
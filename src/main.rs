@@ -1,14 +1,28 @@
-use anyhow::Result;
-use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use anyhow::{Context, Result};
+use clap::{CommandFactory, Parser, Subcommand};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
-use doxx::ExportFormat;
-
-mod document;
-mod export;
-pub mod image_extractor;
-pub mod terminal_image;
-mod ui;
+use doxx_core::ai;
+use doxx_core::cache::DocumentCache;
+use doxx_core::color::ColorChoice;
+use doxx_core::config::Config;
+use doxx_core::convert::{collect_jobs, convert_documents};
+use doxx_core::diff::{colorize_unified, diff_files, format_diff, format_outline_diff, DiffFormat};
+use doxx_core::grep;
+use doxx_core::history::{format_history, HistoryFormat, HistoryStore};
+use doxx_core::object_extractor::{format_object_list, ObjectExtractor};
+use doxx_core::ocr;
+use doxx_core::report::{build_folder_report, format_report, ReportFormat};
+use doxx_core::rpc;
+use doxx_core::sandbox;
+use doxx_core::saved_report;
+use doxx_core::state::load_bookmarks;
+use doxx_core::tags::TagDatabase;
+use doxx_core::text_width::ellipsize;
+use doxx_core::{document, export, hygiene, image_extractor, json_export, pages};
+use doxx_core::{typography, ExportFormat};
+use doxx_tui::{i18n, render, terminal_image, ui};
 
 #[derive(Parser)]
 #[command(
@@ -26,25 +40,114 @@ struct Cli {
     #[arg(short, long)]
     outline: bool,
 
+    /// Print the outline in this format instead of opening the viewer
+    #[arg(long, value_enum, requires = "outline")]
+    format: Option<export::OutlineFormat>,
+
     /// Jump to specific page
     #[arg(short, long)]
     page: Option<usize>,
 
     /// Search and highlight term
-    #[arg(short, long)]
+    #[arg(short, long, group = "query")]
     search: Option<String>,
 
+    /// Search for the text on the clipboard, e.g. a phrase copied from an
+    /// email
+    #[arg(long, group = "query")]
+    search_clipboard: bool,
+
+    /// Search ignoring accents and tolerating small typos
+    #[arg(long)]
+    search_fuzzy: bool,
+
+    /// Search only under headings matching this path, e.g. "3. Risks" or
+    /// "Report > Risks"
+    #[arg(long = "in", value_name = "HEADING", requires = "query")]
+    search_in: Option<String>,
+
+    /// Search only in tables
+    #[arg(long, requires = "query", conflicts_with = "search_in")]
+    in_tables: bool,
+
+    /// Run a search saved as `[reports.<NAME>]` in the config over the
+    /// document, or every document in a folder
+    #[arg(long, value_name = "NAME")]
+    run_report: Option<String>,
+
     /// Export format
     #[arg(long, value_enum)]
     export: Option<ExportFormat>,
 
+    /// Export a single table, by number or title (with `--export csv`)
+    #[arg(long, value_name = "N|TITLE", requires = "export")]
+    table: Option<String>,
+
+    /// Export only these columns, in this order, by header or 1-based
+    /// number, e.g. `Name,Amount` (with `--export csv`)
+    #[arg(
+        long,
+        value_name = "COLUMN,...",
+        value_delimiter = ',',
+        requires = "export"
+    )]
+    columns: Vec<String>,
+
+    /// Add a row under each table's header naming its column types (with `--export csv`)
+    #[arg(long, requires = "export")]
+    csv_types: bool,
+
+    /// Override detected column types, e.g. `Price:currency,Date:date`.
+    /// Columns are named by header or 1-based number; types are text,
+    /// number, currency, percentage, date and boolean
+    #[arg(long, value_name = "COLUMN:TYPE,...")]
+    column_types: Option<String>,
+
+    /// List the document's tables with their size and title
+    #[arg(long)]
+    list_tables: bool,
+
+    /// Report macros, external templates and images, other linked content,
+    /// unresolved tracked changes and hidden text
+    #[arg(long)]
+    hygiene: bool,
+
+    /// Export only these pages, e.g. `3`, `2-5` or `4-`
+    #[arg(long, value_name = "RANGE", requires = "export")]
+    pages: Option<String>,
+
+    /// Export only the section under this heading, e.g. "Appendix B" or
+    /// "Report > Risks". The whole file is parsed, then everything outside
+    /// the section is dropped, including its images
+    #[arg(long, value_name = "HEADING", requires = "export")]
+    section: Option<String>,
+
+    /// Write the export to a file instead of printing it. With `--export csv`
+    /// a directory gets one file per table
+    #[arg(long, value_name = "PATH", requires = "export")]
+    output: Option<PathBuf>,
+
     /// Force interactive UI mode (bypass TTY detection)
     #[arg(long)]
     force_ui: bool,
 
-    /// Enable color support for text rendering
-    #[arg(long)]
-    color: bool,
+    /// Language of the viewer's messages, e.g. `es` (default: from the environment)
+    #[arg(long, value_name = "LANG")]
+    lang: Option<String>,
+
+    /// When to use color in the viewer, printed output and diffs: `auto`
+    /// (on terminals, following NO_COLOR and CLICOLOR_FORCE), `always` or `never`
+    #[arg(
+        long,
+        value_name = "WHEN",
+        value_enum,
+        default_value_t,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "always",
+        global = true
+    )]
+    color: ColorChoice,
 
     /// Display images inline in terminal (auto-detect capabilities)
     #[arg(long)]
@@ -58,68 +161,733 @@ struct Cli {
     #[arg(long)]
     extract_images: Option<PathBuf>,
 
-    /// Maximum image width in terminal columns (default: auto-detect)
-    #[arg(long, value_name = "COLS")]
+    /// With --extract-images, also write manifest.json listing each image's
+    /// original name, relationship ids, size, SHA-256 and place in the document
+    #[arg(long, requires = "extract_images")]
+    manifest: bool,
+
+    /// With --extract-images, replace files already in the directory instead
+    /// of numbering the new ones (image1-2.png)
+    #[arg(long, requires = "extract_images")]
+    overwrite: bool,
+
+    /// Read text in images with tesseract, for search and text exports
+    #[arg(long)]
+    ocr: bool,
+
+    /// Read a document of scanned pages: replace each page image with the
+    /// headings and paragraphs tesseract finds on it
+    #[arg(long, conflicts_with = "ocr")]
+    scanned: bool,
+
+    /// Tesseract language codes for --ocr and --scanned, e.g. eng or eng+deu
+    #[arg(long, value_name = "LANG")]
+    ocr_lang: Option<String>,
+
+    /// List files embedded in the document (spreadsheets, PDFs, OLE objects)
+    #[arg(long)]
+    attachments: bool,
+
+    /// Extract embedded files to a directory
+    #[arg(long, value_name = "DIR")]
+    extract_attachments: Option<PathBuf>,
+
+    /// Maximum image width in terminal columns (default: the terminal's width)
+    #[arg(long, value_name = "COLS", value_parser = clap::value_parser!(u32).range(1..))]
     image_width: Option<u32>,
 
-    /// Maximum image height in terminal rows (default: auto-detect)  
-    #[arg(long, value_name = "ROWS")]
+    /// Maximum image height in terminal rows (default: the terminal's height)
+    #[arg(long, value_name = "ROWS", value_parser = clap::value_parser!(u32).range(1..))]
     image_height: Option<u32>,
 
     /// Image scaling factor (0.1 to 2.0, default: 1.0)
-    #[arg(long, value_name = "SCALE")]
+    #[arg(long, value_name = "SCALE", value_parser = document::parse_image_scale)]
     image_scale: Option<f32>,
 
     /// Test terminal image capabilities
     #[arg(long)]
     debug_terminal: bool,
 
-    /// Configuration commands
+    /// Print the JSON Schema for `--export json` output
+    #[arg(long)]
+    json_schema: bool,
+
+    /// Print a man page for doxx (roff), for packagers
+    #[arg(long)]
+    generate_man: bool,
+
+    /// Serve JSON-RPC on stdin/stdout for editor integrations (load,
+    /// get_elements, search, export)
+    #[arg(long)]
+    rpc: bool,
+
+    /// Print parse warnings (skipped or unsupported content) to stderr
+    #[arg(long)]
+    warnings: bool,
+
+    /// Fail with an error on the first content that would be skipped
+    #[arg(long)]
+    strict: bool,
+
+    /// Parse the document in a separate, resource-limited process
+    /// (for untrusted files)
+    #[arg(long)]
+    sandbox: bool,
+
+    /// Parse the document and write it to stdout for a `--sandbox` parent
+    #[arg(long, hide = true)]
+    parse_worker: bool,
+
+    /// Parse the document even if `cache.enabled` is set and it is cached
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Start at the top instead of resuming the last reading position
+    #[arg(long)]
+    fresh: bool,
+
+    /// Deepest heading level shown in the outline (default: all)
+    #[arg(long, value_name = "LEVEL")]
+    outline_depth: Option<u8>,
+
+    /// Hide heading numbers in the outline
+    #[arg(long)]
+    no_heading_numbers: bool,
+
+    /// Don't add synthetic numbers (1, 1.1, ...) to unnumbered headings
+    #[arg(long)]
+    no_auto_numbering: bool,
+
+    /// Start headings at level 1 and close skipped levels, in the outline
+    /// and in exports
+    #[arg(long)]
+    normalize_heading_levels: bool,
+
+    /// Title to use instead of the document's own, in the viewer and exports
+    #[arg(long)]
+    title: Option<String>,
+
+    /// Author to use instead of the document's own
+    #[arg(long)]
+    author: Option<String>,
+
+    /// Date to use instead of the document's created and modified dates
+    /// (YYYY-MM-DD, or with a time as in 2024-05-01T09:30:00Z)
+    #[arg(long, value_parser = document::parse_date)]
+    date: Option<String>,
+
+    /// Outline for documents without headings: from bold lines (`bold`),
+    /// page starts (`pages`) or every N paragraphs (e.g. `10`)
+    #[arg(long, value_name = "MODE")]
+    synthesize_outline: Option<document::OutlineSynthesis>,
+
+    /// Hard-wrap `--export text` output at this many columns (0 = don't
+    /// wrap, as with `export.wrap = 0` in the config)
+    #[arg(long, value_name = "COLS", conflicts_with = "no_wrap")]
+    wrap: Option<usize>,
+
+    /// Don't wrap `--export text` output, even if the config sets a width
+    #[arg(long)]
+    no_wrap: bool,
+
+    /// Break `--export text` tables longer than this many rows into pages,
+    /// each starting with the header row (0 = never)
+    #[arg(long, value_name = "ROWS")]
+    table_page_rows: Option<usize>,
+
+    /// How to show tracked changes: accept them, reject them, or mark them up
+    #[arg(long, value_enum, default_value = "accept")]
+    changes: document::TrackedChanges,
+
+    /// Convert curly quotes, dashes and ellipses to plain ASCII when exporting
+    #[arg(long, conflicts_with = "smart")]
+    normalize_typography: bool,
+
+    /// Convert straight quotes, `--` and `...` to typographic punctuation when exporting
+    #[arg(long)]
+    smart: bool,
+
+    /// Line endings for exports: `lf` or `crlf` (default: as exported)
+    #[arg(long, value_enum, value_name = "EOL")]
+    eol: Option<export::LineEnding>,
+
+    /// Remove spaces and tabs at the ends of lines in exports
+    #[arg(long)]
+    trim_trailing_whitespace: bool,
+
+    /// Repair double-encoded text ("mojibake"), e.g. `â€¢` back to `•` and
+    /// `Ã©` to `é`
+    #[arg(long)]
+    fix_encoding: bool,
+
+    /// Subcommands (configuration, tagging)
     #[command(subcommand)]
-    config: Option<ConfigCommands>,
+    command: Option<Commands>,
+}
+
+impl Cli {
+    /// Outline depth and numbering from the command line, falling back to the config
+    fn outline_options(&self, config: &Config) -> document::OutlineOptions {
+        document::OutlineOptions {
+            max_depth: self
+                .outline_depth
+                .or((config.outline.max_depth > 0).then_some(config.outline.max_depth)),
+            show_numbers: config.outline.show_numbers && !self.no_heading_numbers,
+            synthesize: self
+                .synthesize_outline
+                .or_else(|| config.outline.synthesize.parse().ok()),
+        }
+    }
+
+    /// Line endings and whitespace of exported text, from `--eol` and
+    /// `--trim-trailing-whitespace`
+    fn output_options(&self) -> export::OutputOptions {
+        export::OutputOptions {
+            eol: self.eol,
+            trim_trailing_whitespace: self.trim_trailing_whitespace,
+        }
+    }
+
+    /// Part of the document `--search` looks in
+    fn search_scope(&self) -> document::SearchScope {
+        search_scope(self.search_in.as_deref(), self.in_tables)
+    }
+
+    /// Whether the document is shown in the viewer rather than reported on,
+    /// exported or extracted from
+    fn opens_viewer(&self) -> bool {
+        !self.parse_worker
+            && !self.hygiene
+            && !self.list_tables
+            && !self.attachments
+            && self.extract_attachments.is_none()
+            && self.format.is_none()
+            && self.extract_images.is_none()
+            && self.export.is_none()
+    }
+
+    /// How the viewer starts, from the command line and the config
+    fn viewer_options(
+        &self,
+        config: &Config,
+        source: ui::DocumentSource,
+    ) -> Result<ui::ViewerOptions> {
+        let color = self
+            .color
+            .enabled(self.force_ui || std::io::IsTerminal::is_terminal(&std::io::stdout()));
+        let search = if self.search_clipboard {
+            Some(ui::read_clipboard_query()?)
+        } else {
+            self.search.clone()
+        };
+        Ok(ui::ViewerOptions {
+            color,
+            monochrome: !color,
+            force_ui: self.force_ui,
+            fresh: self.fresh,
+            images: self.images,
+            outline: self.outline,
+            outline_options: self.outline_options(config),
+            page: self.page,
+            search,
+            search_fuzzy: self.search_fuzzy,
+            search_scope: self.search_scope(),
+            warnings: self.warnings,
+            changes: self.changes,
+            source: Some(source),
+        })
+    }
+
+    /// How documents are parsed, from the command line and the config
+    fn parse_options(&self, config: &Config) -> document::ParseOptions {
+        document::ParseOptions {
+            auto_number_headings: config.outline.auto_number && !self.no_auto_numbering,
+            changes: self.changes,
+            elements_per_page: config.pages.elements_per_page,
+            strict: self.strict,
+            normalize_heading_levels: self.normalize_heading_levels,
+            overrides: self.metadata_overrides(),
+            limits: config.limits.clone(),
+            fix_encoding: self.fix_encoding,
+            section: self.section.clone(),
+            ..Default::default()
+        }
+    }
+
+    fn metadata_overrides(&self) -> document::MetadataOverrides {
+        document::MetadataOverrides {
+            title: self.title.clone(),
+            author: self.author.clone(),
+            date: self.date.clone(),
+        }
+    }
 }
 
 #[derive(Subcommand)]
-enum ConfigCommands {
+enum Commands {
     /// Set configuration value
     Set { key: String, value: String },
     /// Get configuration value
     Get { key: String },
     /// Initialize configuration
     Init,
+    /// Add tags (and optionally a note) to a document
+    Tag {
+        /// Document to tag
+        file: PathBuf,
+        /// Tags to add
+        tags: Vec<String>,
+        /// Attach a free-form note to the document
+        #[arg(long)]
+        note: Option<String>,
+    },
+    /// Remove tags from a document
+    Untag {
+        /// Document to untag
+        file: PathBuf,
+        /// Tags to remove
+        tags: Vec<String>,
+    },
+    /// List tagged documents, optionally filtered by tags
+    Tags {
+        /// Only show documents carrying all of these tags
+        filter: Vec<String>,
+    },
+    /// Compare two documents: added, removed and changed paragraphs and table cells
+    Diff {
+        /// Original document
+        old: PathBuf,
+        /// Revised document
+        new: PathBuf,
+        /// Print the diff in this format instead of opening the viewer
+        #[arg(long, value_enum)]
+        format: Option<DiffFormat>,
+        /// Only compare the heading trees: sections added, removed, renamed
+        /// or moved
+        #[arg(long)]
+        outline: bool,
+    },
+    /// Convert many documents at once, mirroring their folders under the output directory
+    Convert {
+        /// Documents, folders (searched recursively) or glob patterns such as `docs/**/*.docx`
+        #[arg(required = true, value_name = "FILE|DIR|GLOB")]
+        inputs: Vec<String>,
+        /// Output format
+        #[arg(long, value_enum, default_value = "markdown")]
+        export: ExportFormat,
+        /// Directory to write the converted files into
+        #[arg(long, value_name = "DIR")]
+        output_dir: PathBuf,
+        /// Documents converted at the same time (default: number of CPUs)
+        #[arg(long, value_name = "N")]
+        jobs: Option<usize>,
+    },
+    /// Search documents for text, printing `file:page:section: text` for each match
+    Grep {
+        /// Text to search for (case-insensitive)
+        pattern: String,
+        /// Documents, folders (searched recursively) or glob patterns such as `docs/**/*.docx`
+        #[arg(required = true, value_name = "FILE|DIR|GLOB")]
+        inputs: Vec<String>,
+        /// Ignore accents and tolerate small typos in each word
+        #[arg(long)]
+        fuzzy: bool,
+        /// Search only under headings matching this path, e.g. "3. Risks"
+        #[arg(long = "in", value_name = "HEADING")]
+        search_in: Option<String>,
+        /// Search only in tables
+        #[arg(long, conflicts_with = "search_in")]
+        in_tables: bool,
+        /// Print the matches as a JSON array
+        #[arg(long)]
+        json: bool,
+        /// Documents searched at the same time (default: number of CPUs)
+        #[arg(long, value_name = "N")]
+        jobs: Option<usize>,
+    },
+    /// Summarize every document in a folder (title, author, words, headings)
+    Report {
+        /// Folder to scan recursively for .docx files
+        dir: PathBuf,
+        /// Report format
+        #[arg(long, value_enum, default_value = "markdown")]
+        format: ReportFormat,
+    },
+    /// Time spent reading each document, recorded when `history.enabled` is set
+    History {
+        /// Show this document's sessions and the time spent per section
+        file: Option<PathBuf>,
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: HistoryFormat,
+    },
+    /// Print shell completions, e.g. `doxx completions zsh > _doxx`
+    Completions {
+        /// Shell to complete for
+        shell: clap_complete::Shell,
+    },
+    /// Summarize a document with a language model (settings under `ai.`)
+    Summarize {
+        /// Document to summarize (default: the FILE before the command)
+        file: Option<PathBuf>,
+    },
+    /// Answer a question about a document with a language model, citing
+    /// the elements the answer comes from (settings under `ai.`)
+    Ask {
+        /// Question to answer, e.g. "What are the payment terms?"
+        question: String,
+        /// Document to ask about (default: the FILE before the command)
+        file: Option<PathBuf>,
+    },
+    /// Manage the parsed document cache (`cache.enabled`)
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
+#[derive(Subcommand)]
+enum CacheAction {
+    /// Remove every cached document
+    Clear,
+}
+
+fn main() -> Result<()> {
     let cli = Cli::parse();
+    // The sandboxed parser process runs under an address space limit that a
+    // worker thread per core would eat into before parsing starts
+    let mut runtime = if cli.parse_worker {
+        tokio::runtime::Builder::new_current_thread()
+    } else {
+        tokio::runtime::Builder::new_multi_thread()
+    };
+    runtime.enable_all().build()?.block_on(run(cli))
+}
 
+async fn run(cli: Cli) -> Result<()> {
     // Handle debug terminal command
     if cli.debug_terminal {
         use terminal_image::TerminalImageRenderer;
-        let renderer = TerminalImageRenderer::new();
+        let renderer =
+            TerminalImageRenderer::with_options(cli.image_width, cli.image_height, cli.image_scale);
         renderer.print_capabilities();
         return Ok(());
     }
 
-    match &cli.config {
-        Some(ConfigCommands::Init) => {
-            println!("Initializing doxx configuration...");
-            // TODO: Initialize config file
+    if cli.json_schema {
+        print!("{}", json_export::JSON_SCHEMA);
+        return Ok(());
+    }
+
+    if cli.generate_man {
+        clap_mangen::Man::new(Cli::command()).render(&mut std::io::stdout())?;
+        return Ok(());
+    }
+
+    match &cli.command {
+        Some(Commands::Init) => {
+            let path = Config::default_path();
+            if path.exists() {
+                println!("Config already exists: {}", path.display());
+            } else {
+                Config::default().save(&path)?;
+                println!("Created {}", path.display());
+            }
+            return Ok(());
+        }
+        Some(Commands::Set { key, value }) => {
+            let path = Config::default_path();
+            let mut config = Config::load(&path)?;
+            config.set(key, value)?;
+            config.save(&path)?;
+            println!("{key} = {}", config.get(key)?);
+            return Ok(());
+        }
+        Some(Commands::Get { key }) => {
+            println!("{}", Config::load_default()?.get(key)?);
+            return Ok(());
+        }
+        Some(Commands::Tag { file, tags, note }) => {
+            if !file.exists() {
+                anyhow::bail!("File not found: {}", file.display());
+            }
+            let mut db = TagDatabase::open_default()?;
+            db.add_tags(file, tags);
+            if note.is_some() {
+                db.set_note(file, note.clone());
+            }
+            db.save()?;
+            print_tagged_document(&db, file);
+            return Ok(());
+        }
+        Some(Commands::Untag { file, tags }) => {
+            let mut db = TagDatabase::open_default()?;
+            db.remove_tags(file, tags);
+            db.save()?;
+            print_tagged_document(&db, file);
+            return Ok(());
+        }
+        Some(Commands::Tags { filter }) => {
+            let db = TagDatabase::open_default()?;
+            let documents = db.filter(filter);
+            if documents.is_empty() {
+                println!("No tagged documents found.");
+            } else {
+                for (path, entry) in documents {
+                    let tags: Vec<&str> = entry.tags.iter().map(String::as_str).collect();
+                    println!("{path}  [{}]", tags.join(", "));
+                    if let Some(note) = &entry.note {
+                        println!("    📝 {note}");
+                    }
+                }
+            }
+            if filter.is_empty() {
+                let counts = db.tag_counts();
+                if !counts.is_empty() {
+                    println!();
+                    println!("Tags:");
+                    for (tag, count) in counts {
+                        println!("  {tag} ({count})");
+                    }
+                }
+            }
+            return Ok(());
+        }
+        Some(Commands::Diff {
+            old,
+            new,
+            format,
+            outline,
+        }) => {
+            for path in [old, new] {
+                if !path.exists() {
+                    anyhow::bail!("File not found: {}", path.display());
+                }
+            }
+            let diff = diff_files(old, new).await?;
+            let terminal = std::io::IsTerminal::is_terminal(&std::io::stdout());
+            if *outline {
+                let format = format.unwrap_or_default();
+                let output = format_outline_diff(&diff, format)?;
+                match format {
+                    DiffFormat::Unified if cli.color.enabled(terminal) => {
+                        print!("{}", colorize_unified(&output))
+                    }
+                    _ => print!("{output}"),
+                }
+                return Ok(());
+            }
+            match format {
+                None if cli.force_ui || terminal => {
+                    ui::run_diff_viewer(&diff, !cli.color.enabled(true))?
+                }
+                None | Some(DiffFormat::Unified) if cli.color.enabled(terminal) => {
+                    print!(
+                        "{}",
+                        colorize_unified(&format_diff(&diff, DiffFormat::Unified)?)
+                    )
+                }
+                _ => print!("{}", format_diff(&diff, format.unwrap_or_default())?),
+            }
+            return Ok(());
+        }
+        Some(Commands::Convert {
+            inputs,
+            export,
+            output_dir,
+            jobs,
+        }) => {
+            if matches!(export, ExportFormat::Ansi) {
+                anyhow::bail!("doxx convert can't export ANSI; use doxx FILE --export ansi");
+            }
+            let config = Config::load_default()?;
+            let parse_options = cli.parse_options(&config);
+            let parallelism = jobs.unwrap_or_else(|| {
+                std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get)
+            });
+            let conversions = collect_jobs(inputs, export)?;
+            let total = conversions.len();
+            let outcomes = convert_documents(
+                conversions,
+                export.clone(),
+                output_dir,
+                parse_options,
+                cli.output_options(),
+                parallelism,
+            )
+            .await;
+
+            let mut failed = 0;
+            for outcome in &outcomes {
+                match &outcome.result {
+                    Ok(output) => println!("✓ {} → {}", outcome.input.display(), output.display()),
+                    Err(e) => {
+                        failed += 1;
+                        eprintln!("✗ {}: {e:#}", outcome.input.display());
+                    }
+                }
+            }
+            println!("Converted {} of {total} documents", total - failed);
+            if failed > 0 {
+                anyhow::bail!("{failed} of {total} documents failed to convert");
+            }
+            return Ok(());
+        }
+        Some(Commands::Grep {
+            pattern,
+            inputs,
+            fuzzy,
+            search_in,
+            in_tables,
+            json,
+            jobs,
+        }) => {
+            let config = Config::load_default()?;
+            let files = grep::collect_files(inputs)?;
+            let parallelism = jobs.unwrap_or_else(|| {
+                std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get)
+            });
+            let options = document::SearchOptions {
+                fuzzy: *fuzzy,
+                scope: search_scope(search_in.as_deref(), *in_tables),
+                ..Default::default()
+            };
+            let outcomes = grep::grep_files(
+                files,
+                pattern,
+                options,
+                cli.parse_options(&config),
+                parallelism,
+            )
+            .await;
+
+            // Like grep: 0 when something matched, 1 when nothing did, 2 on errors
+            let color = cli
+                .color
+                .enabled(std::io::IsTerminal::is_terminal(&std::io::stdout()));
+            let mut matches = Vec::new();
+            let mut failed = false;
+            for outcome in outcomes {
+                match outcome.result {
+                    Ok(found) => matches.extend(found),
+                    Err(e) => {
+                        failed = true;
+                        eprintln!("doxx grep: {}: {e:#}", outcome.file.display());
+                    }
+                }
+            }
+            if *json {
+                println!("{}", grep::matches_to_json(&matches)?);
+            } else {
+                for found in &matches {
+                    println!("{}", grep::format_match(found, color));
+                }
+            }
+            let code = match (failed, matches.is_empty()) {
+                (true, _) => 2,
+                (false, true) => 1,
+                (false, false) => 0,
+            };
+            std::process::exit(code);
+        }
+        Some(Commands::Report { dir, format }) => {
+            let report = build_folder_report(dir).await?;
+            print!("{}", format_report(&report, *format)?);
+            return Ok(());
+        }
+        Some(Commands::History { file, format }) => {
+            let config = Config::load_default()?;
+            let store = HistoryStore::open_default()?;
+            print!("{}", format_history(&store, file.as_deref(), *format)?);
+            if !config.history.enabled && matches!(format, HistoryFormat::Text) {
+                println!("\nRecording is off; turn it on with `doxx set history.enabled true`.");
+            }
+            return Ok(());
+        }
+        Some(Commands::Completions { shell }) => {
+            clap_complete::generate(*shell, &mut Cli::command(), "doxx", &mut std::io::stdout());
             return Ok(());
         }
-        Some(ConfigCommands::Set { key, value }) => {
-            println!("Setting {key} = {value}");
-            // TODO: Set config value
+        Some(Commands::Cache {
+            action: CacheAction::Clear,
+        }) => {
+            let cache = DocumentCache::open_default();
+            let removed = cache.clear()?;
+            println!(
+                "Removed {removed} cached document{} from {}",
+                if removed == 1 { "" } else { "s" },
+                DocumentCache::default_dir().display()
+            );
             return Ok(());
         }
-        Some(ConfigCommands::Get { key }) => {
-            println!("Getting {key}");
-            // TODO: Get config value
+        Some(Commands::Summarize { file }) => {
+            let file = file
+                .as_ref()
+                .or(cli.file.as_ref())
+                .ok_or_else(|| anyhow::anyhow!("Please provide a document file to summarize"))?;
+            if !file.exists() {
+                anyhow::bail!("File not found: {}", file.display());
+            }
+            let config = Config::load_default()?;
+            let client = ai::AiClient::new(&config.ai)?;
+            let document =
+                document::load_document(file, Default::default(), cli.parse_options(&config))
+                    .await?;
+            let text = export::format_range_as_markdown(&document, 0..document.elements.len());
+            let mut stdout = std::io::stdout();
+            client.summarize(
+                &text,
+                |part, parts| eprintln!("Summarizing part {part} of {parts}…"),
+                |piece| {
+                    print!("{piece}");
+                    let _ = stdout.flush();
+                },
+            )?;
+            println!();
+            return Ok(());
+        }
+        Some(Commands::Ask { question, file }) => {
+            let file = file
+                .as_ref()
+                .or(cli.file.as_ref())
+                .ok_or_else(|| anyhow::anyhow!("Please provide a document file to ask about"))?;
+            if !file.exists() {
+                anyhow::bail!("File not found: {}", file.display());
+            }
+            let config = Config::load_default()?;
+            let client = ai::AiClient::new(&config.ai)?;
+            let document =
+                document::load_document(file, Default::default(), cli.parse_options(&config))
+                    .await?;
+            let mut stdout = std::io::stdout();
+            let answer = client.answer_question(&document, question, |piece| {
+                print!("{piece}");
+                let _ = stdout.flush();
+            })?;
+            println!();
+            if !answer.citations.is_empty() {
+                println!("\nSources:");
+            }
+            for &element in &answer.citations {
+                let text = export::format_range_as_text(&document, element..element + 1);
+                let first_line = text.lines().next().unwrap_or_default();
+                println!("  [{}] {}", element + 1, ellipsize(first_line, 72));
+            }
             return Ok(());
         }
         None => {}
     }
 
+    if cli.rpc {
+        let config = Config::load_default()?;
+        let server = rpc::RpcServer::new(cli.parse_options(&config));
+        return Ok(rpc::serve(
+            server,
+            std::io::stdin().lock(),
+            std::io::stdout().lock(),
+        )?);
+    }
+
     let file_path = cli
         .file
         .clone()
@@ -129,44 +897,404 @@ async fn main() -> Result<()> {
         anyhow::bail!("File not found: {}", file_path.display());
     }
 
-    let image_options = document::ImageOptions {
+    if let Some(name) = &cli.run_report {
+        let config = Config::load_default()?;
+        let definition = config.reports.get(name).ok_or_else(|| {
+            let names: Vec<&str> = config.reports.keys().map(String::as_str).collect();
+            anyhow::anyhow!(
+                "No report named {name} in {} (saved reports: {})",
+                Config::default_path().display(),
+                if names.is_empty() {
+                    "none".to_string()
+                } else {
+                    names.join(", ")
+                }
+            )
+        })?;
+        let files = grep::collect_files(&[file_path.to_string_lossy().to_string()])?;
+        let parallelism =
+            std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get);
+        let report = saved_report::run_report(
+            name,
+            definition,
+            files,
+            cli.parse_options(&config),
+            parallelism,
+        )
+        .await?;
+        print!(
+            "{}",
+            saved_report::format_search_report(&report, definition.format)?
+        );
+        return Ok(());
+    }
+
+    let ocr_options = ocr::OcrOptions {
+        language: cli.ocr_lang.clone(),
+        ..Default::default()
+    };
+    let ocr = cli.ocr || cli.scanned;
+    if ocr {
+        ocr::check_available(&ocr_options)?;
+    }
+    let column_types = match &cli.column_types {
+        Some(spec) => document::parse_column_types(spec)
+            .map_err(|e| anyhow::anyhow!("Invalid --column-types: {e}"))?,
+        None => Vec::new(),
+    };
+    let preparation = ui::Preparation {
+        column_types,
+        ocr: ocr.then_some(ui::OcrPass {
+            options: ocr_options,
+            scanned: cli.scanned,
+            show_images: cli.images,
+        }),
+    };
+    // OCR reads the extracted image files
+    let image_options = preparation.image_options(&document::ImageOptions {
         enabled: cli.images,
         max_width: cli.image_width,
         max_height: cli.image_height,
         scale: cli.image_scale,
+    });
+    let config = Config::load_default()?;
+    let parse_options = cli.parse_options(&config);
+    let cached = config.cache.enabled && !cli.no_cache;
+
+    // The viewer opens right away and shows the document as it loads; OCR,
+    // a sandboxed parse and --warnings print to the terminal first
+    let interactive = cli.force_ui || std::io::IsTerminal::is_terminal(&std::io::stdout());
+    if cli.opens_viewer() && interactive && !ocr && !cli.sandbox && !cli.warnings {
+        install_localizer(&cli, &config);
+        let renderers = renderer_registry(&config);
+        let source = ui::DocumentSource {
+            parser: ui::DocumentParser::File(parse_options.clone()),
+            preparation: preparation.clone(),
+        };
+        let options = cli.viewer_options(&config, source)?;
+        let search_in = cli.search_in.clone();
+        let path = file_path.clone();
+        let load = move |parse_options| async move {
+            let mut document = parse_file(&path, image_options, parse_options, cached).await?;
+            prepare_document(&mut document, &path, &preparation, search_in.as_deref())?;
+            Ok(document)
+        };
+        return ui::run_viewer_while_loading(
+            &file_path,
+            parse_options,
+            load,
+            &options,
+            &config,
+            renderers,
+        )
+        .await;
+    }
+
+    // The viewer parses the file the same way again after it is edited
+    let (mut document, parser) = if cli.sandbox {
+        let worker = parse_worker_command()?;
+        let parser = ui::DocumentParser::Worker {
+            program: worker.get_program().into(),
+            args: worker.get_args().map(Into::into).collect(),
+            limits: config.limits.clone(),
+        };
+        let mut document = sandbox::load_in_worker(worker, &config.limits)?;
+        document.image_options = image_options;
+        (document, parser)
+    } else {
+        let parser = ui::DocumentParser::File(parse_options.clone());
+        let document = parse_file(&file_path, image_options, parse_options, cached).await?;
+        (document, parser)
+    };
+    // A sandboxed parse runs OCR in the worker, which gets --ocr too
+    if !cli.sandbox {
+        for warning in preparation.recognize(&mut document) {
+            eprintln!("warning: {warning}");
+        }
+    }
+    if cli.parse_worker {
+        return Ok(sandbox::write_parsed(document, std::io::stdout().lock())?);
+    }
+    prepare_document(
+        &mut document,
+        &file_path,
+        &preparation,
+        cli.search_in.as_deref(),
+    )?;
+    let source = ui::DocumentSource {
+        parser,
+        preparation,
     };
-    let document = document::load_document(&file_path, image_options).await?;
+
+    if cli.warnings {
+        for warning in &document.warnings {
+            eprintln!("warning: {warning}");
+        }
+    }
+
+    if cli.hygiene {
+        print!(
+            "{}",
+            hygiene::format_report(&document.hygiene, &file_path.display().to_string())
+        );
+        return Ok(());
+    }
+
+    if cli.list_tables {
+        let tables = export::document_tables(&document);
+        if tables.is_empty() {
+            println!("No tables found in document");
+        } else {
+            print!("{}", export::format_table_list(&tables));
+        }
+        return Ok(());
+    }
+
+    if cli.attachments || cli.extract_attachments.is_some() {
+        let extractor = ObjectExtractor::new(&file_path)?;
+        let objects = extractor.list_objects();
+        if objects.is_empty() {
+            println!("No embedded files found in document");
+        } else if let Some(extract_dir) = &cli.extract_attachments {
+            let written = extractor.extract_to(extract_dir, &config.limits)?;
+            for path in &written {
+                println!("Extracted: {}", path.display());
+            }
+            println!(
+                "Successfully extracted {} embedded files to {}",
+                written.len(),
+                extract_dir.display()
+            );
+        } else {
+            print!("{}", format_object_list(objects));
+        }
+        return Ok(());
+    }
+
+    if let Some(format) = cli.format {
+        let outline = document::generate_outline_with(&document, &cli.outline_options(&config));
+        print!("{}", export::format_outline(&document, &outline, format)?);
+        return Ok(());
+    }
 
     // Handle image extraction flag
     if let Some(extract_dir) = &cli.extract_images {
-        use image_extractor::ImageExtractor;
+        use image_extractor::{ExtractOptions, ImageExtractor};
 
         let mut extractor = ImageExtractor::new()?;
         extractor.extract_images_from_docx(&file_path)?;
 
-        // Copy extracted images to the specified directory
-        std::fs::create_dir_all(extract_dir)?;
-        for (rel_id, temp_path) in extractor.list_images() {
-            let target_path = extract_dir.join(rel_id);
-            std::fs::copy(temp_path, &target_path)?;
-            println!("Extracted: {}", target_path.display());
+        let options = ExtractOptions {
+            manifest: cli.manifest,
+            overwrite: cli.overwrite,
+        };
+        let manifest = extractor.extract_to(extract_dir, &options)?;
+        for image in &manifest.images {
+            println!("Extracted: {}", extract_dir.join(&image.file).display());
         }
 
         println!(
             "Successfully extracted {} images to {}",
-            extractor.list_images().len(),
+            manifest.images.len(),
             extract_dir.display()
         );
         return Ok(());
     }
 
     if let Some(export_format) = &cli.export {
-        export::export_document(&document, export_format)?;
+        use typography::{apply_typography, TypographyMode};
+
+        if let Some(range) = &cli.pages {
+            pages::select_pages(&mut document, &pages::parse_page_range(range)?)?;
+        }
+        if cli.normalize_typography {
+            apply_typography(&mut document, TypographyMode::Ascii);
+        } else if cli.smart {
+            apply_typography(&mut document, TypographyMode::Smart);
+        }
+        let is_csv = matches!(export_format, ExportFormat::Csv);
+        if cli.table.is_some() && !is_csv {
+            anyhow::bail!("--table is only supported with --export csv");
+        }
+        if cli.csv_types && !is_csv {
+            anyhow::bail!("--csv-types is only supported with --export csv");
+        }
+        if !cli.columns.is_empty() && !is_csv {
+            anyhow::bail!("--columns is only supported with --export csv");
+        }
+        let csv_options = export::CsvOptions {
+            types: cli.csv_types,
+            columns: cli.columns.clone(),
+        };
+        if let Some(output) = cli
+            .output
+            .as_deref()
+            .filter(|output| is_csv && export::is_directory_output(output))
+        {
+            for path in
+                export::write_csv_tables(&document, cli.table.as_deref(), output, &csv_options)?
+            {
+                println!("Wrote: {}", path.display());
+            }
+            return Ok(());
+        }
+
+        let content = match export_format {
+            ExportFormat::Ansi => {
+                install_localizer(&cli, &config);
+                // Meant to be viewed later with `less -R`, so styled unless
+                // color is turned off explicitly
+                let color = cli.color != ColorChoice::Never;
+                let options = ui::ViewerOptions {
+                    color,
+                    monochrome: !color,
+                    changes: cli.changes,
+                    ..Default::default()
+                };
+                let width = cli
+                    .wrap
+                    .or(Some(config.export.wrap))
+                    .filter(|width| *width > 0)
+                    .unwrap_or(80)
+                    .clamp(20, u16::MAX as usize) as u16;
+                let mut app = ui::App::new(document, &options, &config);
+                app.renderers = renderer_registry(&config);
+                app.render_ansi(width)
+            }
+            ExportFormat::Markdown => export::to_markdown(&document),
+            ExportFormat::Csv => export::csv_tables(&document, cli.table.as_deref(), &csv_options)?,
+            ExportFormat::Json => export::to_json(&document)?,
+            ExportFormat::Text => {
+                // 0 turns wrapping off, from the command line as in the config
+                let wrap = if cli.no_wrap {
+                    None
+                } else {
+                    cli.wrap
+                        .or(Some(config.export.wrap))
+                        .filter(|width| *width > 0)
+                };
+                let options = export::TextOptions {
+                    wrap,
+                    table_page_rows: cli
+                        .table_page_rows
+                        .or(Some(config.export.table_page_rows))
+                        .filter(|rows| *rows > 0),
+                };
+                if cli.output.is_none() && cli.output_options().is_default() {
+                    terminal_image::print_text_with_images(&document, &options);
+                    return Ok(());
+                }
+                export::to_text_with(&document, &options)
+            }
+        };
+
+        let content = cli.output_options().apply(&content).into_owned();
+        match &cli.output {
+            Some(_) if is_csv && content.is_empty() => anyhow::bail!("No tables found in document"),
+            Some(output) => {
+                std::fs::write(output, content)
+                    .with_context(|| format!("Failed to write {}", output.display()))?;
+                println!("Wrote: {}", output.display());
+            }
+            None if is_csv && content.is_empty() => println!("No tables found in document"),
+            None => print!("{content}"),
+        }
         return Ok(());
     }
 
     // Start terminal UI
-    ui::run_viewer(document, &cli).await?;
+    install_localizer(&cli, &config);
+    let renderers = renderer_registry(&config);
+    let options = cli.viewer_options(&config, source)?;
+    ui::run_viewer(document, &options, &config, renderers).await?;
 
     Ok(())
 }
+
+/// Set the viewer's language from `--lang`, the config or the environment
+fn install_localizer(cli: &Cli, config: &Config) {
+    let language = cli
+        .lang
+        .clone()
+        .or_else(|| Some(config.ui.language.clone()).filter(|language| !language.is_empty()))
+        .or_else(i18n::environment_language)
+        .unwrap_or_else(|| "en".to_string());
+    let localizer = i18n::Localizer::new(&language, Some(&i18n::user_catalog_dir()));
+    for warning in &localizer.warnings {
+        eprintln!("warning: {warning}");
+    }
+    i18n::install(localizer);
+}
+
+/// The element renderers `ui.renderers` switches on
+fn renderer_registry(config: &Config) -> render::RendererRegistry {
+    let (renderers, warnings) = render::RendererRegistry::with_builtins(&config.ui.renderers);
+    for warning in &warnings {
+        eprintln!("warning: {warning}");
+    }
+    renderers
+}
+
+/// Search scope from `--in` and `--in-tables`
+fn search_scope(search_in: Option<&str>, in_tables: bool) -> document::SearchScope {
+    match search_in {
+        Some(path) => document::SearchScope::Heading(path.to_string()),
+        None if in_tables => document::SearchScope::Tables,
+        None => document::SearchScope::All,
+    }
+}
+
+/// Parse `file_path` in this process, through the document cache when `cached`
+async fn parse_file(
+    file_path: &Path,
+    image_options: document::ImageOptions,
+    parse_options: document::ParseOptions,
+    cached: bool,
+) -> Result<document::Document> {
+    let loaded = if cached {
+        DocumentCache::open_default().load(file_path, image_options, parse_options)
+    } else {
+        document::load_document(file_path, image_options, parse_options).await
+    };
+    loaded.map_err(|e| anyhow::anyhow!("{}: {e}", file_path.display()))
+}
+
+/// Finish `preparation` of a parsed document and add its saved bookmarks,
+/// once `--in` is known to name one of its headings
+fn prepare_document(
+    document: &mut document::Document,
+    file_path: &Path,
+    preparation: &ui::Preparation,
+    search_in: Option<&str>,
+) -> Result<()> {
+    if let Some(path) = search_in {
+        if document::heading_sections(&document.elements, path).is_empty() {
+            anyhow::bail!("No heading matches --in \"{path}\"");
+        }
+    }
+    document.bookmarks = load_bookmarks(file_path);
+    preparation.finish(document);
+    Ok(())
+}
+
+/// This program with the same arguments, run as a `--sandbox` parser process
+fn parse_worker_command() -> Result<std::process::Command> {
+    let mut command = std::process::Command::new(std::env::current_exe()?);
+    command
+        .args(std::env::args_os().skip(1).filter(|arg| arg != "--sandbox"))
+        .arg("--parse-worker");
+    Ok(command)
+}
+
+fn print_tagged_document(db: &TagDatabase, file: &std::path::Path) {
+    match db.get(file) {
+        Some(entry) => {
+            let tags: Vec<&str> = entry.tags.iter().map(String::as_str).collect();
+            println!("{}: [{}]", file.display(), tags.join(", "));
+            if let Some(note) = &entry.note {
+                println!("📝 {note}");
+            }
+        }
+        None => println!("{}: no tags", file.display()),
+    }
+}
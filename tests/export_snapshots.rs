@@ -0,0 +1,101 @@
+/// Snapshot tests for the exporters
+///
+/// Every fixture is exported to Markdown, text, CSV and JSON and compared with
+/// the files under `tests/snapshots/`. After an intended change in the output,
+/// regenerate them with `UPDATE_SNAPSHOTS=1 cargo test --test export_snapshots`
+/// and review the diff.
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use doxx_core::document::Document;
+use doxx_core::export;
+
+const FIXTURES: &[&str] = &[
+    "business-report",
+    "export-test",
+    "headings-hierarchy",
+    "lists-comprehensive",
+    "tables-heavy",
+    "unicode-special",
+];
+
+fn snapshot_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots")
+}
+
+fn load(name: &str) -> Document {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(format!("{name}.docx"));
+    let data = fs::read(&path).unwrap();
+    Document::from_bytes(&data).unwrap()
+}
+
+/// Compare `actual` with the snapshot `file`, or write it when updating.
+/// Returns a description of the mismatch, if any.
+fn check(file: &str, actual: &str) -> Option<String> {
+    let path = snapshot_dir().join(file);
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(snapshot_dir()).unwrap();
+        fs::write(&path, actual).unwrap();
+        return None;
+    }
+
+    let expected = fs::read_to_string(&path).unwrap_or_default();
+    if expected == actual {
+        return None;
+    }
+    let line = expected
+        .lines()
+        .zip(actual.lines())
+        .position(|(expected, actual)| expected != actual)
+        .unwrap_or_else(|| expected.lines().count().min(actual.lines().count()));
+    Some(format!("{file}: differs from line {}", line + 1))
+}
+
+#[test]
+fn test_export_snapshots() {
+    let mut mismatches = Vec::new();
+    for name in FIXTURES {
+        let document = load(name);
+        let outputs = [
+            ("md", export::to_markdown(&document)),
+            ("txt", export::to_text(&document, None)),
+            ("csv", export::to_csv(&document)),
+            ("json", export::to_json(&document).unwrap()),
+        ];
+        for (extension, actual) in outputs {
+            mismatches.extend(check(&format!("{name}.{extension}"), &actual));
+        }
+    }
+
+    assert!(
+        mismatches.is_empty(),
+        "Export output changed (rerun with UPDATE_SNAPSHOTS=1 to accept):\n{}",
+        mismatches.join("\n")
+    );
+}
+
+#[test]
+fn test_wrapped_text_snapshot() {
+    let document = load("export-test");
+    let mismatch = check(
+        "export-test.wrap40.txt",
+        &export::to_text(&document, Some(40)),
+    );
+    assert!(mismatch.is_none(), "{}", mismatch.unwrap());
+}
+
+#[test]
+fn test_paged_tables_text_snapshot() {
+    let document = load("tables-heavy");
+    let options = export::TextOptions {
+        table_page_rows: Some(2),
+        ..Default::default()
+    };
+    let mismatch = check(
+        "tables-heavy.pages2.txt",
+        &export::to_text_with(&document, &options),
+    );
+    assert!(mismatch.is_none(), "{}", mismatch.unwrap());
+}
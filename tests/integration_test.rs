@@ -44,6 +44,330 @@ fn test_tables_csv_export() {
     );
 }
 
+#[test]
+fn test_completions_and_man_page() {
+    let output = Command::new("cargo")
+        .args(["run", "--bin", "doxx", "completions", "bash"])
+        .output()
+        .expect("Failed to execute doxx");
+    assert!(output.status.success(), "doxx should print completions");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("_doxx()"),
+        "Should define the bash function"
+    );
+    assert!(stdout.contains("--export"), "Should complete options");
+
+    let output = Command::new("cargo")
+        .args(["run", "--bin", "doxx", "--", "--generate-man"])
+        .output()
+        .expect("Failed to execute doxx");
+    assert!(output.status.success(), "doxx should print a man page");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with(".ie"), "Should be roff");
+    assert!(stdout.contains(".TH doxx 1"), "Should be a section 1 page");
+}
+
+#[test]
+fn test_grep_across_files() {
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--bin",
+            "doxx",
+            "grep",
+            "Q1 2024",
+            "tests/fixtures/tables-heavy.docx",
+            "tests/fixtures/minimal.docx",
+        ])
+        .output()
+        .expect("Failed to execute doxx");
+
+    assert!(output.status.success(), "doxx grep should find a match");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout.trim_end(),
+        "tests/fixtures/tables-heavy.docx:1:Financial Data Table: Q1 2024"
+    );
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--bin",
+            "doxx",
+            "grep",
+            "no such text anywhere",
+            "tests/fixtures/minimal.docx",
+        ])
+        .output()
+        .expect("Failed to execute doxx");
+    assert_eq!(output.status.code(), Some(1), "No match should exit with 1");
+}
+
+#[test]
+fn test_grep_in_scope() {
+    let grep = |scope: &[&str]| {
+        let mut args = vec![
+            "run",
+            "--bin",
+            "doxx",
+            "grep",
+            "revenue",
+            "tests/fixtures/business-report.docx",
+        ];
+        args.extend_from_slice(scope);
+        let output = Command::new("cargo")
+            .args(&args)
+            .output()
+            .expect("Failed to execute doxx");
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    let everywhere = grep(&[]).lines().count();
+    let section = grep(&["--in", "revenue analysis"]);
+    assert!(!section.is_empty());
+    assert!(section.lines().count() < everywhere);
+    assert!(section
+        .lines()
+        .all(|line| line.contains(":Revenue Analysis: ")));
+
+    let tables = grep(&["--in-tables"]);
+    assert_eq!(
+        tables.trim_end(),
+        "tests/fixtures/business-report.docx:1:Key Performance Indicators: Revenue"
+    );
+}
+
+#[test]
+fn test_csv_columns() {
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--bin",
+            "doxx",
+            "tests/fixtures/tables-heavy.docx",
+            "--export",
+            "csv",
+            "--columns",
+            "profit,1",
+        ])
+        .output()
+        .expect("Failed to execute doxx");
+
+    assert!(output.status.success(), "doxx should export chosen columns");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Profit,Quarter\n\"$30,000\",Q1 2024\n"),
+        "Should reorder the columns by name and number"
+    );
+    assert!(
+        !stdout.contains("Name,Age,City"),
+        "Should leave out tables without a Profit column"
+    );
+}
+
+#[test]
+fn test_csv_column_types() {
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--bin",
+            "doxx",
+            "tests/fixtures/tables-heavy.docx",
+            "--export",
+            "csv",
+            "--table",
+            "financial",
+            "--csv-types",
+            "--column-types",
+            "Revenue:text",
+        ])
+        .output()
+        .expect("Failed to execute doxx");
+
+    assert!(output.status.success(), "doxx should export column types");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let types = stdout.lines().nth(1).unwrap_or_default();
+    assert_eq!(
+        types, "text,text,currency,currency,percentage",
+        "Should list detected types, with the override applied"
+    );
+}
+
+#[test]
+fn test_tables_csv_single_table() {
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--bin",
+            "doxx",
+            "tests/fixtures/tables-heavy.docx",
+            "--list-tables",
+        ])
+        .output()
+        .expect("Failed to execute doxx");
+
+    assert!(output.status.success(), "doxx should list tables");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("2. Financial Data Table (4 rows × 5 columns)"),
+        "Should list each table with its size and title"
+    );
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--bin",
+            "doxx",
+            "tests/fixtures/tables-heavy.docx",
+            "--export",
+            "csv",
+            "--table",
+            "financial",
+        ])
+        .output()
+        .expect("Failed to execute doxx");
+
+    assert!(output.status.success(), "doxx should export one table");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.starts_with("Quarter,Revenue"),
+        "Should export table 2"
+    );
+    assert!(
+        !stdout.contains("Name,Age,City"),
+        "Should not include other tables"
+    );
+}
+
+#[test]
+fn test_export_to_output_file() {
+    let path = std::env::temp_dir().join(format!("doxx-export-{}.md", std::process::id()));
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--bin",
+            "doxx",
+            "tests/fixtures/headings-hierarchy.docx",
+            "--export",
+            "markdown",
+            "--output",
+        ])
+        .arg(&path)
+        .output()
+        .expect("Failed to execute doxx");
+
+    assert!(output.status.success(), "doxx should write the export");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.starts_with("Wrote: "),
+        "Should report the file written"
+    );
+    let markdown = std::fs::read_to_string(&path).unwrap();
+    assert!(
+        markdown.contains("## 1 Level 1: Introduction"),
+        "File should hold the Markdown export"
+    );
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_export_line_endings() {
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--bin",
+            "doxx",
+            "tests/fixtures/export-test.docx",
+            "--export",
+            "text",
+            "--eol",
+            "crlf",
+            "--trim-trailing-whitespace",
+        ])
+        .output()
+        .expect("Failed to execute doxx");
+    assert!(output.status.success());
+    let text = String::from_utf8(output.stdout).unwrap();
+
+    assert!(text.ends_with("\r\n"));
+    assert_eq!(text.matches('\n').count(), text.matches("\r\n").count());
+    assert!(text
+        .split("\r\n")
+        .all(|line| !line.ends_with(' ') && !line.ends_with('\t')));
+}
+
+#[test]
+fn test_image_size_flags() {
+    let run = |flags: &[&str]| {
+        Command::new("cargo")
+            .args([
+                "run",
+                "--bin",
+                "doxx",
+                "tests/fixtures/images.docx",
+                "--export",
+                "text",
+                "--images",
+            ])
+            .args(flags)
+            .output()
+            .expect("Failed to execute doxx")
+    };
+
+    let sized = run(&[
+        "--image-width",
+        "120",
+        "--image-height",
+        "40",
+        "--image-scale",
+        "0.5",
+    ]);
+    assert!(sized.status.success());
+
+    for flags in [
+        ["--image-scale", "3"],
+        ["--image-scale", "big"],
+        ["--image-width", "0"],
+    ] {
+        let output = run(&flags);
+        assert!(!output.status.success(), "{flags:?} should be rejected");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains(flags[0]), "{stderr}");
+    }
+}
+
+#[test]
+fn test_export_section() {
+    let run = |section: &str| {
+        Command::new("cargo")
+            .args([
+                "run",
+                "--bin",
+                "doxx",
+                "tests/fixtures/handbook-v1.docx",
+                "--export",
+                "markdown",
+                "--section",
+                section,
+            ])
+            .output()
+            .expect("Failed to execute doxx")
+    };
+
+    let output = run("Policies > Expenses");
+    assert!(output.status.success());
+    let markdown = String::from_utf8(output.stdout).unwrap();
+    assert!(markdown.contains("2.2 Expenses"));
+    assert!(markdown.contains("reimbursed within 30 days"));
+    assert!(!markdown.contains("Leave"));
+    assert!(!markdown.contains("Security"));
+
+    let output = run("Benefits");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("no heading matches \"Benefits\""));
+}
+
 #[test]
 fn test_headings_outline() {
     let output = Command::new("cargo")
@@ -153,6 +477,10 @@ fn test_export_test_json() {
     );
     let stdout = String::from_utf8_lossy(&output.stdout);
     assert!(stdout.contains("{"), "Should contain JSON output");
+    assert!(
+        stdout.contains("\"schema_version\": 2"),
+        "Should write the versioned JSON format"
+    );
 }
 
 #[test]
@@ -196,6 +524,127 @@ fn test_help_command() {
     );
 }
 
+#[test]
+fn test_tracked_changes_markup() {
+    let export = |changes: &str| {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "--bin",
+                "doxx",
+                "tests/fixtures/tracked-changes.docx",
+                "--export",
+                "markdown",
+                "--changes",
+                changes,
+            ])
+            .output()
+            .expect("Failed to execute doxx");
+        assert!(output.status.success(), "doxx should export with --changes");
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    assert!(export("accept").contains("due within 45 days"));
+    assert!(export("reject").contains("due within 30 days"));
+    let markup = export("markup");
+    assert!(
+        markup.contains("due within {--30--}{++45++} days"),
+        "Should mark up deletions and insertions"
+    );
+    assert!(markup.contains("{++This paragraph was added in review.++}"));
+}
+
+#[test]
+fn test_diff_unified() {
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--bin",
+            "doxx",
+            "diff",
+            "tests/fixtures/contract-v1.docx",
+            "tests/fixtures/contract-v2.docx",
+            "--format",
+            "unified",
+        ])
+        .output()
+        .expect("Failed to execute doxx");
+
+    assert!(output.status.success(), "doxx diff should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("- Payment is due within 30 days of the invoice date."));
+    assert!(stdout.contains("+ Payment is due within 45 days of the invoice date."));
+    assert!(stdout.contains("- Late payments incur a fee"));
+    assert!(stdout.contains("row 2, column 2: \"$100\" -> \"$120\""));
+    assert!(stdout.contains("+ Confidentiality obligations survive"));
+    assert!(
+        !stdout.contains("This agreement is made between"),
+        "Unchanged elements far from any change should be left out"
+    );
+}
+
+#[test]
+fn test_diff_outline() {
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--bin",
+            "doxx",
+            "diff",
+            "tests/fixtures/handbook-v1.docx",
+            "tests/fixtures/handbook-v2.docx",
+            "--outline",
+        ])
+        .output()
+        .expect("Failed to execute doxx");
+
+    assert!(
+        output.status.success(),
+        "doxx diff --outline should succeed"
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("~ Overview -> Introduction\n"));
+    assert!(stdout.contains("~ Policies > Expenses -> Policies > Travel and Expenses\n"));
+    assert!(stdout.contains("> Policies > Remote Work -> Security > Remote Work\n"));
+    assert!(stdout.contains("- Appendix\n"));
+    assert!(stdout.contains("+ Contacts\n"));
+    assert!(
+        !stdout.contains("Leave"),
+        "Sections that stayed put should be left out"
+    );
+}
+
+#[test]
+fn test_diff_color_policy() {
+    let diff = |color: &str, no_color: Option<&str>| {
+        let mut command = Command::new("cargo");
+        command.args([
+            "run",
+            "--bin",
+            "doxx",
+            "diff",
+            "tests/fixtures/contract-v1.docx",
+            "tests/fixtures/contract-v2.docx",
+            color,
+        ]);
+        command.env_remove("CLICOLOR_FORCE");
+        match no_color {
+            Some(value) => command.env("NO_COLOR", value),
+            None => command.env_remove("NO_COLOR"),
+        };
+        let output = command.output().expect("Failed to execute doxx");
+        assert!(output.status.success(), "doxx diff {color} should succeed");
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    let colored = diff("--color=always", Some("1"));
+    assert!(colored.contains("\x1b[31m- Payment is due within 30 days"));
+    assert!(colored.contains("\x1b[32m+ Payment is due within 45 days"));
+    // Output to a pipe is plain unless asked for
+    assert!(!diff("--color=auto", None).contains('\x1b'));
+    assert!(!diff("--color=never", None).contains('\x1b'));
+}
+
 #[test]
 fn test_all_fixtures_exist() {
     let fixtures = [
@@ -207,6 +656,14 @@ fn test_all_fixtures_exist() {
         "tests/fixtures/unicode-special.docx",
         "tests/fixtures/business-report.docx",
         "tests/fixtures/export-test.docx",
+        "tests/fixtures/tracked-changes.docx",
+        "tests/fixtures/contract-v1.docx",
+        "tests/fixtures/contract-v2.docx",
+        "tests/fixtures/handbook-v1.docx",
+        "tests/fixtures/handbook-v2.docx",
+        "tests/fixtures/paragraph-styles.docx",
+        "tests/fixtures/equations.docx",
+        "tests/fixtures/charts.docx",
     ];
 
     for fixture in &fixtures {
@@ -216,3 +673,493 @@ fn test_all_fixtures_exist() {
         );
     }
 }
+
+#[test]
+fn test_convert_folder() {
+    let input = std::env::temp_dir().join(format!("doxx-convert-input-{}", std::process::id()));
+    let output = std::env::temp_dir().join(format!("doxx-convert-output-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&input);
+    let _ = std::fs::remove_dir_all(&output);
+    std::fs::create_dir_all(input.join("nested")).unwrap();
+    std::fs::copy("tests/fixtures/minimal.docx", input.join("minimal.docx")).unwrap();
+    std::fs::copy(
+        "tests/fixtures/business-report.docx",
+        input.join("nested/business-report.docx"),
+    )
+    .unwrap();
+
+    let output_arg = output.to_string_lossy().to_string();
+    let run = |inputs: &[&str]| {
+        let mut args = vec!["run", "--bin", "doxx", "convert"];
+        args.extend_from_slice(inputs);
+        args.extend_from_slice(&["--export", "markdown", "--output-dir", &output_arg]);
+        Command::new("cargo")
+            .args(args)
+            .output()
+            .expect("Failed to execute doxx")
+    };
+
+    let input_arg = input.to_string_lossy().to_string();
+    let converted = run(&[&input_arg]);
+    assert!(
+        converted.status.success(),
+        "converting a folder should succeed"
+    );
+    let markdown = std::fs::read_to_string(output.join("nested/business-report.md")).unwrap();
+    assert!(markdown.contains("# business-report"));
+    assert!(output.join("minimal.md").exists());
+
+    // A broken document is reported and fails the run, but the rest still converts
+    std::fs::write(input.join("broken.docx"), "not a zip").unwrap();
+    let failed = run(&[&input_arg]);
+    assert!(
+        !failed.status.success(),
+        "a failed conversion should exit non-zero"
+    );
+    let stderr = String::from_utf8_lossy(&failed.stderr);
+    assert!(
+        stderr.contains("broken.docx"),
+        "should name the failed file"
+    );
+    let stdout = String::from_utf8_lossy(&failed.stdout);
+    assert!(stdout.contains("Converted 2 of 3 documents"));
+    let _ = std::fs::remove_dir_all(&input);
+    let _ = std::fs::remove_dir_all(&output);
+}
+
+#[test]
+fn test_export_page_range() {
+    let export = |pages: &str| {
+        Command::new("cargo")
+            .args([
+                "run",
+                "--bin",
+                "doxx",
+                "tests/fixtures/retro-gaming-guide.docx",
+                "--export",
+                "markdown",
+                "--pages",
+                pages,
+            ])
+            .output()
+            .expect("Failed to execute doxx")
+    };
+
+    // Word's saved layout puts the conclusion on the last of three pages
+    let last_page = export("3");
+    assert!(
+        last_page.status.success(),
+        "exporting a page range should succeed"
+    );
+    let markdown = String::from_utf8_lossy(&last_page.stdout);
+    assert!(markdown.contains("Conclusion: The Eternal Appeal"));
+    assert!(!markdown.contains("Certain games transcended entertainment"));
+
+    let past_end = export("4-");
+    assert!(
+        !past_end.status.success(),
+        "a page past the end should fail"
+    );
+    assert!(String::from_utf8_lossy(&past_end.stderr).contains("3 pages"));
+}
+
+#[test]
+fn test_outline_formats() {
+    let outline = |format: &str| {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "--bin",
+                "doxx",
+                "tests/fixtures/headings-hierarchy.docx",
+                "--outline",
+                "--format",
+                format,
+            ])
+            .output()
+            .expect("Failed to execute doxx");
+        assert!(output.status.success(), "--format {format} should succeed");
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    let opml = outline("opml");
+    assert!(opml.contains("<opml version=\"2.0\">"));
+    assert!(opml.contains("<outline text=\"1.1 Level 2: Getting Started\">"));
+
+    let json: serde_json::Value = serde_json::from_str(&outline("json")).unwrap();
+    let introduction = &json["headings"][0];
+    assert_eq!(introduction["title"], "1 Level 1: Introduction");
+    assert_eq!(
+        introduction["children"][0]["title"],
+        "1.1 Level 2: Getting Started"
+    );
+
+    assert!(outline("markdown").contains("\n  - 1.1 Level 2: Getting Started\n"));
+}
+
+#[test]
+fn test_paragraph_styles_export() {
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--bin",
+            "doxx",
+            "tests/fixtures/paragraph-styles.docx",
+            "--export",
+            "markdown",
+        ])
+        .output()
+        .expect("Failed to execute doxx");
+    assert!(output.status.success());
+    let markdown = String::from_utf8_lossy(&output.stdout);
+
+    assert!(markdown.contains("\n> It opens documents faster than my word processor.\n"));
+    assert!(markdown.contains("> **Back up your configuration before upgrading.**"));
+    // Consecutive code paragraphs, blank line included, form one fenced block
+    assert!(
+        markdown.contains("```\n[search]\nfuzzy = true\n\n[pages]\nelements_per_page = 40\n```")
+    );
+    assert!(markdown.contains("*Listing 1: A minimal configuration file*"));
+    // Fenced code keeps its language; monospace code has it guessed
+    assert!(markdown.contains("```python\ndef open_all(paths):\n"));
+    assert!(markdown.contains("```rust\nfn main() {\n"));
+    assert!(markdown.contains("# Release Notes\n\n*Version 2.0 highlights*"));
+    // The Title paragraph is shown as the title, not as a heading
+    assert!(markdown.contains("\n## 1 Overview\n"));
+}
+
+#[test]
+fn test_chart_data_export() {
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--bin",
+            "doxx",
+            "tests/fixtures/charts.docx",
+            "--export",
+            "csv",
+        ])
+        .output()
+        .expect("Failed to execute doxx");
+    assert!(output.status.success());
+    let csv = String::from_utf8_lossy(&output.stdout);
+
+    // Each chart becomes a table of its series, titled after the chart
+    assert!(csv.contains("# Chart 1: Revenue by Quarter\nCategory,2023,2024\nQ1,1.2,1.6\n"));
+    assert!(csv.contains("Q4,1.9,2.1"));
+    assert!(csv.contains("# Chart 2\nCategory,Active users\nJan,1200\n"));
+}
+
+#[test]
+fn test_image_alt_text_export() {
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--bin",
+            "doxx",
+            "tests/fixtures/images.docx",
+            "--export",
+            "markdown",
+        ])
+        .output()
+        .expect("Failed to execute doxx");
+    assert!(output.status.success());
+    let markdown = String::from_utf8_lossy(&output.stdout);
+
+    // Descriptions, or the title when there is no description
+    assert!(markdown.contains("![Milky way galaxy, under mostly clear night skies]("));
+    assert!(markdown.contains("![Photo of boulders on beach in bright sunshine]("));
+    assert!(!markdown.contains("![Image 1]"));
+}
+
+#[test]
+fn test_attachments_list_and_extract() {
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--bin",
+            "doxx",
+            "tests/fixtures/attachments.docx",
+            "--attachments",
+        ])
+        .output()
+        .expect("Failed to execute doxx");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("1. Microsoft_Excel_Worksheet.xlsx (Excel workbook, "));
+    assert!(stdout.contains("2. Travel_Policy.pdf (PDF document, 218 B)"));
+
+    let dir = std::env::temp_dir().join(format!("doxx-attachments-{}", std::process::id()));
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--bin",
+            "doxx",
+            "tests/fixtures/attachments.docx",
+            "--extract-attachments",
+        ])
+        .arg(&dir)
+        .output()
+        .expect("Failed to execute doxx");
+    assert!(output.status.success());
+    let pdf = std::fs::read(dir.join("Travel_Policy.pdf")).unwrap();
+    assert!(pdf.starts_with(b"%PDF-"));
+    assert!(dir.join("Microsoft_Excel_Worksheet.xlsx").exists());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_equations_export() {
+    let export = |format: &str| {
+        let output = Command::new("cargo")
+            .args([
+                "run",
+                "--bin",
+                "doxx",
+                "tests/fixtures/equations.docx",
+                "--export",
+                format,
+            ])
+            .output()
+            .expect("Failed to execute doxx");
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    // Markdown gets LaTeX: in line with the text, or as a display block
+    let markdown = export("markdown");
+    assert!(markdown.contains("The area of a circle is $A= \\pi r^{2}$ for a radius r."));
+    assert!(markdown.contains("$$\nx=\\frac{-b \\pm \\sqrt{b^{2}-4ac}}{2a}\n$$"));
+    assert!(markdown.contains("$$\n\\sum_{i=1}^{n} i=\\frac{n(n+1)}{2}\n$$"));
+
+    // Plain text gets a Unicode approximation
+    let text = export("text");
+    assert!(text.contains("The area of a circle is A=πr² for a radius r."));
+    assert!(text.contains("    x=(−b±√(b²−4ac))/2a"));
+    assert!(text.contains("    ∑ᵢ₌₁ⁿ i=(n(n+1))/2"));
+}
+
+#[test]
+fn test_strict_mode() {
+    let run = |file: &str| {
+        Command::new("cargo")
+            .args(["run", "--bin", "doxx", file, "--export", "text", "--strict"])
+            .output()
+            .expect("Failed to execute doxx")
+    };
+
+    // Documents doxx can show in full still export
+    assert!(run("tests/fixtures/minimal.docx").status.success());
+
+    // Anything that would be skipped is an error pointing at where it is
+    let output = run("tests/fixtures/images.docx");
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("word/document.xml: paragraph 10: unsupported drawing"));
+}
+
+#[test]
+fn test_wrap_zero_does_not_wrap() {
+    let export = |wrap: &str| {
+        let output = Command::new("cargo")
+            .args(["run", "--bin", "doxx", "tests/fixtures/example.docx"])
+            .args(["--export", "text", wrap])
+            .output()
+            .expect("Failed to execute doxx");
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    assert_eq!(export("--wrap=0"), export("--no-wrap"));
+    assert_ne!(export("--wrap=20"), export("--no-wrap"));
+}
+
+#[test]
+fn test_fix_encoding() {
+    let export = |extra: &[&str]| {
+        let output = Command::new("cargo")
+            .args(["run", "--bin", "doxx", "tests/fixtures/mojibake.docx"])
+            .args(["--export", "text", "--no-cache"])
+            .args(extra)
+            .output()
+            .expect("Failed to execute doxx");
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    assert!(export(&[]).contains("CafÃ© Menu â€“ Todayâ€™s Specials"));
+    let fixed = export(&["--fix-encoding"]);
+    assert!(fixed.contains("Café Menu – Today’s Specials"));
+    assert!(fixed.contains("• Crème brûlée for “dessert” at 5 €"));
+    assert!(fixed.contains("Plain text stays as it is: Straße, São Paulo."));
+}
+
+#[test]
+fn test_sandboxed_parsing() {
+    let run = |args: &[&str]| {
+        Command::new("cargo")
+            .args(["run", "--bin", "doxx"])
+            .args(args)
+            .output()
+            .expect("Failed to execute doxx")
+    };
+
+    // The parser process hands back the same document, pages included
+    let direct = run(&["tests/fixtures/business-report.docx", "--export", "text"]);
+    let sandboxed = run(&[
+        "tests/fixtures/business-report.docx",
+        "--export",
+        "text",
+        "--sandbox",
+    ]);
+    assert!(sandboxed.status.success());
+    assert_eq!(direct.stdout, sandboxed.stdout);
+    let pages = [
+        "tests/fixtures/business-report.docx",
+        "--export",
+        "text",
+        "--pages",
+        "2-",
+    ];
+    let paged = run(&[&pages[..], &["--sandbox"]].concat());
+    assert!(paged.status.success());
+    assert_eq!(run(&pages).stdout, paged.stdout);
+
+    // Its errors are reported as if parsed in process
+    let output = run(&[
+        "tests/fixtures/images.docx",
+        "--export",
+        "text",
+        "--strict",
+        "--sandbox",
+    ]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("paragraph 10: unsupported drawing"));
+}
+
+#[test]
+fn test_hygiene_report() {
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--bin",
+            "doxx",
+            "tests/fixtures/tracked-changes.docx",
+            "--hygiene",
+        ])
+        .output()
+        .expect("Failed to execute doxx");
+
+    assert!(output.status.success(), "doxx --hygiene should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("⚠ Unresolved tracked changes: 4"));
+    assert!(!stdout.contains("macros ("), "No macros in this document");
+}
+
+#[test]
+fn test_history_empty() {
+    let state = std::env::temp_dir().join(format!("doxx-history-{}", std::process::id()));
+    let output = Command::new("cargo")
+        .args(["run", "--bin", "doxx", "history"])
+        .env("XDG_STATE_HOME", &state)
+        .env("HOME", &state)
+        .output()
+        .expect("Failed to execute doxx");
+
+    assert!(output.status.success(), "doxx history should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("No reading history."),
+        "Should report an empty history"
+    );
+}
+
+#[test]
+fn test_cache_clear() {
+    let cache = std::env::temp_dir().join(format!("doxx-cache-{}", std::process::id()));
+    let output = Command::new("cargo")
+        .args(["run", "--bin", "doxx", "cache", "clear"])
+        .env("XDG_CACHE_HOME", &cache)
+        .env("HOME", &cache)
+        .output()
+        .expect("Failed to execute doxx");
+
+    assert!(output.status.success(), "doxx cache clear should succeed");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Removed 0 cached documents"),
+        "Should report an empty cache"
+    );
+}
+
+#[test]
+fn test_summarize_privacy_mode() {
+    let config = std::env::temp_dir().join(format!("doxx-ai-{}", std::process::id()));
+    std::fs::create_dir_all(config.join("doxx")).unwrap();
+    std::fs::write(
+        config.join("doxx").join("config.toml"),
+        "[ai]\nendpoint = \"http://models.example.com\"\n",
+    )
+    .unwrap();
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--bin",
+            "doxx",
+            "tests/fixtures/example.docx",
+            "summarize",
+        ])
+        .env("XDG_CONFIG_HOME", &config)
+        .env("HOME", &config)
+        .output()
+        .expect("Failed to execute doxx");
+    let _ = std::fs::remove_dir_all(&config);
+
+    assert!(
+        !output.status.success(),
+        "A remote endpoint should be refused in privacy mode"
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr
+            .contains("ai.privacy_mode is on and http://models.example.com is not on this machine"),
+        "Should explain the refusal: {stderr}"
+    );
+}
+
+#[test]
+fn test_ask_privacy_mode() {
+    let config = std::env::temp_dir().join(format!("doxx-ask-{}", std::process::id()));
+    std::fs::create_dir_all(config.join("doxx")).unwrap();
+    std::fs::write(
+        config.join("doxx").join("config.toml"),
+        "[ai]\nendpoint = \"http://models.example.com\"\n",
+    )
+    .unwrap();
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--bin",
+            "doxx",
+            "ask",
+            "What are the payment terms?",
+            "tests/fixtures/example.docx",
+        ])
+        .env("XDG_CONFIG_HOME", &config)
+        .env("HOME", &config)
+        .output()
+        .expect("Failed to execute doxx");
+    let _ = std::fs::remove_dir_all(&config);
+
+    assert!(
+        !output.status.success(),
+        "A remote endpoint should be refused in privacy mode"
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("ai.privacy_mode is on"),
+        "Should explain the refusal: {stderr}"
+    );
+}
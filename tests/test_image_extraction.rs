@@ -1,4 +1,4 @@
-use doxx::image_extractor::ImageExtractor;
+use doxx_core::image_extractor::ImageExtractor;
 use std::path::Path;
 
 #[test]
@@ -37,7 +37,7 @@ fn test_image_extraction_from_images_docx() {
 
 #[test]
 fn test_terminal_image_capabilities() {
-    use doxx::terminal_image::{TerminalImageRenderer, TerminalImageSupport};
+    use doxx_tui::terminal_image::{TerminalImageRenderer, TerminalImageSupport};
 
     // Test that we can create renderers with specific support
     let kitty_renderer = TerminalImageRenderer::with_support(TerminalImageSupport::Kitty);